@@ -0,0 +1,273 @@
+use pyo3::prelude::*;
+
+use trustfall_core::{
+    ir::Output as CoreOutput,
+    schema::{EdgeInfo, EdgeParameterInfo, PropertyInfo, VertexTypeInfo},
+};
+
+use crate::shim::make_python_value;
+
+pub(crate) fn register(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<VertexType>()?;
+    m.add_class::<Property>()?;
+    m.add_class::<Edge>()?;
+    m.add_class::<EdgeParameter>()?;
+    m.add_class::<Output>()?;
+    Ok(())
+}
+
+/// A vertex type defined in the schema: either an object type or an interface.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct VertexType {
+    #[pyo3(get)]
+    name: String,
+
+    #[pyo3(get)]
+    description: Option<String>,
+
+    #[pyo3(get)]
+    is_interface: bool,
+
+    /// The interfaces this type implements, in declaration order.
+    #[pyo3(get)]
+    implements: Vec<String>,
+
+    /// The properties of this type, in declaration order.
+    #[pyo3(get)]
+    properties: Vec<Property>,
+
+    /// The edges of this type, in declaration order.
+    #[pyo3(get)]
+    edges: Vec<Edge>,
+}
+
+impl VertexType {
+    pub(crate) fn new(py: Python, info: VertexTypeInfo<'_>) -> Self {
+        Self {
+            name: info.name().to_string(),
+            description: info.description().map(str::to_string),
+            is_interface: info.is_interface(),
+            implements: info.implements().map(str::to_string).collect(),
+            properties: info.properties().map(Property::new).collect(),
+            edges: info.edges().map(|edge| Edge::new(py, edge)).collect(),
+        }
+    }
+}
+
+#[pymethods]
+impl VertexType {
+    /// Look up a property of this type by name.
+    pub fn property(&self, name: &str) -> Option<Property> {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+            .cloned()
+    }
+
+    /// Look up an edge of this type by name.
+    pub fn edge(&self, name: &str) -> Option<Edge> {
+        self.edges.iter().find(|edge| edge.name == name).cloned()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("VertexType(name={:?})", self.name)
+    }
+}
+
+/// A property of a vertex type.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct Property {
+    #[pyo3(get)]
+    name: String,
+
+    #[pyo3(get)]
+    description: Option<String>,
+
+    /// The type of the property's values in the schema, such as `String` or `[Int!]!`.
+    #[pyo3(get)]
+    property_type: String,
+
+    #[pyo3(get)]
+    deprecation_reason: Option<String>,
+}
+
+impl Property {
+    fn new(info: PropertyInfo<'_>) -> Self {
+        Self {
+            name: info.name().to_string(),
+            description: info.description().map(str::to_string),
+            property_type: info.property_type().to_string(),
+            deprecation_reason: info.deprecation_reason().map(str::to_string),
+        }
+    }
+}
+
+#[pymethods]
+impl Property {
+    fn __repr__(&self) -> String {
+        format!(
+            "Property(name={:?}, property_type={:?})",
+            self.name, self.property_type
+        )
+    }
+}
+
+/// An edge from one vertex type to another.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct Edge {
+    #[pyo3(get)]
+    name: String,
+
+    #[pyo3(get)]
+    description: Option<String>,
+
+    /// The full type of the edge in the schema, such as `[Number!]` or `Number!`.
+    #[pyo3(get)]
+    edge_type: String,
+
+    /// The name of the vertex type at the other end of the edge.
+    #[pyo3(get)]
+    target_type: String,
+
+    /// Whether the edge may point to more than one vertex.
+    #[pyo3(get)]
+    is_list: bool,
+
+    /// The parameters of this edge, in declaration order.
+    #[pyo3(get)]
+    parameters: Vec<EdgeParameter>,
+
+    #[pyo3(get)]
+    deprecation_reason: Option<String>,
+}
+
+impl Edge {
+    pub(crate) fn new(py: Python, info: EdgeInfo<'_>) -> Self {
+        Self {
+            name: info.name().to_string(),
+            description: info.description().map(str::to_string),
+            edge_type: info.edge_type().to_string(),
+            target_type: info.target_type().name().to_string(),
+            is_list: info.is_list(),
+            parameters: info
+                .parameters()
+                .map(|param| EdgeParameter::new(py, param))
+                .collect(),
+            deprecation_reason: info.deprecation_reason().map(str::to_string),
+        }
+    }
+}
+
+#[pymethods]
+impl Edge {
+    /// Look up a parameter of this edge by name.
+    pub fn parameter(&self, name: &str) -> Option<EdgeParameter> {
+        self.parameters
+            .iter()
+            .find(|param| param.name == name)
+            .cloned()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Edge(name={:?}, edge_type={:?})", self.name, self.edge_type)
+    }
+}
+
+/// A parameter of an edge.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct EdgeParameter {
+    #[pyo3(get)]
+    name: String,
+
+    #[pyo3(get)]
+    description: Option<String>,
+
+    #[pyo3(get)]
+    parameter_type: String,
+
+    /// The value the parameter takes when a query does not specify one, if the schema
+    /// defines an explicit default. Since `None` is also a valid default,
+    /// use `has_default` to tell whether there is one.
+    #[pyo3(get)]
+    default_value: Py<PyAny>,
+
+    #[pyo3(get)]
+    has_default: bool,
+
+    /// Whether queries must specify a value for this parameter.
+    #[pyo3(get)]
+    is_required: bool,
+}
+
+impl EdgeParameter {
+    fn new(py: Python, info: EdgeParameterInfo<'_>) -> Self {
+        let default_value = info.default_value();
+        Self {
+            name: info.name().to_string(),
+            description: info.description().map(str::to_string),
+            parameter_type: info.parameter_type().to_string(),
+            has_default: default_value.is_some(),
+            default_value: default_value
+                .map_or_else(|| py.None(), |value| make_python_value(py, value)),
+            is_required: info.is_required(),
+        }
+    }
+}
+
+#[pymethods]
+impl EdgeParameter {
+    fn __repr__(&self) -> String {
+        format!(
+            "EdgeParameter(name={:?}, parameter_type={:?})",
+            self.name, self.parameter_type
+        )
+    }
+}
+
+/// An output of a query, together with the type of its values.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct Output {
+    #[pyo3(get)]
+    name: String,
+
+    /// The type of the output's values, such as `String` or `[Int]!`.
+    ///
+    /// Outputs inside `@optional` blocks are nullable even if the underlying property isn't,
+    /// and outputs inside `@fold` blocks are lists of the underlying property's values.
+    #[pyo3(get)]
+    value_type: String,
+
+    /// Whether the output's value may be `None`.
+    #[pyo3(get)]
+    is_nullable: bool,
+
+    /// The description of the output given in the query, if any.
+    #[pyo3(get)]
+    description: Option<String>,
+}
+
+impl Output {
+    pub(crate) fn new(output: &CoreOutput) -> Self {
+        Self {
+            name: output.name.to_string(),
+            value_type: output.value_type.to_string(),
+            is_nullable: output.value_type.is_nullable(),
+            description: output.description.as_deref().map(str::to_string),
+        }
+    }
+}
+
+#[pymethods]
+impl Output {
+    fn __repr__(&self) -> String {
+        format!(
+            "Output(name={:?}, value_type={:?})",
+            self.name, self.value_type
+        )
+    }
+}
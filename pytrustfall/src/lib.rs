@@ -1,11 +1,13 @@
 use pyo3::{pymodule, types::PyModule, PyResult, Python};
 
 pub mod errors;
+pub mod introspection;
 pub mod shim;
 
 #[pymodule]
 fn trustfall(py: Python, m: &PyModule) -> PyResult<()> {
     shim::register(py, m)?;
     errors::register(py, m)?;
+    introspection::register(py, m)?;
     Ok(())
 }
@@ -1,6 +1,12 @@
 use std::{collections::BTreeMap, sync::Arc};
 
-use pyo3::{exceptions::PyStopIteration, prelude::*, wrap_pyfunction};
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Timelike, Utc};
+use pyo3::{
+    exceptions::PyStopIteration,
+    prelude::*,
+    types::{timezone_utc, PyDateAccess, PyDateTime, PyTimeAccess, PyTzInfoAccess},
+    wrap_pyfunction,
+};
 
 use trustfall_core::{
     frontend::{error::FrontendError, parse},
@@ -8,9 +14,11 @@ use trustfall_core::{
         execution::interpret_ir, Adapter, ContextIterator as BaseContextIterator,
         ContextOutcomeIterator, DataContext, ResolveEdgeInfo, ResolveInfo, VertexIterator,
     },
-    ir::{EdgeParameters, FieldValue},
+    ir::{EdgeParameters, FieldValue, IndexedQuery, Type},
 };
 
+use crate::introspection::{Edge, Output, VertexType};
+
 pub(crate) fn register(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<Schema>()?;
     m.add_class::<AdapterShim>()?;
@@ -37,6 +45,75 @@ impl Schema {
 
         Ok(Self { inner })
     }
+
+    /// The edges at which queries may begin, in schema declaration order.
+    pub fn root_edges(&self, py: Python) -> Vec<Edge> {
+        self.inner
+            .root_edges()
+            .map(|edge| Edge::new(py, edge))
+            .collect()
+    }
+
+    /// All vertex types other than the root query type, sorted by name.
+    pub fn vertex_types(&self, py: Python) -> Vec<VertexType> {
+        self.inner
+            .vertex_types()
+            .map(|info| VertexType::new(py, info))
+            .collect()
+    }
+
+    /// Look up a vertex type (object type or interface) by name.
+    pub fn vertex_type(&self, py: Python, name: &str) -> Option<VertexType> {
+        self.inner
+            .vertex_type(name)
+            .map(|info| VertexType::new(py, info))
+    }
+
+    /// The names of the named type and all its subtypes, sorted by name.
+    /// Returns `None` if the type is not a vertex type defined in the schema.
+    pub fn subtypes(&self, type_name: &str) -> Option<Vec<String>> {
+        let mut subtypes: Vec<_> = self
+            .inner
+            .subtypes(type_name)?
+            .map(str::to_string)
+            .collect();
+        subtypes.sort_unstable();
+        Some(subtypes)
+    }
+
+    /// The values of the named enum in declaration order, or `None` if it isn't a defined enum.
+    pub fn enum_values(&self, enum_name: &str) -> Option<Vec<String>> {
+        Some(
+            self.inner
+                .enum_values(enum_name)?
+                .map(str::to_string)
+                .collect(),
+        )
+    }
+
+    /// The outputs of the query, with the types of their values.
+    pub fn query_outputs(&self, query: &str) -> PyResult<BTreeMap<String, Output>> {
+        let indexed_query = parse_query(self, query)?;
+        Ok(indexed_query
+            .outputs
+            .iter()
+            .map(|(name, output)| (name.to_string(), Output::new(output)))
+            .collect())
+    }
+}
+
+fn parse_query(schema: &Schema, query: &str) -> PyResult<Arc<IndexedQuery>> {
+    parse(&schema.inner, query).map_err(|err| match err {
+        FrontendError::ParseError(parse_err) => Python::with_gil(|py| {
+            crate::errors::ParseError::new_err(format!("{parse_err}").into_py(py))
+        }),
+        FrontendError::ValidationError(val_err) => Python::with_gil(|py| {
+            crate::errors::ValidationError::new_err(format!("{val_err}").into_py(py))
+        }),
+        _ => Python::with_gil(|py| {
+            crate::errors::FrontendError::new_err(format!("{err}").into_py(py))
+        }),
+    })
 }
 
 fn to_query_arguments(src: &PyAny) -> PyResult<Arc<BTreeMap<Arc<str>, FieldValue>>> {
@@ -81,17 +158,15 @@ pub fn interpret_query(
 ) -> PyResult<ResultIterator> {
     let wrapped_adapter = Arc::from(adapter);
 
-    let indexed_query = parse(&schema.inner, query).map_err(|err| match err {
-        FrontendError::ParseError(parse_err) => Python::with_gil(|py| {
-            crate::errors::ParseError::new_err(format!("{parse_err}").into_py(py))
-        }),
-        FrontendError::ValidationError(val_err) => Python::with_gil(|py| {
-            crate::errors::ValidationError::new_err(format!("{val_err}").into_py(py))
-        }),
-        _ => Python::with_gil(|py| {
-            crate::errors::FrontendError::new_err(format!("{err}").into_py(py))
-        }),
-    })?;
+    let indexed_query = parse_query(schema, query)?;
+
+    // Convert each output's values according to the output's type,
+    // so that e.g. `Float` outputs are always Python floats.
+    let output_types: BTreeMap<Arc<str>, Type> = indexed_query
+        .outputs
+        .iter()
+        .map(|(name, output)| (name.clone(), output.value_type.clone()))
+        .collect();
 
     let execution = interpret_ir(wrapped_adapter, indexed_query, arguments).map_err(|err| {
         Python::with_gil(|py| {
@@ -99,11 +174,14 @@ pub fn interpret_query(
         })
     })?;
     let owned_iter: Box<dyn Iterator<Item = BTreeMap<String, Py<PyAny>>>> =
-        Box::new(execution.map(|res| {
+        Box::new(execution.map(move |res| {
             res.into_iter()
                 .map(|(k, v)| {
                     Python::with_gil(|py| {
-                        let python_value = make_python_value(py, v);
+                        let python_value = match output_types.get(&k) {
+                            Some(value_type) => make_typed_python_value(py, v, value_type),
+                            None => make_python_value(py, v),
+                        };
                         (k.to_string(), python_value)
                     })
                 })
@@ -143,7 +221,7 @@ impl AdapterShim {
     }
 }
 
-fn make_python_value(py: Python, value: FieldValue) -> Py<PyAny> {
+pub(crate) fn make_python_value(py: Python, value: FieldValue) -> Py<PyAny> {
     match value {
         FieldValue::Null => Option::<i64>::None.into_py(py),
         FieldValue::Uint64(x) => x.into_py(py),
@@ -151,8 +229,8 @@ fn make_python_value(py: Python, value: FieldValue) -> Py<PyAny> {
         FieldValue::Float64(x) => x.into_py(py),
         FieldValue::String(x) => x.into_py(py),
         FieldValue::Boolean(x) => x.into_py(py),
-        FieldValue::DateTimeUtc(_) => todo!(),
-        FieldValue::Enum(_) => todo!(),
+        FieldValue::DateTimeUtc(x) => make_python_datetime(py, x),
+        FieldValue::Enum(x) => x.into_py(py),
         FieldValue::List(x) => x
             .into_iter()
             .map(|v| make_python_value(py, v))
@@ -161,6 +239,65 @@ fn make_python_value(py: Python, value: FieldValue) -> Py<PyAny> {
     }
 }
 
+/// Like [`make_python_value`], but producing the Python type matching the value's schema type:
+/// integers are converted to floats when the type is `Float`, including inside lists.
+fn make_typed_python_value(py: Python, value: FieldValue, value_type: &Type) -> Py<PyAny> {
+    let is_float = !value_type.is_list() && value_type.base_type() == "Float";
+    match (value, value_type.element_type()) {
+        (FieldValue::Int64(x), _) if is_float => (x as f64).into_py(py),
+        (FieldValue::Uint64(x), _) if is_float => (x as f64).into_py(py),
+        (FieldValue::List(x), Some(inner)) => x
+            .into_iter()
+            .map(|v| make_typed_python_value(py, v, &inner))
+            .collect::<Vec<_>>()
+            .into_py(py),
+        (value, _) => make_python_value(py, value),
+    }
+}
+
+/// Convert to a timezone-aware `datetime.datetime` in UTC.
+fn make_python_datetime(py: Python, value: DateTime<Utc>) -> Py<PyAny> {
+    // Python datetimes have microsecond precision and no leap seconds,
+    // which chrono represents as nanoseconds past the one-billion mark.
+    let microsecond = (value.nanosecond() / 1000).min(999_999);
+    PyDateTime::new(
+        py,
+        value.year(),
+        value.month() as u8,
+        value.day() as u8,
+        value.hour() as u8,
+        value.minute() as u8,
+        value.second() as u8,
+        microsecond,
+        Some(timezone_utc(py)),
+    )
+    .expect("datetime out of the range supported by Python")
+    .into_py(py)
+}
+
+/// Convert a timezone-aware `datetime.datetime`. Naive datetimes are ambiguous,
+/// so they are not representable.
+fn make_datetime_from_ref(value: &PyDateTime) -> Result<DateTime<Utc>, ()> {
+    value.get_tzinfo().ok_or(())?;
+    let utc: &PyDateTime = value
+        .call_method1("astimezone", (timezone_utc(value.py()),))
+        .and_then(|utc| Ok(utc.downcast()?))
+        .map_err(|_| ())?;
+
+    let naive =
+        NaiveDate::from_ymd_opt(utc.get_year(), utc.get_month().into(), utc.get_day().into())
+            .and_then(|date| {
+                date.and_hms_micro_opt(
+                    utc.get_hour().into(),
+                    utc.get_minute().into(),
+                    utc.get_second().into(),
+                    utc.get_microsecond(),
+                )
+            })
+            .ok_or(())?;
+    Ok(Utc.from_utc_datetime(&naive))
+}
+
 fn make_field_value_from_ref(value: &PyAny) -> Result<FieldValue, ()> {
     if value.is_none() {
         Ok(FieldValue::Null)
@@ -172,6 +309,8 @@ fn make_field_value_from_ref(value: &PyAny) -> Result<FieldValue, ()> {
         Ok(FieldValue::Float64(inner))
     } else if let Ok(inner) = value.extract::<String>() {
         Ok(FieldValue::String(inner))
+    } else if let Ok(inner) = value.downcast::<PyDateTime>() {
+        make_datetime_from_ref(inner).map(FieldValue::DateTimeUtc)
     } else if let Ok(inner) = value.extract::<Vec<&PyAny>>() {
         let converted_values = inner
             .iter()
@@ -1,8 +1,12 @@
 from .adapter import Adapter, Context
 from .execution import execute_query
+from .typegen import generate_result_types
 
 from .trustfall import Schema
 
+# Schema introspection types, returned by Schema methods.
+from .trustfall import Edge, EdgeParameter, Output, Property, VertexType
+
 # Error types:
 # - ParseError, when the provided input doesn't even parse as valid syntax
 # - ValidationError, when the input is syntactically valid but doesn't match the schema
@@ -0,0 +1,68 @@
+"""Collect query results into Polars data frames.
+
+Requires the `polars` package, which is not a dependency of `trustfall` and must be
+installed separately.
+
+Each output becomes a column of the same name, typed according to the output's type:
+outputs inside `@fold` blocks become list columns, and `DateTime` outputs become timezone-aware
+datetime columns in UTC. Enums are strings, and other custom scalars are stored as objects.
+"""
+from typing import TYPE_CHECKING, Any, Dict, Iterable, Mapping
+
+from .trustfall import Schema
+
+if TYPE_CHECKING:
+    import polars
+
+
+def _import_polars() -> Any:
+    try:
+        import polars
+    except ImportError as e:
+        raise ImportError(
+            "Collecting results into data frames requires the polars package: "
+            "pip install polars"
+        ) from e
+    return polars
+
+
+def _dtype(pl: Any, schema: Schema, value_type: str) -> Any:
+    base = value_type[:-1] if value_type.endswith("!") else value_type
+    if base.startswith("["):
+        return pl.List(_dtype(pl, schema, base[1:-1]))
+
+    builtin = {
+        "Int": pl.Int64,
+        "Float": pl.Float64,
+        "String": pl.Utf8,
+        "ID": pl.Utf8,
+        "Boolean": pl.Boolean,
+    }
+    if base in builtin:
+        return builtin[base]
+    if base == "DateTime":
+        return pl.Datetime("us", "UTC")
+    if schema.enum_values(base) is not None:
+        return pl.Utf8
+    return pl.Object
+
+
+def polars_schema(schema: Schema, query: str) -> "Dict[str, polars.PolarsDataType]":
+    """The dtypes of the columns of data frames holding the query's results, by output name."""
+    pl = _import_polars()
+    return {
+        name: _dtype(pl, schema, output.value_type)
+        for name, output in schema.query_outputs(query).items()
+    }
+
+
+def to_polars(
+    schema: Schema, query: str, results: Iterable[Mapping[str, Any]]
+) -> "polars.DataFrame":
+    """Collect the results of executing the query into a data frame.
+
+    The data frame has one column per output, in order of output name, even if there are
+    no results.
+    """
+    pl = _import_polars()
+    return pl.from_dicts(list(results), schema=polars_schema(schema, query))
@@ -0,0 +1,57 @@
+from os import path
+import unittest
+
+from ..trustfall import Schema
+from ..execution import execute_query
+from ..dataframe import to_polars
+from .numbers_adapter import NumbersAdapter
+
+try:
+    import polars
+except ImportError:
+    polars = None
+
+
+def _get_numbers_schema() -> Schema:
+    package_root = path.abspath(path.dirname(path.dirname(path.dirname(__file__))))
+    schema_path = path.join(package_root, "numbers.graphql")
+    with open(schema_path, "r") as f:
+        return Schema(f.read())
+
+
+SCHEMA = _get_numbers_schema()
+
+QUERY = """
+{
+    Number(max: 4) {
+        value @output
+        name @output
+
+        predecessor @optional {
+            previous: value @output
+        }
+        multiple(max: 3) @fold {
+            multiples: value @output
+        }
+    }
+}
+"""
+
+
+@unittest.skipUnless(polars, "polars is not installed")
+class DataFrameTests(unittest.TestCase):
+    def test_to_polars(self) -> None:
+        results = execute_query(NumbersAdapter(), SCHEMA, QUERY, {})
+        df = to_polars(SCHEMA, QUERY, results)
+
+        self.assertEqual(["multiples", "name", "previous", "value"], df.columns)
+        self.assertEqual(polars.List(polars.Int64), df.schema["multiples"])
+        self.assertEqual(polars.Utf8, df.schema["name"])
+        self.assertEqual([0, 1, 2, 3, 4], df["value"].to_list())
+        self.assertEqual([None, 0, 1, 2, 3], df["previous"].to_list())
+        self.assertEqual([4, 6], df["multiples"].to_list()[2])
+
+    def test_empty_results(self) -> None:
+        df = to_polars(SCHEMA, QUERY, [])
+        self.assertEqual((0, 4), df.shape)
+        self.assertEqual(polars.Int64, df.schema["value"])
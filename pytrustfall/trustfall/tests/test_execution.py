@@ -1,6 +1,7 @@
+from datetime import datetime, timedelta, timezone
 from os import path
 from textwrap import dedent
-from typing import Any, Dict
+from typing import Any, Dict, Iterable, Iterator, Mapping, Tuple
 import unittest
 
 from ..trustfall import (
@@ -11,6 +12,7 @@ from ..trustfall import (
     Schema,
     ValidationError,
 )
+from .. import Adapter, Context
 from ..execution import execute_query
 from .numbers_adapter import NumbersAdapter
 
@@ -25,6 +27,90 @@ def _get_numbers_schema() -> Schema:
 SCHEMA = _get_numbers_schema()
 
 
+MEASUREMENTS_SCHEMA = Schema(
+    dedent(
+        """\
+        schema {
+            query: RootSchemaQuery
+        }
+        directive @filter(op: String!, value: [String!]) on FIELD | INLINE_FRAGMENT
+        directive @tag(name: String) on FIELD
+        directive @output(name: String) on FIELD
+        directive @optional on FIELD
+        directive @recurse(depth: Int!) on FIELD
+        directive @fold on FIELD
+        directive @transform(op: String!) on FIELD
+
+        scalar DateTime
+
+        type RootSchemaQuery {
+            Measurement: [Measurement!]!
+        }
+
+        type Measurement {
+            reading: Float!
+            history: [Float!]!
+            count: Int!
+            taken_at: DateTime!
+        }
+        """
+    )
+)
+
+# Python ints are valid values for Float properties, and datetimes in any timezone are valid
+# DateTime values. Results are converted to the types the schema declares.
+_MEASUREMENT = {
+    "reading": 3,
+    "history": [1, 2.5],
+    "count": 2,
+    "taken_at": datetime(2022, 6, 1, 14, 30, 15, 250, tzinfo=timezone(timedelta(hours=2))),
+}
+
+
+class MeasurementsAdapter(Adapter[Dict[str, Any]]):
+    def resolve_starting_vertices(
+        self,
+        edge_name: str,
+        parameters: Mapping[str, Any],
+        *args: Any,
+        **kwargs: Any,
+    ) -> Iterable[Dict[str, Any]]:
+        return [_MEASUREMENT]
+
+    def resolve_property(
+        self,
+        contexts: Iterator[Context[Dict[str, Any]]],
+        type_name: str,
+        property_name: str,
+        *args: Any,
+        **kwargs: Any,
+    ) -> Iterable[Tuple[Context[Dict[str, Any]], Any]]:
+        for context in contexts:
+            vertex = context.active_vertex
+            yield (context, vertex[property_name] if vertex is not None else None)
+
+    def resolve_neighbors(
+        self,
+        contexts: Iterator[Context[Dict[str, Any]]],
+        type_name: str,
+        edge_name: str,
+        parameters: Mapping[str, Any],
+        *args: Any,
+        **kwargs: Any,
+    ) -> Iterable[Tuple[Context[Dict[str, Any]], Iterable[Dict[str, Any]]]]:
+        raise NotImplementedError()
+
+    def resolve_coercion(
+        self,
+        contexts: Iterator[Context[Dict[str, Any]]],
+        type_name: str,
+        coerce_to_type: str,
+        *args: Any,
+        **kwargs: Any,
+    ) -> Iterable[Tuple[Context[Dict[str, Any]], bool]]:
+        raise NotImplementedError()
+
+
 class ExecutionTests(unittest.TestCase):
     def test_simple_query(self) -> None:
         query = dedent(
@@ -105,6 +191,36 @@ class ExecutionTests(unittest.TestCase):
         actual_result = list(execute_query(NumbersAdapter(), SCHEMA, query, args))
         self.assertEqual(expected_result, actual_result)
 
+    def test_results_have_schema_types(self) -> None:
+        query = dedent(
+            """\
+            {
+                Measurement {
+                    reading @output
+                    history @output
+                    count @output
+                    taken_at @output
+                }
+            }
+            """
+        )
+        args: Dict[str, Any] = {}
+
+        (result,) = list(execute_query(MeasurementsAdapter(), MEASUREMENTS_SCHEMA, query, args))
+        self.assertEqual(
+            {
+                "reading": 3.0,
+                "history": [1.0, 2.5],
+                "count": 2,
+                "taken_at": datetime(2022, 6, 1, 12, 30, 15, 250, tzinfo=timezone.utc),
+            },
+            result,
+        )
+        self.assertIsInstance(result["reading"], float)
+        self.assertTrue(all(isinstance(value, float) for value in result["history"]))
+        self.assertIsInstance(result["count"], int)
+        self.assertEqual(timezone.utc, result["taken_at"].tzinfo)
+
     def test_parse_error(self) -> None:
         query = "this isn't valid syntax"
         args: Dict[str, Any] = {}
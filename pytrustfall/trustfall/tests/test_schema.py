@@ -1,3 +1,5 @@
+from os import path
+from textwrap import dedent
 import unittest
 
 from ..trustfall import (
@@ -38,3 +40,90 @@ type Derived implements Base {
 class SchemaTests(unittest.TestCase):
     def test_invalid_schema_raises_exception(self) -> None:
         self.assertRaises(InvalidSchemaError, Schema, INVALID_SCHEMA)
+
+
+def _get_numbers_schema() -> Schema:
+    package_root = path.abspath(path.dirname(path.dirname(path.dirname(__file__))))
+    schema_path = path.join(package_root, "numbers.graphql")
+    with open(schema_path, "r") as f:
+        return Schema(f.read())
+
+
+class SchemaIntrospectionTests(unittest.TestCase):
+    def test_root_edges(self) -> None:
+        schema = _get_numbers_schema()
+
+        (number,) = schema.root_edges()
+        self.assertEqual("Number", number.name)
+        self.assertEqual("[Number!]", number.edge_type)
+        self.assertEqual("Number", number.target_type)
+        self.assertTrue(number.is_list)
+
+        (max_param,) = number.parameters
+        self.assertEqual("max", max_param.name)
+        self.assertEqual("Int!", max_param.parameter_type)
+        self.assertTrue(max_param.is_required)
+        self.assertFalse(max_param.has_default)
+        self.assertIsNone(max_param.default_value)
+
+    def test_vertex_types(self) -> None:
+        schema = _get_numbers_schema()
+
+        self.assertEqual(["Number"], [vertex.name for vertex in schema.vertex_types()])
+        self.assertIsNone(schema.vertex_type("Nonexistent"))
+
+        number = schema.vertex_type("Number")
+        self.assertIsNotNone(number)
+        assert number is not None  # for mypy
+        self.assertFalse(number.is_interface)
+        self.assertEqual([], number.implements)
+        self.assertEqual(
+            [("name", "String"), ("value", "Int!")],
+            [(prop.name, prop.property_type) for prop in number.properties],
+        )
+        self.assertEqual(
+            [("predecessor", False), ("successor", False), ("multiple", True)],
+            [(edge.name, edge.is_list) for edge in number.edges],
+        )
+        self.assertIsNone(number.property("successor"))
+        self.assertIsNone(number.edge("value"))
+
+        multiple = number.edge("multiple")
+        assert multiple is not None  # for mypy
+        self.assertEqual(["max"], [param.name for param in multiple.parameters])
+        self.assertEqual(["Number"], schema.subtypes("Number"))
+        self.assertIsNone(schema.subtypes("Nonexistent"))
+
+    def test_query_outputs(self) -> None:
+        schema = _get_numbers_schema()
+        query = dedent(
+            """\
+            {
+                Number(max: 4) {
+                    # The number's value.
+                    value @output
+
+                    predecessor @optional {
+                        previous: name @output
+                    }
+
+                    multiple(max: 3) @fold {
+                        multiples: value @output
+                    }
+                }
+            }
+            """
+        )
+
+        outputs = schema.query_outputs(query)
+        self.assertEqual(
+            {
+                "value": ("Int!", False, "The number's value."),
+                "previous": ("String", True, None),
+                "multiples": ("[Int!]!", False, None),
+            },
+            {
+                name: (output.value_type, output.is_nullable, output.description)
+                for name, output in outputs.items()
+            },
+        )
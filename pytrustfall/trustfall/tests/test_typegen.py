@@ -0,0 +1,180 @@
+from dataclasses import FrozenInstanceError
+from os import path
+from textwrap import dedent
+from typing import Any, Dict
+import unittest
+
+from ..trustfall import Schema
+from ..execution import execute_query
+from ..typegen import generate_result_types
+from .numbers_adapter import NumbersAdapter
+
+
+def _get_numbers_schema() -> Schema:
+    package_root = path.abspath(path.dirname(path.dirname(path.dirname(__file__))))
+    schema_path = path.join(package_root, "numbers.graphql")
+    with open(schema_path, "r") as f:
+        return Schema(f.read())
+
+
+SCHEMA = _get_numbers_schema()
+
+NUMBERS_QUERY = """
+{
+    Number(max: 6) {
+        # The number itself.
+        value @output
+        name @output
+
+        predecessor @optional {
+            previous: value @output
+        }
+        multiple(max: 3) @fold {
+            multiples: value @output
+        }
+    }
+}
+"""
+
+CATALOG_SCHEMA = Schema(
+    dedent(
+        """\
+        schema {
+            query: RootSchemaQuery
+        }
+        directive @filter(op: String!, value: [String!]) on FIELD | INLINE_FRAGMENT
+        directive @tag(name: String) on FIELD
+        directive @output(name: String) on FIELD
+        directive @optional on FIELD
+        directive @recurse(depth: Int!) on FIELD
+        directive @fold on FIELD
+        directive @transform(op: String!) on FIELD
+
+        scalar DateTime
+        scalar Money
+        enum Status {
+            ACTIVE
+            RETIRED
+        }
+
+        type RootSchemaQuery {
+            Product: [Product!]!
+        }
+
+        type Product {
+            status: Status!
+            released: DateTime
+            price: Money
+            tags: [[String]!]
+        }
+        """
+    )
+)
+
+CATALOG_QUERY = """
+{
+    Product {
+        status @output
+        released @output
+        price @output
+        tags @output
+    }
+}
+"""
+
+
+def _load(source: str) -> Dict[str, Any]:
+    namespace: Dict[str, Any] = {}
+    exec(compile(source, "<generated>", "exec"), namespace)
+    return namespace
+
+
+class TypegenTests(unittest.TestCase):
+    def test_dataclass(self) -> None:
+        source = generate_result_types(SCHEMA, {"NumberRow": NUMBERS_QUERY})
+        expected = '''\
+# Generated by trustfall.typegen from the queries' outputs. Do not edit by hand.
+from dataclasses import dataclass
+from typing import Any, List, Mapping, Optional
+
+
+@dataclass(frozen=True)
+class NumberRow:
+    multiples: List[int]
+    name: Optional[str]
+    previous: Optional[int]
+    #: The number itself.
+    value: int
+
+    @classmethod
+    def from_result(cls, result: Mapping[str, Any]) -> "NumberRow":
+        """Convert a result row of the query into a `NumberRow`."""
+        return cls(**result)
+'''
+        self.assertEqual(expected, source)
+
+        number_row = _load(source)["NumberRow"]
+        rows = [
+            number_row.from_result(result)
+            for result in execute_query(NumbersAdapter(), SCHEMA, NUMBERS_QUERY, {})
+        ]
+        self.assertEqual(number_row(multiples=[4, 6], name="two", previous=1, value=2), rows[2])
+        self.assertIsNone(rows[0].previous)
+        with self.assertRaises(FrozenInstanceError):
+            rows[0].value = 3
+
+    def test_stub(self) -> None:
+        source = generate_result_types(SCHEMA, {"NumberRow": NUMBERS_QUERY}, stub=True)
+        self.assertIn(
+            '    def from_result(cls, result: Mapping[str, Any]) -> "NumberRow": ...\n', source
+        )
+        self.assertNotIn("return", source)
+        _load(source)
+
+    def test_typeddict(self) -> None:
+        source = generate_result_types(
+            SCHEMA,
+            {
+                "NumberRow": NUMBERS_QUERY,
+                "Keywords": '{ Number(max: 1) { value @output(name: "from") } }',
+            },
+            style="typeddict",
+        )
+        expected = '''\
+# Generated by trustfall.typegen from the queries' outputs. Do not edit by hand.
+from typing import List, Optional, TypedDict
+
+
+class NumberRow(TypedDict):
+    multiples: List[int]
+    name: Optional[str]
+    previous: Optional[int]
+    #: The number itself.
+    value: int
+
+
+Keywords = TypedDict('Keywords', {
+    'from': int,
+})
+'''
+        self.assertEqual(expected, source)
+        self.assertEqual({"from"}, _load(source)["Keywords"].__annotations__.keys())
+
+    def test_non_builtin_types(self) -> None:
+        source = generate_result_types(CATALOG_SCHEMA, {"ProductRow": CATALOG_QUERY})
+        self.assertIn("from datetime import datetime\n", source)
+        self.assertIn("    price: Any\n", source)
+        self.assertIn("    released: Optional[datetime]\n", source)
+        self.assertIn("    status: Literal['ACTIVE', 'RETIRED']\n", source)
+        self.assertIn("    tags: Optional[List[List[Optional[str]]]]\n", source)
+        _load(source)
+
+    def test_invalid_names(self) -> None:
+        with self.assertRaisesRegex(ValueError, "not a valid Python identifier"):
+            generate_result_types(SCHEMA, {"number-row": NUMBERS_QUERY})
+        with self.assertRaisesRegex(ValueError, "Python keywords"):
+            generate_result_types(
+                SCHEMA, {"Keywords": '{ Number(max: 1) { value @output(name: "class") } }'}
+            )
+        with self.assertRaisesRegex(ValueError, "Unknown style"):
+            generate_result_types(SCHEMA, {"NumberRow": NUMBERS_QUERY}, style="attrs")  # type: ignore
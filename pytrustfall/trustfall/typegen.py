@@ -0,0 +1,163 @@
+"""Generate Python types describing the result rows of queries.
+
+Each query's result rows are described by a dataclass or a `TypedDict` with one field per output,
+typed according to the output's type: outputs that may be null, such as those inside `@optional`
+blocks, are `Optional`, and outputs inside `@fold` blocks are lists.
+
+The generated source can be saved as a module (`.py`) to use the types at runtime, or as a stub
+(`.pyi`) to only use them for static type checking.
+"""
+from keyword import iskeyword
+from typing import Dict, List, Literal, Mapping, Set
+
+from .trustfall import Output, Schema
+
+Style = Literal["dataclass", "typeddict"]
+
+_BUILTIN_SCALARS = {
+    "Int": "int",
+    "Float": "float",
+    "String": "str",
+    "ID": "str",
+    "Boolean": "bool",
+}
+
+
+class _TypeWriter:
+    """Converts the types of outputs to Python type annotations, tracking the imports they need."""
+
+    def __init__(self, schema: Schema) -> None:
+        self.schema = schema
+        self.typing_imports: Set[str] = set()
+        self.uses_datetime = False
+
+    def annotation(self, value_type: str) -> str:
+        nullable = not value_type.endswith("!")
+        base = value_type if nullable else value_type[:-1]
+
+        if base.startswith("["):
+            self.typing_imports.add("List")
+            annotation = f"List[{self.annotation(base[1:-1])}]"
+        else:
+            annotation = self._named_type(base)
+
+        if nullable and annotation != "Any":
+            self.typing_imports.add("Optional")
+            annotation = f"Optional[{annotation}]"
+        return annotation
+
+    def _named_type(self, name: str) -> str:
+        if name in _BUILTIN_SCALARS:
+            return _BUILTIN_SCALARS[name]
+        if name == "DateTime":
+            self.uses_datetime = True
+            return "datetime"
+
+        enum_values = self.schema.enum_values(name)
+        if enum_values is not None:
+            self.typing_imports.add("Literal")
+            return f"Literal[{', '.join(repr(value) for value in enum_values)}]"
+
+        # Custom scalars may be represented by values of any type.
+        self.typing_imports.add("Any")
+        return "Any"
+
+
+def _field_lines(fields: Dict[str, str], outputs: Mapping[str, Output]) -> List[str]:
+    """The class body lines declaring the fields, preceded by the outputs' descriptions."""
+    lines = []
+    for field_name, annotation in fields.items():
+        description = outputs[field_name].description
+        if description is not None:
+            lines.extend(f"    #: {line}".rstrip() for line in description.splitlines())
+        lines.append(f"    {field_name}: {annotation}")
+    return lines
+
+
+def _dataclass(name: str, fields: Dict[str, str], outputs: Mapping[str, Output], stub: bool) -> str:
+    lines = ["@dataclass(frozen=True)", f"class {name}:"]
+    lines.extend(_field_lines(fields, outputs))
+
+    lines.append("")
+    lines.append("    @classmethod")
+    signature = f'def from_result(cls, result: Mapping[str, Any]) -> "{name}":'
+    if stub:
+        lines.append(f"    {signature} ...")
+    else:
+        lines.append(f"    {signature}")
+        lines.append(f'        """Convert a result row of the query into a `{name}`."""')
+        lines.append("        return cls(**result)")
+    return "\n".join(lines)
+
+
+def _typeddict(name: str, fields: Dict[str, str], outputs: Mapping[str, Output]) -> str:
+    if any(iskeyword(field_name) for field_name in fields):
+        # Keywords can't be the names of class attributes, but they can be TypedDict keys.
+        entries = "".join(
+            f"\n    {field_name!r}: {annotation}," for field_name, annotation in fields.items()
+        )
+        return f"{name} = TypedDict({name!r}, {{{entries}\n}})"
+
+    lines = [f"class {name}(TypedDict):"]
+    lines.extend(_field_lines(fields, outputs))
+    return "\n".join(lines)
+
+
+def generate_result_types(
+    schema: Schema,
+    queries: Mapping[str, str],
+    *,
+    style: Style = "dataclass",
+    stub: bool = False,
+) -> str:
+    """Generate the source of a module defining the types of the queries' result rows.
+
+    `queries` maps the name of each type to define to the query whose result rows it describes.
+    With the default `dataclass` style, each type is a frozen dataclass, whose `from_result()`
+    class method converts a result row into it. With the `typeddict` style, each type is
+    a `TypedDict` matching the result rows themselves.
+
+    With `stub=True`, the source is a `.pyi` stub declaring the types without implementing them.
+
+    Raises ValueError if a name is not a valid Python identifier, or if a dataclass would need
+    a field whose name is a Python keyword; rename such outputs with `@output(name: "...")`.
+    """
+    if style not in ("dataclass", "typeddict"):
+        raise ValueError(f"Unknown style {style!r}, expected 'dataclass' or 'typeddict'.")
+
+    writer = _TypeWriter(schema)
+    definitions: List[str] = []
+    for name, query in queries.items():
+        if not name.isidentifier() or iskeyword(name):
+            raise ValueError(f"The type name {name!r} is not a valid Python identifier.")
+
+        outputs = schema.query_outputs(query)
+        fields = {
+            output_name: writer.annotation(output.value_type)
+            for output_name, output in outputs.items()
+        }
+        if style == "dataclass":
+            keywords = [field_name for field_name in fields if iskeyword(field_name)]
+            if keywords:
+                raise ValueError(
+                    f"The outputs {keywords} of the {name!r} query are Python keywords, "
+                    "so they can't be dataclass fields. Please rename them "
+                    'with @output(name: "..."), or use the "typeddict" style.'
+                )
+            writer.typing_imports.update(("Any", "Mapping"))
+            definitions.append(_dataclass(name, fields, outputs, stub))
+        else:
+            writer.typing_imports.add("TypedDict")
+            definitions.append(_typeddict(name, fields, outputs))
+
+    imports = []
+    if style == "dataclass":
+        imports.append("from dataclasses import dataclass")
+    if writer.uses_datetime:
+        imports.append("from datetime import datetime")
+    if writer.typing_imports:
+        imports.append(f"from typing import {', '.join(sorted(writer.typing_imports))}")
+
+    header = "# Generated by trustfall.typegen from the queries' outputs. Do not edit by hand."
+    sections = [header + "\n" + "\n".join(imports)] + definitions
+    return "\n\n\n".join(sections) + "\n"
@@ -53,7 +53,7 @@ pub mod provider {
     pub use trustfall_core::{accessor_property, field_property};
 
     // Derive macros for common vertex implementation details.
-    pub use trustfall_derive::{TrustfallEnumVertex, Typename};
+    pub use trustfall_derive::{TrustfallEnumVertex, TrustfallVertex, Typename};
 }
 
 // Property values and query variables.
@@ -63,17 +63,250 @@ pub use trustfall_core::ir::{FieldValue, TransparentValue};
 /// Trustfall query schema.
 pub use trustfall_core::schema::Schema;
 
-// Trait for converting query results into structs.
-pub use trustfall_core::TryIntoStruct;
+// Converting query results into structs.
+pub use trustfall_core::{check_result_struct, ResultStructError, TryIntoStruct};
+
+/// The ways in which parsing, checking, or starting to execute a query may fail.
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("The schema is not valid")]
+    InvalidSchema(#[from] trustfall_core::schema::error::InvalidSchemaError),
+
+    #[error("The query is not valid against the schema")]
+    InvalidQuery(#[from] trustfall_core::frontend::error::FrontendError),
+
+    #[error("The query's outputs do not match the struct its results are deserialized into")]
+    ResultStructMismatch(#[from] ResultStructError),
+
+    #[error("The variables are not valid for the query")]
+    InvalidArguments(#[from] trustfall_core::interpreter::error::QueryArgumentsError),
+}
+
+/// Parse the schema, then run a Trustfall query over the data provider specified by it
+/// and the given adapter.
+///
+/// This is a shorthand for [`Schema::parse`] followed by [`execute_query`], for examples,
+/// tests, and small tools that have the schema's text at hand. Programs that run many queries
+/// should parse their schema once and use [`execute_query`] instead.
+///
+/// Every step that may fail reports its errors as a variant of [`Error`].
+///
+/// ```rust
+/// # use std::collections::BTreeMap;
+/// # use std::sync::Arc;
+/// # use trustfall::provider::{
+/// #     resolve_property_with, Adapter, ContextIterator, ContextOutcomeIterator, EdgeParameters,
+/// #     ResolveEdgeInfo, ResolveInfo, VertexIterator,
+/// # };
+/// # use trustfall::FieldValue;
+/// #
+/// # struct Numbers;
+/// #
+/// # impl<'a> Adapter<'a> for Numbers {
+/// #     type Vertex = i64;
+/// #
+/// #     fn resolve_starting_vertices(
+/// #         &self, _edge_name: &Arc<str>, _parameters: &EdgeParameters, _resolve_info: &ResolveInfo,
+/// #     ) -> VertexIterator<'a, Self::Vertex> {
+/// #         Box::new(1..=3)
+/// #     }
+/// #
+/// #     fn resolve_property(
+/// #         &self, contexts: ContextIterator<'a, Self::Vertex>, _type_name: &Arc<str>,
+/// #         _property_name: &Arc<str>, _resolve_info: &ResolveInfo,
+/// #     ) -> ContextOutcomeIterator<'a, Self::Vertex, FieldValue> {
+/// #         resolve_property_with(contexts, |vertex| (*vertex).into())
+/// #     }
+/// #
+/// #     fn resolve_neighbors(
+/// #         &self, _contexts: ContextIterator<'a, Self::Vertex>, _type_name: &Arc<str>,
+/// #         _edge_name: &Arc<str>, _parameters: &EdgeParameters, _resolve_info: &ResolveEdgeInfo,
+/// #     ) -> ContextOutcomeIterator<'a, Self::Vertex, VertexIterator<'a, Self::Vertex>> {
+/// #         unreachable!()
+/// #     }
+/// #
+/// #     fn resolve_coercion(
+/// #         &self, _contexts: ContextIterator<'a, Self::Vertex>, _type_name: &Arc<str>,
+/// #         _coerce_to_type: &Arc<str>, _resolve_info: &ResolveInfo,
+/// #     ) -> ContextOutcomeIterator<'a, Self::Vertex, bool> {
+/// #         unreachable!()
+/// #     }
+/// # }
+/// #
+/// # let adapter = Arc::new(Numbers);
+/// let schema = r#"
+///     schema { query: RootSchemaQuery }
+///     directive @filter(op: String!, value: [String!]) on FIELD | INLINE_FRAGMENT
+///     directive @tag(name: String) on FIELD
+///     directive @output(name: String) on FIELD
+///     directive @optional on FIELD
+///     directive @recurse(depth: Int!) on FIELD
+///     directive @fold on FIELD
+///     directive @transform(op: String!) on FIELD
+///
+///     type RootSchemaQuery { Number: [Number!]! }
+///     type Number { value: Int! }
+/// "#;
+/// let query = r#"
+/// {
+///     Number {
+///         value @output @filter(op: ">", value: ["$min"])
+///     }
+/// }"#;
+///
+/// let variables = BTreeMap::from([("min", 1)]);
+/// let results: Vec<_> = trustfall::execute(schema, adapter.clone(), query, variables)
+///     .expect("failed to run the query")
+///     .collect();
+/// assert_eq!(2, results.len());
+///
+/// // The query's `$min` variable wasn't given a value.
+/// let error = trustfall::execute(schema, adapter, query, BTreeMap::<&str, i64>::new())
+///     .err()
+///     .expect("query ran without its variables");
+/// assert!(matches!(error, trustfall::Error::InvalidArguments(..)));
+///
+/// // The error describes which step failed, and its source describes why.
+/// assert_eq!("The variables are not valid for the query", error.to_string());
+/// let source = std::error::Error::source(&error).expect("error has no source");
+/// assert_eq!(
+///     "One or more arguments required by this query were not provided: [\"min\"]",
+///     source.to_string(),
+/// );
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn execute<'vertex>(
+    schema: &str,
+    adapter: Arc<impl provider::Adapter<'vertex> + 'vertex>,
+    query: &str,
+    variables: BTreeMap<impl Into<Arc<str>>, impl Into<FieldValue>>,
+) -> Result<Box<dyn Iterator<Item = BTreeMap<Arc<str>, FieldValue>> + 'vertex>, Error> {
+    let schema = Schema::parse(schema)?;
+    parse_and_execute(&schema, adapter, query, variables, |_| Ok(()))
+}
 
 /// Run a Trustfall query over the data provider specified by the given schema and adapter.
+///
+/// With the `opentelemetry` feature, the query's execution is recorded in OpenTelemetry spans,
+/// and the adapter's calls in OpenTelemetry metrics, as described in
+/// [`trustfall_core::interpreter::telemetry`].
 pub fn execute_query<'vertex>(
     schema: &Schema,
     adapter: Arc<impl provider::Adapter<'vertex> + 'vertex>,
     query: &str,
     variables: BTreeMap<impl Into<Arc<str>>, impl Into<FieldValue>>,
 ) -> anyhow::Result<Box<dyn Iterator<Item = BTreeMap<Arc<str>, FieldValue>> + 'vertex>> {
-    let parsed_query = trustfall_core::frontend::parse(schema, query)?;
+    let results = parse_and_execute(schema, adapter, query, variables, |_| Ok(()))?;
+    Ok(results)
+}
+
+/// Run a Trustfall query like [`execute_query`], deserializing each result into a `T`.
+///
+/// Before the query is executed, [`check_result_struct`] ensures that the fields of `T`
+/// match the query's outputs and their types, so mistakes like a misspelled
+/// output name or a missing `Option` on a field for an `@optional` output
+/// are reported as errors here instead of on the first result.
+///
+/// ```rust
+/// # use std::collections::BTreeMap;
+/// # use std::sync::Arc;
+/// # use trustfall::provider::{
+/// #     resolve_property_with, Adapter, ContextIterator, ContextOutcomeIterator, EdgeParameters,
+/// #     ResolveEdgeInfo, ResolveInfo, VertexIterator,
+/// # };
+/// # use trustfall::{FieldValue, Schema};
+/// #
+/// # struct Numbers;
+/// #
+/// # impl<'a> Adapter<'a> for Numbers {
+/// #     type Vertex = i64;
+/// #
+/// #     fn resolve_starting_vertices(
+/// #         &self, _edge_name: &Arc<str>, _parameters: &EdgeParameters, _resolve_info: &ResolveInfo,
+/// #     ) -> VertexIterator<'a, Self::Vertex> {
+/// #         Box::new(1..=3)
+/// #     }
+/// #
+/// #     fn resolve_property(
+/// #         &self, contexts: ContextIterator<'a, Self::Vertex>, _type_name: &Arc<str>,
+/// #         _property_name: &Arc<str>, _resolve_info: &ResolveInfo,
+/// #     ) -> ContextOutcomeIterator<'a, Self::Vertex, FieldValue> {
+/// #         resolve_property_with(contexts, |vertex| (*vertex).into())
+/// #     }
+/// #
+/// #     fn resolve_neighbors(
+/// #         &self, _contexts: ContextIterator<'a, Self::Vertex>, _type_name: &Arc<str>,
+/// #         _edge_name: &Arc<str>, _parameters: &EdgeParameters, _resolve_info: &ResolveEdgeInfo,
+/// #     ) -> ContextOutcomeIterator<'a, Self::Vertex, VertexIterator<'a, Self::Vertex>> {
+/// #         unreachable!()
+/// #     }
+/// #
+/// #     fn resolve_coercion(
+/// #         &self, _contexts: ContextIterator<'a, Self::Vertex>, _type_name: &Arc<str>,
+/// #         _coerce_to_type: &Arc<str>, _resolve_info: &ResolveInfo,
+/// #     ) -> ContextOutcomeIterator<'a, Self::Vertex, bool> {
+/// #         unreachable!()
+/// #     }
+/// # }
+/// #
+/// # let schema = Schema::parse(r#"
+/// #     schema { query: RootSchemaQuery }
+/// #     directive @filter(op: String!, value: [String!]) on FIELD | INLINE_FRAGMENT
+/// #     directive @tag(name: String) on FIELD
+/// #     directive @output(name: String) on FIELD
+/// #     directive @optional on FIELD
+/// #     directive @recurse(depth: Int!) on FIELD
+/// #     directive @fold on FIELD
+/// #     directive @transform(op: String!) on FIELD
+/// #
+/// #     type RootSchemaQuery { Number: [Number!]! }
+/// #     type Number { value: Int! }
+/// # "#).unwrap();
+/// # let adapter = Arc::new(Numbers);
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// struct Row {
+///     value: i64,
+/// }
+///
+/// let query = r#"
+/// {
+///     Number {
+///         value @output @filter(op: ">", value: ["$min"])
+///     }
+/// }"#;
+/// let variables = BTreeMap::from([("min", 1)]);
+///
+/// let rows: Vec<Row> = trustfall::execute_query_as(&schema, adapter, query, variables)
+///     .expect("query and struct did not match")
+///     .collect::<Result<_, _>>()
+///     .expect("failed to deserialize a result");
+/// assert_eq!(vec![Row { value: 2 }, Row { value: 3 }], rows);
+/// ```
+pub fn execute_query_as<'vertex, T>(
+    schema: &Schema,
+    adapter: Arc<impl provider::Adapter<'vertex> + 'vertex>,
+    query: &str,
+    variables: BTreeMap<impl Into<Arc<str>>, impl Into<FieldValue>>,
+) -> anyhow::Result<Box<dyn Iterator<Item = anyhow::Result<T>> + 'vertex>>
+where
+    T: for<'de> serde::Deserialize<'de> + 'vertex,
+{
+    let results = parse_and_execute(schema, adapter, query, variables, |parsed_query| {
+        check_result_struct::<T>(&parsed_query.outputs).map_err(Error::from)
+    })?;
+    Ok(Box::new(results.map(|row| Ok(row.try_into_struct::<T>()?))))
+}
+
+/// Parse the query, check it with `check`, then execute it.
+#[allow(clippy::type_complexity)]
+fn parse_and_execute<'vertex>(
+    schema: &Schema,
+    adapter: Arc<impl provider::Adapter<'vertex> + 'vertex>,
+    query: &str,
+    variables: BTreeMap<impl Into<Arc<str>>, impl Into<FieldValue>>,
+    check: impl FnOnce(&trustfall_core::ir::IndexedQuery) -> Result<(), Error>,
+) -> Result<Box<dyn Iterator<Item = BTreeMap<Arc<str>, FieldValue>> + 'vertex>, Error> {
     let vars = Arc::new(
         variables
             .into_iter()
@@ -81,9 +314,25 @@ pub fn execute_query<'vertex>(
             .collect(),
     );
 
-    Ok(trustfall_core::interpreter::execution::interpret_ir(
-        adapter,
-        parsed_query,
-        vars,
-    )?)
+    #[cfg(feature = "opentelemetry")]
+    {
+        use trustfall_core::interpreter::telemetry::{QueryTelemetry, TelemetryAdapter};
+
+        let telemetry = QueryTelemetry::new();
+        let parsed_query = telemetry.parse(schema, query)?;
+        check(&parsed_query)?;
+        let adapter = Arc::new(TelemetryAdapter::new(adapter));
+        Ok(telemetry.execute(adapter, parsed_query, vars)?)
+    }
+
+    #[cfg(not(feature = "opentelemetry"))]
+    {
+        let parsed_query = trustfall_core::frontend::parse(schema, query)?;
+        check(&parsed_query)?;
+        Ok(trustfall_core::interpreter::execution::interpret_ir(
+            adapter,
+            parsed_query,
+            vars,
+        )?)
+    }
 }
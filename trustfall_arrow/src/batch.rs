@@ -0,0 +1,255 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use arrow::{
+    array::{
+        ArrayRef, BooleanArray, Float64Array, Int64Array, ListArray, StringArray,
+        TimestampMicrosecondArray, UInt64Array,
+    },
+    buffer::{NullBuffer, OffsetBuffer},
+    datatypes::{DataType, FieldRef, SchemaRef, TimeUnit},
+    error::ArrowError,
+    record_batch::{RecordBatch, RecordBatchOptions, RecordBatchReader},
+};
+use trustfall_core::ir::FieldValue;
+
+type Row = BTreeMap<Arc<str>, FieldValue>;
+
+/// Collects query results into a [`RecordBatch`] with a given schema,
+/// usually derived with [`ArrowOptions::schema`](crate::ArrowOptions::schema).
+#[derive(Debug)]
+pub struct RecordBatchBuilder {
+    schema: SchemaRef,
+    columns: Vec<Vec<FieldValue>>,
+    rows: usize,
+}
+
+impl RecordBatchBuilder {
+    pub fn new(schema: SchemaRef) -> Self {
+        let columns = schema.fields().iter().map(|_| vec![]).collect();
+        Self {
+            schema,
+            columns,
+            rows: 0,
+        }
+    }
+
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// The number of rows pushed since the builder was created or last finished.
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// Add a query result as the next row.
+    ///
+    /// Outputs the schema has no field for are ignored, and fields the result has
+    /// no output for are null.
+    pub fn push(&mut self, mut row: Row) {
+        for (field, column) in self.schema.fields().iter().zip(self.columns.iter_mut()) {
+            column.push(row.remove(field.name().as_str()).unwrap_or_default());
+        }
+        self.rows += 1;
+    }
+
+    /// Build a record batch from the rows pushed so far, and reset the builder.
+    ///
+    /// Fails if a value can't be represented in its field's data type,
+    /// for example if a non-nullable field has a null value.
+    pub fn finish(&mut self) -> Result<RecordBatch, ArrowError> {
+        let rows = std::mem::take(&mut self.rows);
+        let arrays = self
+            .schema
+            .fields()
+            .iter()
+            .zip(self.columns.iter_mut())
+            .map(|(field, column)| {
+                let values = std::mem::take(column);
+                build_array(field.data_type(), &values).map_err(|e| match e {
+                    ArrowError::InvalidArgumentError(message) => ArrowError::InvalidArgumentError(
+                        format!("invalid value for output {}: {message}", field.name()),
+                    ),
+                    e => e,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        RecordBatch::try_new_with_options(
+            self.schema.clone(),
+            arrays,
+            &RecordBatchOptions::new().with_row_count(Some(rows)),
+        )
+    }
+}
+
+/// Query results converted to record batches, as they are produced.
+///
+/// Implements [`RecordBatchReader`], so it can be passed to Arrow-based libraries,
+/// or to other languages through the Arrow C stream interface.
+pub struct RecordBatches<I> {
+    results: I,
+    builder: RecordBatchBuilder,
+    batch_size: usize,
+}
+
+impl<I: Iterator<Item = Row>> RecordBatches<I> {
+    pub(crate) fn new(results: I, schema: SchemaRef, batch_size: usize) -> Self {
+        Self {
+            results,
+            builder: RecordBatchBuilder::new(schema),
+            batch_size,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Row>> Iterator for RecordBatches<I> {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for row in self.results.by_ref() {
+            self.builder.push(row);
+            if self.builder.len() == self.batch_size {
+                break;
+            }
+        }
+        (!self.builder.is_empty()).then(|| self.builder.finish())
+    }
+}
+
+impl<I: Iterator<Item = Row>> RecordBatchReader for RecordBatches<I> {
+    fn schema(&self) -> SchemaRef {
+        self.builder.schema()
+    }
+}
+
+fn unexpected(value: &FieldValue, data_type: &DataType) -> ArrowError {
+    ArrowError::InvalidArgumentError(format!(
+        "value {value:?} can't be represented as {data_type}"
+    ))
+}
+
+/// Build an array of the given data type from the values.
+///
+/// Integers are converted to floats, and between signed and unsigned types if they fit.
+/// Enums and timestamps are converted to strings; timestamps are converted to RFC 3339.
+fn build_array(data_type: &DataType, values: &[FieldValue]) -> Result<ArrayRef, ArrowError> {
+    let array: ArrayRef = match data_type {
+        DataType::Int64 => Arc::new(
+            values
+                .iter()
+                .map(|value| match value {
+                    FieldValue::Null => Ok(None),
+                    FieldValue::Int64(v) => Ok(Some(*v)),
+                    FieldValue::Uint64(v) => i64::try_from(*v)
+                        .map(Some)
+                        .map_err(|_| unexpected(value, data_type)),
+                    _ => Err(unexpected(value, data_type)),
+                })
+                .collect::<Result<Int64Array, _>>()?,
+        ),
+        DataType::UInt64 => Arc::new(
+            values
+                .iter()
+                .map(|value| match value {
+                    FieldValue::Null => Ok(None),
+                    FieldValue::Uint64(v) => Ok(Some(*v)),
+                    FieldValue::Int64(v) => u64::try_from(*v)
+                        .map(Some)
+                        .map_err(|_| unexpected(value, data_type)),
+                    _ => Err(unexpected(value, data_type)),
+                })
+                .collect::<Result<UInt64Array, _>>()?,
+        ),
+        DataType::Float64 => Arc::new(
+            values
+                .iter()
+                .map(|value| match value {
+                    FieldValue::Null => Ok(None),
+                    FieldValue::Float64(v) => Ok(Some(*v)),
+                    FieldValue::Int64(v) => Ok(Some(*v as f64)),
+                    FieldValue::Uint64(v) => Ok(Some(*v as f64)),
+                    _ => Err(unexpected(value, data_type)),
+                })
+                .collect::<Result<Float64Array, _>>()?,
+        ),
+        DataType::Boolean => Arc::new(
+            values
+                .iter()
+                .map(|value| match value {
+                    FieldValue::Null => Ok(None),
+                    FieldValue::Boolean(v) => Ok(Some(*v)),
+                    _ => Err(unexpected(value, data_type)),
+                })
+                .collect::<Result<BooleanArray, _>>()?,
+        ),
+        DataType::Utf8 => Arc::new(
+            values
+                .iter()
+                .map(|value| match value {
+                    FieldValue::Null => Ok(None),
+                    FieldValue::String(v) | FieldValue::Enum(v) => Ok(Some(v.clone())),
+                    FieldValue::DateTimeUtc(v) => Ok(Some(v.to_rfc3339())),
+                    _ => Err(unexpected(value, data_type)),
+                })
+                .collect::<Result<StringArray, _>>()?,
+        ),
+        DataType::Timestamp(TimeUnit::Microsecond, timezone) => Arc::new(
+            values
+                .iter()
+                .map(|value| match value {
+                    FieldValue::Null => Ok(None),
+                    FieldValue::DateTimeUtc(v) => Ok(Some(v.timestamp_micros())),
+                    _ => Err(unexpected(value, data_type)),
+                })
+                .collect::<Result<TimestampMicrosecondArray, _>>()?
+                .with_timezone_opt(timezone.clone()),
+        ),
+        DataType::List(field) => build_list_array(field, data_type, values)?,
+        _ => {
+            return Err(ArrowError::NotYetImplemented(format!(
+                "converting values to {data_type}"
+            )))
+        }
+    };
+    Ok(array)
+}
+
+fn build_list_array(
+    field: &FieldRef,
+    data_type: &DataType,
+    values: &[FieldValue],
+) -> Result<ArrayRef, ArrowError> {
+    let mut offsets = Vec::with_capacity(values.len() + 1);
+    let mut validity = Vec::with_capacity(values.len());
+    let mut items = vec![];
+    offsets.push(0i32);
+    for value in values {
+        match value {
+            FieldValue::Null => validity.push(false),
+            FieldValue::List(list) => {
+                validity.push(true);
+                items.extend(list.iter().cloned());
+            }
+            _ => return Err(unexpected(value, data_type)),
+        }
+        let offset = i32::try_from(items.len()).map_err(|_| {
+            ArrowError::InvalidArgumentError("too many list items for one batch".to_string())
+        })?;
+        offsets.push(offset);
+    }
+
+    let items = build_array(field.data_type(), &items)?;
+    let nulls = validity
+        .contains(&false)
+        .then(|| NullBuffer::from(validity));
+    Ok(Arc::new(ListArray::try_new(
+        field.clone(),
+        OffsetBuffer::new(offsets.into()),
+        items,
+        nulls,
+    )?))
+}
@@ -0,0 +1,83 @@
+//! Converts trustfall query results into Apache Arrow [`RecordBatch`]es, so they can be used
+//! directly by Arrow-based tools like DataFusion, Polars, and pyarrow.
+//!
+//! The Arrow schema of the results is derived from the types of the query's outputs:
+//! each output becomes a field of the same name, with outputs in `@fold` blocks
+//! becoming lists and outputs in `@optional` blocks becoming nullable.
+//!
+//! ```rust
+//! # use std::{collections::BTreeMap, sync::Arc};
+//! # use trustfall_core::{
+//! #     frontend::parse, interpreter::execution::interpret_ir,
+//! #     numbers_interpreter::NumbersAdapter, schema::Schema,
+//! # };
+//! use trustfall_arrow::{record_batches, ArrowOptions};
+//!
+//! # let schema = Schema::parse(include_str!("../../trustfall_core/test_data/schemas/numbers.graphql")).unwrap();
+//! let query = parse(&schema, r#"
+//! {
+//!     Number(max: 6) {
+//!         value @output
+//!
+//!         multiple(max: 3) @fold {
+//!             multiples: value @output
+//!         }
+//!     }
+//! }"#).unwrap();
+//! # let adapter = Arc::new(NumbersAdapter::new());
+//! let results = interpret_ir(adapter, query.clone(), Arc::new(BTreeMap::new())).unwrap();
+//!
+//! let batches = record_batches(&query.outputs, results, &ArrowOptions::new()).unwrap();
+//! for batch in batches {
+//!     let batch = batch.unwrap();
+//!     assert_eq!(2, batch.num_columns());
+//! }
+//! ```
+//!
+//! [`RecordBatch`]: arrow::record_batch::RecordBatch
+use std::{collections::BTreeMap, sync::Arc};
+
+use arrow::{error::ArrowError, record_batch::RecordBatch};
+use trustfall_core::ir::{FieldValue, Output};
+
+mod batch;
+mod schema;
+
+pub use batch::{RecordBatchBuilder, RecordBatches};
+pub use schema::ArrowOptions;
+
+/// Convert query results into record batches, as the results are produced.
+///
+/// Each batch holds at most [`ArrowOptions::with_batch_size`] rows. Fails if the type
+/// of an output can't be represented in Arrow with the given options.
+pub fn record_batches<I>(
+    outputs: &BTreeMap<Arc<str>, Output>,
+    results: I,
+    options: &ArrowOptions,
+) -> Result<RecordBatches<I::IntoIter>, ArrowError>
+where
+    I: IntoIterator<Item = BTreeMap<Arc<str>, FieldValue>>,
+{
+    let schema = Arc::new(options.schema(outputs)?);
+    Ok(RecordBatches::new(
+        results.into_iter(),
+        schema,
+        options.batch_size,
+    ))
+}
+
+/// Convert all of a query's results into a single record batch.
+pub fn to_record_batch<I>(
+    outputs: &BTreeMap<Arc<str>, Output>,
+    results: I,
+    options: &ArrowOptions,
+) -> Result<RecordBatch, ArrowError>
+where
+    I: IntoIterator<Item = BTreeMap<Arc<str>, FieldValue>>,
+{
+    let mut builder = RecordBatchBuilder::new(Arc::new(options.schema(outputs)?));
+    for row in results {
+        builder.push(row);
+    }
+    builder.finish()
+}
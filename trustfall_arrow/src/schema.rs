@@ -0,0 +1,108 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use arrow::{
+    datatypes::{DataType, Field, Schema, TimeUnit},
+    error::ArrowError,
+};
+use trustfall_core::ir::{Output, Type};
+
+/// Options for converting query results to Arrow.
+#[derive(Debug, Clone)]
+pub struct ArrowOptions {
+    pub(crate) named_types: BTreeMap<String, DataType>,
+    pub(crate) batch_size: usize,
+}
+
+impl Default for ArrowOptions {
+    fn default() -> Self {
+        Self {
+            named_types: Default::default(),
+            batch_size: 1024,
+        }
+    }
+}
+
+impl ArrowOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Use the given Arrow data type for values of the named schema type.
+    ///
+    /// This is needed for custom scalars whose values aren't strings, like timestamps
+    /// stored as `DateTimeUtc` values, which can be mapped to
+    /// `DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))`.
+    /// It also overrides the data types of the built-in scalars.
+    pub fn with_named_type(mut self, type_name: impl Into<String>, data_type: DataType) -> Self {
+        self.named_types.insert(type_name.into(), data_type);
+        self
+    }
+
+    /// The maximum number of rows in each record batch. Defaults to 1024.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "batch size must be positive");
+        self.batch_size = batch_size;
+        self
+    }
+
+    fn named_data_type(&self, name: &str) -> DataType {
+        if let Some(data_type) = self.named_types.get(name) {
+            return data_type.clone();
+        }
+        match name {
+            "Int" => DataType::Int64,
+            "Float" => DataType::Float64,
+            "Boolean" => DataType::Boolean,
+            // Strings and IDs, and also enums and custom scalars by default.
+            _ => DataType::Utf8,
+        }
+    }
+
+    /// The Arrow data type of values of the given trustfall type.
+    pub fn data_type(&self, value_type: &Type) -> DataType {
+        match value_type.element_type() {
+            Some(element) => DataType::List(Arc::new(self.field("item", &element))),
+            None => self.named_data_type(value_type.base_type()),
+        }
+    }
+
+    fn field(&self, name: &str, value_type: &Type) -> Field {
+        Field::new(name, self.data_type(value_type), value_type.is_nullable())
+    }
+
+    /// The Arrow schema of the record batches holding a query's results:
+    /// one field per output, in order of output name.
+    ///
+    /// Outputs inside `@fold` blocks are lists, and outputs inside `@optional` blocks
+    /// are nullable, as given by the types of the outputs.
+    pub fn schema(&self, outputs: &BTreeMap<Arc<str>, Output>) -> Result<Schema, ArrowError> {
+        let fields = outputs
+            .values()
+            .map(|output| {
+                let field = self.field(&output.name, &output.value_type);
+                check_supported(field.data_type()).map_err(|data_type| {
+                    ArrowError::SchemaError(format!(
+                        "output {} of type {} would use unsupported Arrow data type {data_type}",
+                        output.name, output.value_type
+                    ))
+                })?;
+                Ok(field)
+            })
+            .collect::<Result<Vec<_>, ArrowError>>()?;
+        Ok(Schema::new(fields))
+    }
+}
+
+/// Check that values can be converted to the data type, returning the unsupported type if not.
+fn check_supported(data_type: &DataType) -> Result<(), &DataType> {
+    match data_type {
+        DataType::Int64
+        | DataType::UInt64
+        | DataType::Float64
+        | DataType::Boolean
+        | DataType::Utf8
+        | DataType::Timestamp(TimeUnit::Microsecond, _) => Ok(()),
+        DataType::List(field) => check_supported(field.data_type()),
+        _ => Err(data_type),
+    }
+}
@@ -0,0 +1,146 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use arrow::{
+    array::{Array, Int64Array, ListArray, StringArray},
+    datatypes::{DataType, Field, Schema},
+    record_batch::RecordBatchReader,
+};
+use trustfall_arrow::{record_batches, to_record_batch, ArrowOptions};
+use trustfall_core::{
+    frontend::parse,
+    interpreter::execution::interpret_ir,
+    ir::{FieldValue, IndexedQuery},
+    numbers_interpreter::NumbersAdapter,
+    schema::Schema as TrustfallSchema,
+};
+
+type Row = BTreeMap<Arc<str>, FieldValue>;
+
+fn execute(query: &str) -> (Arc<IndexedQuery>, Vec<Row>) {
+    let schema = TrustfallSchema::parse(include_str!(
+        "../../trustfall_core/test_data/schemas/numbers.graphql"
+    ))
+    .unwrap();
+    let query = parse(&schema, query).unwrap();
+    let adapter = Arc::new(NumbersAdapter::new());
+    let results = interpret_ir(adapter, query.clone(), Arc::new(BTreeMap::new()))
+        .unwrap()
+        .collect();
+    (query, results)
+}
+
+const QUERY: &str = r#"
+{
+    Number(min: 1, max: 4) {
+        value @output
+        name @output
+
+        predecessor @optional {
+            previous: value @output
+        }
+
+        multiple(max: 3) @fold {
+            multiples: value @output
+        }
+    }
+}"#;
+
+#[test]
+fn derives_schema_from_outputs() {
+    let (query, _) = execute(QUERY);
+    let schema = ArrowOptions::new().schema(&query.outputs).unwrap();
+    let expected = Schema::new(vec![
+        Field::new(
+            "multiples",
+            DataType::List(Arc::new(Field::new("item", DataType::Int64, true))),
+            false,
+        ),
+        Field::new("name", DataType::Utf8, true),
+        Field::new("previous", DataType::Int64, true),
+        Field::new("value", DataType::Int64, true),
+    ]);
+    assert_eq!(expected, schema);
+}
+
+#[test]
+fn converts_results() {
+    let (query, results) = execute(QUERY);
+    let batch = to_record_batch(&query.outputs, results, &ArrowOptions::new()).unwrap();
+    assert_eq!(4, batch.num_rows());
+
+    let column = |name: &str| batch.column_by_name(name).unwrap().clone();
+    let values = column("value");
+    let values = values.as_any().downcast_ref::<Int64Array>().unwrap();
+    assert_eq!(
+        vec![Some(1), Some(2), Some(3), Some(4)],
+        values.iter().collect::<Vec<_>>()
+    );
+
+    let names = column("name");
+    let names = names.as_any().downcast_ref::<StringArray>().unwrap();
+    assert_eq!(Some("one"), names.iter().next().unwrap());
+
+    let previous = column("previous");
+    let previous = previous.as_any().downcast_ref::<Int64Array>().unwrap();
+    assert_eq!(
+        vec![Some(0), Some(1), Some(2), Some(3)],
+        previous.iter().collect::<Vec<_>>()
+    );
+
+    let multiples = column("multiples");
+    let multiples = multiples.as_any().downcast_ref::<ListArray>().unwrap();
+    let multiples_of_two = multiples.value(1);
+    let multiples_of_two = multiples_of_two
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    assert_eq!(
+        vec![Some(4), Some(6)],
+        multiples_of_two.iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn produces_batches_of_given_size() {
+    let (query, results) = execute(QUERY);
+    let options = ArrowOptions::new().with_batch_size(3);
+    let batches = record_batches(&query.outputs, results, &options).unwrap();
+    assert_eq!(4, batches.schema().fields().len());
+
+    let sizes: Vec<_> = batches.map(|batch| batch.unwrap().num_rows()).collect();
+    assert_eq!(vec![3, 1], sizes);
+}
+
+#[test]
+fn rejects_values_of_other_types() {
+    let (query, _) = execute(QUERY);
+    let mut row = Row::new();
+    row.insert("value".into(), FieldValue::String("four".to_string()));
+    row.insert("multiples".into(), FieldValue::List(vec![]));
+    let error = to_record_batch(&query.outputs, [row], &ArrowOptions::new()).unwrap_err();
+    assert!(
+        error.to_string().contains("invalid value for output value"),
+        "{error}"
+    );
+}
+
+#[test]
+fn rejects_nulls_in_non_nullable_fields() {
+    let (query, _) = execute(QUERY);
+    let row = Row::new();
+    assert!(to_record_batch(&query.outputs, [row], &ArrowOptions::new()).is_err());
+}
+
+#[test]
+fn uses_named_type_overrides() {
+    let (query, _) = execute(QUERY);
+    let options = ArrowOptions::new().with_named_type("Int", DataType::UInt64);
+    let schema = options.schema(&query.outputs).unwrap();
+    assert_eq!(
+        &DataType::UInt64,
+        schema.field_with_name("value").unwrap().data_type()
+    );
+
+    let options = ArrowOptions::new().with_named_type("Int", DataType::Int8);
+    assert!(options.schema(&query.outputs).is_err());
+}
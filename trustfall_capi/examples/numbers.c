@@ -0,0 +1,176 @@
+/*
+ * Queries the prime numbers between 3 and 10, together with their successors.
+ *
+ * Build the library with `cargo build -p trustfall_capi`, then from this directory:
+ *   cc numbers.c -I../include -L../../target/debug -ltrustfall_capi -o numbers
+ *   LD_LIBRARY_PATH=../../target/debug ./numbers
+ */
+#include <inttypes.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "trustfall.h"
+
+static const char *SCHEMA =
+    "schema {\n"
+    "    query: RootSchemaQuery\n"
+    "}\n"
+    "directive @filter(op: String!, value: [String!]) on FIELD | INLINE_FRAGMENT\n"
+    "directive @tag(name: String) on FIELD\n"
+    "directive @output(name: String) on FIELD\n"
+    "directive @optional on FIELD\n"
+    "directive @recurse(depth: Int!) on FIELD\n"
+    "directive @fold on FIELD\n"
+    "directive @transform(op: String!) on FIELD\n"
+    "\n"
+    "type RootSchemaQuery {\n"
+    "    Number(max: Int!): [Number!]\n"
+    "}\n"
+    "\n"
+    "interface Number {\n"
+    "    value: Int\n"
+    "    successor: Number!\n"
+    "}\n"
+    "\n"
+    "type Prime implements Number {\n"
+    "    value: Int\n"
+    "    successor: Number!\n"
+    "}\n"
+    "\n"
+    "type Composite implements Number {\n"
+    "    value: Int\n"
+    "    successor: Number!\n"
+    "}\n";
+
+static const char *QUERY =
+    "{\n"
+    "    Number(max: 10) {\n"
+    "        ... on Prime {\n"
+    "            value @output @filter(op: \">=\", value: [\"$min\"])\n"
+    "            successor {\n"
+    "                next: value @output\n"
+    "            }\n"
+    "        }\n"
+    "    }\n"
+    "}\n";
+
+static void *make_number(int64_t value) {
+    int64_t *number = malloc(sizeof(int64_t));
+    *number = value;
+    return number;
+}
+
+static bool is_prime(int64_t value) {
+    if (value < 2) {
+        return false;
+    }
+    for (int64_t i = 2; i * i <= value; i++) {
+        if (value % i == 0) {
+            return false;
+        }
+    }
+    return true;
+}
+
+static void starting_vertices(void *user_data, TrustfallStr edge_name,
+                              const TrustfallArgument *parameters, size_t parameters_len,
+                              TrustfallCall *call) {
+    (void)user_data;
+    (void)edge_name;
+    for (size_t i = 0; i < parameters_len; i++) {
+        if (strcmp(parameters[i].name.data, "max") == 0) {
+            for (int64_t n = 1; n <= parameters[i].value.data.int64; n++) {
+                trustfall_call_push_vertex(call, make_number(n));
+            }
+            return;
+        }
+    }
+    trustfall_call_fail(call, TRUSTFALL_STR("missing max parameter"));
+}
+
+static void property(void *user_data, void *vertex, TrustfallStr type_name,
+                     TrustfallStr property_name, TrustfallCall *call) {
+    (void)user_data;
+    (void)type_name;
+    (void)property_name;
+    TrustfallValue value = {.kind = TRUSTFALL_VALUE_INT64, .data.int64 = *(int64_t *)vertex};
+    trustfall_call_set_value(call, &value);
+}
+
+static void neighbors(void *user_data, void *vertex, TrustfallStr type_name,
+                      TrustfallStr edge_name, const TrustfallArgument *parameters,
+                      size_t parameters_len, TrustfallCall *call) {
+    (void)user_data;
+    (void)type_name;
+    (void)edge_name;
+    (void)parameters;
+    (void)parameters_len;
+    trustfall_call_push_vertex(call, make_number(*(int64_t *)vertex + 1));
+}
+
+static bool coercion(void *user_data, void *vertex, TrustfallStr type_name,
+                     TrustfallStr coerce_to_type, TrustfallCall *call) {
+    (void)user_data;
+    (void)type_name;
+    (void)call;
+    bool prime = is_prime(*(int64_t *)vertex);
+    return strcmp(coerce_to_type.data, "Prime") == 0 ? prime : !prime;
+}
+
+static void release_vertex(void *user_data, void *vertex) {
+    (void)user_data;
+    free(vertex);
+}
+
+static int fail(TrustfallError *error) {
+    fprintf(stderr, "error: %s\n", trustfall_error_message(error));
+    trustfall_error_free(error);
+    return 1;
+}
+
+int main(void) {
+    TrustfallError *error = NULL;
+
+    TrustfallSchema *schema = trustfall_schema_parse(TRUSTFALL_STR(SCHEMA), &error);
+    if (!schema) {
+        return fail(error);
+    }
+
+    TrustfallAdapterCallbacks callbacks = {
+        .starting_vertices = starting_vertices,
+        .property = property,
+        .neighbors = neighbors,
+        .coercion = coercion,
+        .release_vertex = release_vertex,
+    };
+    TrustfallAdapter *adapter = trustfall_adapter_new(&callbacks, NULL, &error);
+    if (!adapter) {
+        return fail(error);
+    }
+
+    TrustfallArgument arguments[] = {
+        {.name = TRUSTFALL_STR("min"),
+         .value = {.kind = TRUSTFALL_VALUE_INT64, .data.int64 = 3}},
+    };
+    TrustfallResults *results =
+        trustfall_execute(schema, adapter, TRUSTFALL_STR(QUERY), arguments, 1, &error);
+    if (!results) {
+        return fail(error);
+    }
+
+    TrustfallRow *row;
+    while ((row = trustfall_results_next(results, &error))) {
+        const TrustfallValue *value = trustfall_row_get(row, TRUSTFALL_STR("value"));
+        const TrustfallValue *next = trustfall_row_get(row, TRUSTFALL_STR("next"));
+        printf("%" PRId64 " -> %" PRId64 "\n", value->data.int64, next->data.int64);
+        trustfall_row_free(row);
+    }
+    if (error) {
+        return fail(error);
+    }
+
+    trustfall_results_free(results);
+    trustfall_adapter_free(adapter);
+    trustfall_schema_free(schema);
+    return 0;
+}
@@ -0,0 +1,184 @@
+/*
+ * C bindings for the trustfall query engine.
+ *
+ * Schemas, adapters, query results, and errors are opaque handles, each created and freed
+ * with the functions below. Functions that can fail take a `TrustfallError **` out-parameter,
+ * which may be null if the caller isn't interested in the error, and return null on failure.
+ *
+ * Handles are not thread-safe: an adapter and the results of queries executed with it
+ * must stay on one thread.
+ */
+#ifndef TRUSTFALL_H
+#define TRUSTFALL_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <string.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * A UTF-8 string, passed by pointer and length.
+ *
+ * Strings passed to C by trustfall are always followed by a NUL byte that is not counted
+ * in `len`. Strings passed to trustfall by C don't need to be NUL-terminated.
+ */
+typedef struct TrustfallStr {
+    const char *data;
+    size_t len;
+} TrustfallStr;
+
+/* A `TrustfallStr` for a NUL-terminated C string. */
+#define TRUSTFALL_STR(s) ((TrustfallStr){(s), strlen(s)})
+
+/* The kind of a `TrustfallValue`, determining which field of its `data` is set. */
+typedef uint32_t TrustfallValueKind;
+
+#define TRUSTFALL_VALUE_NULL ((TrustfallValueKind)0)
+#define TRUSTFALL_VALUE_INT64 ((TrustfallValueKind)1)
+#define TRUSTFALL_VALUE_UINT64 ((TrustfallValueKind)2)
+#define TRUSTFALL_VALUE_FLOAT64 ((TrustfallValueKind)3)
+#define TRUSTFALL_VALUE_BOOLEAN ((TrustfallValueKind)4)
+#define TRUSTFALL_VALUE_STRING ((TrustfallValueKind)5)
+/* An enum value, given by name in `data.string`. */
+#define TRUSTFALL_VALUE_ENUM ((TrustfallValueKind)6)
+/* A timestamp in UTC, given as an RFC 3339 string in `data.string`. */
+#define TRUSTFALL_VALUE_DATETIME_UTC ((TrustfallValueKind)7)
+#define TRUSTFALL_VALUE_LIST ((TrustfallValueKind)8)
+
+struct TrustfallValue;
+
+/* A list of values, passed by pointer and length. */
+typedef struct TrustfallList {
+    const struct TrustfallValue *items;
+    size_t len;
+} TrustfallList;
+
+/*
+ * A property value, query argument, or query output.
+ *
+ * Values passed to C by trustfall are borrowed, and remain valid for as long as the object
+ * they were obtained from. Values passed to trustfall by C are copied before the call
+ * they are passed to returns.
+ */
+typedef struct TrustfallValue {
+    TrustfallValueKind kind;
+    union {
+        int64_t int64;
+        uint64_t uint64;
+        double float64;
+        bool boolean;
+        TrustfallStr string;
+        TrustfallList list;
+    } data;
+} TrustfallValue;
+
+/* A named value: a query argument, or an edge parameter. */
+typedef struct TrustfallArgument {
+    TrustfallStr name;
+    TrustfallValue value;
+} TrustfallArgument;
+
+typedef struct TrustfallError TrustfallError;
+typedef struct TrustfallSchema TrustfallSchema;
+typedef struct TrustfallAdapter TrustfallAdapter;
+typedef struct TrustfallCall TrustfallCall;
+typedef struct TrustfallResults TrustfallResults;
+typedef struct TrustfallRow TrustfallRow;
+
+/* The error's message, valid until the error is freed. */
+const char *trustfall_error_message(const TrustfallError *error);
+void trustfall_error_free(TrustfallError *error);
+
+/* Parse a schema from its GraphQL SDL definition. */
+TrustfallSchema *trustfall_schema_parse(TrustfallStr input, TrustfallError **error);
+void trustfall_schema_free(TrustfallSchema *schema);
+
+/*
+ * The callbacks with which an adapter resolves a query's data.
+ *
+ * Vertices are opaque pointers owned by the adapter. Each callback receives the adapter's
+ * `user_data` pointer, and a `TrustfallCall` through which it produces its results
+ * or reports failure with `trustfall_call_fail()`:
+ * - `starting_vertices` pushes the vertices at the start of the query
+ *   with `trustfall_call_push_vertex()`;
+ * - `property` sets the value of the vertex's property with `trustfall_call_set_value()`,
+ *   and the value is null if it doesn't set one;
+ * - `neighbors` pushes the vertex's neighbors along the edge
+ *   with `trustfall_call_push_vertex()`;
+ * - `coercion` returns whether the vertex is of the given subtype.
+ *
+ * All callbacks are called from within `trustfall_results_next()`, on the thread calling it.
+ *
+ * `release_vertex` is called once the engine no longer needs a vertex, and
+ * `release_user_data` is called with null as its `pointer` once the adapter and
+ * all queries executing with it have been freed. Both are optional.
+ */
+typedef struct TrustfallAdapterCallbacks {
+    void (*starting_vertices)(void *user_data, TrustfallStr edge_name,
+                              const TrustfallArgument *parameters, size_t parameters_len,
+                              TrustfallCall *call);
+    void (*property)(void *user_data, void *vertex, TrustfallStr type_name,
+                     TrustfallStr property_name, TrustfallCall *call);
+    void (*neighbors)(void *user_data, void *vertex, TrustfallStr type_name,
+                      TrustfallStr edge_name, const TrustfallArgument *parameters,
+                      size_t parameters_len, TrustfallCall *call);
+    bool (*coercion)(void *user_data, void *vertex, TrustfallStr type_name,
+                     TrustfallStr coerce_to_type, TrustfallCall *call);
+    void (*release_vertex)(void *user_data, void *pointer);
+    void (*release_user_data)(void *user_data, void *pointer);
+} TrustfallAdapterCallbacks;
+
+/* Create an adapter from its callbacks, which are copied. */
+TrustfallAdapter *trustfall_adapter_new(const TrustfallAdapterCallbacks *callbacks,
+                                        void *user_data, TrustfallError **error);
+void trustfall_adapter_free(TrustfallAdapter *adapter);
+
+/* Produce a vertex from a `starting_vertices` or `neighbors` callback. */
+void trustfall_call_push_vertex(TrustfallCall *call, void *vertex);
+
+/*
+ * Set the property value produced by a `property` callback. The value is copied.
+ * Returns false and fails the call if the value is invalid.
+ */
+bool trustfall_call_set_value(TrustfallCall *call, const TrustfallValue *value);
+
+/* Fail the call, which stops the query with an error containing the message. */
+void trustfall_call_fail(TrustfallCall *call, TrustfallStr message);
+
+/*
+ * Execute a query with the given arguments. The adapter's callbacks are not called
+ * until results are requested with `trustfall_results_next()`.
+ */
+TrustfallResults *trustfall_execute(const TrustfallSchema *schema,
+                                    const TrustfallAdapter *adapter, TrustfallStr query,
+                                    const TrustfallArgument *arguments, size_t arguments_len,
+                                    TrustfallError **error);
+
+/*
+ * Produce the next result of the query. Returns null once there are no more results,
+ * or if producing the result failed, in which case `*error` is set.
+ */
+TrustfallRow *trustfall_results_next(TrustfallResults *results, TrustfallError **error);
+void trustfall_results_free(TrustfallResults *results);
+
+/*
+ * The outputs of a result, in order of name. Names and values remain valid
+ * until the result is freed.
+ */
+size_t trustfall_row_len(const TrustfallRow *row);
+TrustfallStr trustfall_row_name(const TrustfallRow *row, size_t index);
+const TrustfallValue *trustfall_row_value(const TrustfallRow *row, size_t index);
+
+/* The value of the output with the given name, or null if the query has no such output. */
+const TrustfallValue *trustfall_row_get(const TrustfallRow *row, TrustfallStr name);
+void trustfall_row_free(TrustfallRow *row);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TRUSTFALL_H */
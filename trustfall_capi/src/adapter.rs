@@ -0,0 +1,386 @@
+use std::{ffi::c_void, fmt::Debug, panic::resume_unwind, ptr, rc::Rc, sync::Arc};
+
+use trustfall_core::{
+    interpreter::{
+        helpers::{resolve_coercion_with, resolve_neighbors_with, resolve_property_with},
+        Adapter, ContextIterator, ContextOutcomeIterator, ResolveEdgeInfo, ResolveInfo,
+        VertexIterator,
+    },
+    ir::{EdgeParameters, FieldValue},
+};
+
+use crate::{
+    error::{guard, CallbackFailure, TrustfallError},
+    value::{OwnedArguments, OwnedStr, TrustfallArgument, TrustfallStr, TrustfallValue},
+};
+
+pub type StartingVerticesCallback = unsafe extern "C" fn(
+    user_data: *mut c_void,
+    edge_name: TrustfallStr,
+    parameters: *const TrustfallArgument,
+    parameters_len: usize,
+    call: *mut TrustfallCall,
+);
+
+pub type PropertyCallback = unsafe extern "C" fn(
+    user_data: *mut c_void,
+    vertex: *mut c_void,
+    type_name: TrustfallStr,
+    property_name: TrustfallStr,
+    call: *mut TrustfallCall,
+);
+
+pub type NeighborsCallback = unsafe extern "C" fn(
+    user_data: *mut c_void,
+    vertex: *mut c_void,
+    type_name: TrustfallStr,
+    edge_name: TrustfallStr,
+    parameters: *const TrustfallArgument,
+    parameters_len: usize,
+    call: *mut TrustfallCall,
+);
+
+pub type CoercionCallback = unsafe extern "C" fn(
+    user_data: *mut c_void,
+    vertex: *mut c_void,
+    type_name: TrustfallStr,
+    coerce_to_type: TrustfallStr,
+    call: *mut TrustfallCall,
+) -> bool;
+
+pub type ReleaseCallback = unsafe extern "C" fn(user_data: *mut c_void, pointer: *mut c_void);
+
+/// The callbacks with which an adapter resolves a query's data.
+///
+/// Vertices are opaque pointers owned by the adapter. Each callback receives the adapter's
+/// `user_data` pointer, and a [`TrustfallCall`] through which it produces its results
+/// or reports failure:
+/// - `starting_vertices` pushes the vertices at the start of the query
+///   with [`trustfall_call_push_vertex`];
+/// - `property` sets the value of the vertex's property with [`trustfall_call_set_value`],
+///   and the value is null if it doesn't set one;
+/// - `neighbors` pushes the vertex's neighbors along the edge with
+///   [`trustfall_call_push_vertex`];
+/// - `coercion` returns whether the vertex is of the given subtype.
+///
+/// All callbacks are called from within [`trustfall_results_next`](crate::trustfall_results_next),
+/// on the thread calling it. Callbacks are only called for vertices that exist,
+/// so `vertex` is never null unless the adapter pushed a null vertex.
+///
+/// `release_vertex` is called once the engine no longer needs a vertex, and
+/// `release_user_data` is called with null as its `pointer` once the adapter and
+/// all queries executing with it have been freed. Both are optional.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TrustfallAdapterCallbacks {
+    pub starting_vertices: Option<StartingVerticesCallback>,
+    pub property: Option<PropertyCallback>,
+    pub neighbors: Option<NeighborsCallback>,
+    pub coercion: Option<CoercionCallback>,
+    pub release_vertex: Option<ReleaseCallback>,
+    pub release_user_data: Option<ReleaseCallback>,
+}
+
+#[derive(Debug)]
+struct Callbacks {
+    starting_vertices: StartingVerticesCallback,
+    property: PropertyCallback,
+    neighbors: NeighborsCallback,
+    coercion: CoercionCallback,
+    release_vertex: Option<ReleaseCallback>,
+    release_user_data: Option<ReleaseCallback>,
+}
+
+#[derive(Debug)]
+struct AdapterState {
+    callbacks: Callbacks,
+    user_data: *mut c_void,
+}
+
+impl Drop for AdapterState {
+    fn drop(&mut self) {
+        if let Some(release) = self.callbacks.release_user_data {
+            unsafe { release(self.user_data, ptr::null_mut()) }
+        }
+    }
+}
+
+struct VertexHandle {
+    pointer: *mut c_void,
+    adapter: Rc<AdapterState>,
+}
+
+impl Drop for VertexHandle {
+    fn drop(&mut self) {
+        if let Some(release) = self.adapter.callbacks.release_vertex {
+            unsafe { release(self.adapter.user_data, self.pointer) }
+        }
+    }
+}
+
+/// A vertex produced by the adapter's callbacks.
+#[derive(Clone)]
+pub(crate) struct CVertex(Rc<VertexHandle>);
+
+impl Debug for CVertex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("CVertex").field(&self.0.pointer).finish()
+    }
+}
+
+/// The results of an adapter callback as it is running.
+pub struct TrustfallCall {
+    adapter: Rc<AdapterState>,
+    vertices: Vec<CVertex>,
+    value: FieldValue,
+    failure: Option<String>,
+}
+
+/// Produce a vertex from a `starting_vertices` or `neighbors` callback.
+///
+/// # Safety
+///
+/// `call` must be the call passed to the currently running callback.
+#[no_mangle]
+pub unsafe extern "C" fn trustfall_call_push_vertex(call: *mut TrustfallCall, vertex: *mut c_void) {
+    let call = &mut *call;
+    call.vertices.push(CVertex(Rc::new(VertexHandle {
+        pointer: vertex,
+        adapter: call.adapter.clone(),
+    })));
+}
+
+/// Set the property value produced by a `property` callback. The value is copied.
+///
+/// Returns `false` and fails the call if the value is invalid,
+/// for example if a string is not valid UTF-8.
+///
+/// # Safety
+///
+/// `call` must be the call passed to the currently running callback,
+/// and `value` must point to a valid value.
+#[no_mangle]
+pub unsafe extern "C" fn trustfall_call_set_value(
+    call: *mut TrustfallCall,
+    value: *const TrustfallValue,
+) -> bool {
+    let call = &mut *call;
+    match (*value).to_field_value() {
+        Ok(value) => {
+            call.value = value;
+            true
+        }
+        Err(message) => {
+            call.failure = Some(message);
+            false
+        }
+    }
+}
+
+/// Fail the call, which stops the query with an error containing the message.
+///
+/// # Safety
+///
+/// `call` must be the call passed to the currently running callback,
+/// and `message` must be a valid string.
+#[no_mangle]
+pub unsafe extern "C" fn trustfall_call_fail(call: *mut TrustfallCall, message: TrustfallStr) {
+    let call = &mut *call;
+    let message = match message.to_str() {
+        Ok(message) => message.to_string(),
+        Err(e) => format!("(invalid error message: {e})"),
+    };
+    call.failure = Some(message);
+}
+
+/// An adapter implemented by C callbacks.
+#[derive(Debug)]
+pub struct TrustfallAdapter {
+    state: Rc<AdapterState>,
+}
+
+impl TrustfallAdapter {
+    pub(crate) fn shim(&self) -> AdapterShim {
+        AdapterShim {
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// Create an adapter from its callbacks, which are copied.
+///
+/// Returns null and reports an error if any of the required callbacks are null.
+/// The adapter must be freed with [`trustfall_adapter_free`].
+///
+/// # Safety
+///
+/// `callbacks` must point to valid callbacks, and `error` must be null or valid for writes.
+/// The callbacks must be safe to call with `user_data` until `release_user_data` is called.
+#[no_mangle]
+pub unsafe extern "C" fn trustfall_adapter_new(
+    callbacks: *const TrustfallAdapterCallbacks,
+    user_data: *mut c_void,
+    error: *mut *mut TrustfallError,
+) -> *mut TrustfallAdapter {
+    guard(error, ptr::null_mut(), || {
+        let callbacks = *callbacks;
+        let missing = |name: &str| TrustfallError::new(format!("the {name} callback is required"));
+        let callbacks = Callbacks {
+            starting_vertices: callbacks
+                .starting_vertices
+                .ok_or_else(|| missing("starting_vertices"))?,
+            property: callbacks.property.ok_or_else(|| missing("property"))?,
+            neighbors: callbacks.neighbors.ok_or_else(|| missing("neighbors"))?,
+            coercion: callbacks.coercion.ok_or_else(|| missing("coercion"))?,
+            release_vertex: callbacks.release_vertex,
+            release_user_data: callbacks.release_user_data,
+        };
+        let state = Rc::new(AdapterState {
+            callbacks,
+            user_data,
+        });
+        Ok(Box::into_raw(Box::new(TrustfallAdapter { state })))
+    })
+}
+
+/// Free an adapter. Does nothing if `adapter` is null.
+///
+/// Queries that are already executing with the adapter keep using its callbacks,
+/// and `release_user_data` is only called once they are freed as well.
+///
+/// # Safety
+///
+/// `adapter` must be null, or a valid adapter that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn trustfall_adapter_free(adapter: *mut TrustfallAdapter) {
+    if !adapter.is_null() {
+        drop(Box::from_raw(adapter));
+    }
+}
+
+/// Run a callback, then stop the query if the callback failed.
+fn invoke<R>(
+    state: &Rc<AdapterState>,
+    callback_name: &str,
+    callback: impl FnOnce(*mut TrustfallCall) -> R,
+) -> (R, TrustfallCall) {
+    let mut call = TrustfallCall {
+        adapter: state.clone(),
+        vertices: vec![],
+        value: FieldValue::Null,
+        failure: None,
+    };
+    let result = callback(&mut call);
+    if let Some(message) = call.failure.take() {
+        resume_unwind(Box::new(CallbackFailure(format!(
+            "the {callback_name} callback failed: {message}"
+        ))));
+    }
+    (result, call)
+}
+
+/// The [`Adapter`] implementation calling into the C callbacks.
+pub(crate) struct AdapterShim {
+    state: Rc<AdapterState>,
+}
+
+impl Adapter<'static> for AdapterShim {
+    type Vertex = CVertex;
+
+    fn resolve_starting_vertices(
+        &self,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        _resolve_info: &ResolveInfo,
+    ) -> VertexIterator<'static, Self::Vertex> {
+        let state = &self.state;
+        let edge_name = OwnedStr::new(edge_name);
+        let parameters = OwnedArguments::new(parameters.iter().map(|(k, v)| (k.as_ref(), v)));
+        let ((), call) = invoke(state, "starting_vertices", |call| unsafe {
+            (state.callbacks.starting_vertices)(
+                state.user_data,
+                edge_name.view(),
+                parameters.as_ptr(),
+                parameters.len(),
+                call,
+            )
+        });
+        Box::new(call.vertices.into_iter())
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'static, Self::Vertex>,
+        type_name: &Arc<str>,
+        property_name: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'static, Self::Vertex, FieldValue> {
+        let state = self.state.clone();
+        let type_name = OwnedStr::new(type_name);
+        let property_name = OwnedStr::new(property_name);
+        resolve_property_with(contexts, move |vertex| {
+            let ((), call) = invoke(&state, "property", |call| unsafe {
+                (state.callbacks.property)(
+                    state.user_data,
+                    vertex.0.pointer,
+                    type_name.view(),
+                    property_name.view(),
+                    call,
+                )
+            });
+            call.value
+        })
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: ContextIterator<'static, Self::Vertex>,
+        type_name: &Arc<str>,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        _resolve_info: &ResolveEdgeInfo,
+    ) -> ContextOutcomeIterator<'static, Self::Vertex, VertexIterator<'static, Self::Vertex>> {
+        let state = self.state.clone();
+        let type_name = OwnedStr::new(type_name);
+        let edge_name = OwnedStr::new(edge_name);
+        let parameters = OwnedArguments::new(parameters.iter().map(|(k, v)| (k.as_ref(), v)));
+        resolve_neighbors_with(contexts, move |vertex| {
+            let ((), call) = invoke(&state, "neighbors", |call| unsafe {
+                (state.callbacks.neighbors)(
+                    state.user_data,
+                    vertex.0.pointer,
+                    type_name.view(),
+                    edge_name.view(),
+                    parameters.as_ptr(),
+                    parameters.len(),
+                    call,
+                )
+            });
+            Box::new(call.vertices.into_iter())
+        })
+    }
+
+    fn resolve_coercion(
+        &self,
+        contexts: ContextIterator<'static, Self::Vertex>,
+        type_name: &Arc<str>,
+        coerce_to_type: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'static, Self::Vertex, bool> {
+        let state = self.state.clone();
+        let type_name = OwnedStr::new(type_name);
+        let coerce_to_type = OwnedStr::new(coerce_to_type);
+        resolve_coercion_with(contexts, move |vertex| {
+            let (can_coerce, _) = invoke(&state, "coercion", |call| unsafe {
+                (state.callbacks.coercion)(
+                    state.user_data,
+                    vertex.0.pointer,
+                    type_name.view(),
+                    coerce_to_type.view(),
+                    call,
+                )
+            });
+            can_coerce
+        })
+    }
+}
@@ -0,0 +1,119 @@
+use std::{
+    any::Any,
+    ffi::{c_char, CString},
+    fmt::Display,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+/// An error reported by a trustfall function.
+///
+/// Errors are returned through `TrustfallError **` out-parameters, which may be null
+/// if the caller isn't interested in the error. Errors must be freed with
+/// [`trustfall_error_free`].
+#[derive(Debug)]
+pub struct TrustfallError {
+    message: CString,
+}
+
+impl TrustfallError {
+    pub(crate) fn new(message: impl Into<String>) -> Self {
+        let mut message = message.into().into_bytes();
+        message.retain(|b| *b != 0);
+        Self {
+            message: CString::new(message).expect("NUL bytes were removed"),
+        }
+    }
+}
+
+impl Display for TrustfallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message.to_string_lossy())
+    }
+}
+
+impl std::error::Error for TrustfallError {}
+
+/// The error with which an adapter callback reported failure.
+///
+/// Callback failures are raised with [`std::panic::resume_unwind`], which unwinds
+/// the engine without invoking the panic hook, and are caught at the API boundary.
+pub(crate) struct CallbackFailure(pub(crate) String);
+
+/// Turn the payload of a caught panic into an error.
+pub(crate) fn panic_error(payload: Box<dyn Any + Send>) -> TrustfallError {
+    let message = if let Some(failure) = payload.downcast_ref::<CallbackFailure>() {
+        failure.0.clone()
+    } else if let Some(message) = payload.downcast_ref::<&str>() {
+        format!("trustfall panicked: {message}")
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        format!("trustfall panicked: {message}")
+    } else {
+        "trustfall panicked".to_string()
+    };
+    TrustfallError::new(message)
+}
+
+/// Write the error to the out-parameter, if one was provided.
+///
+/// # Safety
+///
+/// `out` must be null, or valid for writes.
+pub(crate) unsafe fn report(out: *mut *mut TrustfallError, error: TrustfallError) {
+    if !out.is_null() {
+        *out = Box::into_raw(Box::new(error));
+    }
+}
+
+/// Run the body of an API function, reporting its errors and panics through `out`
+/// and returning `default` if it fails. Panics must not unwind into C.
+///
+/// Objects that were being modified when a panic occurred are not used again,
+/// except for query results, which then refuse to produce more results.
+///
+/// # Safety
+///
+/// `out` must be null, or valid for writes.
+pub(crate) unsafe fn guard<T>(
+    out: *mut *mut TrustfallError,
+    default: T,
+    body: impl FnOnce() -> Result<T, TrustfallError>,
+) -> T {
+    if !out.is_null() {
+        *out = std::ptr::null_mut();
+    }
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(error)) => {
+            report(out, error);
+            default
+        }
+        Err(payload) => {
+            report(out, panic_error(payload));
+            default
+        }
+    }
+}
+
+/// The error's message, as a NUL-terminated UTF-8 string.
+///
+/// The message remains valid until the error is freed.
+///
+/// # Safety
+///
+/// `error` must be a valid error that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn trustfall_error_message(error: *const TrustfallError) -> *const c_char {
+    (*error).message.as_ptr()
+}
+
+/// Free an error. Does nothing if `error` is null.
+///
+/// # Safety
+///
+/// `error` must be null, or a valid error that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn trustfall_error_free(error: *mut TrustfallError) {
+    if !error.is_null() {
+        drop(Box::from_raw(error));
+    }
+}
@@ -0,0 +1,215 @@
+use std::{
+    collections::BTreeMap,
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr, slice,
+    sync::Arc,
+};
+
+use trustfall_core::{interpreter::execution::interpret_ir, ir::FieldValue};
+
+use crate::{
+    adapter::TrustfallAdapter,
+    error::{guard, panic_error, TrustfallError},
+    schema::TrustfallSchema,
+    value::{OwnedStr, OwnedValue, TrustfallArgument, TrustfallStr, TrustfallValue},
+};
+
+type QueryResults = Box<dyn Iterator<Item = BTreeMap<Arc<str>, FieldValue>>>;
+
+/// The results of a query, produced lazily by [`trustfall_results_next`].
+pub struct TrustfallResults {
+    iter: QueryResults,
+
+    /// Set once producing a result fails, since the engine can't resume afterward.
+    failed: bool,
+}
+
+/// Execute a query with the given arguments.
+///
+/// Returns null and reports an error if the query is invalid for the schema, or if its
+/// arguments don't match the query's variables. The adapter's callbacks are not called
+/// until results are requested with [`trustfall_results_next`].
+/// The results must be freed with [`trustfall_results_free`].
+///
+/// # Safety
+///
+/// `schema` and `adapter` must be valid and not freed, `query` must be a valid string,
+/// `arguments` must point to `arguments_len` valid arguments or be null with
+/// `arguments_len` zero, and `error` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn trustfall_execute(
+    schema: *const TrustfallSchema,
+    adapter: *const TrustfallAdapter,
+    query: TrustfallStr,
+    arguments: *const TrustfallArgument,
+    arguments_len: usize,
+    error: *mut *mut TrustfallError,
+) -> *mut TrustfallResults {
+    guard(error, ptr::null_mut(), || {
+        let query = query.to_str().map_err(TrustfallError::new)?;
+        let arguments = if arguments_len == 0 {
+            &[]
+        } else {
+            slice::from_raw_parts(arguments, arguments_len)
+        };
+        let arguments = arguments
+            .iter()
+            .map(|argument| {
+                let name = argument.name.to_str()?;
+                let value = argument
+                    .value
+                    .to_field_value()
+                    .map_err(|e| format!("invalid value for argument {name:?}: {e}"))?;
+                Ok((Arc::from(name), value))
+            })
+            .collect::<Result<BTreeMap<_, _>, String>>()
+            .map_err(TrustfallError::new)?;
+
+        let query = trustfall_core::frontend::parse(&(*schema).inner, query)
+            .map_err(|e| TrustfallError::new(e.to_string()))?;
+        let adapter = Arc::new((*adapter).shim());
+        let iter = interpret_ir(adapter, query, Arc::new(arguments))
+            .map_err(|e| TrustfallError::new(e.to_string()))?;
+        Ok(Box::into_raw(Box::new(TrustfallResults {
+            iter,
+            failed: false,
+        })))
+    })
+}
+
+/// Produce the next result of the query.
+///
+/// Returns null once there are no more results, or if producing the result failed,
+/// in which case the error is reported and no more results will be produced.
+/// The result must be freed with [`trustfall_row_free`].
+///
+/// # Safety
+///
+/// `results` must be valid and not freed, and `error` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn trustfall_results_next(
+    results: *mut TrustfallResults,
+    error: *mut *mut TrustfallError,
+) -> *mut TrustfallRow {
+    let results = &mut *results;
+    guard(error, ptr::null_mut(), || {
+        if results.failed {
+            return Err(TrustfallError::new(
+                "the query already failed and produces no more results",
+            ));
+        }
+        match catch_unwind(AssertUnwindSafe(|| results.iter.next())) {
+            Ok(next) => Ok(next.map_or(ptr::null_mut(), |row| {
+                Box::into_raw(Box::new(TrustfallRow::new(row)))
+            })),
+            Err(payload) => {
+                results.failed = true;
+                Err(panic_error(payload))
+            }
+        }
+    })
+}
+
+/// Free the results of a query. Does nothing if `results` is null.
+///
+/// # Safety
+///
+/// `results` must be null, or valid results that have not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn trustfall_results_free(results: *mut TrustfallResults) {
+    if !results.is_null() {
+        drop(Box::from_raw(results));
+    }
+}
+
+/// One result of a query: its outputs' names and values, in order of name.
+pub struct TrustfallRow {
+    names: Vec<OwnedStr>,
+    values: Vec<OwnedValue>,
+}
+
+impl TrustfallRow {
+    fn new(row: BTreeMap<Arc<str>, FieldValue>) -> Self {
+        let (names, values) = row
+            .iter()
+            .map(|(name, value)| (OwnedStr::new(name), OwnedValue::new(value)))
+            .unzip();
+        Self { names, values }
+    }
+}
+
+/// The number of outputs in the result.
+///
+/// # Safety
+///
+/// `row` must be valid and not freed.
+#[no_mangle]
+pub unsafe extern "C" fn trustfall_row_len(row: *const TrustfallRow) -> usize {
+    let row = &*row;
+    row.names.len()
+}
+
+/// The name of the output at `index`, which must be less than [`trustfall_row_len`].
+///
+/// The name remains valid until the result is freed.
+///
+/// # Safety
+///
+/// `row` must be valid and not freed, and `index` must be in bounds.
+#[no_mangle]
+pub unsafe extern "C" fn trustfall_row_name(
+    row: *const TrustfallRow,
+    index: usize,
+) -> TrustfallStr {
+    let row = &*row;
+    row.names[index].view()
+}
+
+/// The value of the output at `index`, which must be less than [`trustfall_row_len`].
+///
+/// The value remains valid until the result is freed.
+///
+/// # Safety
+///
+/// `row` must be valid and not freed, and `index` must be in bounds.
+#[no_mangle]
+pub unsafe extern "C" fn trustfall_row_value(
+    row: *const TrustfallRow,
+    index: usize,
+) -> *const TrustfallValue {
+    let row = &*row;
+    row.values[index].view()
+}
+
+/// The value of the output with the given name, or null if the query has no such output.
+///
+/// The value remains valid until the result is freed.
+///
+/// # Safety
+///
+/// `row` must be valid and not freed, and `name` must be a valid string.
+#[no_mangle]
+pub unsafe extern "C" fn trustfall_row_get(
+    row: *const TrustfallRow,
+    name: TrustfallStr,
+) -> *const TrustfallValue {
+    let row = &*row;
+    let Ok(name) = name.to_str() else {
+        return ptr::null();
+    };
+    row.names
+        .binary_search_by(|probe| probe.as_str().cmp(name))
+        .map_or(ptr::null(), |index| row.values[index].view())
+}
+
+/// Free a result. Does nothing if `row` is null.
+///
+/// # Safety
+///
+/// `row` must be null, or a valid result that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn trustfall_row_free(row: *mut TrustfallRow) {
+    if !row.is_null() {
+        drop(Box::from_raw(row));
+    }
+}
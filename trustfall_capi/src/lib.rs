@@ -0,0 +1,35 @@
+//! C bindings for the trustfall query engine, declared in `include/trustfall.h`.
+//!
+//! Schemas, adapters, query results, and errors are opaque handles created and freed
+//! through these functions. Adapters are implemented by C callbacks that operate on
+//! opaque vertex pointers owned by the adapter, and values cross the boundary as
+//! [`TrustfallValue`], a tagged union that C can construct and inspect directly.
+//!
+//! Handles are not thread-safe: a schema may be shared across threads once parsed, but
+//! an adapter and the results of queries executed with it must stay on one thread.
+//! No function panics across the boundary: failures are reported as [`TrustfallError`]
+//! through out-parameters.
+
+mod adapter;
+mod error;
+mod execution;
+mod schema;
+mod value;
+
+pub use adapter::{
+    trustfall_adapter_free, trustfall_adapter_new, trustfall_call_fail, trustfall_call_push_vertex,
+    trustfall_call_set_value, CoercionCallback, NeighborsCallback, PropertyCallback,
+    ReleaseCallback, StartingVerticesCallback, TrustfallAdapter, TrustfallAdapterCallbacks,
+    TrustfallCall,
+};
+pub use error::{trustfall_error_free, trustfall_error_message, TrustfallError};
+pub use execution::{
+    trustfall_execute, trustfall_results_free, trustfall_results_next, trustfall_row_free,
+    trustfall_row_get, trustfall_row_len, trustfall_row_name, trustfall_row_value,
+    TrustfallResults, TrustfallRow,
+};
+pub use schema::{trustfall_schema_free, trustfall_schema_parse, TrustfallSchema};
+pub use value::{
+    TrustfallArgument, TrustfallList, TrustfallStr, TrustfallValue, TrustfallValueData,
+    TrustfallValueKind,
+};
@@ -0,0 +1,48 @@
+use std::ptr;
+
+use trustfall_core::schema::Schema;
+
+use crate::{
+    error::{guard, TrustfallError},
+    value::TrustfallStr,
+};
+
+/// A parsed schema.
+#[derive(Debug)]
+pub struct TrustfallSchema {
+    pub(crate) inner: Schema,
+}
+
+/// Parse a schema from its GraphQL SDL definition.
+///
+/// Returns null and reports an error if the schema is invalid.
+/// The schema must be freed with [`trustfall_schema_free`].
+///
+/// # Safety
+///
+/// `input` must be a valid string, and `error` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn trustfall_schema_parse(
+    input: TrustfallStr,
+    error: *mut *mut TrustfallError,
+) -> *mut TrustfallSchema {
+    guard(error, ptr::null_mut(), || {
+        let input = input.to_str().map_err(TrustfallError::new)?;
+        let inner = Schema::parse(input).map_err(|e| TrustfallError::new(e.to_string()))?;
+        Ok(Box::into_raw(Box::new(TrustfallSchema { inner })))
+    })
+}
+
+/// Free a schema. Does nothing if `schema` is null.
+///
+/// Queries that are already executing are unaffected.
+///
+/// # Safety
+///
+/// `schema` must be null, or a valid schema that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn trustfall_schema_free(schema: *mut TrustfallSchema) {
+    if !schema.is_null() {
+        drop(Box::from_raw(schema));
+    }
+}
@@ -0,0 +1,326 @@
+use std::{ffi::c_char, ptr, slice};
+
+use chrono::{DateTime, Utc};
+use trustfall_core::ir::FieldValue;
+
+/// A UTF-8 string, passed by pointer and length.
+///
+/// Strings passed to C by trustfall are always followed by a NUL byte that is not counted
+/// in `len`, so `data` may be used as a C string if the string has no interior NUL bytes.
+/// Strings passed to trustfall by C don't need to be NUL-terminated.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TrustfallStr {
+    pub data: *const c_char,
+    pub len: usize,
+}
+
+impl TrustfallStr {
+    /// # Safety
+    ///
+    /// `data` must point to `len` readable bytes, or be null with `len` zero.
+    pub(crate) unsafe fn to_str<'a>(self) -> Result<&'a str, String> {
+        if self.data.is_null() {
+            return if self.len == 0 {
+                Ok("")
+            } else {
+                Err("null string with nonzero length".to_string())
+            };
+        }
+        let bytes = slice::from_raw_parts(self.data.cast::<u8>(), self.len);
+        std::str::from_utf8(bytes).map_err(|e| format!("string is not valid UTF-8: {e}"))
+    }
+}
+
+/// A NUL-terminated copy of a string, which can be passed to C as a [`TrustfallStr`].
+#[derive(Debug)]
+pub(crate) struct OwnedStr(Box<[u8]>);
+
+impl OwnedStr {
+    pub(crate) fn new(value: &str) -> Self {
+        let mut bytes = Vec::with_capacity(value.len() + 1);
+        bytes.extend_from_slice(value.as_bytes());
+        bytes.push(0);
+        Self(bytes.into_boxed_slice())
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.0[..self.0.len() - 1]).expect("was created from a str")
+    }
+
+    pub(crate) fn view(&self) -> TrustfallStr {
+        TrustfallStr {
+            data: self.0.as_ptr().cast(),
+            len: self.0.len() - 1,
+        }
+    }
+}
+
+/// The kind of a [`TrustfallValue`], determining which field of its `data` is set.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrustfallValueKind(pub u32);
+
+impl TrustfallValueKind {
+    pub const NULL: Self = Self(0);
+    pub const INT64: Self = Self(1);
+    pub const UINT64: Self = Self(2);
+    pub const FLOAT64: Self = Self(3);
+    pub const BOOLEAN: Self = Self(4);
+    pub const STRING: Self = Self(5);
+    /// An enum value, given by name in `data.string`.
+    pub const ENUM: Self = Self(6);
+    /// A timestamp in UTC, given as an RFC 3339 string in `data.string`.
+    pub const DATETIME_UTC: Self = Self(7);
+    pub const LIST: Self = Self(8);
+}
+
+/// A list of values, passed by pointer and length.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TrustfallList {
+    pub items: *const TrustfallValue,
+    pub len: usize,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union TrustfallValueData {
+    pub int64: i64,
+    pub uint64: u64,
+    pub float64: f64,
+    pub boolean: bool,
+    pub string: TrustfallStr,
+    pub list: TrustfallList,
+}
+
+/// A property value, query argument, or query output.
+///
+/// Values passed to C by trustfall are borrowed, and remain valid for as long as
+/// the object they were obtained from. Values passed to trustfall by C are copied
+/// before the call they are passed to returns.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct TrustfallValue {
+    pub kind: TrustfallValueKind,
+    pub data: TrustfallValueData,
+}
+
+impl TrustfallValue {
+    const NULL: Self = Self {
+        kind: TrustfallValueKind::NULL,
+        data: TrustfallValueData { uint64: 0 },
+    };
+
+    /// # Safety
+    ///
+    /// The value must be valid: its `data` must match its `kind`,
+    /// and any strings and lists it points to must be readable.
+    pub(crate) unsafe fn to_field_value(self) -> Result<FieldValue, String> {
+        let value = match self.kind {
+            TrustfallValueKind::NULL => FieldValue::Null,
+            TrustfallValueKind::INT64 => FieldValue::Int64(self.data.int64),
+            TrustfallValueKind::UINT64 => FieldValue::Uint64(self.data.uint64),
+            TrustfallValueKind::FLOAT64 => {
+                let value = self.data.float64;
+                if value.is_nan() {
+                    return Err("float values must not be NaN".to_string());
+                }
+                FieldValue::Float64(value)
+            }
+            TrustfallValueKind::BOOLEAN => FieldValue::Boolean(self.data.boolean),
+            TrustfallValueKind::STRING => {
+                FieldValue::String(self.data.string.to_str()?.to_string())
+            }
+            TrustfallValueKind::ENUM => FieldValue::Enum(self.data.string.to_str()?.to_string()),
+            TrustfallValueKind::DATETIME_UTC => {
+                let value = self.data.string.to_str()?;
+                let datetime = DateTime::parse_from_rfc3339(value)
+                    .map_err(|e| format!("invalid RFC 3339 timestamp {value:?}: {e}"))?;
+                FieldValue::DateTimeUtc(datetime.with_timezone(&Utc))
+            }
+            TrustfallValueKind::LIST => {
+                let list = self.data.list;
+                let items = if list.len == 0 {
+                    &[]
+                } else if list.items.is_null() {
+                    return Err("null list with nonzero length".to_string());
+                } else {
+                    slice::from_raw_parts(list.items, list.len)
+                };
+                FieldValue::List(
+                    items
+                        .iter()
+                        .map(|item| item.to_field_value())
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            TrustfallValueKind(kind) => return Err(format!("unknown value kind {kind}")),
+        };
+        Ok(value)
+    }
+}
+
+/// A [`TrustfallValue`] together with the strings and lists it points to.
+pub(crate) struct OwnedValue {
+    view: TrustfallValue,
+    _storage: Storage,
+}
+
+/// The heap allocations a value's view points into, which are only kept alive.
+#[allow(dead_code)]
+enum Storage {
+    None,
+    String(OwnedStr),
+    List(Vec<OwnedValue>, Box<[TrustfallValue]>),
+}
+
+impl OwnedValue {
+    pub(crate) fn new(value: &FieldValue) -> Self {
+        let (kind, data) = match value {
+            FieldValue::Null => return Self::plain(TrustfallValue::NULL),
+            FieldValue::Int64(value) => (
+                TrustfallValueKind::INT64,
+                TrustfallValueData { int64: *value },
+            ),
+            FieldValue::Uint64(value) => (
+                TrustfallValueKind::UINT64,
+                TrustfallValueData { uint64: *value },
+            ),
+            FieldValue::Float64(value) => (
+                TrustfallValueKind::FLOAT64,
+                TrustfallValueData { float64: *value },
+            ),
+            FieldValue::Boolean(value) => (
+                TrustfallValueKind::BOOLEAN,
+                TrustfallValueData { boolean: *value },
+            ),
+            FieldValue::String(value) => return Self::string(TrustfallValueKind::STRING, value),
+            FieldValue::Enum(value) => return Self::string(TrustfallValueKind::ENUM, value),
+            FieldValue::DateTimeUtc(value) => {
+                return Self::string(TrustfallValueKind::DATETIME_UTC, &value.to_rfc3339())
+            }
+            FieldValue::List(values) => {
+                let owned: Vec<_> = values.iter().map(OwnedValue::new).collect();
+                let views: Box<[_]> = owned.iter().map(|value| value.view).collect();
+                let list = TrustfallList {
+                    items: if views.is_empty() {
+                        ptr::null()
+                    } else {
+                        views.as_ptr()
+                    },
+                    len: views.len(),
+                };
+                return Self {
+                    view: TrustfallValue {
+                        kind: TrustfallValueKind::LIST,
+                        data: TrustfallValueData { list },
+                    },
+                    _storage: Storage::List(owned, views),
+                };
+            }
+        };
+        Self::plain(TrustfallValue { kind, data })
+    }
+
+    fn plain(view: TrustfallValue) -> Self {
+        Self {
+            view,
+            _storage: Storage::None,
+        }
+    }
+
+    fn string(kind: TrustfallValueKind, value: &str) -> Self {
+        let string = OwnedStr::new(value);
+        Self {
+            view: TrustfallValue {
+                kind,
+                data: TrustfallValueData {
+                    string: string.view(),
+                },
+            },
+            _storage: Storage::String(string),
+        }
+    }
+
+    pub(crate) fn view(&self) -> &TrustfallValue {
+        &self.view
+    }
+}
+
+/// A named value: a query argument, or an edge parameter.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct TrustfallArgument {
+    pub name: TrustfallStr,
+    pub value: TrustfallValue,
+}
+
+/// Edge parameters, in a form that can be passed to C.
+pub(crate) struct OwnedArguments {
+    _storage: Vec<(OwnedStr, OwnedValue)>,
+    views: Vec<TrustfallArgument>,
+}
+
+impl OwnedArguments {
+    pub(crate) fn new<'a>(arguments: impl Iterator<Item = (&'a str, &'a FieldValue)>) -> Self {
+        let storage: Vec<_> = arguments
+            .map(|(name, value)| (OwnedStr::new(name), OwnedValue::new(value)))
+            .collect();
+        let views = storage
+            .iter()
+            .map(|(name, value)| TrustfallArgument {
+                name: name.view(),
+                value: *value.view(),
+            })
+            .collect();
+        Self {
+            _storage: storage,
+            views,
+        }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *const TrustfallArgument {
+        if self.views.is_empty() {
+            ptr::null()
+        } else {
+            self.views.as_ptr()
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.views.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use trustfall_core::ir::FieldValue;
+
+    use super::OwnedValue;
+
+    #[test]
+    fn values_round_trip() {
+        let values = [
+            FieldValue::Null,
+            FieldValue::Int64(-3),
+            FieldValue::Uint64(u64::MAX),
+            FieldValue::Float64(2.5),
+            FieldValue::Boolean(true),
+            FieldValue::String("text with a \0 byte".to_string()),
+            FieldValue::Enum("VARIANT".to_string()),
+            FieldValue::DateTimeUtc(Utc.with_ymd_and_hms(2022, 3, 4, 5, 6, 7).unwrap()),
+            FieldValue::List(vec![]),
+            FieldValue::List(vec![
+                FieldValue::List(vec![FieldValue::Int64(1), FieldValue::Null]),
+                FieldValue::String("nested".to_string()),
+            ]),
+        ];
+        for value in values {
+            let owned = OwnedValue::new(&value);
+            let converted = unsafe { owned.view().to_field_value() };
+            assert_eq!(Ok(value), converted);
+        }
+    }
+}
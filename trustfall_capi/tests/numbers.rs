@@ -0,0 +1,304 @@
+//! Executes queries through the C API, with an adapter implemented by `extern "C"` callbacks.
+use std::{
+    cell::Cell,
+    collections::BTreeMap,
+    ffi::{c_void, CStr},
+    ptr, slice,
+};
+
+use trustfall_capi::*;
+
+const SCHEMA: &str = include_str!("../../trustfall_core/test_data/schemas/numbers.graphql");
+
+fn to_str(value: &str) -> TrustfallStr {
+    TrustfallStr {
+        data: value.as_ptr().cast(),
+        len: value.len(),
+    }
+}
+
+unsafe fn from_str<'a>(value: TrustfallStr) -> &'a str {
+    std::str::from_utf8(slice::from_raw_parts(value.data.cast(), value.len)).unwrap()
+}
+
+#[derive(Default)]
+struct State {
+    live_vertices: Cell<i64>,
+    released_user_data: Cell<bool>,
+    fail_on_property: Option<i64>,
+}
+
+unsafe fn state<'a>(user_data: *mut c_void) -> &'a State {
+    &*user_data.cast::<State>()
+}
+
+unsafe fn push_number(user_data: *mut c_void, call: *mut TrustfallCall, value: i64) {
+    let state = state(user_data);
+    state.live_vertices.set(state.live_vertices.get() + 1);
+    trustfall_call_push_vertex(call, Box::into_raw(Box::new(value)).cast());
+}
+
+unsafe extern "C" fn starting_vertices(
+    user_data: *mut c_void,
+    edge_name: TrustfallStr,
+    parameters: *const TrustfallArgument,
+    parameters_len: usize,
+    call: *mut TrustfallCall,
+) {
+    assert_eq!("Number", from_str(edge_name));
+    let parameters = slice::from_raw_parts(parameters, parameters_len);
+    let mut min = 0;
+    let mut max = 0;
+    for parameter in parameters {
+        assert_eq!(TrustfallValueKind::INT64, parameter.value.kind);
+        match from_str(parameter.name) {
+            "min" => min = parameter.value.data.int64,
+            "max" => max = parameter.value.data.int64,
+            other => panic!("unexpected parameter {other}"),
+        }
+    }
+    for value in min..=max {
+        push_number(user_data, call, value);
+    }
+}
+
+unsafe extern "C" fn property(
+    user_data: *mut c_void,
+    vertex: *mut c_void,
+    _type_name: TrustfallStr,
+    property_name: TrustfallStr,
+    call: *mut TrustfallCall,
+) {
+    let number = *vertex.cast::<i64>();
+    if state(user_data).fail_on_property == Some(number) {
+        trustfall_call_fail(call, to_str("no value for this number"));
+        return;
+    }
+
+    assert_eq!("value", from_str(property_name));
+    let value = TrustfallValue {
+        kind: TrustfallValueKind::INT64,
+        data: TrustfallValueData { int64: number },
+    };
+    assert!(trustfall_call_set_value(call, &value));
+}
+
+unsafe extern "C" fn neighbors(
+    user_data: *mut c_void,
+    vertex: *mut c_void,
+    _type_name: TrustfallStr,
+    edge_name: TrustfallStr,
+    _parameters: *const TrustfallArgument,
+    _parameters_len: usize,
+    call: *mut TrustfallCall,
+) {
+    assert_eq!("successor", from_str(edge_name));
+    push_number(user_data, call, *vertex.cast::<i64>() + 1);
+}
+
+unsafe extern "C" fn coercion(
+    _user_data: *mut c_void,
+    vertex: *mut c_void,
+    _type_name: TrustfallStr,
+    coerce_to_type: TrustfallStr,
+    _call: *mut TrustfallCall,
+) -> bool {
+    let number = *vertex.cast::<i64>();
+    let is_prime = number > 1 && (2..number).all(|d| number % d != 0);
+    match from_str(coerce_to_type) {
+        "Prime" => is_prime,
+        "Composite" => number > 1 && !is_prime,
+        other => panic!("unexpected coercion to {other}"),
+    }
+}
+
+unsafe extern "C" fn release_vertex(user_data: *mut c_void, vertex: *mut c_void) {
+    let state = state(user_data);
+    state.live_vertices.set(state.live_vertices.get() - 1);
+    drop(Box::from_raw(vertex.cast::<i64>()));
+}
+
+unsafe extern "C" fn release_user_data(user_data: *mut c_void, pointer: *mut c_void) {
+    assert!(pointer.is_null());
+    state(user_data).released_user_data.set(true);
+}
+
+const CALLBACKS: TrustfallAdapterCallbacks = TrustfallAdapterCallbacks {
+    starting_vertices: Some(starting_vertices),
+    property: Some(property),
+    neighbors: Some(neighbors),
+    coercion: Some(coercion),
+    release_vertex: Some(release_vertex),
+    release_user_data: Some(release_user_data),
+};
+
+unsafe fn take_error(error: *mut TrustfallError) -> String {
+    assert!(!error.is_null());
+    let message = CStr::from_ptr(trustfall_error_message(error))
+        .to_str()
+        .unwrap()
+        .to_string();
+    trustfall_error_free(error);
+    message
+}
+
+unsafe fn row_to_map(row: *mut TrustfallRow) -> BTreeMap<String, i64> {
+    let outputs = (0..trustfall_row_len(row))
+        .map(|index| {
+            let value = &*trustfall_row_value(row, index);
+            assert_eq!(TrustfallValueKind::INT64, value.kind);
+            (
+                from_str(trustfall_row_name(row, index)).to_string(),
+                value.data.int64,
+            )
+        })
+        .collect();
+    trustfall_row_free(row);
+    outputs
+}
+
+/// Execute the query, returning its results or the error it failed with.
+unsafe fn run(
+    state: &State,
+    query: &str,
+    arguments: &[TrustfallArgument],
+) -> Result<Vec<BTreeMap<String, i64>>, String> {
+    let mut error = ptr::null_mut();
+    let schema = trustfall_schema_parse(to_str(SCHEMA), &mut error);
+    assert!(!schema.is_null(), "{}", take_error(error));
+    let user_data = (state as *const State).cast_mut().cast();
+    let adapter = trustfall_adapter_new(&CALLBACKS, user_data, &mut error);
+    assert!(!adapter.is_null(), "{}", take_error(error));
+
+    let results = trustfall_execute(
+        schema,
+        adapter,
+        to_str(query),
+        arguments.as_ptr(),
+        arguments.len(),
+        &mut error,
+    );
+    // Neither is needed by queries that are already executing.
+    trustfall_adapter_free(adapter);
+    trustfall_schema_free(schema);
+    if results.is_null() {
+        return Err(take_error(error));
+    }
+
+    let mut rows = vec![];
+    loop {
+        let row = trustfall_results_next(results, &mut error);
+        if row.is_null() {
+            break;
+        }
+        rows.push(row_to_map(row));
+    }
+    assert!(!state.released_user_data.get());
+    trustfall_results_free(results);
+    assert!(state.released_user_data.get());
+    assert_eq!(
+        0,
+        state.live_vertices.get(),
+        "not all vertices were released"
+    );
+
+    if error.is_null() {
+        Ok(rows)
+    } else {
+        Err(take_error(error))
+    }
+}
+
+const QUERY: &str = r#"
+{
+    Number(max: 10) {
+        ... on Prime {
+            value @output @filter(op: ">=", value: ["$min"])
+
+            successor {
+                next: value @output
+            }
+        }
+    }
+}"#;
+
+fn expected(pairs: &[(i64, i64)]) -> Vec<BTreeMap<String, i64>> {
+    pairs
+        .iter()
+        .map(|(value, next)| {
+            BTreeMap::from([("value".to_string(), *value), ("next".to_string(), *next)])
+        })
+        .collect()
+}
+
+fn min_argument(min: i64) -> TrustfallArgument {
+    TrustfallArgument {
+        name: to_str("min"),
+        value: TrustfallValue {
+            kind: TrustfallValueKind::INT64,
+            data: TrustfallValueData { int64: min },
+        },
+    }
+}
+
+#[test]
+fn executes_query() {
+    let state = State::default();
+    let results = unsafe { run(&state, QUERY, &[min_argument(3)]) };
+    assert_eq!(Ok(expected(&[(3, 4), (5, 6), (7, 8)])), results);
+}
+
+#[test]
+fn reports_callback_failures() {
+    let state = State {
+        fail_on_property: Some(5),
+        ..Default::default()
+    };
+    let results = unsafe { run(&state, QUERY, &[min_argument(3)]) };
+    let error = results.unwrap_err();
+    assert!(
+        error.contains("the property callback failed: no value for this number"),
+        "{error}"
+    );
+}
+
+#[test]
+fn reports_invalid_queries() {
+    let state = State::default();
+    let error = unsafe { run(&state, "{ Nonexistent { value @output } }", &[]) }.unwrap_err();
+    assert!(error.contains("Nonexistent"), "{error}");
+}
+
+#[test]
+fn reports_invalid_arguments() {
+    let state = State::default();
+    let invalid = TrustfallArgument {
+        name: to_str("min"),
+        value: TrustfallValue {
+            kind: TrustfallValueKind(1000),
+            data: TrustfallValueData { int64: 0 },
+        },
+    };
+    let error = unsafe { run(&state, QUERY, &[invalid]) }.unwrap_err();
+    assert_eq!(
+        r#"invalid value for argument "min": unknown value kind 1000"#,
+        error
+    );
+
+    let error = unsafe { run(&state, QUERY, &[]) }.unwrap_err();
+    assert!(error.contains("min"), "{error}");
+}
+
+#[test]
+fn requires_callbacks() {
+    let callbacks = TrustfallAdapterCallbacks {
+        coercion: None,
+        ..CALLBACKS
+    };
+    unsafe {
+        let mut error = ptr::null_mut();
+        let adapter = trustfall_adapter_new(&callbacks, ptr::null_mut(), &mut error);
+        assert!(adapter.is_null());
+        assert_eq!("the coercion callback is required", take_error(error));
+    }
+}
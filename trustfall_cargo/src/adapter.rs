@@ -0,0 +1,327 @@
+use std::{env, io, path::Path, process::Command, sync::Arc};
+
+use trustfall_core::{
+    accessor_property,
+    interpreter::{
+        basic_adapter::BasicAdapter,
+        helpers::{resolve_coercion_with, resolve_neighbors_with, resolve_property_with},
+        ContextIterator, ContextOutcomeIterator, Typename, VertexIterator,
+    },
+    ir::{EdgeParameters, FieldValue},
+    schema::Schema,
+};
+
+use crate::{
+    metadata::{version_numbers, Graph, Metadata},
+    vertex::{Vertex, Workspace},
+    SCHEMA,
+};
+
+/// Errors from reading the metadata of a workspace.
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum CargoError {
+    #[error("Failed to run cargo")]
+    Run(#[from] io::Error),
+
+    #[error("`cargo metadata` failed: {0}")]
+    Failed(String),
+
+    #[error("The metadata is not valid")]
+    InvalidMetadata(#[from] serde_json::Error),
+}
+
+/// An adapter querying the packages of a Cargo workspace and its dependency graph,
+/// from the output of `cargo metadata`.
+#[derive(Debug, Clone)]
+pub struct CargoAdapter {
+    graph: Arc<Graph>,
+    schema: Schema,
+}
+
+impl CargoAdapter {
+    /// Query the output of `cargo metadata --format-version 1`.
+    ///
+    /// Metadata from `cargo metadata --no-deps` has no resolved dependency graph,
+    /// so its packages have no resolved dependencies or dependents.
+    pub fn from_json(json: &str) -> Result<Self, CargoError> {
+        let metadata: Metadata = serde_json::from_str(json)?;
+        Ok(Self {
+            graph: Arc::new(Graph::new(metadata)),
+            schema: Schema::parse(SCHEMA).expect("schema is not valid"),
+        })
+    }
+
+    /// Run `cargo metadata` for the given `Cargo.toml`, or the one in the given directory,
+    /// and query its output.
+    ///
+    /// This runs the cargo that's running the build, if any, or otherwise the `cargo` in `PATH`.
+    /// Like cargo, it may need to update the registry index, and to write `Cargo.lock`.
+    pub fn from_manifest(manifest_path: impl AsRef<Path>) -> Result<Self, CargoError> {
+        let manifest_path = manifest_path.as_ref();
+        let manifest_path = if manifest_path.is_dir() {
+            manifest_path.join("Cargo.toml")
+        } else {
+            manifest_path.to_path_buf()
+        };
+
+        let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+        let output = Command::new(cargo)
+            .args(["metadata", "--format-version", "1", "--manifest-path"])
+            .arg(&manifest_path)
+            .output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(CargoError::Failed(stderr.trim().to_string()));
+        }
+        Self::from_json(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// The schema of the packages and dependencies this adapter queries.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+}
+
+impl<'a> BasicAdapter<'a> for CargoAdapter {
+    type Vertex = Vertex;
+
+    fn resolve_starting_vertices(
+        &self,
+        edge_name: &str,
+        parameters: &EdgeParameters,
+    ) -> VertexIterator<'a, Self::Vertex> {
+        let graph = &self.graph;
+        let packages = graph.metadata.packages.iter().enumerate();
+        let vertices: Vec<_> = match edge_name {
+            "Workspace" => vec![Vertex::Workspace(Workspace::new(graph))],
+            "Packages" => packages
+                .map(|(index, _)| Vertex::package(graph, index))
+                .collect(),
+            "Package" => {
+                let name = parameters["name"].as_str().expect("name is not a string");
+                packages
+                    .filter(|(_, package)| package.name == name)
+                    .map(|(index, _)| Vertex::package(graph, index))
+                    .collect()
+            }
+            _ => unreachable!("unexpected starting edge: {edge_name}"),
+        };
+        Box::new(vertices.into_iter())
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'a, Self::Vertex>,
+        type_name: &str,
+        property_name: &str,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, FieldValue> {
+        match (type_name, property_name) {
+            // properties on Workspace
+            ("Workspace", "root") => {
+                resolve_property_with(contexts, accessor_property!(as_workspace, root))
+            }
+            ("Workspace", "targetDirectory") => {
+                resolve_property_with(contexts, accessor_property!(as_workspace, target_directory))
+            }
+
+            // properties on Package
+            ("Package", "id") => {
+                resolve_property_with(contexts, accessor_property!(as_package, id))
+            }
+            ("Package", "name") => {
+                resolve_property_with(contexts, accessor_property!(as_package, name))
+            }
+            ("Package", "version") => {
+                resolve_property_with(contexts, accessor_property!(as_package, version))
+            }
+            ("Package", "major" | "minor" | "patch") => {
+                let position = match property_name {
+                    "major" => 0,
+                    "minor" => 1,
+                    _ => 2,
+                };
+                resolve_property_with(contexts, move |vertex| {
+                    let version = vertex.as_package().unwrap().version();
+                    version_numbers(version)[position].into()
+                })
+            }
+            ("Package", "license") => resolve_property_with(contexts, |vertex| {
+                vertex
+                    .as_package()
+                    .unwrap()
+                    .data()
+                    .license
+                    .as_deref()
+                    .into()
+            }),
+            ("Package", "licenseFile") => resolve_property_with(contexts, |vertex| {
+                let package = vertex.as_package().unwrap();
+                package.data().license_file.as_deref().into()
+            }),
+            ("Package", "description") => resolve_property_with(contexts, |vertex| {
+                let package = vertex.as_package().unwrap();
+                package.data().description.as_deref().into()
+            }),
+            ("Package", "repository") => resolve_property_with(contexts, |vertex| {
+                let package = vertex.as_package().unwrap();
+                package.data().repository.as_deref().into()
+            }),
+            ("Package", "edition") => resolve_property_with(contexts, |vertex| {
+                vertex.as_package().unwrap().data().edition.as_str().into()
+            }),
+            ("Package", "source") => resolve_property_with(contexts, |vertex| {
+                vertex.as_package().unwrap().data().source.as_deref().into()
+            }),
+            ("Package", "manifestPath") => resolve_property_with(contexts, |vertex| {
+                let package = vertex.as_package().unwrap();
+                package.data().manifest_path.as_str().into()
+            }),
+            ("Package", "workspaceMember") => resolve_property_with(
+                contexts,
+                accessor_property!(as_package, is_workspace_member),
+            ),
+            ("Package", "enabledFeatures") => {
+                resolve_property_with(contexts, accessor_property!(as_package, enabled_features))
+            }
+
+            // properties on Dependency
+            ("Dependency", "name") => {
+                resolve_property_with(contexts, accessor_property!(as_dependency, name))
+            }
+            ("Dependency", "rename") => resolve_property_with(contexts, |vertex| {
+                let dependency = vertex.as_dependency().unwrap();
+                dependency.data().rename.as_deref().into()
+            }),
+            ("Dependency", "requirement") => resolve_property_with(contexts, |vertex| {
+                vertex.as_dependency().unwrap().data().req.as_str().into()
+            }),
+            ("Dependency", "kind") => {
+                resolve_property_with(contexts, accessor_property!(as_dependency, kind))
+            }
+            ("Dependency", "target") => resolve_property_with(contexts, |vertex| {
+                let dependency = vertex.as_dependency().unwrap();
+                dependency.data().target.as_deref().into()
+            }),
+            ("Dependency", "optional") => resolve_property_with(contexts, |vertex| {
+                vertex.as_dependency().unwrap().data().optional.into()
+            }),
+            ("Dependency", "usesDefaultFeatures") => resolve_property_with(contexts, |vertex| {
+                let dependency = vertex.as_dependency().unwrap();
+                dependency.data().uses_default_features.into()
+            }),
+            ("Dependency", "features") => resolve_property_with(contexts, |vertex| {
+                let dependency = vertex.as_dependency().unwrap();
+                dependency.data().features.as_slice().into()
+            }),
+            ("Dependency", "source") => resolve_property_with(contexts, |vertex| {
+                let dependency = vertex.as_dependency().unwrap();
+                dependency.data().source.as_deref().into()
+            }),
+
+            // properties on Feature
+            ("Feature", "name") => {
+                resolve_property_with(contexts, accessor_property!(as_feature, name))
+            }
+            ("Feature", "enables") => {
+                resolve_property_with(contexts, accessor_property!(as_feature, enables))
+            }
+
+            // properties on Target
+            ("Target", "name") => {
+                resolve_property_with(contexts, accessor_property!(as_target, name))
+            }
+            ("Target", "kinds") => resolve_property_with(contexts, |vertex| {
+                vertex.as_target().unwrap().data().kind.as_slice().into()
+            }),
+            ("Target", "crateTypes") => resolve_property_with(contexts, |vertex| {
+                vertex
+                    .as_target()
+                    .unwrap()
+                    .data()
+                    .crate_types
+                    .as_slice()
+                    .into()
+            }),
+            ("Target", "sourcePath") => resolve_property_with(contexts, |vertex| {
+                vertex.as_target().unwrap().data().src_path.as_str().into()
+            }),
+            ("Target", "edition") => resolve_property_with(contexts, |vertex| {
+                vertex.as_target().unwrap().data().edition.as_str().into()
+            }),
+            _ => unreachable!("unexpected property {type_name}.{property_name}"),
+        }
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: ContextIterator<'a, Self::Vertex>,
+        type_name: &str,
+        edge_name: &str,
+        parameters: &EdgeParameters,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, VertexIterator<'a, Self::Vertex>> {
+        match (type_name, edge_name) {
+            ("Workspace", "members") => resolve_neighbors_with(contexts, |vertex| {
+                let members = vertex.as_workspace().unwrap().members();
+                Box::new(members.into_iter())
+            }),
+            ("Workspace", "rootPackage") => resolve_neighbors_with(contexts, |vertex| {
+                let package = vertex.as_workspace().unwrap().root_package();
+                Box::new(package.into_iter())
+            }),
+            ("Package", "dependencies") => resolve_neighbors_with(contexts, |vertex| {
+                let dependencies = vertex.as_package().unwrap().dependencies();
+                Box::new(dependencies.into_iter())
+            }),
+            ("Package", "resolvedDependencies") => {
+                let include_dev = parameters["includeDev"]
+                    .as_bool()
+                    .expect("includeDev is not a boolean");
+                resolve_neighbors_with(contexts, move |vertex| {
+                    let package = vertex.as_package().unwrap();
+                    Box::new(package.resolved_dependencies(include_dev).into_iter())
+                })
+            }
+            ("Package", "dependents") => resolve_neighbors_with(contexts, |vertex| {
+                let dependents = vertex.as_package().unwrap().dependents();
+                Box::new(dependents.into_iter())
+            }),
+            ("Package", "otherVersions") => resolve_neighbors_with(contexts, |vertex| {
+                let packages = vertex.as_package().unwrap().other_versions();
+                Box::new(packages.into_iter())
+            }),
+            ("Package", "features") => resolve_neighbors_with(contexts, |vertex| {
+                let features = vertex.as_package().unwrap().features();
+                Box::new(features.into_iter())
+            }),
+            ("Package", "targets") => resolve_neighbors_with(contexts, |vertex| {
+                let targets = vertex.as_package().unwrap().targets();
+                Box::new(targets.into_iter())
+            }),
+            ("Dependency", "dependent") => resolve_neighbors_with(contexts, |vertex| {
+                let package = vertex.as_dependency().unwrap().dependent().clone();
+                Box::new(std::iter::once(Vertex::Package(package)))
+            }),
+            ("Dependency", "package") => resolve_neighbors_with(contexts, |vertex| {
+                let package = vertex.as_dependency().unwrap().resolved();
+                Box::new(package.into_iter())
+            }),
+            ("Feature", "package") => resolve_neighbors_with(contexts, |vertex| {
+                let package = vertex.as_feature().unwrap().package().clone();
+                Box::new(std::iter::once(Vertex::Package(package)))
+            }),
+            _ => unreachable!("unexpected edge {type_name}.{edge_name}"),
+        }
+    }
+
+    fn resolve_coercion(
+        &self,
+        contexts: ContextIterator<'a, Self::Vertex>,
+        _type_name: &str,
+        coerce_to_type: &str,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, bool> {
+        // None of the types have subtypes, so vertices only coerce to their own type.
+        let coerce_to_type = coerce_to_type.to_string();
+        resolve_coercion_with(contexts, move |vertex| vertex.typename() == coerce_to_type)
+    }
+}
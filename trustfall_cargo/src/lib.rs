@@ -0,0 +1,67 @@
+//! Query the packages and dependencies of Cargo workspaces with trustfall.
+//!
+//! [`CargoAdapter`] queries the output of `cargo metadata` with the schema in [`SCHEMA`]:
+//! the workspace's members and every package they depend on, with their licenses,
+//! versions, features, and targets, and both the dependencies declared in their manifests
+//! and the dependency graph cargo resolved them to.
+//!
+//! This makes policies about a workspace's dependencies into queries. For example,
+//! which GPL-licensed packages the workspace's members depend on, directly or not,
+//! and which packages are in the graph at more than one incompatible version:
+//!
+//! ```
+//! use std::{collections::BTreeMap, sync::Arc};
+//!
+//! use trustfall_cargo::CargoAdapter;
+//! use trustfall_core::{frontend::parse, interpreter::execution::interpret_ir, ir::FieldValue};
+//!
+//! let metadata = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/metadata.json"));
+//! let adapter = Arc::new(CargoAdapter::from_json(metadata).unwrap());
+//!
+//! let query = parse(adapter.schema(), r#"
+//! {
+//!     Workspace {
+//!         members {
+//!             member: name @output
+//!
+//!             resolvedDependencies @recurse(depth: 10) {
+//!                 name @output
+//!                 license @filter(op: "has_substring", value: ["$gpl"]) @output
+//!             }
+//!         }
+//!     }
+//! }"#).unwrap();
+//! let arguments = Arc::new(BTreeMap::from([("gpl".into(), "GPL".into())]));
+//! let rows: Vec<_> = interpret_ir(adapter.clone(), query, arguments).unwrap().collect();
+//! let members: Vec<_> = rows.iter().map(|row| row["member"].clone()).collect();
+//! let names: Vec<_> = rows.iter().map(|row| row["name"].clone()).collect();
+//! // `@recurse` includes the members themselves.
+//! assert_eq!(vec![FieldValue::from("app"), "util".into()], members);
+//! assert_eq!(vec![FieldValue::from("util"), "util".into()], names);
+//!
+//! let query = parse(adapter.schema(), r#"
+//! {
+//!     Packages {
+//!         name @output
+//!         version @output
+//!         major @tag
+//!
+//!         otherVersions {
+//!             major @filter(op: "!=", value: ["%major"])
+//!             other: version @output
+//!         }
+//!     }
+//! }"#).unwrap();
+//! let rows: Vec<_> = interpret_ir(adapter, query, Arc::new(BTreeMap::new())).unwrap().collect();
+//! let versions: Vec<_> = rows.iter().map(|row| row["version"].clone()).collect();
+//! assert_eq!(vec![FieldValue::from("0.4.8"), "1.0.6".into()], versions);
+//! ```
+mod adapter;
+mod metadata;
+mod vertex;
+
+pub use adapter::{CargoAdapter, CargoError};
+pub use vertex::{Dependency, Feature, Package, Target, Vertex, Workspace};
+
+/// The schema of the packages and dependencies queried by [`CargoAdapter`].
+pub const SCHEMA: &str = include_str!("cargo.graphql");
@@ -0,0 +1,190 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Deserialize;
+
+/// The output of `cargo metadata --format-version 1`, with the fields the schema uses.
+#[derive(Debug, Deserialize)]
+pub(crate) struct Metadata {
+    pub(crate) packages: Vec<PackageData>,
+    pub(crate) workspace_members: Vec<String>,
+    pub(crate) workspace_root: String,
+    pub(crate) target_directory: String,
+    /// `None` if cargo was run with `--no-deps`.
+    pub(crate) resolve: Option<Resolve>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct PackageData {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) version: String,
+    pub(crate) license: Option<String>,
+    pub(crate) license_file: Option<String>,
+    pub(crate) description: Option<String>,
+    pub(crate) repository: Option<String>,
+    pub(crate) edition: String,
+    pub(crate) source: Option<String>,
+    pub(crate) manifest_path: String,
+    pub(crate) dependencies: Vec<DependencyData>,
+    pub(crate) targets: Vec<TargetData>,
+    pub(crate) features: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct DependencyData {
+    pub(crate) name: String,
+    pub(crate) source: Option<String>,
+    pub(crate) req: String,
+    /// `None` for normal dependencies, otherwise `"dev"` or `"build"`.
+    pub(crate) kind: Option<String>,
+    pub(crate) rename: Option<String>,
+    pub(crate) optional: bool,
+    pub(crate) uses_default_features: bool,
+    pub(crate) features: Vec<String>,
+    pub(crate) target: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct TargetData {
+    pub(crate) name: String,
+    pub(crate) kind: Vec<String>,
+    pub(crate) crate_types: Vec<String>,
+    pub(crate) src_path: String,
+    pub(crate) edition: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Resolve {
+    pub(crate) nodes: Vec<Node>,
+    /// `None` if cargo was run on a virtual manifest.
+    pub(crate) root: Option<String>,
+}
+
+/// A package in the resolved dependency graph.
+#[derive(Debug, Deserialize)]
+pub(crate) struct Node {
+    pub(crate) id: String,
+    pub(crate) deps: Vec<NodeDep>,
+    pub(crate) features: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct NodeDep {
+    pub(crate) pkg: String,
+    /// Missing from the output of versions of cargo before 1.41.
+    #[serde(default)]
+    pub(crate) dep_kinds: Vec<DepKind>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub(crate) struct DepKind {
+    pub(crate) kind: Option<String>,
+    pub(crate) target: Option<String>,
+}
+
+/// The metadata, indexed for following its edges.
+#[derive(Debug)]
+pub(crate) struct Graph {
+    pub(crate) metadata: Metadata,
+    /// The index of each package, by ID.
+    packages: HashMap<String, usize>,
+    /// The index of each package's node in the resolved graph, by package index.
+    nodes: HashMap<usize, usize>,
+    /// The indexes of the packages that depend on each package, by package index.
+    dependents: HashMap<usize, Vec<usize>>,
+}
+
+impl Graph {
+    pub(crate) fn new(metadata: Metadata) -> Self {
+        let packages: HashMap<_, _> = metadata
+            .packages
+            .iter()
+            .enumerate()
+            .map(|(index, package)| (package.id.clone(), index))
+            .collect();
+
+        let mut nodes = HashMap::new();
+        let mut dependents: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (node_index, node) in metadata
+            .resolve
+            .iter()
+            .flat_map(|r| r.nodes.iter().enumerate())
+        {
+            let Some(&package) = packages.get(&node.id) else {
+                continue;
+            };
+            nodes.insert(package, node_index);
+            for dep in &node.deps {
+                if let Some(&dependency) = packages.get(&dep.pkg) {
+                    dependents.entry(dependency).or_default().push(package);
+                }
+            }
+        }
+        for dependents in dependents.values_mut() {
+            dependents.sort_unstable();
+            dependents.dedup();
+        }
+
+        Self {
+            metadata,
+            packages,
+            nodes,
+            dependents,
+        }
+    }
+
+    pub(crate) fn package(&self, index: usize) -> &PackageData {
+        &self.metadata.packages[index]
+    }
+
+    pub(crate) fn package_index(&self, id: &str) -> Option<usize> {
+        self.packages.get(id).copied()
+    }
+
+    /// The package's node in the resolved graph, unless the graph wasn't resolved.
+    pub(crate) fn node(&self, package: usize) -> Option<&Node> {
+        let resolve = self.metadata.resolve.as_ref()?;
+        self.nodes.get(&package).map(|index| &resolve.nodes[*index])
+    }
+
+    pub(crate) fn dependents(&self, package: usize) -> &[usize] {
+        self.dependents
+            .get(&package)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// The package a dependency of the given package resolved to, if any. Optional
+    /// dependencies whose features aren't enabled don't resolve to a package.
+    pub(crate) fn resolved_dependency(
+        &self,
+        package: usize,
+        dependency: &DependencyData,
+    ) -> Option<usize> {
+        let kind = DepKind {
+            kind: dependency.kind.clone(),
+            target: dependency.target.clone(),
+        };
+        self.node(package)?.deps.iter().find_map(|dep| {
+            let index = self.package_index(&dep.pkg)?;
+            let resolved = self.package(index);
+            // Git sources of packages end with the commit they're at, like `#a1b2c3d`.
+            let source = |source: &Option<String>| {
+                source
+                    .as_deref()
+                    .map(|source| source.split('#').next().unwrap_or_default().to_string())
+            };
+            let matches = resolved.name == dependency.name
+                && source(&resolved.source) == source(&dependency.source)
+                && (dep.dep_kinds.is_empty() || dep.dep_kinds.contains(&kind));
+            matches.then_some(index)
+        })
+    }
+}
+
+/// The major, minor, and patch numbers of a version like `1.0.163` or `0.5.0-beta.1`.
+pub(crate) fn version_numbers(version: &str) -> [u64; 3] {
+    let release = version.split(['-', '+']).next().unwrap_or_default();
+    let mut numbers = release.split('.').map(|number| number.parse().unwrap_or(0));
+    [(); 3].map(|_| numbers.next().unwrap_or(0))
+}
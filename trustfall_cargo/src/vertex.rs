@@ -0,0 +1,305 @@
+use std::sync::Arc;
+
+use trustfall_core::interpreter::Typename;
+
+use crate::metadata::{DependencyData, Graph, PackageData, TargetData};
+
+/// A vertex of the Cargo schema.
+#[derive(Debug, Clone)]
+pub enum Vertex {
+    Workspace(Workspace),
+    Package(Package),
+    Dependency(Dependency),
+    Feature(Feature),
+    Target(Target),
+}
+
+impl Vertex {
+    pub(crate) fn package(graph: &Arc<Graph>, index: usize) -> Self {
+        Self::Package(Package {
+            graph: graph.clone(),
+            index,
+        })
+    }
+
+    pub fn as_workspace(&self) -> Option<&Workspace> {
+        match self {
+            Self::Workspace(workspace) => Some(workspace),
+            _ => None,
+        }
+    }
+
+    pub fn as_package(&self) -> Option<&Package> {
+        match self {
+            Self::Package(package) => Some(package),
+            _ => None,
+        }
+    }
+
+    pub fn as_dependency(&self) -> Option<&Dependency> {
+        match self {
+            Self::Dependency(dependency) => Some(dependency),
+            _ => None,
+        }
+    }
+
+    pub fn as_feature(&self) -> Option<&Feature> {
+        match self {
+            Self::Feature(feature) => Some(feature),
+            _ => None,
+        }
+    }
+
+    pub fn as_target(&self) -> Option<&Target> {
+        match self {
+            Self::Target(target) => Some(target),
+            _ => None,
+        }
+    }
+}
+
+impl Typename for Vertex {
+    fn typename(&self) -> &'static str {
+        match self {
+            Self::Workspace(_) => "Workspace",
+            Self::Package(_) => "Package",
+            Self::Dependency(_) => "Dependency",
+            Self::Feature(_) => "Feature",
+            Self::Target(_) => "Target",
+        }
+    }
+}
+
+/// The workspace the metadata is for.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    graph: Arc<Graph>,
+}
+
+impl Workspace {
+    pub(crate) fn new(graph: &Arc<Graph>) -> Self {
+        Self {
+            graph: graph.clone(),
+        }
+    }
+
+    pub fn root(&self) -> &str {
+        &self.graph.metadata.workspace_root
+    }
+
+    pub fn target_directory(&self) -> &str {
+        &self.graph.metadata.target_directory
+    }
+
+    pub(crate) fn members(&self) -> Vec<Vertex> {
+        self.graph
+            .metadata
+            .workspace_members
+            .iter()
+            .filter_map(|id| self.graph.package_index(id))
+            .map(|index| Vertex::package(&self.graph, index))
+            .collect()
+    }
+
+    /// The package cargo was run on, unless it was run on a virtual manifest.
+    pub(crate) fn root_package(&self) -> Option<Vertex> {
+        let root = self.graph.metadata.resolve.as_ref()?.root.as_ref()?;
+        let index = self.graph.package_index(root)?;
+        Some(Vertex::package(&self.graph, index))
+    }
+}
+
+/// A package in the metadata: a workspace member, or one of their dependencies.
+#[derive(Debug, Clone)]
+pub struct Package {
+    graph: Arc<Graph>,
+    index: usize,
+}
+
+impl Package {
+    pub fn id(&self) -> &str {
+        &self.data().id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.data().name
+    }
+
+    pub fn version(&self) -> &str {
+        &self.data().version
+    }
+
+    pub(crate) fn data(&self) -> &PackageData {
+        self.graph.package(self.index)
+    }
+
+    pub(crate) fn is_workspace_member(&self) -> bool {
+        self.graph
+            .metadata
+            .workspace_members
+            .contains(&self.data().id)
+    }
+
+    /// The features enabled in the resolved graph, unless the graph wasn't resolved.
+    pub(crate) fn enabled_features(&self) -> Option<Vec<String>> {
+        self.graph
+            .node(self.index)
+            .map(|node| node.features.clone())
+    }
+
+    pub(crate) fn dependencies(&self) -> Vec<Vertex> {
+        (0..self.data().dependencies.len())
+            .map(|index| {
+                Vertex::Dependency(Dependency {
+                    package: self.clone(),
+                    index,
+                })
+            })
+            .collect()
+    }
+
+    /// The packages this package depends on in the resolved graph, in order of package ID.
+    /// Dev-dependencies are only included if `include_dev` is set.
+    pub(crate) fn resolved_dependencies(&self, include_dev: bool) -> Vec<Vertex> {
+        let Some(node) = self.graph.node(self.index) else {
+            return vec![];
+        };
+        let mut dependencies: Vec<_> = node
+            .deps
+            .iter()
+            .filter(|dep| {
+                include_dev
+                    || dep.dep_kinds.is_empty()
+                    || dep
+                        .dep_kinds
+                        .iter()
+                        .any(|kind| kind.kind.as_deref() != Some("dev"))
+            })
+            .filter_map(|dep| self.graph.package_index(&dep.pkg))
+            .collect();
+        dependencies.sort_unstable();
+        dependencies
+            .into_iter()
+            .map(|index| Vertex::package(&self.graph, index))
+            .collect()
+    }
+
+    pub(crate) fn dependents(&self) -> Vec<Vertex> {
+        self.graph
+            .dependents(self.index)
+            .iter()
+            .map(|index| Vertex::package(&self.graph, *index))
+            .collect()
+    }
+
+    pub(crate) fn other_versions(&self) -> Vec<Vertex> {
+        let name = &self.data().name;
+        self.graph
+            .metadata
+            .packages
+            .iter()
+            .enumerate()
+            .filter(|(index, package)| *index != self.index && &package.name == name)
+            .map(|(index, _)| Vertex::package(&self.graph, index))
+            .collect()
+    }
+
+    /// The package's features, in order of name.
+    pub(crate) fn features(&self) -> Vec<Vertex> {
+        self.data()
+            .features
+            .keys()
+            .map(|name| {
+                Vertex::Feature(Feature {
+                    package: self.clone(),
+                    name: name.clone(),
+                })
+            })
+            .collect()
+    }
+
+    pub(crate) fn targets(&self) -> Vec<Vertex> {
+        (0..self.data().targets.len())
+            .map(|index| {
+                Vertex::Target(Target {
+                    package: self.clone(),
+                    index,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A dependency declared in a package's manifest.
+#[derive(Debug, Clone)]
+pub struct Dependency {
+    package: Package,
+    index: usize,
+}
+
+impl Dependency {
+    /// The name of the package depended on, even if the manifest renames it.
+    pub fn name(&self) -> &str {
+        &self.data().name
+    }
+
+    /// The package with the dependency.
+    pub fn dependent(&self) -> &Package {
+        &self.package
+    }
+
+    pub(crate) fn data(&self) -> &DependencyData {
+        &self.package.data().dependencies[self.index]
+    }
+
+    pub(crate) fn kind(&self) -> &str {
+        self.data().kind.as_deref().unwrap_or("normal")
+    }
+
+    pub(crate) fn resolved(&self) -> Option<Vertex> {
+        let graph = &self.package.graph;
+        graph
+            .resolved_dependency(self.package.index, self.data())
+            .map(|index| Vertex::package(graph, index))
+    }
+}
+
+/// A feature of a package.
+#[derive(Debug, Clone)]
+pub struct Feature {
+    package: Package,
+    name: String,
+}
+
+impl Feature {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The package with the feature.
+    pub fn package(&self) -> &Package {
+        &self.package
+    }
+
+    /// The features and optional dependencies the feature enables.
+    pub fn enables(&self) -> &[String] {
+        &self.package.data().features[&self.name]
+    }
+}
+
+/// A target of a package, such as its library, a binary, or a test.
+#[derive(Debug, Clone)]
+pub struct Target {
+    package: Package,
+    index: usize,
+}
+
+impl Target {
+    pub fn name(&self) -> &str {
+        &self.data().name
+    }
+
+    pub(crate) fn data(&self) -> &TargetData {
+        &self.package.data().targets[self.index]
+    }
+}
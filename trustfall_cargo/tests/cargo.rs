@@ -0,0 +1,318 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use trustfall_cargo::{CargoAdapter, CargoError};
+use trustfall_core::ir::FieldValue;
+use trustfall_testing::{column, execute, strings};
+
+const METADATA: &str = include_str!("fixtures/metadata.json");
+
+#[test]
+fn reads_packages_in_order_of_id() {
+    let query = r#"
+{
+    Packages {
+        name @output
+        version @output
+        major @output
+        minor @output
+        patch @output
+        license @output
+        source @output
+        workspaceMember @output
+        enabledFeatures @output
+    }
+}"#;
+    let adapter = Arc::new(CargoAdapter::from_json(METADATA).unwrap());
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    // Path packages' IDs start with "path+", after those of registry packages.
+    assert_eq!(
+        strings(&[
+            "app",
+            "cc",
+            "itoa",
+            "itoa",
+            "ryu",
+            "serde",
+            "serde_json",
+            "util"
+        ]),
+        column(&rows, "name"),
+    );
+    assert_eq!(
+        strings(&["0.1.0", "1.0.79", "0.4.8", "1.0.6", "1.0.13", "1.0.163", "1.0.96", "0.1.0"]),
+        column(&rows, "version"),
+    );
+    let serde = &rows[5];
+    assert_eq!(FieldValue::Uint64(1), serde["major"]);
+    assert_eq!(FieldValue::Uint64(0), serde["minor"]);
+    assert_eq!(FieldValue::Uint64(163), serde["patch"]);
+    assert_eq!(FieldValue::from("MIT OR Apache-2.0"), serde["license"]);
+    assert_eq!(
+        FieldValue::from("registry+https://github.com/rust-lang/crates.io-index"),
+        serde["source"]
+    );
+    assert_eq!(
+        FieldValue::List(strings(&["std"])),
+        serde["enabledFeatures"]
+    );
+
+    let util = &rows[7];
+    assert_eq!(FieldValue::from("GPL-3.0-only"), util["license"]);
+    assert_eq!(FieldValue::Null, util["source"]);
+    assert_eq!(
+        vec![true, false, false, false, false, false, false, true]
+            .into_iter()
+            .map(FieldValue::from)
+            .collect::<Vec<_>>(),
+        column(&rows, "workspaceMember"),
+    );
+}
+
+#[test]
+fn resolves_declared_dependencies() {
+    let query = r#"
+{
+    Package(name: "app") {
+        dependencies {
+            name @output
+            requirement @output
+            kind @output
+            optional @output
+            usesDefaultFeatures @output
+            features @output
+
+            package @optional {
+                resolved: version @output
+            }
+        }
+    }
+}"#;
+    let adapter = Arc::new(CargoAdapter::from_json(METADATA).unwrap());
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(
+        strings(&["itoa", "serde", "util", "serde_json", "cc"]),
+        column(&rows, "name"),
+    );
+    assert_eq!(
+        strings(&["^1", "^1.0.130", "*", "^1", "^1"]),
+        column(&rows, "requirement"),
+    );
+    assert_eq!(
+        strings(&["normal", "normal", "normal", "dev", "build"]),
+        column(&rows, "kind"),
+    );
+    assert_eq!(
+        vec![false, true, false, false, false]
+            .into_iter()
+            .map(FieldValue::from)
+            .collect::<Vec<_>>(),
+        column(&rows, "optional"),
+    );
+    assert_eq!(FieldValue::Boolean(false), rows[4]["usesDefaultFeatures"]);
+    assert_eq!(FieldValue::List(strings(&["fast"])), rows[2]["features"]);
+    // The optional dependency on serde isn't enabled, so it isn't resolved.
+    assert_eq!(
+        vec![
+            FieldValue::from("1.0.6"),
+            FieldValue::Null,
+            "0.1.0".into(),
+            "1.0.96".into(),
+            "1.0.79".into(),
+        ],
+        column(&rows, "resolved"),
+    );
+
+    let query = r#"
+{
+    Package(name: "util") {
+        dependencies {
+            name @output
+            target @output
+
+            package {
+                resolved: version @output
+            }
+            dependent {
+                dependent: name @output
+            }
+        }
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    // The platform-specific dependency resolves to the older version of itoa.
+    assert_eq!(1, rows.len(), "{rows:?}");
+    assert_eq!(FieldValue::from("itoa"), rows[0]["name"]);
+    assert_eq!(FieldValue::from("cfg(unix)"), rows[0]["target"]);
+    assert_eq!(FieldValue::from("0.4.8"), rows[0]["resolved"]);
+    assert_eq!(FieldValue::from("util"), rows[0]["dependent"]);
+}
+
+#[test]
+fn follows_the_resolved_dependency_graph() {
+    let query = r#"
+{
+    Package(name: "app") {
+        resolvedDependencies @fold {
+            name @output
+        }
+        resolvedDependencies(includeDev: true) @fold {
+            with_dev: name @output
+        }
+    }
+}"#;
+    let adapter = Arc::new(CargoAdapter::from_json(METADATA).unwrap());
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(
+        vec![FieldValue::List(strings(&["cc", "itoa", "util"]))],
+        column(&rows, "name")
+    );
+    assert_eq!(
+        vec![FieldValue::List(strings(&[
+            "cc",
+            "itoa",
+            "serde_json",
+            "util"
+        ]))],
+        column(&rows, "with_dev")
+    );
+
+    let query = r#"
+{
+    Package(name: "itoa") {
+        version @output
+
+        dependents @fold {
+            dependent: name @output
+        }
+        otherVersions {
+            other: version @output
+        }
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(strings(&["0.4.8", "1.0.6"]), column(&rows, "version"));
+    assert_eq!(strings(&["1.0.6", "0.4.8"]), column(&rows, "other"));
+    assert_eq!(
+        vec![
+            FieldValue::List(strings(&["util"])),
+            FieldValue::List(strings(&["app", "serde_json"])),
+        ],
+        column(&rows, "dependent"),
+    );
+}
+
+#[test]
+fn reads_workspace_features_and_targets() {
+    let query = r#"
+{
+    Workspace {
+        root @output
+        targetDirectory @output
+
+        members {
+            member: name @output
+            manifestPath @output
+        }
+        rootPackage @optional {
+            root_package: name @output
+        }
+    }
+}"#;
+    let adapter = Arc::new(CargoAdapter::from_json(METADATA).unwrap());
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(strings(&["app", "util"]), column(&rows, "member"));
+    assert_eq!(
+        strings(&[
+            "/home/user/project/app/Cargo.toml",
+            "/home/user/project/util/Cargo.toml"
+        ]),
+        column(&rows, "manifestPath"),
+    );
+    assert_eq!(
+        vec![FieldValue::from("/home/user/project/target"); 2],
+        column(&rows, "targetDirectory")
+    );
+    // The workspace's manifest is virtual.
+    assert_eq!(vec![FieldValue::Null; 2], column(&rows, "root_package"));
+
+    let query = r#"
+{
+    Package(name: "app") {
+        features {
+            feature: name @output
+            enables @output
+        }
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(
+        strings(&["default", "serialize", "std"]),
+        column(&rows, "feature")
+    );
+    assert_eq!(
+        vec![
+            FieldValue::List(strings(&["std"])),
+            FieldValue::List(strings(&["dep:serde", "util/serde"])),
+            FieldValue::List(vec![]),
+        ],
+        column(&rows, "enables"),
+    );
+
+    let query = r#"
+{
+    Package(name: "serde") {
+        targets {
+            name @output
+            kinds @output
+            sourcePath @output
+        }
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(
+        strings(&["serde", "build-script-build"]),
+        column(&rows, "name")
+    );
+    assert_eq!(
+        vec![
+            FieldValue::List(strings(&["lib"])),
+            FieldValue::List(strings(&["custom-build"])),
+        ],
+        column(&rows, "kinds"),
+    );
+}
+
+#[test]
+fn queries_metadata_without_a_resolved_graph() {
+    let mut metadata: serde_json::Value = serde_json::from_str(METADATA).unwrap();
+    metadata["resolve"] = serde_json::Value::Null;
+    let adapter = Arc::new(CargoAdapter::from_json(&metadata.to_string()).unwrap());
+
+    let query = r#"
+{
+    Package(name: "app") {
+        enabledFeatures @output
+
+        dependencies @fold @transform(op: "count") @output(name: "declared")
+        resolvedDependencies @fold @transform(op: "count") @output(name: "resolved")
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(1, rows.len(), "{rows:?}");
+    assert_eq!(FieldValue::Null, rows[0]["enabledFeatures"]);
+    assert_eq!(FieldValue::Uint64(5), rows[0]["declared"]);
+    assert_eq!(FieldValue::Uint64(0), rows[0]["resolved"]);
+}
+
+#[test]
+fn reports_invalid_metadata() {
+    let error = CargoAdapter::from_json("{}").err().unwrap();
+    assert!(matches!(error, CargoError::InvalidMetadata(_)), "{error}");
+
+    let missing = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/missing/Cargo.toml"
+    );
+    let error = CargoAdapter::from_manifest(missing).err().unwrap();
+    assert!(matches!(error, CargoError::Failed(_)), "{error}");
+}
@@ -0,0 +1,180 @@
+use std::{collections::BTreeMap, fs, str::FromStr, sync::Arc};
+
+use anyhow::{bail, Context};
+use trustfall_cargo::CargoAdapter;
+use trustfall_config::{document, ConfigFile};
+use trustfall_core::{
+    frontend::parse,
+    interpreter::{execution::interpret_ir, Adapter},
+    ir::{FieldValue, IndexedQuery},
+    schema::{json::SchemaJson, Schema},
+};
+use trustfall_csv::CsvAdapter;
+use trustfall_filesystem::FilesystemAdapter;
+use trustfall_json::JsonAdapter;
+use trustfall_os::OsAdapter;
+
+pub(crate) type Row = BTreeMap<Arc<str>, FieldValue>;
+
+/// One of the built-in adapters, with the data source it queries.
+///
+/// Adapters are given on the command line as `<name>` or `<name>:<source>`,
+/// for example `filesystem:./src`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum AdapterSpec {
+    /// The packages and dependencies of the Cargo workspace or package
+    /// with the given manifest, or the manifest in the given directory.
+    Cargo(String),
+
+    /// The YAML, TOML, and JSON configuration files under the given directory,
+    /// with a schema inferred from their contents.
+    Config(String),
+
+    /// The rows of the given CSV or TSV file, with column types inferred from its first rows.
+    Csv(String),
+
+    /// Files and directories under the given directory.
+    Filesystem(String),
+
+    /// The given JSON file, with a schema inferred from its contents.
+    Json(String),
+
+    /// The processes, sockets, and mounted filesystems of this machine.
+    Os,
+}
+
+impl FromStr for AdapterSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, source) = match s.split_once(':') {
+            Some((name, source)) => (name, Some(source)),
+            None => (s, None),
+        };
+        match (name, source) {
+            ("cargo", source) => Ok(Self::Cargo(source.unwrap_or(".").to_string())),
+            ("config", source) => Ok(Self::Config(source.unwrap_or(".").to_string())),
+            ("csv", Some(path)) => Ok(Self::Csv(path.to_string())),
+            ("csv", None) => bail!("the csv adapter needs a file, as csv:<file>"),
+            ("filesystem", source) => Ok(Self::Filesystem(source.unwrap_or(".").to_string())),
+            ("json", Some(path)) => Ok(Self::Json(path.to_string())),
+            ("json", None) => bail!("the json adapter needs a file, as json:<file>"),
+            ("os", None) => Ok(Self::Os),
+            ("os", Some(_)) => bail!("the os adapter doesn't take a data source"),
+            (name, _) => {
+                bail!(
+                    "unknown adapter \"{name}\", expected one of: \
+                    cargo, config, csv, filesystem, json, os"
+                )
+            }
+        }
+    }
+}
+
+impl AdapterSpec {
+    /// The schema of the adapters whose schema doesn't depend on their data source.
+    fn static_schema_text(&self) -> Option<&'static str> {
+        match self {
+            Self::Cargo(_) => Some(trustfall_cargo::SCHEMA),
+            Self::Filesystem(_) => Some(trustfall_filesystem::SCHEMA),
+            Self::Os => Some(trustfall_os::SCHEMA),
+            Self::Config(_) | Self::Csv(_) | Self::Json(_) => None,
+        }
+    }
+
+    fn static_schema(&self) -> Schema {
+        let text = self
+            .static_schema_text()
+            .unwrap_or_else(|| panic!("{self:?} has no static schema"));
+        Schema::parse(text).expect("built-in schema is not valid")
+    }
+
+    /// The adapter's schema, for printing.
+    ///
+    /// Schemas inferred from the data source have no GraphQL text of their own,
+    /// so they're printed in the JSON schema format of [`SchemaJson`] instead.
+    pub(crate) fn schema_text(&self) -> anyhow::Result<String> {
+        if let Some(text) = self.static_schema_text() {
+            return Ok(text.to_string());
+        }
+        let schema = match self {
+            Self::Config(dir) => config_adapter(dir)?.schema().clone(),
+            Self::Csv(path) => csv_adapter(path)?.schema().clone(),
+            Self::Json(path) => json_adapter(path)?.schema().clone(),
+            _ => unreachable!("{self:?} has a static schema"),
+        };
+        let text = serde_json::to_string_pretty(&SchemaJson::from(&schema))
+            .expect("failed to serialize schema");
+        Ok(text + "\n")
+    }
+
+    /// Parse the query against the adapter's schema, then execute it over the adapter.
+    ///
+    /// The query is returned along with its results, since its outputs determine
+    /// the columns of tables and CSV files.
+    pub(crate) fn execute(
+        &self,
+        query: &str,
+        variables: BTreeMap<Arc<str>, FieldValue>,
+    ) -> anyhow::Result<(Arc<IndexedQuery>, Box<dyn Iterator<Item = Row>>)> {
+        match self {
+            Self::Cargo(manifest_path) => {
+                let adapter = CargoAdapter::from_manifest(manifest_path).with_context(|| {
+                    format!("failed to read the metadata of \"{manifest_path}\"")
+                })?;
+                run(adapter, &self.static_schema(), query, variables)
+            }
+            Self::Config(dir) => {
+                let adapter = config_adapter(dir)?;
+                let schema = adapter.schema().clone();
+                run(adapter, &schema, query, variables)
+            }
+            Self::Csv(path) => {
+                let adapter = csv_adapter(path)?;
+                let schema = adapter.schema().clone();
+                run(adapter, &schema, query, variables)
+            }
+            Self::Filesystem(root) => {
+                let adapter = FilesystemAdapter::new(root)
+                    .with_context(|| format!("\"{root}\" is not a directory"))?;
+                run(adapter, &self.static_schema(), query, variables)
+            }
+            Self::Json(path) => {
+                let adapter = json_adapter(path)?;
+                let schema = adapter.schema().clone();
+                run(adapter, &schema, query, variables)
+            }
+            Self::Os => run(OsAdapter::new(), &self.static_schema(), query, variables),
+        }
+    }
+}
+
+fn config_adapter(dir: &str) -> anyhow::Result<JsonAdapter> {
+    let files = ConfigFile::read_dir(dir)
+        .with_context(|| format!("failed to read the configuration files in \"{dir}\""))?;
+    JsonAdapter::infer(document(&files))
+        .with_context(|| format!("failed to infer a schema for the configuration in \"{dir}\""))
+}
+
+fn csv_adapter(path: &str) -> anyhow::Result<CsvAdapter> {
+    CsvAdapter::open(path).with_context(|| format!("failed to read \"{path}\""))
+}
+
+fn json_adapter(path: &str) -> anyhow::Result<JsonAdapter> {
+    let text = fs::read_to_string(path).with_context(|| format!("failed to read \"{path}\""))?;
+    let document = serde_json::from_str(&text)
+        .with_context(|| format!("\"{path}\" is not a valid JSON document"))?;
+    JsonAdapter::infer(document).with_context(|| format!("failed to infer a schema for \"{path}\""))
+}
+
+fn run<A: Adapter<'static> + 'static>(
+    adapter: A,
+    schema: &Schema,
+    query: &str,
+    variables: BTreeMap<Arc<str>, FieldValue>,
+) -> anyhow::Result<(Arc<IndexedQuery>, Box<dyn Iterator<Item = Row>>)> {
+    let query = parse(schema, query).context("invalid query")?;
+    let results = interpret_ir(Arc::new(adapter), query.clone(), Arc::new(variables))
+        .context("invalid query arguments")?;
+    Ok((query, results))
+}
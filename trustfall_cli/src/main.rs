@@ -0,0 +1,112 @@
+#![forbid(unsafe_code)]
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Read},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use trustfall_core::ir::{FieldValue, TransparentValue};
+
+mod adapter;
+mod output;
+
+use adapter::AdapterSpec;
+use output::Format;
+
+/// Run trustfall queries over the built-in adapters.
+#[derive(Debug, Parser)]
+#[clap(name = "trustfall", version)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Run a query and print its results.
+    Query {
+        /// The adapter to query, as `<name>` or `<name>:<source>`.
+        ///
+        /// The built-in adapters are `cargo:<manifest>`, which queries the packages and
+        /// dependencies of a Cargo workspace, `config:<directory>`, which queries the YAML,
+        /// TOML, and JSON configuration files in a directory, `csv:<file>`,
+        /// `filesystem:<directory>`, `json:<file>`, and `os`, which queries this
+        /// machine's processes, sockets, and mounted filesystems. `cargo`, `config`,
+        /// and `filesystem` default to the current directory.
+        #[clap(short, long, value_parser = clap::value_parser!(AdapterSpec))]
+        adapter: AdapterSpec,
+
+        /// The file containing the query, or `-` to read it from stdin.
+        #[clap(default_value = "-")]
+        query: PathBuf,
+
+        /// A query variable, as `<name>=<value>`.
+        ///
+        /// Values are parsed as JSON, falling back to strings for values that aren't
+        /// valid JSON: `--var max=5` is a number, while `--var name=five` and
+        /// `--var 'name="5"'` are strings.
+        #[clap(short, long = "var", value_parser = parse_variable)]
+        variables: Vec<(Arc<str>, FieldValue)>,
+
+        /// The format to print results in.
+        #[clap(short, long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+    },
+
+    /// Print the schema of an adapter.
+    Schema {
+        /// The adapter, as `<name>` or `<name>:<source>`.
+        #[clap(value_parser = clap::value_parser!(AdapterSpec))]
+        adapter: AdapterSpec,
+    },
+}
+
+fn parse_variable(arg: &str) -> Result<(Arc<str>, FieldValue), String> {
+    let (name, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected <name>=<value>, got \"{arg}\""))?;
+    if name.is_empty() {
+        return Err(format!("missing variable name in \"{arg}\""));
+    }
+    let value = match serde_json::from_str::<TransparentValue>(value) {
+        Ok(value) => value.into(),
+        Err(_) => FieldValue::String(value.to_string()),
+    };
+    Ok((name.into(), value))
+}
+
+fn read_query(path: &PathBuf) -> anyhow::Result<String> {
+    if path.as_os_str() == "-" {
+        let mut query = String::new();
+        io::stdin()
+            .read_to_string(&mut query)
+            .context("failed to read the query from stdin")?;
+        Ok(query)
+    } else {
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    match Cli::parse().command {
+        Command::Query {
+            adapter,
+            query,
+            variables,
+            format,
+        } => {
+            let query = read_query(&query)?;
+            let variables: BTreeMap<_, _> = variables.into_iter().collect();
+            let (query, results) = adapter.execute(&query, variables)?;
+            let columns = query.outputs.keys().cloned().collect();
+            output::write_results(format, columns, results, io::stdout().lock())?;
+        }
+        Command::Schema { adapter } => print!("{}", adapter.schema_text()?),
+    }
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use std::{io::Write, sync::Arc};
+
+use clap::ValueEnum;
+use serde_json::Value;
+use trustfall_core::{
+    ir::{FieldValue, TransparentValue},
+    output::{CsvWriter, JsonLinesWriter},
+};
+
+use crate::adapter::Row;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Format {
+    /// A table with a column per output, written once all results are available.
+    Table,
+
+    /// A JSON array with an object per result.
+    Json,
+
+    /// A JSON object per line, written as results become available.
+    Jsonl,
+
+    /// CSV with a column per output, written as results become available.
+    Csv,
+}
+
+/// Write the results in the given format, returning the number of results written.
+pub(crate) fn write_results(
+    format: Format,
+    columns: Vec<Arc<str>>,
+    results: impl Iterator<Item = Row>,
+    mut writer: impl Write,
+) -> anyhow::Result<usize> {
+    let count = match format {
+        Format::Table => {
+            let results: Vec<_> = results.collect();
+            write_table(&mut writer, &columns, &results)?;
+            results.len()
+        }
+        Format::Json => {
+            let results: Vec<Value> = results.map(to_json).collect();
+            serde_json::to_writer_pretty(&mut writer, &results)?;
+            writeln!(writer)?;
+            results.len()
+        }
+        Format::Jsonl => {
+            let mut json = JsonLinesWriter::new(&mut writer);
+            let count = json.write_all(results)?;
+            json.flush()?;
+            count
+        }
+        Format::Csv => {
+            let mut csv = CsvWriter::new(&mut writer, columns);
+            let count = csv.write_all(results)?;
+            csv.flush()?;
+            count
+        }
+    };
+    writer.flush()?;
+    Ok(count)
+}
+
+fn to_json(row: Row) -> Value {
+    let row = row
+        .into_iter()
+        .map(|(name, value)| {
+            let value = serde_json::to_value(TransparentValue::from(value))
+                .expect("failed to serialize value");
+            (name.to_string(), value)
+        })
+        .collect();
+    Value::Object(row)
+}
+
+/// Render a value for a table cell: strings as-is, and other values as JSON.
+fn cell(value: &FieldValue) -> String {
+    match value {
+        FieldValue::String(s) => s.clone(),
+        value => serde_json::to_string(&TransparentValue::from(value.clone()))
+            .expect("failed to serialize value"),
+    }
+}
+
+fn write_table(
+    writer: &mut impl Write,
+    columns: &[Arc<str>],
+    results: &[Row],
+) -> std::io::Result<()> {
+    let rows: Vec<Vec<String>> = results
+        .iter()
+        .map(|row| columns.iter().map(|column| cell(&row[column])).collect())
+        .collect();
+
+    let mut widths: Vec<usize> = columns
+        .iter()
+        .map(|column| column.chars().count())
+        .collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let write_row = |writer: &mut dyn Write, cells: &[&str]| -> std::io::Result<()> {
+        let line = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        writeln!(writer, "{}", line.trim_end())
+    };
+
+    let header: Vec<&str> = columns.iter().map(|column| column.as_ref()).collect();
+    write_row(writer, &header)?;
+    let separator: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+    writeln!(writer, "{}", separator.join("-+-"))?;
+    for row in &rows {
+        let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+        write_row(writer, &cells)?;
+    }
+    Ok(())
+}
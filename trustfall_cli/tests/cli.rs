@@ -0,0 +1,317 @@
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+fn trustfall(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_trustfall"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // The command may exit without reading its input, such as when its arguments are invalid.
+    let written = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    if let Err(e) = written {
+        assert_eq!(std::io::ErrorKind::BrokenPipe, e.kind(), "{e}");
+    }
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: Output) -> String {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+const CRATES_QUERY: &str = r#"
+{
+    Document {
+        crates {
+            name @output
+            downloads @output @filter(op: ">", value: ["$min"])
+        }
+    }
+}"#;
+
+#[test]
+fn prints_tables() {
+    let output = trustfall(
+        &[
+            "query",
+            "--adapter",
+            "json:tests/fixtures/crates.json",
+            "--var",
+            "min=1000",
+        ],
+        CRATES_QUERY,
+    );
+    assert_eq!(
+        "\
+downloads | name
+----------+----------
+300000000 | serde
+5000      | trustfall
+",
+        stdout(output),
+    );
+}
+
+#[test]
+fn prints_json_csv_and_json_lines() {
+    let args = |format| {
+        let adapter = "json:tests/fixtures/crates.json";
+        ["query", "-a", adapter, "-v", "min=1000", "-f", format]
+    };
+    assert_eq!(
+        "\
+[
+  {
+    \"downloads\": 300000000,
+    \"name\": \"serde\"
+  },
+  {
+    \"downloads\": 5000,
+    \"name\": \"trustfall\"
+  }
+]
+",
+        stdout(trustfall(&args("json"), CRATES_QUERY)),
+    );
+    assert_eq!(
+        "downloads,name\n300000000,serde\n5000,trustfall\n",
+        stdout(trustfall(&args("csv"), CRATES_QUERY)),
+    );
+    assert_eq!(
+        "{\"downloads\":300000000,\"name\":\"serde\"}\n{\"downloads\":5000,\"name\":\"trustfall\"}\n",
+        stdout(trustfall(&args("jsonl"), CRATES_QUERY)),
+    );
+}
+
+#[test]
+fn reads_queries_from_files_and_parses_string_variables() {
+    let query = r#"
+{
+    Root {
+        subdirectories {
+            directory: name @output
+
+            files {
+                name @output @filter(op: "has_suffix", value: ["$suffix"])
+                extension @output
+            }
+        }
+    }
+}"#;
+    let path = std::env::temp_dir().join("trustfall_cli_query.graphql");
+    std::fs::write(&path, query).unwrap();
+
+    let output = trustfall(
+        &[
+            "query",
+            "--adapter",
+            "filesystem:tests/fixtures",
+            "--var",
+            "suffix=.txt",
+            path.to_str().unwrap(),
+        ],
+        "",
+    );
+    assert_eq!(
+        "\
+directory | extension | name
+----------+-----------+---------
+notes     | txt       | todo.txt
+",
+        stdout(output),
+    );
+}
+
+#[test]
+fn queries_json_files() {
+    let query = r#"
+{
+    Document {
+        crates {
+            name @output
+            keywords @output
+            downloads @filter(op: "<", value: ["$max"])
+        }
+    }
+}"#;
+    let output = trustfall(
+        &[
+            "query",
+            "-a",
+            "json:tests/fixtures/crates.json",
+            "-v",
+            "max=10000",
+            "-f",
+            "jsonl",
+        ],
+        query,
+    );
+    assert_eq!(
+        "{\"keywords\":[\"query\"],\"name\":\"trustfall\"}\n",
+        stdout(output)
+    );
+}
+
+#[test]
+fn queries_csv_files() {
+    let query = r#"
+{
+    Row {
+        name @output
+        first_released @output
+        downloads @filter(op: ">", value: ["$min"])
+    }
+}"#;
+    let output = trustfall(
+        &[
+            "query",
+            "-a",
+            "csv:tests/fixtures/crates.csv",
+            "-v",
+            "min=10000",
+            "-f",
+            "csv",
+        ],
+        query,
+    );
+    assert_eq!("first_released,name\n2015-02-25,serde\n", stdout(output));
+}
+
+#[test]
+fn queries_configuration_files() {
+    let query = r#"
+{
+    Document {
+        path @output
+        format @output
+
+        contents {
+            name @output
+
+            logging {
+                level @filter(op: "=", value: ["$level"])
+            }
+        }
+    }
+}"#;
+    let output = trustfall(
+        &[
+            "query",
+            "-a",
+            "config:tests/fixtures/services",
+            "-v",
+            "level=info",
+            "-f",
+            "jsonl",
+        ],
+        query,
+    );
+    assert_eq!(
+        "{\"format\":\"toml\",\"name\":\"web\",\"path\":\"tests/fixtures/services/web.toml\"}\n",
+        stdout(output)
+    );
+}
+
+#[test]
+fn prints_schemas() {
+    let schema = stdout(trustfall(&["schema", "filesystem"], ""));
+    assert!(schema.contains("interface Entry"), "{schema}");
+
+    let schema = stdout(trustfall(&["schema", "os"], ""));
+    assert!(schema.contains("type Process"), "{schema}");
+
+    let schema = stdout(trustfall(&["schema", "cargo"], ""));
+    assert!(schema.contains("type Package"), "{schema}");
+
+    // Inferred schemas are printed in the JSON schema format.
+    let schema = stdout(trustfall(&["schema", "csv:tests/fixtures/crates.csv"], ""));
+    let schema: serde_json::Value = serde_json::from_str(&schema).unwrap();
+    let row_type = schema["types"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|vertex_type| vertex_type["name"] == "Row")
+        .unwrap();
+    assert_eq!("first_released", row_type["fields"][3]["name"]);
+}
+
+#[test]
+fn reports_errors() {
+    let crates = "json:tests/fixtures/crates.json";
+    let output = trustfall(
+        &["query", "--adapter", crates],
+        "{ Document { nope @output } }",
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("Error: invalid query"), "{stderr}");
+
+    let output = trustfall(&["query", "--adapter", crates], CRATES_QUERY);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.starts_with("Error: invalid query arguments"),
+        "{stderr}"
+    );
+
+    let output = trustfall(
+        &["query", "--adapter", crates, "--var", "min"],
+        CRATES_QUERY,
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("expected <name>=<value>"), "{stderr}");
+
+    let output = trustfall(
+        &["query", "--adapter", "filesystem:missing"],
+        "{ Root { name @output } }",
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("\"missing\" is not a directory"),
+        "{stderr}"
+    );
+
+    let output = trustfall(
+        &["query", "--adapter", "cargo:missing"],
+        "{ Workspace { root @output } }",
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("failed to read the metadata of \"missing\""),
+        "{stderr}"
+    );
+
+    for adapter in ["json", "csv"] {
+        let output = trustfall(&["query", "--adapter", adapter], "{ Row { name @output } }");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.contains(&format!("the {adapter} adapter needs a file")),
+            "{stderr}"
+        );
+    }
+
+    let output = trustfall(
+        &["query", "--adapter", "json:tests/fixtures/crates.csv"],
+        "{ Document { crates { name @output } } }",
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("\"tests/fixtures/crates.csv\" is not a valid JSON document"),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,120 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Value};
+
+use crate::format::{Format, ParseError};
+
+/// Errors from reading configuration files.
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Failed to read \"{}\"", .path.display())]
+    Read { path: PathBuf, source: io::Error },
+
+    #[error("\"{}\" is not a configuration file in a known format.", .0.display())]
+    UnknownFormat(PathBuf),
+
+    #[error("Failed to parse \"{}\"", .path.display())]
+    Parse { path: PathBuf, source: ParseError },
+}
+
+/// A document in a configuration file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigFile {
+    pub path: PathBuf,
+    pub format: Format,
+    /// The index of the document in the file, which is 0 unless the file
+    /// is YAML with several `---`-separated documents.
+    pub document: usize,
+    pub contents: Value,
+}
+
+impl ConfigFile {
+    /// Parse the documents in the text of a configuration file.
+    pub fn parse(
+        path: impl Into<PathBuf>,
+        format: Format,
+        text: &str,
+    ) -> Result<Vec<Self>, ConfigError> {
+        let path = path.into();
+        let documents = match format.parse(text) {
+            Ok(documents) => documents,
+            Err(source) => return Err(ConfigError::Parse { path, source }),
+        };
+        Ok(documents
+            .into_iter()
+            .enumerate()
+            .map(|(document, contents)| Self {
+                path: path.clone(),
+                format,
+                document,
+                contents,
+            })
+            .collect())
+    }
+
+    /// Read the documents in the configuration file at the path, whose format
+    /// is determined by its extension.
+    pub fn read(path: impl AsRef<Path>) -> Result<Vec<Self>, ConfigError> {
+        let path = path.as_ref();
+        let format =
+            Format::from_path(path).ok_or_else(|| ConfigError::UnknownFormat(path.into()))?;
+        let text = fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.into(),
+            source,
+        })?;
+        Self::parse(path, format, &text)
+    }
+
+    /// Read the documents in all the configuration files in the directory and its
+    /// subdirectories, in order of path. Files in unknown formats are skipped.
+    pub fn read_dir(dir: impl AsRef<Path>) -> Result<Vec<Self>, ConfigError> {
+        let mut paths = vec![];
+        find_files(dir.as_ref(), &mut paths)?;
+        paths.sort();
+
+        let mut files = vec![];
+        for path in paths {
+            files.extend(Self::read(path)?);
+        }
+        Ok(files)
+    }
+
+    /// The document as an object with its `path`, `format`, `document` index, and `contents`.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "path": self.path.to_string_lossy(),
+            "format": self.format.name(),
+            "document": self.document,
+            "contents": self.contents,
+        })
+    }
+}
+
+fn find_files(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<(), ConfigError> {
+    let read_error = |source| ConfigError::Read {
+        path: dir.into(),
+        source,
+    };
+    for entry in fs::read_dir(dir).map_err(read_error)? {
+        let entry = entry.map_err(read_error)?;
+        let path = entry.path();
+        if entry.file_type().map_err(read_error)?.is_dir() {
+            find_files(&path, paths)?;
+        } else if Format::from_path(&path).is_some() {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// A JSON document of the configuration files, for querying with [`JsonAdapter`]:
+/// an array of the objects from [`ConfigFile::to_json`].
+///
+/// [`JsonAdapter`]: trustfall_json::JsonAdapter
+pub fn document<'a>(files: impl IntoIterator<Item = &'a ConfigFile>) -> Value {
+    Value::Array(files.into_iter().map(ConfigFile::to_json).collect())
+}
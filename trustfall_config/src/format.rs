@@ -0,0 +1,118 @@
+use std::{fmt, path::Path};
+
+use serde_json::{Map, Number, Value};
+use yaml_rust::{Yaml, YamlLoader};
+
+/// The formats of configuration files.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+/// Errors from parsing a configuration file.
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum ParseError {
+    #[error("The JSON is not valid")]
+    Json(#[from] serde_json::Error),
+
+    #[error("The YAML is not valid")]
+    Yaml(#[from] yaml_rust::ScanError),
+
+    #[error("The TOML is not valid")]
+    Toml(#[from] basic_toml::Error),
+
+    #[error("The YAML has a value that can't be represented as JSON: {0}")]
+    UnsupportedYaml(String),
+}
+
+impl Format {
+    /// The format of the file at the path, according to its extension:
+    /// `.json`, `.yaml` or `.yml`, or `.toml`.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()? {
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+
+    /// The format's name, as queried in the `format` field of configuration files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+            Self::Toml => "toml",
+        }
+    }
+
+    /// Parse the documents in the text into JSON values.
+    ///
+    /// JSON and TOML files are one document, while YAML files are one document for each
+    /// of their `---`-separated sections, and empty YAML files are a single `null` one.
+    /// As with any [`Value`], objects' keys are in sorted order rather than that of the file.
+    pub fn parse(self, text: &str) -> Result<Vec<Value>, ParseError> {
+        match self {
+            Self::Json => Ok(vec![serde_json::from_str(text)?]),
+            Self::Toml => Ok(vec![basic_toml::from_str(text)?]),
+            Self::Yaml => {
+                let documents = YamlLoader::load_from_str(text)?;
+                if documents.is_empty() {
+                    return Ok(vec![Value::Null]);
+                }
+                documents.iter().map(yaml_to_json).collect()
+            }
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+fn yaml_to_json(yaml: &Yaml) -> Result<Value, ParseError> {
+    let value = match yaml {
+        // Empty documents, and values tagged with a type they don't match, like `!!int a`.
+        Yaml::Null | Yaml::BadValue => Value::Null,
+        Yaml::Boolean(b) => Value::Bool(*b),
+        Yaml::Integer(n) => Value::Number((*n).into()),
+        Yaml::Real(text) => yaml
+            .as_f64()
+            .and_then(Number::from_f64)
+            .map(Value::Number)
+            .ok_or_else(|| ParseError::UnsupportedYaml(format!("the number {text}")))?,
+        Yaml::String(s) => Value::String(s.clone()),
+        Yaml::Array(items) => {
+            Value::Array(items.iter().map(yaml_to_json).collect::<Result<_, _>>()?)
+        }
+        Yaml::Hash(fields) => {
+            let mut object = Map::new();
+            for (key, value) in fields {
+                object.insert(yaml_key(key)?, yaml_to_json(value)?);
+            }
+            Value::Object(object)
+        }
+        // The loader replaces aliases with the values of their anchors.
+        Yaml::Alias(_) => unreachable!("unresolved YAML alias: {yaml:?}"),
+    };
+    Ok(value)
+}
+
+/// The object key for the key of a YAML mapping, which can be any scalar.
+fn yaml_key(key: &Yaml) -> Result<String, ParseError> {
+    match key {
+        Yaml::String(s) | Yaml::Real(s) => Ok(s.clone()),
+        Yaml::Integer(n) => Ok(n.to_string()),
+        Yaml::Boolean(b) => Ok(b.to_string()),
+        Yaml::Null => Ok("null".to_string()),
+        _ => Err(ParseError::UnsupportedYaml(format!(
+            "the mapping key {key:?}"
+        ))),
+    }
+}
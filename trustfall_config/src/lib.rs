@@ -0,0 +1,50 @@
+//! Query YAML, TOML, and JSON configuration files with trustfall.
+//!
+//! [`ConfigFile`] reads configuration files into the same nested values that
+//! [`trustfall_json`] queries, and [`document`] puts them together into one JSON document
+//! with an object for each file: its `path`, its `format`, and its `contents`. Querying
+//! that document with [`JsonAdapter`] turns audits of configuration across many services
+//! into queries, whether with a schema inferred from the files' contents or with the
+//! generic schema in [`trustfall_json::SCHEMA`].
+//!
+//! For example, which services have debug logging enabled:
+//!
+//! ```
+//! use std::{collections::BTreeMap, sync::Arc};
+//!
+//! use trustfall_config::{document, ConfigFile};
+//! use trustfall_core::{frontend::parse, interpreter::execution::interpret_ir, ir::FieldValue};
+//! use trustfall_json::JsonAdapter;
+//!
+//! let services = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/services");
+//! let files = ConfigFile::read_dir(services).unwrap();
+//! let adapter = Arc::new(JsonAdapter::infer(document(&files)).unwrap());
+//!
+//! let query = parse(adapter.schema(), r#"
+//! {
+//!     Document {
+//!         format @output
+//!
+//!         contents {
+//!             name @output
+//!
+//!             logging {
+//!                 level @filter(op: "=", value: ["$level"])
+//!             }
+//!         }
+//!     }
+//! }"#).unwrap();
+//! let arguments = Arc::new(BTreeMap::from([("level".into(), "debug".into())]));
+//! let rows: Vec<_> = interpret_ir(adapter, query, arguments).unwrap().collect();
+//! let names: Vec<_> = rows.iter().map(|row| row["name"].clone()).collect();
+//! let formats: Vec<_> = rows.iter().map(|row| row["format"].clone()).collect();
+//! assert_eq!(vec![FieldValue::from("api"), "backup".into(), "web".into()], names);
+//! assert_eq!(vec![FieldValue::from("yaml"), "yaml".into(), "json".into()], formats);
+//! ```
+//!
+//! [`JsonAdapter`]: trustfall_json::JsonAdapter
+mod file;
+mod format;
+
+pub use file::{document, ConfigError, ConfigFile};
+pub use format::{Format, ParseError};
@@ -0,0 +1,221 @@
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+
+use serde_json::{json, Value};
+use trustfall_config::{document, ConfigError, ConfigFile, Format, ParseError};
+use trustfall_core::ir::FieldValue;
+use trustfall_json::JsonAdapter;
+use trustfall_testing::{column, execute, strings};
+
+const SERVICES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/services");
+
+#[test]
+fn parses_each_format_into_json() {
+    let yaml = r#"
+name: api
+ratio: 0.5
+enabled: yes
+1: one
+true: false
+~: nothing
+ports: &ports [80, 443]
+backup: *ports
+untagged: !!int abc
+"#;
+    assert_eq!(
+        vec![json!({
+            "name": "api",
+            "ratio": 0.5,
+            "enabled": "yes",
+            "1": "one",
+            "true": false,
+            "null": "nothing",
+            "ports": [80, 443],
+            "backup": [80, 443],
+            "untagged": null,
+        })],
+        Format::Yaml.parse(yaml).unwrap(),
+    );
+    assert_eq!(
+        vec![json!({"a": 1}), json!([2]), Value::Null],
+        Format::Yaml.parse("a: 1\n---\n- 2\n---\n").unwrap(),
+    );
+    assert_eq!(vec![Value::Null], Format::Yaml.parse("").unwrap());
+
+    let toml = r#"
+title = "config"
+
+[server]
+port = 8080
+hosts = ["a", "b"]
+
+[[server.routes]]
+path = "/"
+"#;
+    assert_eq!(
+        vec![json!({
+            "title": "config",
+            "server": {"port": 8080, "hosts": ["a", "b"], "routes": [{"path": "/"}]},
+        })],
+        Format::Toml.parse(toml).unwrap(),
+    );
+    assert_eq!(
+        vec![json!([1, {"a": null}])],
+        Format::Json.parse(r#"[1, {"a": null}]"#).unwrap(),
+    );
+}
+
+#[test]
+fn reports_values_json_cannot_represent() {
+    let error = Format::Yaml.parse("ratio: .inf").unwrap_err();
+    assert!(matches!(error, ParseError::UnsupportedYaml(_)), "{error}");
+
+    let error = Format::Yaml.parse("? [a, b]\n: c").unwrap_err();
+    assert!(matches!(error, ParseError::UnsupportedYaml(_)), "{error}");
+}
+
+#[test]
+fn reads_directories_in_order_of_path() {
+    let files = ConfigFile::read_dir(SERVICES).unwrap();
+    let paths: Vec<_> = files
+        .iter()
+        .map(|file| (file.path.strip_prefix(SERVICES).unwrap(), file.document))
+        .collect();
+    // The README isn't in a configuration format, so it's skipped.
+    assert_eq!(
+        vec![
+            (PathBuf::from("api.yaml").as_path(), 0),
+            (PathBuf::from("jobs/cleanup.yml").as_path(), 0),
+            (PathBuf::from("jobs/cleanup.yml").as_path(), 1),
+            (PathBuf::from("web.json").as_path(), 0),
+            (PathBuf::from("worker.toml").as_path(), 0),
+        ],
+        paths,
+    );
+    assert_eq!(
+        vec![
+            Format::Yaml,
+            Format::Yaml,
+            Format::Yaml,
+            Format::Json,
+            Format::Toml
+        ],
+        files.iter().map(|file| file.format).collect::<Vec<_>>(),
+    );
+
+    let document = document(&files[3..4]);
+    assert_eq!(
+        json!([{
+            "path": format!("{SERVICES}/web.json"),
+            "format": "json",
+            "document": 0,
+            "contents": {
+                "name": "web",
+                "replicas": 1,
+                "logging": {"level": "debug", "format": "text"},
+                "ports": [80],
+            },
+        }]),
+        document,
+    );
+}
+
+#[test]
+fn queries_files_with_an_inferred_schema() {
+    let files = ConfigFile::read_dir(SERVICES).unwrap();
+    let adapter = Arc::new(JsonAdapter::infer(document(&files)).unwrap());
+
+    let query = r#"
+{
+    Document {
+        contents {
+            name @output
+            replicas @output
+            ports @output
+
+            queues {
+                queue: name @output
+                concurrency @filter(op: ">", value: ["$concurrency"])
+            }
+        }
+    }
+}"#;
+    let arguments = BTreeMap::from([("concurrency".into(), 1.into())]);
+    let rows = execute(adapter.schema(), adapter.clone(), query, arguments);
+    assert_eq!(strings(&["worker"]), column(&rows, "name"));
+    assert_eq!(strings(&["emails"]), column(&rows, "queue"));
+    assert_eq!(vec![FieldValue::Int64(2)], column(&rows, "replicas"));
+    assert_eq!(vec![FieldValue::Null], column(&rows, "ports"));
+}
+
+#[test]
+fn queries_files_with_the_generic_schema() {
+    let files = ConfigFile::read_dir(SERVICES).unwrap();
+    let adapter = Arc::new(JsonAdapter::new(document(&files)));
+
+    // Every port of every file, wherever it's configured.
+    let query = r#"
+{
+    Root {
+        children {
+            ... on ObjectValue {
+                field(key: "format") {
+                    ... on StringValue {
+                        format: value @output
+                    }
+                }
+                field(key: "contents") {
+                    children @recurse(depth: 3) {
+                        ... on ArrayValue {
+                            key @filter(op: "=", value: ["$ports"])
+                            children {
+                                pointer @output
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}"#;
+    let arguments = BTreeMap::from([("ports".into(), "ports".into())]);
+    let rows = execute(adapter.schema(), adapter.clone(), query, arguments);
+    // Objects' fields are in order of key, rather than of their order in the file.
+    assert_eq!(
+        strings(&[
+            "/0/contents/healthcheck/ports/0",
+            "/0/contents/healthcheck/ports/1",
+            "/0/contents/ports/0",
+            "/0/contents/ports/1",
+            "/3/contents/ports/0",
+        ]),
+        column(&rows, "pointer"),
+    );
+    assert_eq!(
+        strings(&["yaml", "yaml", "yaml", "yaml", "json"]),
+        column(&rows, "format"),
+    );
+}
+
+#[test]
+fn reports_unreadable_files() {
+    let error = ConfigFile::read(format!("{SERVICES}/README.md")).unwrap_err();
+    assert!(matches!(error, ConfigError::UnknownFormat(_)), "{error}");
+
+    let error = ConfigFile::read(format!("{SERVICES}/missing.yaml")).unwrap_err();
+    assert!(matches!(error, ConfigError::Read { .. }), "{error}");
+
+    let error = ConfigFile::parse("broken.toml", Format::Toml, "name = ").unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("Failed to parse \"broken.toml\""),
+        "{error}"
+    );
+    assert!(matches!(
+        error,
+        ConfigError::Parse {
+            source: ParseError::Toml(_),
+            ..
+        }
+    ));
+}
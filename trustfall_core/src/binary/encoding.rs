@@ -0,0 +1,856 @@
+//! A compact, self-describing binary encoding of the `serde` data model.
+//!
+//! Every value starts with a one-byte tag, followed by its contents:
+//! - `null`, `false`, and `true` have no contents;
+//! - integers are a varint, with negative integers encoded as the varint of `-1 - n`;
+//! - floats are eight little-endian bytes;
+//! - strings are either a varint length followed by UTF-8 bytes, or a varint index into
+//!   the strings seen so far in the same encoding, so repeated field names and vertex type
+//!   names are only stored once;
+//! - byte arrays are a varint length followed by the bytes;
+//! - sequences and maps are a varint element count followed by the elements.
+//!
+//! Values map onto tags the same way they map onto JSON values with `serde_json`:
+//! structs are maps keyed by field name, `None` and unit are `null`, unit enum variants
+//! are strings, and other enum variants are single-entry maps from the variant name
+//! to its contents. That is what allows the encoding to represent every type that
+//! round-trips through `serde_json`, including ones with skipped or defaulted fields.
+use std::{collections::HashMap, fmt};
+
+use serde::{
+    de::{self, value::BorrowedStrDeserializer},
+    ser, Deserialize, Serialize,
+};
+
+const TAG_NULL: u8 = 0;
+const TAG_FALSE: u8 = 1;
+const TAG_TRUE: u8 = 2;
+const TAG_UINT: u8 = 3;
+const TAG_NEGATIVE_INT: u8 = 4;
+const TAG_FLOAT: u8 = 5;
+const TAG_STRING: u8 = 6;
+const TAG_STRING_REF: u8 = 7;
+const TAG_BYTES: u8 = 8;
+const TAG_SEQ: u8 = 9;
+const TAG_MAP: u8 = 10;
+
+/// How deeply sequences and maps may be nested, so malicious input
+/// can't exhaust the stack while decoding.
+const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EncodingError(String);
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for EncodingError {}
+
+impl ser::Error for EncodingError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+impl de::Error for EncodingError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+pub(crate) fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, EncodingError> {
+    let mut encoder = Encoder {
+        output: vec![],
+        strings: HashMap::new(),
+    };
+    value.serialize(&mut encoder)?;
+    Ok(encoder.output)
+}
+
+pub(crate) fn from_bytes<'de, T: Deserialize<'de>>(input: &'de [u8]) -> Result<T, EncodingError> {
+    let mut decoder = Decoder {
+        input,
+        strings: vec![],
+        remaining_depth: MAX_DEPTH,
+    };
+    let value = T::deserialize(&mut decoder)?;
+    if decoder.input.is_empty() {
+        Ok(value)
+    } else {
+        Err(EncodingError(format!(
+            "{} unexpected bytes after the end of the value",
+            decoder.input.len()
+        )))
+    }
+}
+
+pub(crate) fn write_varint(output: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        output.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+/// Read a varint from the start of the input, advancing the input past it.
+pub(crate) fn read_varint(input: &mut &[u8]) -> Result<u64, EncodingError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = input
+            .split_first()
+            .ok_or_else(|| EncodingError("unexpected end of input".to_string()))?;
+        *input = rest;
+        let bits = u64::from(byte & 0x7f);
+        if shift == 63 && bits > 1 {
+            break;
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(EncodingError("varint is too large".to_string()))
+}
+
+struct Encoder {
+    output: Vec<u8>,
+    strings: HashMap<String, u64>,
+}
+
+impl Encoder {
+    fn write_str(&mut self, value: &str) {
+        if let Some(&index) = self.strings.get(value) {
+            self.output.push(TAG_STRING_REF);
+            write_varint(&mut self.output, index);
+        } else {
+            self.output.push(TAG_STRING);
+            write_varint(&mut self.output, value.len() as u64);
+            self.output.extend_from_slice(value.as_bytes());
+            let index = self.strings.len() as u64;
+            self.strings.insert(value.to_string(), index);
+        }
+    }
+
+    /// Start a sequence or map whose element count is written once all its elements are.
+    fn start_container(&mut self, tag: u8) -> Container<'_> {
+        self.output.push(tag);
+        let count_position = self.output.len();
+        Container {
+            encoder: self,
+            count_position,
+            count: 0,
+        }
+    }
+
+    /// Start the single-entry map representing an enum variant with contents.
+    fn start_variant(&mut self, variant: &str) {
+        self.output.push(TAG_MAP);
+        write_varint(&mut self.output, 1);
+        self.write_str(variant);
+    }
+}
+
+struct Container<'a> {
+    encoder: &'a mut Encoder,
+    count_position: usize,
+    count: u64,
+}
+
+impl Container<'_> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodingError> {
+        self.count += 1;
+        value.serialize(&mut *self.encoder)
+    }
+
+    fn entry<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), EncodingError> {
+        self.count += 1;
+        self.encoder.write_str(key);
+        value.serialize(&mut *self.encoder)
+    }
+
+    fn finish(self) -> Result<(), EncodingError> {
+        let mut count = vec![];
+        write_varint(&mut count, self.count);
+        self.encoder
+            .output
+            .splice(self.count_position..self.count_position, count);
+        Ok(())
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut Encoder {
+    type Ok = ();
+    type Error = EncodingError;
+
+    type SerializeSeq = Container<'a>;
+    type SerializeTuple = Container<'a>;
+    type SerializeTupleStruct = Container<'a>;
+    type SerializeTupleVariant = Container<'a>;
+    type SerializeMap = Container<'a>;
+    type SerializeStruct = Container<'a>;
+    type SerializeStructVariant = Container<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), EncodingError> {
+        self.output.push(if v { TAG_TRUE } else { TAG_FALSE });
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), EncodingError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), EncodingError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), EncodingError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), EncodingError> {
+        if v >= 0 {
+            self.serialize_u64(v as u64)
+        } else {
+            self.output.push(TAG_NEGATIVE_INT);
+            write_varint(&mut self.output, !v as u64);
+            Ok(())
+        }
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), EncodingError> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), EncodingError> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), EncodingError> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), EncodingError> {
+        self.output.push(TAG_UINT);
+        write_varint(&mut self.output, v);
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), EncodingError> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), EncodingError> {
+        self.output.push(TAG_FLOAT);
+        self.output.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), EncodingError> {
+        self.write_str(v.encode_utf8(&mut [0; 4]));
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), EncodingError> {
+        self.write_str(v);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), EncodingError> {
+        self.output.push(TAG_BYTES);
+        write_varint(&mut self.output, v.len() as u64);
+        self.output.extend_from_slice(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), EncodingError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), EncodingError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), EncodingError> {
+        self.output.push(TAG_NULL);
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), EncodingError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), EncodingError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), EncodingError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), EncodingError> {
+        self.start_variant(variant);
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Container<'a>, EncodingError> {
+        Ok(self.start_container(TAG_SEQ))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Container<'a>, EncodingError> {
+        Ok(self.start_container(TAG_SEQ))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Container<'a>, EncodingError> {
+        Ok(self.start_container(TAG_SEQ))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Container<'a>, EncodingError> {
+        self.start_variant(variant);
+        Ok(self.start_container(TAG_SEQ))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Container<'a>, EncodingError> {
+        Ok(self.start_container(TAG_MAP))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Container<'a>, EncodingError> {
+        Ok(self.start_container(TAG_MAP))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Container<'a>, EncodingError> {
+        self.start_variant(variant);
+        Ok(self.start_container(TAG_MAP))
+    }
+}
+
+impl ser::SerializeSeq for Container<'_> {
+    type Ok = ();
+    type Error = EncodingError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for Container<'_> {
+    type Ok = ();
+    type Error = EncodingError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for Container<'_> {
+    type Ok = ();
+    type Error = EncodingError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for Container<'_> {
+    type Ok = ();
+    type Error = EncodingError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeMap for Container<'_> {
+    type Ok = ();
+    type Error = EncodingError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        // Each entry is counted once, when its key is written.
+        self.element(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(&mut *self.encoder)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for Container<'_> {
+    type Ok = ();
+    type Error = EncodingError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.entry(key, value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for Container<'_> {
+    type Ok = ();
+    type Error = EncodingError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.entry(key, value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.finish()
+    }
+}
+
+struct Decoder<'de> {
+    input: &'de [u8],
+    strings: Vec<&'de str>,
+    remaining_depth: usize,
+}
+
+impl<'de> Decoder<'de> {
+    fn peek_tag(&self) -> Result<u8, EncodingError> {
+        self.input
+            .first()
+            .copied()
+            .ok_or_else(|| EncodingError("unexpected end of input".to_string()))
+    }
+
+    fn read_tag(&mut self) -> Result<u8, EncodingError> {
+        let tag = self.peek_tag()?;
+        self.input = &self.input[1..];
+        Ok(tag)
+    }
+
+    fn read_varint(&mut self) -> Result<u64, EncodingError> {
+        read_varint(&mut self.input)
+    }
+
+    fn read_slice(&mut self) -> Result<&'de [u8], EncodingError> {
+        let len = self.read_varint()?;
+        match usize::try_from(len) {
+            Ok(len) if len <= self.input.len() => {
+                let (slice, rest) = self.input.split_at(len);
+                self.input = rest;
+                Ok(slice)
+            }
+            _ => Err(EncodingError(format!(
+                "length {len} is longer than the remaining input"
+            ))),
+        }
+    }
+
+    /// Read the contents of a string, whose tag has already been read.
+    fn read_str(&mut self, tag: u8) -> Result<&'de str, EncodingError> {
+        if tag == TAG_STRING {
+            let bytes = self.read_slice()?;
+            let value = std::str::from_utf8(bytes)
+                .map_err(|e| EncodingError(format!("invalid UTF-8 in string: {e}")))?;
+            self.strings.push(value);
+            Ok(value)
+        } else {
+            let index = self.read_varint()?;
+            usize::try_from(index)
+                .ok()
+                .and_then(|index| self.strings.get(index).copied())
+                .ok_or_else(|| EncodingError(format!("invalid string reference {index}")))
+        }
+    }
+
+    fn read_count(&mut self) -> Result<u64, EncodingError> {
+        let count = self.read_varint()?;
+        // Every element takes at least one byte, which bounds the count of valid input.
+        if count > self.input.len() as u64 {
+            return Err(EncodingError(format!(
+                "element count {count} is larger than the remaining input"
+            )));
+        }
+        Ok(count)
+    }
+
+    fn nested<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, EncodingError>,
+    ) -> Result<T, EncodingError> {
+        if self.remaining_depth == 0 {
+            return Err(EncodingError(format!(
+                "values are nested more than {MAX_DEPTH} levels deep"
+            )));
+        }
+        self.remaining_depth -= 1;
+        let result = f(self);
+        self.remaining_depth += 1;
+        result
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Decoder<'de> {
+    type Error = EncodingError;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, EncodingError> {
+        match self.read_tag()? {
+            TAG_NULL => visitor.visit_unit(),
+            TAG_FALSE => visitor.visit_bool(false),
+            TAG_TRUE => visitor.visit_bool(true),
+            TAG_UINT => visitor.visit_u64(self.read_varint()?),
+            TAG_NEGATIVE_INT => {
+                let encoded = self.read_varint()?;
+                let value = i64::try_from(encoded)
+                    .map_err(|_| EncodingError(format!("integer -1-{encoded} is out of range")))?;
+                visitor.visit_i64(!value)
+            }
+            TAG_FLOAT => {
+                let bytes = self
+                    .input
+                    .get(..8)
+                    .ok_or_else(|| EncodingError("unexpected end of input".to_string()))?;
+                let value = f64::from_le_bytes(bytes.try_into().expect("slice of 8 bytes"));
+                self.input = &self.input[8..];
+                visitor.visit_f64(value)
+            }
+            tag @ (TAG_STRING | TAG_STRING_REF) => visitor.visit_borrowed_str(self.read_str(tag)?),
+            TAG_BYTES => visitor.visit_borrowed_bytes(self.read_slice()?),
+            TAG_SEQ => {
+                let remaining = self.read_count()?;
+                self.nested(|decoder| {
+                    let mut access = Elements { decoder, remaining };
+                    let value = visitor.visit_seq(&mut access)?;
+                    access.finish(value)
+                })
+            }
+            TAG_MAP => {
+                let remaining = self.read_count()?;
+                self.nested(|decoder| {
+                    let mut access = Elements { decoder, remaining };
+                    let value = visitor.visit_map(&mut access)?;
+                    access.finish(value)
+                })
+            }
+            tag => Err(EncodingError(format!("unknown value tag {tag}"))),
+        }
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, EncodingError> {
+        if self.peek_tag()? == TAG_NULL {
+            self.read_tag()?;
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, EncodingError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, EncodingError> {
+        match self.read_tag()? {
+            tag @ (TAG_STRING | TAG_STRING_REF) => {
+                let variant = self.read_str(tag)?;
+                visitor.visit_enum(BorrowedStrDeserializer::<EncodingError>::new(variant))
+            }
+            TAG_MAP => {
+                let count = self.read_varint()?;
+                if count != 1 {
+                    return Err(EncodingError(format!(
+                        "expected an enum variant as a map with one entry, got {count} entries"
+                    )));
+                }
+                self.nested(|decoder| visitor.visit_enum(Variant { decoder }))
+            }
+            tag => Err(EncodingError(format!(
+                "expected an enum variant, got value tag {tag}"
+            ))),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+/// The elements of a sequence, or the entries of a map.
+struct Elements<'a, 'de> {
+    decoder: &'a mut Decoder<'de>,
+    remaining: u64,
+}
+
+impl<'de> Elements<'_, 'de> {
+    fn finish<T>(self, value: T) -> Result<T, EncodingError> {
+        if self.remaining == 0 {
+            Ok(value)
+        } else {
+            Err(EncodingError(format!(
+                "{} elements were left unread",
+                self.remaining
+            )))
+        }
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for Elements<'_, 'de> {
+    type Error = EncodingError;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, EncodingError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.decoder).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        usize::try_from(self.remaining).ok()
+    }
+}
+
+impl<'de> de::MapAccess<'de> for Elements<'_, 'de> {
+    type Error = EncodingError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, EncodingError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.decoder).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, EncodingError> {
+        seed.deserialize(&mut *self.decoder)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        usize::try_from(self.remaining).ok()
+    }
+}
+
+/// An enum variant with contents, represented as a map from the variant name to them.
+struct Variant<'a, 'de> {
+    decoder: &'a mut Decoder<'de>,
+}
+
+impl<'de> de::EnumAccess<'de> for Variant<'_, 'de> {
+    type Error = EncodingError;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self), EncodingError> {
+        let variant = seed.deserialize(&mut *self.decoder)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Variant<'_, 'de> {
+    type Error = EncodingError;
+
+    fn unit_variant(self) -> Result<(), EncodingError> {
+        <()>::deserialize(&mut *self.decoder)
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, EncodingError> {
+        seed.deserialize(&mut *self.decoder)
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, EncodingError> {
+        de::Deserializer::deserialize_seq(&mut *self.decoder, visitor)
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, EncodingError> {
+        de::Deserializer::deserialize_map(&mut *self.decoder, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, fmt::Debug, sync::Arc};
+
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+    use super::{from_bytes, read_varint, to_bytes, write_varint};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Empty,
+        Point(i64, i64),
+        Circle { radius: f64 },
+        Named(String),
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Drawing {
+        name: Arc<str>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        author: Option<String>,
+        shapes: Vec<Shape>,
+        layers: BTreeMap<String, Option<u32>>,
+    }
+
+    fn assert_round_trips<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: T) {
+        let bytes = to_bytes(&value).unwrap();
+        assert_eq!(value, from_bytes::<T>(&bytes).unwrap());
+    }
+
+    #[test]
+    fn values_round_trip() {
+        assert_round_trips(0u64);
+        assert_round_trips(u64::MAX);
+        assert_round_trips(i64::MIN);
+        assert_round_trips(-1i32);
+        assert_round_trips(1.5f64);
+        assert_round_trips(true);
+        assert_round_trips(Some("text".to_string()));
+        assert_round_trips(None::<u8>);
+        assert_round_trips('ñ');
+        assert_round_trips(Drawing {
+            name: "sketch".into(),
+            author: None,
+            shapes: vec![
+                Shape::Empty,
+                Shape::Point(-3, 4),
+                Shape::Circle { radius: 2.0 },
+                Shape::Named("sketch".to_string()),
+            ],
+            layers: btreemap! {
+                "background".to_string() => None,
+                "foreground".to_string() => Some(2),
+            },
+        });
+    }
+
+    #[test]
+    fn repeated_strings_are_stored_once() {
+        let names = vec!["a long vertex type name"; 20];
+        let bytes = to_bytes(&names).unwrap();
+        assert!(bytes.len() < 3 * names[0].len(), "{}", bytes.len());
+        assert_eq!(names, from_bytes::<Vec<&str>>(&bytes).unwrap());
+    }
+
+    #[test]
+    fn varints_round_trip() {
+        for value in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut bytes = vec![];
+            write_varint(&mut bytes, value);
+            let mut input = bytes.as_slice();
+            assert_eq!(value, read_varint(&mut input).unwrap());
+            assert!(input.is_empty());
+        }
+
+        let mut too_large: &[u8] = &[0xff; 10];
+        assert!(read_varint(&mut too_large).is_err());
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        let bytes = to_bytes(&vec!["one", "two"]).unwrap();
+        for len in 0..bytes.len() {
+            assert!(from_bytes::<Vec<String>>(&bytes[..len]).is_err());
+        }
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(from_bytes::<Vec<String>>(&trailing).is_err());
+
+        // A sequence nested within itself far too many times.
+        let deeply_nested: Vec<u8> = [9, 1].repeat(1000);
+        assert!(from_bytes::<serde_json::Value>(&deeply_nested).is_err());
+    }
+}
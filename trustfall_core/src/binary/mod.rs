@@ -0,0 +1,634 @@
+//! A compact, versioned binary representation of compiled queries and schemas,
+//! for embedding them in build artifacts and loading them without parsing again.
+//!
+//! The JSON representations in [`ir::json`](crate::ir::json) and
+//! [`schema::json`](crate::schema::json) are meant to be read by people and other tools.
+//! This format is meant to be small and fast to load, and to evolve without breaking
+//! artifacts that were produced by other versions of trustfall.
+//!
+//! # Layout
+//!
+//! An encoded query or schema starts with a header:
+//! - the four bytes `TFBN`;
+//! - one byte for the [`BinaryKind`] of the contents: `1` for queries, `2` for schemas;
+//! - the major and minor [`BinaryVersion`] of the format, as little-endian `u16` values.
+//!
+//! The rest of the input is a sequence of sections, each of which is:
+//! - a varint (LEB128) section identifier;
+//! - a flags byte, whose lowest bit marks the section as required;
+//! - a varint length, followed by that many bytes of section contents.
+//!
+//! Encoded queries have a required section with the 8-byte little-endian
+//! [`SchemaFingerprint`] of the schema they were compiled against, and a required section
+//! with their [`IRQuery`]. Encoded schemas have a required section with their
+//! [`SchemaJson`] model. Both are stored in a compact self-describing encoding
+//! of the same data as their JSON representation, in which repeated strings
+//! such as field names are only stored once.
+//!
+//! # Compatibility
+//!
+//! - Readers accept any version with the same major version as their own,
+//!   including newer minor versions.
+//! - A new minor version may only add sections. Readers skip sections they don't know,
+//!   unless the section is marked as required, in which case they reject the input with
+//!   [`BinaryFormatError::UnsupportedSection`].
+//! - Any other change, including to the contents of existing sections, requires a new
+//!   major version.
+//!
+//! Writers that need to produce artifacts for older versions of trustfall can use
+//! [`BinaryVersion::negotiate`] to find a version both sides support, and then encode
+//! with that version to leave out sections the older reader doesn't know.
+use std::{fmt, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ir::{IRQuery, IndexedQuery, InvalidIRQueryError},
+    schema::{
+        error::{IncompatibleQueryError, InvalidSchemaError},
+        json::SchemaJson,
+        Schema, SchemaFingerprint,
+    },
+};
+
+mod encoding;
+
+const MAGIC: &[u8; 4] = b"TFBN";
+
+const SECTION_REQUIRED: u8 = 1;
+
+const QUERY_SECTION_SCHEMA_FINGERPRINT: u64 = 1;
+const QUERY_SECTION_IR: u64 = 2;
+const SCHEMA_SECTION_MODEL: u64 = 1;
+
+/// A version of the binary format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct BinaryVersion {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl BinaryVersion {
+    /// The newest version of the format, which this version of trustfall produces by default.
+    pub const CURRENT: BinaryVersion = BinaryVersion { major: 1, minor: 0 };
+
+    pub const fn new(major: u16, minor: u16) -> Self {
+        Self { major, minor }
+    }
+
+    /// Whether a reader supporting this version is able to read input of the given version.
+    pub fn can_read(self, input: BinaryVersion) -> bool {
+        self.major == input.major
+    }
+
+    /// The newest version that both this version of trustfall and a reader supporting
+    /// `reader` are able to use, or `None` if there isn't one.
+    pub fn negotiate(reader: BinaryVersion) -> Option<BinaryVersion> {
+        if Self::CURRENT.major == reader.major {
+            // Versions with the same major version are ordered by their minor version.
+            Some(reader.min(Self::CURRENT))
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for BinaryVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// What an encoded input contains.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BinaryKind {
+    Query,
+    Schema,
+}
+
+impl BinaryKind {
+    fn to_byte(self) -> u8 {
+        match self {
+            BinaryKind::Query => 1,
+            BinaryKind::Schema => 2,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(BinaryKind::Query),
+            2 => Some(BinaryKind::Schema),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BinaryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryKind::Query => f.write_str("query"),
+            BinaryKind::Schema => f.write_str("schema"),
+        }
+    }
+}
+
+/// The header of an encoded query or schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BinaryHeader {
+    pub kind: BinaryKind,
+    pub version: BinaryVersion,
+}
+
+impl BinaryHeader {
+    const LEN: usize = MAGIC.len() + 5;
+
+    /// Read the header at the start of the input, without checking whether
+    /// this version of trustfall is able to read the rest of it.
+    pub fn read(input: &[u8]) -> Result<Self, BinaryFormatError> {
+        let header = input
+            .get(..Self::LEN)
+            .filter(|header| header.starts_with(MAGIC))
+            .ok_or(BinaryFormatError::NotBinaryFormat)?;
+        let kind = BinaryKind::from_byte(header[4]).ok_or_else(|| {
+            BinaryFormatError::InvalidInput(format!("unknown contents kind {}", header[4]))
+        })?;
+        let major = u16::from_le_bytes([header[5], header[6]]);
+        let minor = u16::from_le_bytes([header[7], header[8]]);
+        Ok(Self {
+            kind,
+            version: BinaryVersion::new(major, minor),
+        })
+    }
+}
+
+/// Errors from loading an encoded query or schema.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, thiserror::Error)]
+pub enum BinaryFormatError {
+    #[error("The input is not in trustfall's binary format.")]
+    NotBinaryFormat,
+
+    #[error("Expected an encoded {expected}, but the input contains an encoded {found}.")]
+    UnexpectedKind {
+        expected: BinaryKind,
+        found: BinaryKind,
+    },
+
+    #[error(
+        "The input uses version {found} of the binary format, \
+        but only versions {}.x are supported.", supported.major
+    )]
+    UnsupportedVersion {
+        found: BinaryVersion,
+        supported: BinaryVersion,
+    },
+
+    #[error(
+        "The input contains required section {0}, which this version of trustfall \
+        does not support."
+    )]
+    UnsupportedSection(u64),
+
+    #[error("The input is missing its required \"{0}\" section.")]
+    MissingSection(String),
+
+    #[error("The input is malformed: {0}")]
+    InvalidInput(String),
+
+    #[error("The stored query is not valid against the current schema")]
+    IncompatibleSchema(#[from] IncompatibleQueryError),
+
+    #[error("The stored query is malformed")]
+    InvalidQuery(#[source] InvalidIRQueryError),
+
+    #[error("The stored schema is invalid")]
+    InvalidSchema(#[source] InvalidSchemaError),
+}
+
+impl From<encoding::EncodingError> for BinaryFormatError {
+    fn from(e: encoding::EncodingError) -> Self {
+        Self::InvalidInput(e.to_string())
+    }
+}
+
+struct Section<'a> {
+    id: u64,
+    required: bool,
+    contents: &'a [u8],
+}
+
+struct Writer {
+    output: Vec<u8>,
+}
+
+impl Writer {
+    fn new(kind: BinaryKind, version: BinaryVersion) -> Result<Self, BinaryFormatError> {
+        if !BinaryVersion::CURRENT.can_read(version) || version > BinaryVersion::CURRENT {
+            return Err(BinaryFormatError::UnsupportedVersion {
+                found: version,
+                supported: BinaryVersion::CURRENT,
+            });
+        }
+
+        let mut output = MAGIC.to_vec();
+        output.push(kind.to_byte());
+        output.extend_from_slice(&version.major.to_le_bytes());
+        output.extend_from_slice(&version.minor.to_le_bytes());
+        Ok(Self { output })
+    }
+
+    fn section(&mut self, id: u64, required: bool, contents: &[u8]) {
+        encoding::write_varint(&mut self.output, id);
+        self.output
+            .push(if required { SECTION_REQUIRED } else { 0 });
+        encoding::write_varint(&mut self.output, contents.len() as u64);
+        self.output.extend_from_slice(contents);
+    }
+}
+
+/// Check the input's header, then split the rest of it into sections,
+/// keeping only the ones with the given identifiers.
+fn read_sections<'a>(
+    input: &'a [u8],
+    kind: BinaryKind,
+    known_sections: &[u64],
+) -> Result<Vec<Section<'a>>, BinaryFormatError> {
+    let header = BinaryHeader::read(input)?;
+    if !BinaryVersion::CURRENT.can_read(header.version) {
+        return Err(BinaryFormatError::UnsupportedVersion {
+            found: header.version,
+            supported: BinaryVersion::CURRENT,
+        });
+    }
+    if header.kind != kind {
+        return Err(BinaryFormatError::UnexpectedKind {
+            expected: kind,
+            found: header.kind,
+        });
+    }
+
+    let mut rest = &input[BinaryHeader::LEN..];
+    let mut sections = vec![];
+    while !rest.is_empty() {
+        let id = encoding::read_varint(&mut rest)?;
+        let (&flags, after_flags) = rest.split_first().ok_or_else(|| {
+            BinaryFormatError::InvalidInput(format!("section {id} is missing its flags"))
+        })?;
+        rest = after_flags;
+        let len = encoding::read_varint(&mut rest)?;
+        let len = usize::try_from(len)
+            .ok()
+            .filter(|len| *len <= rest.len())
+            .ok_or_else(|| {
+                BinaryFormatError::InvalidInput(format!(
+                    "section {id} is longer than the remaining input"
+                ))
+            })?;
+        let (contents, after_section) = rest.split_at(len);
+        rest = after_section;
+
+        let section = Section {
+            id,
+            required: flags & SECTION_REQUIRED != 0,
+            contents,
+        };
+        if known_sections.contains(&id) {
+            sections.push(section);
+        } else if section.required {
+            return Err(BinaryFormatError::UnsupportedSection(id));
+        }
+    }
+    Ok(sections)
+}
+
+fn required_section<'a>(
+    sections: &[Section<'a>],
+    id: u64,
+    name: &str,
+) -> Result<&'a [u8], BinaryFormatError> {
+    sections
+        .iter()
+        .find(|section| section.id == id)
+        .map(|section| section.contents)
+        .ok_or_else(|| BinaryFormatError::MissingSection(name.to_string()))
+}
+
+impl IndexedQuery {
+    /// Encode the query in the current version of the binary format, recording
+    /// the schema it was compiled against. See the [module-level documentation](crate::binary)
+    /// for the format.
+    pub fn to_binary(&self, schema: &Schema) -> Vec<u8> {
+        self.to_binary_version(schema, BinaryVersion::CURRENT)
+            .expect("the current version is always supported")
+    }
+
+    /// Encode the query in the given version of the binary format, such as one found
+    /// with [`BinaryVersion::negotiate`].
+    pub fn to_binary_version(
+        &self,
+        schema: &Schema,
+        version: BinaryVersion,
+    ) -> Result<Vec<u8>, BinaryFormatError> {
+        let mut writer = Writer::new(BinaryKind::Query, version)?;
+        writer.section(
+            QUERY_SECTION_SCHEMA_FINGERPRINT,
+            true,
+            &schema.fingerprint().value().to_le_bytes(),
+        );
+        writer.section(
+            QUERY_SECTION_IR,
+            true,
+            &encoding::to_bytes(&self.ir_query).expect("failed to serialize query"),
+        );
+        Ok(writer.output)
+    }
+
+    /// Load a query from the binary representation produced by [`IndexedQuery::to_binary`],
+    /// checking that it is valid against the given schema.
+    ///
+    /// As with [`IndexedQuery::from_json`], a query compiled against a different schema
+    /// is only loaded if it is still valid according to [`Schema::check_query_compatibility`].
+    pub fn from_binary(schema: &Schema, input: &[u8]) -> Result<Arc<Self>, BinaryFormatError> {
+        let sections = read_sections(
+            input,
+            BinaryKind::Query,
+            &[QUERY_SECTION_SCHEMA_FINGERPRINT, QUERY_SECTION_IR],
+        )?;
+
+        let fingerprint = required_section(
+            &sections,
+            QUERY_SECTION_SCHEMA_FINGERPRINT,
+            "schema fingerprint",
+        )?;
+        let fingerprint: [u8; 8] = fingerprint.try_into().map_err(|_| {
+            BinaryFormatError::InvalidInput(format!(
+                "the schema fingerprint is {} bytes long instead of 8",
+                fingerprint.len()
+            ))
+        })?;
+        let fingerprint = SchemaFingerprint::from_value(u64::from_le_bytes(fingerprint));
+
+        let ir_query: IRQuery =
+            encoding::from_bytes(required_section(&sections, QUERY_SECTION_IR, "query")?)?;
+        if fingerprint != schema.fingerprint() {
+            schema.check_query_compatibility(&ir_query)?;
+        }
+
+        let indexed_query =
+            IndexedQuery::try_from(ir_query).map_err(BinaryFormatError::InvalidQuery)?;
+        Ok(Arc::new(
+            indexed_query.with_custom_scalars(schema.custom_scalars.clone()),
+        ))
+    }
+}
+
+impl Schema {
+    /// Encode the schema in the current version of the binary format.
+    /// See the [module-level documentation](crate::binary) for the format.
+    pub fn to_binary(&self) -> Vec<u8> {
+        self.to_binary_version(BinaryVersion::CURRENT)
+            .expect("the current version is always supported")
+    }
+
+    /// Encode the schema in the given version of the binary format, such as one found
+    /// with [`BinaryVersion::negotiate`].
+    pub fn to_binary_version(&self, version: BinaryVersion) -> Result<Vec<u8>, BinaryFormatError> {
+        let mut writer = Writer::new(BinaryKind::Schema, version)?;
+        writer.section(
+            SCHEMA_SECTION_MODEL,
+            true,
+            &encoding::to_bytes(&SchemaJson::from(self)).expect("failed to serialize schema"),
+        );
+        Ok(writer.output)
+    }
+
+    /// Load a schema from the binary representation produced by [`Schema::to_binary`].
+    pub fn from_binary(input: &[u8]) -> Result<Self, BinaryFormatError> {
+        let sections = read_sections(input, BinaryKind::Schema, &[SCHEMA_SECTION_MODEL])?;
+        let schema_json: SchemaJson =
+            encoding::from_bytes(required_section(&sections, SCHEMA_SECTION_MODEL, "schema")?)?;
+        Self::try_from(schema_json).map_err(BinaryFormatError::InvalidSchema)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        frontend::parse,
+        ir::IndexedQuery,
+        schema::{error::IncompatibleQueryError, Schema},
+    };
+
+    use super::{encoding, BinaryFormatError, BinaryHeader, BinaryKind, BinaryVersion, Writer};
+
+    const QUERY: &str = r#"
+{
+    Number(min: 2, max: 10) {
+        value @tag(name: "start") @output
+        next: successor @optional {
+            name @output
+        }
+        multiple(max: 3) @fold @transform(op: "count") @filter(op: ">", value: ["$count"])
+                @output(name: "multiples") {
+            value @output(name: "multiple_values")
+        }
+        predecessor @recurse(depth: 2) {
+            ... on Prime {
+                value @filter(op: "<", value: ["%start"])
+            }
+        }
+        vowelsInName @filter(op: "contains", value: ["$vowel"])
+    }
+}"#;
+
+    fn numbers_schema() -> Schema {
+        Schema::parse(fs::read_to_string("test_data/schemas/numbers.graphql").unwrap()).unwrap()
+    }
+
+    /// Changes to the representation of queries must not silently change the stored format.
+    /// If this test fails, either keep the format unchanged, or move to a new major version
+    /// and regenerate the stored query.
+    #[test]
+    fn format_is_stable() {
+        let schema = numbers_schema();
+        let indexed_query = parse(&schema, QUERY).unwrap();
+
+        let expected = fs::read("test_data/tests/query_binary/v1.bin").unwrap();
+        assert_eq!(BinaryVersion::new(1, 0), BinaryVersion::CURRENT);
+        assert_eq!(expected, indexed_query.to_binary(&schema));
+
+        let loaded = IndexedQuery::from_binary(&schema, &expected).unwrap();
+        assert_eq!(indexed_query, loaded);
+    }
+
+    #[test]
+    fn encoding_is_smaller_than_json() {
+        let schema = numbers_schema();
+        let indexed_query = parse(&schema, QUERY).unwrap();
+
+        let binary = indexed_query.to_binary(&schema);
+        let json = indexed_query.to_json(&schema);
+        assert!(
+            binary.len() * 3 < json.len() * 2,
+            "{} {}",
+            binary.len(),
+            json.len()
+        );
+        assert!(schema.to_binary().len() < schema.to_json().len());
+    }
+
+    #[test]
+    fn schemas_round_trip() {
+        for entry in fs::read_dir("test_data/schemas").unwrap() {
+            let path = entry.unwrap().path();
+            let schema = Schema::parse(fs::read_to_string(&path).unwrap()).unwrap();
+            let loaded = Schema::from_binary(&schema.to_binary()).unwrap();
+            assert_eq!(schema.to_json(), loaded.to_json(), "{}", path.display());
+            assert_eq!(schema.fingerprint(), loaded.fingerprint());
+        }
+    }
+
+    #[test]
+    fn header_describes_the_contents() {
+        let schema = numbers_schema();
+        assert_eq!(
+            BinaryHeader {
+                kind: BinaryKind::Schema,
+                version: BinaryVersion::CURRENT,
+            },
+            BinaryHeader::read(&schema.to_binary()).unwrap(),
+        );
+        assert_eq!(
+            BinaryFormatError::NotBinaryFormat,
+            BinaryHeader::read(schema.to_json().as_bytes()).unwrap_err(),
+        );
+
+        let query = parse(&schema, QUERY).unwrap().to_binary(&schema);
+        assert_eq!(
+            BinaryFormatError::UnexpectedKind {
+                expected: BinaryKind::Schema,
+                found: BinaryKind::Query,
+            },
+            Schema::from_binary(&query).unwrap_err(),
+        );
+    }
+
+    #[test]
+    fn versions_are_negotiated() {
+        let current = BinaryVersion::CURRENT;
+        assert_eq!(Some(current), BinaryVersion::negotiate(current));
+        assert_eq!(
+            Some(current),
+            BinaryVersion::negotiate(BinaryVersion::new(current.major, current.minor + 1)),
+        );
+        assert_eq!(
+            None,
+            BinaryVersion::negotiate(BinaryVersion::new(current.major + 1, 0))
+        );
+        assert!(current.can_read(BinaryVersion::new(current.major, current.minor + 3)));
+        assert!(!current.can_read(BinaryVersion::new(current.major + 1, current.minor)));
+
+        let schema = numbers_schema();
+        let newer_major = BinaryVersion::new(current.major + 1, 0);
+        assert_eq!(
+            BinaryFormatError::UnsupportedVersion {
+                found: newer_major,
+                supported: current,
+            },
+            schema.to_binary_version(newer_major).unwrap_err(),
+        );
+
+        let mut encoded = schema.to_binary();
+        encoded[5..7].copy_from_slice(&newer_major.major.to_le_bytes());
+        assert_eq!(
+            BinaryFormatError::UnsupportedVersion {
+                found: newer_major,
+                supported: current,
+            },
+            Schema::from_binary(&encoded).unwrap_err(),
+        );
+    }
+
+    /// Input from a newer minor version loads as long as the sections it adds are optional.
+    #[test]
+    fn unknown_sections_are_skipped_unless_required() {
+        let schema = numbers_schema();
+        let indexed_query = parse(&schema, QUERY).unwrap();
+        let newer_minor = BinaryVersion::new(BinaryVersion::CURRENT.major, u16::MAX);
+
+        let mut writer = Writer::new(BinaryKind::Query, BinaryVersion::CURRENT).unwrap();
+        writer.output.truncate(BinaryHeader::LEN - 2);
+        writer
+            .output
+            .extend_from_slice(&newer_minor.minor.to_le_bytes());
+        writer.section(1000, false, b"contents from the future");
+        writer
+            .output
+            .extend_from_slice(&indexed_query.to_binary(&schema)[BinaryHeader::LEN..]);
+        let with_optional_section = writer.output.clone();
+        assert_eq!(
+            newer_minor,
+            BinaryHeader::read(&with_optional_section).unwrap().version
+        );
+        assert_eq!(
+            indexed_query,
+            IndexedQuery::from_binary(&schema, &with_optional_section).unwrap()
+        );
+
+        writer.section(1001, true, b"");
+        assert_eq!(
+            BinaryFormatError::UnsupportedSection(1001),
+            IndexedQuery::from_binary(&schema, &writer.output).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn queries_load_if_still_compatible_with_a_changed_schema() {
+        let schema = numbers_schema();
+        let stored = parse(&schema, QUERY).unwrap().to_binary(&schema);
+
+        let narrowed_schema = Schema::parse(
+            fs::read_to_string("test_data/schemas/numbers.graphql")
+                .unwrap()
+                .replace("    vowelsInName: [String]\n", ""),
+        )
+        .unwrap();
+        let error = IndexedQuery::from_binary(&narrowed_schema, &stored).unwrap_err();
+        assert!(
+            matches!(
+                &error,
+                BinaryFormatError::IncompatibleSchema(IncompatibleQueryError::NonExistentProperty(
+                    type_name,
+                    property
+                )) if type_name == "Number" && property == "vowelsInName"
+            ),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        let schema = numbers_schema();
+        let stored = parse(&schema, QUERY).unwrap().to_binary(&schema);
+        for len in BinaryHeader::LEN..stored.len() {
+            assert!(IndexedQuery::from_binary(&schema, &stored[..len]).is_err());
+        }
+
+        let header_only = &stored[..BinaryHeader::LEN];
+        assert_eq!(
+            BinaryFormatError::MissingSection("schema fingerprint".to_string()),
+            IndexedQuery::from_binary(&schema, header_only).unwrap_err()
+        );
+
+        let mut writer = Writer::new(BinaryKind::Query, BinaryVersion::CURRENT).unwrap();
+        writer.section(1, true, &schema.fingerprint().value().to_le_bytes());
+        writer.section(2, true, &encoding::to_bytes("not a query").unwrap());
+        assert!(matches!(
+            IndexedQuery::from_binary(&schema, &writer.output).unwrap_err(),
+            BinaryFormatError::InvalidInput(..)
+        ));
+    }
+}
@@ -0,0 +1,436 @@
+//! Constructing queries programmatically, for applications that generate queries at runtime.
+use std::{num::NonZeroUsize, sync::Arc};
+
+use async_graphql_parser::Pos;
+
+use crate::{
+    graphql_query::{
+        directives::{
+            FilterDirective, FoldDirective, FoldGroup, OptionalDirective, OutputDirective,
+            RecurseDirective, TagDirective, TransformDirective, TransformGroup,
+        },
+        query::{FieldConnection, FieldNode, Query},
+    },
+    ir::{FieldValue, IRQuery, IndexedQuery, Operation, TransformationKind},
+    schema::Schema,
+};
+
+pub use crate::graphql_query::directives::OperatorArgument;
+
+use super::{error::FrontendError, make_indexed_query, make_ir_for_query};
+
+/// Builds a query without writing it as GraphQL text.
+///
+/// The builder mirrors the structure of the query text: it starts at an edge of the schema's
+/// root query type, and [`property`](Self::property) and [`edge`](Self::edge) select fields of
+/// the vertices that edge produces. Fields keep the order in which they are added,
+/// so properties with a [`tag`](Property::tag) must be added before the filters that use them.
+///
+/// The query is only validated against the schema by [`build`](Self::build), and is then
+/// subject to exactly the same checks as a query parsed from text. Built queries have no text,
+/// so the spans in their errors are always empty.
+///
+/// ```
+/// # use std::fs;
+/// use trustfall_core::{
+///     frontend::builder::{Edge, OperatorArgument, Property, QueryBuilder},
+///     ir::Operation,
+///     schema::Schema,
+/// };
+///
+/// # let schema = Schema::parse(
+/// #     fs::read_to_string("test_data/schemas/numbers.graphql").unwrap()
+/// # ).unwrap();
+/// let query = QueryBuilder::new("Number")
+///     .argument("max", 10)
+///     .property(
+///         Property::new("value")
+///             .filter(Operation::GreaterThan((), OperatorArgument::VariableRef("min".into())))
+///             .output(),
+///     )
+///     .edge(
+///         Edge::new("successor")
+///             .optional()
+///             .property(Property::new("name").output_named("successor_name")),
+///     )
+///     .build(&schema)
+///     .unwrap();
+/// assert!(query.outputs.contains_key("successor_name"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct QueryBuilder {
+    root: Edge,
+}
+
+impl QueryBuilder {
+    /// Start a query at the named edge of the schema's root query type.
+    pub fn new(root_edge: &str) -> Self {
+        Self {
+            root: Edge::new(root_edge),
+        }
+    }
+
+    /// Set the value of one of the root edge's parameters.
+    pub fn argument(mut self, name: &str, value: impl Into<FieldValue>) -> Self {
+        self.root = self.root.argument(name, value);
+        self
+    }
+
+    /// Coerce the vertices produced by the root edge to the named subtype.
+    pub fn coerce_to(mut self, type_name: &str) -> Self {
+        self.root = self.root.coerce_to(type_name);
+        self
+    }
+
+    /// Select a property of the vertices produced by the root edge.
+    pub fn property(mut self, property: Property) -> Self {
+        self.root = self.root.property(property);
+        self
+    }
+
+    /// Expand an edge of the vertices produced by the root edge.
+    pub fn edge(mut self, edge: Edge) -> Self {
+        self.root = self.root.edge(edge);
+        self
+    }
+
+    /// Validate the query against the schema, and compile it.
+    pub fn build(&self, schema: &Schema) -> Result<Arc<IndexedQuery>, FrontendError> {
+        let ir_query = self.build_ir(schema)?;
+        Ok(make_indexed_query(schema, ir_query))
+    }
+
+    /// Validate the query against the schema, and compile it into its intermediate representation.
+    pub fn build_ir(&self, schema: &Schema) -> Result<IRQuery, FrontendError> {
+        make_ir_for_query(schema, &self.to_query())
+    }
+
+    fn to_query(&self) -> Query {
+        Query {
+            variables: vec![],
+            root_connection: self.root.connection.clone(),
+            root_field: self.root.node.clone(),
+        }
+    }
+}
+
+/// Writes the query as GraphQL text, in the canonical format of
+/// [`format_query`](crate::graphql_query::format_query).
+impl std::fmt::Display for QueryBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_query().fmt(f)
+    }
+}
+
+fn make_connection(name: &str) -> FieldConnection {
+    FieldConnection {
+        position: Pos::default(),
+        name: name.into(),
+        alias: None,
+        arguments: Default::default(),
+        optional: None,
+        recurse: None,
+        fold: None,
+    }
+}
+
+fn make_node(name: &str) -> FieldNode {
+    FieldNode {
+        position: Pos::default(),
+        name: name.into(),
+        alias: None,
+        coerced_to: None,
+        description: None,
+        filter: Default::default(),
+        output: Default::default(),
+        tag: Default::default(),
+        connections: vec![],
+        transform_group: None,
+    }
+}
+
+/// An edge expanded by a query, together with the fields it selects on the vertices it reaches.
+#[derive(Debug, Clone)]
+pub struct Edge {
+    connection: FieldConnection,
+    node: FieldNode,
+}
+
+impl Edge {
+    pub fn new(name: &str) -> Self {
+        Self {
+            connection: make_connection(name),
+            node: make_node(name),
+        }
+    }
+
+    /// Name this use of the edge, as the prefix of the default names of the outputs beyond it.
+    pub fn alias(mut self, alias: &str) -> Self {
+        self.connection.alias = Some(alias.into());
+        self.node.alias = Some(alias.into());
+        self
+    }
+
+    /// Set the value of one of the edge's parameters.
+    pub fn argument(mut self, name: &str, value: impl Into<FieldValue>) -> Self {
+        self.connection.arguments.insert(name.into(), value.into());
+        self
+    }
+
+    /// Mark the edge `@optional`.
+    pub fn optional(mut self) -> Self {
+        self.connection.optional = Some(OptionalDirective {});
+        self
+    }
+
+    /// Mark the edge `@recurse(depth: ...)`.
+    ///
+    /// Panics if the depth is zero.
+    pub fn recurse(mut self, depth: usize) -> Self {
+        let depth = NonZeroUsize::new(depth).expect("recursion depth must be at least 1");
+        self.connection.recurse = Some(RecurseDirective { depth });
+        self
+    }
+
+    /// Mark the edge `@fold`.
+    pub fn fold(mut self) -> Self {
+        if self.connection.fold.is_none() {
+            self.connection.fold = Some(FoldGroup {
+                fold: FoldDirective {},
+                transform: None,
+            });
+        }
+        self
+    }
+
+    /// Coerce the vertices the edge reaches to the named subtype.
+    pub fn coerce_to(mut self, type_name: &str) -> Self {
+        self.node.coerced_to = Some(type_name.into());
+        self
+    }
+
+    /// Select a property of the vertices the edge reaches.
+    pub fn property(mut self, property: Property) -> Self {
+        self.node
+            .connections
+            .push((property.connection, property.node));
+        self
+    }
+
+    /// Expand an edge of the vertices the edge reaches.
+    pub fn edge(mut self, edge: Edge) -> Self {
+        self.node.connections.push((edge.connection, edge.node));
+        self
+    }
+
+    /// Output the number of vertices in the fold, under the default name.
+    ///
+    /// Marks the edge `@fold` if it isn't already, and applies `@transform(op: "count")`.
+    pub fn count_output(self) -> Self {
+        self.with_count(|count| count.output.push(OutputDirective { name: None }))
+    }
+
+    /// Output the number of vertices in the fold, under the given name.
+    pub fn count_output_named(self, name: &str) -> Self {
+        self.with_count(|count| {
+            count.output.push(OutputDirective {
+                name: Some(name.into()),
+            })
+        })
+    }
+
+    /// Filter on the number of vertices in the fold.
+    pub fn count_filter(self, operation: Operation<(), OperatorArgument>) -> Self {
+        self.with_count(|count| count.filter.push(FilterDirective { operation }))
+    }
+
+    /// Tag the number of vertices in the fold, under the given name.
+    pub fn count_tag_named(self, name: &str) -> Self {
+        self.with_count(|count| {
+            count.tag.push(TagDirective {
+                name: Some(name.into()),
+            })
+        })
+    }
+
+    fn with_count(mut self, update: impl FnOnce(&mut TransformGroup)) -> Self {
+        self = self.fold();
+        let fold = self.connection.fold.as_mut().expect("edge was just folded");
+        let count = fold.transform.get_or_insert_with(|| TransformGroup {
+            transform: TransformDirective {
+                kind: TransformationKind::Count,
+            },
+            output: vec![],
+            tag: vec![],
+            filter: vec![],
+            retransform: None,
+        });
+        update(count);
+
+        // Like the parser, record the transform on both the edge and the vertex it reaches.
+        self.node.transform_group = Some(count.clone());
+        self
+    }
+}
+
+/// A property selected by a query, together with the filters, tags, and outputs applied to it.
+#[derive(Debug, Clone)]
+pub struct Property {
+    connection: FieldConnection,
+    node: FieldNode,
+}
+
+impl Property {
+    pub fn new(name: &str) -> Self {
+        Self {
+            connection: make_connection(name),
+            node: make_node(name),
+        }
+    }
+
+    /// Name this use of the property, as the default name of its outputs and tags.
+    pub fn alias(mut self, alias: &str) -> Self {
+        self.connection.alias = Some(alias.into());
+        self.node.alias = Some(alias.into());
+        self
+    }
+
+    /// Filter on the property's value.
+    pub fn filter(mut self, operation: Operation<(), OperatorArgument>) -> Self {
+        self.node.filter.push(FilterDirective { operation });
+        self
+    }
+
+    /// Tag the property's value under the default name.
+    pub fn tag(mut self) -> Self {
+        self.node.tag.push(TagDirective { name: None });
+        self
+    }
+
+    /// Tag the property's value under the given name.
+    pub fn tag_named(mut self, name: &str) -> Self {
+        self.node.tag.push(TagDirective {
+            name: Some(name.into()),
+        });
+        self
+    }
+
+    /// Output the property's value under the default name.
+    pub fn output(mut self) -> Self {
+        self.node.output.push(OutputDirective { name: None });
+        self
+    }
+
+    /// Output the property's value under the given name.
+    pub fn output_named(mut self, name: &str) -> Self {
+        self.node.output.push(OutputDirective {
+            name: Some(name.into()),
+        });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        frontend::{error::FrontendError, error::ValidationError, parse_to_ir},
+        ir::Operation,
+        schema::Schema,
+    };
+
+    use super::{Edge, OperatorArgument, Property, QueryBuilder};
+
+    fn numbers_schema() -> Schema {
+        Schema::parse(fs::read_to_string("test_data/schemas/numbers.graphql").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn built_queries_match_parsed_queries() {
+        let schema = numbers_schema();
+        let builder = QueryBuilder::new("Number")
+            .argument("min", 2)
+            .argument("max", 10)
+            .property(Property::new("value").tag_named("start").output())
+            .edge(
+                Edge::new("successor")
+                    .alias("next")
+                    .optional()
+                    .property(Property::new("name").output()),
+            )
+            .edge(
+                Edge::new("multiple")
+                    .argument("max", 3)
+                    .count_filter(Operation::GreaterThan(
+                        (),
+                        OperatorArgument::VariableRef("count".into()),
+                    ))
+                    .count_output_named("multiples")
+                    .property(Property::new("value").output_named("multiple_values")),
+            )
+            .edge(
+                Edge::new("predecessor")
+                    .recurse(2)
+                    .coerce_to("Prime")
+                    .property(Property::new("value").filter(Operation::LessThan(
+                        (),
+                        OperatorArgument::TagRef("start".into()),
+                    ))),
+            );
+
+        let query = r#"
+{
+    Number(min: 2, max: 10) {
+        value @tag(name: "start") @output
+        next: successor @optional {
+            name @output
+        }
+        multiple(max: 3) @fold @transform(op: "count") @filter(op: ">", value: ["$count"])
+                @output(name: "multiples") {
+            value @output(name: "multiple_values")
+        }
+        predecessor @recurse(depth: 2) {
+            ... on Prime {
+                value @filter(op: "<", value: ["%start"])
+            }
+        }
+    }
+}"#;
+        assert_eq!(
+            parse_to_ir(&schema, query).unwrap(),
+            builder.build_ir(&schema).unwrap()
+        );
+        assert_eq!(
+            parse_to_ir(&schema, builder.to_string()).unwrap(),
+            builder.build_ir(&schema).unwrap()
+        );
+    }
+
+    #[test]
+    fn built_queries_are_validated_against_the_schema() {
+        let schema = numbers_schema();
+        let error = QueryBuilder::new("Number")
+            .argument("max", 10)
+            .property(Property::new("vaule").output())
+            .build(&schema)
+            .unwrap_err();
+        assert!(
+            matches!(
+                &error,
+                FrontendError::ValidationError(ValidationError::NonExistentPath(path, suggestions, _))
+                    if path == &["Number", "vaule"] && suggestions == &["value"]
+            ),
+            "{error:?}"
+        );
+
+        let error = QueryBuilder::new("Number")
+            .property(Property::new("value").output())
+            .build(&schema)
+            .unwrap_err();
+        assert!(
+            matches!(error, FrontendError::MissingRequiredEdgeParameter(..)),
+            "{error:?}"
+        );
+    }
+}
@@ -1,8 +1,12 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, fmt::Display, ops::Range};
 
+use async_graphql_parser::Pos;
 use serde::{Deserialize, Serialize};
 
-use crate::{ir::FieldValue, util::DisplayVec};
+use crate::{
+    ir::FieldValue,
+    util::{did_you_mean, DisplayVec},
+};
 
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, thiserror::Error)]
@@ -10,7 +14,7 @@ pub enum FrontendError {
     #[error("{0}")]
     MultipleErrors(DisplayVec<FrontendError>),
 
-    #[error("{0}")]
+    #[error(transparent)]
     ParseError(#[from] crate::graphql_query::error::ParseError),
 
     #[error("Filter on property name \"{0}\" uses undefined tag: %{1}")]
@@ -37,7 +41,7 @@ pub enum FrontendError {
     )]
     UnusedTags(Vec<String>),
 
-    #[error("Multiple fields are being output under the same name: {0:?}")]
+    #[error("Multiple fields are being output under the same name: {0}")]
     MultipleOutputsWithSameName(DuplicatedNamesConflict),
 
     #[error("Multiple fields have @tag directives with the same name: {0}")]
@@ -49,29 +53,32 @@ pub enum FrontendError {
     )]
     ExplicitTagNameRequired(String),
 
-    #[error("Incompatible types encountered in @filter: {0}")]
+    #[error(transparent)]
     FilterTypeError(#[from] FilterTypeError),
 
     #[error("Found an edge with an @output directive, this is not supported: {0}")]
-    UnsupportedEdgeOutput(String),
+    UnsupportedEdgeOutput(String, Span),
 
     #[error("Found an edge with an unsupported @filter directive: {0}")]
-    UnsupportedEdgeFilter(String),
+    UnsupportedEdgeFilter(String, Span),
 
     #[error("Found an unsupported {1} directive on an edge with @fold: {0}")]
-    UnsupportedDirectiveOnFoldedEdge(String, String),
+    UnsupportedDirectiveOnFoldedEdge(String, String, Span),
 
     #[error("Missing required edge parameter {0} on edge {1}")]
-    MissingRequiredEdgeParameter(String, String),
+    MissingRequiredEdgeParameter(String, String, Span),
 
     #[error("Unexpected edge parameter {0} on edge {1}")]
-    UnexpectedEdgeParameter(String, String),
+    UnexpectedEdgeParameter(String, String, Span),
 
     #[error(
         "Invalid value for edge parameter {0} on edge {1}. \
         Expected a value of type {2}, but got: {3:?}"
     )]
-    InvalidEdgeParameterType(String, String, String, FieldValue),
+    InvalidEdgeParameterType(String, String, String, FieldValue, Span),
+
+    #[error("Invalid value for edge parameter {0} on edge {1}: {3}, but got: {2:?}")]
+    EdgeParameterConstraintViolated(String, String, FieldValue, String, Span),
 
     #[error(
         "Invalid use of @recurse on edge \"{0}\". That edge cannot be recursed since it connects \
@@ -101,9 +108,23 @@ pub enum FrontendError {
     EdgeRecursionNeedingMultipleCoercions(String),
 
     #[error("Meta field \"{0}\" is a property but the query uses it as an edge.")]
-    PropertyMetaFieldUsedAsEdge(String),
+    PropertyMetaFieldUsedAsEdge(String, Span),
+
+    #[error("Variable ${0} is declared but never used. Please remove its declaration.")]
+    UnusedDeclaredVariable(String, Span),
+
+    #[error(
+        "Variable ${0} is declared with type {1}, but the query's uses of the variable \
+        require a value of type {2}."
+    )]
+    IncompatibleDeclaredVariableType(String, String, String, Span),
 
-    #[error("The query failed to validate against the schema: {0}")]
+    #[error(
+        "Variable ${0} is declared with type {1}, but its default value is not of that type: {2:?}"
+    )]
+    InvalidVariableDefaultValue(String, String, FieldValue, Span),
+
+    #[error(transparent)]
     ValidationError(#[from] ValidationError),
 
     #[error("Unexpected error: {0}")]
@@ -177,25 +198,169 @@ pub struct DuplicatedNamesConflict {
     pub duplicates: BTreeMap<String, Vec<(String, String)>>,
 }
 
+impl Display for DuplicatedNamesConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, (name, fields)) in self.duplicates.iter().enumerate() {
+            if index > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "\"{name}\" is the name of ")?;
+            for (field_index, (type_name, field_name)) in fields.iter().enumerate() {
+                if field_index > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{type_name}.{field_name}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, thiserror::Error)]
 pub enum ValidationError {
-    #[error("The referenced path does not exist in the schema: {0:?}")]
-    NonExistentPath(Vec<String>),
+    /// The path to the field that does not exist, and the most similar fields that do.
+    #[error("The referenced path does not exist in the schema: {}.{}", .0.join("."), did_you_mean(.1))]
+    NonExistentPath(Vec<String>, Vec<String>, Span),
 
-    #[error("The referenced type does not exist in the schema: {0}")]
-    NonExistentType(String),
+    /// The type that does not exist, and the most similar types that do.
+    #[error("The referenced type does not exist in the schema: {0}.{}", did_you_mean(.1))]
+    NonExistentType(String, Vec<String>, Span),
 
     #[error(
         "Attempted to coerce type {0} into type {1}, but type {0} is not an interface. \
         Only interface types may be coerced to subtypes."
     )]
-    CannotCoerceNonInterfaceType(String, String),
+    CannotCoerceNonInterfaceType(String, String, Span),
 
     #[error(
         "Attempted to coerce type {0} into type {1}, which is not a subtype of {0}. \
         This is not allowed."
     )]
-    CannotCoerceToUnrelatedType(String, String),
+    CannotCoerceToUnrelatedType(String, String, Span),
+}
+
+/// Non-fatal diagnostics produced while compiling a query.
+///
+/// Unlike [`FrontendError`], warnings do not prevent the query from being compiled
+/// and executed. They are attached to the compiled query in [`IRQuery::warnings`].
+///
+/// [`IRQuery::warnings`]: crate::ir::IRQuery::warnings
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, thiserror::Error)]
+pub enum FrontendWarning {
+    #[error("The query uses property \"{1}\" on type \"{0}\", which is deprecated: {2}")]
+    DeprecatedPropertyUsed(String, String, String),
+
+    #[error("The query uses edge \"{1}\" on type \"{0}\", which is deprecated: {2}")]
+    DeprecatedEdgeUsed(String, String, String),
+}
+
+impl FrontendError {
+    /// The individual errors that make up this error.
+    ///
+    /// The frontend reports all the independent problems it finds in a query at once,
+    /// combining them into a [`FrontendError::MultipleErrors`] when there's more than one.
+    /// Any other error is returned as the sole element.
+    pub fn errors(&self) -> &[FrontendError] {
+        match self {
+            Self::MultipleErrors(errors) => &errors.0,
+            _ => std::slice::from_ref(self),
+        }
+    }
+
+    /// The part of the query text that caused this error, if the error can be attributed
+    /// to a single location in the query.
+    ///
+    /// Errors found while parsing the query only know the position at which they start,
+    /// and are reported with an empty span at that position.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Self::UnsupportedEdgeOutput(.., span)
+            | Self::UnsupportedEdgeFilter(.., span)
+            | Self::UnsupportedDirectiveOnFoldedEdge(.., span)
+            | Self::MissingRequiredEdgeParameter(.., span)
+            | Self::UnexpectedEdgeParameter(.., span)
+            | Self::InvalidEdgeParameterType(.., span)
+            | Self::EdgeParameterConstraintViolated(.., span)
+            | Self::PropertyMetaFieldUsedAsEdge(.., span)
+            | Self::UnusedDeclaredVariable(.., span)
+            | Self::IncompatibleDeclaredVariableType(.., span)
+            | Self::InvalidVariableDefaultValue(.., span) => Some(*span),
+            Self::ValidationError(e) => Some(e.span()),
+            Self::ParseError(e) => e.position().map(Span::empty),
+            _ => None,
+        }
+    }
+}
+
+impl ValidationError {
+    /// The part of the query text that failed to validate.
+    pub fn span(&self) -> Span {
+        match self {
+            Self::NonExistentPath(.., span)
+            | Self::NonExistentType(.., span)
+            | Self::CannotCoerceNonInterfaceType(.., span)
+            | Self::CannotCoerceToUnrelatedType(.., span) => *span,
+        }
+    }
+}
+
+/// A range of the query text, such as the name of a field, to which an error refers.
+///
+/// Positions are the 1-based line and column numbers reported by the GraphQL parser,
+/// with columns counted in bytes. The end position is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Span {
+    pub start: Pos,
+    pub end: Pos,
+}
+
+impl Span {
+    pub fn new(start: Pos, end: Pos) -> Self {
+        Self { start, end }
+    }
+
+    /// An empty span at the given position.
+    pub fn empty(pos: Pos) -> Self {
+        Self::new(pos, pos)
+    }
+
+    /// The span of the given token, if it starts at the given position.
+    pub(crate) fn of_token(start: Pos, token: &str) -> Self {
+        let end = Pos {
+            line: start.line,
+            column: start.column + token.len(),
+        };
+        Self::new(start, end)
+    }
+
+    /// The range of byte offsets that this span covers in the given query text,
+    /// or `None` if the span lies outside the text.
+    pub fn byte_range(&self, query: &str) -> Option<Range<usize>> {
+        let start = byte_offset(query, self.start)?;
+        let end = byte_offset(query, self.end)?;
+        Some(start..end)
+    }
+}
+
+fn byte_offset(text: &str, pos: Pos) -> Option<usize> {
+    if pos.line == 0 || pos.column == 0 {
+        return None;
+    }
+
+    let mut line_start = 0;
+    for (index, line) in text.split_inclusive('\n').enumerate() {
+        if index + 1 == pos.line {
+            // Spans that end at the end of the line end just past its last byte.
+            let offset = pos.column - 1;
+            return (offset <= line.len() && line.is_char_boundary(offset))
+                .then_some(line_start + offset);
+        }
+        line_start += line.len();
+    }
+
+    // The position just past the end of the text, on an otherwise empty last line.
+    (pos.line == text.split_inclusive('\n').count() + 1 && pos.column == 1).then_some(text.len())
 }
 
 impl From<async_graphql_parser::Error> for FrontendError {
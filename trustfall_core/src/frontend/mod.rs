@@ -1,11 +1,15 @@
 //! Frontend for Trustfall: takes a parsed query, validates it, and turns it into IR.
 #![allow(dead_code, unused_variables, unused_mut)]
 use std::{
-    collections::BTreeMap, convert::TryFrom, iter::successors, num::NonZeroUsize, sync::Arc,
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+    iter::successors,
+    num::NonZeroUsize,
+    sync::Arc,
 };
 
 use async_graphql_parser::{
-    types::{BaseType, ExecutableDocument, FieldDefinition, Type, TypeDefinition, TypeKind},
+    types::{ExecutableDocument, FieldDefinition, TypeDefinition, TypeKind},
     Positioned,
 };
 use async_graphql_value::Name;
@@ -14,53 +18,63 @@ use smallvec::SmallVec;
 use crate::{
     graphql_query::{
         directives::{FilterDirective, FoldGroup, OperatorArgument, RecurseDirective},
-        query::{parse_document, FieldConnection, FieldNode, Query},
+        query::{parse_document, parse_query_text, FieldConnection, FieldNode, Query},
     },
     ir::{
-        types::{intersect_types, is_argument_type_valid, NamedTypedValue},
-        Argument, ContextField, EdgeParameters, Eid, FieldRef, FieldValue, FoldSpecificField,
-        FoldSpecificFieldKind, IREdge, IRFold, IRQuery, IRQueryComponent, IRVertex, IndexedQuery,
-        LocalField, Operation, Recursive, TransformationKind, VariableRef, Vid,
-        TYPENAME_META_FIELD, TYPENAME_META_FIELD_ARC, TYPENAME_META_FIELD_NAME,
+        types::{intersect_types, NamedTypedValue},
+        Argument, ContextField, EdgeParameters, Eid, FieldCost, FieldRef, FieldValue,
+        FoldSpecificField, FoldSpecificFieldKind, IREdge, IRFold, IRQuery, IRQueryComponent,
+        IRVertex, IndexedQuery, LocalField, Operation, Recursive, TransformationKind, Type,
+        VariableRef, Vid, TYPENAME_META_FIELD, TYPENAME_META_FIELD_ARC, TYPENAME_META_FIELD_NAME,
         TYPENAME_META_FIELD_TYPE,
     },
-    schema::{FieldOrigin, Schema, BUILTIN_SCALARS},
-    util::{BTreeMapTryInsertExt, TryCollectUniqueKey},
+    schema::{get_field_cost, get_parameter_constraints, FieldOrigin, Schema, BUILTIN_SCALARS},
+    util::{similar_names, BTreeMapTryInsertExt, TryCollectUniqueKey},
 };
 
 use self::{
-    error::{DuplicatedNamesConflict, FilterTypeError, FrontendError, ValidationError},
+    error::{DuplicatedNamesConflict, FilterTypeError, FrontendError, Span, ValidationError},
     outputs::OutputHandler,
+    restrictions::collect_restricted_fields,
     tags::{TagHandler, TagLookupError},
-    util::{get_underlying_named_type, ComponentPath},
+    util::{field_span, get_underlying_named_type, ComponentPath},
     validation::validate_query_against_schema,
+    variables::apply_variable_declarations,
+    warnings::collect_query_warnings,
 };
 
+pub mod builder;
 pub mod error;
 mod outputs;
+mod restrictions;
 mod tags;
 mod util;
 mod validation;
+mod variables;
+mod warnings;
 
 /// Parses a query string to the Trustfall IR using a provided
 /// [Schema](crate::schema::Schema). May fail if [parse_to_ir](parse_to_ir)
 /// fails for the provided schema and query.
 pub fn parse(schema: &Schema, query: impl AsRef<str>) -> Result<Arc<IndexedQuery>, FrontendError> {
     let ir_query = parse_to_ir(schema, query)?;
+    Ok(make_indexed_query(schema, ir_query))
+}
 
+/// Index a query freshly generated by the frontend.
+fn make_indexed_query(schema: &Schema, ir_query: IRQuery) -> Arc<IndexedQuery> {
     // .unwrap() must be safe here, since freshly-generated IRQuery objects must always
     // be safe to convert to IndexedQuery. This is a try_into() instead of into() because
     // IRQuery is Serialize/Deserialize and may therefore have been edited (e.g. by hand)
     // before being converted into IndexedQuery.
     let indexed_query: IndexedQuery = ir_query.try_into().unwrap();
 
-    Ok(Arc::from(indexed_query))
+    Arc::from(indexed_query.with_custom_scalars(schema.custom_scalars.clone()))
 }
 
 /// Parses a query string to IR using a [Schema](crate::schema::Schema)
 pub fn parse_to_ir<T: AsRef<str>>(schema: &Schema, query: T) -> Result<IRQuery, FrontendError> {
-    let document = async_graphql_parser::parse_query(query)?;
-    let q = parse_document(&document)?;
+    let q = parse_query_text(query.as_ref())?;
     make_ir_for_query(schema, &q)
 }
 
@@ -70,15 +84,16 @@ pub fn parse_doc(schema: &Schema, document: &ExecutableDocument) -> Result<IRQue
 }
 
 fn get_field_name_and_type_from_schema<'a>(
+    schema: &Schema,
     defined_fields: &'a [Positioned<FieldDefinition>],
     field_node: &FieldNode,
-) -> (&'a Name, Arc<str>, Arc<str>, &'a Type) {
+) -> (&'a Name, Arc<str>, Arc<str>, Type) {
     if field_node.name.as_ref() == TYPENAME_META_FIELD {
         return (
             &TYPENAME_META_FIELD_NAME,
             TYPENAME_META_FIELD_ARC.clone(),
             TYPENAME_META_FIELD_ARC.clone(),
-            &TYPENAME_META_FIELD_TYPE,
+            TYPENAME_META_FIELD_TYPE.clone(),
         );
     }
 
@@ -86,10 +101,11 @@ fn get_field_name_and_type_from_schema<'a>(
         let field_name = &defined_field.node.name.node;
         let field_raw_type = &defined_field.node.ty.node;
         if field_name.as_ref() == field_node.name.as_ref() {
-            let pre_coercion_type_name: Arc<str> =
-                get_underlying_named_type(field_raw_type).to_string().into();
+            let pre_coercion_type_name = schema
+                .interner
+                .name(get_underlying_named_type(field_raw_type));
             let post_coercion_type_name = if let Some(coerced_to) = &field_node.coerced_to {
-                coerced_to.clone()
+                schema.interner.name(coerced_to)
             } else {
                 pre_coercion_type_name.clone()
             };
@@ -97,7 +113,7 @@ fn get_field_name_and_type_from_schema<'a>(
                 field_name,
                 pre_coercion_type_name,
                 post_coercion_type_name,
-                field_raw_type,
+                schema.interner.ir_type(field_raw_type),
             );
         }
     }
@@ -108,10 +124,16 @@ fn get_field_name_and_type_from_schema<'a>(
 fn get_vertex_type_definition_from_schema<'a>(
     schema: &'a Schema,
     vertex_type_name: &str,
+    span: Span,
 ) -> Result<&'a TypeDefinition, FrontendError> {
     schema.vertex_types.get(vertex_type_name).ok_or_else(|| {
         FrontendError::ValidationError(ValidationError::NonExistentType(
             vertex_type_name.to_owned(),
+            similar_names(
+                vertex_type_name,
+                schema.vertex_types.keys().map(|name| name.as_ref()),
+            ),
+            span,
         ))
     })
 }
@@ -145,9 +167,17 @@ fn get_vertex_field_definitions<'a>(
 }
 
 fn make_edge_parameters(
+    schema: &Schema,
     edge_definition: &FieldDefinition,
-    specified_arguments: &BTreeMap<Arc<str>, FieldValue>,
+    connection: &FieldConnection,
 ) -> Result<EdgeParameters, Vec<FrontendError>> {
+    let specified_arguments = &connection.arguments;
+    let span = field_span(
+        connection.position,
+        &connection.name,
+        connection.alias.as_deref(),
+    );
+
     let mut errors: Vec<FrontendError> = vec![];
 
     let mut edge_arguments: BTreeMap<Arc<str>, FieldValue> = BTreeMap::new();
@@ -167,7 +197,7 @@ fn make_edge_parameters(
 
                         // The default value must be a valid type for the parameter,
                         // otherwise the schema itself is invalid.
-                        assert!(is_argument_type_valid(&arg.node.ty.node, &value));
+                        assert!(schema.is_parameter_value_valid(&arg.node.ty.node, &value));
 
                         value
                     })
@@ -181,13 +211,25 @@ fn make_edge_parameters(
             }
             Some(value) => {
                 // Type-check the supplied value against the schema.
-                if !is_argument_type_valid(&arg.node.ty.node, value) {
+                if !schema.is_parameter_value_valid(&arg.node.ty.node, value) {
                     errors.push(FrontendError::InvalidEdgeParameterType(
                         arg_name.to_string(),
                         edge_definition.name.node.to_string(),
                         arg.node.ty.to_string(),
                         value.clone(),
+                        span,
                     ));
+                } else if let Some(constraints) = get_parameter_constraints(&arg.node) {
+                    // The value's type is valid, so check it against the schema's constraints.
+                    if let Err(violation) = constraints.check(value) {
+                        errors.push(FrontendError::EdgeParameterConstraintViolated(
+                            arg_name.to_string(),
+                            edge_definition.name.node.to_string(),
+                            value.clone(),
+                            violation.to_string(),
+                            span,
+                        ));
+                    }
                 }
                 Some(value.clone())
             }
@@ -198,11 +240,12 @@ fn make_edge_parameters(
                 errors.push(FrontendError::MissingRequiredEdgeParameter(
                     arg_name.to_string(),
                     edge_definition.name.node.to_string(),
+                    span,
                 ));
             }
             Some(value) => {
                 edge_arguments
-                    .insert_or_error(arg_name.to_owned().into(), value)
+                    .insert_or_error(schema.interner.name(arg_name), value)
                     .unwrap(); // Duplicates should have been caught at parse time.
             }
         }
@@ -216,6 +259,7 @@ fn make_edge_parameters(
             errors.push(FrontendError::UnexpectedEdgeParameter(
                 specified_argument_name.to_string(),
                 edge_definition.name.node.to_string(),
+                span,
             ))
         }
     }
@@ -228,6 +272,7 @@ fn make_edge_parameters(
 }
 
 fn infer_variable_type(
+    schema: &Schema,
     property_name: &str,
     property_type: &Type,
     operation: &Operation<(), OperatorArgument>,
@@ -250,39 +295,28 @@ fn infer_variable_type(
             // Using a "null" valued variable doesn't make sense as a comparison.
             // However, [[1], [2], null] is a valid value to use in the comparison, since
             // there are definitely values that it is smaller than or bigger than.
-            Ok(Type {
-                base: property_type.base.clone(),
-                nullable: false,
-            })
+            Ok(property_type.with_nullability(false))
         }
         Operation::Contains(..) | Operation::NotContains(..) => {
             // To be able to check whether the property's value contains the operand,
             // the property needs to be a list. If it's not a list, this is a bad filter.
-            let inner_type = match &property_type.base {
-                BaseType::Named(_) => {
-                    return Err(Box::new(
-                        FilterTypeError::ListFilterOperationOnNonListField(
-                            operation.operation_name().to_string(),
-                            property_name.to_string(),
-                            property_type.to_string(),
-                        ),
-                    ))
-                }
-                BaseType::List(inner) => inner.as_ref(),
-            };
+            let inner_type = property_type.element_type().ok_or_else(|| {
+                Box::new(FilterTypeError::ListFilterOperationOnNonListField(
+                    operation.operation_name().to_string(),
+                    property_name.to_string(),
+                    property_type.to_string(),
+                ))
+            })?;
 
             // We're trying to see if a list of element contains our element, so its type
             // is whatever is inside the list -- nullable or not.
-            Ok(inner_type.clone())
+            Ok(inner_type)
         }
         Operation::OneOf(..) | Operation::NotOneOf(..) => {
             // Whatever the property's type is, the argument must be a non-nullable list of
             // the same type, so that the elements of that list may be checked for equality
             // against that property's value.
-            Ok(Type {
-                base: BaseType::List(Box::new(property_type.clone())),
-                nullable: false,
-            })
+            Ok(Type::list_of(property_type, false))
         }
         Operation::HasPrefix(..)
         | Operation::NotHasPrefix(..)
@@ -293,10 +327,7 @@ fn infer_variable_type(
         | Operation::RegexMatches(..)
         | Operation::NotRegexMatches(..) => {
             // Filtering operations involving strings only take non-nullable strings as inputs.
-            Ok(Type {
-                base: BaseType::Named(Name::new("String")),
-                nullable: false,
-            })
+            Ok(schema.interner.named_type("String"))
         }
         Operation::IsNull(..) | Operation::IsNotNull(..) => {
             // These are unary operations, there's no place where a variable can be used.
@@ -350,6 +381,7 @@ fn make_filter_expr<LeftT: NamedTypedValue>(
                     OperatorArgument::VariableRef(var_name) => Argument::Variable(VariableRef {
                         variable_name: var_name.clone(),
                         variable_type: infer_variable_type(
+                            schema,
                             left_operand.named(),
                             left_operand.typed(),
                             &filter_directive.operation,
@@ -397,7 +429,9 @@ fn make_filter_expr<LeftT: NamedTypedValue>(
         _ => None,
     };
 
-    if let Err(e) = filter_operation.operand_types_valid(maybe_tag_name) {
+    if let Err(e) =
+        filter_operation.operand_types_valid(maybe_tag_name, |ty| schema.is_base_type_orderable(ty))
+    {
         Err(e.into_iter().map(|x| x.into()).collect())
     } else {
         Ok(filter_operation)
@@ -423,12 +457,13 @@ pub fn make_ir_for_query(schema: &Schema, query: &Query) -> Result<IRQuery, Fron
     let mut errors: Vec<FrontendError> = vec![];
 
     let (root_field_name, root_field_pre_coercion_type, root_field_post_coercion_type, _) =
-        get_field_name_and_type_from_schema(&schema.query_type.fields, &query.root_field);
+        get_field_name_and_type_from_schema(schema, &schema.query_type.fields, &query.root_field);
     let starting_vid = vid_maker.next().unwrap();
 
     let root_parameters = make_edge_parameters(
+        schema,
         get_edge_definition_from_schema(schema, schema.query_type_name(), root_field_name.as_ref()),
-        &query.root_connection.arguments,
+        &query.root_connection,
     );
 
     let mut component_path = ComponentPath::new(starting_vid);
@@ -461,8 +496,13 @@ pub fn make_ir_for_query(schema: &Schema, query: &Query) -> Result<IRQuery, Fron
         }
     };
     let mut variables: BTreeMap<Arc<str>, Type> = Default::default();
-    if let Err(v) = fill_in_query_variables(&mut variables, &root_component) {
-        errors.extend(v.into_iter().map(|x| x.into()));
+    let mut variable_defaults = Default::default();
+    match fill_in_query_variables(&mut variables, &root_component) {
+        Ok(()) => match apply_variable_declarations(&query.variables, &mut variables) {
+            Ok(defaults) => variable_defaults = defaults,
+            Err(e) => errors.extend(e),
+        },
+        Err(v) => errors.extend(v.into_iter().map(|x| x.into())),
     }
 
     if let Err(e) = tags.finish() {
@@ -471,6 +511,7 @@ pub fn make_ir_for_query(schema: &Schema, query: &Query) -> Result<IRQuery, Fron
         ));
     }
 
+    let output_descriptions = output_handler.take_descriptions();
     let all_outputs = output_handler.finish();
     if let Err(duplicates) = check_for_duplicate_output_names(all_outputs) {
         let all_vertices = collect_ir_vertices(&root_component);
@@ -479,17 +520,62 @@ pub fn make_ir_for_query(schema: &Schema, query: &Query) -> Result<IRQuery, Fron
     }
 
     if errors.is_empty() {
+        let enum_values = collect_enum_values(schema, &variables, &root_component);
+        let restricted_fields =
+            collect_restricted_fields(schema, root_field_name.as_ref(), &root_component);
         Ok(IRQuery {
-            root_name: root_field_name.as_ref().to_owned().into(),
+            root_name: schema.interner.name(root_field_name),
             root_parameters: root_parameters.unwrap(),
             root_component: root_component.into(),
             variables,
+            variable_defaults,
+            enum_values,
+            output_descriptions,
+            warnings: collect_query_warnings(schema, query),
+            restricted_fields,
         })
     } else {
         Err(errors.into())
     }
 }
 
+/// Collect the declared values of each enum type used by the query's variables and outputs,
+/// so they can be checked when the query is executed.
+fn collect_enum_values(
+    schema: &Schema,
+    variables: &BTreeMap<Arc<str>, Type>,
+    root_component: &IRQueryComponent,
+) -> BTreeMap<Arc<str>, BTreeSet<Arc<str>>> {
+    let mut used_types: BTreeSet<&str> = variables.values().map(Type::base_type).collect();
+    collect_output_type_names(&mut used_types, root_component);
+
+    used_types
+        .into_iter()
+        .filter_map(|type_name| {
+            schema.enum_values(type_name).map(|values| {
+                (
+                    Arc::from(type_name),
+                    values.map(Arc::from).collect::<BTreeSet<_>>(),
+                )
+            })
+        })
+        .collect()
+}
+
+fn collect_output_type_names<'a>(result: &mut BTreeSet<&'a str>, component: &'a IRQueryComponent) {
+    result.extend(
+        component
+            .outputs
+            .values()
+            .map(|field| field.field_type.base_type()),
+    );
+
+    component
+        .folds
+        .values()
+        .for_each(move |fold| collect_output_type_names(result, &fold.component))
+}
+
 fn collect_ir_vertices(root_component: &IRQueryComponent) -> BTreeMap<Vid, IRVertex> {
     let mut result = Default::default();
     collect_ir_vertices_recursive_step(&mut result, root_component);
@@ -643,7 +729,7 @@ where
     #[allow(clippy::type_complexity)]
     let mut properties: BTreeMap<
         (Vid, Arc<str>),
-        (Arc<str>, &'schema Type, SmallVec<[&'query FieldNode; 1]>),
+        (Arc<str>, Type, SmallVec<[&'query FieldNode; 1]>),
     > = Default::default();
 
     output_handler.begin_subcomponent();
@@ -708,9 +794,9 @@ where
             from_vertex_type.as_ref(),
             field_connection.name.as_ref(),
         );
-        let edge_name = edge_definition.name.node.as_ref().to_owned().into();
+        let edge_name = schema.interner.name(&edge_definition.name.node);
 
-        let parameters_result = make_edge_parameters(edge_definition, &field_connection.arguments);
+        let parameters_result = make_edge_parameters(schema, edge_definition, field_connection);
 
         let optional = field_connection.optional.is_some();
         let recursive = match field_connection.recurse.as_ref() {
@@ -743,6 +829,7 @@ where
                         parameters,
                         optional,
                         recursive,
+                        cost: get_field_cost(edge_definition),
                     }
                     .into(),
                 );
@@ -870,10 +957,10 @@ fn get_recurse_implicit_coercion(
     }
 
     // Case 4, check whether the destination type also has an edge by that name.
-    let edge_name: Arc<str> = Arc::from(edge_definition.name.node.as_ref());
+    let edge_name = schema.interner.name(&edge_definition.name.node);
     let destination_edge = schema
         .fields
-        .get(&(Arc::from(destination_type), edge_name.clone()));
+        .get(&(schema.interner.name(destination_type), edge_name.clone()));
     match destination_edge {
         Some(destination_edge) => {
             // The destination type has that edge too.
@@ -919,13 +1006,10 @@ fn get_recurse_implicit_coercion(
 
 #[allow(clippy::too_many_arguments)]
 #[allow(clippy::type_complexity)]
-fn make_vertex<'schema, 'query>(
-    schema: &'schema Schema,
+fn make_vertex<'query>(
+    schema: &Schema,
     property_names_by_vertex: &BTreeMap<Vid, Vec<Arc<str>>>,
-    properties: &BTreeMap<
-        (Vid, Arc<str>),
-        (Arc<str>, &'schema Type, SmallVec<[&'query FieldNode; 1]>),
-    >,
+    properties: &BTreeMap<(Vid, Arc<str>), (Arc<str>, Type, SmallVec<[&'query FieldNode; 1]>)>,
     tags: &mut TagHandler,
     component_path: &ComponentPath,
     vid: Vid,
@@ -939,10 +1023,16 @@ fn make_vertex<'schema, 'query>(
     //
     // If the current vertex is not the root of a fold, then outputs are not allowed
     // and we should report an error.
+    let span = field_span(
+        field_node.position,
+        &field_node.name,
+        field_node.alias.as_deref(),
+    );
     let is_fold_root = component_path.is_component_root(vid);
     if !is_fold_root && !field_node.output.is_empty() {
         errors.push(FrontendError::UnsupportedEdgeOutput(
             field_node.name.as_ref().to_owned(),
+            span,
         ));
     }
 
@@ -950,6 +1040,7 @@ fn make_vertex<'schema, 'query>(
         // TODO: If @filter on edges is allowed, tweak this.
         errors.push(FrontendError::UnsupportedEdgeFilter(
             field_node.name.as_ref().to_owned(),
+            span,
         ));
     }
 
@@ -962,9 +1053,9 @@ fn make_vertex<'schema, 'query>(
         },
         |coerced_to_type| {
             let coerced_type =
-                get_vertex_type_definition_from_schema(schema, coerced_to_type.as_ref())?;
+                get_vertex_type_definition_from_schema(schema, coerced_to_type.as_ref(), span)?;
             Ok((
-                coerced_type.name.node.as_ref().to_owned().into(),
+                schema.interner.name(&coerced_type.name.node),
                 Some(uncoerced_type_name.clone()),
             ))
         },
@@ -976,7 +1067,7 @@ fn make_vertex<'schema, 'query>(
         }
     };
 
-    let mut filters = vec![];
+    let mut filters: SmallVec<[_; 1]> = SmallVec::new();
     for property_name in property_names_by_vertex.get(&vid).into_iter().flatten() {
         let (_, property_type, property_fields) =
             properties.get(&(vid, property_name.clone())).unwrap();
@@ -1003,6 +1094,16 @@ fn make_vertex<'schema, 'query>(
         }
     }
 
+    // Evaluate filters on cheaper properties first, so that expensive properties
+    // only need to be resolved for vertices that pass the cheaper filters.
+    filters.sort_by_key(|filter| {
+        schema
+            .fields
+            .get(&(type_name.clone(), filter.left().field_name.clone()))
+            .and_then(get_field_cost)
+            .map_or(1, |cost| cost.estimate())
+    });
+
     if errors.is_empty() {
         Ok(IRVertex {
             vid,
@@ -1026,10 +1127,7 @@ fn fill_in_vertex_data<'schema, 'query, V, E>(
     edges: &mut BTreeMap<Eid, (Vid, Vid, &'query FieldConnection)>,
     folds: &mut BTreeMap<Eid, Arc<IRFold>>,
     property_names_by_vertex: &mut BTreeMap<Vid, Vec<Arc<str>>>,
-    properties: &mut BTreeMap<
-        (Vid, Arc<str>),
-        (Arc<str>, &'schema Type, SmallVec<[&'query FieldNode; 1]>),
-    >,
+    properties: &mut BTreeMap<(Vid, Arc<str>), (Arc<str>, Type, SmallVec<[&'query FieldNode; 1]>)>,
     component_path: &mut ComponentPath,
     output_handler: &mut OutputHandler<'query>,
     tags: &mut TagHandler<'query>,
@@ -1058,7 +1156,7 @@ where
             subfield_pre_coercion_type,
             subfield_post_coercion_type,
             subfield_raw_type,
-        ) = get_field_name_and_type_from_schema(defined_fields, subfield);
+        ) = get_field_name_and_type_from_schema(schema, defined_fields, subfield);
 
         if schema
             .vertex_types
@@ -1076,12 +1174,14 @@ where
                     errors.push(FrontendError::UnsupportedDirectiveOnFoldedEdge(
                         subfield.name.to_string(),
                         "@optional".to_owned(),
+                        field_span(subfield.position, &subfield.name, subfield.alias.as_deref()),
                     ));
                 }
                 if connection.recurse.is_some() {
                     errors.push(FrontendError::UnsupportedDirectiveOnFoldedEdge(
                         subfield.name.to_string(),
                         "@recurse".to_owned(),
+                        field_span(subfield.position, &subfield.name, subfield.alias.as_deref()),
                     ));
                 }
 
@@ -1090,7 +1190,7 @@ where
                     post_coercion_type.as_ref(),
                     connection.name.as_ref(),
                 );
-                match make_edge_parameters(edge_definition, &connection.arguments) {
+                match make_edge_parameters(schema, edge_definition, connection) {
                     Ok(edge_parameters) => {
                         match make_fold(
                             schema,
@@ -1102,8 +1202,9 @@ where
                             tags,
                             fold_group,
                             next_eid,
-                            edge_definition.name.node.as_str().to_owned().into(),
+                            schema.interner.name(&edge_definition.name.node),
                             edge_parameters,
+                            get_field_cost(edge_definition),
                             current_vid,
                             next_vid,
                             subfield_pre_coercion_type,
@@ -1155,11 +1256,14 @@ where
             || schema
                 .scalars
                 .contains_key(subfield_post_coercion_type.as_ref())
+            || schema
+                .enums
+                .contains_key(subfield_post_coercion_type.as_ref())
             || subfield_name.as_ref() == TYPENAME_META_FIELD
         {
             // Processing a property.
 
-            let subfield_name: Arc<str> = subfield_name.as_ref().to_owned().into();
+            let subfield_name = schema.interner.name(subfield_name);
             let key = (current_vid, subfield_name.clone());
             properties
                 .entry(key)
@@ -1174,14 +1278,18 @@ where
                         .or_default()
                         .push(subfield_name.clone());
 
-                    (subfield_name, subfield_raw_type, SmallVec::from([subfield]))
+                    (
+                        subfield_name.clone(),
+                        subfield_raw_type.clone(),
+                        SmallVec::from([subfield]),
+                    )
                 });
 
             for output_directive in &subfield.output {
                 // TODO: handle outputs of non-fold-related transformed fields here.
                 let field_ref = FieldRef::ContextField(ContextField {
                     vertex_id: current_vid,
-                    field_name: subfield.name.clone(),
+                    field_name: subfield_name.clone(),
                     field_type: subfield_raw_type.clone(),
                 });
 
@@ -1193,15 +1301,23 @@ where
                 // otherwise. The local name is appended to any prefixes given as aliases
                 // applied to the edges whose scopes enclose the output.
                 if let Some(explicit_name) = output_directive.name.as_ref() {
-                    output_handler
-                        .register_explicitly_named_output(explicit_name.clone(), field_ref);
+                    output_handler.register_explicitly_named_output(
+                        explicit_name.clone(),
+                        field_ref,
+                        subfield.description.as_ref(),
+                    );
                 } else {
                     let local_name = subfield
                         .alias
                         .as_ref()
                         .map(|x| x.as_ref())
                         .unwrap_or_else(|| subfield.name.as_ref());
-                    output_handler.register_locally_named_output(local_name, None, field_ref);
+                    output_handler.register_locally_named_output(
+                        local_name,
+                        None,
+                        field_ref,
+                        subfield.description.as_ref(),
+                    );
                 }
             }
 
@@ -1223,8 +1339,8 @@ where
                     });
                 let tag_field = ContextField {
                     vertex_id: current_vid,
-                    field_name: subfield.name.clone(),
-                    field_type: subfield_raw_type.to_owned(),
+                    field_name: subfield_name.clone(),
+                    field_type: subfield_raw_type.clone(),
                 };
 
                 // TODO: handle tags on non-fold-related transformed fields here
@@ -1261,6 +1377,7 @@ fn make_fold<'schema, 'query, V, E>(
     fold_eid: Eid,
     edge_name: Arc<str>,
     edge_parameters: EdgeParameters,
+    edge_cost: Option<FieldCost>,
     parent_vid: Vid,
     starting_vid: Vid,
     starting_pre_coercion_type: Arc<str>,
@@ -1298,10 +1415,15 @@ where
         // If it had a @transform then the output would have been in the field's transform group.
         errors.push(FrontendError::UnsupportedEdgeOutput(
             starting_field.name.as_ref().to_owned(),
+            field_span(
+                starting_field.position,
+                &starting_field.name,
+                starting_field.alias.as_deref(),
+            ),
         ));
     }
 
-    let mut post_filters = vec![];
+    let mut post_filters = SmallVec::new();
     let mut fold_specific_outputs = BTreeMap::new();
 
     if let Some(transform_group) = &fold_group.transform {
@@ -1334,8 +1456,11 @@ where
         for output in &transform_group.output {
             let final_output_name = match output.name.as_ref() {
                 Some(explicit_name) => {
-                    output_handler
-                        .register_explicitly_named_output(explicit_name.clone(), field_ref.clone());
+                    output_handler.register_explicitly_named_output(
+                        explicit_name.clone(),
+                        field_ref.clone(),
+                        starting_field.description.as_ref(),
+                    );
                     explicit_name.clone()
                 }
                 None => {
@@ -1352,6 +1477,7 @@ where
                         local_name,
                         Some(&[fold_specific_field.kind.transform_suffix()]),
                         field_ref.clone(),
+                        starting_field.description.as_ref(),
                     )
                 }
             };
@@ -1401,22 +1527,32 @@ where
         imported_tags,
         post_filters,
         fold_specific_outputs,
+        cost: edge_cost,
     })
 }
 
 #[cfg(test)]
 mod tests {
     use std::{
+        collections::BTreeMap,
         fs,
         path::{Path, PathBuf},
+        sync::Arc,
     };
 
+    use async_graphql_parser::Pos;
     use trustfall_filetests_macros::parameterize;
 
     use crate::{
-        frontend::make_ir_for_query,
+        frontend::{
+            error::{FrontendError, FrontendWarning, Span, ValidationError},
+            make_ir_for_query,
+        },
+        graphql_query::error::ParseError,
+        ir::{FieldCost, FieldValue, Latency},
         schema::Schema,
         test_types::{TestIRQuery, TestIRQueryResult, TestParsedGraphQLQueryResult},
+        util::DisplayVec,
     };
 
     lazy_static! {
@@ -1434,6 +1570,19 @@ mod tests {
                 .unwrap();
     }
 
+    fn span(line: usize, start_column: usize, end_column: usize) -> Span {
+        Span::new(
+            Pos {
+                line,
+                column: start_column,
+            },
+            Pos {
+                line,
+                column: end_column,
+            },
+        )
+    }
+
     #[test]
     fn test_schemas_load_correctly() {
         // We want to merely touch the lazy-static variables so they get initialized.
@@ -1444,6 +1593,451 @@ mod tests {
         assert!(!RECURSES_SCHEMA.vertex_types.is_empty());
     }
 
+    #[test]
+    fn edge_costs_and_cheaper_filters_first() {
+        let schema = Schema::parse(
+            fs::read_to_string("test_data/tests/valid_schemas/edge_costs.graphql").unwrap(),
+        )
+        .unwrap();
+
+        let query = r#"
+{
+    Package(name: "trustfall") {
+        downloads @filter(op: ">", value: ["$min"])
+        name @filter(op: "=", value: ["$name"]) @output
+
+        releases {
+            version @output
+        }
+    }
+}"#;
+        let ir_query = super::parse_to_ir(&schema, query).unwrap();
+        let root_vertex = &ir_query.root_component.vertices[&ir_query.root_component.root];
+        let filtered_properties: Vec<_> = root_vertex
+            .filters
+            .iter()
+            .map(|filter| filter.left().field_name.as_ref())
+            .collect();
+        assert_eq!(vec!["name", "downloads"], filtered_properties);
+
+        let edge = ir_query.root_component.edges.values().next().unwrap();
+        assert_eq!(
+            Some(FieldCost {
+                fanout: Some(1000),
+                latency: Some(Latency::High),
+            }),
+            edge.cost
+        );
+    }
+
+    #[test]
+    fn omitted_edge_parameters_use_schema_defaults() {
+        let schema = Schema::parse(
+            fs::read_to_string("test_data/tests/valid_schemas/edge_parameter_defaults.graphql")
+                .unwrap(),
+        )
+        .unwrap();
+
+        let query = r#"
+{
+    Package(name: "trustfall") {
+        versions(since: "0.4.0") {
+            version @output
+        }
+    }
+}"#;
+        let ir_query = super::parse_to_ir(&schema, query).unwrap();
+        let edge = ir_query.root_component.edges.values().next().unwrap();
+        let parameters: BTreeMap<_, _> = edge
+            .parameters
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect();
+        assert_eq!(
+            btreemap! {
+                "limit".to_string() => FieldValue::Int64(10),
+                "order".to_string() => FieldValue::Enum("DESC".to_string()),
+                "prerelease".to_string() => FieldValue::Null,
+                "since".to_string() => FieldValue::String("0.4.0".to_string()),
+            },
+            parameters,
+        );
+
+        let query = r#"
+{
+    Package(name: "trustfall") {
+        versions(order: SIDEWAYS) {
+            version @output
+        }
+    }
+}"#;
+        assert_eq!(
+            Err(FrontendError::InvalidEdgeParameterType(
+                "order".to_string(),
+                "versions".to_string(),
+                "Order".to_string(),
+                FieldValue::Enum("SIDEWAYS".to_string()),
+                span(4, 9, 17),
+            )),
+            super::parse_to_ir(&schema, query),
+        );
+    }
+
+    #[test]
+    fn edge_parameter_values_must_satisfy_constraints() {
+        let schema = Schema::parse(
+            fs::read_to_string("test_data/tests/valid_schemas/parameter_constraints.graphql")
+                .unwrap(),
+        )
+        .unwrap();
+
+        let query = r#"
+{
+    Package(name: "trustfall") {
+        versions(limit: 100, order: ASC, channel: "beta", minScore: 0.5) {
+            version @output
+        }
+    }
+}"#;
+        assert!(super::parse_to_ir(&schema, query).is_ok());
+
+        let query = r#"
+{
+    Package(name: "") {
+        versions(limit: 0, order: DESC, channel: "nightly", minScore: 2.0) {
+            version @output
+        }
+    }
+}"#;
+        assert_eq!(
+            Err(FrontendError::MultipleErrors(DisplayVec(vec![
+                FrontendError::EdgeParameterConstraintViolated(
+                    "name".to_string(),
+                    "Package".to_string(),
+                    FieldValue::String(String::new()),
+                    "the value must not be empty".to_string(),
+                    span(3, 5, 12),
+                ),
+                FrontendError::EdgeParameterConstraintViolated(
+                    "limit".to_string(),
+                    "versions".to_string(),
+                    FieldValue::Int64(0),
+                    "the value must be at least Int64(1)".to_string(),
+                    span(4, 9, 17),
+                ),
+                FrontendError::EdgeParameterConstraintViolated(
+                    "channel".to_string(),
+                    "versions".to_string(),
+                    FieldValue::String("nightly".to_string()),
+                    "the value must be one of [String(\"stable\"), String(\"beta\")]".to_string(),
+                    span(4, 9, 17),
+                ),
+                FrontendError::EdgeParameterConstraintViolated(
+                    "minScore".to_string(),
+                    "versions".to_string(),
+                    FieldValue::Float64(2.0),
+                    "the value must be at most Float64(1.5)".to_string(),
+                    span(4, 9, 17),
+                ),
+            ]))),
+            super::parse_to_ir(&schema, query),
+        );
+
+        let query = r#"
+{
+    Packages(names: []) {
+        name @output
+    }
+}"#;
+        assert_eq!(
+            Err(FrontendError::EdgeParameterConstraintViolated(
+                "names".to_string(),
+                "Packages".to_string(),
+                FieldValue::List(vec![]),
+                "the value must not be empty".to_string(),
+                span(3, 5, 13),
+            )),
+            super::parse_to_ir(&schema, query),
+        );
+    }
+
+    #[test]
+    fn errors_point_to_the_offending_query_text() {
+        let schema = Schema::parse(
+            fs::read_to_string("test_data/tests/valid_schemas/parameter_constraints.graphql")
+                .unwrap(),
+        )
+        .unwrap();
+
+        // The non-ASCII string literal precedes the offending field on the same line,
+        // so the field's column differs from its character count.
+        let query = r#"
+{
+    Package(name: "ünïcödé") { nonexistent @output }
+}"#;
+        let error = super::parse_to_ir(&schema, query).unwrap_err();
+        assert!(matches!(
+            error,
+            FrontendError::ValidationError(ValidationError::NonExistentPath(..))
+        ));
+        let error_span = error.span().unwrap();
+        assert_eq!(span(3, 36, 47), error_span);
+        assert_eq!("nonexistent", &query[error_span.byte_range(query).unwrap()]);
+
+        let query = r#"
+{
+    Package(name: "trustfall") {
+        latest: versions(limit: 1000) {
+            version @output
+        }
+    }
+}"#;
+        let error = super::parse_to_ir(&schema, query).unwrap_err();
+        let error_span = error.span().unwrap();
+        assert_eq!("latest", &query[error_span.byte_range(query).unwrap()]);
+
+        // Parse errors only know where they start.
+        let query = r#"
+{
+    Package(name: "trustfall") @filter(op: "=", value: ["$name"]) {
+        name @output
+    }
+}"#;
+        let error = super::parse_to_ir(&schema, query).unwrap_err();
+        let error_span = error.span().unwrap();
+        assert_eq!(error_span.start, error_span.end);
+        assert!(error_span.byte_range(query).is_some());
+    }
+
+    #[test]
+    fn independent_errors_are_reported_together() {
+        let query = r#"
+{
+    OriginDirectory {
+        nmae @output
+        out_Directory_ContainsFile {
+            ... on ImageFile {
+                width @output
+            }
+        }
+        out_Directory_Subdirectory {
+            NonExistent {
+                name @output
+            }
+        }
+    }
+}"#;
+        let error = super::parse_to_ir(&FILESYSTEM_SCHEMA, query).unwrap_err();
+        let errors = error.errors();
+        assert_eq!(3, errors.len(), "{error:?}");
+
+        let reported: Vec<_> = errors
+            .iter()
+            .map(|e| &query[e.span().unwrap().byte_range(query).unwrap()])
+            .collect();
+        assert_eq!(
+            vec!["nmae", "out_Directory_ContainsFile", "NonExistent"],
+            reported
+        );
+
+        // A single error is its own list of errors.
+        let query = r#"
+{
+    OriginDirectory {
+        nmae @output
+    }
+}"#;
+        let error = super::parse_to_ir(&FILESYSTEM_SCHEMA, query).unwrap_err();
+        assert_eq!(std::slice::from_ref(&error), error.errors());
+    }
+
+    #[test]
+    fn unknown_names_come_with_suggestions() {
+        let schema = &*NUMBERS_SCHEMA;
+
+        let query = r#"
+{
+    Number(max: 5) {
+        vaule @output
+    }
+}"#;
+        let error = super::parse_to_ir(schema, query).unwrap_err();
+        let FrontendError::ValidationError(ValidationError::NonExistentPath(_, suggestions, _)) =
+            &error
+        else {
+            panic!("unexpected error: {error:?}");
+        };
+        assert_eq!(&["value".to_string()], suggestions.as_slice());
+        assert!(
+            error.to_string().ends_with(r#"Did you mean "value"?"#),
+            "{error}"
+        );
+
+        let query = r#"
+{
+    Number(max: 5) {
+        ... on Prim {
+            value @output
+        }
+    }
+}"#;
+        let error = super::parse_to_ir(schema, query).unwrap_err();
+        let FrontendError::ValidationError(ValidationError::NonExistentType(_, suggestions, _)) =
+            &error
+        else {
+            panic!("unexpected error: {error:?}");
+        };
+        assert_eq!(&["Prime".to_string()], suggestions.as_slice());
+
+        let query = r#"
+{
+    Number(max: 5) {
+        value @otput
+    }
+}"#;
+        let error = super::parse_to_ir(schema, query).unwrap_err();
+        let FrontendError::ParseError(ParseError::UnrecognizedDirective(_, suggestions, _)) =
+            &error
+        else {
+            panic!("unexpected error: {error:?}");
+        };
+        assert_eq!(&["output".to_string()], suggestions.as_slice());
+
+        // Names that aren't close to anything in the schema get no suggestions.
+        let query = r#"
+{
+    Number(max: 5) {
+        somethingElseEntirely @output
+    }
+}"#;
+        let error = super::parse_to_ir(schema, query).unwrap_err();
+        assert!(!error.to_string().contains("Did you mean"), "{error}");
+    }
+
+    #[test]
+    fn errors_have_readable_messages() {
+        let schema = &*NUMBERS_SCHEMA;
+
+        let query = r#"
+{
+    Number(max: 5) {
+        value @output
+        successor {
+            value @output
+        }
+    }
+}"#;
+        let error = super::parse_to_ir(schema, query).unwrap_err();
+        assert_eq!(
+            "Multiple fields are being output under the same name: \
+            \"value\" is the name of Number.value, Number.value",
+            error.to_string(),
+        );
+
+        let query = r#"
+{
+    Number(max: 5) {
+        successor {
+            vaule @output
+        }
+    }
+}"#;
+        let error = super::parse_to_ir(schema, query).unwrap_err();
+        assert!(
+            error.to_string().ends_with(
+                "The referenced path does not exist in the schema: \
+                Number.successor.vaule. Did you mean \"value\"?"
+            ),
+            "{error}"
+        );
+
+        // Parse errors are reported as-is, without a prefix of their own.
+        let query = "{ Number(max: 5) { value @otput } }";
+        let FrontendError::ParseError(parse_error) = super::parse_to_ir(schema, query).unwrap_err()
+        else {
+            panic!("expected a parse error");
+        };
+        let error = FrontendError::from(parse_error.clone());
+        assert_eq!(parse_error.to_string(), error.to_string());
+    }
+
+    #[test]
+    fn enum_values_used_by_query_are_recorded() {
+        let schema = Schema::parse(
+            fs::read_to_string("test_data/tests/valid_schemas/enum_properties.graphql").unwrap(),
+        )
+        .unwrap();
+        let query = r#"
+{
+    Shape {
+        name @output @filter(op: "=", value: ["$name"])
+        accents @output
+    }
+}"#;
+
+        let ir_query = super::parse_to_ir(&schema, query).unwrap();
+        assert_eq!(
+            btreemap! {
+                Arc::from("Color") => btreeset![Arc::from("RED"), Arc::from("GREEN"), Arc::from("BLUE")],
+            },
+            ir_query.enum_values,
+        );
+    }
+
+    #[test]
+    fn deprecated_field_uses_produce_warnings() {
+        let schema = Schema::parse(
+            fs::read_to_string("test_data/tests/valid_schemas/deprecated_fields.graphql").unwrap(),
+        )
+        .unwrap();
+        let query = r#"
+{
+    OldBase {
+        newField @output
+        field @output
+        neighbor {
+            otherField @output
+        }
+    }
+}"#;
+
+        let ir_query = super::parse_to_ir(&schema, query).unwrap();
+        assert_eq!(
+            vec![
+                FrontendWarning::DeprecatedEdgeUsed(
+                    "RootSchemaQuery".to_string(),
+                    "OldBase".to_string(),
+                    "Use Base instead.".to_string(),
+                ),
+                FrontendWarning::DeprecatedPropertyUsed(
+                    "Base".to_string(),
+                    "field".to_string(),
+                    "No longer supported".to_string(),
+                ),
+                FrontendWarning::DeprecatedEdgeUsed(
+                    "Base".to_string(),
+                    "neighbor".to_string(),
+                    "No longer supported".to_string(),
+                ),
+                FrontendWarning::DeprecatedPropertyUsed(
+                    "Base".to_string(),
+                    "otherField".to_string(),
+                    "Use field instead.".to_string(),
+                ),
+            ],
+            ir_query.warnings,
+        );
+
+        let query = r#"
+{
+    Base {
+        newField @output
+    }
+}"#;
+        let ir_query = super::parse_to_ir(&schema, query).unwrap();
+        assert!(ir_query.warnings.is_empty());
+    }
+
     #[parameterize("trustfall_core/test_data/tests/frontend_errors")]
     fn frontend_errors(base: &Path, stem: &str) {
         parameterizable_tester(base, stem, ".frontend-error.ron")
@@ -12,6 +12,7 @@ pub(super) struct OutputHandler<'query> {
     root_prefix: Option<&'query str>,
     component_outputs_stack: Vec<BTreeMap<Arc<str>, Vec<FieldRef>>>,
     global_outputs: BTreeMap<Arc<str>, Vec<FieldRef>>,
+    descriptions: BTreeMap<Arc<str>, Arc<str>>,
 }
 
 impl<'query> OutputHandler<'query> {
@@ -23,6 +24,7 @@ impl<'query> OutputHandler<'query> {
             root_prefix,
             component_outputs_stack: Default::default(),
             global_outputs: Default::default(),
+            descriptions: Default::default(),
         }
     }
 
@@ -60,7 +62,11 @@ impl<'query> OutputHandler<'query> {
         Arc::from(name)
     }
 
-    fn register_output(&mut self, name: Arc<str>, value: FieldRef) {
+    fn register_output(&mut self, name: Arc<str>, value: FieldRef, description: Option<&Arc<str>>) {
+        if let Some(description) = description {
+            self.descriptions.insert(name.clone(), description.clone());
+        }
+
         self.component_outputs_stack
             .last_mut()
             .expect("stack was unexpectedly empty")
@@ -76,9 +82,10 @@ impl<'query> OutputHandler<'query> {
         local_name: &str,
         transforms: Option<&[&str]>,
         value: FieldRef,
+        description: Option<&Arc<str>>,
     ) -> Arc<str> {
         let complete_name = self.make_output_name(local_name, transforms);
-        self.register_output(complete_name.clone(), value);
+        self.register_output(complete_name.clone(), value, description);
         complete_name
     }
 
@@ -86,8 +93,14 @@ impl<'query> OutputHandler<'query> {
         &mut self,
         explicit_name: Arc<str>,
         value: FieldRef,
+        description: Option<&Arc<str>>,
     ) {
-        self.register_output(explicit_name, value)
+        self.register_output(explicit_name, value, description)
+    }
+
+    /// The descriptions of the outputs registered so far, by output name.
+    pub(super) fn take_descriptions(&mut self) -> BTreeMap<Arc<str>, Arc<str>> {
+        std::mem::take(&mut self.descriptions)
     }
 
     pub(crate) fn finish(self) -> BTreeMap<Arc<str>, Vec<FieldRef>> {
@@ -0,0 +1,130 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use crate::{
+    ir::{
+        Argument, ContextField, FieldRef, IRQueryComponent, IRVertex, RestrictedFieldUse, Vid,
+        TYPENAME_META_FIELD,
+    },
+    schema::{get_field_restriction, Schema},
+};
+
+/// Collects the uses of `@restricted` schema fields in the query,
+/// so that access policies can be applied before the query is executed.
+pub(super) fn collect_restricted_fields(
+    schema: &Schema,
+    root_edge: &str,
+    root_component: &IRQueryComponent,
+) -> Vec<RestrictedFieldUse> {
+    let mut vertices: BTreeMap<Vid, &IRVertex> = Default::default();
+    collect_vertices(&mut vertices, root_component);
+
+    let mut collector = RestrictionCollector {
+        schema,
+        vertices,
+        uses: Default::default(),
+    };
+    collector.use_edge(schema.query_type_name(), root_edge);
+    collector.visit_component(root_component);
+
+    collector.uses.into_values().collect()
+}
+
+fn collect_vertices<'a>(result: &mut BTreeMap<Vid, &'a IRVertex>, component: &'a IRQueryComponent) {
+    result.extend(
+        component
+            .vertices
+            .iter()
+            .map(|(vid, vertex)| (*vid, vertex)),
+    );
+    for fold in component.folds.values() {
+        collect_vertices(result, &fold.component);
+    }
+}
+
+struct RestrictionCollector<'a> {
+    schema: &'a Schema,
+    vertices: BTreeMap<Vid, &'a IRVertex>,
+    uses: BTreeMap<(Arc<str>, Arc<str>), RestrictedFieldUse>,
+}
+
+impl<'a> RestrictionCollector<'a> {
+    /// Returns the use record for the field if it's restricted, creating it if necessary.
+    fn get_use(
+        &mut self,
+        type_name: &str,
+        field_name: &str,
+        is_edge: bool,
+    ) -> Option<&mut RestrictedFieldUse> {
+        if field_name == TYPENAME_META_FIELD {
+            return None;
+        }
+
+        let key: (Arc<str>, Arc<str>) = (type_name.into(), field_name.into());
+        let field_defn = &self.schema.fields[&key];
+        let restriction = get_field_restriction(field_defn)?;
+
+        Some(
+            self.uses
+                .entry(key.clone())
+                .or_insert_with(|| RestrictedFieldUse {
+                    type_name: key.0,
+                    field_name: key.1,
+                    scope: restriction.scope.map(Arc::from),
+                    is_edge,
+                    outputs: Default::default(),
+                    used_in_filters: false,
+                }),
+        )
+    }
+
+    fn use_edge(&mut self, type_name: &str, edge_name: &str) {
+        self.get_use(type_name, edge_name, true);
+    }
+
+    fn use_field_in_filter(&mut self, field: &ContextField) {
+        let type_name = self.vertices[&field.vertex_id].type_name.clone();
+        if let Some(field_use) = self.get_use(&type_name, &field.field_name, false) {
+            field_use.used_in_filters = true;
+        }
+    }
+
+    fn use_tagged_argument(&mut self, argument: Option<&Argument>) {
+        if let Some(Argument::Tag(FieldRef::ContextField(field))) = argument {
+            self.use_field_in_filter(field);
+        }
+    }
+
+    fn visit_component(&mut self, component: &IRQueryComponent) {
+        for vertex in component.vertices.values() {
+            for filter in &vertex.filters {
+                if let Some(field_use) =
+                    self.get_use(&vertex.type_name, &filter.left().field_name, false)
+                {
+                    field_use.used_in_filters = true;
+                }
+                self.use_tagged_argument(filter.right());
+            }
+        }
+
+        for edge in component.edges.values() {
+            let from_type = self.vertices[&edge.from_vid].type_name.clone();
+            self.use_edge(&from_type, &edge.edge_name);
+        }
+
+        for fold in component.folds.values() {
+            let from_type = self.vertices[&fold.from_vid].type_name.clone();
+            self.use_edge(&from_type, &fold.edge_name);
+            for filter in &fold.post_filters {
+                self.use_tagged_argument(filter.right());
+            }
+            self.visit_component(&fold.component);
+        }
+
+        for (output_name, field) in &component.outputs {
+            let type_name = self.vertices[&field.vertex_id].type_name.clone();
+            if let Some(field_use) = self.get_use(&type_name, &field.field_name, false) {
+                field_use.outputs.insert(output_name.clone());
+            }
+        }
+    }
+}
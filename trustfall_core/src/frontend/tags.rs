@@ -3,6 +3,8 @@ use std::{
     fmt::Debug,
 };
 
+use smallvec::SmallVec;
+
 use super::util::ComponentPath;
 use crate::{
     ir::{FieldRef, Vid},
@@ -13,7 +15,7 @@ use crate::{
 pub(super) struct TagHandler<'a> {
     tags: BTreeMap<&'a str, TagEntry<'a>>,
     used_tags: BTreeSet<&'a str>,
-    component_imported_tags: Vec<(Vid, Vec<FieldRef>)>,
+    component_imported_tags: Vec<(Vid, SmallVec<[FieldRef; 1]>)>,
 }
 
 #[derive(Debug, Clone)]
@@ -48,10 +50,11 @@ impl<'a> TagHandler<'a> {
     }
 
     pub(super) fn begin_subcomponent(&mut self, component_root: Vid) {
-        self.component_imported_tags.push((component_root, vec![]));
+        self.component_imported_tags
+            .push((component_root, SmallVec::new()));
     }
 
-    pub(super) fn end_subcomponent(&mut self, component_root: Vid) -> Vec<FieldRef> {
+    pub(super) fn end_subcomponent(&mut self, component_root: Vid) -> SmallVec<[FieldRef; 1]> {
         let (expected_vid, external_tags) = self.component_imported_tags.pop().unwrap();
         assert_eq!(expected_vid, component_root);
         external_tags
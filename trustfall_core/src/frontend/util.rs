@@ -1,10 +1,15 @@
 use std::ops::Index;
 
-use async_graphql_parser::types::{BaseType, Type};
+use async_graphql_parser::{
+    types::{BaseType, Type},
+    Pos,
+};
 use async_graphql_value::Name;
 
 use crate::ir::Vid;
 
+use super::error::Span;
+
 /// Retrieves the underlying type name by looping through any [list
 /// types](BaseType::List) until a [named type](Type) is found
 pub(super) fn get_underlying_named_type(t: &Type) -> &Name {
@@ -17,6 +22,12 @@ pub(super) fn get_underlying_named_type(t: &Type) -> &Name {
     }
 }
 
+/// The span of a field in the query text. The field's position is that of its alias
+/// if it has one, so the span covers the alias in that case and the field's name otherwise.
+pub(super) fn field_span(position: Pos, name: &str, alias: Option<&str>) -> Span {
+    Span::of_token(position, alias.unwrap_or(name))
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub(super) struct ComponentPath {
     path: Vec<Vid>,
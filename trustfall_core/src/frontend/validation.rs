@@ -6,11 +6,12 @@ use crate::{
     graphql_query::query::{FieldConnection, FieldNode, Query},
     ir::TYPENAME_META_FIELD,
     schema::Schema,
+    util::similar_names,
 };
 
 use super::{
-    error::{FrontendError, ValidationError},
-    util::get_underlying_named_type,
+    error::{FrontendError, Span, ValidationError},
+    util::{field_span, get_underlying_named_type},
 };
 
 pub(super) fn validate_query_against_schema(
@@ -18,39 +19,94 @@ pub(super) fn validate_query_against_schema(
     query: &Query,
 ) -> Result<(), FrontendError> {
     let mut path = vec![];
+    let mut errors = vec![];
     validate_field(
         schema,
         schema.query_type_name(),
         &mut path,
         &query.root_connection,
         &query.root_field,
-    )
+        &mut errors,
+    );
+    assert!(path.is_empty());
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.into())
+    }
 }
 
+/// Validate the field and, if it is valid, its subfields, recording any errors found.
+///
+/// Errors in one field don't prevent its sibling fields from being validated,
+/// but the subfields of an invalid field are skipped since they'd have no type to check against.
 fn validate_field<'a>(
-    schema: &Schema,
+    schema: &'a Schema,
     parent_type_name: &str,
     path: &mut Vec<&'a str>,
     connection: &FieldConnection,
     node: &'a FieldNode,
-) -> Result<(), FrontendError> {
+    errors: &mut Vec<FrontendError>,
+) {
     // TODO: Maybe consider a better representation that doesn't have this duplication?
     assert_eq!(connection.name, node.name);
     assert_eq!(connection.alias, node.alias);
+    let span = field_span(node.position, &node.name, node.alias.as_deref());
 
     if node.name.as_ref() == TYPENAME_META_FIELD {
         // This is a meta field of scalar "String!" type that is guaranteed to exist.
         // We just have to make sure that it's used as a property, and not as an edge.
         if !node.connections.is_empty() {
-            return Err(FrontendError::PropertyMetaFieldUsedAsEdge(
+            errors.push(FrontendError::PropertyMetaFieldUsedAsEdge(
                 TYPENAME_META_FIELD.to_string(),
+                span,
             ));
         }
 
-        return Ok(());
+        return;
     }
 
     let old_path_length = path.len();
+    path.push(&node.name);
+
+    match get_field_type_name(schema, parent_type_name, path, node, span) {
+        Ok(field_type_name) => {
+            if let Some(coerced) = &node.coerced_to {
+                path.push(coerced);
+            }
+
+            for (child_connection, child_node) in node.connections.iter() {
+                validate_field(
+                    schema,
+                    field_type_name,
+                    path,
+                    child_connection,
+                    child_node,
+                    errors,
+                );
+            }
+
+            if node.coerced_to.is_some() {
+                path.pop().unwrap();
+            }
+        }
+        Err(e) => errors.push(e),
+    }
+
+    path.pop().unwrap();
+    assert_eq!(old_path_length, path.len());
+}
+
+/// Look up the field in the schema, returning the name of the vertex type
+/// its subfields should be validated against, after applying any type coercion.
+fn get_field_type_name<'a>(
+    schema: &'a Schema,
+    parent_type_name: &str,
+    path: &[&str],
+    node: &'a FieldNode,
+    span: Span,
+) -> Result<&'a str, FrontendError> {
     let field_def = schema
         .fields
         .get(&(
@@ -58,71 +114,73 @@ fn validate_field<'a>(
             Arc::from(node.name.to_string()),
         ))
         .ok_or_else(|| {
-            path.push(&node.name);
+            let candidates = schema
+                .fields
+                .keys()
+                .filter(|(type_name, _)| type_name.as_ref() == parent_type_name)
+                .map(|(_, field_name)| field_name.as_ref())
+                .chain([TYPENAME_META_FIELD]);
             FrontendError::ValidationError(ValidationError::NonExistentPath(
                 path.iter().map(|x| x.to_string()).collect(),
+                similar_names(&node.name, candidates),
+                span,
             ))
         })?;
 
-    path.push(&node.name);
-
     let pre_coercion_type_name = get_underlying_named_type(&field_def.ty.node).as_ref();
-    let field_type_name = if let Some(coerced) = &node.coerced_to {
-        let pre_coercion_type_definition = &schema.vertex_types[pre_coercion_type_name];
-        if let TypeKind::Interface(_) = &pre_coercion_type_definition.kind {
-        } else {
-            // Only interface types may be coerced into other types. This is not an interface.
-            return Err(FrontendError::ValidationError(
-                ValidationError::CannotCoerceNonInterfaceType(
-                    pre_coercion_type_name.to_string(),
-                    coerced.to_string(),
-                ),
-            ));
-        }
-
-        if let Some(post_coercion_type_definition) = schema.vertex_types.get(coerced) {
-            let implemented_interfaces = match &post_coercion_type_definition.kind {
-                TypeKind::Object(o) => &o.implements,
-                TypeKind::Interface(i) => &i.implements,
-                TypeKind::Scalar
-                | TypeKind::Union(_)
-                | TypeKind::Enum(_)
-                | TypeKind::InputObject(_) => unreachable!(),
-            };
-            if !implemented_interfaces
-                .iter()
-                .any(|x| x.node.as_ref() == pre_coercion_type_name)
-            {
-                // The specified coerced-to type does not implement the source interface.
-                return Err(FrontendError::ValidationError(
-                    ValidationError::CannotCoerceToUnrelatedType(
-                        pre_coercion_type_name.to_string(),
-                        coerced.to_string(),
-                    ),
-                ));
-            }
-        } else {
-            // The coerced-to type is not part of the schema.
-            return Err(FrontendError::ValidationError(
-                ValidationError::NonExistentType(coerced.to_string()),
-            ));
-        }
-
-        path.push(coerced);
-        coerced.as_ref()
-    } else {
-        pre_coercion_type_name
+    let Some(coerced) = &node.coerced_to else {
+        return Ok(pre_coercion_type_name);
     };
 
-    for (child_connection, child_node) in node.connections.iter() {
-        validate_field(schema, field_type_name, path, child_connection, child_node)?;
+    let pre_coercion_type_definition = &schema.vertex_types[pre_coercion_type_name];
+    if let TypeKind::Interface(_) = &pre_coercion_type_definition.kind {
+    } else {
+        // Only interface types may be coerced into other types. This is not an interface.
+        return Err(FrontendError::ValidationError(
+            ValidationError::CannotCoerceNonInterfaceType(
+                pre_coercion_type_name.to_string(),
+                coerced.to_string(),
+                span,
+            ),
+        ));
     }
 
-    path.pop().unwrap();
-    if node.coerced_to.is_some() {
-        path.pop().unwrap();
+    let Some(post_coercion_type_definition) = schema.vertex_types.get(coerced) else {
+        // The coerced-to type is not part of the schema.
+        // Only subtypes of the pre-coercion type are worth suggesting as alternatives.
+        let candidates = schema
+            .subtypes(pre_coercion_type_name)
+            .into_iter()
+            .flatten();
+        return Err(FrontendError::ValidationError(
+            ValidationError::NonExistentType(
+                coerced.to_string(),
+                similar_names(coerced, candidates),
+                span,
+            ),
+        ));
+    };
+
+    let implemented_interfaces = match &post_coercion_type_definition.kind {
+        TypeKind::Object(o) => &o.implements,
+        TypeKind::Interface(i) => &i.implements,
+        TypeKind::Scalar | TypeKind::Union(_) | TypeKind::Enum(_) | TypeKind::InputObject(_) => {
+            unreachable!()
+        }
+    };
+    if !implemented_interfaces
+        .iter()
+        .any(|x| x.node.as_ref() == pre_coercion_type_name)
+    {
+        // The specified coerced-to type does not implement the source interface.
+        return Err(FrontendError::ValidationError(
+            ValidationError::CannotCoerceToUnrelatedType(
+                pre_coercion_type_name.to_string(),
+                coerced.to_string(),
+                span,
+            ),
+        ));
     }
-    assert_eq!(old_path_length, path.len());
 
-    Ok(())
+    Ok(coerced.as_ref())
 }
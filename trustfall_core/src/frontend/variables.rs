@@ -0,0 +1,75 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use crate::{
+    graphql_query::query::VariableDefinition,
+    ir::{
+        types::{is_argument_type_valid, is_builtin_value_type},
+        FieldValue, Type,
+    },
+};
+
+use super::error::{FrontendError, Span};
+
+/// Applies the variable declarations in the query's header to the variable types
+/// inferred from the variables' uses, and collects the declared default values.
+///
+/// Each declared variable must be used, and its declared type must be acceptable to every
+/// one of its uses, in which case the declared type replaces the inferred one.
+/// Variables that aren't declared keep their inferred types.
+///
+/// Default values of built-in scalar types are checked here. The default values of
+/// enums and custom scalars are checked when the query is executed, the same way
+/// as the values provided for those variables.
+pub(super) fn apply_variable_declarations(
+    declarations: &[VariableDefinition],
+    variables: &mut BTreeMap<Arc<str>, Type>,
+) -> Result<BTreeMap<Arc<str>, FieldValue>, Vec<FrontendError>> {
+    let mut errors = vec![];
+    let mut defaults: BTreeMap<Arc<str>, FieldValue> = Default::default();
+
+    for declaration in declarations {
+        let span = Span::of_token(declaration.position, &format!("${}", declaration.name));
+        let declared_type = &declaration.variable_type;
+
+        let Some(inferred_type) = variables.get_mut(&declaration.name) else {
+            errors.push(FrontendError::UnusedDeclaredVariable(
+                declaration.name.to_string(),
+                span,
+            ));
+            continue;
+        };
+
+        if !declared_type.is_scalar_only_subtype_of(inferred_type) {
+            errors.push(FrontendError::IncompatibleDeclaredVariableType(
+                declaration.name.to_string(),
+                declared_type.to_string(),
+                inferred_type.to_string(),
+                span,
+            ));
+            continue;
+        }
+
+        if let Some(default_value) = &declaration.default_value {
+            if is_builtin_value_type(declared_type.base_type())
+                && !is_argument_type_valid(declared_type, default_value)
+            {
+                errors.push(FrontendError::InvalidVariableDefaultValue(
+                    declaration.name.to_string(),
+                    declared_type.to_string(),
+                    default_value.clone(),
+                    span,
+                ));
+                continue;
+            }
+            defaults.insert(declaration.name.clone(), default_value.clone());
+        }
+
+        *inferred_type = declared_type.clone();
+    }
+
+    if errors.is_empty() {
+        Ok(defaults)
+    } else {
+        Err(errors)
+    }
+}
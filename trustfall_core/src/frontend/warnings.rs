@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use crate::{
+    graphql_query::query::{FieldNode, Query},
+    ir::TYPENAME_META_FIELD,
+    schema::{get_deprecation_reason, Schema},
+};
+
+use super::{error::FrontendWarning, util::get_underlying_named_type};
+
+/// Collects the non-fatal diagnostics for a query, in the order the query mentions them.
+///
+/// The query must have already been validated against the schema.
+pub(super) fn collect_query_warnings(schema: &Schema, query: &Query) -> Vec<FrontendWarning> {
+    let mut warnings = vec![];
+    collect_field_warnings(
+        schema,
+        schema.query_type_name(),
+        &query.root_field,
+        &mut warnings,
+    );
+    warnings
+}
+
+fn collect_field_warnings(
+    schema: &Schema,
+    parent_type_name: &str,
+    node: &FieldNode,
+    warnings: &mut Vec<FrontendWarning>,
+) {
+    if node.name.as_ref() == TYPENAME_META_FIELD {
+        return;
+    }
+
+    let field_def = &schema.fields[&(Arc::from(parent_type_name), Arc::from(node.name.as_ref()))];
+    let field_type_name = get_underlying_named_type(&field_def.ty.node).as_ref();
+    let is_edge = schema.vertex_types.contains_key(field_type_name);
+
+    if let Some(reason) = get_deprecation_reason(field_def) {
+        let (type_name, field_name, reason) = (
+            parent_type_name.to_string(),
+            node.name.to_string(),
+            reason.to_string(),
+        );
+        warnings.push(if is_edge {
+            FrontendWarning::DeprecatedEdgeUsed(type_name, field_name, reason)
+        } else {
+            FrontendWarning::DeprecatedPropertyUsed(type_name, field_name, reason)
+        });
+    }
+
+    let child_parent_type_name = node
+        .coerced_to
+        .as_ref()
+        .map(|x| x.as_ref())
+        .unwrap_or(field_type_name);
+    for (_, child_node) in node.connections.iter() {
+        collect_field_warnings(schema, child_parent_type_name, child_node, warnings);
+    }
+}
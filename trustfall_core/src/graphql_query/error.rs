@@ -3,11 +3,14 @@ use async_graphql_parser::Pos;
 use async_graphql_value::Value;
 use serde::{ser::Error as SerError, Deserialize, Serialize, Serializer};
 
+use crate::util::did_you_mean;
+
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, thiserror::Error)]
 pub enum ParseError {
-    #[error("Unrecognized directive {0}")]
-    UnrecognizedDirective(String, Pos),
+    /// The unrecognized directive's name, and the most similar supported directives.
+    #[error("Unrecognized directive {0}.{}", did_you_mean(.1))]
+    UnrecognizedDirective(String, Vec<String>, Pos),
 
     #[error("Directive in unsupported position {0}: {1}")]
     UnsupportedDirectivePosition(String, String, Pos),
@@ -64,7 +67,7 @@ pub enum ParseError {
         skip_deserializing,
         serialize_with = "fail_serialize_invalid_graphql_error"
     )]
-    #[error("{0}")]
+    #[error(transparent)]
     InvalidGraphQL(async_graphql_parser::Error),
 
     #[error("Unsupported syntax feature found: {0}")]
@@ -82,10 +85,50 @@ pub enum ParseError {
     #[error("Edge {1} specifies a duplicated parameter {0}")]
     DuplicatedEdgeParameter(String, String, Pos),
 
+    #[error("Variable ${0} is declared more than once")]
+    DuplicatedVariableDefinition(String, Pos),
+
+    #[error("Variable ${0} is declared with an invalid default value: {1}")]
+    InvalidVariableDefaultValue(String, Value, Pos),
+
     #[error("Unexpected error: {0}")]
     OtherError(String, Pos),
 }
 
+impl ParseError {
+    /// The position in the query text at which the error was found, if known.
+    pub fn position(&self) -> Option<Pos> {
+        match self {
+            Self::UnrecognizedDirective(.., pos)
+            | Self::UnsupportedDirectivePosition(.., pos)
+            | Self::MissingRequiredDirectiveArgument(.., pos)
+            | Self::UnrecognizedDirectiveArgument(.., pos)
+            | Self::DuplicatedDirectiveArgument(.., pos)
+            | Self::InappropriateTypeForDirectiveArgument(.., pos)
+            | Self::InvalidFieldArgument(.., pos)
+            | Self::DocumentContainsNonInlineFragments(pos)
+            | Self::MultipleOperationsInDocument(pos)
+            | Self::MultipleQueryRoots(pos)
+            | Self::UnsupportedQueryRoot(.., pos)
+            | Self::DirectiveNotInsideQueryRoot(.., pos)
+            | Self::DocumentNotAQuery(pos)
+            | Self::UnsupportedFilterOperator(.., pos)
+            | Self::UnsupportedTransformOperator(.., pos)
+            | Self::InvalidOutputName(.., pos)
+            | Self::InvalidTagName(.., pos)
+            | Self::UnsupportedSyntax(.., pos)
+            | Self::NestedTypeCoercion(pos)
+            | Self::TypeCoercionWithSiblingFields(pos)
+            | Self::UnsupportedDuplicatedDirective(.., pos)
+            | Self::DuplicatedEdgeParameter(.., pos)
+            | Self::DuplicatedVariableDefinition(.., pos)
+            | Self::InvalidVariableDefaultValue(.., pos)
+            | Self::OtherError(.., pos) => Some(*pos),
+            Self::InvalidGraphQL(e) => e.positions().next(),
+        }
+    }
+}
+
 fn fail_serialize_invalid_graphql_error<S: Serializer>(
     _: &async_graphql_parser::Error,
     _: S,
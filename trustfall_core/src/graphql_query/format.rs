@@ -0,0 +1,361 @@
+//! Formatting queries as text in a canonical style.
+use std::fmt::{self, Write};
+
+use crate::ir::{FieldValue, TransformationKind};
+
+use super::{
+    directives::{
+        FilterDirective, OperatorArgument, OutputDirective, TagDirective, TransformGroup,
+    },
+    error::ParseError,
+    query::{parse_query_text, FieldConnection, FieldNode, Query},
+};
+
+const INDENT: &str = "    ";
+
+/// Parse a query and format it in a canonical style.
+///
+/// Queries that parse into the same query produce the same text, and formatting
+/// an already-formatted query leaves it unchanged. In the canonical style:
+/// - Each field is on its own line, indented by four spaces per level of nesting.
+///   Fields stay in their original order, since `@tag` directives must precede their uses.
+/// - Edge arguments are sorted by name.
+/// - Edge directives come first, in the order `@optional`, `@recurse`, `@fold`.
+///   They are followed by the field's `@filter`, `@tag`, and `@output` directives in that order,
+///   the same order also being used after each `@transform` for the directives applied to
+///   the transformed value.
+/// - Optional directive arguments are written only if they were specified.
+/// - Variable declarations, if any, are written in their original order on the first line.
+///
+/// Comments are not preserved, except for the ones describing fields, which are written
+/// on the lines preceding their fields.
+pub fn format_query(query: &str) -> Result<String, ParseError> {
+    let query = parse_query_text(query)?;
+    Ok(query.to_string())
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.variables.is_empty() {
+            f.write_str("query(")?;
+            for (index, variable) in self.variables.iter().enumerate() {
+                if index > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "${}: {}", variable.name, variable.variable_type)?;
+                if let Some(default_value) = &variable.default_value {
+                    f.write_str(" = ")?;
+                    write_value(f, default_value)?;
+                }
+            }
+            f.write_str(") ")?;
+        }
+        writeln!(f, "{{")?;
+        write_field(f, &self.root_connection, &self.root_field, 1)?;
+        write!(f, "}}")
+    }
+}
+
+fn write_indent(f: &mut impl Write, depth: usize) -> fmt::Result {
+    for _ in 0..depth {
+        f.write_str(INDENT)?;
+    }
+    Ok(())
+}
+
+fn write_field(
+    f: &mut impl Write,
+    connection: &FieldConnection,
+    node: &FieldNode,
+    depth: usize,
+) -> fmt::Result {
+    if let Some(description) = &node.description {
+        for line in description.lines() {
+            write_indent(f, depth)?;
+            if line.is_empty() {
+                f.write_str("#\n")?;
+            } else {
+                writeln!(f, "# {line}")?;
+            }
+        }
+    }
+
+    write_indent(f, depth)?;
+    if let Some(alias) = &connection.alias {
+        write!(f, "{alias}: ")?;
+    }
+    f.write_str(&connection.name)?;
+
+    if !connection.arguments.is_empty() {
+        f.write_char('(')?;
+        for (index, (name, value)) in connection.arguments.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{name}: ")?;
+            write_value(f, value)?;
+        }
+        f.write_char(')')?;
+    }
+
+    if connection.optional.is_some() {
+        f.write_str(" @optional")?;
+    }
+    if let Some(recurse) = &connection.recurse {
+        write!(f, " @recurse(depth: {})", recurse.depth)?;
+    }
+    if let Some(fold) = &connection.fold {
+        f.write_str(" @fold")?;
+        if let Some(transform) = &fold.transform {
+            write_transform_group(f, transform)?;
+        }
+    }
+
+    write_value_directives(f, &node.filter, &node.tag, &node.output)?;
+    // The transforms of folded edges are recorded on both the connection and the node.
+    let fold_transform = connection
+        .fold
+        .as_ref()
+        .and_then(|fold| fold.transform.as_ref());
+    if let (Some(transform), None) = (&node.transform_group, fold_transform) {
+        write_transform_group(f, transform)?;
+    }
+
+    if let Some(coerced_to) = &node.coerced_to {
+        f.write_str(" {\n")?;
+        write_indent(f, depth + 1)?;
+        writeln!(f, "... on {coerced_to} {{")?;
+        write_subfields(f, node, depth + 2)?;
+        write_indent(f, depth + 1)?;
+        f.write_str("}\n")?;
+        write_indent(f, depth)?;
+        f.write_char('}')?;
+    } else if !node.connections.is_empty() {
+        f.write_str(" {\n")?;
+        write_subfields(f, node, depth + 1)?;
+        write_indent(f, depth)?;
+        f.write_char('}')?;
+    }
+
+    f.write_char('\n')
+}
+
+fn write_subfields(f: &mut impl Write, node: &FieldNode, depth: usize) -> fmt::Result {
+    for (connection, subfield) in &node.connections {
+        write_field(f, connection, subfield, depth)?;
+    }
+    Ok(())
+}
+
+fn write_transform_group(f: &mut impl Write, group: &TransformGroup) -> fmt::Result {
+    let op = match group.transform.kind {
+        TransformationKind::Count => "count",
+    };
+    write!(f, " @transform(op: \"{op}\")")?;
+    write_value_directives(f, &group.filter, &group.tag, &group.output)?;
+    if let Some(retransform) = &group.retransform {
+        write_transform_group(f, retransform)?;
+    }
+    Ok(())
+}
+
+fn write_value_directives(
+    f: &mut impl Write,
+    filters: &[FilterDirective],
+    tags: &[TagDirective],
+    outputs: &[OutputDirective],
+) -> fmt::Result {
+    for filter in filters {
+        write!(f, " @filter(op: \"{}\"", filter.operation.operation_name())?;
+        if let Some(argument) = filter.operation.right() {
+            let argument = match argument {
+                OperatorArgument::VariableRef(name) => format!("${name}"),
+                OperatorArgument::TagRef(name) => format!("%{name}"),
+            };
+            f.write_str(", value: [")?;
+            write_string(f, &argument)?;
+            f.write_char(']')?;
+        }
+        f.write_char(')')?;
+    }
+
+    for tag in tags {
+        write_named_directive(f, "tag", tag.name.as_deref())?;
+    }
+    for output in outputs {
+        write_named_directive(f, "output", output.name.as_deref())?;
+    }
+
+    Ok(())
+}
+
+fn write_named_directive(f: &mut impl Write, directive: &str, name: Option<&str>) -> fmt::Result {
+    write!(f, " @{directive}")?;
+    if let Some(name) = name {
+        f.write_str("(name: ")?;
+        write_string(f, name)?;
+        f.write_char(')')?;
+    }
+    Ok(())
+}
+
+/// Write the value as a GraphQL literal.
+fn write_value(f: &mut impl Write, value: &FieldValue) -> fmt::Result {
+    match value {
+        FieldValue::Null => f.write_str("null"),
+        FieldValue::Int64(n) => write!(f, "{n}"),
+        FieldValue::Uint64(n) => write!(f, "{n}"),
+        // The `Debug` representation always includes a decimal point or an exponent,
+        // so the value isn't mistaken for an integer when parsed again.
+        FieldValue::Float64(n) => write!(f, "{n:?}"),
+        FieldValue::String(s) => write_string(f, s),
+        FieldValue::Boolean(b) => write!(f, "{b}"),
+        FieldValue::DateTimeUtc(dt) => write_string(f, &dt.to_rfc3339()),
+        FieldValue::Enum(name) => f.write_str(name),
+        FieldValue::List(values) => {
+            f.write_char('[')?;
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    f.write_str(", ")?;
+                }
+                write_value(f, value)?;
+            }
+            f.write_char(']')
+        }
+    }
+}
+
+fn write_string(f: &mut impl Write, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04X}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use trustfall_filetests_macros::parameterize;
+
+    use crate::{frontend::parse_to_ir, schema::Schema, test_types::TestGraphQLQuery};
+
+    use super::format_query;
+
+    #[test]
+    fn queries_are_formatted_canonically() {
+        let query = r#"
+# Comments are dropped.
+{ Number(min: 1, max: 10) { value @output(name: "n") @filter(op: ">=", value: ["$min"])
+  successor@optional { name @output @tag(name: "successor") value @filter(op: "is_not_null") }
+  m: multiple(max: 3) @fold @transform(op: "count") @output @filter(op: "=", value: ["%successor"]) {
+     ... on Composite { name    @output(name: "multiple_name") } }
+  predecessor @recurse(depth: 2) { vowelsInName @filter(op: "contains", value: ["$vowel"]) }
+} }"#;
+        let expected = r#"{
+    Number(max: 10, min: 1) {
+        value @filter(op: ">=", value: ["$min"]) @output(name: "n")
+        successor @optional {
+            name @tag(name: "successor") @output
+            value @filter(op: "is_not_null")
+        }
+        m: multiple(max: 3) @fold @transform(op: "count") @filter(op: "=", value: ["%successor"]) @output {
+            ... on Composite {
+                name @output(name: "multiple_name")
+            }
+        }
+        predecessor @recurse(depth: 2) {
+            vowelsInName @filter(op: "contains", value: ["$vowel"])
+        }
+    }
+}"#;
+        let formatted = format_query(query).unwrap();
+        assert_eq!(expected, formatted);
+        assert_eq!(formatted, format_query(&formatted).unwrap());
+    }
+
+    #[test]
+    fn literals_are_formatted_as_graphql() {
+        let query = r#"{
+    Root(float: 2.0, exp: 1e-7, list: [1, -2, null], enum: DESC, text: "a \"quoted\"\n\u0001 ünïcödé", flag: true) {
+        name @output
+    }
+}"#;
+        let expected = r#"{
+    Root(enum: DESC, exp: 1e-7, flag: true, float: 2.0, list: [1, -2, null], text: "a \"quoted\"\n\u0001 ünïcödé") {
+        name @output
+    }
+}"#;
+        assert_eq!(expected, format_query(query).unwrap());
+    }
+
+    #[test]
+    fn field_descriptions_are_preserved() {
+        let query = r#"
+{
+    Number(max: 10) {
+        #   The number's value.
+        #
+        # Always positive.
+        value @output
+        name @output # Trailing comments don't describe fields.
+        # Separated from its field by a blank line, so not a description.
+
+        successor {
+            #Multiples of the successor.
+            multiple(max: 3) @fold @transform(op: "count") @output
+        }
+    }
+}"#;
+        let expected = r#"{
+    Number(max: 10) {
+        #   The number's value.
+        #
+        # Always positive.
+        value @output
+        name @output
+        successor {
+            # Multiples of the successor.
+            multiple(max: 3) @fold @transform(op: "count") @output
+        }
+    }
+}"#;
+        let formatted = format_query(query).unwrap();
+        assert_eq!(expected, formatted);
+        assert_eq!(formatted, format_query(&formatted).unwrap());
+    }
+
+    /// Formatting produces text that compiles to the same query, and is left unchanged
+    /// when formatted again.
+    #[parameterize("trustfall_core/test_data/tests/valid_queries")]
+    fn valid_queries(base: &Path, stem: &str) {
+        let input_path = base.join(format!("{stem}.graphql.ron"));
+        let test_query: TestGraphQLQuery =
+            ron::from_str(&fs::read_to_string(input_path).unwrap()).unwrap();
+        let schema = Schema::parse(
+            fs::read_to_string(format!(
+                "test_data/schemas/{}.graphql",
+                test_query.schema_name
+            ))
+            .unwrap(),
+        )
+        .unwrap();
+
+        let formatted = format_query(&test_query.query).unwrap();
+        assert_eq!(formatted, format_query(&formatted).unwrap());
+        assert_eq!(
+            parse_to_ir(&schema, &test_query.query).unwrap(),
+            parse_to_ir(&schema, &formatted).unwrap(),
+            "{formatted}"
+        );
+    }
+}
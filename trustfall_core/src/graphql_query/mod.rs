@@ -2,10 +2,13 @@
 //! which are then handed to the frontend for further processing.
 pub(crate) mod directives;
 pub mod error;
+mod format;
 pub(crate) mod query;
 
+pub use format::format_query;
+
 // Test-only uses. `#[doc(hidden)]` items are not part of public API
 // and are not subject to semantic versioning rules.
 #[cfg(feature = "__private")]
 #[doc(hidden)]
-pub use query::parse_document;
+pub use query::{parse_document, parse_query_text};
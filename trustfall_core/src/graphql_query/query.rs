@@ -2,7 +2,10 @@
 
 use std::fmt::Debug;
 use std::sync::Arc;
-use std::{collections::BTreeMap, convert::TryFrom};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+};
 
 use async_graphql_parser::types::Directive;
 use async_graphql_parser::{
@@ -12,8 +15,8 @@ use async_graphql_parser::{
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 
-use crate::ir::FieldValue;
-use crate::util::BTreeMapTryInsertExt;
+use crate::ir::{FieldValue, Type};
+use crate::util::{similar_names, BTreeMapTryInsertExt};
 
 use super::directives::{FoldGroup, TransformDirective, TransformGroup};
 use super::{
@@ -56,6 +59,11 @@ pub(crate) struct FieldNode {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub(crate) coerced_to: Option<Arc<str>>,
 
+    /// The text of the comment lines directly preceding the field, if the field
+    /// is the first thing on its line. Only set by [`parse_query_text`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) description: Option<Arc<str>>,
+
     #[serde(default, skip_serializing_if = "SmallVec::is_empty")]
     pub(crate) filter: SmallVec<[FilterDirective; 1]>,
 
@@ -72,13 +80,41 @@ pub(crate) struct FieldNode {
     pub(crate) transform_group: Option<TransformGroup>,
 }
 
+/// A variable declared in the query's header, like `$limit: Int = 10`
+/// in `query($limit: Int = 10) { ... }`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub(crate) struct VariableDefinition {
+    pub(crate) position: Pos,
+    pub(crate) name: Arc<str>,
+
+    pub(crate) variable_type: Type,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) default_value: Option<FieldValue>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Query {
+    /// The variables declared in the query's header, in the order of their declaration.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) variables: Vec<VariableDefinition>,
+
     pub(crate) root_connection: FieldConnection,
 
     pub(crate) root_field: FieldNode,
 }
 
+/// The names of the directives that may be used in queries.
+const SUPPORTED_DIRECTIVES: [&str; 7] = [
+    "filter",
+    "fold",
+    "optional",
+    "output",
+    "recurse",
+    "tag",
+    "transform",
+];
+
 #[derive(Debug, Clone)]
 enum ParsedDirective {
     Filter(FilterDirective, Pos),
@@ -138,16 +174,6 @@ fn try_get_query_root(document: &ExecutableDocument) -> Result<&Positioned<Field
                 return Err(ParseError::DocumentNotAQuery(op.pos));
             }
 
-            if !root_node.variable_definitions.is_empty() {
-                let first_variable_definition = root_node.variable_definitions.first().unwrap();
-                return Err(ParseError::OtherError(
-                    "Found GraphQL query variable definitions. \
-                    These are not necessary since variables are defined implicitly, \
-                    and must be removed."
-                        .to_string(),
-                    first_variable_definition.pos,
-                ));
-            }
             if !root_node.directives.is_empty() {
                 let first_directive = root_node.directives.first().unwrap();
                 return Err(ParseError::DirectiveNotInsideQueryRoot(
@@ -213,9 +239,10 @@ fn make_directives(
                 let parsed = FoldDirective::try_from(directive)?;
                 parsed_directives.push(ParsedDirective::Fold(parsed, directive.pos));
             }
-            _ => {
+            name => {
                 return Err(ParseError::UnrecognizedDirective(
-                    directive.node.name.node.to_string(),
+                    name.to_string(),
+                    similar_names(name, SUPPORTED_DIRECTIVES),
                     directive.pos,
                 ))
             }
@@ -334,6 +361,7 @@ fn make_field_node(field: &Positioned<Field>) -> Result<FieldNode, ParseError> {
         name: name.as_ref().to_owned().into(),
         alias: alias.map(|x| x.as_ref().to_owned().into()),
         coerced_to: coerced_to.map(|x| x.as_ref().to_owned().into()),
+        description: None,
         filter,
         transform_group,
         output,
@@ -511,9 +539,63 @@ fn make_transform_group(
     })
 }
 
+/// Extracts the variables declared in the header of the document's query operation.
+///
+/// Must be called only on documents for which [try_get_query_root] succeeds.
+fn make_variable_definitions(
+    document: &ExecutableDocument,
+) -> Result<Vec<VariableDefinition>, ParseError> {
+    let DocumentOperations::Single(op) = &document.operations else {
+        unreachable!("document does not contain a single operation: {document:?}")
+    };
+
+    let mut names: BTreeSet<&str> = Default::default();
+    let mut variables = vec![];
+    for definition in &op.node.variable_definitions {
+        let name = definition.node.name.node.as_str();
+        if !names.insert(name) {
+            return Err(ParseError::DuplicatedVariableDefinition(
+                name.to_string(),
+                definition.pos,
+            ));
+        }
+        if let Some(directive) = definition.node.directives.first() {
+            return Err(ParseError::DirectiveNotInsideQueryRoot(
+                directive.node.name.node.to_string(),
+                directive.pos,
+            ));
+        }
+
+        let default_value = definition
+            .node
+            .default_value
+            .as_ref()
+            .map(|value| {
+                FieldValue::try_from(value.node.clone()).map_err(|_| {
+                    ParseError::InvalidVariableDefaultValue(
+                        name.to_string(),
+                        value.node.clone().into_value(),
+                        value.pos,
+                    )
+                })
+            })
+            .transpose()?;
+
+        variables.push(VariableDefinition {
+            position: definition.pos,
+            name: name.into(),
+            variable_type: Type::from(&definition.node.var_type.node),
+            default_value,
+        });
+    }
+
+    Ok(variables)
+}
+
 /// Parses a query document. May fail if there is no query root.
 pub fn parse_document(document: &ExecutableDocument) -> Result<Query, ParseError> {
     let query_root = try_get_query_root(document)?;
+    let variables = make_variable_definitions(document)?;
 
     if let Some(dir) = query_root.node.directives.first() {
         return Err(ParseError::DirectiveNotInsideQueryRoot(
@@ -530,11 +612,65 @@ pub fn parse_document(document: &ExecutableDocument) -> Result<Query, ParseError
     let root_field = make_field_node(query_root)?;
 
     Ok(Query {
+        variables,
         root_connection,
         root_field,
     })
 }
 
+/// Parses a query's text, including the descriptions of its fields given in comments:
+/// ```graphql
+/// {
+///     Number(max: 10) {
+///         # The number's value, in decimal.
+///         value @output
+///     }
+/// }
+/// ```
+/// A comment describes a field if it's on the lines directly preceding the field,
+/// and the field is the first thing on its line. Comments spanning multiple lines
+/// produce multi-line descriptions, without any leading or trailing blank lines.
+pub fn parse_query_text(query: &str) -> Result<Query, ParseError> {
+    let document = async_graphql_parser::parse_query(query)?;
+    let mut parsed = parse_document(&document)?;
+
+    let lines: Vec<&str> = query.lines().collect();
+    attach_field_descriptions(&lines, &mut parsed.root_field);
+    Ok(parsed)
+}
+
+fn attach_field_descriptions(lines: &[&str], node: &mut FieldNode) {
+    node.description = field_description(lines, node.position);
+    for (_, subfield) in &mut node.connections {
+        attach_field_descriptions(lines, subfield);
+    }
+}
+
+fn field_description(lines: &[&str], position: Pos) -> Option<Arc<str>> {
+    // Positions are 1-based.
+    let line_index = position.line.checked_sub(1)?;
+    let field_line = lines.get(line_index)?;
+    if !field_line
+        .chars()
+        .take(position.column.saturating_sub(1))
+        .all(char::is_whitespace)
+    {
+        return None;
+    }
+
+    let mut comment_lines: Vec<&str> = lines[..line_index]
+        .iter()
+        .rev()
+        .map_while(|line| line.trim().strip_prefix('#'))
+        .map(|comment| comment.strip_prefix(' ').unwrap_or(comment).trim_end())
+        .collect();
+    comment_lines.reverse();
+
+    let description = comment_lines.join("\n");
+    let description = description.trim_matches('\n');
+    (!description.is_empty()).then(|| description.into())
+}
+
 #[cfg(test)]
 mod tests {
     use std::{
@@ -542,8 +678,6 @@ mod tests {
         path::{Path, PathBuf},
     };
 
-    use async_graphql_parser::parse_query;
-
     use trustfall_filetests_macros::parameterize;
 
     use super::*;
@@ -562,11 +696,10 @@ mod tests {
         let test_query: TestGraphQLQuery = ron::from_str(&input_data).unwrap();
 
         let arguments = test_query.arguments;
-        let document = parse_query(test_query.query).unwrap();
         let check_data = fs::read_to_string(check_path).unwrap();
 
         let constructed_test_item =
-            parse_document(&document).map(move |query| TestParsedGraphQLQuery {
+            parse_query_text(&test_query.query).map(move |query| TestParsedGraphQLQuery {
                 schema_name: test_query.schema_name,
                 query,
                 arguments,
@@ -0,0 +1,392 @@
+//! Access control for schema fields marked `@restricted(scope: String)`.
+//!
+//! Before executing a query on behalf of a user, pass it through [`authorize_query`]
+//! together with an [`AccessPolicy`] and the user's context. The policy decides whether
+//! each restricted field the query uses is allowed, denied, or redacted:
+//! denied fields cause the query to be rejected, while redacted properties produce `null`
+//! outputs without their values ever being resolved by the adapter.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+use crate::ir::{FieldValue, IRQueryComponent, IndexedQuery, RestrictedFieldUse};
+
+use super::{
+    error::{AccessError, QueryArgumentsError},
+    execution::interpret_ir,
+    Adapter,
+};
+
+/// What an [`AccessPolicy`] decides about a query's use of a restricted field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessDecision {
+    /// The query may use the field.
+    Allow,
+
+    /// The query may not use the field, and is rejected with [`AccessError::AccessDenied`].
+    Deny,
+
+    /// The query may use the property, but its outputs are replaced with `null`.
+    ///
+    /// Redacted properties may not be used in filters, since the query's results could then
+    /// reveal their values. Edges cannot be redacted.
+    Redact,
+}
+
+/// Decides whether a query may use each `@restricted` field, based on a per-query context
+/// such as the identity of the user on whose behalf the query is executed.
+///
+/// Implemented for all functions and closures with the appropriate signature.
+pub trait AccessPolicy<Context: ?Sized> {
+    fn check(&self, context: &Context, field: &RestrictedFieldUse) -> AccessDecision;
+}
+
+impl<Context, F> AccessPolicy<Context> for F
+where
+    Context: ?Sized,
+    F: Fn(&Context, &RestrictedFieldUse) -> AccessDecision,
+{
+    fn check(&self, context: &Context, field: &RestrictedFieldUse) -> AccessDecision {
+        self(context, field)
+    }
+}
+
+/// A query that an [`AccessPolicy`] has permitted to execute.
+#[derive(Debug, Clone)]
+pub struct AuthorizedQuery {
+    indexed_query: Arc<IndexedQuery>,
+    redacted_outputs: BTreeSet<Arc<str>>,
+}
+
+impl AuthorizedQuery {
+    /// The query to execute, with redacted outputs removed.
+    pub fn indexed_query(&self) -> &Arc<IndexedQuery> {
+        &self.indexed_query
+    }
+
+    /// The names of the outputs that are always `null` due to redaction.
+    pub fn redacted_outputs(&self) -> &BTreeSet<Arc<str>> {
+        &self.redacted_outputs
+    }
+
+    /// Execute the query. Redacted outputs are included in the results with `null` values.
+    #[allow(clippy::type_complexity)]
+    pub fn interpret<'query, AdapterT: Adapter<'query> + 'query>(
+        &self,
+        adapter: Arc<AdapterT>,
+        arguments: Arc<BTreeMap<Arc<str>, FieldValue>>,
+    ) -> Result<
+        Box<dyn Iterator<Item = BTreeMap<Arc<str>, FieldValue>> + 'query>,
+        QueryArgumentsError,
+    > {
+        let results = interpret_ir(adapter, self.indexed_query.clone(), arguments)?;
+        if self.redacted_outputs.is_empty() {
+            return Ok(results);
+        }
+
+        let redacted_outputs = self.redacted_outputs.clone();
+        Ok(Box::new(results.map(move |mut row| {
+            for output in &redacted_outputs {
+                row.insert(output.clone(), FieldValue::Null);
+            }
+            row
+        })))
+    }
+}
+
+/// Apply the access policy to the query's uses of `@restricted` fields.
+///
+/// Returns an error listing every denied field, and every redacted field the query
+/// uses in a way that cannot be redacted.
+pub fn authorize_query<Context: ?Sized>(
+    indexed_query: &Arc<IndexedQuery>,
+    policy: &impl AccessPolicy<Context>,
+    context: &Context,
+) -> Result<AuthorizedQuery, AccessError> {
+    let mut errors = vec![];
+    let mut redacted_outputs: BTreeSet<Arc<str>> = Default::default();
+    for field in &indexed_query.ir_query.restricted_fields {
+        match policy.check(context, field) {
+            AccessDecision::Allow => {}
+            AccessDecision::Deny => errors.push(AccessError::AccessDenied(
+                field.type_name.to_string(),
+                field.field_name.to_string(),
+            )),
+            AccessDecision::Redact => {
+                if field.is_edge {
+                    errors.push(AccessError::RedactedEdge(
+                        field.type_name.to_string(),
+                        field.field_name.to_string(),
+                    ));
+                } else if field.used_in_filters {
+                    errors.push(AccessError::RedactedFieldUsedInFilter(
+                        field.type_name.to_string(),
+                        field.field_name.to_string(),
+                    ));
+                } else {
+                    redacted_outputs.extend(field.outputs.iter().cloned());
+                }
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors.into());
+    }
+
+    let indexed_query = if redacted_outputs.is_empty() {
+        indexed_query.clone()
+    } else {
+        // Remove the redacted outputs from the query itself,
+        // so the adapter is never asked to resolve their values.
+        let mut ir_query = indexed_query.ir_query.clone();
+        remove_outputs(
+            Arc::make_mut(&mut ir_query.root_component),
+            &redacted_outputs,
+        );
+        let redacted_query = IndexedQuery::try_from(ir_query)
+            .expect("removing outputs produced an invalid query")
+            .with_custom_scalars(indexed_query.custom_scalars.clone());
+        Arc::new(redacted_query)
+    };
+
+    Ok(AuthorizedQuery {
+        indexed_query,
+        redacted_outputs,
+    })
+}
+
+fn remove_outputs(component: &mut IRQueryComponent, outputs: &BTreeSet<Arc<str>>) {
+    component.outputs.retain(|name, _| !outputs.contains(name));
+    for fold in component.folds.values_mut() {
+        let fold = Arc::make_mut(fold);
+        remove_outputs(Arc::make_mut(&mut fold.component), outputs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use crate::{
+        frontend,
+        interpreter::{
+            basic_adapter::BasicAdapter, error::AccessError, helpers::resolve_property_with,
+            ContextIterator, ContextOutcomeIterator, Typename, VertexIterator,
+        },
+        ir::{EdgeParameters, FieldValue, RestrictedFieldUse},
+        schema::Schema,
+    };
+
+    use super::{authorize_query, AccessDecision};
+
+    const EMPLOYEES_SCHEMA: &str = r#"
+schema {
+    query: RootSchemaQuery
+}
+directive @filter(op: String!, value: [String!]) on FIELD | INLINE_FRAGMENT
+directive @tag(name: String) on FIELD
+directive @output(name: String) on FIELD
+directive @optional on FIELD
+directive @recurse(depth: Int!) on FIELD
+directive @fold on FIELD
+directive @transform(op: String!) on FIELD
+
+type RootSchemaQuery {
+    Employee: [Employee!]!
+}
+
+type Employee {
+    name: String!
+    salary: Int @restricted(scope: "hr")
+    reports: [Employee!] @restricted
+}
+"#;
+
+    #[derive(Debug, Clone)]
+    struct Employee {
+        name: &'static str,
+    }
+
+    impl Typename for Employee {
+        fn typename(&self) -> &'static str {
+            "Employee"
+        }
+    }
+
+    struct EmployeesAdapter;
+
+    impl<'a> BasicAdapter<'a> for EmployeesAdapter {
+        type Vertex = Employee;
+
+        fn resolve_starting_vertices(
+            &self,
+            _edge_name: &str,
+            _parameters: &EdgeParameters,
+        ) -> VertexIterator<'a, Self::Vertex> {
+            Box::new(["alice", "bob"].into_iter().map(|name| Employee { name }))
+        }
+
+        fn resolve_property(
+            &self,
+            contexts: ContextIterator<'a, Self::Vertex>,
+            _type_name: &str,
+            property_name: &str,
+        ) -> ContextOutcomeIterator<'a, Self::Vertex, FieldValue> {
+            match property_name {
+                "name" => resolve_property_with(contexts, |employee| employee.name.into()),
+                "salary" => panic!("redacted property was resolved"),
+                _ => unreachable!(),
+            }
+        }
+
+        fn resolve_neighbors(
+            &self,
+            _contexts: ContextIterator<'a, Self::Vertex>,
+            _type_name: &str,
+            _edge_name: &str,
+            _parameters: &EdgeParameters,
+        ) -> ContextOutcomeIterator<'a, Self::Vertex, VertexIterator<'a, Self::Vertex>> {
+            unreachable!()
+        }
+
+        fn resolve_coercion(
+            &self,
+            _contexts: ContextIterator<'a, Self::Vertex>,
+            _type_name: &str,
+            _coerce_to_type: &str,
+        ) -> ContextOutcomeIterator<'a, Self::Vertex, bool> {
+            unreachable!()
+        }
+    }
+
+    /// HR may see salaries, which are redacted for everyone else.
+    /// Only admins may see reporting relationships.
+    fn policy(role: &str, field: &RestrictedFieldUse) -> AccessDecision {
+        match (field.scope.as_deref(), role) {
+            (Some("hr"), "hr") | (None, "admin") => AccessDecision::Allow,
+            (Some("hr"), _) => AccessDecision::Redact,
+            _ => AccessDecision::Deny,
+        }
+    }
+
+    #[test]
+    fn restricted_fields_are_recorded() {
+        let schema = Schema::parse(EMPLOYEES_SCHEMA).unwrap();
+        let query = r#"
+{
+    Employee {
+        name @output
+        salary @output(name: "pay")
+        reports @fold {
+            salary @filter(op: ">", value: ["$min"])
+        }
+    }
+}"#;
+        let ir_query = frontend::parse_to_ir(&schema, query).unwrap();
+        assert_eq!(
+            vec![
+                RestrictedFieldUse {
+                    type_name: "Employee".into(),
+                    field_name: "reports".into(),
+                    scope: None,
+                    is_edge: true,
+                    outputs: Default::default(),
+                    used_in_filters: false,
+                },
+                RestrictedFieldUse {
+                    type_name: "Employee".into(),
+                    field_name: "salary".into(),
+                    scope: Some("hr".into()),
+                    is_edge: false,
+                    outputs: btreeset! {"pay".into()},
+                    used_in_filters: true,
+                },
+            ],
+            ir_query.restricted_fields
+        );
+    }
+
+    #[test]
+    fn redacted_outputs_are_null_and_never_resolved() {
+        let schema = Schema::parse(EMPLOYEES_SCHEMA).unwrap();
+        let query = r#"
+{
+    Employee {
+        name @output
+        salary @output
+    }
+}"#;
+        let indexed_query = frontend::parse(&schema, query).unwrap();
+
+        let authorized = authorize_query(&indexed_query, &policy, "engineer").unwrap();
+        assert_eq!(
+            &btreeset! {Arc::from("salary")},
+            authorized.redacted_outputs()
+        );
+        let results: Vec<_> = authorized
+            .interpret(Arc::new(EmployeesAdapter), Default::default())
+            .unwrap()
+            .collect();
+        let expected_results: Vec<BTreeMap<Arc<str>, FieldValue>> = vec![
+            btreemap! {"name".into() => "alice".into(), "salary".into() => FieldValue::Null},
+            btreemap! {"name".into() => "bob".into(), "salary".into() => FieldValue::Null},
+        ];
+        assert_eq!(expected_results, results);
+    }
+
+    #[test]
+    fn denied_and_unredactable_uses_are_errors() {
+        let schema = Schema::parse(EMPLOYEES_SCHEMA).unwrap();
+        let query = r#"
+{
+    Employee {
+        name @output
+        salary @filter(op: ">", value: ["$min"])
+        reports @fold {
+            name @output(name: "report")
+        }
+    }
+}"#;
+        let indexed_query = frontend::parse(&schema, query).unwrap();
+
+        assert_eq!(
+            Err(AccessError::MultipleErrors(crate::util::DisplayVec(vec![
+                AccessError::AccessDenied("Employee".to_string(), "reports".to_string()),
+                AccessError::RedactedFieldUsedInFilter(
+                    "Employee".to_string(),
+                    "salary".to_string()
+                ),
+            ]))),
+            authorize_query(&indexed_query, &policy, "engineer").map(|_| ()),
+        );
+        assert_eq!(
+            Err(AccessError::AccessDenied(
+                "Employee".to_string(),
+                "reports".to_string()
+            )),
+            authorize_query(&indexed_query, &policy, "hr").map(|_| ()),
+        );
+
+        let authorized = authorize_query(&indexed_query, &policy, "admin").unwrap_err();
+        assert_eq!(
+            AccessError::RedactedFieldUsedInFilter("Employee".to_string(), "salary".to_string()),
+            authorized
+        );
+    }
+
+    #[test]
+    fn unrestricted_queries_are_unchanged() {
+        let schema = Schema::parse(EMPLOYEES_SCHEMA).unwrap();
+        let query = r#"
+{
+    Employee {
+        name @output
+    }
+}"#;
+        let indexed_query = frontend::parse(&schema, query).unwrap();
+        let authorized = authorize_query(&indexed_query, &policy, "engineer").unwrap();
+        assert!(Arc::ptr_eq(&indexed_query, authorized.indexed_query()));
+        assert!(authorized.redacted_outputs().is_empty());
+    }
+}
@@ -38,7 +38,8 @@ pub trait BasicAdapter<'vertex> {
     ///
     /// The caller guarantees that:
     /// - The specified edge is a starting edge in the schema being queried.
-    /// - Any parameters the edge requires per the schema have values provided.
+    /// - Every parameter the edge defines in the schema has a value: either the one specified
+    ///   in the query, or the schema-defined default, or `null` for nullable parameters.
     fn resolve_starting_vertices(
         &self,
         edge_name: &str,
@@ -93,7 +94,8 @@ pub trait BasicAdapter<'vertex> {
     /// The caller guarantees that:
     /// - `type_name` is a type or interface defined in the schema.
     /// - `edge_name` is an edge field on `type_name` defined in the schema.
-    /// - Any parameters the edge requires per the schema have values provided.
+    /// - Every parameter the edge defines in the schema has a value: either the one specified
+    ///   in the query, or the schema-defined default, or `null` for nullable parameters.
     /// - When the active vertex is `Some(...)`, it's a vertex of type `type_name`:
     ///   either its type is exactly `type_name`, or `type_name` is an interface that
     ///   the vertex's type implements.
@@ -208,6 +210,22 @@ pub trait BasicAdapter<'vertex> {
     ) -> ContextOutcomeIterator<'vertex, Self::Vertex, FieldValue> {
         resolve_property_with(contexts, |vertex| vertex.typename().into())
     }
+
+    /// Identify the vertex, so that coercions of the vertex aren't resolved more than once.
+    ///
+    /// The default implementation doesn't identify any vertices.
+    /// See [`Adapter::vertex_identity`] for how identities are used.
+    fn vertex_identity(&self, _vertex: &Self::Vertex) -> Option<u64> {
+        None
+    }
+
+    /// Whether `@recurse` should reach each identified vertex once, rather than once per path.
+    ///
+    /// The default implementation returns `false`.
+    /// See [`Adapter::skip_revisited_vertices_in_recursion`] for what this changes.
+    fn skip_revisited_vertices_in_recursion(&self) -> bool {
+        false
+    }
 }
 
 impl<'vertex, T> Adapter<'vertex> for T
@@ -275,4 +293,12 @@ where
             coerce_to_type.as_ref(),
         )
     }
+
+    fn vertex_identity(&self, vertex: &Self::Vertex) -> Option<u64> {
+        <Self as BasicAdapter>::vertex_identity(self, vertex)
+    }
+
+    fn skip_revisited_vertices_in_recursion(&self) -> bool {
+        <Self as BasicAdapter>::skip_revisited_vertices_in_recursion(self)
+    }
 }
@@ -0,0 +1,625 @@
+//! Executing queries that other threads may cancel.
+//!
+//! An execution's iterators are built on top of the adapter's, which usually aren't `Send`,
+//! so the results of [`interpret_ir`] may only be consumed by the thread that started
+//! the execution. There are two ways to execute a query whose results are consumed
+//! while other threads keep an [`ExecutionHandle`] to check on the execution or cancel it,
+//! such as when the client streaming the results disconnects:
+//! - [`execute_in_background`] executes the query on a new thread and sends its results back.
+//!   The [`BackgroundRows`] it returns are `Send`, so they may be consumed by any thread,
+//!   such as one streaming them in response to a web request. The execution stays only a few
+//!   results ahead of those consumed, so a slow consumer doesn't make it buffer all its results.
+//!   This requires an adapter that's `Send` and `Sync`.
+//! - [`execute_cancellable`] executes the query like [`interpret_ir_rows`], for any adapter.
+//!   Its [`CancellableRows`] must be consumed by the thread that started the execution.
+//!
+//! Either way, a cancelled execution doesn't only stop between results: it also stops the next
+//! time it would pull a vertex or context from or into the adapter. So it stops promptly even
+//! while it's going through many vertices per result, as for a query with selective filters.
+//!
+//! [`interpret_ir`]: super::execution::interpret_ir
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    panic,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::ir::{EdgeParameters, FieldValue, IndexedQuery};
+
+use super::{
+    error::QueryArgumentsError, execution::interpret_ir_rows, Adapter, ContextIterator,
+    ContextOutcomeIterator, ResolveEdgeInfo, ResolveInfo, Row, VertexIterator,
+};
+
+/// How many results an execution in the background may produce ahead of those consumed.
+const BUFFERED_ROWS: usize = 64;
+
+/// Execute the query like [`interpret_ir_rows`], but on a new thread.
+///
+/// The arguments are checked before this returns, so invalid arguments are reported here
+/// rather than by the results. If the execution panics, such as because the adapter did,
+/// the panic is resumed by the thread consuming the results.
+pub fn execute_in_background<AdapterT>(
+    adapter: Arc<AdapterT>,
+    indexed_query: Arc<IndexedQuery>,
+    arguments: Arc<BTreeMap<Arc<str>, FieldValue>>,
+) -> Result<BackgroundRows, QueryArgumentsError>
+where
+    AdapterT: Adapter<'static> + Send + Sync + 'static,
+{
+    let handle = ExecutionHandle {
+        state: Default::default(),
+    };
+    let (started_sender, started) = mpsc::sync_channel(1);
+    let (sender, receiver) = mpsc::sync_channel(BUFFERED_ROWS);
+
+    let state = handle.state.clone();
+    let worker = thread::spawn(move || {
+        // Declared first so that it's dropped last, after the execution's iterators,
+        // even if the execution panics.
+        let _finished = FinishOnDrop(state.clone());
+
+        let adapter = Arc::new(CancellableAdapter {
+            inner: adapter,
+            state: state.clone(),
+        });
+        let rows = match interpret_ir_rows(adapter, indexed_query, arguments) {
+            Ok(rows) => {
+                let _ = started_sender.send(Ok(()));
+                rows
+            }
+            Err(e) => {
+                let _ = started_sender.send(Err(e));
+                return;
+            }
+        };
+        for row in rows {
+            if state.is_cancelled() || sender.send(row).is_err() {
+                // The execution was cancelled, or its results were dropped.
+                break;
+            }
+        }
+    });
+
+    match started.recv() {
+        Ok(Ok(())) => Ok(BackgroundRows {
+            receiver: Some(receiver),
+            worker: Some(worker),
+            handle,
+        }),
+        Ok(Err(e)) => {
+            let _ = worker.join();
+            Err(e)
+        }
+        Err(_) => match worker.join() {
+            Err(panic) => panic::resume_unwind(panic),
+            Ok(()) => unreachable!("the execution ended without reporting whether it started"),
+        },
+    }
+}
+
+/// Execute the query like [`interpret_ir_rows`], producing results that stop
+/// once the execution is cancelled through their [`handle`](CancellableRows::handle).
+pub fn execute_cancellable<'query, AdapterT: Adapter<'query> + 'query>(
+    adapter: Arc<AdapterT>,
+    indexed_query: Arc<IndexedQuery>,
+    arguments: Arc<BTreeMap<Arc<str>, FieldValue>>,
+) -> Result<CancellableRows<'query>, QueryArgumentsError> {
+    let handle = ExecutionHandle {
+        state: Default::default(),
+    };
+    let adapter = Arc::new(CancellableAdapter {
+        inner: adapter,
+        state: handle.state.clone(),
+    });
+    let rows = interpret_ir_rows(adapter, indexed_query, arguments)?;
+    Ok(CancellableRows {
+        rows: Some(rows),
+        handle,
+    })
+}
+
+#[derive(Debug, Default)]
+struct ExecutionState {
+    cancelled: AtomicBool,
+    finished: AtomicBool,
+}
+
+impl ExecutionState {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn finish(&self) {
+        self.finished.store(true, Ordering::Release);
+    }
+}
+
+struct FinishOnDrop(Arc<ExecutionState>);
+
+impl Drop for FinishOnDrop {
+    fn drop(&mut self) {
+        self.0.finish();
+    }
+}
+
+/// Checks on and cancels an execution started by [`execute_in_background`]
+/// or [`execute_cancellable`].
+///
+/// Clones refer to the same execution, and may be used from any thread.
+#[derive(Debug, Clone)]
+pub struct ExecutionHandle {
+    state: Arc<ExecutionState>,
+}
+
+impl ExecutionHandle {
+    /// Stop the execution. It won't produce any more results, and won't pull any more vertices
+    /// or contexts from or into the adapter, though a call to the adapter that's already
+    /// in progress runs to completion before the execution stops.
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.is_cancelled()
+    }
+
+    /// Whether the execution is done, either because it produced all its results
+    /// or because it was cancelled or its results were dropped. Once it's done,
+    /// all its iterators have been dropped.
+    ///
+    /// The results of an execution in the background may not all have been consumed yet.
+    pub fn is_finished(&self) -> bool {
+        self.state.finished.load(Ordering::Acquire)
+    }
+}
+
+/// The results of an execution started by [`execute_in_background`].
+///
+/// Dropping the results before consuming all of them cancels the execution,
+/// and waits for its thread to stop.
+#[derive(Debug)]
+pub struct BackgroundRows {
+    receiver: Option<Receiver<Row>>,
+    worker: Option<JoinHandle<()>>,
+    handle: ExecutionHandle,
+}
+
+impl BackgroundRows {
+    pub fn handle(&self) -> &ExecutionHandle {
+        &self.handle
+    }
+
+    fn finish(&mut self) {
+        // Disconnecting stops the execution if it's waiting for room for another result.
+        self.receiver = None;
+
+        // A cancelled execution stops the next time it pulls from or into the adapter,
+        // so this only waits for any call to the adapter that's in progress.
+        if let Some(worker) = self.worker.take() {
+            if let Err(panic) = worker.join() {
+                if !thread::panicking() {
+                    panic::resume_unwind(panic);
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for BackgroundRows {
+    type Item = Row;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let receiver = self.receiver.as_ref()?;
+        let row = if self.handle.is_cancelled() {
+            None
+        } else {
+            receiver.recv().ok()
+        };
+        if row.is_none() {
+            self.finish();
+        }
+        row
+    }
+}
+
+impl Drop for BackgroundRows {
+    fn drop(&mut self) {
+        if self.worker.is_some() {
+            self.handle.cancel();
+            self.finish();
+        }
+    }
+}
+
+/// The results of an execution started by [`execute_cancellable`].
+pub struct CancellableRows<'query> {
+    rows: Option<Box<dyn Iterator<Item = Row> + 'query>>,
+    handle: ExecutionHandle,
+}
+
+impl<'query> CancellableRows<'query> {
+    pub fn handle(&self) -> &ExecutionHandle {
+        &self.handle
+    }
+
+    fn finish(&mut self) {
+        self.rows = None;
+        self.handle.state.finish();
+    }
+}
+
+impl<'query> Debug for CancellableRows<'query> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancellableRows")
+            .field("handle", &self.handle)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'query> Iterator for CancellableRows<'query> {
+    type Item = Row;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rows = self.rows.as_mut()?;
+        let row = if self.handle.is_cancelled() {
+            None
+        } else {
+            rows.next()
+        };
+        if row.is_none() {
+            self.finish();
+        }
+        row
+    }
+}
+
+impl<'query> Drop for CancellableRows<'query> {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// Delegates to the executed adapter, ending the iterators passed to and produced by it
+/// once the execution is cancelled.
+///
+/// Only the contexts given to the adapter are ended, rather than the outcomes it produces,
+/// so that it still produces an outcome for each context it was given.
+struct CancellableAdapter<AdapterT> {
+    inner: Arc<AdapterT>,
+    state: Arc<ExecutionState>,
+}
+
+impl<AdapterT> CancellableAdapter<AdapterT> {
+    fn until_cancelled<'a, T: 'a>(
+        &self,
+        iterator: Box<dyn Iterator<Item = T> + 'a>,
+    ) -> Box<dyn Iterator<Item = T> + 'a> {
+        until_cancelled(self.state.clone(), iterator)
+    }
+}
+
+fn until_cancelled<'a, T: 'a>(
+    state: Arc<ExecutionState>,
+    mut iterator: Box<dyn Iterator<Item = T> + 'a>,
+) -> Box<dyn Iterator<Item = T> + 'a> {
+    Box::new(std::iter::from_fn(move || {
+        if state.is_cancelled() {
+            None
+        } else {
+            iterator.next()
+        }
+    }))
+}
+
+impl<'query, AdapterT: Adapter<'query>> Adapter<'query> for CancellableAdapter<AdapterT> {
+    type Vertex = AdapterT::Vertex;
+
+    fn resolve_starting_vertices(
+        &self,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        resolve_info: &ResolveInfo,
+    ) -> VertexIterator<'query, Self::Vertex> {
+        self.until_cancelled(self.inner.resolve_starting_vertices(
+            edge_name,
+            parameters,
+            resolve_info,
+        ))
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'query, Self::Vertex>,
+        type_name: &Arc<str>,
+        property_name: &Arc<str>,
+        resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'query, Self::Vertex, FieldValue> {
+        self.inner.resolve_property(
+            self.until_cancelled(contexts),
+            type_name,
+            property_name,
+            resolve_info,
+        )
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: ContextIterator<'query, Self::Vertex>,
+        type_name: &Arc<str>,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        resolve_info: &ResolveEdgeInfo,
+    ) -> ContextOutcomeIterator<'query, Self::Vertex, VertexIterator<'query, Self::Vertex>> {
+        let state = self.state.clone();
+        let neighbors = self.inner.resolve_neighbors(
+            self.until_cancelled(contexts),
+            type_name,
+            edge_name,
+            parameters,
+            resolve_info,
+        );
+        Box::new(
+            neighbors.map(move |(context, neighbors)| {
+                (context, until_cancelled(state.clone(), neighbors))
+            }),
+        )
+    }
+
+    fn resolve_coercion(
+        &self,
+        contexts: ContextIterator<'query, Self::Vertex>,
+        type_name: &Arc<str>,
+        coerce_to_type: &Arc<str>,
+        resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'query, Self::Vertex, bool> {
+        self.inner.resolve_coercion(
+            self.until_cancelled(contexts),
+            type_name,
+            coerce_to_type,
+            resolve_info,
+        )
+    }
+
+    fn vertex_identity(&self, vertex: &Self::Vertex) -> Option<u64> {
+        self.inner.vertex_identity(vertex)
+    }
+
+    fn skip_revisited_vertices_in_recursion(&self) -> bool {
+        self.inner.skip_revisited_vertices_in_recursion()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc, thread, time::Duration};
+
+    use crate::{
+        frontend::parse,
+        interpreter::{
+            error::QueryArgumentsError, helpers::resolve_property_with, Adapter, ContextIterator,
+            ContextOutcomeIterator, ResolveEdgeInfo, ResolveInfo, VertexIterator,
+        },
+        ir::{EdgeParameters, FieldValue, IndexedQuery},
+        numbers_interpreter::NumbersAdapter,
+        schema::Schema,
+    };
+
+    use super::{execute_cancellable, execute_in_background};
+
+    fn numbers_query(query: &str) -> Arc<IndexedQuery> {
+        let schema = Schema::parse(include_str!("../../test_data/schemas/numbers.graphql"))
+            .expect("schema is not valid");
+        parse(&schema, query).unwrap()
+    }
+
+    /// Numbers counting up from zero without end, so that only cancelling stops
+    /// a query that filters out all but the first of them.
+    struct EndlessAdapter {
+        panic_at: Option<i64>,
+    }
+
+    impl<'a> Adapter<'a> for EndlessAdapter {
+        type Vertex = i64;
+
+        fn resolve_starting_vertices(
+            &self,
+            _edge_name: &Arc<str>,
+            _parameters: &EdgeParameters,
+            _resolve_info: &ResolveInfo,
+        ) -> VertexIterator<'a, Self::Vertex> {
+            Box::new(0..)
+        }
+
+        fn resolve_property(
+            &self,
+            contexts: ContextIterator<'a, Self::Vertex>,
+            _type_name: &Arc<str>,
+            _property_name: &Arc<str>,
+            _resolve_info: &ResolveInfo,
+        ) -> ContextOutcomeIterator<'a, Self::Vertex, FieldValue> {
+            let panic_at = self.panic_at;
+            resolve_property_with(contexts, move |value| {
+                assert_ne!(Some(*value), panic_at, "the adapter failed");
+                FieldValue::Int64(*value)
+            })
+        }
+
+        fn resolve_neighbors(
+            &self,
+            _contexts: ContextIterator<'a, Self::Vertex>,
+            _type_name: &Arc<str>,
+            _edge_name: &Arc<str>,
+            _parameters: &EdgeParameters,
+            _resolve_info: &ResolveEdgeInfo,
+        ) -> ContextOutcomeIterator<'a, Self::Vertex, VertexIterator<'a, Self::Vertex>> {
+            unreachable!("the query has no edges")
+        }
+
+        fn resolve_coercion(
+            &self,
+            _contexts: ContextIterator<'a, Self::Vertex>,
+            _type_name: &Arc<str>,
+            _coerce_to_type: &Arc<str>,
+            _resolve_info: &ResolveInfo,
+        ) -> ContextOutcomeIterator<'a, Self::Vertex, bool> {
+            unreachable!("the query has no coercions")
+        }
+    }
+
+    const FIRST_NUMBER_QUERY: &str = r#"
+{
+    Number(max: 0) {
+        value @output @filter(op: "=", value: ["$zero"])
+    }
+}"#;
+
+    fn first_number_arguments() -> Arc<BTreeMap<Arc<str>, FieldValue>> {
+        Arc::new(BTreeMap::from([("zero".into(), FieldValue::Int64(0))]))
+    }
+
+    #[test]
+    fn results_are_consumed_on_another_thread() {
+        fn assert_send<T: Send>(_: &T) {}
+
+        let query = numbers_query(
+            r#"
+{
+    Number(min: 1, max: 10) {
+        value @output @filter(op: ">", value: ["$min"])
+    }
+}"#,
+        );
+        let arguments = Arc::new(BTreeMap::from([("min".into(), FieldValue::Int64(7))]));
+        let rows =
+            execute_in_background(Arc::new(NumbersAdapter::new()), query, arguments).unwrap();
+        assert_send(&rows);
+
+        let handle = rows.handle().clone();
+        let values: Vec<_> =
+            thread::spawn(move || rows.map(|row| row["value"].clone()).collect::<Vec<_>>())
+                .join()
+                .unwrap();
+        assert_eq!(vec![FieldValue::Int64(8), 9.into(), 10.into()], values);
+        assert!(handle.is_finished());
+        assert!(!handle.is_cancelled());
+    }
+
+    #[test]
+    fn invalid_arguments_are_reported_before_executing() {
+        let query = r#"
+{
+    Number(min: 1, max: 10) {
+        value @output @filter(op: ">", value: ["$min"])
+    }
+}"#;
+        let expected = QueryArgumentsError::MissingArguments(vec!["min".to_string()]);
+        let error = execute_in_background(
+            Arc::new(NumbersAdapter::new()),
+            numbers_query(query),
+            Default::default(),
+        )
+        .unwrap_err();
+        assert_eq!(expected, error);
+
+        let error = execute_cancellable(
+            Arc::new(NumbersAdapter::new()),
+            numbers_query(query),
+            Default::default(),
+        )
+        .unwrap_err();
+        assert_eq!(expected, error);
+    }
+
+    #[test]
+    fn cancelled_executions_in_the_background_stop_between_adapter_calls() {
+        let mut rows = execute_in_background(
+            Arc::new(EndlessAdapter { panic_at: None }),
+            numbers_query(FIRST_NUMBER_QUERY),
+            first_number_arguments(),
+        )
+        .unwrap();
+        assert_eq!(
+            Some(&FieldValue::Int64(0)),
+            rows.next().as_ref().map(|row| &row["value"])
+        );
+
+        // The execution is looking for another number equal to zero, which it won't find.
+        let handle = rows.handle().clone();
+        thread::spawn(move || handle.cancel()).join().unwrap();
+        assert!(rows.handle().is_cancelled());
+        assert_eq!(None, rows.next());
+        assert!(rows.handle().is_finished());
+        assert_eq!(None, rows.next());
+    }
+
+    #[test]
+    fn cancelled_executions_stop_while_producing_a_result() {
+        let mut rows = execute_cancellable(
+            Arc::new(EndlessAdapter { panic_at: None }),
+            numbers_query(FIRST_NUMBER_QUERY),
+            first_number_arguments(),
+        )
+        .unwrap();
+        assert_eq!(
+            Some(&FieldValue::Int64(0)),
+            rows.next().as_ref().map(|row| &row["value"])
+        );
+
+        // Looking for the next result never ends unless the execution is cancelled meanwhile.
+        let handle = rows.handle().clone();
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            handle.cancel();
+        });
+        assert_eq!(None, rows.next());
+        canceller.join().unwrap();
+        assert!(rows.handle().is_cancelled());
+        assert!(rows.handle().is_finished());
+    }
+
+    #[test]
+    fn dropped_results_finish_the_execution() {
+        let mut rows = execute_in_background(
+            Arc::new(EndlessAdapter { panic_at: None }),
+            numbers_query(FIRST_NUMBER_QUERY),
+            first_number_arguments(),
+        )
+        .unwrap();
+        rows.next().unwrap();
+        let handle = rows.handle().clone();
+        drop(rows);
+        assert!(handle.is_finished());
+        assert!(handle.is_cancelled());
+
+        let mut rows = execute_cancellable(
+            Arc::new(NumbersAdapter::new()),
+            numbers_query("{ Number(max: 10) { value @output } }"),
+            Default::default(),
+        )
+        .unwrap();
+        rows.next().unwrap();
+        let handle = rows.handle().clone();
+        drop(rows);
+        assert!(handle.is_finished());
+        assert!(!handle.is_cancelled());
+    }
+
+    #[test]
+    #[should_panic(expected = "the adapter failed")]
+    fn panics_in_the_background_are_resumed_by_the_consumer() {
+        let rows = execute_in_background(
+            Arc::new(EndlessAdapter { panic_at: Some(3) }),
+            numbers_query(FIRST_NUMBER_QUERY),
+            first_number_arguments(),
+        )
+        .unwrap();
+        for _ in rows {}
+    }
+}
@@ -0,0 +1,266 @@
+//! Estimating how expensive a query may be to execute, without executing it.
+//!
+//! Services that execute untrusted queries can use [`ComplexityLimits`] to reject queries
+//! that are likely to be too expensive, before the adapter does any work on their behalf.
+use crate::ir::{FieldCost, IRQuery, IRQueryComponent};
+
+use super::error::ComplexityError;
+
+/// Measures of how expensive a query may be to execute, computed from the query's structure
+/// and the `@cost` annotations of the edges it expands.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryComplexity {
+    /// The largest number of edges between the query's root vertex and any other vertex,
+    /// counting each level of a `@recurse` as a separate edge.
+    pub depth: usize,
+
+    /// The largest number of `@fold` directives nested within each other.
+    pub fold_depth: usize,
+
+    /// The largest depth of any `@recurse` directive in the query.
+    pub recursion_depth: usize,
+
+    /// The estimated total cost of the edge expansions the query may perform.
+    ///
+    /// Each expansion costs its edge's [`FieldCost::estimate`], and produces the edge's
+    /// `@cost(fanout: Int)` neighbors each of which expands the edges beyond it.
+    /// Edges without a `@cost` annotation count as producing a single low-latency neighbor,
+    /// and the query's root edge is counted as a single unannotated expansion.
+    /// Saturates at `u64::MAX` instead of overflowing.
+    pub score: u64,
+}
+
+impl QueryComplexity {
+    pub fn of(query: &IRQuery) -> Self {
+        let mut complexity = Self {
+            score: 1,
+            ..Default::default()
+        };
+        complexity.visit_component(&query.root_component, 0, 0, 1);
+        complexity
+    }
+
+    fn visit_component(
+        &mut self,
+        component: &IRQueryComponent,
+        depth: usize,
+        fold_depth: usize,
+        root_vertices: u64,
+    ) {
+        self.fold_depth = self.fold_depth.max(fold_depth);
+
+        // The number of vertices each of the component's vertices is expected to represent,
+        // together with the vertex's depth in the query.
+        let mut stack = vec![(component.root, depth, root_vertices)];
+        while let Some((vid, depth, vertices)) = stack.pop() {
+            self.depth = self.depth.max(depth);
+
+            for edge in component.edges.values().filter(|edge| edge.from_vid == vid) {
+                let recursion_depth = edge.recursive.as_ref().map(|r| r.depth.get());
+                if let Some(recursion_depth) = recursion_depth {
+                    self.recursion_depth = self.recursion_depth.max(recursion_depth);
+                }
+
+                let neighbors = self.expand(vertices, edge.cost, recursion_depth);
+                stack.push((edge.to_vid, depth + recursion_depth.unwrap_or(1), neighbors));
+            }
+
+            for fold in component.folds.values().filter(|fold| fold.from_vid == vid) {
+                let neighbors = self.expand(vertices, fold.cost, None);
+                self.visit_component(&fold.component, depth + 1, fold_depth + 1, neighbors);
+            }
+        }
+    }
+
+    /// Add the cost of expanding an edge from the given number of vertices to the score,
+    /// and return the expected number of neighboring vertices.
+    fn expand(&mut self, vertices: u64, cost: Option<FieldCost>, recursion: Option<usize>) -> u64 {
+        let fanout = cost.and_then(|cost| cost.fanout).unwrap_or(1);
+        let estimate = cost.map_or(1, |cost| cost.estimate());
+
+        let (expansions, neighbors) = match recursion {
+            None => (vertices, vertices.saturating_mul(fanout)),
+            Some(depth) => {
+                // Recursion starts at the origin vertex itself, and every vertex found
+                // at a depth less than the recursion's depth is expanded again.
+                let mut level = vertices;
+                let mut expansions = 0u64;
+                let mut neighbors = vertices;
+                for _ in 0..depth {
+                    expansions = expansions.saturating_add(level);
+                    level = level.saturating_mul(fanout);
+                    neighbors = neighbors.saturating_add(level);
+                }
+                (expansions, neighbors)
+            }
+        };
+
+        self.score = self
+            .score
+            .saturating_add(expansions.saturating_mul(estimate));
+        neighbors
+    }
+}
+
+/// Upper bounds on the [`QueryComplexity`] of the queries a service is willing to execute.
+///
+/// Limits that are `None` are not enforced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComplexityLimits {
+    pub max_depth: Option<usize>,
+    pub max_fold_depth: Option<usize>,
+    pub max_recursion_depth: Option<usize>,
+    pub max_score: Option<u64>,
+}
+
+impl ComplexityLimits {
+    /// Estimate the query's complexity and check it against these limits.
+    ///
+    /// Returns an error listing every exceeded limit.
+    pub fn check(&self, query: &IRQuery) -> Result<QueryComplexity, ComplexityError> {
+        let complexity = QueryComplexity::of(query);
+
+        let mut errors = vec![];
+        if let Some(limit) = self.max_depth.filter(|limit| complexity.depth > *limit) {
+            errors.push(ComplexityError::DepthLimitExceeded(complexity.depth, limit));
+        }
+        if let Some(limit) = self
+            .max_fold_depth
+            .filter(|limit| complexity.fold_depth > *limit)
+        {
+            errors.push(ComplexityError::FoldDepthLimitExceeded(
+                complexity.fold_depth,
+                limit,
+            ));
+        }
+        if let Some(limit) = self
+            .max_recursion_depth
+            .filter(|limit| complexity.recursion_depth > *limit)
+        {
+            errors.push(ComplexityError::RecursionDepthLimitExceeded(
+                complexity.recursion_depth,
+                limit,
+            ));
+        }
+        if let Some(limit) = self.max_score.filter(|limit| complexity.score > *limit) {
+            errors.push(ComplexityError::ScoreLimitExceeded(complexity.score, limit));
+        }
+
+        if errors.is_empty() {
+            Ok(complexity)
+        } else {
+            Err(errors.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{frontend::parse, interpreter::error::ComplexityError, schema::Schema};
+
+    use super::{ComplexityLimits, QueryComplexity};
+
+    fn complexity(schema_path: &str, query: &str) -> QueryComplexity {
+        let schema = Schema::parse(fs::read_to_string(schema_path).unwrap()).unwrap();
+        let indexed_query = parse(&schema, query).unwrap();
+        QueryComplexity::of(&indexed_query.ir_query)
+    }
+
+    #[test]
+    fn fanout_multiplies_the_cost_of_later_edges() {
+        let query = r#"
+{
+    Package(name: "trustfall") {
+        releases {
+            version @output
+            unannotated {
+                name @output
+            }
+        }
+        owner {
+            name @output(name: "owner")
+        }
+    }
+}"#;
+        let complexity = complexity("test_data/tests/valid_schemas/edge_costs.graphql", query);
+
+        // The root edge (1), `releases` costing 1000 x 100 for high latency, `owner` (1),
+        // and one `unannotated` edge expansion for each of the 1000 releases.
+        assert_eq!(
+            QueryComplexity {
+                depth: 2,
+                fold_depth: 0,
+                recursion_depth: 0,
+                score: 1 + 100_000 + 1 + 1000,
+            },
+            complexity,
+        );
+    }
+
+    #[test]
+    fn folds_and_recursion_are_measured() {
+        let query = r#"
+{
+    Number(max: 10) {
+        successor @recurse(depth: 3) {
+            multiple(max: 3) @fold {
+                predecessor @fold {
+                    value @output
+                }
+            }
+        }
+    }
+}"#;
+        let complexity = complexity("test_data/schemas/numbers.graphql", query);
+        assert_eq!(3, complexity.recursion_depth);
+        assert_eq!(2, complexity.fold_depth);
+        assert_eq!(5, complexity.depth);
+
+        // Without `@cost` annotations, the recursion expands once per level,
+        // and reaches four vertices each of which expands both folds.
+        assert_eq!(1 + 3 + 4 + 4, complexity.score);
+    }
+
+    #[test]
+    fn queries_exceeding_limits_are_rejected() {
+        let schema = Schema::parse(
+            fs::read_to_string("test_data/tests/valid_schemas/edge_costs.graphql").unwrap(),
+        )
+        .unwrap();
+        let query = r#"
+{
+    Package(name: "trustfall") {
+        releases {
+            unannotated {
+                name @output
+            }
+        }
+    }
+}"#;
+        let indexed_query = parse(&schema, query).unwrap();
+
+        let limits = ComplexityLimits {
+            max_depth: Some(2),
+            max_score: Some(1_000_000),
+            ..Default::default()
+        };
+        assert!(limits.check(&indexed_query.ir_query).is_ok());
+
+        let limits = ComplexityLimits {
+            max_depth: Some(1),
+            max_fold_depth: Some(0),
+            max_score: Some(1000),
+            ..Default::default()
+        };
+        let error = limits.check(&indexed_query.ir_query).unwrap_err();
+        assert_eq!(
+            ComplexityError::MultipleErrors(crate::util::DisplayVec(vec![
+                ComplexityError::DepthLimitExceeded(2, 1),
+                ComplexityError::ScoreLimitExceeded(1 + 100_000 + 1000, 1000),
+            ])),
+            error,
+        );
+    }
+}
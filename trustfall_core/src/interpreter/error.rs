@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{ir::FieldValue, util::DisplayVec};
+use crate::{
+    frontend::error::FrontendError,
+    ir::{canonical::QueryHash, json::QueryJsonError, FieldValue, InvalidIRQueryError},
+    schema::error::IncompatibleQueryError,
+    util::DisplayVec,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, thiserror::Error)]
 pub enum QueryArgumentsError {
@@ -16,6 +21,24 @@ pub enum QueryArgumentsError {
     )]
     ArgumentTypeError(String, String, FieldValue),
 
+    #[error(
+        "The provided value for argument \"{0}\" of custom scalar type {1} is not valid: {3} \
+        (value: {2:?})"
+    )]
+    InvalidCustomScalarArgument(String, String, FieldValue, String),
+
+    #[error(
+        "The provided value for argument \"{0}\" of enum type {1} is not one of the enum's \
+        declared values {3:?}: {2:?}"
+    )]
+    UndeclaredEnumValue(String, String, FieldValue, Vec<String>),
+
+    #[error(
+        "The provided value for argument \"{0}\" is used as a regular expression, \
+        but is not a valid one: {2} (value: {1:?})"
+    )]
+    InvalidRegexArgument(String, String, String),
+
     #[error("Multiple argument errors: {0}")]
     MultipleErrors(DisplayVec<QueryArgumentsError>),
 }
@@ -30,3 +53,94 @@ impl From<Vec<QueryArgumentsError>> for QueryArgumentsError {
         }
     }
 }
+
+/// Errors from applying an [`AccessPolicy`](super::access::AccessPolicy) to a query.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, thiserror::Error)]
+pub enum AccessError {
+    #[error("Access to field \"{1}\" on type \"{0}\" was denied.")]
+    AccessDenied(String, String),
+
+    #[error(
+        "Field \"{1}\" on type \"{0}\" is redacted, but the query uses its values in filters. \
+        Redacted fields may only be used in outputs."
+    )]
+    RedactedFieldUsedInFilter(String, String),
+
+    #[error(
+        "Edge \"{1}\" on type \"{0}\" cannot be redacted. Access to edges may only be \
+        allowed or denied."
+    )]
+    RedactedEdge(String, String),
+
+    #[error("Multiple access errors: {0}")]
+    MultipleErrors(DisplayVec<AccessError>),
+}
+
+impl From<Vec<AccessError>> for AccessError {
+    fn from(v: Vec<AccessError>) -> Self {
+        assert!(!v.is_empty());
+        if v.len() == 1 {
+            v.into_iter().next().unwrap()
+        } else {
+            Self::MultipleErrors(DisplayVec(v))
+        }
+    }
+}
+
+/// Errors from checking a query against [`ComplexityLimits`](super::complexity::ComplexityLimits).
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, thiserror::Error)]
+pub enum ComplexityError {
+    #[error("The query's depth of {0} exceeds the limit of {1}.")]
+    DepthLimitExceeded(usize, usize),
+
+    #[error("The query nests {0} @fold directives within each other, exceeding the limit of {1}.")]
+    FoldDepthLimitExceeded(usize, usize),
+
+    #[error("The query recurses to depth {0}, exceeding the limit of {1}.")]
+    RecursionDepthLimitExceeded(usize, usize),
+
+    #[error("The query's estimated complexity score of {0} exceeds the limit of {1}.")]
+    ScoreLimitExceeded(u64, u64),
+
+    #[error("Multiple complexity limits exceeded: {0}")]
+    MultipleErrors(DisplayVec<ComplexityError>),
+}
+
+impl From<Vec<ComplexityError>> for ComplexityError {
+    fn from(v: Vec<ComplexityError>) -> Self {
+        assert!(!v.is_empty());
+        if v.len() == 1 {
+            v.into_iter().next().unwrap()
+        } else {
+            Self::MultipleErrors(DisplayVec(v))
+        }
+    }
+}
+
+/// Errors from registering or executing queries in a [`QueryRegistry`](super::registry::QueryRegistry).
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, thiserror::Error)]
+pub enum RegistryError {
+    #[error("The query is not valid against the registry's schema")]
+    InvalidQuery(#[from] FrontendError),
+
+    #[error("The compiled query is not valid against the registry's schema")]
+    IncompatibleQuery(#[from] IncompatibleQueryError),
+
+    #[error("The compiled query is malformed")]
+    MalformedQuery(#[from] InvalidIRQueryError),
+
+    #[error("The stored query could not be loaded")]
+    InvalidStoredQuery(#[from] QueryJsonError),
+
+    #[error("The name \"{0}\" is already registered for a different query, with hash {1}.")]
+    DuplicatedQueryName(String, QueryHash),
+
+    #[error("No query is registered with the identifier \"{0}\".")]
+    UnregisteredQuery(String),
+
+    #[error("The arguments are not valid for the registered query")]
+    InvalidArguments(#[from] QueryArgumentsError),
+}
@@ -1,27 +1,36 @@
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::{hash_map::Entry, BTreeMap, HashMap, VecDeque},
     fmt::Debug,
-    sync::Arc,
+    iter::Fuse,
+    ops::Range,
+    sync::{Arc, Mutex},
 };
 
-use crate::{
-    ir::{
-        Argument, ContextField, EdgeParameters, Eid, FieldRef, FieldValue, FoldSpecificFieldKind,
-        IREdge, IRFold, IRQueryComponent, IRVertex, IndexedQuery, LocalField, Operation, Recursive,
-        Vid,
-    },
-    util::BTreeMapTryInsertExt,
+use crate::ir::{
+    Argument, ContextField, EdgeParameters, Eid, FieldRef, FieldValue, FoldSpecificFieldKind,
+    IREdge, IRFold, IRQueryComponent, IRVertex, IndexedQuery, LocalField, Operation, Recursive,
+    VertexFilter, Vid,
 };
 
 use super::{
-    error::QueryArgumentsError, filtering::apply_filter, Adapter, ContextIterator,
-    ContextOutcomeIterator, DataContext, InterpretedQuery, ResolveEdgeInfo, ResolveInfo,
-    TaggedValue, ValueOrVec, VertexIterator,
+    error::QueryArgumentsError,
+    filtering::apply_filter,
+    has_only_declared_enum_values,
+    planning::{cached_plan, Step},
+    pool::Pool,
+    row::Row,
+    statistics::{Observations, StatisticsCache},
+    Adapter, ContextIterator, ContextOutcomeIterator, DataContext, InterpretedQuery,
+    ResolveEdgeInfo, ResolveInfo, TaggedValue, ValueOrVec, VertexIterator,
 };
 
 #[derive(Debug, Clone)]
 pub(super) struct QueryCarrier {
     pub(in crate::interpreter) query: Option<InterpretedQuery>,
+    pub(in crate::interpreter) coercions: CoercionCache,
+
+    /// Where the execution counts what it observes, if it's recording statistics.
+    pub(in crate::interpreter) observations: Option<Arc<Observations>>,
 }
 
 #[allow(clippy::type_complexity)]
@@ -31,14 +40,74 @@ pub fn interpret_ir<'query, AdapterT: Adapter<'query> + 'query>(
     arguments: Arc<BTreeMap<Arc<str>, FieldValue>>,
 ) -> Result<Box<dyn Iterator<Item = BTreeMap<Arc<str>, FieldValue>> + 'query>, QueryArgumentsError>
 {
-    let query = InterpretedQuery::from_query_and_arguments(indexed_query, arguments)?;
+    let rows = interpret_ir_rows(adapter, indexed_query, arguments)?;
+    Ok(Box::new(rows.map(Row::into_map)))
+}
+
+/// Execute the query like [`interpret_ir`], producing each result as a [`Row`]
+/// rather than a map of output names to values.
+pub fn interpret_ir_rows<'query, AdapterT: Adapter<'query> + 'query>(
+    adapter: Arc<AdapterT>,
+    indexed_query: Arc<IndexedQuery>,
+    arguments: Arc<BTreeMap<Arc<str>, FieldValue>>,
+) -> Result<Box<dyn Iterator<Item = Row> + 'query>, QueryArgumentsError> {
+    execute_query(adapter, indexed_query, arguments, None)
+}
+
+/// Execute the query like [`interpret_ir`], planning it using the statistics the cache has
+/// for it, and recording what this execution observes in the cache.
+///
+/// The observed statistics are recorded once the results iterator is dropped.
+/// See the [`statistics`](super::statistics) module for details.
+#[allow(clippy::type_complexity)]
+pub fn interpret_ir_with_statistics<'query, AdapterT: Adapter<'query> + 'query>(
+    adapter: Arc<AdapterT>,
+    indexed_query: Arc<IndexedQuery>,
+    arguments: Arc<BTreeMap<Arc<str>, FieldValue>>,
+    statistics: &StatisticsCache,
+) -> Result<Box<dyn Iterator<Item = BTreeMap<Arc<str>, FieldValue>> + 'query>, QueryArgumentsError>
+{
+    let rows = execute_query(adapter, indexed_query, arguments, Some(statistics))?;
+    Ok(Box::new(rows.map(Row::into_map)))
+}
+
+fn execute_query<'query, AdapterT: Adapter<'query> + 'query>(
+    adapter: Arc<AdapterT>,
+    indexed_query: Arc<IndexedQuery>,
+    arguments: Arc<BTreeMap<Arc<str>, FieldValue>>,
+    statistics: Option<&StatisticsCache>,
+) -> Result<Box<dyn Iterator<Item = Row> + 'query>, QueryArgumentsError> {
+    let mut query = InterpretedQuery::from_query_and_arguments(indexed_query, arguments)?;
     let root_vid = query.indexed_query.ir_query.root_component.root;
 
+    let (observations, observed) = match statistics {
+        Some(cache) => {
+            let hash = query.indexed_query.content_hash();
+            let observations = Observations::new(cache.clone(), hash, &query.indexed_query);
+            (Some(Arc::new(observations)), cache.get(hash))
+        }
+        None => (None, None),
+    };
+    let plan = cached_plan(&query.indexed_query, observed.as_ref());
+    query.deferred_tags = Arc::new(
+        plan.iter()
+            .filter_map(|step| match step {
+                Step::BeginFold { deferred_tags, .. } => Some(deferred_tags.iter().cloned()),
+                _ => None,
+            })
+            .flatten()
+            .collect(),
+    );
+
     let ir_query = &query.indexed_query.ir_query;
     let root_edge = &ir_query.root_name;
     let root_edge_parameters = &ir_query.root_parameters;
 
-    let mut carrier = QueryCarrier { query: None };
+    let mut carrier = QueryCarrier {
+        query: None,
+        coercions: CoercionCache::default(),
+        observations,
+    };
 
     let resolve_info = ResolveInfo::new(query.clone(), root_vid, false);
 
@@ -49,42 +118,29 @@ pub fn interpret_ir<'query, AdapterT: Adapter<'query> + 'query>(
     );
     carrier.query = Some(resolve_info.into_inner());
 
-    let component = &ir_query.root_component;
-    iterator = compute_component(adapter.clone(), &mut carrier, component, iterator);
+    iterator = execute_steps(adapter, &mut carrier, &plan, 0..plan.len(), iterator);
 
-    Ok(construct_outputs(adapter.as_ref(), &mut carrier, iterator))
-}
-
-fn coerce_if_needed<'query, AdapterT: Adapter<'query>>(
-    adapter: &AdapterT,
-    carrier: &mut QueryCarrier,
-    vertex: &IRVertex,
-    iterator: ContextIterator<'query, AdapterT::Vertex>,
-) -> ContextIterator<'query, AdapterT::Vertex> {
-    match vertex.coerced_from_type.as_ref() {
-        None => iterator,
-        Some(coerced_from) => perform_coercion(
-            adapter,
-            carrier,
-            vertex,
-            coerced_from,
-            &vertex.type_name,
-            iterator,
-        ),
-    }
+    Ok(construct_outputs(&mut carrier, iterator))
 }
 
-fn perform_coercion<'query, AdapterT: Adapter<'query>>(
-    adapter: &AdapterT,
+fn perform_coercion<'query, AdapterT: Adapter<'query> + 'query>(
+    adapter: &Arc<AdapterT>,
     carrier: &mut QueryCarrier,
-    vertex: &IRVertex,
+    vid: Vid,
     coerced_from: &Arc<str>,
     coerce_to: &Arc<str>,
     iterator: ContextIterator<'query, AdapterT::Vertex>,
 ) -> ContextIterator<'query, AdapterT::Vertex> {
     let query = carrier.query.take().expect("query was not returned");
-    let resolve_info = ResolveInfo::new(query, vertex.vid, false);
-    let coercion_iter = adapter.resolve_coercion(iterator, coerced_from, coerce_to, &resolve_info);
+    let resolve_info = ResolveInfo::new(query, vid, false);
+    let coercion_iter = resolve_coercion_using_cache(
+        adapter,
+        &carrier.coercions,
+        iterator,
+        coerced_from,
+        coerce_to,
+        &resolve_info,
+    );
     carrier.query = Some(resolve_info.into_inner());
 
     Box::new(coercion_iter.filter_map(
@@ -98,160 +154,440 @@ fn perform_coercion<'query, AdapterT: Adapter<'query>>(
     ))
 }
 
-fn compute_component<'query, AdapterT: Adapter<'query> + 'query>(
-    adapter: Arc<AdapterT>,
-    carrier: &mut QueryCarrier,
-    component: &IRQueryComponent,
-    mut iterator: ContextIterator<'query, AdapterT::Vertex>,
-) -> ContextIterator<'query, AdapterT::Vertex> {
-    let component_root_vid = component.root;
-    let root_vertex = &component.vertices[&component_root_vid];
+/// Resolve the coercions of the contexts' active vertices, using the outcomes of earlier
+/// coercions of the same vertices to the same type instead of asking the adapter again.
+///
+/// Only vertices the adapter identifies are cached, see [`Adapter::vertex_identity`].
+fn resolve_coercion_using_cache<'query, AdapterT: Adapter<'query> + 'query>(
+    adapter: &Arc<AdapterT>,
+    cache: &CoercionCache,
+    iterator: ContextIterator<'query, AdapterT::Vertex>,
+    coerced_from: &Arc<str>,
+    coerce_to: &Arc<str>,
+    resolve_info: &ResolveInfo,
+) -> ContextOutcomeIterator<'query, AdapterT::Vertex, bool> {
+    let identifying_adapter = adapter.clone();
+    let lookup_cache = cache.clone();
+    let lookup_coerce_to = coerce_to.clone();
+    let record_cache = cache.clone();
+    let record_coerce_to = coerce_to.clone();
+    resolve_unknown_outcomes(
+        iterator,
+        move |context| {
+            let identity = context
+                .active_vertex
+                .as_ref()
+                .and_then(|vertex| identifying_adapter.vertex_identity(vertex));
+            match identity.and_then(|identity| lookup_cache.get(identity, &lookup_coerce_to)) {
+                Some(can_coerce) => Lookup::Known(can_coerce),
+                None => Lookup::Unknown(identity),
+            }
+        },
+        |contexts| adapter.resolve_coercion(contexts, coerced_from, coerce_to, resolve_info),
+        move |identity, can_coerce| {
+            if let Some(identity) = identity {
+                record_cache.insert(identity, &record_coerce_to, *can_coerce);
+            }
+        },
+    )
+}
 
-    iterator = coerce_if_needed(adapter.as_ref(), carrier, root_vertex, iterator);
+/// The outcomes of coercing identified vertices to types, for the duration of one execution.
+#[allow(clippy::type_complexity)]
+#[derive(Debug, Clone, Default)]
+pub(super) struct CoercionCache(Arc<Mutex<HashMap<(u64, Arc<str>), bool>>>);
 
-    for filter_expr in &root_vertex.filters {
-        iterator = apply_local_field_filter(
-            adapter.as_ref(),
-            carrier,
-            component,
-            component.root,
-            filter_expr,
-            iterator,
-        );
+impl CoercionCache {
+    fn get(&self, identity: u64, coerce_to: &Arc<str>) -> Option<bool> {
+        let outcomes = self.0.lock().expect("coercion cache lock was poisoned");
+        outcomes.get(&(identity, coerce_to.clone())).copied()
     }
 
-    iterator = Box::new(iterator.map(move |mut context| {
-        context.record_vertex(component_root_vid);
-        context
-    }));
+    fn insert(&self, identity: u64, coerce_to: &Arc<str>, can_coerce: bool) {
+        let mut outcomes = self.0.lock().expect("coercion cache lock was poisoned");
+        outcomes.insert((identity, coerce_to.clone()), can_coerce);
+    }
+}
 
-    let mut visited_vids: BTreeSet<Vid> = btreeset! {component_root_vid};
-
-    let mut edge_iter = component.edges.values();
-    let mut fold_iter = component.folds.values();
-    let mut next_edge = edge_iter.next();
-    let mut next_fold = fold_iter.next();
-    loop {
-        let (process_next_fold, process_next_edge) = match (next_fold, next_edge) {
-            (None, None) => break,
-            (None, Some(_)) | (Some(_), None) => (next_fold, next_edge),
-            (Some(fold), Some(edge)) => match fold.eid.cmp(&edge.eid) {
-                std::cmp::Ordering::Greater => (None, Some(edge)),
-                std::cmp::Ordering::Less => (Some(fold), None),
-                std::cmp::Ordering::Equal => unreachable!(),
-            },
-        };
+/// Whether a context's outcome is already known, or needs to be resolved by the adapter.
+enum Lookup<T, K> {
+    Known(T),
+
+    /// The adapter resolves the outcome, which is then recorded under this key.
+    Unknown(K),
+}
+
+/// Produce an outcome for each context, asking the adapter only for those whose outcomes
+/// aren't already known.
+///
+/// The `lookup` decides for each context whether its outcome is known. The rest of
+/// the contexts are given to `resolve`, which asks the adapter for their outcomes, and
+/// `record` is called with each of those outcomes as it's produced. The outcomes are
+/// produced in the order of the contexts, whether they were known or not.
+fn resolve_unknown_outcomes<'query, Vertex: Clone + Debug + 'query, T: 'query, K: 'query>(
+    iterator: ContextIterator<'query, Vertex>,
+    mut lookup: impl FnMut(&DataContext<Vertex>) -> Lookup<T, K> + 'query,
+    resolve: impl FnOnce(ContextIterator<'query, Vertex>) -> ContextOutcomeIterator<'query, Vertex, T>,
+    record: impl FnMut(K, &T) + 'query,
+) -> ContextOutcomeIterator<'query, Vertex, T> {
+    let slots: Arc<Mutex<VecDeque<OutcomeSlot<Vertex, T, K>>>> = Default::default();
+
+    let input_slots = slots.clone();
+    let unknown_contexts: ContextIterator<'query, Vertex> =
+        Box::new(iterator.filter_map(move |context| {
+            let lookup = lookup(&context);
+            let mut slots = input_slots.lock().expect("outcome slots lock was poisoned");
+            match lookup {
+                Lookup::Known(outcome) => {
+                    slots.push_back(OutcomeSlot::Known(context, outcome));
+                    None
+                }
+                Lookup::Unknown(key) => {
+                    slots.push_back(OutcomeSlot::Unknown(key));
+                    Some(context)
+                }
+            }
+        }));
 
-        assert!(process_next_fold.is_some() ^ process_next_edge.is_some());
+    Box::new(MergedOutcomes {
+        slots,
+        resolved: resolve(unknown_contexts).fuse(),
+        pending: None,
+        record: Box::new(record),
+    })
+}
 
-        if let Some(fold) = process_next_fold {
-            let from_vid_unvisited = visited_vids.insert(fold.from_vid);
-            let to_vid_unvisited = visited_vids.insert(fold.to_vid);
-            assert!(!from_vid_unvisited);
-            assert!(to_vid_unvisited);
+/// Where the outcome for the next context comes from.
+enum OutcomeSlot<Vertex: Clone + Debug, T, K> {
+    /// The context's outcome was known, so the context bypassed the adapter.
+    Known(DataContext<Vertex>, T),
 
-            iterator = compute_fold(
-                adapter.clone(),
-                carrier,
-                &component.vertices[&fold.from_vid],
-                component,
-                fold.clone(),
-                iterator,
-            );
+    /// The adapter resolves the context's outcome.
+    Unknown(K),
+}
+
+#[allow(clippy::type_complexity)]
+struct MergedOutcomes<'query, Vertex: Clone + Debug, T, K> {
+    /// Filled in order as the adapter pulls contexts, including those it doesn't see.
+    slots: Arc<Mutex<VecDeque<OutcomeSlot<Vertex, T, K>>>>,
+    resolved: Fuse<ContextOutcomeIterator<'query, Vertex, T>>,
+
+    /// An outcome from the adapter that's preceded by known ones which haven't been produced yet.
+    pending: Option<(DataContext<Vertex>, T)>,
+    record: Box<dyn FnMut(K, &T) + 'query>,
+}
+
+impl<'query, Vertex: Clone + Debug, T, K> MergedOutcomes<'query, Vertex, T, K> {
+    fn slots_are_empty(&self) -> bool {
+        self.slots
+            .lock()
+            .expect("outcome slots lock was poisoned")
+            .is_empty()
+    }
+}
+
+impl<'query, Vertex: Clone + Debug, T, K> Iterator for MergedOutcomes<'query, Vertex, T, K> {
+    type Item = (DataContext<Vertex>, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let slot = self
+                .slots
+                .lock()
+                .expect("outcome slots lock was poisoned")
+                .pop_front();
+            match slot {
+                Some(OutcomeSlot::Known(context, outcome)) => {
+                    return Some((context, outcome));
+                }
+                Some(OutcomeSlot::Unknown(key)) => {
+                    let (context, outcome) = self
+                        .pending
+                        .take()
+                        .or_else(|| self.resolved.next())
+                        .expect("adapter produced fewer outcomes than it was given contexts");
+                    (self.record)(key, &outcome);
+                    return Some((context, outcome));
+                }
+                None => {
+                    // Pulling the adapter's next outcome makes it pull contexts,
+                    // which fills the slots up to and including that outcome's.
+                    // The adapter may also run out of contexts after pulling known ones.
+                    debug_assert!(self.pending.is_none());
+                    self.pending = self.resolved.next();
+                    if self.pending.is_none() && self.slots_are_empty() {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A tag imported into a fold whose value is resolved when it's first used,
+/// see [`Step::BeginFold`].
+///
+/// Clones share the value, so the contexts of the fold's elements for the same
+/// starting vertex resolve it at most once, whichever of them uses it first.
+#[derive(Debug, Clone)]
+pub(super) struct DeferredTag<Vertex>(Arc<(Vertex, Mutex<Option<FieldValue>>)>);
+
+impl<Vertex> DeferredTag<Vertex> {
+    pub(super) fn new(vertex: Vertex) -> Self {
+        Self(Arc::new((vertex, Mutex::new(None))))
+    }
+
+    /// The vertex whose property is the tag's value.
+    pub(super) fn vertex(&self) -> &Vertex {
+        &self.0 .0
+    }
+
+    pub(super) fn value(&self) -> Option<FieldValue> {
+        self.0
+             .1
+            .lock()
+            .expect("deferred tag lock was poisoned")
+            .clone()
+    }
 
-            next_fold = fold_iter.next();
-        } else if let Some(edge) = process_next_edge {
-            let from_vid_unvisited = visited_vids.insert(edge.from_vid);
-            let to_vid_unvisited = visited_vids.insert(edge.to_vid);
-            assert!(!from_vid_unvisited);
-            assert!(to_vid_unvisited);
+    fn resolve(&self, value: FieldValue) {
+        *self.0 .1.lock().expect("deferred tag lock was poisoned") = Some(value);
+    }
+}
 
-            iterator = expand_edge(
+/// Execute the steps of the plan in the range, which contains the [`Step::EndFold`]
+/// of every [`Step::BeginFold`] in it.
+fn execute_steps<'query, AdapterT: Adapter<'query> + 'query>(
+    adapter: Arc<AdapterT>,
+    carrier: &mut QueryCarrier,
+    plan: &Arc<[Step]>,
+    steps: Range<usize>,
+    mut iterator: ContextIterator<'query, AdapterT::Vertex>,
+) -> ContextIterator<'query, AdapterT::Vertex> {
+    let indexed_query = carrier
+        .query
+        .as_ref()
+        .expect("query was not returned")
+        .indexed_query
+        .clone();
+
+    let mut index = steps.start;
+    while index < steps.end {
+        iterator = match &plan[index] {
+            Step::Coerce(vid) => {
+                let vertex = &indexed_query.vertices[vid];
+                let coerced_from = vertex
+                    .coerced_from_type
+                    .as_ref()
+                    .expect("vertex is not coerced");
+                perform_coercion(
+                    &adapter,
+                    carrier,
+                    *vid,
+                    coerced_from,
+                    &vertex.type_name,
+                    iterator,
+                )
+            }
+            Step::Filter(vid, filter_index) => apply_local_field_filter(
                 adapter.as_ref(),
                 carrier,
-                component,
-                edge.from_vid,
-                edge.to_vid,
-                edge,
+                &indexed_query.vids[vid],
+                *vid,
+                &indexed_query.vertices[vid].filters[*filter_index],
                 iterator,
-            );
-
-            next_edge = edge_iter.next();
-        }
+            ),
+            Step::Record(vid) => {
+                let vid = *vid;
+                let iterator = Box::new(iterator.map(move |mut context| {
+                    context.record_vertex(vid);
+                    context
+                }));
+                match carrier.observations.as_ref().and_then(|o| o.edge_to(vid)) {
+                    Some(eid) => observe(carrier, eid, Observations::count_retained, iterator),
+                    None => iterator,
+                }
+            }
+            Step::ExpandEdge(edge) => {
+                let iterator = observe(carrier, edge.eid, Observations::count_source, iterator);
+                let iterator = expand_edge(
+                    &adapter,
+                    carrier,
+                    &indexed_query.vids[&edge.from_vid],
+                    edge,
+                    iterator,
+                );
+                observe(carrier, edge.eid, Observations::count_neighbor, iterator)
+            }
+            Step::BeginFold {
+                fold,
+                end,
+                deferred_tags,
+            } => {
+                let fold_steps = index + 1..*end;
+                // The fold executes the steps of its component, so continue from its end.
+                index = *end - 1;
+                let iterator = observe(carrier, fold.eid, Observations::count_source, iterator);
+                let iterator = begin_fold(
+                    adapter.clone(),
+                    carrier,
+                    fold.clone(),
+                    deferred_tags,
+                    plan,
+                    fold_steps,
+                    iterator,
+                );
+                observe(carrier, fold.eid, Observations::count_neighbor, iterator)
+            }
+            Step::EndFold(fold) => {
+                let iterator = end_fold(adapter.clone(), carrier, fold.clone(), iterator);
+                observe(carrier, fold.eid, Observations::count_retained, iterator)
+            }
+            Step::Output(output_name) => {
+                resolve_output(adapter.as_ref(), carrier, output_name, iterator)
+            }
+        };
+        index += 1;
     }
 
     iterator
 }
 
-fn construct_outputs<'query, AdapterT: Adapter<'query>>(
+/// Count each context passing through the iterator for the expansion,
+/// if the execution is recording statistics.
+fn observe<'query, Vertex: Clone + Debug + 'query>(
+    carrier: &QueryCarrier,
+    eid: Eid,
+    count: fn(&Observations, Eid),
+    iterator: ContextIterator<'query, Vertex>,
+) -> ContextIterator<'query, Vertex> {
+    match &carrier.observations {
+        Some(observations) => {
+            let observations = observations.clone();
+            Box::new(iterator.inspect(move |_| count(&observations, eid)))
+        }
+        None => iterator,
+    }
+}
+
+/// Resolve the value of an output from the query's root component,
+/// and add it to each context's values.
+fn resolve_output<'query, AdapterT: Adapter<'query>>(
     adapter: &AdapterT,
     carrier: &mut QueryCarrier,
+    output_name: &str,
     iterator: ContextIterator<'query, AdapterT::Vertex>,
-) -> Box<dyn Iterator<Item = BTreeMap<Arc<str>, FieldValue>> + 'query> {
-    let mut query = carrier.query.take().expect("query was not returned");
+) -> ContextIterator<'query, AdapterT::Vertex> {
+    let query = carrier.query.take().expect("query was not returned");
+    let indexed_query = query.indexed_query.clone();
+    let context_field = &indexed_query.ir_query.root_component.outputs[output_name];
+    let vertex_id = context_field.vertex_id;
+
+    let moved_iterator = Box::new(iterator.map(move |context| {
+        let new_vertex = context.vertices[&vertex_id].clone();
+        context.move_to_vertex(new_vertex)
+    }));
+
+    let resolve_info = ResolveInfo::new(query, vertex_id, true);
+    let field_data_iterator = adapter.resolve_property(
+        moved_iterator,
+        &indexed_query.vertices[&vertex_id].type_name,
+        &context_field.field_name,
+        &resolve_info,
+    );
+    carrier.query = Some(resolve_info.into_inner());
+
+    Box::new(field_data_iterator.map(|(mut context, value)| {
+        context.values.push(value);
+        context
+    }))
+}
 
-    let root_component = query.indexed_query.ir_query.root_component.clone();
-    let mut output_names: Vec<Arc<str>> = query
+/// Assemble the results from the contexts, whose values are those of the outputs
+/// from the query's root component in order of name, as resolved by [`Step::Output`].
+fn construct_outputs<'query, Vertex: Clone + Debug + 'query>(
+    carrier: &mut QueryCarrier,
+    iterator: ContextIterator<'query, Vertex>,
+) -> Box<dyn Iterator<Item = Row> + 'query> {
+    let query = carrier.query.as_ref().expect("query was not returned");
+
+    // Both maps are ordered by name, so when there are no folded outputs,
+    // the values of the root component's outputs are already in the order of the row.
+    let names: Arc<[Arc<str>]> = query.indexed_query.outputs.keys().cloned().collect();
+    fn position(names: &[Arc<str>], name: &str) -> usize {
+        names
+            .binary_search_by(|x| x.as_ref().cmp(name))
+            .expect("output is not in the row")
+    }
+    let root_positions: Vec<usize> = query
         .indexed_query
         .ir_query
         .root_component
         .outputs
         .keys()
-        .cloned()
+        .map(|name| position(&names, name))
+        .collect();
+    let has_folded_outputs = root_positions.len() != names.len();
+    let enum_outputs: Vec<_> = query
+        .indexed_query
+        .outputs
+        .values()
+        .filter_map(|output| {
+            let type_name = output.value_type.base_type();
+            query
+                .indexed_query
+                .ir_query
+                .enum_values
+                .get(type_name)
+                .map(|values| {
+                    let index = position(&names, &output.name);
+                    (index, type_name.to_string(), values.clone())
+                })
+        })
         .collect();
-    output_names.sort_unstable(); // to ensure deterministic resolve_property() ordering
-
-    let mut output_iterator = iterator;
-
-    for output_name in output_names.iter() {
-        let context_field = &root_component.outputs[output_name];
-        let vertex_id = context_field.vertex_id;
-
-        let moved_iterator = Box::new(output_iterator.map(move |context| {
-            let new_vertex = context.vertices[&vertex_id].clone();
-            context.move_to_vertex(new_vertex)
-        }));
-
-        let resolve_info = ResolveInfo::new(query, vertex_id, true);
-
-        let type_name = &root_component.vertices[&vertex_id].type_name;
-        let field_data_iterator = adapter.resolve_property(
-            moved_iterator,
-            type_name,
-            &context_field.field_name,
-            &resolve_info,
-        );
-        query = resolve_info.into_inner();
-
-        output_iterator = Box::new(field_data_iterator.map(|(mut context, value)| {
-            context.values.push(value);
-            context
-        }));
-    }
-    let expected_output_names: BTreeSet<_> = query.indexed_query.outputs.keys().cloned().collect();
-    carrier.query = Some(query);
 
-    Box::new(output_iterator.map(move |mut context| {
+    let mut root_values = Vec::with_capacity(root_positions.len());
+    Box::new(iterator.map(move |mut context| {
         assert!(
-            context.values.len() == output_names.len(),
-            "expected {output_names:?} but got {:?}",
+            context.values.len() == root_positions.len(),
+            "expected {} values for the outputs {names:?} but got {:?}",
+            root_positions.len(),
             &context.values
         );
 
-        let mut output: BTreeMap<Arc<str>, FieldValue> = output_names
-            .iter()
-            .cloned()
-            .zip(context.values.drain(..))
-            .collect();
+        let mut values = Vec::with_capacity(names.len());
+        if has_folded_outputs {
+            values.resize(names.len(), FieldValue::Null);
+            context.values.take_all_into(&mut root_values);
+            for (index, value) in root_positions.iter().zip(root_values.drain(..)) {
+                values[*index] = value;
+            }
 
-        for ((_, output_name), output_value) in context.folded_values {
-            let existing = output.insert(output_name, output_value.into());
-            assert!(existing.is_none());
+            let mut folded_outputs = 0;
+            for ((_, output_name), output_value) in context.folded_values {
+                values[position(&names, &output_name)] = output_value.into();
+                folded_outputs += 1;
+            }
+            assert_eq!(
+                names.len(),
+                root_positions.len() + folded_outputs,
+                "the root and folded outputs don't match the outputs {names:?}"
+            );
+        } else {
+            context.values.take_all_into(&mut values);
         }
 
-        debug_assert_eq!(expected_output_names, output.keys().cloned().collect());
+        for (index, enum_name, allowed_values) in &enum_outputs {
+            let value = &values[*index];
+            debug_assert!(
+                has_only_declared_enum_values(value, allowed_values),
+                "adapter produced value {value:?} for output \"{}\" of enum type \
+                {enum_name}, but that enum only allows the values {allowed_values:?}",
+                names[*index],
+            );
+        }
 
-        output
+        Row::new(names.clone(), values)
     }))
 }
 
@@ -342,25 +678,40 @@ fn collect_fold_elements<'query, Vertex: Clone + Debug + 'query>(
     }
 }
 
-#[allow(unused_variables)]
-fn compute_fold<'query, AdapterT: Adapter<'query> + 'query>(
+/// Expand the fold's edge and execute the steps of the fold's component starting from
+/// each of its neighbors, collecting the results into each context's folded contexts.
+fn begin_fold<'query, AdapterT: Adapter<'query> + 'query>(
     adapter: Arc<AdapterT>,
     carrier: &mut QueryCarrier,
-    expanding_from: &IRVertex,
-    parent_component: &IRQueryComponent,
     fold: Arc<IRFold>,
+    deferred_tags: &[FieldRef],
+    plan: &Arc<[Step]>,
+    fold_steps: Range<usize>,
     mut iterator: ContextIterator<'query, AdapterT::Vertex>,
 ) -> ContextIterator<'query, AdapterT::Vertex> {
-    // Get any imported tag values needed inside the fold component or one of its subcomponents.
-    for imported_field in fold.imported_tags.iter() {
+    let indexed_query = carrier
+        .query
+        .as_ref()
+        .expect("query was not returned")
+        .indexed_query
+        .clone();
+
+    // Get any imported tag values needed inside the fold component or one of its subcomponents,
+    // except for those deferred until they are used.
+    let eager_tags: Vec<FieldRef> = fold
+        .imported_tags
+        .iter()
+        .filter(|tag| !deferred_tags.contains(tag))
+        .cloned()
+        .collect();
+    for imported_field in eager_tags.iter() {
         match &imported_field {
             FieldRef::ContextField(field) => {
                 let vertex_id = field.vertex_id;
                 let activated_vertex_iterator: ContextIterator<'query, AdapterT::Vertex> =
                     Box::new(iterator.map(move |x| x.activate_vertex(&vertex_id)));
 
-                let field_vertex = &parent_component.vertices[&field.vertex_id];
-                let type_name = &field_vertex.type_name;
+                let type_name = &indexed_query.vertices[&field.vertex_id].type_name;
 
                 let query = carrier.query.take().expect("query was not returned");
                 let resolve_info = ResolveInfo::new(query, vertex_id, true);
@@ -390,7 +741,6 @@ fn compute_fold<'query, AdapterT: Adapter<'query> + 'query>(
             }
             FieldRef::FoldSpecificField(fold_specific_field) => {
                 let cloned_field = imported_field.clone();
-                let fold_eid = fold_specific_field.fold_eid;
                 iterator = Box::new(
                     compute_fold_specific_field_with_separate_value(
                         fold_specific_field.fold_eid,
@@ -407,10 +757,10 @@ fn compute_fold<'query, AdapterT: Adapter<'query> + 'query>(
     }
 
     // Get the initial vertices inside the folded scope.
-    let expanding_from_vid = expanding_from.vid;
+    let expanding_from_vid = fold.from_vid;
     let activated_vertex_iterator: ContextIterator<'query, AdapterT::Vertex> =
         Box::new(iterator.map(move |x| x.activate_vertex(&expanding_from_vid)));
-    let type_name = &expanding_from.type_name;
+    let type_name = &indexed_query.vertices[&expanding_from_vid].type_name;
 
     let query = carrier.query.take().expect("query was not returned");
     let resolve_info = ResolveEdgeInfo::new(query, expanding_from_vid, fold.to_vid, fold.eid);
@@ -428,23 +778,44 @@ fn compute_fold<'query, AdapterT: Adapter<'query> + 'query>(
     // These values are moved into the closure.
     let cloned_adapter = adapter.clone();
     let mut cloned_carrier = carrier.clone();
-    let fold_component = fold.component.clone();
+    let plan = plan.clone();
     let fold_eid = fold.eid;
     let max_fold_size = get_max_fold_count_limit(carrier, fold.as_ref());
-    let moved_fold = fold.clone();
+    let deferred_tags: Vec<(FieldRef, Vid)> = deferred_tags
+        .iter()
+        .map(|tag| match tag {
+            FieldRef::ContextField(field) => (tag.clone(), field.vertex_id),
+            FieldRef::FoldSpecificField(_) => unreachable!("deferred fold-specific tag: {tag:?}"),
+        })
+        .collect();
     let folded_iterator = edge_iterator.filter_map(move |(mut context, neighbors)| {
-        let imported_tags = context.imported_tags.clone();
+        let mut imported_tags = context.imported_tags.clone();
+        let mut inner_deferred_tags = context.deferred_tags.clone();
+        for (tag, vid) in &deferred_tags {
+            match &context.vertices[vid] {
+                Some(vertex) => {
+                    inner_deferred_tags.insert(tag.clone(), DeferredTag::new(vertex.clone()));
+                }
+                None => {
+                    // The tagged value is coming from an `@optional` scope that did not exist,
+                    // so there's nothing to resolve.
+                    imported_tags.insert(tag.clone(), TaggedValue::NonexistentOptional);
+                }
+            }
+        }
 
         let neighbor_contexts = Box::new(neighbors.map(move |x| {
             let mut ctx = DataContext::new(Some(x));
             ctx.imported_tags = imported_tags.clone();
+            ctx.deferred_tags = inner_deferred_tags.clone();
             ctx
         }));
 
-        let computed_iterator = compute_component(
+        let computed_iterator = execute_steps(
             cloned_adapter.clone(),
             &mut cloned_carrier,
-            &fold_component,
+            &plan,
+            fold_steps.clone(),
             neighbor_contexts,
         );
 
@@ -465,23 +836,42 @@ fn compute_fold<'query, AdapterT: Adapter<'query> + 'query>(
             .unwrap();
 
         // Remove no-longer-needed imported tags.
-        for imported_tag in &moved_fold.imported_tags {
+        for imported_tag in &eager_tags {
             context.imported_tags.remove(imported_tag).unwrap();
         }
 
         Some(context)
     });
 
+    Box::new(folded_iterator)
+}
+
+/// Apply the fold's post-filters and compute its outputs,
+/// from the folded contexts collected by [`begin_fold`].
+fn end_fold<'query, AdapterT: Adapter<'query> + 'query>(
+    adapter: Arc<AdapterT>,
+    carrier: &mut QueryCarrier,
+    fold: Arc<IRFold>,
+    iterator: ContextIterator<'query, AdapterT::Vertex>,
+) -> ContextIterator<'query, AdapterT::Vertex> {
+    let indexed_query = carrier
+        .query
+        .as_ref()
+        .expect("query was not returned")
+        .indexed_query
+        .clone();
+    let expanding_from_vid = fold.from_vid;
+    let fold_eid = fold.eid;
+
     // Apply post-fold filters.
-    let mut post_filtered_iterator: ContextIterator<'query, AdapterT::Vertex> =
-        Box::new(folded_iterator);
+    let mut post_filtered_iterator = iterator;
     for post_fold_filter in fold.post_filters.iter() {
         post_filtered_iterator = apply_fold_specific_filter(
             adapter.as_ref(),
             carrier,
-            parent_component,
+            &indexed_query.vids[&expanding_from_vid],
             fold.as_ref(),
-            expanding_from.vid,
+            expanding_from_vid,
             post_fold_filter,
             post_filtered_iterator,
         );
@@ -494,6 +884,7 @@ fn compute_fold<'query, AdapterT: Adapter<'query> + 'query>(
     let cloned_adapter = adapter.clone();
     let mut cloned_carrier = carrier.clone();
     let fold_component = fold.component.clone();
+    let element_pool = Pool::new();
     let final_iterator = post_filtered_iterator.map(move |mut ctx| {
         let fold_elements = &ctx.folded_contexts[&fold_eid];
         debug_assert_eq!(
@@ -555,13 +946,16 @@ mismatch on whether the fold below {expanding_from_vid:?} was inside an `@option
             }
         } else {
             // Iterate through the elements of the fold and get the values we need.
-            let mut output_iterator: ContextIterator<'query, AdapterT::Vertex> = Box::new(
+            let mut elements = element_pool.take();
+            elements.extend(
                 fold_elements
                     .as_ref()
                     .expect("fold did not contain elements")
-                    .clone()
-                    .into_iter(),
+                    .iter()
+                    .cloned(),
             );
+            let mut output_iterator: ContextIterator<'query, AdapterT::Vertex> =
+                Box::new(element_pool.drain(elements));
             for output_name in output_names.iter() {
                 // This is a slimmed-down version of computing a context field:
                 // - it does not restore the prior active vertex after getting each value
@@ -577,7 +971,7 @@ mismatch on whether the fold below {expanding_from_vid:?} was inside an `@option
                 let resolve_info = ResolveInfo::new(query, vertex_id, true);
                 let field_data_iterator = cloned_adapter.resolve_property(
                     moved_iterator,
-                    &fold.component.vertices[&vertex_id].type_name,
+                    &indexed_query.vertices[&vertex_id].type_name,
                     &context_field.field_name,
                     &resolve_info,
                 );
@@ -639,25 +1033,29 @@ fn apply_local_field_filter<'query, AdapterT: Adapter<'query>>(
     carrier: &mut QueryCarrier,
     component: &IRQueryComponent,
     current_vid: Vid,
-    filter: &Operation<LocalField, Argument>,
+    filter: &VertexFilter,
     iterator: ContextIterator<'query, AdapterT::Vertex>,
 ) -> ContextIterator<'query, AdapterT::Vertex> {
-    let local_field = filter.left();
-    let field_iterator = compute_local_field(
-        adapter,
-        carrier,
-        component,
-        current_vid,
-        local_field,
-        iterator,
-    );
+    let local_field = filter.operation.left();
+    let custom_scalar = carrier
+        .query
+        .as_ref()
+        .expect("query was not returned")
+        .indexed_query
+        .custom_scalars
+        .get(local_field.field_type.base_type())
+        .filter(|scalar| scalar.is_orderable())
+        .cloned();
+    let field_iterator = compute_local_field(adapter, carrier, current_vid, local_field, iterator);
 
     apply_filter(
         adapter,
         carrier,
         component,
         current_vid,
-        &filter.map(|_| (), |r| r),
+        &filter.operation.map(|_| (), |r| r),
+        filter.variable_slot,
+        custom_scalar,
         field_iterator,
     )
 }
@@ -672,6 +1070,15 @@ fn apply_fold_specific_filter<'query, AdapterT: Adapter<'query>>(
     iterator: ContextIterator<'query, AdapterT::Vertex>,
 ) -> ContextIterator<'query, AdapterT::Vertex> {
     let fold_specific_field = filter.left();
+    let variable_slot = match filter.right() {
+        Some(Argument::Variable(variable)) => carrier
+            .query
+            .as_ref()
+            .expect("query was not returned")
+            .indexed_query
+            .variable_slot(&variable.variable_name),
+        _ => None,
+    };
     let field_iterator = Box::new(compute_fold_specific_field_with_separate_value(fold.eid, fold_specific_field, iterator).map(|(mut ctx, tagged_value)| {
         let value = match tagged_value {
             TaggedValue::Some(value) => value,
@@ -689,6 +1096,8 @@ fn apply_fold_specific_filter<'query, AdapterT: Adapter<'query>>(
         component,
         current_vid,
         &filter.map(|_| (), |r| r),
+        variable_slot,
+        None,
         field_iterator,
     )
 }
@@ -739,12 +1148,72 @@ pub(super) fn compute_context_field_with_separate_value<'query, AdapterT: Adapte
         Box::new(context_and_value_iterator)
     } else {
         // This context field represents an imported tag value from an outer component.
-        // Grab its value from the context itself.
+        // Grab its value from the context itself, unless its resolution was deferred
+        // until now and no context for the same fold has resolved it yet.
         let field_ref = FieldRef::ContextField(context_field.clone());
-        Box::new(iterator.map(move |context| {
-            let value = context.imported_tags[&field_ref].clone();
-            (context, value)
-        }))
+        let query = carrier.query.as_ref().expect("query was not returned");
+        if !query.deferred_tags.contains(&field_ref) {
+            return Box::new(iterator.map(move |context| {
+                let value = context.imported_tags[&field_ref].clone();
+                (context, value)
+            }));
+        }
+
+        let lookup_ref = field_ref.clone();
+        let lookup = move |context: &DataContext<AdapterT::Vertex>| {
+            if let Some(value) = context.imported_tags.get(&lookup_ref) {
+                return Lookup::Known(value.clone());
+            }
+            let tag = context
+                .deferred_tags
+                .get(&lookup_ref)
+                .expect("tag was neither imported nor deferred");
+            match tag.value() {
+                Some(value) => Lookup::Known(TaggedValue::Some(value)),
+                None => Lookup::Unknown(tag.clone()),
+            }
+        };
+
+        let query = carrier.query.take().expect("query was not returned");
+        let type_name = query.indexed_query.vertices[&vertex_id].type_name.clone();
+        let resolve_info = ResolveInfo::new(query, vertex_id, true);
+        let resolve = |contexts: ContextIterator<'query, AdapterT::Vertex>| {
+            let moved_contexts = Box::new(contexts.map(move |mut context| {
+                let tagged_vertex = context.deferred_tags[&field_ref].vertex().clone();
+                let active_vertex = context.active_vertex.clone();
+                context.suspended_vertices.push(active_vertex);
+                context.move_to_vertex(Some(tagged_vertex))
+            }));
+            let resolved: ContextOutcomeIterator<'query, AdapterT::Vertex, TaggedValue> = Box::new(
+                adapter
+                    .resolve_property(
+                        moved_contexts,
+                        &type_name,
+                        &context_field.field_name,
+                        &resolve_info,
+                    )
+                    .map(|(mut context, value)| {
+                        let old_current_token = context.suspended_vertices.pop().unwrap();
+                        (
+                            context.move_to_vertex(old_current_token),
+                            TaggedValue::Some(value),
+                        )
+                    }),
+            );
+            resolved
+        };
+        let values = resolve_unknown_outcomes(
+            iterator,
+            lookup,
+            resolve,
+            |tag: DeferredTag<AdapterT::Vertex>, value| match value {
+                TaggedValue::Some(value) => tag.resolve(value.clone()),
+                TaggedValue::NonexistentOptional => unreachable!("deferred tags always exist"),
+            },
+        );
+        carrier.query = Some(resolve_info.into_inner());
+
+        values
     }
 }
 
@@ -772,13 +1241,13 @@ pub(super) fn compute_fold_specific_field_with_separate_value<
 pub(super) fn compute_local_field_with_separate_value<'query, AdapterT: Adapter<'query>>(
     adapter: &AdapterT,
     carrier: &mut QueryCarrier,
-    component: &IRQueryComponent,
     current_vid: Vid,
     local_field: &LocalField,
     iterator: ContextIterator<'query, AdapterT::Vertex>,
 ) -> ContextOutcomeIterator<'query, AdapterT::Vertex, FieldValue> {
-    let type_name = &component.vertices[&current_vid].type_name;
     let query = carrier.query.take().expect("query was not returned");
+    let indexed_query = query.indexed_query.clone();
+    let type_name = &indexed_query.vertices[&current_vid].type_name;
     let resolve_info = ResolveInfo::new(query, current_vid, true);
 
     let context_and_value_iterator =
@@ -791,7 +1260,6 @@ pub(super) fn compute_local_field_with_separate_value<'query, AdapterT: Adapter<
 fn compute_local_field<'query, AdapterT: Adapter<'query>>(
     adapter: &AdapterT,
     carrier: &mut QueryCarrier,
-    component: &IRQueryComponent,
     current_vid: Vid,
     local_field: &LocalField,
     iterator: ContextIterator<'query, AdapterT::Vertex>,
@@ -799,7 +1267,6 @@ fn compute_local_field<'query, AdapterT: Adapter<'query>>(
     let context_and_value_iterator = compute_local_field_with_separate_value(
         adapter,
         carrier,
-        component,
         current_vid,
         local_field,
         iterator,
@@ -880,21 +1347,21 @@ impl<'query, Vertex: Clone + Debug + 'query> Iterator for EdgeExpander<'query, V
 }
 
 fn expand_edge<'query, AdapterT: Adapter<'query> + 'query>(
-    adapter: &AdapterT,
+    adapter: &Arc<AdapterT>,
     carrier: &mut QueryCarrier,
     component: &IRQueryComponent,
-    expanding_from_vid: Vid,
-    expanding_to_vid: Vid,
     edge: &IREdge,
     iterator: ContextIterator<'query, AdapterT::Vertex>,
 ) -> ContextIterator<'query, AdapterT::Vertex> {
-    let expanded_iterator = if let Some(recursive) = &edge.recursive {
+    let expanding_from = &component.vertices[&edge.from_vid];
+    let expanding_to = &component.vertices[&edge.to_vid];
+    if let Some(recursive) = &edge.recursive {
         expand_recursive_edge(
-            adapter,
+            adapter.clone(),
             carrier,
             component,
-            &component.vertices[&expanding_from_vid],
-            &component.vertices[&expanding_to_vid],
+            expanding_from,
+            expanding_to,
             edge.eid,
             &edge.edge_name,
             &edge.parameters,
@@ -903,27 +1370,19 @@ fn expand_edge<'query, AdapterT: Adapter<'query> + 'query>(
         )
     } else {
         expand_non_recursive_edge(
-            adapter,
+            adapter.as_ref(),
             carrier,
             component,
-            &component.vertices[&expanding_from_vid],
-            &component.vertices[&expanding_to_vid],
+            expanding_from,
+            expanding_to,
             edge.eid,
             &edge.edge_name,
             &edge.parameters,
             edge.optional,
             iterator,
         )
-    };
-
-    perform_entry_into_new_vertex(
-        adapter,
-        carrier,
-        component,
-        &component.vertices[&expanding_to_vid],
-        expanded_iterator,
-    )
-}
+    }
+}
 
 #[allow(clippy::too_many_arguments)]
 fn expand_non_recursive_edge<'query, AdapterT: Adapter<'query>>(
@@ -960,38 +1419,9 @@ fn expand_non_recursive_edge<'query, AdapterT: Adapter<'query>>(
     }))
 }
 
-/// Apply all the operations needed at entry into a new vertex:
-/// - coerce the type, if needed
-/// - apply all local filters
-/// - record the vertex at this Vid in the context
-fn perform_entry_into_new_vertex<'query, AdapterT: Adapter<'query>>(
-    adapter: &AdapterT,
-    carrier: &mut QueryCarrier,
-    component: &IRQueryComponent,
-    vertex: &IRVertex,
-    iterator: ContextIterator<'query, AdapterT::Vertex>,
-) -> ContextIterator<'query, AdapterT::Vertex> {
-    let vertex_id = vertex.vid;
-    let mut iterator = coerce_if_needed(adapter, carrier, vertex, iterator);
-    for filter_expr in vertex.filters.iter() {
-        iterator = apply_local_field_filter(
-            adapter,
-            carrier,
-            component,
-            vertex_id,
-            filter_expr,
-            iterator,
-        );
-    }
-    Box::new(iterator.map(move |mut x| {
-        x.record_vertex(vertex_id);
-        x
-    }))
-}
-
 #[allow(clippy::too_many_arguments)]
 fn expand_recursive_edge<'query, AdapterT: Adapter<'query> + 'query>(
-    adapter: &AdapterT,
+    adapter: Arc<AdapterT>,
     carrier: &mut QueryCarrier,
     component: &IRQueryComponent,
     expanding_from: &IRVertex,
@@ -1003,6 +1433,9 @@ fn expand_recursive_edge<'query, AdapterT: Adapter<'query> + 'query>(
     iterator: ContextIterator<'query, AdapterT::Vertex>,
 ) -> ContextIterator<'query, AdapterT::Vertex> {
     let expanding_from_vid = expanding_from.vid;
+    let identifying_adapter = adapter
+        .skip_revisited_vertices_in_recursion()
+        .then(|| adapter.clone());
     let mut recursion_iterator: ContextIterator<'query, AdapterT::Vertex> =
         Box::new(iterator.map(move |mut context| {
             if context.active_vertex.is_none() {
@@ -1010,12 +1443,18 @@ fn expand_recursive_edge<'query, AdapterT: Adapter<'query> + 'query>(
                 // so the later unsuspend() call should restore it to such a state later.
                 context.suspended_vertices.push(None);
             }
-            context.activate_vertex(&expanding_from_vid)
+            let mut context = context.activate_vertex(&expanding_from_vid);
+            context.recursion_visits = identifying_adapter
+                .as_ref()
+                .zip(context.active_vertex.as_ref())
+                .and_then(|(adapter, vertex)| adapter.vertex_identity(vertex))
+                .map(RecursionVisits::starting_from);
+            context
         }));
 
     let max_depth = usize::from(recursive.depth);
     recursion_iterator = perform_one_recursive_edge_expansion(
-        adapter,
+        &adapter,
         carrier,
         component,
         &expanding_from.type_name,
@@ -1024,6 +1463,7 @@ fn expand_recursive_edge<'query, AdapterT: Adapter<'query> + 'query>(
         edge_id,
         edge_name,
         edge_parameters,
+        1,
         recursion_iterator,
     );
 
@@ -1033,12 +1473,14 @@ fn expand_recursive_edge<'query, AdapterT: Adapter<'query> + 'query>(
         .unwrap_or(&expanding_to.type_name);
     let recursing_from = recursive.coerce_to.as_ref().unwrap_or(edge_endpoint_type);
 
-    for _ in 2..=max_depth {
+    for depth in 2..=max_depth {
         if let Some(coerce_to) = recursive.coerce_to.as_ref() {
             let query = carrier.query.take().expect("query was not returned");
             let resolve_info = ResolveInfo::new(query, expanding_from_vid, false);
 
-            let coercion_iter = adapter.resolve_coercion(
+            let coercion_iter = resolve_coercion_using_cache(
+                &adapter,
+                &carrier.coercions,
                 recursion_iterator,
                 edge_endpoint_type,
                 coerce_to,
@@ -1059,7 +1501,7 @@ fn expand_recursive_edge<'query, AdapterT: Adapter<'query> + 'query>(
         }
 
         recursion_iterator = perform_one_recursive_edge_expansion(
-            adapter,
+            &adapter,
             carrier,
             component,
             recursing_from,
@@ -1068,6 +1510,7 @@ fn expand_recursive_edge<'query, AdapterT: Adapter<'query> + 'query>(
             edge_id,
             edge_name,
             edge_parameters,
+            depth,
             recursion_iterator,
         );
     }
@@ -1076,8 +1519,8 @@ fn expand_recursive_edge<'query, AdapterT: Adapter<'query> + 'query>(
 }
 
 #[allow(clippy::too_many_arguments)]
-fn perform_one_recursive_edge_expansion<'query, AdapterT: Adapter<'query>>(
-    adapter: &AdapterT,
+fn perform_one_recursive_edge_expansion<'query, AdapterT: Adapter<'query> + 'query>(
+    adapter: &Arc<AdapterT>,
     carrier: &mut QueryCarrier,
     _component: &IRQueryComponent,
     expanding_from_type: &Arc<str>,
@@ -1086,6 +1529,7 @@ fn perform_one_recursive_edge_expansion<'query, AdapterT: Adapter<'query>>(
     edge_id: Eid,
     edge_name: &Arc<str>,
     edge_parameters: &EdgeParameters,
+    depth: usize,
     iterator: ContextIterator<'query, AdapterT::Vertex>,
 ) -> ContextIterator<'query, AdapterT::Vertex> {
     let query = carrier.query.take().expect("query was not returned");
@@ -1100,14 +1544,58 @@ fn perform_one_recursive_edge_expansion<'query, AdapterT: Adapter<'query>>(
     );
     carrier.query = Some(resolve_info.into_inner());
 
+    let adapter = adapter.clone();
     let result_iterator: ContextIterator<'query, AdapterT::Vertex> =
         Box::new(edge_iterator.flat_map(move |(context, neighbor_iterator)| {
+            // Skip the neighbors that this recursion already reached with at most as many edges.
+            let neighbor_iterator: VertexIterator<'query, AdapterT::Vertex> =
+                match context.recursion_visits.clone() {
+                    Some(visits) => {
+                        let adapter = adapter.clone();
+                        Box::new(neighbor_iterator.filter(move |vertex| {
+                            visits.visit(adapter.vertex_identity(vertex), depth)
+                        }))
+                    }
+                    None => neighbor_iterator,
+                };
             RecursiveEdgeExpander::new(context, neighbor_iterator)
         }));
 
     result_iterator
 }
 
+/// The vertices that a `@recurse` reached from the vertex it started from, with the fewest
+/// edges it took to reach each of them. Only kept for adapters that skip the vertices
+/// a recursion has already reached, see [`Adapter::skip_revisited_vertices_in_recursion`].
+#[derive(Debug, Clone)]
+pub(super) struct RecursionVisits(Arc<Mutex<HashMap<u64, usize>>>);
+
+impl RecursionVisits {
+    fn starting_from(identity: u64) -> Self {
+        Self(Arc::new(Mutex::new(HashMap::from([(identity, 0)]))))
+    }
+
+    /// Record reaching the vertex after `depth` edges, and return whether to expand it:
+    /// vertices reached before are only expanded again if it took more edges to reach them.
+    fn visit(&self, identity: Option<u64>, depth: usize) -> bool {
+        let Some(identity) = identity else {
+            return true;
+        };
+        let mut visits = self.0.lock().expect("recursion visits lock was poisoned");
+        match visits.entry(identity) {
+            Entry::Occupied(mut prior) if depth < *prior.get() => {
+                prior.insert(depth);
+                true
+            }
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(depth);
+                true
+            }
+        }
+    }
+}
+
 struct RecursiveEdgeExpander<'query, Vertex: Clone + Debug + 'query> {
     context: Option<DataContext<Vertex>>,
     neighbor_base: Option<DataContext<Vertex>>,
@@ -1180,22 +1668,12 @@ impl<'query, Vertex: Clone + Debug + 'query> Iterator for RecursiveEdgeExpander<
 }
 
 fn unpack_piggyback<Vertex: Debug + Clone>(
-    context: DataContext<Vertex>,
-) -> Vec<DataContext<Vertex>> {
-    let mut result = Default::default();
-
-    unpack_piggyback_inner(&mut result, context);
-
-    result
-}
-
-fn unpack_piggyback_inner<Vertex: Debug + Clone>(
     output: &mut Vec<DataContext<Vertex>>,
     mut context: DataContext<Vertex>,
 ) {
     if let Some(mut piggyback) = context.piggyback.take() {
         for ctx in piggyback.drain(..) {
-            unpack_piggyback_inner(output, ctx);
+            unpack_piggyback(output, ctx);
         }
     }
 
@@ -1205,11 +1683,17 @@ fn unpack_piggyback_inner<Vertex: Debug + Clone>(
 fn post_process_recursive_expansion<'query, Vertex: Clone + Debug + 'query>(
     iterator: ContextIterator<'query, Vertex>,
 ) -> ContextIterator<'query, Vertex> {
+    let pool = Pool::new();
     Box::new(
         iterator
-            .flat_map(|context| unpack_piggyback(context))
-            .map(|context| {
+            .flat_map(move |context| {
+                let mut contexts = pool.take();
+                unpack_piggyback(&mut contexts, context);
+                pool.drain(contexts)
+            })
+            .map(|mut context| {
                 assert!(context.piggyback.is_none());
+                context.recursion_visits = None;
                 context.ensure_unsuspended()
             }),
     )
@@ -1227,11 +1711,16 @@ mod tests {
     use trustfall_filetests_macros::parameterize;
 
     use crate::{
-        interpreter::{error::QueryArgumentsError, InterpretedQuery},
-        ir::{FieldValue, IndexedQuery},
+        frontend::parse,
+        interpreter::{error::QueryArgumentsError, statistics::StatisticsCache, InterpretedQuery},
+        ir::{Eid, FieldValue, IndexedQuery},
+        numbers_interpreter::NumbersAdapter,
+        schema::Schema,
         test_types::{TestIRQueryResult, TestInterpreterOutputData},
     };
 
+    use super::interpret_ir_with_statistics;
+
     #[parameterize("trustfall_core/test_data/tests/valid_queries")]
     fn parameterized_output_metadata_tester(base: &Path, stem: &str) {
         let mut input_path = PathBuf::from(base);
@@ -1446,6 +1935,14 @@ mod tests {
                 );
                 Box::new(VariableChunkIterator::new(inner, sequence))
             }
+
+            fn vertex_identity(&self, vertex: &Self::Vertex) -> Option<u64> {
+                self.adapter.vertex_identity(vertex)
+            }
+
+            fn skip_revisited_vertices_in_recursion(&self) -> bool {
+                self.adapter.skip_revisited_vertices_in_recursion()
+            }
         }
 
         fn run_test(file_stub: &str, batch_sequences: Vec<u64>) {
@@ -1499,4 +1996,593 @@ mod tests {
             run_test(input_file, batch_sequences);
         }
     }
+
+    mod vertex_identities {
+        use std::{
+            collections::{BTreeMap, BTreeSet},
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Arc,
+            },
+        };
+
+        use crate::{
+            frontend::parse,
+            interpreter::{
+                execution::interpret_ir, Adapter, ContextIterator, ContextOutcomeIterator,
+                ResolveEdgeInfo, ResolveInfo, VertexIterator,
+            },
+            ir::{EdgeParameters, FieldValue},
+            numbers_interpreter::{Number, NumbersAdapter, NumbersVertex},
+            schema::Schema,
+        };
+
+        /// Identifies each number by its value, and counts the coercions it resolves.
+        #[derive(Default)]
+        struct IdentifyingAdapter {
+            inner: NumbersAdapter,
+            coercions: Arc<AtomicUsize>,
+            skip_revisited_vertices: bool,
+        }
+
+        impl<'a> Adapter<'a> for IdentifyingAdapter {
+            type Vertex = NumbersVertex;
+
+            fn resolve_starting_vertices(
+                &self,
+                edge_name: &Arc<str>,
+                parameters: &EdgeParameters,
+                resolve_info: &ResolveInfo,
+            ) -> VertexIterator<'a, Self::Vertex> {
+                self.inner
+                    .resolve_starting_vertices(edge_name, parameters, resolve_info)
+            }
+
+            fn resolve_property(
+                &self,
+                contexts: ContextIterator<'a, Self::Vertex>,
+                type_name: &Arc<str>,
+                property_name: &Arc<str>,
+                resolve_info: &ResolveInfo,
+            ) -> ContextOutcomeIterator<'a, Self::Vertex, FieldValue> {
+                self.inner
+                    .resolve_property(contexts, type_name, property_name, resolve_info)
+            }
+
+            fn resolve_neighbors(
+                &self,
+                contexts: ContextIterator<'a, Self::Vertex>,
+                type_name: &Arc<str>,
+                edge_name: &Arc<str>,
+                parameters: &EdgeParameters,
+                resolve_info: &ResolveEdgeInfo,
+            ) -> ContextOutcomeIterator<'a, Self::Vertex, VertexIterator<'a, Self::Vertex>>
+            {
+                self.inner.resolve_neighbors(
+                    contexts,
+                    type_name,
+                    edge_name,
+                    parameters,
+                    resolve_info,
+                )
+            }
+
+            fn resolve_coercion(
+                &self,
+                contexts: ContextIterator<'a, Self::Vertex>,
+                type_name: &Arc<str>,
+                coerce_to_type: &Arc<str>,
+                resolve_info: &ResolveInfo,
+            ) -> ContextOutcomeIterator<'a, Self::Vertex, bool> {
+                let coercions = self.coercions.clone();
+                let contexts = Box::new(contexts.inspect(move |_| {
+                    coercions.fetch_add(1, Ordering::Relaxed);
+                }));
+                self.inner
+                    .resolve_coercion(contexts, type_name, coerce_to_type, resolve_info)
+            }
+
+            fn vertex_identity(&self, vertex: &Self::Vertex) -> Option<u64> {
+                Some(vertex.value() as u64)
+            }
+
+            fn skip_revisited_vertices_in_recursion(&self) -> bool {
+                self.skip_revisited_vertices
+            }
+        }
+
+        fn run<'a>(
+            adapter: impl Adapter<'a> + 'a,
+            query: &str,
+        ) -> Vec<BTreeMap<Arc<str>, FieldValue>> {
+            let schema = Schema::parse(include_str!("../../test_data/schemas/numbers.graphql"))
+                .expect("schema is not valid");
+            let indexed_query = parse(&schema, query).unwrap();
+            interpret_ir(Arc::new(adapter), indexed_query, Default::default())
+                .unwrap()
+                .collect()
+        }
+
+        #[test]
+        fn identified_vertices_are_reached_once_per_recursion_if_the_adapter_skips_them() {
+            // Every multiple of a composite number includes the number itself, and
+            // numbers with several factors are multiples along several paths.
+            let query = r#"
+{
+    Number(min: 4, max: 4) {
+        ... on Composite {
+            multiple(max: 3) @recurse(depth: 3) {
+                value @output
+            }
+        }
+    }
+}"#;
+            let values = |rows: Vec<BTreeMap<Arc<str>, FieldValue>>| {
+                rows.into_iter()
+                    .map(|row| row["value"].as_i64().unwrap())
+                    .collect::<Vec<_>>()
+            };
+            let every_path = values(run(NumbersAdapter::new(), query));
+            let skipping = IdentifyingAdapter {
+                skip_revisited_vertices: true,
+                ..Default::default()
+            };
+            let identified = values(run(skipping, query));
+
+            let distinct = |values: &[i64]| values.iter().copied().collect::<BTreeSet<_>>();
+            assert_eq!(distinct(&every_path), distinct(&identified));
+            assert_eq!(distinct(&identified).len(), identified.len());
+            assert!(identified.len() < every_path.len());
+
+            // Identifying vertices alone doesn't change which paths are followed.
+            assert_eq!(
+                every_path,
+                values(run(IdentifyingAdapter::default(), query))
+            );
+        }
+
+        #[test]
+        fn coercions_of_identified_vertices_are_cached() {
+            // The numbers reached by both edges are coerced to `Prime` once each.
+            let query = r#"
+{
+    Number(min: 1, max: 3) {
+        predecessor {
+            successor {
+                ... on Prime {
+                    value @output
+                }
+            }
+        }
+        successor {
+            predecessor {
+                ... on Prime {
+                    same: value @output
+                }
+            }
+        }
+    }
+}"#;
+            let adapter = IdentifyingAdapter::default();
+            let coercions = adapter.coercions.clone();
+            assert_eq!(run(NumbersAdapter::new(), query), run(adapter, query));
+            assert_eq!(3, coercions.load(Ordering::Relaxed));
+        }
+    }
+
+    mod deferred_tags {
+        use std::{
+            collections::BTreeMap,
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Arc,
+            },
+        };
+
+        use crate::{
+            frontend::parse,
+            interpreter::{
+                execution::interpret_ir, Adapter, ContextIterator, ContextOutcomeIterator,
+                ResolveEdgeInfo, ResolveInfo, VertexIterator,
+            },
+            ir::{EdgeParameters, FieldValue},
+            numbers_interpreter::{NumbersAdapter, NumbersVertex},
+            schema::Schema,
+        };
+
+        /// Counts the vertices whose names it resolves.
+        #[derive(Default)]
+        struct NameCountingAdapter {
+            inner: NumbersAdapter,
+            names: Arc<AtomicUsize>,
+        }
+
+        impl<'a> Adapter<'a> for NameCountingAdapter {
+            type Vertex = NumbersVertex;
+
+            fn resolve_starting_vertices(
+                &self,
+                edge_name: &Arc<str>,
+                parameters: &EdgeParameters,
+                resolve_info: &ResolveInfo,
+            ) -> VertexIterator<'a, Self::Vertex> {
+                self.inner
+                    .resolve_starting_vertices(edge_name, parameters, resolve_info)
+            }
+
+            fn resolve_property(
+                &self,
+                contexts: ContextIterator<'a, Self::Vertex>,
+                type_name: &Arc<str>,
+                property_name: &Arc<str>,
+                resolve_info: &ResolveInfo,
+            ) -> ContextOutcomeIterator<'a, Self::Vertex, FieldValue> {
+                let contexts: ContextIterator<'a, Self::Vertex> =
+                    if property_name.as_ref() == "name" {
+                        let names = self.names.clone();
+                        Box::new(contexts.inspect(move |context| {
+                            if context.active_vertex().is_some() {
+                                names.fetch_add(1, Ordering::Relaxed);
+                            }
+                        }))
+                    } else {
+                        contexts
+                    };
+                self.inner
+                    .resolve_property(contexts, type_name, property_name, resolve_info)
+            }
+
+            fn resolve_neighbors(
+                &self,
+                contexts: ContextIterator<'a, Self::Vertex>,
+                type_name: &Arc<str>,
+                edge_name: &Arc<str>,
+                parameters: &EdgeParameters,
+                resolve_info: &ResolveEdgeInfo,
+            ) -> ContextOutcomeIterator<'a, Self::Vertex, VertexIterator<'a, Self::Vertex>>
+            {
+                self.inner.resolve_neighbors(
+                    contexts,
+                    type_name,
+                    edge_name,
+                    parameters,
+                    resolve_info,
+                )
+            }
+
+            fn resolve_coercion(
+                &self,
+                contexts: ContextIterator<'a, Self::Vertex>,
+                type_name: &Arc<str>,
+                coerce_to_type: &Arc<str>,
+                resolve_info: &ResolveInfo,
+            ) -> ContextOutcomeIterator<'a, Self::Vertex, bool> {
+                self.inner
+                    .resolve_coercion(contexts, type_name, coerce_to_type, resolve_info)
+            }
+        }
+
+        #[test]
+        fn tags_are_resolved_only_once_used() {
+            // The tag is first used after the filter on `value`, so it's only resolved
+            // for the numbers whose successor passes that filter.
+            let query = r#"
+{
+    Number(min: 1, max: 3) {
+        name @tag
+        value @output
+
+        successor @fold {
+            value @filter(op: ">", value: ["$min"])
+            name @filter(op: "!=", value: ["%name"])
+            successor: value @output
+        }
+    }
+}"#;
+            let schema = Schema::parse(include_str!("../../test_data/schemas/numbers.graphql"))
+                .expect("schema is not valid");
+            let indexed_query = parse(&schema, query).unwrap();
+
+            let run = |min: i64| {
+                let adapter = NameCountingAdapter::default();
+                let names = adapter.names.clone();
+                let arguments = Arc::new(BTreeMap::from([("min".into(), min.into())]));
+                let rows: Vec<_> =
+                    interpret_ir(Arc::new(adapter), indexed_query.clone(), arguments)
+                        .unwrap()
+                        .map(|row| (row["value"].clone(), row["successor"].clone()))
+                        .collect();
+                (rows, names.load(Ordering::Relaxed))
+            };
+
+            let successors = |values: &[i64]| {
+                FieldValue::List(values.iter().map(|&value| value.into()).collect())
+            };
+            let (rows, names) = run(2);
+            assert_eq!(
+                vec![
+                    (1.into(), successors(&[])),
+                    (2.into(), successors(&[3])),
+                    (3.into(), successors(&[4])),
+                ],
+                rows,
+            );
+            // The names of the two numbers and of their successors.
+            assert_eq!(4, names);
+
+            let (rows, names) = run(10);
+            assert!(rows
+                .iter()
+                .all(|(_, successors)| successors == &FieldValue::List(vec![].into())));
+            assert_eq!(0, names);
+        }
+
+        /// Serializes and deserializes the contexts it's given, like an adapter
+        /// in another process would, and counts those with tags still deferred.
+        #[derive(Default)]
+        struct SerializingAdapter {
+            inner: NumbersAdapter,
+            deferred: Arc<AtomicUsize>,
+        }
+
+        impl SerializingAdapter {
+            fn round_trip<'a>(
+                &self,
+                contexts: ContextIterator<'a, NumbersVertex>,
+            ) -> ContextIterator<'a, NumbersVertex> {
+                let deferred = self.deferred.clone();
+                Box::new(contexts.map(move |context| {
+                    if context
+                        .deferred_tags
+                        .iter()
+                        .any(|(_, tag)| tag.value().is_none())
+                    {
+                        deferred.fetch_add(1, Ordering::Relaxed);
+                    }
+                    ron::from_str(&ron::to_string(&context).unwrap()).unwrap()
+                }))
+            }
+        }
+
+        impl<'a> Adapter<'a> for SerializingAdapter {
+            type Vertex = NumbersVertex;
+
+            fn resolve_starting_vertices(
+                &self,
+                edge_name: &Arc<str>,
+                parameters: &EdgeParameters,
+                resolve_info: &ResolveInfo,
+            ) -> VertexIterator<'a, Self::Vertex> {
+                self.inner
+                    .resolve_starting_vertices(edge_name, parameters, resolve_info)
+            }
+
+            fn resolve_property(
+                &self,
+                contexts: ContextIterator<'a, Self::Vertex>,
+                type_name: &Arc<str>,
+                property_name: &Arc<str>,
+                resolve_info: &ResolveInfo,
+            ) -> ContextOutcomeIterator<'a, Self::Vertex, FieldValue> {
+                self.inner.resolve_property(
+                    self.round_trip(contexts),
+                    type_name,
+                    property_name,
+                    resolve_info,
+                )
+            }
+
+            fn resolve_neighbors(
+                &self,
+                contexts: ContextIterator<'a, Self::Vertex>,
+                type_name: &Arc<str>,
+                edge_name: &Arc<str>,
+                parameters: &EdgeParameters,
+                resolve_info: &ResolveEdgeInfo,
+            ) -> ContextOutcomeIterator<'a, Self::Vertex, VertexIterator<'a, Self::Vertex>>
+            {
+                self.inner.resolve_neighbors(
+                    self.round_trip(contexts),
+                    type_name,
+                    edge_name,
+                    parameters,
+                    resolve_info,
+                )
+            }
+
+            fn resolve_coercion(
+                &self,
+                contexts: ContextIterator<'a, Self::Vertex>,
+                type_name: &Arc<str>,
+                coerce_to_type: &Arc<str>,
+                resolve_info: &ResolveInfo,
+            ) -> ContextOutcomeIterator<'a, Self::Vertex, bool> {
+                self.inner.resolve_coercion(
+                    self.round_trip(contexts),
+                    type_name,
+                    coerce_to_type,
+                    resolve_info,
+                )
+            }
+        }
+
+        #[test]
+        fn deferred_tags_survive_serialization() {
+            let query = r#"
+{
+    Number(min: 1, max: 3) {
+        name @tag
+        value @output
+
+        successor @fold {
+            value @filter(op: ">", value: ["$min"])
+            name @filter(op: "!=", value: ["%name"])
+            successor: value @output
+        }
+    }
+}"#;
+            let schema = Schema::parse(include_str!("../../test_data/schemas/numbers.graphql"))
+                .expect("schema is not valid");
+            let indexed_query = parse(&schema, query).unwrap();
+            let arguments = Arc::new(BTreeMap::from([("min".into(), 2.into())]));
+
+            let expected: Vec<_> = interpret_ir(
+                Arc::new(NumbersAdapter::new()),
+                indexed_query.clone(),
+                arguments.clone(),
+            )
+            .unwrap()
+            .collect();
+
+            let adapter = SerializingAdapter::default();
+            let deferred = adapter.deferred.clone();
+            let rows: Vec<_> = interpret_ir(Arc::new(adapter), indexed_query, arguments)
+                .unwrap()
+                .collect();
+            assert_eq!(expected, rows);
+            assert!(deferred.load(Ordering::Relaxed) > 0);
+        }
+    }
+
+    #[test]
+    fn executions_record_observed_statistics() {
+        let schema = Schema::parse(include_str!("../../test_data/schemas/numbers.graphql"))
+            .expect("schema is not valid");
+        let query = r#"
+{
+    Number(min: 1, max: 10) {
+        value @output
+
+        successor {
+            ... on Prime {
+                next: value @output
+            }
+        }
+    }
+}"#;
+        let indexed_query = parse(&schema, query).unwrap();
+        let cache = StatisticsCache::new();
+        for _ in 0..2 {
+            let results: Vec<_> = interpret_ir_with_statistics(
+                Arc::new(NumbersAdapter::new()),
+                indexed_query.clone(),
+                Default::default(),
+                &cache,
+            )
+            .unwrap()
+            .collect();
+            assert_eq!(5, results.len());
+        }
+
+        let statistics = cache.get(indexed_query.content_hash()).unwrap();
+        assert_eq!(2, statistics.executions);
+        let successor = &statistics.expansions[&Eid::new(1.try_into().unwrap())];
+        assert_eq!(
+            (20, 20, 10),
+            (successor.sources, successor.neighbors, successor.retained)
+        );
+        assert_eq!(Some(0.5), successor.selectivity());
+    }
+
+    mod enum_outputs {
+        use std::sync::Arc;
+
+        use crate::{
+            frontend::parse,
+            interpreter::{
+                execution::interpret_ir, helpers::resolve_property_with, Adapter, ContextIterator,
+                ContextOutcomeIterator, ResolveEdgeInfo, ResolveInfo, VertexIterator,
+            },
+            ir::{EdgeParameters, FieldValue},
+            schema::Schema,
+        };
+
+        /// Shapes of the given colors, which need not be values of the schema's `Color` enum.
+        struct ShapesAdapter(&'static [&'static str]);
+
+        impl<'a> Adapter<'a> for ShapesAdapter {
+            type Vertex = &'static str;
+
+            fn resolve_starting_vertices(
+                &self,
+                _edge_name: &Arc<str>,
+                _parameters: &EdgeParameters,
+                _resolve_info: &ResolveInfo,
+            ) -> VertexIterator<'a, Self::Vertex> {
+                Box::new(self.0.iter().copied())
+            }
+
+            fn resolve_property(
+                &self,
+                contexts: ContextIterator<'a, Self::Vertex>,
+                _type_name: &Arc<str>,
+                _property_name: &Arc<str>,
+                _resolve_info: &ResolveInfo,
+            ) -> ContextOutcomeIterator<'a, Self::Vertex, FieldValue> {
+                resolve_property_with(contexts, |color| FieldValue::Enum(color.to_string()))
+            }
+
+            fn resolve_neighbors(
+                &self,
+                _contexts: ContextIterator<'a, Self::Vertex>,
+                _type_name: &Arc<str>,
+                _edge_name: &Arc<str>,
+                _parameters: &EdgeParameters,
+                _resolve_info: &ResolveEdgeInfo,
+            ) -> ContextOutcomeIterator<'a, Self::Vertex, VertexIterator<'a, Self::Vertex>>
+            {
+                unreachable!("the query has no edges")
+            }
+
+            fn resolve_coercion(
+                &self,
+                _contexts: ContextIterator<'a, Self::Vertex>,
+                _type_name: &Arc<str>,
+                _coerce_to_type: &Arc<str>,
+                _resolve_info: &ResolveInfo,
+            ) -> ContextOutcomeIterator<'a, Self::Vertex, bool> {
+                unreachable!("the query has no coercions")
+            }
+        }
+
+        fn output_colors(colors: &'static [&'static str]) -> Vec<FieldValue> {
+            let schema = Schema::parse(include_str!(
+                "../../test_data/tests/valid_schemas/enum_properties.graphql"
+            ))
+            .expect("schema is not valid");
+            let query = r#"
+{
+    Shape {
+        color @output
+    }
+}"#;
+            let indexed_query = parse(&schema, query).unwrap();
+            interpret_ir(
+                Arc::new(ShapesAdapter(colors)),
+                indexed_query,
+                Default::default(),
+            )
+            .unwrap()
+            .map(|mut result| result.remove("color").unwrap())
+            .collect()
+        }
+
+        #[test]
+        fn declared_enum_values_are_output() {
+            assert_eq!(
+                vec![
+                    FieldValue::Enum("RED".to_string()),
+                    FieldValue::Enum("BLUE".to_string())
+                ],
+                output_colors(&["RED", "BLUE"]),
+            );
+        }
+
+        #[cfg(debug_assertions)]
+        #[test]
+        #[should_panic(expected = "but that enum only allows the values")]
+        fn undeclared_enum_values_from_the_adapter_are_caught_in_debug_builds() {
+            output_colors(&["RED", "PURPLE"]);
+        }
+    }
 }
@@ -1,8 +1,11 @@
-use std::{fmt::Debug, mem};
+use std::{cmp::Ordering, fmt::Debug, mem};
 
 use regex::Regex;
 
-use crate::ir::{Argument, FieldRef, FieldValue, IRQueryComponent, LocalField, Operation, Vid};
+use crate::{
+    ir::{Argument, FieldRef, FieldValue, IRQueryComponent, LocalField, Operation, Vid},
+    schema::CustomScalar,
+};
 
 use super::{
     execution::{
@@ -255,12 +258,30 @@ fn attempt_apply_unary_filter<'query, Vertex: Debug + Clone + 'query>(
     }
 }
 
+/// For ordering filters, returns the check that the operands' [`Ordering`] must satisfy.
+fn expected_ordering(filter: &Operation<(), &Argument>) -> Option<fn(Ordering) -> bool> {
+    match filter {
+        Operation::LessThan(..) => Some(Ordering::is_lt),
+        Operation::LessThanOrEqual(..) => Some(Ordering::is_le),
+        Operation::GreaterThan(..) => Some(Ordering::is_gt),
+        Operation::GreaterThanOrEqual(..) => Some(Ordering::is_ge),
+        _ => None,
+    }
+}
+
+/// Apply the filter to the values at the top of each context's value stack.
+///
+/// If the filtered values are of a custom scalar type, its hooks must be passed in
+/// `custom_scalar` so that ordering filters can use the scalar's comparator.
+#[allow(clippy::too_many_arguments)]
 pub(super) fn apply_filter<'query, AdapterT: Adapter<'query>>(
     adapter: &AdapterT,
     carrier: &mut QueryCarrier,
     component: &IRQueryComponent,
     current_vid: Vid,
     filter: &Operation<(), &Argument>,
+    variable_slot: Option<usize>,
+    custom_scalar: Option<CustomScalar>,
     iterator: ContextIterator<'query, AdapterT::Vertex>,
 ) -> ContextIterator<'query, AdapterT::Vertex> {
     // If the filter operator is unary, we don't need to evaluate any arguments.
@@ -279,13 +300,19 @@ pub(super) fn apply_filter<'query, AdapterT: Adapter<'query>>(
     //       - turn "in_collection" filter arguments into sets if possible
     match filter.right() {
         Some(Argument::Variable(var)) => {
-            let query_arguments = &carrier
+            let slot = variable_slot.unwrap_or_else(|| {
+                unreachable!(
+                    "no slot for variable \"{}\" in {filter:?}",
+                    var.variable_name
+                )
+            });
+            let variable_values = &carrier
                 .query
                 .as_ref()
                 .expect("query was not returned")
-                .arguments;
-            let right_value = query_arguments[var.variable_name.as_ref()].to_owned();
-            apply_filter_with_static_argument_value(filter, right_value, iterator)
+                .variable_values;
+            let right_value = variable_values[slot].clone();
+            apply_filter_with_static_argument_value(filter, custom_scalar, right_value, iterator)
         }
         Some(Argument::Tag(FieldRef::ContextField(context_field))) => {
             // TODO: Benchmark if it would be faster to duplicate the filtering code to special-case
@@ -303,7 +330,6 @@ pub(super) fn apply_filter<'query, AdapterT: Adapter<'query>>(
                     compute_local_field_with_separate_value(
                         adapter,
                         carrier,
-                        component,
                         current_vid,
                         &local_equivalent_field,
                         iterator,
@@ -319,7 +345,7 @@ pub(super) fn apply_filter<'query, AdapterT: Adapter<'query>>(
                     iterator,
                 )
             };
-            apply_filter_with_tagged_argument_value(filter, argument_value_iterator)
+            apply_filter_with_tagged_argument_value(filter, custom_scalar, argument_value_iterator)
         }
         Some(Argument::Tag(field_ref @ FieldRef::FoldSpecificField(fold_field))) => {
             let argument_value_iterator = if component.folds.contains_key(&fold_field.fold_eid) {
@@ -337,7 +363,7 @@ pub(super) fn apply_filter<'query, AdapterT: Adapter<'query>>(
                     (ctx, right_value)
                 }))
             };
-            apply_filter_with_tagged_argument_value(filter, argument_value_iterator)
+            apply_filter_with_tagged_argument_value(filter, custom_scalar, argument_value_iterator)
         }
         None => unreachable!(
             "no argument present for filter, but not handled in unary filters fn: {filter:?}"
@@ -347,9 +373,20 @@ pub(super) fn apply_filter<'query, AdapterT: Adapter<'query>>(
 
 fn apply_filter_with_static_argument_value<'query, Vertex: Debug + Clone + 'query>(
     filter: &Operation<(), &Argument>,
+    custom_scalar: Option<CustomScalar>,
     right_value: FieldValue,
     iterator: ContextIterator<'query, Vertex>,
 ) -> ContextIterator<'query, Vertex> {
+    if let (Some(scalar), Some(expected)) = (custom_scalar, expected_ordering(filter)) {
+        return Box::new(iterator.filter_map(move |mut ctx| {
+            let left_value = ctx.values.pop().expect("no value present");
+            scalar
+                .compare(&left_value, &right_value)
+                .is_some_and(expected)
+                .then_some(ctx)
+        }));
+    }
+
     match filter {
         Operation::Equals(_, _) => Box::new(iterator.filter_map(move |mut ctx| {
             let left_value = ctx.values.pop().expect("no value present");
@@ -445,8 +482,24 @@ fn apply_filter_with_static_argument_value<'query, Vertex: Debug + Clone + 'quer
 
 fn apply_filter_with_tagged_argument_value<'query, Vertex: Debug + Clone + 'query>(
     filter: &Operation<(), &Argument>,
+    custom_scalar: Option<CustomScalar>,
     argument_value_iterator: ContextOutcomeIterator<'query, Vertex, TaggedValue>,
 ) -> ContextIterator<'query, Vertex> {
+    if let (Some(scalar), Some(expected)) = (custom_scalar, expected_ordering(filter)) {
+        return Box::new(
+            argument_value_iterator.filter_map(move |(mut ctx, tagged_value)| {
+                let left_value = ctx.values.pop().expect("no value present");
+                let TaggedValue::Some(right_value) = tagged_value else {
+                    return Some(ctx);
+                };
+                scalar
+                    .compare(&left_value, &right_value)
+                    .is_some_and(expected)
+                    .then_some(ctx)
+            }),
+        );
+    }
+
     match filter {
         Operation::Equals(_, _) => Box::new(argument_value_iterator.filter_map(
             move |(mut ctx, tagged_value)| {
@@ -1,7 +1,5 @@
 use std::{fmt::Debug, ops::Bound, sync::Arc};
 
-use async_graphql_parser::types::Type;
-
 use crate::{
     interpreter::{
         execution::{
@@ -12,7 +10,9 @@ use crate::{
         Adapter, ContextIterator, ContextOutcomeIterator, InterpretedQuery, TaggedValue,
         VertexIterator,
     },
-    ir::{ContextField, FieldRef, FieldValue, FoldSpecificField, IRQueryComponent, Operation},
+    ir::{
+        ContextField, FieldRef, FieldValue, FoldSpecificField, IRQueryComponent, Operation, Type,
+    },
 };
 
 use super::CandidateValue;
@@ -208,16 +208,10 @@ impl<'a> DynamicallyResolvedValue<'a> {
     ) -> ContextOutcomeIterator<'vertex, AdapterT::Vertex, CandidateValue<FieldValue>> {
         match &self.field {
             FieldRef::ContextField(context_field) => {
-                if context_field.vertex_id < self.resolve_on_component.root {
-                    // We're inside at least one level of `@fold` relative to
-                    // the origin of this tag.
-                    //
-                    // We'll have to grab the tag's value from the context directly.
-                    let field_ref = self.field;
-                    self.compute_candidate_from_tagged_value_with_imported_tags(field_ref, contexts)
-                } else {
-                    self.compute_candidate_from_tagged_value(context_field, adapter, contexts)
-                }
+                // If we're inside at least one level of `@fold` relative to the origin
+                // of this tag, its value is imported into the context, or resolved now
+                // if the fold deferred resolving it.
+                self.compute_candidate_from_tagged_value(context_field, adapter, contexts)
             }
             FieldRef::FoldSpecificField(fold_field) => {
                 // TODO cover this with tests
@@ -267,6 +261,8 @@ impl<'a> DynamicallyResolvedValue<'a> {
     ) -> ContextOutcomeIterator<'vertex, AdapterT::Vertex, CandidateValue<FieldValue>> {
         let mut carrier = QueryCarrier {
             query: Some(self.query),
+            coercions: Default::default(),
+            observations: None,
         };
         let iterator = compute_context_field_with_separate_value(
             adapter,
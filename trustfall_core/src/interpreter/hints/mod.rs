@@ -324,6 +324,14 @@ impl EdgeInfo {
     pub fn destination(&self) -> &NeighborInfo {
         &self.destination
     }
+
+    /// The `@recurse` directive on this edge, if any: how deep the recursion goes,
+    /// and which type the vertices are coerced to at each step.
+    #[allow(dead_code)] // false-positive: dead in the bin target, not dead in the lib
+    #[inline]
+    pub fn recursive(&self) -> Option<&Recursive> {
+        self.recursive.as_ref()
+    }
 }
 
 /// Information about a neighboring vertex. Implements [`VertexInfo`].
@@ -320,6 +320,7 @@ fn recurse_directive() {
                 assert!(!edge.optional);
                 assert!(!edge.folded);
                 assert_eq!(edge.recursive, Some(Recursive::new(NonZeroUsize::new(3).unwrap(), None)));
+                assert_eq!(edge.recursive(), edge.recursive.as_ref());
                 assert_eq!(edge.destination().vid(), vid(2));
 
                 // The "first_edge()" method produces the same outcome
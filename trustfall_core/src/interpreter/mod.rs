@@ -1,27 +1,51 @@
-use std::{collections::BTreeMap, fmt::Debug, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    sync::Arc,
+};
 
-use async_graphql_parser::types::Type;
 use itertools::Itertools;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     ir::{
-        types::is_argument_type_valid, EdgeParameters, Eid, FieldRef, FieldValue, IndexedQuery, Vid,
+        types::is_argument_type_valid, Argument, EdgeParameters, Eid, FieldRef, FieldValue,
+        IRQueryComponent, IndexedQuery, Operation, Type, Vid,
     },
-    util::BTreeMapTryInsertExt,
+    schema::CustomScalar,
 };
 
-use self::error::QueryArgumentsError;
+use self::{
+    error::QueryArgumentsError,
+    execution::{DeferredTag, RecursionVisits},
+    persistent::{PersistentMap, PersistentStack},
+};
 
+pub mod access;
 pub mod basic_adapter;
+pub mod cancellation;
+pub mod complexity;
 pub mod error;
 pub mod execution;
 mod filtering;
 pub mod helpers;
 mod hints;
+mod persistent;
+pub(crate) mod planning;
+mod pool;
+pub mod registry;
+pub mod renaming;
 pub mod replay;
+mod row;
+pub mod sql;
+pub mod statistics;
+#[cfg(feature = "opentelemetry")]
+pub mod telemetry;
 pub mod trace;
 
+pub use row::Row;
+
 pub use hints::{
     CandidateValue, DynamicallyResolvedValue, EdgeInfo, NeighborInfo, QueryInfo, Range,
     ResolveEdgeInfo, ResolveInfo, VertexInfo,
@@ -74,13 +98,15 @@ pub(crate) enum TaggedValue {
 #[derive(Debug, Clone)]
 pub struct DataContext<Vertex: Clone + Debug> {
     active_vertex: Option<Vertex>,
-    vertices: BTreeMap<Vid, Option<Vertex>>,
-    values: Vec<FieldValue>,
+    vertices: PersistentMap<Vid, Option<Vertex>>,
+    values: PersistentStack<FieldValue>,
     suspended_vertices: Vec<Option<Vertex>>,
-    folded_contexts: BTreeMap<Eid, Option<Vec<DataContext<Vertex>>>>,
-    folded_values: BTreeMap<(Eid, Arc<str>), Option<ValueOrVec>>,
+    folded_contexts: PersistentMap<Eid, Option<Vec<DataContext<Vertex>>>>,
+    folded_values: PersistentMap<(Eid, Arc<str>), Option<ValueOrVec>>,
     piggyback: Option<Vec<DataContext<Vertex>>>,
-    imported_tags: BTreeMap<FieldRef, TaggedValue>,
+    imported_tags: PersistentMap<FieldRef, TaggedValue>,
+    deferred_tags: PersistentMap<FieldRef, DeferredTag<Vertex>>,
+    recursion_visits: Option<RecursionVisits>,
 }
 
 impl<Vertex: Clone + Debug> DataContext<Vertex> {
@@ -152,6 +178,12 @@ where
     /// Tagged values imported from an ancestor component of the one currently being evaluated.
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     imported_tags: BTreeMap<FieldRef, TaggedValue>,
+
+    /// Imported tags whose values haven't been resolved yet, with the vertices
+    /// whose properties they are. Tags whose deferred values were already resolved
+    /// are in `imported_tags` instead.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    deferred_tags: BTreeMap<FieldRef, Vertex>,
 }
 
 impl<Vertex> From<SerializableContext<Vertex>> for DataContext<Vertex>
@@ -162,13 +194,19 @@ where
     fn from(context: SerializableContext<Vertex>) -> Self {
         Self {
             active_vertex: context.active_vertex,
-            vertices: context.vertices,
-            values: context.values,
+            vertices: context.vertices.into_iter().collect(),
+            values: context.values.into(),
             suspended_vertices: context.suspended_vertices,
-            folded_contexts: context.folded_contexts,
-            folded_values: context.folded_values,
+            folded_contexts: context.folded_contexts.into_iter().collect(),
+            folded_values: context.folded_values.into_iter().collect(),
             piggyback: context.piggyback,
-            imported_tags: context.imported_tags,
+            imported_tags: context.imported_tags.into_iter().collect(),
+            deferred_tags: context
+                .deferred_tags
+                .into_iter()
+                .map(|(tag, vertex)| (tag, DeferredTag::new(vertex)))
+                .collect(),
+            recursion_visits: None,
         }
     }
 }
@@ -179,15 +217,29 @@ where
     for<'d> Vertex: Deserialize<'d>,
 {
     fn from(context: DataContext<Vertex>) -> Self {
+        let mut imported_tags: BTreeMap<_, _> = context.imported_tags.into_iter().collect();
+        let mut deferred_tags = BTreeMap::new();
+        for (tag, deferred) in context.deferred_tags {
+            match deferred.value() {
+                Some(value) => {
+                    imported_tags.insert(tag, TaggedValue::Some(value));
+                }
+                None => {
+                    deferred_tags.insert(tag, deferred.vertex().clone());
+                }
+            }
+        }
+
         Self {
             active_vertex: context.active_vertex,
-            vertices: context.vertices,
-            values: context.values,
+            vertices: context.vertices.into_iter().collect(),
+            values: context.values.into(),
             suspended_vertices: context.suspended_vertices,
-            folded_contexts: context.folded_contexts,
-            folded_values: context.folded_values,
+            folded_contexts: context.folded_contexts.into_iter().collect(),
+            folded_values: context.folded_values.into_iter().collect(),
             piggyback: context.piggyback,
-            imported_tags: context.imported_tags,
+            imported_tags,
+            deferred_tags,
         }
     }
 }
@@ -203,6 +255,8 @@ impl<Vertex: Clone + Debug> DataContext<Vertex> {
             folded_contexts: Default::default(),
             folded_values: Default::default(),
             imported_tags: Default::default(),
+            deferred_tags: Default::default(),
+            recursion_visits: None,
         }
     }
 
@@ -222,6 +276,8 @@ impl<Vertex: Clone + Debug> DataContext<Vertex> {
             folded_values: self.folded_values,
             piggyback: self.piggyback,
             imported_tags: self.imported_tags,
+            deferred_tags: self.deferred_tags,
+            recursion_visits: self.recursion_visits,
         }
     }
 
@@ -235,6 +291,8 @@ impl<Vertex: Clone + Debug> DataContext<Vertex> {
             folded_values: self.folded_values.clone(),
             piggyback: None,
             imported_tags: self.imported_tags.clone(),
+            deferred_tags: self.deferred_tags.clone(),
+            recursion_visits: self.recursion_visits.clone(),
         }
     }
 
@@ -248,6 +306,8 @@ impl<Vertex: Clone + Debug> DataContext<Vertex> {
             folded_values: self.folded_values,
             piggyback: self.piggyback,
             imported_tags: self.imported_tags,
+            deferred_tags: self.deferred_tags,
+            recursion_visits: self.recursion_visits,
         }
     }
 
@@ -263,6 +323,8 @@ impl<Vertex: Clone + Debug> DataContext<Vertex> {
                 folded_values: self.folded_values,
                 piggyback: self.piggyback,
                 imported_tags: self.imported_tags,
+                deferred_tags: self.deferred_tags,
+                recursion_visits: self.recursion_visits,
             }
         } else {
             self
@@ -282,6 +344,8 @@ impl<Vertex: Clone + Debug> DataContext<Vertex> {
                     folded_values: self.folded_values,
                     piggyback: self.piggyback,
                     imported_tags: self.imported_tags,
+                    deferred_tags: self.deferred_tags,
+                    recursion_visits: self.recursion_visits,
                 }
             }
             Some(_) => self,
@@ -335,6 +399,13 @@ where
 pub struct InterpretedQuery {
     pub indexed_query: Arc<IndexedQuery>,
     pub arguments: Arc<BTreeMap<Arc<str>, FieldValue>>,
+
+    /// The values of the query's variables, by [slot](IndexedQuery::variable_slot).
+    pub(crate) variable_values: Arc<[FieldValue]>,
+
+    /// The tags imported into folds that are resolved when they are first used,
+    /// rather than before their fold's edge is expanded. Chosen when planning the execution.
+    deferred_tags: Arc<BTreeSet<FieldRef>>,
 }
 
 impl InterpretedQuery {
@@ -346,16 +417,49 @@ impl InterpretedQuery {
         let mut errors = vec![];
 
         let mut missing_arguments = vec![];
+        let mut parsed_arguments = vec![];
         for (variable_name, variable_type) in &indexed_query.ir_query.variables {
-            match arguments.get(variable_name) {
+            // Variables declared with a default value take that value when not provided.
+            let default_value = indexed_query.ir_query.variable_defaults.get(variable_name);
+            let provided_value = arguments.get(variable_name);
+            match provided_value.or(default_value) {
                 Some(argument_value) => {
-                    // Ensure the provided argument value is valid for the variable's inferred type.
-                    if let Err(e) = validate_argument_type(
+                    let base_type_name = variable_type.base_type();
+                    let custom_scalar = indexed_query.custom_scalars.get(base_type_name);
+                    let enum_values = indexed_query.ir_query.enum_values.get(base_type_name);
+                    if let Some(allowed_values) = enum_values {
+                        // Only the enum's declared values are valid.
+                        match validate_enum_argument(
+                            variable_name.as_ref(),
+                            variable_type,
+                            allowed_values,
+                            argument_value,
+                        ) {
+                            Ok(parsed) => parsed_arguments.push((variable_name.clone(), parsed)),
+                            Err(e) => errors.push(e),
+                        }
+                    } else if let Some(scalar) = custom_scalar {
+                        // Custom scalars' own hooks decide which values are valid,
+                        // and may convert the values into the form the adapter expects.
+                        match validate_custom_scalar_argument(
+                            variable_name.as_ref(),
+                            variable_type,
+                            scalar,
+                            argument_value,
+                        ) {
+                            Ok(parsed) => parsed_arguments.push((variable_name.clone(), parsed)),
+                            Err(e) => errors.push(e),
+                        }
+                    } else if let Err(e) = validate_argument_type(
                         variable_name.as_ref(),
                         variable_type,
                         argument_value,
                     ) {
+                        // Ensure the provided argument value is valid
+                        // for the variable's inferred type.
                         errors.push(e);
+                    } else if provided_value.is_none() {
+                        parsed_arguments.push((variable_name.clone(), argument_value.clone()));
                     }
                 }
                 None => {
@@ -363,6 +467,26 @@ impl InterpretedQuery {
                 }
             }
         }
+
+        // Regex patterns are only compiled once the filters using them are applied,
+        // so check that they are valid before the query starts executing.
+        let mut regex_variables = BTreeSet::new();
+        collect_regex_variables(&indexed_query.ir_query.root_component, &mut regex_variables);
+        for variable_name in regex_variables {
+            let argument_value = arguments
+                .get(variable_name)
+                .or_else(|| indexed_query.ir_query.variable_defaults.get(variable_name));
+            if let Some(FieldValue::String(pattern)) = argument_value {
+                if let Err(e) = Regex::new(pattern) {
+                    errors.push(QueryArgumentsError::InvalidRegexArgument(
+                        variable_name.to_string(),
+                        pattern.clone(),
+                        e.to_string(),
+                    ));
+                }
+            }
+        }
+
         if !missing_arguments.is_empty() {
             errors.push(QueryArgumentsError::MissingArguments(
                 missing_arguments
@@ -387,9 +511,25 @@ impl InterpretedQuery {
         }
 
         if errors.is_empty() {
+            let arguments = if parsed_arguments.is_empty() {
+                arguments
+            } else {
+                let mut arguments = arguments.as_ref().clone();
+                arguments.extend(parsed_arguments);
+                Arc::new(arguments)
+            };
+            let variable_values = indexed_query
+                .ir_query
+                .variables
+                .keys()
+                .map(|name| arguments[name].clone())
+                .collect();
+
             Ok(Self {
                 indexed_query,
                 arguments,
+                variable_values,
+                deferred_tags: Default::default(),
             })
         } else {
             Err(errors.into())
@@ -397,6 +537,25 @@ impl InterpretedQuery {
     }
 }
 
+/// Collect the names of the variables used as regex patterns in the component's filters.
+fn collect_regex_variables<'a>(component: &'a IRQueryComponent, variables: &mut BTreeSet<&'a str>) {
+    let filters = component
+        .vertices
+        .values()
+        .flat_map(|vertex| &vertex.filters);
+    for filter in filters {
+        if let Operation::RegexMatches(_, Argument::Variable(variable))
+        | Operation::NotRegexMatches(_, Argument::Variable(variable)) = filter
+        {
+            variables.insert(variable.variable_name.as_ref());
+        }
+    }
+
+    for fold in component.folds.values() {
+        collect_regex_variables(&fold.component, variables);
+    }
+}
+
 fn validate_argument_type(
     variable_name: &str,
     variable_type: &Type,
@@ -413,6 +572,105 @@ fn validate_argument_type(
     }
 }
 
+fn validate_custom_scalar_argument(
+    variable_name: &str,
+    variable_type: &Type,
+    scalar: &CustomScalar,
+    argument_value: &FieldValue,
+) -> Result<FieldValue, QueryArgumentsError> {
+    convert_argument_value(variable_type, argument_value, &|value| {
+        scalar.parse_and_validate(value).map_err(Some)
+    })
+    .map_err(|message| match message {
+        Some(message) => QueryArgumentsError::InvalidCustomScalarArgument(
+            variable_name.to_string(),
+            variable_type.to_string(),
+            argument_value.to_owned(),
+            message,
+        ),
+        None => QueryArgumentsError::ArgumentTypeError(
+            variable_name.to_string(),
+            variable_type.to_string(),
+            argument_value.to_owned(),
+        ),
+    })
+}
+
+/// Enum values may be provided either as [`FieldValue::Enum`] or as [`FieldValue::String`],
+/// and are passed to the adapter as [`FieldValue::Enum`].
+fn validate_enum_argument(
+    variable_name: &str,
+    variable_type: &Type,
+    allowed_values: &BTreeSet<Arc<str>>,
+    argument_value: &FieldValue,
+) -> Result<FieldValue, QueryArgumentsError> {
+    convert_argument_value(variable_type, argument_value, &|value| match value {
+        FieldValue::Enum(s) | FieldValue::String(s) => {
+            if allowed_values.contains(s.as_str()) {
+                Ok(FieldValue::Enum(s.clone()))
+            } else {
+                Err(Some(()))
+            }
+        }
+        _ => Err(None),
+    })
+    .map_err(|e| match e {
+        Some(()) => QueryArgumentsError::UndeclaredEnumValue(
+            variable_name.to_string(),
+            variable_type.to_string(),
+            argument_value.to_owned(),
+            allowed_values.iter().map(|x| x.to_string()).collect(),
+        ),
+        None => QueryArgumentsError::ArgumentTypeError(
+            variable_name.to_string(),
+            variable_type.to_string(),
+            argument_value.to_owned(),
+        ),
+    })
+}
+
+/// Convert each non-null value nested inside lists of the argument using `convert_leaf`.
+///
+/// Returns `Err(None)` if the value doesn't match the shape of the type (nullability or lists),
+/// and `Err(Some(..))` if `convert_leaf` rejected one of the values.
+fn convert_argument_value<E>(
+    value_type: &Type,
+    value: &FieldValue,
+    convert_leaf: &impl Fn(&FieldValue) -> Result<FieldValue, Option<E>>,
+) -> Result<FieldValue, Option<E>> {
+    match (value_type.element_type(), value) {
+        (_, FieldValue::Null) => {
+            if value_type.is_nullable() {
+                Ok(FieldValue::Null)
+            } else {
+                Err(None)
+            }
+        }
+        (Some(inner), FieldValue::List(values)) => values
+            .iter()
+            .map(|value| convert_argument_value(&inner, value, convert_leaf))
+            .collect::<Result<Vec<_>, _>>()
+            .map(FieldValue::List),
+        (Some(_), _) => Err(None),
+        (None, value) => convert_leaf(value),
+    }
+}
+
+/// Whether the value only contains values of the given enum, possibly nested inside lists.
+pub(crate) fn has_only_declared_enum_values(
+    value: &FieldValue,
+    allowed_values: &BTreeSet<Arc<str>>,
+) -> bool {
+    match value {
+        FieldValue::Null => true,
+        FieldValue::Enum(s) | FieldValue::String(s) => allowed_values.contains(s.as_str()),
+        FieldValue::List(values) => values
+            .iter()
+            .all(|value| has_only_declared_enum_values(value, allowed_values)),
+        _ => false,
+    }
+}
+
 /// Trustfall data providers implement this trait to enable querying their data sets.
 ///
 /// The most straightforward way to implement this trait is by implementing
@@ -445,7 +703,8 @@ pub trait Adapter<'vertex> {
     ///
     /// The caller guarantees that:
     /// - The specified edge is a starting edge in the schema being queried.
-    /// - Any parameters the edge requires per the schema have values provided.
+    /// - Every parameter the edge defines in the schema has a value: either the one specified
+    ///   in the query, or the schema-defined default, or `null` for nullable parameters.
     fn resolve_starting_vertices(
         &self,
         edge_name: &Arc<str>,
@@ -473,6 +732,8 @@ pub trait Adapter<'vertex> {
     /// - Produce `(context, property_value)` tuples with the property's value for that context.
     /// - Produce contexts in the same order as the input `contexts` iterator produced them.
     /// - Produce property values whose type matches the property's type defined in the schema.
+    ///   For properties of enum type, that means only values the schema declares for the enum.
+    ///   Only debug builds check this, and only for the values of the query's outputs.
     /// - When a context's active vertex is `None`, its property value is [`FieldValue::Null`].
     fn resolve_property(
         &self,
@@ -496,7 +757,8 @@ pub trait Adapter<'vertex> {
     /// The caller guarantees that:
     /// - `type_name` is a type or interface defined in the schema.
     /// - `edge_name` is an edge field on `type_name` defined in the schema.
-    /// - Any parameters the edge requires per the schema have values provided.
+    /// - Every parameter the edge defines in the schema has a value: either the one specified
+    ///   in the query, or the schema-defined default, or `null` for nullable parameters.
     /// - When the active vertex is `Some(...)`, it's a vertex of type `type_name`:
     ///   either its type is exactly `type_name`, or `type_name` is an interface that
     ///   the vertex's type implements.
@@ -558,4 +820,154 @@ pub trait Adapter<'vertex> {
         coerce_to_type: &Arc<str>,
         resolve_info: &ResolveInfo,
     ) -> ContextOutcomeIterator<'vertex, Self::Vertex, bool>;
+
+    /// Identify the vertex, so that the interpreter can tell when it reaches the same vertex again.
+    ///
+    /// Identified vertices have the outcome of coercing them to a type cached for the rest
+    /// of the query's execution: when the same vertex is coerced to the same type again,
+    /// whether by another row or another part of the query, [`Adapter::resolve_coercion`]
+    /// isn't given its context and the earlier outcome is used instead. Adapters that also opt
+    /// into [`Adapter::skip_revisited_vertices_in_recursion`] have `@recurse` use the identities
+    /// to skip vertices it has already reached.
+    ///
+    /// The identity of a vertex must be unique among all the adapter's vertices, such as
+    /// an ID or the index of the vertex within the data set. Vertices without an identity
+    /// are always coerced. The default implementation doesn't identify any vertices.
+    fn vertex_identity(&self, _vertex: &Self::Vertex) -> Option<u64> {
+        None
+    }
+
+    /// Whether `@recurse` should reach each identified vertex once, rather than once per path.
+    ///
+    /// By default, `@recurse` follows every path through the recursed edge, producing
+    /// a result each time a path reaches a vertex. Over data where many paths lead to the same
+    /// vertices, such as a graph of dependencies, the number of paths can grow exponentially
+    /// with the depth of the recursion even though the number of vertices doesn't.
+    ///
+    /// Adapters opt into skipping vertices by returning `true` here, and identifying their
+    /// vertices with [`Adapter::vertex_identity`]. Then each time a `@recurse` starts from
+    /// a vertex with an identity, it reaches each vertex with an identity once, without following
+    /// the recursed edge from it again. The exception is a vertex reached by a path with fewer
+    /// edges than the one that reached it first, which is reached again so that all vertices
+    /// within the recursion's depth are still reached. Vertices without an identity are always
+    /// expanded. Since this produces fewer results for queries whose `@recurse` reaches vertices
+    /// along more than one path, it's a choice of the adapter rather than an optimization
+    /// of its queries.
+    ///
+    /// The default implementation returns `false`.
+    fn skip_revisited_vertices_in_recursion(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use crate::{frontend, ir::FieldValue, schema::Schema};
+
+    use super::{error::QueryArgumentsError, InterpretedQuery};
+
+    fn enum_arguments_outcome(
+        value: FieldValue,
+    ) -> Result<BTreeMap<Arc<str>, FieldValue>, QueryArgumentsError> {
+        let schema = Schema::parse(include_str!(
+            "../../test_data/tests/valid_schemas/enum_properties.graphql"
+        ))
+        .unwrap();
+        let query = r#"
+{
+    Shape {
+        name @output
+        color @filter(op: "one_of", value: ["$colors"])
+    }
+}"#;
+        let indexed_query = frontend::parse(&schema, query).unwrap();
+        let arguments = btreemap! {
+            Arc::from("colors") => value,
+        };
+
+        InterpretedQuery::from_query_and_arguments(indexed_query, Arc::new(arguments))
+            .map(|query| query.arguments.as_ref().clone())
+    }
+
+    #[test]
+    fn enum_arguments_are_converted_to_enum_values() {
+        let arguments = enum_arguments_outcome(FieldValue::List(vec![
+            FieldValue::Enum("RED".to_string()),
+            FieldValue::String("BLUE".to_string()),
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            FieldValue::List(vec![
+                FieldValue::Enum("RED".to_string()),
+                FieldValue::Enum("BLUE".to_string()),
+            ]),
+            arguments["colors"],
+        );
+    }
+
+    #[test]
+    fn undeclared_enum_arguments_are_rejected() {
+        let value = FieldValue::List(vec![FieldValue::String("PURPLE".to_string())]);
+        assert_eq!(
+            Err(QueryArgumentsError::UndeclaredEnumValue(
+                "colors".to_string(),
+                "[Color!]!".to_string(),
+                value.clone(),
+                vec!["BLUE".to_string(), "GREEN".to_string(), "RED".to_string()],
+            )),
+            enum_arguments_outcome(value),
+        );
+
+        let value = FieldValue::List(vec![FieldValue::Int64(1)]);
+        assert_eq!(
+            Err(QueryArgumentsError::ArgumentTypeError(
+                "colors".to_string(),
+                "[Color!]!".to_string(),
+                value.clone(),
+            )),
+            enum_arguments_outcome(value),
+        );
+    }
+
+    #[test]
+    fn declared_default_values_are_used_for_omitted_arguments() {
+        let schema = Schema::parse(include_str!(
+            "../../test_data/tests/valid_schemas/enum_properties.graphql"
+        ))
+        .unwrap();
+        let query = r#"
+query($colors: [Color!]! = [RED, GREEN]) {
+    Shape {
+        name @output
+        color @filter(op: "one_of", value: ["$colors"])
+    }
+}"#;
+        let indexed_query = frontend::parse(&schema, query).unwrap();
+
+        let arguments = InterpretedQuery::from_query_and_arguments(
+            indexed_query.clone(),
+            Arc::new(BTreeMap::new()),
+        )
+        .unwrap()
+        .arguments;
+        assert_eq!(
+            FieldValue::List(vec![
+                FieldValue::Enum("RED".to_string()),
+                FieldValue::Enum("GREEN".to_string()),
+            ]),
+            arguments["colors"],
+        );
+
+        let provided = btreemap! {
+            Arc::from("colors") => FieldValue::List(vec![FieldValue::Enum("BLUE".to_string())]),
+        };
+        let arguments =
+            InterpretedQuery::from_query_and_arguments(indexed_query, Arc::new(provided.clone()))
+                .unwrap()
+                .arguments;
+        assert_eq!(provided, *arguments);
+    }
 }
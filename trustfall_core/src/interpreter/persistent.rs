@@ -0,0 +1,543 @@
+//! Immutable collections with structural sharing, for the state the interpreter keeps
+//! in each [`DataContext`](super::DataContext).
+//!
+//! Expanding an edge splits a context into one context per neighbor, each of which
+//! goes on to record its own vertices and values. With these collections, cloning is
+//! O(1) no matter how much state the context has built up, and the clones share that state
+//! instead of copying it: modifying a clone only copies what's on the path to the change.
+use std::{borrow::Borrow, cmp::Ordering, fmt::Debug, ops::Index, sync::Arc};
+
+type Tree<K, V> = Arc<Node<K, V>>;
+
+type Link<K, V> = Option<Tree<K, V>>;
+
+type Entry<K, V> = Arc<(K, V)>;
+
+/// A node of an AVL tree. Entries are behind their own `Arc`, so copying the path
+/// to a modified node never copies keys or values.
+struct Node<K, V> {
+    entry: Entry<K, V>,
+    height: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+fn height<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+fn node<K, V>(entry: Entry<K, V>, left: Link<K, V>, right: Link<K, V>) -> Arc<Node<K, V>> {
+    Arc::new(Node {
+        entry,
+        height: 1 + height(&left).max(height(&right)),
+        left,
+        right,
+    })
+}
+
+/// A node with the entry and subtrees, rotated to be balanced
+/// if the heights of the subtrees differ by two.
+fn balanced<K, V>(entry: Entry<K, V>, left: Link<K, V>, right: Link<K, V>) -> Arc<Node<K, V>> {
+    let (left_height, right_height) = (height(&left), height(&right));
+    if left_height > right_height + 1 {
+        let left = left.expect("taller subtree is empty");
+        if height(&left.left) >= height(&left.right) {
+            node(
+                left.entry.clone(),
+                left.left.clone(),
+                Some(node(entry, left.right.clone(), right)),
+            )
+        } else {
+            let inner = left.right.as_ref().expect("taller subtree is empty");
+            node(
+                inner.entry.clone(),
+                Some(node(
+                    left.entry.clone(),
+                    left.left.clone(),
+                    inner.left.clone(),
+                )),
+                Some(node(entry, inner.right.clone(), right)),
+            )
+        }
+    } else if right_height > left_height + 1 {
+        let right = right.expect("taller subtree is empty");
+        if height(&right.right) >= height(&right.left) {
+            node(
+                right.entry.clone(),
+                Some(node(entry, left, right.left.clone())),
+                right.right.clone(),
+            )
+        } else {
+            let inner = right.left.as_ref().expect("taller subtree is empty");
+            node(
+                inner.entry.clone(),
+                Some(node(entry, left, inner.left.clone())),
+                Some(node(
+                    right.entry.clone(),
+                    inner.right.clone(),
+                    right.right.clone(),
+                )),
+            )
+        }
+    } else {
+        node(entry, left, right)
+    }
+}
+
+/// Insert the entry, returning the new subtree and the entry it replaced, if any.
+fn insert<K: Ord, V>(link: &Link<K, V>, entry: Entry<K, V>) -> (Tree<K, V>, Option<Entry<K, V>>) {
+    let Some(current) = link else {
+        return (node(entry, None, None), None);
+    };
+    match entry.0.cmp(&current.entry.0) {
+        Ordering::Less => {
+            let (left, replaced) = insert(&current.left, entry);
+            let subtree = balanced(current.entry.clone(), Some(left), current.right.clone());
+            (subtree, replaced)
+        }
+        Ordering::Greater => {
+            let (right, replaced) = insert(&current.right, entry);
+            let subtree = balanced(current.entry.clone(), current.left.clone(), Some(right));
+            (subtree, replaced)
+        }
+        Ordering::Equal => {
+            let subtree = node(entry, current.left.clone(), current.right.clone());
+            (subtree, Some(current.entry.clone()))
+        }
+    }
+}
+
+/// Remove the smallest entry of the subtree, returning it and the rest of the subtree.
+fn remove_min<K, V>(current: &Arc<Node<K, V>>) -> (Entry<K, V>, Link<K, V>) {
+    match &current.left {
+        None => (current.entry.clone(), current.right.clone()),
+        Some(left) => {
+            let (min, left) = remove_min(left);
+            let subtree = balanced(current.entry.clone(), left, current.right.clone());
+            (min, Some(subtree))
+        }
+    }
+}
+
+/// Remove the key's entry, returning the new subtree and the entry, if the key was present.
+fn remove<K, V, Q>(link: &Link<K, V>, key: &Q) -> Option<(Link<K, V>, Entry<K, V>)>
+where
+    K: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    let current = link.as_ref()?;
+    match key.cmp(current.entry.0.borrow()) {
+        Ordering::Less => {
+            let (left, removed) = remove(&current.left, key)?;
+            let subtree = balanced(current.entry.clone(), left, current.right.clone());
+            Some((Some(subtree), removed))
+        }
+        Ordering::Greater => {
+            let (right, removed) = remove(&current.right, key)?;
+            let subtree = balanced(current.entry.clone(), current.left.clone(), right);
+            Some((Some(subtree), removed))
+        }
+        Ordering::Equal => {
+            let subtree = match (&current.left, &current.right) {
+                (None, subtree) | (subtree, None) => subtree.clone(),
+                (Some(left), Some(right)) => {
+                    let (successor, right) = remove_min(right);
+                    Some(balanced(successor, Some(left.clone()), right))
+                }
+            };
+            Some((subtree, current.entry.clone()))
+        }
+    }
+}
+
+/// The entry's value, without copying it unless the entry is shared.
+fn into_value<K, V: Clone>(entry: Entry<K, V>) -> V {
+    match Arc::try_unwrap(entry) {
+        Ok((_, value)) => value,
+        Err(entry) => entry.1.clone(),
+    }
+}
+
+/// An ordered map whose clones share their entries. Its API mirrors that of `BTreeMap`.
+pub(crate) struct PersistentMap<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+impl<K, V> PersistentMap<K, V> {
+    pub(crate) fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The entries, in order of key.
+    pub(crate) fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: vec![] };
+        iter.push_left_spine(&self.root);
+        iter
+    }
+}
+
+impl<K: Ord, V> PersistentMap<K, V> {
+    pub(crate) fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = &self.root;
+        while let Some(current) = link {
+            link = match key.cmp(current.entry.0.borrow()) {
+                Ordering::Less => &current.left,
+                Ordering::Greater => &current.right,
+                Ordering::Equal => return Some(&current.entry.1),
+            };
+        }
+        None
+    }
+
+    pub(crate) fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+}
+
+impl<K: Ord, V: Clone> PersistentMap<K, V> {
+    /// Insert the value at the key, returning the value it replaced, if any.
+    pub(crate) fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (root, replaced) = insert(&self.root, Arc::new((key, value)));
+        self.root = Some(root);
+        match replaced {
+            Some(entry) => Some(into_value(entry)),
+            None => {
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    /// Insert the value at the key, unless the key is already present,
+    /// in which case the map is unchanged and the value is returned.
+    pub(crate) fn insert_or_error(&mut self, key: K, value: V) -> Result<(), V> {
+        if self.contains_key(&key) {
+            Err(value)
+        } else {
+            self.insert(key, value);
+            Ok(())
+        }
+    }
+
+    pub(crate) fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (root, removed) = remove(&self.root, key)?;
+        self.root = root;
+        self.len -= 1;
+        Some(into_value(removed))
+    }
+}
+
+impl<K, V> Clone for PersistentMap<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K, V> Default for PersistentMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for PersistentMap<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for PersistentMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for PersistentMap<K, V> {}
+
+impl<K: Ord, V, Q> Index<&Q> for PersistentMap<K, V>
+where
+    K: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+impl<K: Ord, V: Clone> Extend<(K, V)> for PersistentMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V: Clone> FromIterator<(K, V)> for PersistentMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+/// The entries of a map, in order of key, copied out of the map
+/// unless the map is the only one sharing them.
+impl<K: Clone, V: Clone> IntoIterator for PersistentMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = std::vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut entries = Vec::with_capacity(self.len);
+        let mut stack = vec![];
+        let mut link = self.root;
+        loop {
+            while let Some(current) = link {
+                link = current.left.clone();
+                stack.push(current);
+            }
+            let Some(current) = stack.pop() else {
+                break;
+            };
+            link = current.right.clone();
+            let entry = match Arc::try_unwrap(current) {
+                Ok(node) => node.entry,
+                Err(node) => node.entry.clone(),
+            };
+            entries.push(Arc::try_unwrap(entry).unwrap_or_else(|entry| (*entry).clone()));
+        }
+        entries.into_iter()
+    }
+}
+
+pub(crate) struct Iter<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left_spine(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(current) = link {
+            self.stack.push(current);
+            link = &current.left;
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.stack.pop()?;
+        self.push_left_spine(&current.right);
+        Some((&current.entry.0, &current.entry.1))
+    }
+}
+
+struct StackNode<T> {
+    value: T,
+    len: usize,
+    next: Option<Arc<StackNode<T>>>,
+}
+
+/// A stack whose clones share their values. Its API mirrors that of `Vec`,
+/// without indexing: values are pushed and popped at the end.
+pub(crate) struct PersistentStack<T> {
+    top: Option<Arc<StackNode<T>>>,
+}
+
+impl<T> PersistentStack<T> {
+    pub(crate) fn new() -> Self {
+        Self { top: None }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.top.as_ref().map_or(0, |top| top.len)
+    }
+
+    pub(crate) fn push(&mut self, value: T) {
+        let len = self.len() + 1;
+        let next = self.top.take();
+        self.top = Some(Arc::new(StackNode { value, len, next }));
+    }
+
+    /// The values, from the top of the stack down.
+    fn iter(&self) -> impl Iterator<Item = &T> {
+        let mut node = self.top.as_deref();
+        std::iter::from_fn(move || {
+            let current = node?;
+            node = current.next.as_deref();
+            Some(&current.value)
+        })
+    }
+}
+
+impl<T: Clone> PersistentStack<T> {
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        let top = self.top.take()?;
+        match Arc::try_unwrap(top) {
+            Ok(top) => {
+                self.top = top.next;
+                Some(top.value)
+            }
+            Err(top) => {
+                self.top = top.next.clone();
+                Some(top.value.clone())
+            }
+        }
+    }
+
+    /// Empty the stack, returning its values in the order they were pushed.
+    pub(crate) fn take_all(&mut self) -> Vec<T> {
+        let mut values = Vec::with_capacity(self.len());
+        self.take_all_into(&mut values);
+        values
+    }
+
+    /// Empty the stack, appending its values to the buffer in the order they were pushed.
+    pub(crate) fn take_all_into(&mut self, values: &mut Vec<T>) {
+        let start = values.len();
+        values.reserve(self.len());
+        while let Some(value) = self.pop() {
+            values.push(value);
+        }
+        values[start..].reverse();
+    }
+}
+
+impl<T> Clone for PersistentStack<T> {
+    fn clone(&self) -> Self {
+        Self {
+            top: self.top.clone(),
+        }
+    }
+}
+
+impl<T> Default for PersistentStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Debug> Debug for PersistentStack<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut values: Vec<_> = self.iter().collect();
+        values.reverse();
+        f.debug_list().entries(values).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for PersistentStack<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for PersistentStack<T> {}
+
+impl<T: Clone> From<Vec<T>> for PersistentStack<T> {
+    fn from(values: Vec<T>) -> Self {
+        let mut stack = Self::new();
+        for value in values {
+            stack.push(value);
+        }
+        stack
+    }
+}
+
+impl<T: Clone> From<PersistentStack<T>> for Vec<T> {
+    fn from(mut stack: PersistentStack<T>) -> Self {
+        stack.take_all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{height, Link, PersistentMap, PersistentStack};
+
+    /// Check the tree is ordered and balanced, returning its height.
+    fn check_balanced<K: Ord, V>(link: &Link<K, V>) -> usize {
+        let Some(node) = link else {
+            return 0;
+        };
+        if let Some(left) = &node.left {
+            assert!(left.entry.0 < node.entry.0);
+        }
+        if let Some(right) = &node.right {
+            assert!(right.entry.0 > node.entry.0);
+        }
+        let (left, right) = (check_balanced(&node.left), check_balanced(&node.right));
+        assert!(left.abs_diff(right) <= 1);
+        assert_eq!(node.height, 1 + left.max(right));
+        node.height
+    }
+
+    #[test]
+    fn map_matches_btree_map() {
+        let mut map = PersistentMap::new();
+        let mut expected = BTreeMap::new();
+        // A deterministic shuffle of the keys, so inserts and removals hit every rotation.
+        let keys: Vec<u64> = (0..500).map(|n| (n * 7919) % 503).collect();
+        for (index, key) in keys.iter().enumerate() {
+            assert_eq!(expected.insert(*key, index), map.insert(*key, index));
+            check_balanced(&map.root);
+        }
+        let snapshot = map.clone();
+        for key in keys.iter().step_by(3) {
+            assert_eq!(expected.remove(key), map.remove(key));
+            assert_eq!(None, map.remove(key));
+            check_balanced(&map.root);
+        }
+        assert_eq!(Err(1), map.insert_or_error(keys[1], 1));
+        assert_eq!(Ok(()), map.insert_or_error(1000, 1));
+        expected.insert(1000, 1);
+
+        assert_eq!(expected.len(), map.len());
+        assert!(map.iter().eq(expected.iter()));
+        assert_eq!(
+            expected.into_iter().collect::<Vec<_>>(),
+            map.into_iter().collect::<Vec<_>>()
+        );
+        // Modifying a clone doesn't affect the map it was cloned from.
+        assert_eq!(500, snapshot.len());
+        assert_eq!(Some(&0), snapshot.get(&0));
+        assert!(height(&snapshot.root) <= 13);
+    }
+
+    #[test]
+    fn stack_clones_share_values() {
+        let mut stack = PersistentStack::from(vec![1, 2, 3]);
+        let mut clone = stack.clone();
+        clone.push(4);
+        assert_eq!(Some(3), stack.pop());
+        assert_eq!(vec![1, 2, 3, 4], Vec::from(clone));
+        assert_eq!(2, stack.len());
+        assert_eq!(vec![1, 2], stack.take_all());
+        assert_eq!(0, stack.len());
+
+        let mut values = vec![0];
+        PersistentStack::from(vec![1, 2]).take_all_into(&mut values);
+        assert_eq!(vec![0, 1, 2], values);
+    }
+}
@@ -0,0 +1,648 @@
+//! Choosing the order in which a component's edges and folds are expanded,
+//! and lowering a query to the flat sequence of steps that executes it.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use crate::ir::{
+    Argument, Eid, FieldCost, FieldRef, IREdge, IRFold, IRQueryComponent, IndexedQuery, Vid,
+};
+
+use super::statistics::QueryStatistics;
+
+/// One step of executing a query, as planned by [`plan_execution`].
+#[derive(Debug, Clone)]
+pub(super) enum Step {
+    /// Coerce the active vertices to the vertex's type, discarding those of other types.
+    Coerce(Vid),
+
+    /// Apply the vertex's filter at this index in [`VertexMetadata::filters`].
+    ///
+    /// [`VertexMetadata::filters`]: crate::ir::VertexMetadata::filters
+    Filter(Vid, usize),
+
+    /// Record the active vertex as the vertex at this [`Vid`].
+    Record(Vid),
+
+    /// Expand the edge, making its neighbors the active vertices.
+    ExpandEdge(Arc<IREdge>),
+
+    /// Expand the fold's edge, and execute the steps after this one up to the one at index
+    /// `end`, which is the fold's [`Step::EndFold`], starting from each of its neighbors.
+    ///
+    /// The fold's imported tags are resolved before its edge is expanded, except for
+    /// the `deferred_tags` chosen by [`plan_deferred_tags`], which are resolved when
+    /// the fold's steps first use them.
+    BeginFold {
+        fold: Arc<IRFold>,
+        end: usize,
+        deferred_tags: Vec<FieldRef>,
+    },
+
+    /// Apply the fold's post-filters and compute its outputs.
+    EndFold(Arc<IRFold>),
+
+    /// Resolve the value of the output from the query's root component.
+    Output(Arc<str>),
+}
+
+/// Lower the query to the steps that execute it, in order.
+///
+/// The steps for the root component and each fold component are those for entering
+/// its root vertex, followed by those for each of its expansions in the order chosen by
+/// [`plan_expansions`]. The steps for a fold's component are between the fold's
+/// [`Step::BeginFold`] and [`Step::EndFold`], and the query's outputs come last.
+///
+/// The `statistics` observed while executing the query before, if any, inform the order
+/// of its expansions.
+pub(super) fn plan_execution(
+    query: &IndexedQuery,
+    statistics: Option<&QueryStatistics>,
+) -> Vec<Step> {
+    let mut steps = vec![];
+    let root_component = &query.ir_query.root_component;
+    plan_component(query, statistics, root_component, &mut steps);
+    steps.extend(root_component.outputs.keys().cloned().map(Step::Output));
+    steps
+}
+
+/// The plans of a query, computed once and shared by all its executions, see [`cached_plan`].
+#[derive(Default, Clone)]
+pub(crate) struct PlanCache {
+    unobserved: Arc<OnceLock<Arc<[Step]>>>,
+
+    /// The plans for statistics, by the order of expansions the statistics led to.
+    #[allow(clippy::type_complexity)]
+    observed: Arc<Mutex<BTreeMap<Vec<Eid>, Arc<[Step]>>>>,
+}
+
+impl Debug for PlanCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PlanCache")
+            .field("planned", &self.unobserved.get().is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Plans are derived from the query, so they don't affect whether queries are equal.
+impl PartialEq for PlanCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for PlanCache {}
+
+/// The steps that execute the query, like [`plan_execution`], but only planning the query
+/// the first time it's executed.
+///
+/// Statistics may change the order of the query's expansions, which is all they affect
+/// about its plan. So with statistics, the query is only planned again when they lead to
+/// an order of expansions that its earlier executions didn't use.
+pub(super) fn cached_plan(
+    query: &IndexedQuery,
+    statistics: Option<&QueryStatistics>,
+) -> Arc<[Step]> {
+    let cache = &query.plans;
+    let Some(statistics) = statistics else {
+        return cache
+            .unobserved
+            .get_or_init(|| plan_execution(query, None).into())
+            .clone();
+    };
+
+    let mut order = vec![];
+    collect_expansion_order(&query.ir_query.root_component, statistics, &mut order);
+    cache
+        .observed
+        .lock()
+        .expect("plan cache lock was poisoned")
+        .entry(order)
+        .or_insert_with(|| plan_execution(query, Some(statistics)).into())
+        .clone()
+}
+
+fn collect_expansion_order(
+    component: &IRQueryComponent,
+    statistics: &QueryStatistics,
+    order: &mut Vec<Eid>,
+) {
+    for expansion in plan_expansions(component, Some(statistics)) {
+        order.push(expansion.eid());
+        if let Expansion::Fold(fold) = expansion {
+            collect_expansion_order(&fold.component, statistics, order);
+        }
+    }
+}
+
+fn plan_component(
+    query: &IndexedQuery,
+    statistics: Option<&QueryStatistics>,
+    component: &IRQueryComponent,
+    steps: &mut Vec<Step>,
+) {
+    plan_vertex_entry(query, component.root, steps);
+    for expansion in plan_expansions(component, statistics) {
+        match expansion {
+            Expansion::Edge(edge) => {
+                steps.push(Step::ExpandEdge(edge.clone()));
+                plan_vertex_entry(query, edge.to_vid, steps);
+            }
+            Expansion::Fold(fold) => {
+                let begin = steps.len();
+                steps.push(Step::BeginFold {
+                    fold: fold.clone(),
+                    end: begin,
+                    deferred_tags: vec![],
+                });
+                plan_component(query, statistics, &fold.component, steps);
+
+                let fold_end = steps.len();
+                let deferred = plan_deferred_tags(query, fold, &steps[begin + 1..]);
+                if let Step::BeginFold {
+                    end, deferred_tags, ..
+                } = &mut steps[begin]
+                {
+                    *end = fold_end;
+                    *deferred_tags = deferred;
+                }
+                steps.push(Step::EndFold(fold.clone()));
+            }
+        }
+    }
+}
+
+fn plan_vertex_entry(query: &IndexedQuery, vid: Vid, steps: &mut Vec<Step>) {
+    let vertex = &query.vertices[&vid];
+    if vertex.coerced_from_type.is_some() {
+        steps.push(Step::Coerce(vid));
+    }
+    steps.extend((0..vertex.filters.len()).map(|index| Step::Filter(vid, index)));
+    steps.push(Step::Record(vid));
+}
+
+/// The tags imported into the fold that are worth resolving only once its steps use them.
+///
+/// Resolving an imported tag before the fold's edge is expanded takes one call to the adapter
+/// for all the fold's starting vertices. Deferring it takes a call for each starting vertex
+/// whose fold elements reach a use of the tag, and none for those whose elements are all
+/// discarded first. So tags are deferred if a step of the fold that may discard elements
+/// comes before the tag's first use, and resolved eagerly otherwise. Uses include expanding
+/// an edge to a vertex with a filter using the tag, since the adapter may ask for the tag's
+/// value while expanding the edge, see [`VertexInfo`](super::VertexInfo).
+///
+/// Tags of fold-specific fields are never deferred, since they don't need the adapter.
+fn plan_deferred_tags(query: &IndexedQuery, fold: &IRFold, fold_steps: &[Step]) -> Vec<FieldRef> {
+    let uses_tag = |vid: Vid, tag: &FieldRef| {
+        query.vertices[&vid]
+            .filters
+            .iter()
+            .any(|filter| matches!(filter.operation.right(), Some(Argument::Tag(t)) if t == tag))
+    };
+
+    fold.imported_tags
+        .iter()
+        .filter(|tag| matches!(tag, FieldRef::ContextField(..)))
+        .filter(|tag| {
+            for step in fold_steps {
+                let (uses, may_discard) = match step {
+                    Step::Coerce(_) => (false, true),
+                    Step::Filter(vid, index) => {
+                        let filter = &query.vertices[vid].filters[*index];
+                        let uses =
+                            matches!(filter.operation.right(), Some(Argument::Tag(t)) if t == *tag);
+                        (uses, true)
+                    }
+                    Step::Record(_) | Step::Output(_) => (false, false),
+                    Step::ExpandEdge(edge) => (
+                        uses_tag(edge.to_vid, tag),
+                        !edge.optional && edge.recursive.is_none(),
+                    ),
+                    Step::BeginFold { fold, .. } => (uses_tag(fold.to_vid, tag), false),
+                    Step::EndFold(fold) => {
+                        let uses = fold.post_filters.iter().any(
+                            |filter| matches!(filter.right(), Some(Argument::Tag(t)) if t == *tag),
+                        );
+                        (uses, !fold.post_filters.is_empty())
+                    }
+                };
+                if uses {
+                    return false;
+                }
+                if may_discard {
+                    return true;
+                }
+            }
+            false
+        })
+        .cloned()
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Expansion<'a> {
+    Edge(&'a Arc<IREdge>),
+    Fold(&'a Arc<IRFold>),
+}
+
+impl<'a> Expansion<'a> {
+    fn eid(&self) -> Eid {
+        match self {
+            Expansion::Edge(edge) => edge.eid,
+            Expansion::Fold(fold) => fold.eid,
+        }
+    }
+
+    fn source_vid(&self) -> Vid {
+        match self {
+            Expansion::Edge(edge) => edge.from_vid,
+            Expansion::Fold(fold) => fold.from_vid,
+        }
+    }
+
+    fn destination_vid(&self) -> Vid {
+        match self {
+            Expansion::Edge(edge) => edge.to_vid,
+            Expansion::Fold(fold) => fold.to_vid,
+        }
+    }
+
+    fn cost(&self) -> Option<FieldCost> {
+        match self {
+            Expansion::Edge(edge) => edge.cost,
+            Expansion::Fold(fold) => fold.cost,
+        }
+    }
+
+    /// The relative cost of the expansion. An observed number of results per source replaces
+    /// the annotated fan-out, since it also accounts for the filters at the expansion's neighbor.
+    fn estimate(&self, statistics: Option<&QueryStatistics>) -> f64 {
+        let cost = self.cost().unwrap_or(FieldCost {
+            fanout: None,
+            latency: None,
+        });
+        let observed = statistics
+            .and_then(|statistics| statistics.expansions.get(&self.eid()))
+            .and_then(|expansion| expansion.retained_per_source());
+        match observed {
+            Some(retained) => {
+                retained
+                    * FieldCost {
+                        fanout: None,
+                        ..cost
+                    }
+                    .estimate() as f64
+            }
+            None => cost.estimate() as f64,
+        }
+    }
+}
+
+/// The order in which to expand the component's edges and folds.
+///
+/// Without `@cost` annotations or observed `statistics`, expansions happen in query order.
+/// Otherwise, the cheapest expansion that is ready is chosen at each step, so that expensive
+/// expansions are deferred until the filters on cheaper ones have had a chance to discard results.
+///
+/// An expansion is ready when its starting vertex has been reached, and every vertex
+/// whose tagged values are used in this component and whose [`Vid`] is smaller than
+/// the expansion's destination has been reached too. The latter preserves the guarantee that
+/// adapters may rely on tagged values from vertices before the current one being available.
+pub(super) fn plan_expansions<'a>(
+    component: &'a IRQueryComponent,
+    statistics: Option<&QueryStatistics>,
+) -> Vec<Expansion<'a>> {
+    let mut expansions: Vec<_> = component
+        .edges
+        .values()
+        .map(Expansion::Edge)
+        .chain(component.folds.values().map(Expansion::Fold))
+        .collect();
+    expansions.sort_unstable_by_key(|expansion| expansion.eid());
+
+    let observed = |expansion: &Expansion| {
+        statistics.is_some_and(|statistics| statistics.expansions.contains_key(&expansion.eid()))
+    };
+    if expansions
+        .iter()
+        .all(|expansion| expansion.cost().is_none() && !observed(expansion))
+    {
+        return expansions;
+    }
+
+    let tag_sources = collect_tag_sources(component);
+    let mut reached: BTreeSet<Vid> = btreeset! {component.root};
+    let mut plan = Vec::with_capacity(expansions.len());
+    while !expansions.is_empty() {
+        // Query order is always a valid order, so some expansion is always ready.
+        // Ties between equally-expensive expansions are broken by query order.
+        let (index, _) = expansions
+            .iter()
+            .enumerate()
+            .filter(|(_, expansion)| {
+                reached.contains(&expansion.source_vid())
+                    && tag_sources
+                        .range(..expansion.destination_vid())
+                        .all(|vid| reached.contains(vid))
+            })
+            .min_by(|(_, left), (_, right)| {
+                left.estimate(statistics)
+                    .total_cmp(&right.estimate(statistics))
+            })
+            .expect("no expansion is ready");
+
+        let expansion = expansions.remove(index);
+        reached.insert(expansion.destination_vid());
+        plan.push(expansion);
+    }
+
+    plan
+}
+
+/// The vertices in this component whose values are used by tags within the component.
+fn collect_tag_sources(component: &IRQueryComponent) -> BTreeSet<Vid> {
+    let mut tags: Vec<&FieldRef> = vec![];
+    for vertex in component.vertices.values() {
+        tags.extend(
+            vertex
+                .filters
+                .iter()
+                .filter_map(|filter| match filter.right() {
+                    Some(Argument::Tag(tag)) => Some(tag),
+                    _ => None,
+                }),
+        );
+    }
+    for fold in component.folds.values() {
+        tags.extend(fold.imported_tags.iter());
+        tags.extend(
+            fold.post_filters
+                .iter()
+                .filter_map(|filter| match filter.right() {
+                    Some(Argument::Tag(tag)) => Some(tag),
+                    _ => None,
+                }),
+        );
+    }
+
+    tags.into_iter()
+        .filter_map(|tag| match tag {
+            FieldRef::ContextField(field) => Some(field.vertex_id),
+            FieldRef::FoldSpecificField(field) => {
+                component.folds.get(&field.fold_eid).map(|fold| fold.to_vid)
+            }
+        })
+        // Vertices outside this component have already been reached.
+        .filter(|vid| component.vertices.contains_key(vid))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, fs, sync::Arc};
+
+    use crate::{
+        frontend::parse, interpreter::statistics::ExpansionStatistics, ir::Eid, schema::Schema,
+    };
+
+    use super::{cached_plan, plan_execution, plan_expansions, QueryStatistics, Step};
+
+    fn planned_edge_names(query: &str) -> Vec<String> {
+        planned_edge_names_with_statistics(query, None)
+    }
+
+    fn planned_edge_names_with_statistics(
+        query: &str,
+        statistics: Option<&QueryStatistics>,
+    ) -> Vec<String> {
+        let schema = Schema::parse(
+            fs::read_to_string("test_data/tests/valid_schemas/edge_costs.graphql").unwrap(),
+        )
+        .unwrap();
+        let indexed_query = parse(&schema, query).unwrap();
+        let component = &indexed_query.ir_query.root_component;
+        plan_expansions(component, statistics)
+            .into_iter()
+            .map(|expansion| match expansion {
+                super::Expansion::Edge(edge) => edge.edge_name.to_string(),
+                super::Expansion::Fold(fold) => fold.edge_name.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn expensive_edges_are_expanded_last() {
+        let query = r#"
+{
+    Package(name: "trustfall") {
+        releases {
+            version @output
+        }
+        maintainers @fold {
+            name @output
+        }
+        owner {
+            name @output(name: "owner")
+        }
+    }
+}"#;
+        assert_eq!(
+            vec!["owner", "maintainers", "releases"],
+            planned_edge_names(query)
+        );
+    }
+
+    #[test]
+    fn observed_statistics_replace_annotated_fanouts() {
+        let query = r#"
+{
+    Package(name: "trustfall") {
+        releases {
+            version @output @filter(op: "=", value: ["$version"])
+        }
+        maintainers @fold {
+            name @output
+        }
+        owner {
+            name @output(name: "owner")
+        }
+    }
+}"#;
+        // Few releases match the filter, so even with high latency `releases` is the cheapest.
+        // The fan-out of `maintainers` wasn't observed, so its annotation is still used.
+        let observed = |sources, neighbors, retained| ExpansionStatistics {
+            sources,
+            neighbors,
+            retained,
+        };
+        let statistics = QueryStatistics {
+            executions: 1,
+            expansions: BTreeMap::from([
+                (Eid::new(1.try_into().unwrap()), observed(1000, 900_000, 1)),
+                (Eid::new(3.try_into().unwrap()), observed(1000, 1000, 1000)),
+            ]),
+        };
+        assert_eq!(
+            vec!["releases", "owner", "maintainers"],
+            planned_edge_names_with_statistics(query, Some(&statistics))
+        );
+    }
+
+    #[test]
+    fn queries_are_planned_again_only_for_new_expansion_orders() {
+        let schema = Schema::parse(
+            fs::read_to_string("test_data/tests/valid_schemas/edge_costs.graphql").unwrap(),
+        )
+        .unwrap();
+        let query = r#"
+{
+    Package(name: "trustfall") {
+        releases {
+            version @output
+        }
+        owner {
+            name @output(name: "owner")
+        }
+    }
+}"#;
+        let indexed_query = parse(&schema, query).unwrap();
+        let plan = cached_plan(&indexed_query, None);
+        assert!(Arc::ptr_eq(&plan, &cached_plan(&indexed_query, None)));
+
+        let with_retained = |retained| QueryStatistics {
+            executions: 1,
+            expansions: BTreeMap::from([(
+                Eid::new(1.try_into().unwrap()),
+                ExpansionStatistics {
+                    sources: 10,
+                    neighbors: 1000,
+                    retained,
+                },
+            )]),
+        };
+
+        // Both of these keep `releases` after `owner`, so they share a plan.
+        let many = cached_plan(&indexed_query, Some(&with_retained(1000)));
+        let fewer = cached_plan(&indexed_query, Some(&with_retained(900)));
+        assert!(Arc::ptr_eq(&many, &fewer));
+
+        // Few enough releases move `releases` first, which needs a new plan.
+        let few = cached_plan(&indexed_query, Some(&with_retained(0)));
+        assert!(!Arc::ptr_eq(&many, &few));
+        let first_edge = |plan: &[Step]| {
+            plan.iter().find_map(|step| match step {
+                Step::ExpandEdge(edge) => Some(edge.edge_name.to_string()),
+                _ => None,
+            })
+        };
+        assert_eq!(Some("owner".to_string()), first_edge(&many));
+        assert_eq!(Some("releases".to_string()), first_edge(&few));
+        assert!(Arc::ptr_eq(
+            &few,
+            &cached_plan(
+                &indexed_query,
+                Some(&QueryStatistics {
+                    executions: 2,
+                    ..with_retained(0)
+                })
+            )
+        ));
+    }
+
+    #[test]
+    fn tagged_values_are_available_before_they_are_used() {
+        // The filter on `owner` uses a tag from the `releases` vertex,
+        // so `releases` must be expanded first despite being more expensive.
+        let query = r#"
+{
+    Package(name: "trustfall") {
+        releases {
+            author @tag @output
+        }
+        owner {
+            name @filter(op: "=", value: ["%author"]) @output(name: "owner")
+        }
+    }
+}"#;
+        assert_eq!(vec!["releases", "owner"], planned_edge_names(query));
+    }
+
+    #[test]
+    fn unannotated_edges_count_as_cheap() {
+        let query = r#"
+{
+    Package(name: "trustfall") {
+        name @output
+        releases {
+            version @output
+            unannotated {
+                name @output(name: "second")
+            }
+        }
+        unannotated {
+            name @output(name: "first")
+        }
+    }
+}"#;
+        assert_eq!(
+            vec!["unannotated", "releases", "unannotated"],
+            planned_edge_names(query)
+        );
+    }
+
+    #[test]
+    fn execution_is_planned_as_a_flat_sequence_of_steps() {
+        let schema =
+            Schema::parse(fs::read_to_string("test_data/schemas/numbers.graphql").unwrap())
+                .unwrap();
+        let query = r#"
+{
+    Number(max: 10) {
+        ... on Prime {
+            value @output @filter(op: ">", value: ["$min"])
+
+            multiple(max: 3) @fold @transform(op: "count") @filter(op: ">", value: ["$count"]) {
+                name @output @filter(op: "is_not_null")
+
+                successor {
+                    successor: value @output
+                }
+            }
+        }
+    }
+}"#;
+        let indexed_query = parse(&schema, query).unwrap();
+        let steps: Vec<String> = plan_execution(&indexed_query, None)
+            .into_iter()
+            .map(|step| match step {
+                Step::Coerce(vid) => format!("coerce {}", vid.0),
+                Step::Filter(vid, index) => format!("filter {} #{index}", vid.0),
+                Step::Record(vid) => format!("record {}", vid.0),
+                Step::ExpandEdge(edge) => format!("expand {}", edge.edge_name),
+                Step::BeginFold { fold, end, .. } => {
+                    format!("begin {} until {end}", fold.edge_name)
+                }
+                Step::EndFold(fold) => format!("end {}", fold.edge_name),
+                Step::Output(name) => format!("output {name}"),
+            })
+            .collect();
+
+        assert_eq!(
+            vec![
+                "coerce 1",
+                "filter 1 #0",
+                "record 1",
+                "begin multiple until 8",
+                "filter 2 #0",
+                "record 2",
+                "expand successor",
+                "record 3",
+                "end multiple",
+                "output value",
+            ],
+            steps,
+        );
+    }
+}
@@ -0,0 +1,153 @@
+//! Reusing buffers across the rows of one execution of a query.
+//!
+//! Some steps of execution need a buffer for every row passing through them, such as
+//! the elements of a fold whose outputs are being computed. Rather than allocating each one,
+//! they take it from a [`Pool`] that earlier rows returned theirs to, so execution stops
+//! allocating these buffers once it reaches a steady state.
+//!
+//! Each execution makes its own pools, so buffers are never shared between executions.
+//! Pools are locked rather than only usable from one thread so that the iterators of
+//! an execution can be moved to another thread, but the locks are never contended
+//! since an execution's iterators are only advanced by one thread at a time.
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+/// How many buffers a pool keeps for reuse. Rows that return a buffer to a full pool free it.
+const MAX_POOLED_BUFFERS: usize = 16;
+
+/// Empty buffers returned by earlier rows, for reuse by later ones.
+///
+/// Clones share the same buffers.
+pub(super) struct Pool<T> {
+    buffers: Arc<Mutex<Vec<Vec<T>>>>,
+}
+
+impl<T> Pool<T> {
+    pub(super) fn new() -> Self {
+        Self {
+            buffers: Default::default(),
+        }
+    }
+
+    /// An empty buffer, reusing the allocation of one returned to the pool if there is one.
+    pub(super) fn take(&self) -> Vec<T> {
+        self.buffers
+            .lock()
+            .expect("pool lock was poisoned")
+            .pop()
+            .unwrap_or_default()
+    }
+
+    /// Return the buffer to the pool, dropping its values.
+    pub(super) fn put(&self, mut buffer: Vec<T>) {
+        buffer.clear();
+        if buffer.capacity() == 0 {
+            return;
+        }
+
+        let mut buffers = self.buffers.lock().expect("pool lock was poisoned");
+        if buffers.len() < MAX_POOLED_BUFFERS {
+            buffers.push(buffer);
+        }
+    }
+
+    /// Iterate over the buffer's values in order, returning the buffer to the pool
+    /// once the iterator is dropped.
+    pub(super) fn drain(&self, mut buffer: Vec<T>) -> PooledIter<T> {
+        // Values are popped off the end of the buffer, so reverse it to produce them in order.
+        buffer.reverse();
+        PooledIter {
+            buffer,
+            pool: self.clone(),
+        }
+    }
+}
+
+impl<T> Clone for Pool<T> {
+    fn clone(&self) -> Self {
+        Self {
+            buffers: self.buffers.clone(),
+        }
+    }
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Debug for Pool<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let buffers = self.buffers.lock().expect("pool lock was poisoned").len();
+        f.debug_struct("Pool").field("buffers", &buffers).finish()
+    }
+}
+
+/// An iterator over the values of a buffer from a [`Pool`], see [`Pool::drain`].
+pub(super) struct PooledIter<T> {
+    buffer: Vec<T>,
+    pool: Pool<T>,
+}
+
+impl<T> Iterator for PooledIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buffer.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.buffer.len(), Some(self.buffer.len()))
+    }
+}
+
+impl<T> Drop for PooledIter<T> {
+    fn drop(&mut self) {
+        self.pool.put(std::mem::take(&mut self.buffer));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pool, MAX_POOLED_BUFFERS};
+
+    #[test]
+    fn buffers_are_reused() {
+        let pool = Pool::new();
+        let mut buffer = pool.take();
+        buffer.extend([1, 2, 3]);
+        let allocation = buffer.as_ptr();
+
+        assert_eq!(vec![1, 2, 3], pool.drain(buffer).collect::<Vec<_>>());
+        let buffer = pool.take();
+        assert!(buffer.is_empty());
+        assert_eq!(allocation, buffer.as_ptr());
+
+        // Iterators return their buffers even if they're dropped before they're exhausted.
+        let mut iter = pool.drain(buffer);
+        assert_eq!(None, iter.next());
+        drop(iter);
+        let buffer = pool.clone().take();
+        assert_eq!(allocation, buffer.as_ptr());
+    }
+
+    #[test]
+    fn pools_keep_a_limited_number_of_buffers() {
+        let pool = Pool::new();
+        for _ in 0..=MAX_POOLED_BUFFERS {
+            pool.put(vec![0u8; 4]);
+        }
+        // Buffers without an allocation aren't worth keeping.
+        pool.put(vec![]);
+
+        let buffers: Vec<_> = (0..=MAX_POOLED_BUFFERS).map(|_| pool.take()).collect();
+        let reused = buffers
+            .iter()
+            .filter(|buffer| buffer.capacity() > 0)
+            .count();
+        assert_eq!(MAX_POOLED_BUFFERS, reused);
+    }
+}
@@ -0,0 +1,306 @@
+//! Registering queries ahead of time, and executing them later by identifier.
+//!
+//! Services that execute queries on behalf of untrusted clients can use a [`QueryRegistry`]
+//! to restrict them to a fixed set of vetted queries: clients send only the identifier of
+//! a registered query together with its arguments, and never the query text itself.
+//!
+//! Each registered query is identified by its [`QueryHash`], so registering equivalent queries
+//! more than once has no effect. Queries may additionally be given names.
+//! Every query is validated against the registry's schema when it's registered,
+//! including queries that were compiled or stored elsewhere, possibly against
+//! a different version of the schema.
+use std::{collections::BTreeMap, fmt::Display, sync::Arc};
+
+use crate::{
+    frontend,
+    ir::{canonical::QueryHash, FieldValue, IRQuery, IndexedQuery},
+    schema::Schema,
+};
+
+use super::{error::RegistryError, execution::interpret_ir, Adapter};
+
+/// Identifies a query in a [`QueryRegistry`], either by its hash or by a name given to it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum QueryId {
+    Hash(QueryHash),
+    Name(Arc<str>),
+}
+
+impl From<QueryHash> for QueryId {
+    fn from(hash: QueryHash) -> Self {
+        Self::Hash(hash)
+    }
+}
+
+impl From<&str> for QueryId {
+    fn from(name: &str) -> Self {
+        Self::Name(Arc::from(name))
+    }
+}
+
+impl From<Arc<str>> for QueryId {
+    fn from(name: Arc<str>) -> Self {
+        Self::Name(name)
+    }
+}
+
+impl Display for QueryId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryId::Hash(hash) => write!(f, "{hash}"),
+            QueryId::Name(name) => f.write_str(name),
+        }
+    }
+}
+
+/// A set of queries validated against a schema, which can be executed by [`QueryId`].
+#[derive(Debug, Clone)]
+pub struct QueryRegistry {
+    schema: Schema,
+    queries: BTreeMap<QueryHash, Arc<IndexedQuery>>,
+    names: BTreeMap<Arc<str>, QueryHash>,
+}
+
+impl QueryRegistry {
+    pub fn new(schema: Schema) -> Self {
+        Self {
+            schema,
+            queries: Default::default(),
+            names: Default::default(),
+        }
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Compile the query against the registry's schema and register it.
+    pub fn register(&mut self, query: &str) -> Result<QueryHash, RegistryError> {
+        let indexed_query = frontend::parse(&self.schema, query)?;
+        Ok(self.insert(indexed_query))
+    }
+
+    /// Register a query that was compiled elsewhere, after checking that it's still valid
+    /// against the registry's schema.
+    pub fn register_ir(&mut self, query: IRQuery) -> Result<QueryHash, RegistryError> {
+        self.schema.check_query_compatibility(&query)?;
+        let indexed_query = IndexedQuery::try_from(query)?;
+        Ok(self.insert(Arc::new(
+            indexed_query.with_custom_scalars(self.schema.custom_scalars.clone()),
+        )))
+    }
+
+    /// Register a query stored in the JSON format of [`IndexedQuery::to_json`],
+    /// after checking that it's still valid against the registry's schema.
+    pub fn register_json(&mut self, query: &str) -> Result<QueryHash, RegistryError> {
+        let indexed_query = IndexedQuery::from_json(&self.schema, query)?;
+        Ok(self.insert(indexed_query))
+    }
+
+    /// Give a name to a registered query, so it can also be executed by that name.
+    ///
+    /// Names are unique: naming a different query with a name that's already in use
+    /// is an error. A query may have any number of names.
+    pub fn set_name(&mut self, name: &str, hash: QueryHash) -> Result<(), RegistryError> {
+        if !self.queries.contains_key(&hash) {
+            return Err(RegistryError::UnregisteredQuery(hash.to_string()));
+        }
+        match self.names.get(name) {
+            Some(existing) if *existing != hash => Err(RegistryError::DuplicatedQueryName(
+                name.to_string(),
+                *existing,
+            )),
+            Some(_) => Ok(()),
+            None => {
+                self.names.insert(Arc::from(name), hash);
+                Ok(())
+            }
+        }
+    }
+
+    /// Compile the query against the registry's schema, register it, and give it a name.
+    pub fn register_named(&mut self, name: &str, query: &str) -> Result<QueryHash, RegistryError> {
+        let indexed_query = frontend::parse(&self.schema, query)?;
+        let hash = indexed_query.content_hash();
+        if let Some(existing) = self.names.get(name).filter(|existing| **existing != hash) {
+            return Err(RegistryError::DuplicatedQueryName(
+                name.to_string(),
+                *existing,
+            ));
+        }
+
+        self.insert(indexed_query);
+        self.set_name(name, hash)?;
+        Ok(hash)
+    }
+
+    /// Look up a registered query.
+    pub fn get(&self, id: impl Into<QueryId>) -> Option<&Arc<IndexedQuery>> {
+        let hash = match id.into() {
+            QueryId::Hash(hash) => hash,
+            QueryId::Name(name) => *self.names.get(&name)?,
+        };
+        self.queries.get(&hash)
+    }
+
+    /// Iterate over the registered queries, in order of their hashes.
+    pub fn iter(&self) -> impl Iterator<Item = (QueryHash, &'_ Arc<IndexedQuery>)> + '_ {
+        self.queries.iter().map(|(hash, query)| (*hash, query))
+    }
+
+    /// Iterate over the names given to registered queries, in order of name.
+    pub fn names(&self) -> impl Iterator<Item = (&'_ Arc<str>, QueryHash)> + '_ {
+        self.names.iter().map(|(name, hash)| (name, *hash))
+    }
+
+    /// Execute a registered query with the given arguments.
+    #[allow(clippy::type_complexity)]
+    pub fn execute<'query, AdapterT: Adapter<'query> + 'query>(
+        &self,
+        adapter: Arc<AdapterT>,
+        id: impl Into<QueryId>,
+        arguments: Arc<BTreeMap<Arc<str>, FieldValue>>,
+    ) -> Result<Box<dyn Iterator<Item = BTreeMap<Arc<str>, FieldValue>> + 'query>, RegistryError>
+    {
+        let id = id.into();
+        let Some(indexed_query) = self.get(id.clone()) else {
+            return Err(RegistryError::UnregisteredQuery(id.to_string()));
+        };
+        Ok(interpret_ir(adapter, indexed_query.clone(), arguments)?)
+    }
+
+    fn insert(&mut self, indexed_query: Arc<IndexedQuery>) -> QueryHash {
+        let hash = indexed_query.content_hash();
+        self.queries.entry(hash).or_insert(indexed_query);
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, fs, sync::Arc};
+
+    use crate::{
+        frontend,
+        interpreter::error::{QueryArgumentsError, RegistryError},
+        ir::FieldValue,
+        numbers_interpreter::NumbersAdapter,
+        schema::{error::IncompatibleQueryError, Schema},
+    };
+
+    use super::QueryRegistry;
+
+    const QUERY: &str = r#"
+{
+    Number(max: 10) {
+        value @output @filter(op: ">", value: ["$min"])
+    }
+}"#;
+
+    fn numbers_schema() -> Schema {
+        Schema::parse(fs::read_to_string("test_data/schemas/numbers.graphql").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn registered_queries_execute_by_hash_or_name() {
+        let mut registry = QueryRegistry::new(numbers_schema());
+        let hash = registry.register_named("large_numbers", QUERY).unwrap();
+        assert_eq!(hash, registry.register(QUERY).unwrap());
+        assert_eq!(1, registry.iter().count());
+
+        let adapter = Arc::new(NumbersAdapter::new());
+        let arguments = Arc::new(btreemap! { Arc::from("min") => FieldValue::Int64(8) });
+        let expected: Vec<_> = [9, 10]
+            .into_iter()
+            .map(|value| btreemap! { Arc::from("value") => FieldValue::Int64(value) })
+            .collect();
+        for results in [
+            registry.execute(adapter.clone(), hash, arguments.clone()),
+            registry.execute(adapter.clone(), "large_numbers", arguments.clone()),
+        ] {
+            assert_eq!(expected, results.unwrap().collect::<Vec<_>>());
+        }
+
+        assert_eq!(
+            RegistryError::UnregisteredQuery("small_numbers".to_string()),
+            registry
+                .execute(adapter.clone(), "small_numbers", arguments)
+                .err()
+                .unwrap()
+        );
+        assert_eq!(
+            RegistryError::InvalidArguments(QueryArgumentsError::MissingArguments(vec![
+                "min".to_string()
+            ])),
+            registry
+                .execute(adapter, hash, Arc::new(BTreeMap::new()))
+                .err()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn names_are_unique() {
+        let mut registry = QueryRegistry::new(numbers_schema());
+        let hash = registry.register_named("numbers", QUERY).unwrap();
+        let other_query = QUERY.replace("max: 10", "max: 20");
+        assert_eq!(
+            RegistryError::DuplicatedQueryName("numbers".to_string(), hash),
+            registry
+                .register_named("numbers", &other_query)
+                .unwrap_err()
+        );
+
+        let other_hash = registry.register(&other_query).unwrap();
+        assert_eq!(
+            RegistryError::DuplicatedQueryName("numbers".to_string(), hash),
+            registry.set_name("numbers", other_hash).unwrap_err()
+        );
+        registry.set_name("more_numbers", other_hash).unwrap();
+        assert_eq!(
+            other_hash,
+            registry.get("more_numbers").unwrap().content_hash()
+        );
+    }
+
+    #[test]
+    fn compiled_queries_are_revalidated_on_registration() {
+        let schema = numbers_schema();
+        let query = r#"
+{
+    Number(max: 10) {
+        vowelsInName @output
+    }
+}"#;
+        let ir_query = frontend::parse_to_ir(&schema, query).unwrap();
+        let stored = frontend::parse(&schema, query).unwrap().to_json(&schema);
+
+        let narrowed_schema = Schema::parse(
+            fs::read_to_string("test_data/schemas/numbers.graphql")
+                .unwrap()
+                .replace("    vowelsInName: [String]\n", ""),
+        )
+        .unwrap();
+        let mut registry = QueryRegistry::new(narrowed_schema);
+        let expected_error = IncompatibleQueryError::NonExistentProperty(
+            "Number".to_string(),
+            "vowelsInName".to_string(),
+        );
+        assert_eq!(
+            RegistryError::IncompatibleQuery(expected_error.clone()),
+            registry.register_ir(ir_query.clone()).unwrap_err()
+        );
+        assert_eq!(
+            RegistryError::InvalidStoredQuery(expected_error.into()),
+            registry.register_json(&stored).unwrap_err()
+        );
+        assert!(matches!(
+            registry.register(query).unwrap_err(),
+            RegistryError::InvalidQuery(..)
+        ));
+
+        let mut registry = QueryRegistry::new(schema);
+        let hash = registry.register_ir(ir_query).unwrap();
+        assert_eq!(hash, registry.register_json(&stored).unwrap());
+    }
+}
@@ -0,0 +1,224 @@
+//! Executing queries against a renamed schema, using an adapter for the original schema.
+use std::{marker::PhantomData, sync::Arc};
+
+use crate::{
+    ir::{EdgeParameters, FieldValue, TYPENAME_META_FIELD},
+    schema::SchemaRenaming,
+};
+
+use super::{
+    Adapter, ContextIterator, ContextOutcomeIterator, ResolveEdgeInfo, ResolveInfo, VertexIterator,
+};
+
+/// An adapter for a schema renamed with [`Schema::rename`](crate::schema::Schema::rename),
+/// that delegates to an adapter for the original schema.
+///
+/// Type and root edge names are translated back to their original names before being passed
+/// to the original adapter, and `__typename` values it produces are translated to the renamed
+/// type names. The query information available through `resolve_info` is not translated,
+/// and refers to types by their renamed names.
+#[derive(Debug, Clone)]
+pub struct RenamingAdapter<'vertex, AdapterT: Adapter<'vertex>> {
+    inner: AdapterT,
+    renaming: Arc<SchemaRenaming>,
+    _phantom: PhantomData<&'vertex ()>,
+}
+
+impl<'vertex, AdapterT: Adapter<'vertex>> RenamingAdapter<'vertex, AdapterT> {
+    pub fn new(adapter: AdapterT, renaming: Arc<SchemaRenaming>) -> Self {
+        Self {
+            inner: adapter,
+            renaming,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn renaming(&self) -> &Arc<SchemaRenaming> {
+        &self.renaming
+    }
+
+    pub fn into_inner(self) -> AdapterT {
+        self.inner
+    }
+
+    fn original_type(&self, type_name: &Arc<str>) -> Arc<str> {
+        let original = self.renaming.original_type(type_name);
+        if original == type_name.as_ref() {
+            type_name.clone()
+        } else {
+            original.into()
+        }
+    }
+}
+
+impl<'vertex, AdapterT: Adapter<'vertex>> Adapter<'vertex> for RenamingAdapter<'vertex, AdapterT> {
+    type Vertex = AdapterT::Vertex;
+
+    fn resolve_starting_vertices(
+        &self,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        resolve_info: &ResolveInfo,
+    ) -> VertexIterator<'vertex, Self::Vertex> {
+        let edge_name: Arc<str> = self.renaming.original_root_edge(edge_name).into();
+        self.inner
+            .resolve_starting_vertices(&edge_name, parameters, resolve_info)
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        type_name: &Arc<str>,
+        property_name: &Arc<str>,
+        resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, FieldValue> {
+        let original_type = self.original_type(type_name);
+        let values =
+            self.inner
+                .resolve_property(contexts, &original_type, property_name, resolve_info);
+
+        if property_name.as_ref() != TYPENAME_META_FIELD {
+            return values;
+        }
+
+        let renaming = self.renaming.clone();
+        Box::new(values.map(move |(context, value)| {
+            let value = match value {
+                FieldValue::String(name) => renaming.renamed_type(&name).into(),
+                other => other,
+            };
+            (context, value)
+        }))
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        type_name: &Arc<str>,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        resolve_info: &ResolveEdgeInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, VertexIterator<'vertex, Self::Vertex>> {
+        let original_type = self.original_type(type_name);
+        self.inner.resolve_neighbors(
+            contexts,
+            &original_type,
+            edge_name,
+            parameters,
+            resolve_info,
+        )
+    }
+
+    fn resolve_coercion(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        type_name: &Arc<str>,
+        coerce_to_type: &Arc<str>,
+        resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, bool> {
+        let original_type = self.original_type(type_name);
+        let original_coerce_to_type = self.original_type(coerce_to_type);
+        self.inner.resolve_coercion(
+            contexts,
+            &original_type,
+            &original_coerce_to_type,
+            resolve_info,
+        )
+    }
+
+    fn vertex_identity(&self, vertex: &Self::Vertex) -> Option<u64> {
+        self.inner.vertex_identity(vertex)
+    }
+
+    fn skip_revisited_vertices_in_recursion(&self) -> bool {
+        self.inner.skip_revisited_vertices_in_recursion()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use crate::{
+        frontend::parse,
+        interpreter::{execution::interpret_ir, Adapter},
+        ir::FieldValue,
+        numbers_interpreter::NumbersAdapter,
+        schema::{Schema, SchemaRenaming},
+    };
+
+    use super::RenamingAdapter;
+
+    fn run<'a>(
+        schema: &Schema,
+        adapter: Arc<impl Adapter<'a> + 'a>,
+        query: &str,
+    ) -> Vec<BTreeMap<Arc<str>, FieldValue>> {
+        let indexed_query = parse(schema, query).unwrap();
+        interpret_ir(adapter, indexed_query, Default::default())
+            .unwrap()
+            .collect()
+    }
+
+    #[test]
+    fn renamed_query_matches_original_query() {
+        let schema =
+            Schema::parse(include_str!("../../test_data/schemas/numbers.graphql")).unwrap();
+        let renaming = Arc::new(SchemaRenaming::with_prefix(&schema, "Num_"));
+        let renamed_schema = schema.rename(&renaming).unwrap();
+
+        let original_results = run(
+            &schema,
+            Arc::new(NumbersAdapter::new()),
+            r#"
+{
+    Number(max: 10) {
+        ... on Prime {
+            __typename @output
+            value @output
+            successor {
+                value @output(name: "next")
+                __typename @output(name: "kind")
+            }
+        }
+    }
+}"#,
+        );
+        let renamed_results = run(
+            &renamed_schema,
+            Arc::new(RenamingAdapter::new(
+                NumbersAdapter::new(),
+                renaming.clone(),
+            )),
+            r#"
+{
+    Num_Number(max: 10) {
+        ... on Num_Prime {
+            __typename @output
+            value @output
+            successor {
+                value @output(name: "next")
+                __typename @output(name: "kind")
+            }
+        }
+    }
+}"#,
+        );
+
+        assert!(!original_results.is_empty());
+        let prefixed_original_results: Vec<_> = original_results
+            .into_iter()
+            .map(|mut row| {
+                for key in ["__typename", "kind"] {
+                    let FieldValue::String(name) = &row[key] else {
+                        unreachable!()
+                    };
+                    let prefixed = format!("Num_{name}");
+                    row.insert(key.into(), prefixed.into());
+                }
+                row
+            })
+            .collect();
+        assert_eq!(prefixed_original_results, renamed_results);
+    }
+}
@@ -0,0 +1,118 @@
+use std::{collections::BTreeMap, ops::Index, sync::Arc};
+
+use crate::ir::FieldValue;
+
+/// One result of a query: the value of each of its outputs.
+///
+/// A query's outputs are known before it's executed, so rather than a map per result,
+/// each row holds its values in a fixed layout: the `i`-th value is that of the `i`-th output
+/// in order of name. The names of the outputs are allocated once per execution and shared by
+/// all its rows, making a row a single allocation no matter how many outputs the query has.
+///
+/// Rows convert into the `BTreeMap` form of results with [`Row::into_map`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    names: Arc<[Arc<str>]>,
+    values: Vec<FieldValue>,
+}
+
+impl Row {
+    /// The row with the given values for the named outputs.
+    ///
+    /// # Panics
+    ///
+    /// If there isn't a value for each name. In debug builds, also if the names aren't sorted
+    /// without duplicates.
+    pub fn new(names: Arc<[Arc<str>]>, values: Vec<FieldValue>) -> Self {
+        debug_assert!(
+            names.windows(2).all(|pair| pair[0] < pair[1]),
+            "output names {names:?} are not sorted without duplicates"
+        );
+        assert_eq!(
+            names.len(),
+            values.len(),
+            "expected a value for each of the outputs {names:?} but got {values:?}"
+        );
+        Self { names, values }
+    }
+
+    /// The names of the outputs in order, shared by the rows of the same execution.
+    pub fn names(&self) -> &Arc<[Arc<str>]> {
+        &self.names
+    }
+
+    /// The values of the outputs, in the order of [`Row::names`].
+    pub fn values(&self) -> &[FieldValue] {
+        &self.values
+    }
+
+    pub fn into_values(self) -> Vec<FieldValue> {
+        self.values
+    }
+
+    /// The value of the named output, or `None` if the query has no such output.
+    pub fn get(&self, name: &str) -> Option<&FieldValue> {
+        self.position(name).map(|index| &self.values[index])
+    }
+
+    /// The position of the named output in the row, or `None` if the query has no such output.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.names.binary_search_by(|x| x.as_ref().cmp(name)).ok()
+    }
+
+    /// The names and values of the outputs, in order of name.
+    pub fn iter(&self) -> impl Iterator<Item = (&Arc<str>, &FieldValue)> + '_ {
+        self.names.iter().zip(self.values.iter())
+    }
+
+    pub fn into_map(self) -> BTreeMap<Arc<str>, FieldValue> {
+        self.names.iter().cloned().zip(self.values).collect()
+    }
+}
+
+impl Index<&str> for Row {
+    type Output = FieldValue;
+
+    fn index(&self, name: &str) -> &Self::Output {
+        self.get(name)
+            .unwrap_or_else(|| panic!("no output named \"{name}\" in {:?}", self.names))
+    }
+}
+
+impl From<Row> for BTreeMap<Arc<str>, FieldValue> {
+    fn from(value: Row) -> Self {
+        value.into_map()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use crate::ir::FieldValue;
+
+    use super::Row;
+
+    #[test]
+    fn rows_share_their_names() {
+        let names: Arc<[Arc<str>]> = vec!["name".into(), "value".into()].into();
+        let first = Row::new(names.clone(), vec!["two".into(), 2.into()]);
+        let second = Row::new(names.clone(), vec!["three".into(), 3.into()]);
+        assert!(Arc::ptr_eq(first.names(), second.names()));
+
+        assert_eq!(Some(1), second.position("value"));
+        assert_eq!(&FieldValue::Int64(3), &second["value"]);
+        assert_eq!(None, second.get("missing"));
+        assert_eq!(
+            vec![("name", "two".into()), ("value", 2.into())],
+            first
+                .iter()
+                .map(|(name, value)| (name.as_ref(), value.clone()))
+                .collect::<Vec<(_, FieldValue)>>(),
+        );
+        assert_eq!(
+            BTreeMap::from([("name".into(), "three".into()), ("value".into(), 3.into())]),
+            second.into_map(),
+        );
+    }
+}
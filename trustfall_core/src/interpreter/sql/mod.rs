@@ -0,0 +1,624 @@
+//! Executing queries against relational databases, by translating parts of them to SQL.
+//!
+//! Adapters over databases describe how their schema's types are stored in tables with a
+//! [`RelationalMapping`], and implement [`RelationalAdapter`] to execute [`Select`] statements.
+//! Wrapping them in a [`SqlAdapter`] then gives an [`Adapter`] that:
+//! - loads starting vertices with one statement, with the query's filters on their
+//!   properties and the starting edge's parameters translated to `WHERE` conditions;
+//! - loads the neighbors of many vertices at once across edges that join two tables,
+//!   with one statement per batch of vertices, translating the filters on the neighbors too;
+//! - reads properties from the selected columns.
+//!
+//! Everything the mapping doesn't cover, like properties that aren't columns or edges that
+//! aren't joins, is resolved by the [`RelationalAdapter`] itself, one vertex at a time.
+//!
+//! Filters are translated using the same information adapters get from
+//! [`VertexInfo`](super::VertexInfo), and are still applied by Trustfall to the vertices
+//! the database returns. Conditions on values that can't be compared in SQL, like lists,
+//! aren't translated at all. Translated conditions assume that the database compares values
+//! the same way Trustfall does; for example, string comparisons must not ignore case.
+//!
+//! The same mapping can also lower whole queries to plans for other engines to execute,
+//! with the [`substrait`] module.
+//!
+//! ```rust
+//! use trustfall_core::interpreter::sql::{Join, RelationalMapping, StartingEdge, Table};
+//!
+//! let mapping = RelationalMapping::new()
+//!     .with_table(
+//!         "User",
+//!         Table::new("users")
+//!             .with_column("id", "id")
+//!             .with_column("name", "display_name")
+//!             .with_join("post", Join::new("Post", "id", "author_id")),
+//!     )
+//!     .with_table(
+//!         "Post",
+//!         Table::new("posts")
+//!             .with_column("title", "title")
+//!             .with_join("author", Join::new("User", "author_id", "id")),
+//!     )
+//!     .with_starting_edge(
+//!         "User",
+//!         StartingEdge::new("User")
+//!             .with_parameter_column("id", "id")
+//!             .with_limit_parameter("limit"),
+//!     );
+//! ```
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fmt::Display,
+    sync::Arc,
+};
+
+use crate::ir::{EdgeParameters, FieldValue, TYPENAME_META_FIELD};
+
+use super::{
+    helpers::{resolve_coercion_with, resolve_neighbors_with, resolve_property_with},
+    Adapter, ContextIterator, ContextOutcomeIterator, DataContext, ResolveEdgeInfo, ResolveInfo,
+    VertexInfo, VertexIterator,
+};
+
+mod select;
+pub mod substrait;
+
+#[cfg(test)]
+mod tests;
+
+pub use select::{Condition, Operator, ParameterStyle, Select};
+
+use select::{candidate_constraint, equals_condition, Constraint};
+
+/// How the vertex types of a schema are stored in the tables of a relational database.
+#[derive(Debug, Clone, Default)]
+pub struct RelationalMapping {
+    tables: BTreeMap<Arc<str>, Table>,
+    starting_edges: BTreeMap<Arc<str>, StartingEdge>,
+}
+
+impl RelationalMapping {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Store vertices of the given type as rows of the table.
+    pub fn with_table(mut self, type_name: impl Into<Arc<str>>, table: Table) -> Self {
+        self.tables.insert(type_name.into(), table);
+        self
+    }
+
+    /// Load the vertices of a starting edge from the table of its vertex type.
+    pub fn with_starting_edge(
+        mut self,
+        edge_name: impl Into<Arc<str>>,
+        edge: StartingEdge,
+    ) -> Self {
+        self.starting_edges.insert(edge_name.into(), edge);
+        self
+    }
+
+    pub fn table(&self, type_name: &str) -> Option<&Table> {
+        self.tables.get(type_name)
+    }
+
+    pub fn starting_edge(&self, edge_name: &str) -> Option<&StartingEdge> {
+        self.starting_edges.get(edge_name)
+    }
+
+    fn expect_table(&self, type_name: &str) -> &Table {
+        self.table(type_name).unwrap_or_else(|| {
+            panic!("type {type_name} is used in the relational mapping but has no table")
+        })
+    }
+}
+
+/// A table holding the vertices of one type: one row per vertex.
+#[derive(Debug, Clone)]
+pub struct Table {
+    name: String,
+    columns: BTreeMap<Arc<str>, String>,
+    joins: BTreeMap<Arc<str>, Join>,
+}
+
+impl Table {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            columns: Default::default(),
+            joins: Default::default(),
+        }
+    }
+
+    /// Read the values of the given property from the column.
+    pub fn with_column(
+        mut self,
+        property_name: impl Into<Arc<str>>,
+        column: impl Into<String>,
+    ) -> Self {
+        self.columns.insert(property_name.into(), column.into());
+        self
+    }
+
+    /// Resolve the given edge by joining this table with the table of another type.
+    ///
+    /// Uses of the edge with parameters are resolved by the [`RelationalAdapter`] instead.
+    pub fn with_join(mut self, edge_name: impl Into<Arc<str>>, join: Join) -> Self {
+        self.joins.insert(edge_name.into(), join);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The column holding the values of the given property, if any.
+    pub fn column(&self, property_name: &str) -> Option<&str> {
+        self.columns.get(property_name).map(String::as_str)
+    }
+
+    pub fn join(&self, edge_name: &str) -> Option<&Join> {
+        self.joins.get(edge_name)
+    }
+
+    /// The columns selected from this table: the columns of its properties, and the columns
+    /// its joins start from, so that the joins can be resolved without further statements.
+    fn selected_columns(&self) -> Vec<String> {
+        let mut columns: Vec<_> = self
+            .columns
+            .values()
+            .chain(self.joins.values().map(|join| &join.from_column))
+            .cloned()
+            .collect();
+        columns.sort_unstable();
+        columns.dedup();
+        columns
+    }
+}
+
+/// An edge whose neighbors are the rows of another type's table whose `to_column` value equals
+/// the `from_column` value of the edge's starting vertex.
+///
+/// The values of both columns must be integers, strings, or booleans.
+#[derive(Debug, Clone)]
+pub struct Join {
+    to_type: Arc<str>,
+    from_column: String,
+    to_column: String,
+}
+
+impl Join {
+    pub fn new(
+        to_type: impl Into<Arc<str>>,
+        from_column: impl Into<String>,
+        to_column: impl Into<String>,
+    ) -> Self {
+        Self {
+            to_type: to_type.into(),
+            from_column: from_column.into(),
+            to_column: to_column.into(),
+        }
+    }
+}
+
+/// A starting edge whose vertices are rows of a table.
+#[derive(Debug, Clone)]
+pub struct StartingEdge {
+    type_name: Arc<str>,
+    parameter_columns: BTreeMap<Arc<str>, String>,
+    limit_parameter: Option<Arc<str>>,
+}
+
+impl StartingEdge {
+    /// The edge produces vertices of the given type, and all rows of its table by default.
+    pub fn new(type_name: impl Into<Arc<str>>) -> Self {
+        Self {
+            type_name: type_name.into(),
+            parameter_columns: Default::default(),
+            limit_parameter: None,
+        }
+    }
+
+    /// Only produce the rows whose value in the column equals the value of the edge parameter,
+    /// when it has a non-null value.
+    pub fn with_parameter_column(
+        mut self,
+        parameter_name: impl Into<Arc<str>>,
+        column: impl Into<String>,
+    ) -> Self {
+        self.parameter_columns
+            .insert(parameter_name.into(), column.into());
+        self
+    }
+
+    /// Produce at most as many rows as the value of the edge parameter, when it has a value.
+    ///
+    /// Limits must be applied before filtering for the query to see the rows the edge
+    /// produces, so filters aren't translated to conditions when the limit has a value.
+    pub fn with_limit_parameter(mut self, parameter_name: impl Into<Arc<str>>) -> Self {
+        self.limit_parameter = Some(parameter_name.into());
+        self
+    }
+}
+
+/// A vertex loaded from a table: the type of the vertex, and its row's selected values.
+#[derive(Debug, Clone)]
+pub struct SqlVertex {
+    type_name: Arc<str>,
+    values: Arc<BTreeMap<String, FieldValue>>,
+}
+
+impl SqlVertex {
+    pub fn new(type_name: impl Into<Arc<str>>, values: BTreeMap<String, FieldValue>) -> Self {
+        Self {
+            type_name: type_name.into(),
+            values: Arc::new(values),
+        }
+    }
+
+    pub fn type_name(&self) -> &Arc<str> {
+        &self.type_name
+    }
+
+    /// The value of the given column in the vertex's row, if the column was selected.
+    pub fn get(&self, column: &str) -> Option<&FieldValue> {
+        self.values.get(column)
+    }
+
+    fn from_row(type_name: &Arc<str>, columns: &[String], row: Vec<FieldValue>) -> Self {
+        assert_eq!(
+            columns.len(),
+            row.len(),
+            "a row of {type_name} has {} values, but {} columns were selected",
+            row.len(),
+            columns.len(),
+        );
+        Self {
+            type_name: type_name.clone(),
+            values: Arc::new(columns.iter().cloned().zip(row).collect()),
+        }
+    }
+}
+
+/// A data source backed by a relational database, which [`SqlAdapter`] can query with SQL.
+pub trait RelationalAdapter {
+    type Error: Display;
+
+    /// How the schema's types are stored in the database.
+    fn mapping(&self) -> &RelationalMapping;
+
+    /// Execute the statement, returning the values of its selected columns in each row.
+    fn execute(&self, select: &Select) -> Result<Vec<Vec<FieldValue>>, Self::Error>;
+
+    /// Resolve a starting edge that isn't in the mapping.
+    fn resolve_unmapped_starting_vertices(
+        &self,
+        edge_name: &str,
+        parameters: &EdgeParameters,
+    ) -> Vec<SqlVertex>;
+
+    /// Resolve a property that isn't a column of the vertex's table.
+    fn resolve_unmapped_property(&self, vertex: &SqlVertex, property_name: &str) -> FieldValue;
+
+    /// Resolve an edge that isn't a join of the vertex's table, or that has parameters.
+    fn resolve_unmapped_neighbors(
+        &self,
+        vertex: &SqlVertex,
+        edge_name: &str,
+        parameters: &EdgeParameters,
+    ) -> Vec<SqlVertex>;
+
+    /// Whether the vertex is of the given type. By default, this is the case only if
+    /// it's the vertex's own type; adapters over schemas with interfaces should override this.
+    fn is_of_type(&self, vertex: &SqlVertex, type_name: &str) -> bool {
+        vertex.type_name().as_ref() == type_name
+    }
+}
+
+/// An [`Adapter`] that translates the parts of queries covered by a [`RelationalMapping`]
+/// to SQL statements executed by a [`RelationalAdapter`].
+#[derive(Debug)]
+pub struct SqlAdapter<A> {
+    inner: Arc<A>,
+    batch_size: usize,
+}
+
+impl<A> Clone for SqlAdapter<A> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            batch_size: self.batch_size,
+        }
+    }
+}
+
+impl<A: RelationalAdapter> SqlAdapter<A> {
+    pub fn new(adapter: A) -> Self {
+        Self {
+            inner: Arc::new(adapter),
+            batch_size: 100,
+        }
+    }
+
+    /// The maximum number of vertices whose neighbors are loaded with one statement.
+    /// Defaults to 100.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the batch size is zero.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "batch size must be positive");
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+}
+
+/// The conditions on a table's rows that are equivalent to the filters on the properties
+/// of the vertex, or `None` if no row satisfies them.
+fn property_conditions(table: &Table, vertex_info: &impl VertexInfo) -> Option<Vec<Condition>> {
+    let mut conditions = vec![];
+    for (property, column) in &table.columns {
+        if let Some(candidate) = vertex_info.statically_required_property(property) {
+            match candidate_constraint(column, candidate) {
+                Constraint::Unconstrained => {}
+                Constraint::Impossible => return None,
+                Constraint::Condition(condition) => conditions.push(condition),
+            }
+        }
+    }
+    Some(conditions)
+}
+
+fn execute<A: RelationalAdapter>(adapter: &A, select: &Select) -> Vec<Vec<FieldValue>> {
+    adapter.execute(select).unwrap_or_else(|e| {
+        let (sql, _) = select.to_sql(ParameterStyle::default());
+        panic!("failed to execute SQL statement {sql}: {e}")
+    })
+}
+
+impl<'vertex, A: RelationalAdapter + 'vertex> Adapter<'vertex> for SqlAdapter<A> {
+    type Vertex = SqlVertex;
+
+    fn resolve_starting_vertices(
+        &self,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        resolve_info: &ResolveInfo,
+    ) -> VertexIterator<'vertex, Self::Vertex> {
+        let mapping = self.inner.mapping();
+        let Some(edge) = mapping.starting_edge(edge_name) else {
+            let vertices = self
+                .inner
+                .resolve_unmapped_starting_vertices(edge_name, parameters);
+            return Box::new(vertices.into_iter());
+        };
+        let table = mapping.expect_table(&edge.type_name);
+
+        let mut conditions = vec![];
+        for (parameter, column) in &edge.parameter_columns {
+            match parameters.get(parameter) {
+                None | Some(FieldValue::Null) => {}
+                Some(value) => match equals_condition(column, value) {
+                    Some(condition) => conditions.push(condition),
+                    None => panic!(
+                        "parameter {parameter} of edge {edge_name} has value {value:?}, \
+                        which can't be compared to the values of column {column}"
+                    ),
+                },
+            }
+        }
+
+        let limit = edge
+            .limit_parameter
+            .as_ref()
+            .and_then(|parameter| parameters.get(parameter))
+            .and_then(|value| match value {
+                FieldValue::Int64(limit) => Some((*limit).max(0) as u64),
+                FieldValue::Uint64(limit) => Some(*limit),
+                _ => None,
+            });
+        if limit.is_none() {
+            match property_conditions(table, resolve_info) {
+                Some(property_conditions) => conditions.extend(property_conditions),
+                None => return Box::new(std::iter::empty()),
+            }
+        }
+
+        let select = Select {
+            table: table.name.clone(),
+            columns: table.selected_columns(),
+            conditions,
+            limit,
+        };
+        let rows = execute(self.inner.as_ref(), &select);
+        let type_name = edge.type_name.clone();
+        Box::new(
+            rows.into_iter()
+                .map(move |row| SqlVertex::from_row(&type_name, &select.columns, row)),
+        )
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        _type_name: &Arc<str>,
+        property_name: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, FieldValue> {
+        if property_name.as_ref() == TYPENAME_META_FIELD {
+            return resolve_property_with(contexts, |vertex| {
+                FieldValue::String(vertex.type_name().to_string())
+            });
+        }
+
+        let inner = self.inner.clone();
+        let property_name = property_name.clone();
+        resolve_property_with(contexts, move |vertex| {
+            let column = inner
+                .mapping()
+                .table(vertex.type_name())
+                .and_then(|table| table.column(&property_name));
+            match column {
+                Some(column) => vertex.get(column).cloned().unwrap_or_default(),
+                None => inner.resolve_unmapped_property(vertex, &property_name),
+            }
+        })
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        type_name: &Arc<str>,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        resolve_info: &ResolveEdgeInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, VertexIterator<'vertex, Self::Vertex>> {
+        let mapping = self.inner.mapping();
+        let join = mapping
+            .table(type_name)
+            .and_then(|table| table.join(edge_name));
+        let Some(join) = join.filter(|_| parameters.is_empty()) else {
+            let inner = self.inner.clone();
+            let edge_name = edge_name.clone();
+            let parameters = parameters.clone();
+            return resolve_neighbors_with(contexts, move |vertex| {
+                let neighbors = inner.resolve_unmapped_neighbors(vertex, &edge_name, &parameters);
+                Box::new(neighbors.into_iter())
+            });
+        };
+
+        let table = mapping.expect_table(&join.to_type);
+        let conditions = property_conditions(table, &resolve_info.destination());
+        let mut columns = table.selected_columns();
+        if !columns.contains(&join.to_column) {
+            columns.push(join.to_column.clone());
+        }
+        Box::new(JoinBatches {
+            adapter: self.inner.clone(),
+            contexts,
+            join: join.clone(),
+            template: conditions.map(|conditions| Select {
+                table: table.name.clone(),
+                columns,
+                conditions,
+                limit: None,
+            }),
+            batch_size: self.batch_size,
+            batch: Default::default(),
+        })
+    }
+
+    fn resolve_coercion(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        _type_name: &Arc<str>,
+        coerce_to_type: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, bool> {
+        let inner = self.inner.clone();
+        let coerce_to_type = coerce_to_type.clone();
+        resolve_coercion_with(contexts, move |vertex| {
+            inner.is_of_type(vertex, &coerce_to_type)
+        })
+    }
+}
+
+/// The values of join columns, in a form that can be hashed.
+///
+/// Integers of either signedness are compared by value, as in the rest of Trustfall.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum JoinKey {
+    Integer(i128),
+    String(String),
+    Boolean(bool),
+}
+
+impl JoinKey {
+    fn new(value: &FieldValue) -> Option<Self> {
+        match value {
+            FieldValue::Int64(v) => Some(Self::Integer((*v).into())),
+            FieldValue::Uint64(v) => Some(Self::Integer((*v).into())),
+            FieldValue::String(v) | FieldValue::Enum(v) => Some(Self::String(v.clone())),
+            FieldValue::Boolean(v) => Some(Self::Boolean(*v)),
+            _ => None,
+        }
+    }
+}
+
+/// Resolves a join for batches of contexts, with one statement per batch.
+struct JoinBatches<'vertex, A> {
+    adapter: Arc<A>,
+    contexts: ContextIterator<'vertex, SqlVertex>,
+    join: Join,
+
+    /// The statement selecting the neighbors, without the condition on the join column,
+    /// or `None` if no neighbor can satisfy the query's filters.
+    template: Option<Select>,
+
+    batch_size: usize,
+    batch: VecDeque<(DataContext<SqlVertex>, Vec<SqlVertex>)>,
+}
+
+impl<'vertex, A: RelationalAdapter> JoinBatches<'vertex, A> {
+    fn load_batch(&mut self) {
+        let contexts: Vec<_> = self.contexts.by_ref().take(self.batch_size).collect();
+
+        let mut seen = HashSet::new();
+        let mut values = vec![];
+        let keys: Vec<_> = contexts
+            .iter()
+            .map(|context| {
+                let value = context.active_vertex()?.get(&self.join.from_column)?;
+                let key = JoinKey::new(value)?;
+                if seen.insert(key.clone()) {
+                    values.push(value.clone());
+                }
+                Some(key)
+            })
+            .collect();
+
+        let mut neighbors: HashMap<JoinKey, Vec<SqlVertex>> = HashMap::new();
+        if let Some(template) = self.template.as_ref().filter(|_| !values.is_empty()) {
+            let mut select = template.clone();
+            select.conditions.push(Condition::In {
+                column: self.join.to_column.clone(),
+                values,
+            });
+            let key_index = select
+                .columns
+                .iter()
+                .position(|column| column == &self.join.to_column)
+                .expect("join column was not selected");
+            for row in execute(self.adapter.as_ref(), &select) {
+                let Some(key) = row.get(key_index).and_then(JoinKey::new) else {
+                    continue;
+                };
+                let vertex = SqlVertex::from_row(&self.join.to_type, &select.columns, row);
+                neighbors.entry(key).or_default().push(vertex);
+            }
+        }
+
+        for (context, key) in contexts.into_iter().zip(keys) {
+            let vertices = key
+                .and_then(|key| neighbors.get(&key).cloned())
+                .unwrap_or_default();
+            self.batch.push_back((context, vertices));
+        }
+    }
+}
+
+impl<'vertex, A: RelationalAdapter> Iterator for JoinBatches<'vertex, A> {
+    type Item = (DataContext<SqlVertex>, VertexIterator<'vertex, SqlVertex>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.batch.is_empty() {
+            self.load_batch();
+        }
+        let (context, neighbors) = self.batch.pop_front()?;
+        let neighbors: VertexIterator<'vertex, SqlVertex> = Box::new(neighbors.into_iter());
+        Some((context, neighbors))
+    }
+}
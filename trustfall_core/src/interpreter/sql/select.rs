@@ -0,0 +1,308 @@
+use std::{fmt::Write, ops::Bound};
+
+use crate::{interpreter::CandidateValue, ir::FieldValue};
+
+/// A `SELECT` statement over a single table, built by [`SqlAdapter`](super::SqlAdapter)
+/// for a [`RelationalAdapter`](super::RelationalAdapter) to execute.
+///
+/// Adapters usually render it with [`Select::to_sql`] and execute the resulting SQL,
+/// but may also translate it any other way their database supports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Select {
+    /// The table to select rows from.
+    pub table: String,
+
+    /// The columns to select, in the order their values must appear in each returned row.
+    pub columns: Vec<String>,
+
+    /// Conditions that all selected rows must satisfy.
+    pub conditions: Vec<Condition>,
+
+    /// The maximum number of rows to select, if any.
+    pub limit: Option<u64>,
+}
+
+/// A condition on the values of a row, as found in a `WHERE` clause.
+///
+/// Conditions compare values the way SQL does: a comparison with `NULL` is never satisfied,
+/// so null values only satisfy [`Condition::IsNull`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Compare {
+        column: String,
+        operator: Operator,
+        value: FieldValue,
+    },
+    In {
+        column: String,
+        values: Vec<FieldValue>,
+    },
+    IsNull(String),
+    IsNotNull(String),
+    And(Vec<Condition>),
+    Or(Vec<Condition>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Equals,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+}
+
+impl Operator {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Operator::Equals => "=",
+            Operator::LessThan => "<",
+            Operator::LessThanOrEqual => "<=",
+            Operator::GreaterThan => ">",
+            Operator::GreaterThanOrEqual => ">=",
+        }
+    }
+}
+
+/// How query parameters are written in rendered SQL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParameterStyle {
+    /// `?`, as used by SQLite and MySQL.
+    #[default]
+    QuestionMark,
+
+    /// `$1`, `$2`, and so on, as used by PostgreSQL.
+    Numbered,
+}
+
+impl Select {
+    /// Render the statement as SQL, returning the SQL text and the values of its parameters.
+    ///
+    /// Table and column names are quoted with double quotes, and all values are passed
+    /// as parameters instead of being written into the SQL text.
+    pub fn to_sql(&self, style: ParameterStyle) -> (String, Vec<FieldValue>) {
+        let mut renderer = Renderer {
+            sql: String::new(),
+            parameters: vec![],
+            style,
+        };
+
+        renderer.sql.push_str("SELECT ");
+        for (index, column) in self.columns.iter().enumerate() {
+            if index > 0 {
+                renderer.sql.push_str(", ");
+            }
+            renderer.identifier(column);
+        }
+        renderer.sql.push_str(" FROM ");
+        renderer.identifier(&self.table);
+
+        if !self.conditions.is_empty() {
+            renderer.sql.push_str(" WHERE ");
+            renderer.conjunction(&self.conditions, " AND ");
+        }
+        if let Some(limit) = self.limit {
+            write!(renderer.sql, " LIMIT {limit}").expect("writing to a string failed");
+        }
+
+        (renderer.sql, renderer.parameters)
+    }
+}
+
+struct Renderer {
+    sql: String,
+    parameters: Vec<FieldValue>,
+    style: ParameterStyle,
+}
+
+impl Renderer {
+    fn identifier(&mut self, name: &str) {
+        write!(self.sql, "\"{}\"", name.replace('"', "\"\"")).expect("writing to a string failed");
+    }
+
+    fn parameter(&mut self, value: &FieldValue) {
+        self.parameters.push(value.clone());
+        match self.style {
+            ParameterStyle::QuestionMark => self.sql.push('?'),
+            ParameterStyle::Numbered => {
+                write!(self.sql, "${}", self.parameters.len()).expect("writing to a string failed")
+            }
+        }
+    }
+
+    fn conjunction(&mut self, conditions: &[Condition], separator: &str) {
+        for (index, condition) in conditions.iter().enumerate() {
+            if index > 0 {
+                self.sql.push_str(separator);
+            }
+            self.condition(condition, conditions.len() > 1);
+        }
+    }
+
+    fn condition(&mut self, condition: &Condition, nested: bool) {
+        match condition {
+            Condition::Compare {
+                column,
+                operator,
+                value,
+            } => {
+                self.identifier(column);
+                write!(self.sql, " {} ", operator.as_sql()).expect("writing to a string failed");
+                self.parameter(value);
+            }
+            Condition::In { values, .. } if values.is_empty() => {
+                // `IN ()` isn't valid SQL, but no row matches an empty list of values.
+                self.sql.push_str("1 = 0");
+            }
+            Condition::In { column, values } => {
+                self.identifier(column);
+                self.sql.push_str(" IN (");
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        self.sql.push_str(", ");
+                    }
+                    self.parameter(value);
+                }
+                self.sql.push(')');
+            }
+            Condition::IsNull(column) => {
+                self.identifier(column);
+                self.sql.push_str(" IS NULL");
+            }
+            Condition::IsNotNull(column) => {
+                self.identifier(column);
+                self.sql.push_str(" IS NOT NULL");
+            }
+            Condition::And(conditions) if conditions.is_empty() => self.sql.push_str("1 = 1"),
+            Condition::Or(conditions) if conditions.is_empty() => self.sql.push_str("1 = 0"),
+            Condition::And(conditions) | Condition::Or(conditions) => {
+                let separator = if matches!(condition, Condition::And(_)) {
+                    " AND "
+                } else {
+                    " OR "
+                };
+                let parenthesize = nested && conditions.len() > 1;
+                if parenthesize {
+                    self.sql.push('(');
+                }
+                self.conjunction(conditions, separator);
+                if parenthesize {
+                    self.sql.push(')');
+                }
+            }
+        }
+    }
+}
+
+/// Whether a value can be passed to the database as a parameter and compared there
+/// the same way Trustfall compares it.
+fn is_comparable(value: &FieldValue) -> bool {
+    !matches!(value, FieldValue::Null | FieldValue::List(_))
+}
+
+/// What a column's candidate values mean for the rows to select.
+pub(super) enum Constraint {
+    /// The candidate doesn't constrain the column's values, or constrains them in a way
+    /// not expressible as a condition, like candidates that are lists.
+    Unconstrained,
+
+    /// No value satisfies the candidate.
+    Impossible,
+
+    Condition(Condition),
+}
+
+/// The constraint on the rows to select that is equivalent to a column's candidate values.
+pub(super) fn candidate_constraint(
+    column: &str,
+    candidate: CandidateValue<&FieldValue>,
+) -> Constraint {
+    let condition = match candidate {
+        CandidateValue::Impossible => return Constraint::Impossible,
+        CandidateValue::All => return Constraint::Unconstrained,
+        CandidateValue::Single(FieldValue::Null) => Condition::IsNull(column.to_string()),
+        CandidateValue::Single(value) if is_comparable(value) => Condition::Compare {
+            column: column.to_string(),
+            operator: Operator::Equals,
+            value: value.clone(),
+        },
+        CandidateValue::Single(_) => return Constraint::Unconstrained,
+        CandidateValue::Multiple(values) => {
+            let null_included = values.iter().any(|value| matches!(value, FieldValue::Null));
+            let values: Vec<_> = values
+                .into_iter()
+                .filter(|value| !matches!(value, FieldValue::Null))
+                .cloned()
+                .collect();
+            if !values.iter().all(is_comparable) {
+                return Constraint::Unconstrained;
+            }
+            if values.is_empty() && null_included {
+                return Constraint::Condition(Condition::IsNull(column.to_string()));
+            }
+            with_null(
+                column,
+                Condition::In {
+                    column: column.to_string(),
+                    values,
+                },
+                null_included,
+            )
+        }
+        CandidateValue::Range(range) => {
+            let mut bounds = vec![];
+            for (bound, inclusive, exclusive) in [
+                (
+                    range.start_bound(),
+                    Operator::GreaterThanOrEqual,
+                    Operator::GreaterThan,
+                ),
+                (
+                    range.end_bound(),
+                    Operator::LessThanOrEqual,
+                    Operator::LessThan,
+                ),
+            ] {
+                let (operator, value) = match bound {
+                    Bound::Included(value) => (inclusive, *value),
+                    Bound::Excluded(value) => (exclusive, *value),
+                    Bound::Unbounded => continue,
+                };
+                if !is_comparable(value) {
+                    return Constraint::Unconstrained;
+                }
+                bounds.push(Condition::Compare {
+                    column: column.to_string(),
+                    operator,
+                    value: value.clone(),
+                });
+            }
+            match (bounds.len(), range.null_included()) {
+                (0, true) => return Constraint::Unconstrained,
+                (0, false) => Condition::IsNotNull(column.to_string()),
+                (1, null_included) => with_null(column, bounds.pop().unwrap(), null_included),
+                (_, null_included) => with_null(column, Condition::And(bounds), null_included),
+            }
+        }
+    };
+    Constraint::Condition(condition)
+}
+
+fn with_null(column: &str, condition: Condition, null_included: bool) -> Condition {
+    if null_included {
+        Condition::Or(vec![condition, Condition::IsNull(column.to_string())])
+    } else {
+        condition
+    }
+}
+
+/// The condition that a column's value equals the given value,
+/// or `None` if the value can't be compared in SQL.
+pub(super) fn equals_condition(column: &str, value: &FieldValue) -> Option<Condition> {
+    is_comparable(value).then(|| Condition::Compare {
+        column: column.to_string(),
+        operator: Operator::Equals,
+        value: value.clone(),
+    })
+}
@@ -0,0 +1,890 @@
+//! Exporting queries over relational databases as [Substrait](https://substrait.io) plans,
+//! for engines like DataFusion and Velox to execute.
+//!
+//! [`to_plan`] lowers queries whose vertices are all rows of the tables of a
+//! [`RelationalMapping`] and whose edges are all joins between those tables:
+//! - the starting edge reads its type's table, with its parameters translated the same way
+//!   [`SqlAdapter`](super::SqlAdapter) translates them;
+//! - each edge joins the table of its neighbors, with a left join for `@optional` edges;
+//! - filters become conditions on the joined rows, compared the way Trustfall compares values;
+//! - outputs are projected from the joined rows, in the order of their names.
+//!
+//! Queries using `@fold`, `@recurse`, type coercions, edge parameters outside the starting
+//! edge, or `regex` and `contains` filters have no such plan, and fail to lower.
+//! Substrait plans don't have parameters, so the query's arguments are written into the plan.
+//!
+//! Plans serialize to Substrait's JSON form:
+//! ```rust
+//! # use std::{collections::BTreeMap, sync::Arc};
+//! # use trustfall_core::{
+//! #     frontend::parse,
+//! #     interpreter::sql::{substrait::to_plan, RelationalMapping, StartingEdge, Table},
+//! #     schema::Schema,
+//! # };
+//! let schema = Schema::parse(format!(
+//!     "schema {{ query: RootSchemaQuery }} {} \
+//!     type RootSchemaQuery {{ User: [User!]! }} \
+//!     type User {{ name: String }}",
+//!     Schema::ALL_DIRECTIVE_DEFINITIONS,
+//! ))
+//! .unwrap();
+//! let mapping = RelationalMapping::new()
+//!     .with_table("User", Table::new("users").with_column("name", "name"))
+//!     .with_starting_edge("User", StartingEdge::new("User"));
+//!
+//! let query = parse(&schema, "{ User { name @output } }").unwrap();
+//! let plan = to_plan(&schema, &mapping, &query, &BTreeMap::new()).unwrap();
+//! let json = serde_json::to_string(&plan).unwrap();
+//! ```
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+use async_graphql_parser::types::{BaseType, Type as GraphQLType};
+
+use crate::{
+    ir::{
+        Argument, FieldRef, FieldValue, IREdge, IRQueryComponent, IndexedQuery, LocalField,
+        Operation, Vid,
+    },
+    schema::Schema,
+};
+
+use super::{Join, RelationalMapping, Table};
+
+pub mod plan;
+
+#[cfg(test)]
+mod tests;
+
+use plan::{
+    Emit, Expression, Extension, ExtensionFunction, ExtensionUri, FetchRel, FieldReference,
+    FilterRel, FunctionArgument, JoinRel, JoinType, Literal, NamedStruct, NamedTable, Nullability,
+    Plan, PlanRel, ProjectRel, ReadRel, Rel, RelCommon, RelRoot, ScalarFunction, SingularOrList,
+    StructType, Type, TypeInfo, Version, SUBSTRAIT_MINOR_VERSION,
+};
+
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SubstraitError {
+    #[error("The query cannot be lowered to a Substrait plan, since it uses {0}")]
+    UnsupportedQuery(String),
+
+    #[error("Type {0} is used in the query, but the relational mapping has no table for it")]
+    UnmappedType(String),
+
+    #[error(
+        "Property {1} of type {0} is used in the query, but the relational mapping \
+        has no column for it"
+    )]
+    UnmappedProperty(String, String),
+
+    #[error(
+        "Edge {1} of type {0} is used in the query, but the relational mapping \
+        doesn't resolve it by joining tables"
+    )]
+    UnmappedEdge(String, String),
+
+    #[error(
+        "Column {1} of table {0} has type {2}, which has no equivalent Substrait type \
+        supported by exported plans"
+    )]
+    UnsupportedColumnType(String, String, String),
+
+    #[error(
+        "The type of column {1} of table {0} is unknown, since no property of the relational \
+        mapping is stored in it or joined with it"
+    )]
+    UnknownColumnType(String, String),
+
+    #[error("The query requires argument \"{0}\", which was not provided")]
+    MissingArgument(String),
+}
+
+/// Lower the query to a Substrait plan reading the tables of the relational mapping.
+///
+/// The plan's results have one column per output, in the order of the output names.
+pub fn to_plan(
+    schema: &Schema,
+    mapping: &RelationalMapping,
+    query: &IndexedQuery,
+    arguments: &BTreeMap<Arc<str>, FieldValue>,
+) -> Result<Plan, SubstraitError> {
+    let mut lowering = Lowering::new(schema, mapping, query, arguments)?;
+    let component = &query.ir_query.root_component;
+    let (relation, layout) = lowering.relation(component.root)?;
+
+    let mut conditions = vec![];
+    for (vid, filter) in std::mem::take(&mut lowering.remaining_filters) {
+        let condition = lowering.filter_condition(&layout, vid, filter)?;
+        conditions.push(lowering.guard_optional_vertices(&layout, vid, filter, condition));
+    }
+    let relation = match lowering.conjunction(conditions) {
+        Some(condition) => Rel::Filter(Box::new(FilterRel {
+            input: relation,
+            condition,
+        })),
+        None => relation,
+    };
+
+    let mut expressions = vec![];
+    let mut names = vec![];
+    for (name, field) in &component.outputs {
+        let column = lowering.property_column(field.vertex_id, &field.field_name)?;
+        expressions.push(field_expression(&layout, field.vertex_id, &column));
+        names.push(name.to_string());
+    }
+    let relation = Rel::Project(Box::new(ProjectRel {
+        common: RelCommon {
+            emit: Emit {
+                output_mapping: (layout.len()..layout.len() + names.len())
+                    .map(|index| index as u32)
+                    .collect(),
+            },
+        },
+        input: relation,
+        expressions,
+    }));
+
+    let (extension_uris, extensions) = lowering.functions.extensions();
+    Ok(Plan {
+        version: Version {
+            minor_number: SUBSTRAIT_MINOR_VERSION,
+            producer: "trustfall".to_string(),
+        },
+        extension_uris,
+        extensions,
+        relations: vec![PlanRel::Root(RelRoot {
+            input: relation,
+            names,
+        })],
+    })
+}
+
+/// The vertex and column of each column of a relation, in order.
+type Layout = Vec<(Vid, String)>;
+
+struct Vertex<'a> {
+    type_name: &'a str,
+    table: &'a Table,
+
+    /// The columns read from the table, as needed by the rest of the plan.
+    columns: BTreeSet<String>,
+
+    /// The edges from this vertex, along with the joins that resolve them.
+    edges: Vec<(&'a IREdge, &'a Join)>,
+
+    /// The vertex at the end of the innermost `@optional` edge this vertex is within, if any,
+    /// along with the column whose value is null when that vertex doesn't exist.
+    optional_scope: Option<(Vid, String)>,
+
+    /// The filters applied right after reading the table.
+    pushed_filters: Vec<&'a Operation<LocalField, Argument>>,
+}
+
+struct Lowering<'a> {
+    schema: &'a Schema,
+    mapping: &'a RelationalMapping,
+    component: &'a IRQueryComponent,
+    arguments: &'a BTreeMap<Arc<str>, FieldValue>,
+    vertices: BTreeMap<Vid, Vertex<'a>>,
+
+    /// Conditions on the starting edge's parameter columns, and its limit if any.
+    starting_conditions: Vec<(String, &'a FieldValue)>,
+    starting_limit: Option<i64>,
+
+    /// The filters applied to the joined rows of all vertices.
+    remaining_filters: Vec<(Vid, &'a Operation<LocalField, Argument>)>,
+
+    functions: Functions,
+}
+
+impl<'a> Lowering<'a> {
+    fn new(
+        schema: &'a Schema,
+        mapping: &'a RelationalMapping,
+        query: &'a IndexedQuery,
+        arguments: &'a BTreeMap<Arc<str>, FieldValue>,
+    ) -> Result<Self, SubstraitError> {
+        let ir_query = &query.ir_query;
+        let component = &*ir_query.root_component;
+        if !component.folds.is_empty() {
+            return Err(SubstraitError::UnsupportedQuery("@fold".to_string()));
+        }
+        if let Some(vertex) = component
+            .vertices
+            .values()
+            .find(|v| v.coerced_from_type.is_some())
+        {
+            return Err(SubstraitError::UnsupportedQuery(format!(
+                "a type coercion to {}",
+                vertex.type_name
+            )));
+        }
+
+        let starting_edge = mapping.starting_edge(&ir_query.root_name).ok_or_else(|| {
+            SubstraitError::UnmappedEdge(
+                schema.query_type_name().to_string(),
+                ir_query.root_name.to_string(),
+            )
+        })?;
+        let mut lowering = Self {
+            schema,
+            mapping,
+            component,
+            arguments,
+            vertices: Default::default(),
+            starting_conditions: vec![],
+            starting_limit: None,
+            remaining_filters: vec![],
+            functions: Default::default(),
+        };
+
+        let root = lowering.vertex(&starting_edge.type_name, None)?;
+        lowering.vertices.insert(component.root, root);
+        for (name, value) in ir_query.root_parameters.iter() {
+            if matches!(value, FieldValue::Null) {
+                continue;
+            }
+            if let Some(column) = starting_edge.parameter_columns.get(name) {
+                lowering.add_column(component.root, column.clone());
+                lowering.starting_conditions.push((column.clone(), value));
+            } else if starting_edge.limit_parameter.as_ref() == Some(name) {
+                lowering.starting_limit = Some(value.as_i64().ok_or_else(|| {
+                    SubstraitError::UnsupportedQuery(format!("the limit {value:?}"))
+                })?);
+            } else {
+                return Err(SubstraitError::UnsupportedQuery(format!(
+                    "parameter {name} of edge {}, which isn't stored in a column",
+                    ir_query.root_name
+                )));
+            }
+        }
+
+        for edge in component.edges.values() {
+            if edge.recursive.is_some() {
+                return Err(SubstraitError::UnsupportedQuery("@recurse".to_string()));
+            }
+            if !edge.parameters.is_empty() {
+                return Err(SubstraitError::UnsupportedQuery(format!(
+                    "parameters of edge {}",
+                    edge.edge_name
+                )));
+            }
+
+            let parent = &lowering.vertices[&edge.from_vid];
+            let join = parent.table.join(&edge.edge_name).ok_or_else(|| {
+                SubstraitError::UnmappedEdge(
+                    parent.type_name.to_string(),
+                    edge.edge_name.to_string(),
+                )
+            })?;
+            let optional_scope = if edge.optional {
+                Some((edge.to_vid, join.to_column.clone()))
+            } else {
+                parent.optional_scope.clone()
+            };
+            let child = lowering.vertex(&join.to_type, optional_scope)?;
+            lowering.vertices.insert(edge.to_vid, child);
+
+            lowering.add_column(edge.from_vid, join.from_column.clone());
+            lowering.add_column(edge.to_vid, join.to_column.clone());
+            lowering
+                .vertices
+                .get_mut(&edge.from_vid)
+                .expect("no vertex for the edge's starting vid")
+                .edges
+                .push((&**edge, join));
+        }
+
+        for (vid, vertex) in &component.vertices {
+            for filter in &vertex.filters {
+                lowering.property_column(*vid, &filter.left().field_name)?;
+                let tagged_vid = match filter.right() {
+                    Some(Argument::Tag(FieldRef::ContextField(field))) => {
+                        lowering.property_column(field.vertex_id, &field.field_name)?;
+                        Some(field.vertex_id)
+                    }
+                    Some(Argument::Tag(FieldRef::FoldSpecificField(_))) => {
+                        unreachable!("fold-specific tag in a query without folds: {filter:?}")
+                    }
+                    Some(Argument::Variable(_)) | None => None,
+                };
+
+                // Filters can only be applied before joining when every result row has
+                // the vertex, and when they only use the vertex's own values.
+                let vertex = lowering
+                    .vertices
+                    .get_mut(vid)
+                    .expect("no vertex for the filter's vid");
+                if vertex.optional_scope.is_none() && tagged_vid.unwrap_or(*vid) == *vid {
+                    vertex.pushed_filters.push(filter);
+                } else {
+                    lowering.remaining_filters.push((*vid, filter));
+                }
+            }
+        }
+        for field in component.outputs.values() {
+            lowering.property_column(field.vertex_id, &field.field_name)?;
+        }
+
+        Ok(lowering)
+    }
+
+    fn vertex(
+        &self,
+        type_name: &'a str,
+        optional_scope: Option<(Vid, String)>,
+    ) -> Result<Vertex<'a>, SubstraitError> {
+        let table = self
+            .mapping
+            .table(type_name)
+            .ok_or_else(|| SubstraitError::UnmappedType(type_name.to_string()))?;
+        Ok(Vertex {
+            type_name,
+            table,
+            columns: Default::default(),
+            edges: vec![],
+            optional_scope,
+            pushed_filters: vec![],
+        })
+    }
+
+    fn add_column(&mut self, vid: Vid, column: String) {
+        self.vertices
+            .get_mut(&vid)
+            .expect("no vertex for vid")
+            .columns
+            .insert(column);
+    }
+
+    /// The column holding the property of the vertex, which is then read from its table.
+    fn property_column(&mut self, vid: Vid, property_name: &str) -> Result<String, SubstraitError> {
+        let vertex = &self.vertices[&vid];
+        let column = vertex.table.column(property_name).ok_or_else(|| {
+            SubstraitError::UnmappedProperty(
+                vertex.type_name.to_string(),
+                property_name.to_string(),
+            )
+        })?;
+        let column = column.to_string();
+        self.add_column(vid, column.clone());
+        Ok(column)
+    }
+
+    /// The relation joining the vertex's table with the tables of all vertices after it.
+    fn relation(&mut self, vid: Vid) -> Result<(Rel, Layout), SubstraitError> {
+        let vertex = &self.vertices[&vid];
+        let table = vertex.table;
+        let edges = vertex.edges.clone();
+        let pushed_filters = vertex.pushed_filters.clone();
+
+        let mut types = vec![];
+        for column in &vertex.columns {
+            types.push(self.column_type(vertex.type_name, table, column)?);
+        }
+        let mut layout: Layout = vertex.columns.iter().map(|c| (vid, c.clone())).collect();
+        let mut relation = Rel::Read(Box::new(ReadRel {
+            base_schema: NamedStruct {
+                names: vertex.columns.iter().cloned().collect(),
+                struct_type: StructType {
+                    types,
+                    nullability: Nullability::Required,
+                },
+            },
+            named_table: NamedTable {
+                names: vec![table.name().to_string()],
+            },
+        }));
+
+        if vid == self.component.root {
+            let conditions = std::mem::take(&mut self.starting_conditions)
+                .into_iter()
+                .map(|(column, value)| {
+                    let left = field_expression(&layout, vid, &column);
+                    let right = Expression::Literal(literal(value)?);
+                    Ok(self.functions.call(Function::Equal, vec![left, right]))
+                })
+                .collect::<Result<Vec<_>, SubstraitError>>()?;
+            relation = self.filtered(relation, conditions);
+
+            if let Some(count) = self.starting_limit {
+                relation = Rel::Fetch(Box::new(FetchRel {
+                    input: relation,
+                    offset: 0,
+                    count,
+                }));
+            }
+        }
+
+        let mut conditions = vec![];
+        for filter in pushed_filters {
+            conditions.push(self.filter_condition(&layout, vid, filter)?);
+        }
+        relation = self.filtered(relation, conditions);
+
+        for (edge, join) in edges {
+            let (right, right_layout) = self.relation(edge.to_vid)?;
+            let left_key = field_expression(&layout, vid, &join.from_column);
+            let right_key = FieldReference::field(
+                (layout.len() + column_index(&right_layout, edge.to_vid, &join.to_column)) as u32,
+            );
+            relation = Rel::Join(Box::new(JoinRel {
+                left: relation,
+                right,
+                expression: self.functions.call(
+                    Function::Equal,
+                    vec![left_key, Expression::Selection(right_key)],
+                ),
+                join_type: if edge.optional {
+                    JoinType::Left
+                } else {
+                    JoinType::Inner
+                },
+            }));
+            layout.extend(right_layout);
+        }
+
+        Ok((relation, layout))
+    }
+
+    fn filtered(&mut self, relation: Rel, conditions: Vec<Expression>) -> Rel {
+        match self.conjunction(conditions) {
+            Some(condition) => Rel::Filter(Box::new(FilterRel {
+                input: relation,
+                condition,
+            })),
+            None => relation,
+        }
+    }
+
+    fn conjunction(&mut self, mut conditions: Vec<Expression>) -> Option<Expression> {
+        match conditions.len() {
+            0 => None,
+            1 => conditions.pop(),
+            _ => Some(self.functions.call(Function::And, conditions)),
+        }
+    }
+
+    /// The type of a column, from the type of the property stored in it, or of the property
+    /// stored in a column it is joined with.
+    fn column_type(
+        &self,
+        type_name: &str,
+        table: &Table,
+        column: &str,
+    ) -> Result<Type, SubstraitError> {
+        let unsupported = |ty: &GraphQLType| {
+            SubstraitError::UnsupportedColumnType(
+                table.name().to_string(),
+                column.to_string(),
+                ty.to_string(),
+            )
+        };
+
+        if let Some(ty) = self.property_type(type_name, table, column) {
+            return scalar_type(ty, ty.nullable).ok_or_else(|| unsupported(ty));
+        }
+
+        // Columns only used by joins may hold null values when the joined rows don't exist.
+        let joined_types = table
+            .joins
+            .values()
+            .filter(|join| join.from_column == column)
+            .filter_map(|join| {
+                let to_table = self.mapping.table(&join.to_type)?;
+                self.property_type(&join.to_type, to_table, &join.to_column)
+            });
+        let joining_types = self
+            .mapping
+            .tables
+            .iter()
+            .flat_map(|(from_type, from_table)| {
+                from_table
+                    .joins
+                    .values()
+                    .map(move |join| (from_type, from_table, join))
+            })
+            .filter(|(_, _, join)| &*join.to_type == type_name && join.to_column == column)
+            .filter_map(|(from_type, from_table, join)| {
+                self.property_type(from_type, from_table, &join.from_column)
+            });
+        match joined_types.chain(joining_types).next() {
+            Some(ty) => scalar_type(ty, true).ok_or_else(|| unsupported(ty)),
+            None => Err(SubstraitError::UnknownColumnType(
+                table.name().to_string(),
+                column.to_string(),
+            )),
+        }
+    }
+
+    fn property_type(
+        &self,
+        type_name: &str,
+        table: &Table,
+        column: &str,
+    ) -> Option<&'a GraphQLType> {
+        let (property_name, _) = table
+            .columns
+            .iter()
+            .find(|(_, property_column)| *property_column == column)?;
+        self.schema
+            .fields
+            .get(&(Arc::from(type_name), property_name.clone()))
+            .map(|field| &field.ty.node)
+    }
+
+    /// The condition equivalent to the filter on the given vertex's property.
+    fn filter_condition(
+        &mut self,
+        layout: &Layout,
+        vid: Vid,
+        filter: &Operation<LocalField, Argument>,
+    ) -> Result<Expression, SubstraitError> {
+        let left_column = self.property_column(vid, &filter.left().field_name)?;
+        let left = field_expression(layout, vid, &left_column);
+        let right = match filter.right() {
+            None => None,
+            Some(Argument::Variable(variable)) => Some(Operand::Value(
+                self.arguments.get(&variable.variable_name).ok_or_else(|| {
+                    SubstraitError::MissingArgument(variable.variable_name.to_string())
+                })?,
+            )),
+            Some(Argument::Tag(FieldRef::ContextField(field))) => {
+                let column = self.property_column(field.vertex_id, &field.field_name)?;
+                Some(Operand::Column(field_expression(
+                    layout,
+                    field.vertex_id,
+                    &column,
+                )))
+            }
+            Some(Argument::Tag(FieldRef::FoldSpecificField(_))) => {
+                unreachable!("fold-specific tag in a query without folds: {filter:?}")
+            }
+        };
+        let right = || right.clone().expect("binary filter without an argument");
+
+        let condition = match filter {
+            Operation::IsNull(_) => self.functions.call(Function::IsNull, vec![left]),
+            Operation::IsNotNull(_) => self.functions.call(Function::IsNotNull, vec![left]),
+            Operation::Equals(..) => self.equals(left, right())?,
+            Operation::NotEquals(..) => {
+                let equals = self.equals(left, right())?;
+                self.functions.call(Function::Not, vec![equals])
+            }
+            Operation::LessThan(..) => self.compare(Function::LessThan, left, right())?,
+            Operation::LessThanOrEqual(..) => {
+                self.compare(Function::LessThanOrEqual, left, right())?
+            }
+            Operation::GreaterThan(..) => self.compare(Function::GreaterThan, left, right())?,
+            Operation::GreaterThanOrEqual(..) => {
+                self.compare(Function::GreaterThanOrEqual, left, right())?
+            }
+            Operation::HasPrefix(..) => self.compare(Function::StartsWith, left, right())?,
+            Operation::HasSuffix(..) => self.compare(Function::EndsWith, left, right())?,
+            Operation::HasSubstring(..) => self.compare(Function::Contains, left, right())?,
+            Operation::OneOf(..) => self.one_of(left, right())?,
+
+            // Negated filters are satisfied whenever the filter they negate isn't,
+            // including when that filter's condition is null because of null values.
+            Operation::NotHasPrefix(..) => {
+                let condition = self.compare(Function::StartsWith, left, right())?;
+                self.functions.call(Function::IsNotTrue, vec![condition])
+            }
+            Operation::NotHasSuffix(..) => {
+                let condition = self.compare(Function::EndsWith, left, right())?;
+                self.functions.call(Function::IsNotTrue, vec![condition])
+            }
+            Operation::NotHasSubstring(..) => {
+                let condition = self.compare(Function::Contains, left, right())?;
+                self.functions.call(Function::IsNotTrue, vec![condition])
+            }
+            Operation::NotOneOf(..) => {
+                let condition = self.one_of(left, right())?;
+                self.functions.call(Function::IsNotTrue, vec![condition])
+            }
+            Operation::Contains(..)
+            | Operation::NotContains(..)
+            | Operation::RegexMatches(..)
+            | Operation::NotRegexMatches(..) => {
+                return Err(SubstraitError::UnsupportedQuery(format!(
+                    "the {} filter operation",
+                    filter.operation_name()
+                )))
+            }
+        };
+        Ok(condition)
+    }
+
+    /// Filters on vertices that may not exist, or that use tags from such vertices,
+    /// are satisfied when the vertices don't exist.
+    fn guard_optional_vertices(
+        &mut self,
+        layout: &Layout,
+        vid: Vid,
+        filter: &Operation<LocalField, Argument>,
+        condition: Expression,
+    ) -> Expression {
+        let mut vids = vec![vid];
+        if let Some(Argument::Tag(FieldRef::ContextField(field))) = filter.right() {
+            vids.push(field.vertex_id);
+        }
+        let scopes: BTreeSet<_> = vids
+            .into_iter()
+            .filter_map(|vid| self.vertices[&vid].optional_scope.clone())
+            .collect();
+        if scopes.is_empty() {
+            return condition;
+        }
+
+        let mut alternatives: Vec<_> = scopes
+            .into_iter()
+            .map(|(scope_vid, column)| {
+                let key = field_expression(layout, scope_vid, &column);
+                self.functions.call(Function::IsNull, vec![key])
+            })
+            .collect();
+        alternatives.push(condition);
+        self.functions.call(Function::Or, alternatives)
+    }
+
+    /// Trustfall considers null values equal to each other.
+    fn equals(&mut self, left: Expression, right: Operand) -> Result<Expression, SubstraitError> {
+        let condition = match right {
+            Operand::Value(FieldValue::Null) => self.functions.call(Function::IsNull, vec![left]),
+            right => {
+                let right = right.expression()?;
+                self.functions
+                    .call(Function::IsNotDistinctFrom, vec![left, right])
+            }
+        };
+        Ok(condition)
+    }
+
+    /// Comparisons with null values aren't satisfied.
+    fn compare(
+        &mut self,
+        function: Function,
+        left: Expression,
+        right: Operand,
+    ) -> Result<Expression, SubstraitError> {
+        let condition = match right {
+            Operand::Value(FieldValue::Null) => Expression::Literal(Literal::Boolean(false)),
+            right => {
+                let right = right.expression()?;
+                self.functions.call(function, vec![left, right])
+            }
+        };
+        Ok(condition)
+    }
+
+    fn one_of(&mut self, left: Expression, right: Operand) -> Result<Expression, SubstraitError> {
+        let values = match right {
+            Operand::Value(FieldValue::Null) => {
+                return Ok(Expression::Literal(Literal::Boolean(false)))
+            }
+            Operand::Value(FieldValue::List(values)) => values,
+            Operand::Value(value) => unreachable!("one_of filter with a non-list value {value:?}"),
+            Operand::Column(_) => {
+                return Err(SubstraitError::UnsupportedQuery(
+                    "a one_of filter with a tagged value".to_string(),
+                ))
+            }
+        };
+
+        let null_included = values.iter().any(|value| matches!(value, FieldValue::Null));
+        let options = values
+            .iter()
+            .filter(|value| !matches!(value, FieldValue::Null))
+            .map(|value| literal(value).map(Expression::Literal))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut alternatives = vec![];
+        if null_included {
+            alternatives.push(self.functions.call(Function::IsNull, vec![left.clone()]));
+        }
+        if !options.is_empty() {
+            alternatives.push(Expression::SingularOrList(Box::new(SingularOrList {
+                value: left,
+                options,
+            })));
+        }
+        let condition = match alternatives.len() {
+            0 => Expression::Literal(Literal::Boolean(false)),
+            1 => alternatives.pop().expect("no alternatives"),
+            _ => self.functions.call(Function::Or, alternatives),
+        };
+        Ok(condition)
+    }
+}
+
+/// The argument of a binary filter.
+#[derive(Clone)]
+enum Operand<'a> {
+    Value(&'a FieldValue),
+    Column(Expression),
+}
+
+impl Operand<'_> {
+    fn expression(self) -> Result<Expression, SubstraitError> {
+        match self {
+            Operand::Value(value) => literal(value).map(Expression::Literal),
+            Operand::Column(expression) => Ok(expression),
+        }
+    }
+}
+
+fn column_index(layout: &Layout, vid: Vid, column: &str) -> usize {
+    layout
+        .iter()
+        .position(|(column_vid, name)| *column_vid == vid && name == column)
+        .unwrap_or_else(|| panic!("column {column} of vertex {vid:?} was not read"))
+}
+
+fn field_expression(layout: &Layout, vid: Vid, column: &str) -> Expression {
+    Expression::Selection(FieldReference::field(
+        column_index(layout, vid, column) as u32
+    ))
+}
+
+fn literal(value: &FieldValue) -> Result<Literal, SubstraitError> {
+    let literal = match value {
+        FieldValue::Int64(value) => Literal::I64(*value),
+        FieldValue::Uint64(value) => Literal::I64(i64::try_from(*value).map_err(|_| {
+            SubstraitError::UnsupportedQuery(format!("the out-of-range integer {value}"))
+        })?),
+        FieldValue::Float64(value) => Literal::Fp64(*value),
+        FieldValue::String(value) => Literal::String(value.to_string()),
+        FieldValue::Boolean(value) => Literal::Boolean(*value),
+        _ => {
+            return Err(SubstraitError::UnsupportedQuery(format!(
+                "the value {value:?}"
+            )))
+        }
+    };
+    Ok(literal)
+}
+
+fn scalar_type(ty: &GraphQLType, nullable: bool) -> Option<Type> {
+    let info = TypeInfo {
+        nullability: if nullable {
+            Nullability::Nullable
+        } else {
+            Nullability::Required
+        },
+    };
+    match &ty.base {
+        BaseType::Named(name) => match name.as_str() {
+            "Int" => Some(Type::I64(info)),
+            "Float" => Some(Type::Fp64(info)),
+            "String" | "ID" => Some(Type::String(info)),
+            "Boolean" => Some(Type::Bool(info)),
+            _ => None,
+        },
+        BaseType::List(_) => None,
+    }
+}
+
+const COMPARISON_FUNCTIONS: &str =
+    "https://github.com/substrait-io/substrait/blob/main/extensions/functions_comparison.yaml";
+const BOOLEAN_FUNCTIONS: &str =
+    "https://github.com/substrait-io/substrait/blob/main/extensions/functions_boolean.yaml";
+const STRING_FUNCTIONS: &str =
+    "https://github.com/substrait-io/substrait/blob/main/extensions/functions_string.yaml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    Equal,
+    IsNotDistinctFrom,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+    IsNull,
+    IsNotNull,
+    IsNotTrue,
+    And,
+    Or,
+    Not,
+    StartsWith,
+    EndsWith,
+    Contains,
+}
+
+impl Function {
+    /// The extension file defining the function, and the function's name including
+    /// the types of its arguments.
+    fn definition(self) -> (&'static str, &'static str) {
+        match self {
+            Function::Equal => (COMPARISON_FUNCTIONS, "equal:any_any"),
+            Function::IsNotDistinctFrom => (COMPARISON_FUNCTIONS, "is_not_distinct_from:any_any"),
+            Function::LessThan => (COMPARISON_FUNCTIONS, "lt:any_any"),
+            Function::LessThanOrEqual => (COMPARISON_FUNCTIONS, "lte:any_any"),
+            Function::GreaterThan => (COMPARISON_FUNCTIONS, "gt:any_any"),
+            Function::GreaterThanOrEqual => (COMPARISON_FUNCTIONS, "gte:any_any"),
+            Function::IsNull => (COMPARISON_FUNCTIONS, "is_null:any"),
+            Function::IsNotNull => (COMPARISON_FUNCTIONS, "is_not_null:any"),
+            Function::IsNotTrue => (COMPARISON_FUNCTIONS, "is_not_true:bool"),
+            Function::And => (BOOLEAN_FUNCTIONS, "and:bool"),
+            Function::Or => (BOOLEAN_FUNCTIONS, "or:bool"),
+            Function::Not => (BOOLEAN_FUNCTIONS, "not:bool"),
+            Function::StartsWith => (STRING_FUNCTIONS, "starts_with:str_str"),
+            Function::EndsWith => (STRING_FUNCTIONS, "ends_with:str_str"),
+            Function::Contains => (STRING_FUNCTIONS, "contains:str_str"),
+        }
+    }
+}
+
+/// The functions used by a plan, in the order of their anchors.
+#[derive(Debug, Default)]
+struct Functions {
+    used: Vec<Function>,
+}
+
+impl Functions {
+    fn call(&mut self, function: Function, arguments: Vec<Expression>) -> Expression {
+        let index = match self.used.iter().position(|used| *used == function) {
+            Some(index) => index,
+            None => {
+                self.used.push(function);
+                self.used.len() - 1
+            }
+        };
+        Expression::ScalarFunction(ScalarFunction {
+            function_reference: index as u32 + 1,
+            arguments: arguments.into_iter().map(FunctionArgument::Value).collect(),
+            output_type: Type::Bool(TypeInfo {
+                nullability: Nullability::Nullable,
+            }),
+        })
+    }
+
+    fn extensions(&self) -> (Vec<ExtensionUri>, Vec<Extension>) {
+        let mut uris: Vec<&str> = vec![];
+        let mut extensions = vec![];
+        for (index, function) in self.used.iter().enumerate() {
+            let (uri, name) = function.definition();
+            let uri_index = match uris.iter().position(|used| *used == uri) {
+                Some(uri_index) => uri_index,
+                None => {
+                    uris.push(uri);
+                    uris.len() - 1
+                }
+            };
+            extensions.push(Extension::ExtensionFunction(ExtensionFunction {
+                extension_uri_reference: uri_index as u32 + 1,
+                function_anchor: index as u32 + 1,
+                name: name.to_string(),
+            }));
+        }
+        let uris = uris
+            .into_iter()
+            .enumerate()
+            .map(|(index, uri)| ExtensionUri {
+                extension_uri_anchor: index as u32 + 1,
+                uri: uri.to_string(),
+            })
+            .collect();
+        (uris, extensions)
+    }
+}
@@ -0,0 +1,264 @@
+//! The parts of Substrait's plan messages that exported plans use.
+//!
+//! The types serialize to the canonical JSON form of the protobuf messages of the same names,
+//! which Substrait consumers can parse into their own representation of plans.
+use serde::{Serialize, Serializer};
+
+/// The version of the Substrait specification that exported plans follow.
+pub const SUBSTRAIT_MINOR_VERSION: u32 = 29;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Plan {
+    pub version: Version,
+    pub extension_uris: Vec<ExtensionUri>,
+    pub extensions: Vec<Extension>,
+    pub relations: Vec<PlanRel>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Version {
+    pub minor_number: u32,
+    pub producer: String,
+}
+
+/// A YAML file of function definitions, referenced by its anchor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionUri {
+    pub extension_uri_anchor: u32,
+    pub uri: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Extension {
+    ExtensionFunction(ExtensionFunction),
+}
+
+/// A function from an extension file, referenced by its anchor in scalar function expressions.
+///
+/// Its name includes the short names of its argument types, like `equal:any_any`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionFunction {
+    pub extension_uri_reference: u32,
+    pub function_anchor: u32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PlanRel {
+    Root(RelRoot),
+}
+
+/// The relation whose rows are the plan's results, along with the names of their columns.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelRoot {
+    pub input: Rel,
+    pub names: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Rel {
+    Read(Box<ReadRel>),
+    Filter(Box<FilterRel>),
+    Fetch(Box<FetchRel>),
+    Join(Box<JoinRel>),
+    Project(Box<ProjectRel>),
+}
+
+/// Reads the given columns of a table.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadRel {
+    pub base_schema: NamedStruct,
+    pub named_table: NamedTable,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedStruct {
+    pub names: Vec<String>,
+    #[serde(rename = "struct")]
+    pub struct_type: StructType,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructType {
+    pub types: Vec<Type>,
+    pub nullability: Nullability,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedTable {
+    pub names: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterRel {
+    pub input: Rel,
+    pub condition: Expression,
+}
+
+/// Skips the first `offset` rows of its input, and produces at most `count` of the rest.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchRel {
+    pub input: Rel,
+    #[serde(serialize_with = "serialize_int64")]
+    pub offset: i64,
+    #[serde(serialize_with = "serialize_int64")]
+    pub count: i64,
+}
+
+/// Joins two relations, whose rows are the columns of the left row followed by
+/// the columns of the right row.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JoinRel {
+    pub left: Rel,
+    pub right: Rel,
+    pub expression: Expression,
+    #[serde(rename = "type")]
+    pub join_type: JoinType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum JoinType {
+    #[serde(rename = "JOIN_TYPE_INNER")]
+    Inner,
+    #[serde(rename = "JOIN_TYPE_LEFT")]
+    Left,
+}
+
+/// Appends the values of its expressions to the columns of its input,
+/// then produces the columns given by the emit mapping.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectRel {
+    pub common: RelCommon,
+    pub input: Rel,
+    pub expressions: Vec<Expression>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelCommon {
+    pub emit: Emit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Emit {
+    pub output_mapping: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Type {
+    I64(TypeInfo),
+    Fp64(TypeInfo),
+    String(TypeInfo),
+    Bool(TypeInfo),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeInfo {
+    pub nullability: Nullability,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Nullability {
+    #[serde(rename = "NULLABILITY_NULLABLE")]
+    Nullable,
+    #[serde(rename = "NULLABILITY_REQUIRED")]
+    Required,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Expression {
+    Literal(Literal),
+    Selection(FieldReference),
+    ScalarFunction(ScalarFunction),
+    SingularOrList(Box<SingularOrList>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Literal {
+    Boolean(bool),
+    #[serde(serialize_with = "serialize_int64")]
+    I64(i64),
+    Fp64(f64),
+    String(String),
+}
+
+/// A reference to a column of the input row, by its index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldReference {
+    pub direct_reference: ReferenceSegment,
+    pub root_reference: RootReference,
+}
+
+impl FieldReference {
+    pub fn field(index: u32) -> Self {
+        Self {
+            direct_reference: ReferenceSegment::StructField(StructField { field: index }),
+            root_reference: RootReference {},
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReferenceSegment {
+    StructField(StructField),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructField {
+    pub field: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RootReference {}
+
+/// A call to the extension function with the given anchor.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScalarFunction {
+    pub function_reference: u32,
+    pub arguments: Vec<FunctionArgument>,
+    pub output_type: Type,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FunctionArgument {
+    Value(Expression),
+}
+
+/// Whether the value equals any of the options.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SingularOrList {
+    pub value: Expression,
+    pub options: Vec<Expression>,
+}
+
+/// Protobuf's JSON form represents 64-bit integers as strings.
+fn serialize_int64<S: Serializer>(value: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
@@ -0,0 +1,282 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use serde_json::{json, Value};
+
+use crate::{
+    frontend::parse,
+    interpreter::sql::{tests::SCHEMA, Join, RelationalMapping, StartingEdge, Table},
+    ir::FieldValue,
+    schema::Schema,
+};
+
+use super::{to_plan, SubstraitError};
+
+fn mapping() -> RelationalMapping {
+    RelationalMapping::new()
+        .with_table(
+            "User",
+            Table::new("users")
+                .with_column("id", "id")
+                .with_column("name", "name")
+                .with_column("age", "age")
+                .with_join("post", Join::new("Post", "id", "author_id")),
+        )
+        .with_table(
+            "Post",
+            Table::new("posts")
+                .with_column("id", "id")
+                .with_column("title", "title")
+                .with_column("likes", "likes")
+                .with_join("author", Join::new("User", "author_id", "id")),
+        )
+        .with_starting_edge(
+            "User",
+            StartingEdge::new("User")
+                .with_parameter_column("id", "id")
+                .with_limit_parameter("limit"),
+        )
+}
+
+fn lower(query: &str, arguments: BTreeMap<Arc<str>, FieldValue>) -> Result<Value, SubstraitError> {
+    let schema = Schema::parse(SCHEMA).unwrap();
+    let query = parse(&schema, query).unwrap();
+    to_plan(&schema, &mapping(), &query, &arguments).map(|plan| serde_json::to_value(plan).unwrap())
+}
+
+fn field(index: u32) -> Value {
+    json!({"selection": {"directReference": {"structField": {"field": index}}, "rootReference": {}}})
+}
+
+fn call(anchor: u32, arguments: Vec<Value>) -> Value {
+    let arguments: Vec<_> = arguments
+        .into_iter()
+        .map(|argument| json!({ "value": argument }))
+        .collect();
+    json!({
+        "scalarFunction": {
+            "functionReference": anchor,
+            "arguments": arguments,
+            "outputType": {"bool": {"nullability": "NULLABILITY_NULLABLE"}},
+        }
+    })
+}
+
+fn function(uri: u32, anchor: u32, name: &str) -> Value {
+    json!({
+        "extensionFunction": {
+            "extensionUriReference": uri,
+            "functionAnchor": anchor,
+            "name": name,
+        }
+    })
+}
+
+#[test]
+fn lowers_starting_edges_filters_and_outputs() {
+    let query = r#"
+{
+    User(id: 2) {
+        name @output
+        age @filter(op: ">=", value: ["$min"])
+    }
+}"#;
+    let plan = lower(query, btreemap! {"min".into() => 26.into()}).unwrap();
+    let read = json!({
+        "read": {
+            "baseSchema": {
+                "names": ["age", "id", "name"],
+                "struct": {
+                    "types": [
+                        {"i64": {"nullability": "NULLABILITY_NULLABLE"}},
+                        {"i64": {"nullability": "NULLABILITY_REQUIRED"}},
+                        {"string": {"nullability": "NULLABILITY_NULLABLE"}},
+                    ],
+                    "nullability": "NULLABILITY_REQUIRED",
+                },
+            },
+            "namedTable": {"names": ["users"]},
+        }
+    });
+    let edge_filter = json!({
+        "filter": {
+            "input": read,
+            "condition": call(1, vec![field(1), json!({"literal": {"i64": "2"}})]),
+        }
+    });
+    let filter = json!({
+        "filter": {
+            "input": edge_filter,
+            "condition": call(2, vec![field(0), json!({"literal": {"i64": "26"}})]),
+        }
+    });
+    assert_eq!(
+        json!({
+            "version": {"minorNumber": 29, "producer": "trustfall"},
+            "extensionUris": [{
+                "extensionUriAnchor": 1,
+                "uri": "https://github.com/substrait-io/substrait/blob/main/extensions/functions_comparison.yaml",
+            }],
+            "extensions": [
+                function(1, 1, "equal:any_any"),
+                function(1, 2, "gte:any_any"),
+            ],
+            "relations": [{
+                "root": {
+                    "input": {
+                        "project": {
+                            "common": {"emit": {"outputMapping": [3]}},
+                            "input": filter,
+                            "expressions": [field(2)],
+                        }
+                    },
+                    "names": ["name"],
+                }
+            }],
+        }),
+        plan,
+    );
+}
+
+#[test]
+fn lowers_optional_edges_to_guarded_left_joins() {
+    let query = r#"
+{
+    User {
+        name @output
+
+        post @optional {
+            title @output
+            likes @filter(op: ">", value: ["$min_likes"])
+        }
+    }
+}"#;
+    let plan = lower(query, btreemap! {"min_likes".into() => 1.into()}).unwrap();
+    let root = &plan["relations"][0]["root"];
+    assert_eq!(json!(["name", "title"]), root["names"]);
+
+    let project = &root["input"]["project"];
+    assert_eq!(
+        vec![field(1), field(4)],
+        project["expressions"].as_array().unwrap()[..]
+    );
+
+    // The filter on the optional vertex is satisfied when it doesn't exist.
+    let filter = &project["input"]["filter"];
+    assert_eq!(
+        call(
+            4,
+            vec![
+                call(3, vec![field(2)]),
+                call(2, vec![field(3), json!({"literal": {"i64": "1"}})]),
+            ]
+        ),
+        filter["condition"]
+    );
+
+    let join = &filter["input"]["join"];
+    assert_eq!("JOIN_TYPE_LEFT", join["type"]);
+    assert_eq!(call(1, vec![field(0), field(2)]), join["expression"]);
+    assert_eq!(
+        json!(["users"]),
+        join["left"]["read"]["namedTable"]["names"]
+    );
+
+    // The type of the column only used by the join comes from the column it joins with.
+    let posts = &join["right"]["read"]["baseSchema"];
+    assert_eq!(json!(["author_id", "likes", "title"]), posts["names"]);
+    assert_eq!(
+        json!({"i64": {"nullability": "NULLABILITY_NULLABLE"}}),
+        posts["struct"]["types"][0]
+    );
+
+    let names: Vec<_> = plan["extensions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|extension| extension["extensionFunction"]["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        vec!["equal:any_any", "gt:any_any", "is_null:any", "or:bool"],
+        names
+    );
+}
+
+#[test]
+fn lowers_limits_and_null_sensitive_filters() {
+    let query = r#"
+{
+    User(limit: 2) {
+        name @output @filter(op: "not_has_prefix", value: ["$prefix"])
+        age @filter(op: "one_of", value: ["$ages"])
+        id @filter(op: "!=", value: ["$id"])
+    }
+}"#;
+    let arguments = btreemap! {
+        "prefix".into() => "a".into(),
+        "ages".into() => vec![Some(30), None].into(),
+        "id".into() => 3.into(),
+    };
+    let plan = lower(query, arguments).unwrap();
+
+    // Filters apply to the rows the starting edge produces, so after its limit.
+    let filter = &plan["relations"][0]["root"]["input"]["project"]["input"]["filter"];
+    let fetch = &filter["input"]["fetch"];
+    assert_eq!(json!("0"), fetch["offset"]);
+    assert_eq!(json!("2"), fetch["count"]);
+    assert!(fetch["input"]["read"].is_object());
+
+    // Columns: age, id, name. Functions: not_has_prefix first, then one_of, then `!=`.
+    let starts_with = call(1, vec![field(2), json!({"literal": {"string": "a"}})]);
+    let one_of = json!({
+        "singularOrList": {
+            "value": field(0),
+            "options": [{"literal": {"i64": "30"}}],
+        }
+    });
+    let equals = call(5, vec![field(1), json!({"literal": {"i64": "3"}})]);
+    assert_eq!(
+        call(
+            7,
+            vec![
+                call(2, vec![starts_with]),
+                call(4, vec![call(3, vec![field(0)]), one_of]),
+                call(6, vec![equals]),
+            ]
+        ),
+        filter["condition"]
+    );
+}
+
+#[test]
+fn rejects_queries_without_plans() {
+    let unsupported = [
+        (
+            "{ User { post @fold { title @output } } }",
+            SubstraitError::UnsupportedQuery("@fold".to_string()),
+        ),
+        (
+            "{ User { friend { name @output } } }",
+            SubstraitError::UnmappedEdge("User".to_string(), "friend".to_string()),
+        ),
+        (
+            "{ User { nickname @output } }",
+            SubstraitError::UnmappedProperty("User".to_string(), "nickname".to_string()),
+        ),
+        (
+            r#"{ User { name @output @filter(op: "regex", value: ["$pattern"]) } }"#,
+            SubstraitError::UnsupportedQuery("the regex filter operation".to_string()),
+        ),
+    ];
+    for (query, expected) in unsupported {
+        let arguments = btreemap! {"pattern".into() => "a".into()};
+        assert_eq!(Err(expected), lower(query, arguments), "{query}");
+    }
+
+    assert_eq!(
+        Err(SubstraitError::MissingArgument("min".to_string())),
+        lower(
+            r#"{ User { name @output @filter(op: ">", value: ["$min"]) } }"#,
+            btreemap! {}
+        ),
+    );
+}
@@ -0,0 +1,448 @@
+use std::{cell::RefCell, collections::BTreeMap, convert::Infallible, sync::Arc};
+
+use crate::{
+    frontend::parse,
+    interpreter::execution::interpret_ir,
+    ir::{EdgeParameters, FieldValue},
+    schema::Schema,
+};
+
+use super::{
+    Condition, Join, Operator, ParameterStyle, RelationalAdapter, RelationalMapping, Select,
+    SqlAdapter, SqlVertex, StartingEdge, Table,
+};
+
+pub(super) const SCHEMA: &str = r#"
+schema {
+    query: RootSchemaQuery
+}
+directive @filter(op: String!, value: [String!]) on FIELD | INLINE_FRAGMENT
+directive @tag(name: String) on FIELD
+directive @output(name: String) on FIELD
+directive @optional on FIELD
+directive @recurse(depth: Int!) on FIELD
+directive @fold on FIELD
+directive @transform(op: String!) on FIELD
+
+type RootSchemaQuery {
+    User(id: Int, limit: Int): [User!]!
+    Newest: [User!]!
+}
+
+type User {
+    id: Int!
+    name: String
+    age: Int
+    nickname: String
+    post: [Post!]
+    friend: [User!]
+}
+
+type Post {
+    id: Int!
+    title: String
+    likes: Int
+    author: User!
+}
+"#;
+
+type Row = BTreeMap<&'static str, FieldValue>;
+
+/// An in-memory database, which evaluates statements the way a SQL database would
+/// and records the SQL of the statements it executes.
+struct Database {
+    mapping: RelationalMapping,
+    tables: BTreeMap<&'static str, Vec<Row>>,
+    statements: RefCell<Vec<(String, Vec<FieldValue>)>>,
+}
+
+impl Database {
+    fn new() -> Self {
+        let mapping = RelationalMapping::new()
+            .with_table(
+                "User",
+                Table::new("users")
+                    .with_column("id", "id")
+                    .with_column("name", "name")
+                    .with_column("age", "age")
+                    .with_join("post", Join::new("Post", "id", "author_id")),
+            )
+            .with_table(
+                "Post",
+                Table::new("posts")
+                    .with_column("id", "id")
+                    .with_column("title", "title")
+                    .with_column("likes", "likes")
+                    .with_join("author", Join::new("User", "author_id", "id")),
+            )
+            .with_starting_edge(
+                "User",
+                StartingEdge::new("User")
+                    .with_parameter_column("id", "id")
+                    .with_limit_parameter("limit"),
+            );
+
+        let user = |id: i64, name: &str, age: Option<i64>| -> Row {
+            btreemap! {
+                "id" => id.into(),
+                "name" => name.into(),
+                "age" => age.into(),
+            }
+        };
+        let post = |id: i64, author_id: i64, title: &str, likes: i64| -> Row {
+            btreemap! {
+                "id" => id.into(),
+                "author_id" => author_id.into(),
+                "title" => title.into(),
+                "likes" => likes.into(),
+            }
+        };
+        let tables = btreemap! {
+            "users" => vec![
+                user(1, "alice", Some(30)),
+                user(2, "bob", Some(25)),
+                user(3, "carol", None),
+            ],
+            "posts" => vec![
+                post(10, 1, "hello", 5),
+                post(11, 1, "again", 0),
+                post(12, 3, "hi", 7),
+            ],
+        };
+
+        Self {
+            mapping,
+            tables,
+            statements: Default::default(),
+        }
+    }
+
+    fn statements(&self) -> Vec<(String, Vec<FieldValue>)> {
+        self.statements.borrow().clone()
+    }
+}
+
+fn matches(condition: &Condition, row: &Row) -> bool {
+    let value = |column: &String| row.get(column.as_str()).unwrap_or(&FieldValue::Null);
+    match condition {
+        Condition::Compare {
+            column,
+            operator,
+            value: expected,
+        } => {
+            let value = value(column);
+            !matches!(value, FieldValue::Null)
+                && match operator {
+                    Operator::Equals => value == expected,
+                    Operator::LessThan => value < expected,
+                    Operator::LessThanOrEqual => value <= expected,
+                    Operator::GreaterThan => value > expected,
+                    Operator::GreaterThanOrEqual => value >= expected,
+                }
+        }
+        Condition::In { column, values } => values.contains(value(column)),
+        Condition::IsNull(column) => matches!(value(column), FieldValue::Null),
+        Condition::IsNotNull(column) => !matches!(value(column), FieldValue::Null),
+        Condition::And(conditions) => conditions.iter().all(|c| matches(c, row)),
+        Condition::Or(conditions) => conditions.iter().any(|c| matches(c, row)),
+    }
+}
+
+impl RelationalAdapter for Database {
+    type Error = Infallible;
+
+    fn mapping(&self) -> &RelationalMapping {
+        &self.mapping
+    }
+
+    fn execute(&self, select: &Select) -> Result<Vec<Vec<FieldValue>>, Self::Error> {
+        self.statements
+            .borrow_mut()
+            .push(select.to_sql(ParameterStyle::QuestionMark));
+
+        let rows = self.tables[select.table.as_str()]
+            .iter()
+            .filter(|row| select.conditions.iter().all(|c| matches(c, row)))
+            .take(select.limit.map_or(usize::MAX, |limit| limit as usize))
+            .map(|row| {
+                select
+                    .columns
+                    .iter()
+                    .map(|column| row[column.as_str()].clone())
+                    .collect()
+            })
+            .collect();
+        Ok(rows)
+    }
+
+    fn resolve_unmapped_starting_vertices(
+        &self,
+        edge_name: &str,
+        _parameters: &EdgeParameters,
+    ) -> Vec<SqlVertex> {
+        // The newest user is the one with the largest id.
+        assert_eq!("Newest", edge_name);
+        let newest = self.tables["users"]
+            .iter()
+            .max_by_key(|row| row["id"].as_i64())
+            .unwrap();
+        vec![user_vertex(newest)]
+    }
+
+    fn resolve_unmapped_property(&self, vertex: &SqlVertex, property_name: &str) -> FieldValue {
+        assert_eq!("nickname", property_name);
+        let name = vertex.get("name").and_then(FieldValue::as_str).unwrap();
+        name.to_uppercase().into()
+    }
+
+    fn resolve_unmapped_neighbors(
+        &self,
+        vertex: &SqlVertex,
+        edge_name: &str,
+        _parameters: &EdgeParameters,
+    ) -> Vec<SqlVertex> {
+        // Everyone is friends with the next user, and the last user with the first one.
+        assert_eq!("friend", edge_name);
+        let users = &self.tables["users"];
+        let id = vertex.get("id").and_then(FieldValue::as_i64).unwrap();
+        let friend = &users[id as usize % users.len()];
+        vec![user_vertex(friend)]
+    }
+}
+
+fn user_vertex(row: &Row) -> SqlVertex {
+    let values = row
+        .iter()
+        .map(|(column, value)| (column.to_string(), value.clone()))
+        .collect();
+    SqlVertex::new("User", values)
+}
+
+fn run(
+    adapter: &SqlAdapter<Database>,
+    query: &str,
+    variables: BTreeMap<Arc<str>, FieldValue>,
+) -> Vec<BTreeMap<Arc<str>, FieldValue>> {
+    let schema = Schema::parse(SCHEMA).unwrap();
+    let query = parse(&schema, query).unwrap();
+    interpret_ir(Arc::new(adapter.clone()), query, Arc::new(variables))
+        .unwrap()
+        .collect()
+}
+
+fn statement(sql: &str, parameters: Vec<FieldValue>) -> (String, Vec<FieldValue>) {
+    (sql.to_string(), parameters)
+}
+
+#[test]
+fn translates_filters_on_starting_vertices() {
+    let adapter = SqlAdapter::new(Database::new());
+    let query = r#"
+{
+    User {
+        name @output
+        age @filter(op: ">=", value: ["$min"])
+    }
+}"#;
+    let results = run(&adapter, query, btreemap! {"min".into() => 26.into()});
+    assert_eq!(vec![btreemap! {"name".into() => "alice".into()}], results);
+    assert_eq!(
+        vec![statement(
+            r#"SELECT "age", "id", "name" FROM "users" WHERE "age" >= ? OR "age" IS NULL"#,
+            vec![26.into()]
+        )],
+        adapter.inner().statements(),
+    );
+}
+
+#[test]
+fn translates_edge_parameters_and_limits() {
+    let adapter = SqlAdapter::new(Database::new());
+    let query = r#"
+{
+    User(id: 2) {
+        name @output
+    }
+}"#;
+    let results = run(&adapter, query, btreemap! {});
+    assert_eq!(vec![btreemap! {"name".into() => "bob".into()}], results);
+
+    // Filters aren't translated when the edge has a limit, since the edge's vertices
+    // are the first rows of the table rather than the first rows that match the filters.
+    let query = r#"
+{
+    User(limit: 2) {
+        name @output @filter(op: "!=", value: ["$name"])
+    }
+}"#;
+    let results = run(&adapter, query, btreemap! {"name".into() => "alice".into()});
+    assert_eq!(vec![btreemap! {"name".into() => "bob".into()}], results);
+
+    assert_eq!(
+        vec![
+            statement(
+                r#"SELECT "age", "id", "name" FROM "users" WHERE "id" = ?"#,
+                vec![2.into()]
+            ),
+            statement(r#"SELECT "age", "id", "name" FROM "users" LIMIT 2"#, vec![]),
+        ],
+        adapter.inner().statements(),
+    );
+}
+
+#[test]
+fn loads_joined_vertices_in_batches() {
+    let adapter = SqlAdapter::new(Database::new()).with_batch_size(2);
+    let query = r#"
+{
+    User {
+        name @output
+
+        post {
+            title @output
+            likes @filter(op: ">", value: ["$min_likes"])
+        }
+    }
+}"#;
+    let results = run(&adapter, query, btreemap! {"min_likes".into() => 1.into()});
+    assert_eq!(
+        vec![
+            btreemap! {"name".into() => "alice".into(), "title".into() => "hello".into()},
+            btreemap! {"name".into() => "carol".into(), "title".into() => "hi".into()},
+        ],
+        results
+    );
+    assert_eq!(
+        vec![
+            statement(r#"SELECT "age", "id", "name" FROM "users""#, vec![]),
+            statement(
+                r#"SELECT "author_id", "id", "likes", "title" FROM "posts" WHERE ("likes" > ? OR "likes" IS NULL) AND "author_id" IN (?, ?)"#,
+                vec![1.into(), 1.into(), 2.into()]
+            ),
+            statement(
+                r#"SELECT "author_id", "id", "likes", "title" FROM "posts" WHERE ("likes" > ? OR "likes" IS NULL) AND "author_id" IN (?)"#,
+                vec![1.into(), 3.into()]
+            ),
+        ],
+        adapter.inner().statements(),
+    );
+}
+
+#[test]
+fn resolves_unmapped_fields_with_the_adapter() {
+    let adapter = SqlAdapter::new(Database::new());
+    let query = r#"
+{
+    User(id: 3) {
+        nickname @output
+
+        friend {
+            friend: name @output
+
+            post @fold {
+                titles: title @output
+            }
+        }
+    }
+}"#;
+    let results = run(&adapter, query, btreemap! {});
+    assert_eq!(
+        vec![btreemap! {
+            "nickname".into() => "CAROL".into(),
+            "friend".into() => "alice".into(),
+            "titles".into() => vec!["hello", "again"].into(),
+        }],
+        results
+    );
+}
+
+#[test]
+fn resolves_unmapped_starting_edges_with_the_adapter() {
+    let adapter = SqlAdapter::new(Database::new());
+    let query = r#"
+{
+    Newest {
+        name @output
+        nickname @output
+
+        post {
+            title @output
+        }
+    }
+}"#;
+    let results = run(&adapter, query, btreemap! {});
+    assert_eq!(
+        vec![btreemap! {
+            "name".into() => "carol".into(),
+            "nickname".into() => "CAROL".into(),
+            "title".into() => "hi".into(),
+        }],
+        results
+    );
+
+    // Only the posts of the unmapped edge's vertex are loaded with SQL.
+    assert_eq!(
+        vec![statement(
+            "SELECT \"author_id\", \"id\", \"likes\", \"title\" FROM \"posts\" \
+            WHERE \"author_id\" IN (?)",
+            vec![3.into()]
+        )],
+        adapter.inner().statements(),
+    );
+}
+
+#[test]
+fn skips_statements_for_unsatisfiable_filters() {
+    let adapter = SqlAdapter::new(Database::new());
+    let query = r#"
+{
+    User {
+        name @output
+        id @filter(op: "=", value: ["$first"]) @filter(op: "=", value: ["$second"])
+    }
+}"#;
+    let variables = btreemap! {"first".into() => 1.into(), "second".into() => 2.into()};
+    assert!(run(&adapter, query, variables).is_empty());
+    assert!(adapter.inner().statements().is_empty());
+}
+
+#[test]
+fn renders_sql() {
+    let select = Select {
+        table: "my \"table\"".to_string(),
+        columns: vec!["a".to_string(), "b".to_string()],
+        conditions: vec![
+            Condition::Or(vec![
+                Condition::In {
+                    column: "a".to_string(),
+                    values: vec![1.into(), 2.into()],
+                },
+                Condition::IsNull("a".to_string()),
+            ]),
+            Condition::And(vec![
+                Condition::Compare {
+                    column: "b".to_string(),
+                    operator: Operator::GreaterThan,
+                    value: "x".into(),
+                },
+                Condition::Compare {
+                    column: "b".to_string(),
+                    operator: Operator::LessThanOrEqual,
+                    value: "y".into(),
+                },
+            ]),
+            Condition::In {
+                column: "b".to_string(),
+                values: vec![],
+            },
+        ],
+        limit: Some(5),
+    };
+    assert_eq!(
+        (
+            r#"SELECT "a", "b" FROM "my ""table""" WHERE ("a" IN ($1, $2) OR "a" IS NULL) AND ("b" > $3 AND "b" <= $4) AND 1 = 0 LIMIT 5"#.to_string(),
+            vec![1.into(), 2.into(), "x".into(), "y".into()],
+        ),
+        select.to_sql(ParameterStyle::Numbered),
+    );
+}
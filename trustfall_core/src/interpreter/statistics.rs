@@ -0,0 +1,270 @@
+//! Statistics observed while executing queries, used to plan later executions of them.
+//!
+//! Executing a query with [`interpret_ir_with_statistics`] counts, for each of its edges
+//! and folds, how many results reached the expansion, how many neighbors it produced,
+//! and how many of them remained after the filters at the neighbor. Once the execution's
+//! results are dropped, those counts are added to the [`StatisticsCache`] under the query's
+//! [`QueryHash`], and later executions of equivalent queries order their expansions
+//! by the observed fan-outs and selectivities instead of the schema's `@cost` annotations.
+//!
+//! The cache's statistics can be [exported](StatisticsCache::export) and
+//! [imported](StatisticsCache::import), for example to persist them across processes.
+//!
+//! [`interpret_ir_with_statistics`]: super::execution::interpret_ir_with_statistics
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ir::{canonical::QueryHash, EdgeKind, Eid, IndexedQuery, Vid};
+
+/// What was observed while expanding one edge or fold of a query.
+///
+/// For folds, every result that reaches the fold produces one result carrying its folded
+/// values, and only the fold's post-filters, such as `@transform(op: "count") @filter(...)`,
+/// discard results. So their fan-out is always 1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpansionStatistics {
+    /// The number of results that reached the expansion.
+    pub sources: u64,
+
+    /// The number of results the expansion produced, before the filters at its neighbor.
+    pub neighbors: u64,
+
+    /// The number of results that remained after the filters at the expansion's neighbor.
+    pub retained: u64,
+}
+
+impl ExpansionStatistics {
+    /// The average number of neighbors produced for each result reaching the expansion,
+    /// or `None` if none have been observed.
+    pub fn fan_out(&self) -> Option<f64> {
+        ratio(self.neighbors, self.sources)
+    }
+
+    /// The fraction of the expansion's neighbors that remained after the filters
+    /// at the neighbor, or `None` if none have been observed.
+    pub fn selectivity(&self) -> Option<f64> {
+        ratio(self.retained, self.neighbors)
+    }
+
+    /// The average number of results remaining after the expansion for each result
+    /// that reached it, or `None` if none have been observed.
+    pub fn retained_per_source(&self) -> Option<f64> {
+        ratio(self.retained, self.sources)
+    }
+
+    fn merge(&mut self, other: &ExpansionStatistics) {
+        self.sources = self.sources.saturating_add(other.sources);
+        self.neighbors = self.neighbors.saturating_add(other.neighbors);
+        self.retained = self.retained.saturating_add(other.retained);
+    }
+}
+
+fn ratio(numerator: u64, denominator: u64) -> Option<f64> {
+    (denominator > 0).then(|| numerator as f64 / denominator as f64)
+}
+
+/// What was observed while executing a query, summed over its executions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryStatistics {
+    /// The number of executions whose statistics are included.
+    pub executions: u64,
+
+    /// The statistics of each edge and fold of the query.
+    pub expansions: BTreeMap<Eid, ExpansionStatistics>,
+}
+
+impl QueryStatistics {
+    /// Add the other statistics of the same query to these.
+    pub fn merge(&mut self, other: &QueryStatistics) {
+        self.executions = self.executions.saturating_add(other.executions);
+        for (eid, expansion) in &other.expansions {
+            self.expansions.entry(*eid).or_default().merge(expansion);
+        }
+    }
+}
+
+/// The statistics observed while executing queries, keyed by the queries' hashes.
+///
+/// Clones share the same statistics, so one cache may be used by many executions,
+/// including concurrent ones.
+#[derive(Debug, Clone, Default)]
+pub struct StatisticsCache {
+    queries: Arc<Mutex<BTreeMap<QueryHash, QueryStatistics>>>,
+}
+
+impl StatisticsCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The statistics observed for the query with this hash, if it's been executed.
+    pub fn get(&self, query: QueryHash) -> Option<QueryStatistics> {
+        self.lock().get(&query).cloned()
+    }
+
+    /// Add the statistics to those of the query with this hash.
+    pub fn record(&self, query: QueryHash, statistics: &QueryStatistics) {
+        self.lock().entry(query).or_default().merge(statistics);
+    }
+
+    /// All the statistics in the cache, for example to persist them.
+    pub fn export(&self) -> BTreeMap<QueryHash, QueryStatistics> {
+        self.lock().clone()
+    }
+
+    /// Add statistics, such as those [exported](Self::export) from another cache,
+    /// to those in this cache.
+    pub fn import(&self, statistics: BTreeMap<QueryHash, QueryStatistics>) {
+        let mut queries = self.lock();
+        for (query, statistics) in statistics {
+            queries.entry(query).or_default().merge(&statistics);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<QueryHash, QueryStatistics>> {
+        self.queries
+            .lock()
+            .expect("statistics cache lock was poisoned")
+    }
+}
+
+#[derive(Debug, Default)]
+struct ExpansionCounters {
+    sources: AtomicU64,
+    neighbors: AtomicU64,
+    retained: AtomicU64,
+}
+
+/// The counts of one execution, added to the cache once every iterator of the execution
+/// that could still count has been dropped.
+#[derive(Debug)]
+pub(super) struct Observations {
+    cache: StatisticsCache,
+    query: QueryHash,
+    counters: BTreeMap<Eid, ExpansionCounters>,
+
+    /// The edge leading to each vertex, other than the roots of the query's components.
+    edge_destinations: BTreeMap<Vid, Eid>,
+}
+
+impl Observations {
+    pub(super) fn new(
+        cache: StatisticsCache,
+        query: QueryHash,
+        indexed_query: &IndexedQuery,
+    ) -> Self {
+        let counters = indexed_query
+            .eids
+            .keys()
+            .map(|eid| (*eid, Default::default()))
+            .collect();
+        let edge_destinations = indexed_query
+            .eids
+            .iter()
+            .filter_map(|(eid, kind)| match kind {
+                EdgeKind::Regular(edge) => Some((edge.to_vid, *eid)),
+                EdgeKind::Fold(_) => None,
+            })
+            .collect();
+        Self {
+            cache,
+            query,
+            counters,
+            edge_destinations,
+        }
+    }
+
+    /// The edge whose neighbors are recorded as this vertex, if it isn't a component's root.
+    pub(super) fn edge_to(&self, vid: Vid) -> Option<Eid> {
+        self.edge_destinations.get(&vid).copied()
+    }
+
+    pub(super) fn count_source(&self, eid: Eid) {
+        self.counters[&eid].sources.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn count_neighbor(&self, eid: Eid) {
+        self.counters[&eid]
+            .neighbors
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn count_retained(&self, eid: Eid) {
+        self.counters[&eid].retained.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Drop for Observations {
+    fn drop(&mut self) {
+        let statistics = QueryStatistics {
+            executions: 1,
+            expansions: self
+                .counters
+                .iter()
+                .map(|(eid, counters)| {
+                    let statistics = ExpansionStatistics {
+                        sources: counters.sources.load(Ordering::Relaxed),
+                        neighbors: counters.neighbors.load(Ordering::Relaxed),
+                        retained: counters.retained.load(Ordering::Relaxed),
+                    };
+                    (*eid, statistics)
+                })
+                .collect(),
+        };
+        self.cache.record(self.query, &statistics);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, num::NonZeroUsize};
+
+    use crate::ir::{canonical::QueryHash, Eid};
+
+    use super::{ExpansionStatistics, QueryStatistics, StatisticsCache};
+
+    #[test]
+    fn statistics_are_merged_and_round_trip_through_exports() {
+        let eid = Eid::new(NonZeroUsize::new(1).unwrap());
+        let observed = QueryStatistics {
+            executions: 1,
+            expansions: BTreeMap::from([(
+                eid,
+                ExpansionStatistics {
+                    sources: 2,
+                    neighbors: 8,
+                    retained: 2,
+                },
+            )]),
+        };
+        let query = QueryHash::from_value(0xabcd);
+
+        let cache = StatisticsCache::new();
+        cache.record(query, &observed);
+        cache.clone().record(query, &observed);
+        let statistics = cache.get(query).unwrap();
+        assert_eq!(2, statistics.executions);
+        let expansion = statistics.expansions[&eid];
+        assert_eq!(Some(4.0), expansion.fan_out());
+        assert_eq!(Some(0.25), expansion.selectivity());
+        assert_eq!(Some(1.0), expansion.retained_per_source());
+        assert_eq!(None, ExpansionStatistics::default().fan_out());
+
+        let exported = serde_json::to_string(&cache.export()).unwrap();
+        let imported = StatisticsCache::new();
+        imported.import(serde_json::from_str(&exported).unwrap());
+        assert_eq!(cache.export(), imported.export());
+        assert_eq!(None, imported.get(QueryHash::from_value(0)));
+    }
+}
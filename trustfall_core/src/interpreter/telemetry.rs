@@ -0,0 +1,671 @@
+//! Recording OpenTelemetry spans and metrics for query execution.
+//!
+//! [`QueryTelemetry`] records a span for each query, with child spans for its phases:
+//! - `trustfall.parse`, while the query is parsed and validated against the schema;
+//! - `trustfall.execute`, while its arguments are checked and its execution is planned;
+//! - `trustfall.results`, from then until its results iterator is exhausted or dropped.
+//!
+//! [`TelemetryAdapter`] wraps an adapter to count calls to its resolvers, and record how long
+//! each call spends in the adapter, including while the interpreter iterates over its outputs:
+//! - `trustfall.adapter.calls`, a counter of resolver calls;
+//! - `trustfall.adapter.duration`, the seconds each call spends in the adapter.
+//!
+//! Both metrics have the `trustfall.resolver` attribute naming the resolver, and attributes
+//! naming the type and the property, edge, or coercion target the call resolves.
+//!
+//! Spans and metrics are recorded with the global tracer and meter providers by default.
+use std::{
+    cell::Cell,
+    collections::BTreeMap,
+    fmt::Display,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use opentelemetry::{
+    global::{self, BoxedTracer},
+    metrics::{Counter, Meter, Unit, ValueRecorder},
+    trace::{Span, StatusCode, TraceContextExt, Tracer},
+    Context, KeyValue,
+};
+
+use crate::{
+    frontend::{error::FrontendError, parse},
+    ir::{EdgeParameters, FieldValue, IndexedQuery},
+    schema::Schema,
+};
+
+use super::{
+    error::QueryArgumentsError, execution::interpret_ir, Adapter, ContextIterator,
+    ContextOutcomeIterator, ResolveEdgeInfo, ResolveInfo, VertexIterator,
+};
+
+const INSTRUMENTATION_NAME: &str = "trustfall";
+
+/// Records the spans of a single query's execution.
+///
+/// ```rust
+/// # use std::{collections::BTreeMap, sync::Arc};
+/// # use trustfall_core::{
+/// #     interpreter::{telemetry::{QueryTelemetry, TelemetryAdapter}, Adapter},
+/// #     ir::FieldValue,
+/// #     schema::Schema,
+/// # };
+/// fn run<'a>(
+///     schema: &Schema,
+///     adapter: Arc<impl Adapter<'a> + 'a>,
+///     query: &str,
+/// ) -> Vec<BTreeMap<Arc<str>, FieldValue>> {
+///     let telemetry = QueryTelemetry::new();
+///     let query = telemetry.parse(schema, query).unwrap();
+///
+///     let adapter = Arc::new(TelemetryAdapter::new(adapter));
+///     telemetry
+///         .execute(adapter, query, Arc::new(BTreeMap::new()))
+///         .unwrap()
+///         .collect()
+/// }
+/// ```
+#[derive(Debug)]
+pub struct QueryTelemetry<T = BoxedTracer> {
+    tracer: T,
+
+    /// The current context, with the query's span as its active span.
+    context: Context,
+}
+
+impl QueryTelemetry {
+    /// Start recording the query's span with the global tracer provider,
+    /// as a child of the current context's active span if any.
+    pub fn new() -> Self {
+        Self::with_tracer(global::tracer(INSTRUMENTATION_NAME))
+    }
+}
+
+impl<T> QueryTelemetry<T>
+where
+    T: Tracer,
+    T::Span: Send + Sync + 'static,
+{
+    pub fn with_tracer(tracer: T) -> Self {
+        let span = tracer.start("trustfall.query");
+        let context = Context::current_with_span(span);
+        Self { tracer, context }
+    }
+
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Parse the query, recording the `trustfall.parse` span.
+    ///
+    /// The query's span ends if the query is invalid.
+    pub fn parse(&self, schema: &Schema, query: &str) -> Result<Arc<IndexedQuery>, FrontendError> {
+        let mut span = self
+            .tracer
+            .start_with_context("trustfall.parse", &self.context);
+        match parse(schema, query) {
+            Ok(query) => {
+                span.end();
+                let root_edge = query.ir_query.root_name.to_string();
+                self.context
+                    .span()
+                    .set_attribute(KeyValue::new("trustfall.root_edge", root_edge));
+                Ok(query)
+            }
+            Err(e) => {
+                fail(&mut span, &e);
+                self.fail(&e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Execute the query like [`interpret_ir`], recording the `trustfall.execute` span,
+    /// then the `trustfall.results` span until the returned iterator is exhausted or dropped.
+    ///
+    /// The query's span ends along with the `trustfall.results` span, or if the arguments
+    /// aren't valid for the query.
+    #[allow(clippy::type_complexity)]
+    pub fn execute<'query, AdapterT: Adapter<'query> + 'query>(
+        self,
+        adapter: Arc<AdapterT>,
+        indexed_query: Arc<IndexedQuery>,
+        arguments: Arc<BTreeMap<Arc<str>, FieldValue>>,
+    ) -> Result<
+        Box<dyn Iterator<Item = BTreeMap<Arc<str>, FieldValue>> + 'query>,
+        QueryArgumentsError,
+    > {
+        let mut span = self
+            .tracer
+            .start_with_context("trustfall.execute", &self.context);
+        let results = match interpret_ir(adapter, indexed_query, arguments) {
+            Ok(results) => results,
+            Err(e) => {
+                fail(&mut span, &e);
+                self.fail(&e);
+                return Err(e);
+            }
+        };
+        span.end();
+
+        let span = self
+            .tracer
+            .start_with_context("trustfall.results", &self.context);
+        Ok(Box::new(TracedResults {
+            results,
+            count: 0,
+            span: Some(span),
+            query_context: self.context,
+        }))
+    }
+
+    fn fail(&self, error: &impl Display) {
+        let span = self.context.span();
+        span.set_status(StatusCode::Error, error.to_string());
+        span.end();
+    }
+}
+
+impl Default for QueryTelemetry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn fail(span: &mut impl Span, error: &impl Display) {
+    span.set_status(StatusCode::Error, error.to_string());
+    span.end();
+}
+
+struct TracedResults<'query, S: Span> {
+    results: Box<dyn Iterator<Item = BTreeMap<Arc<str>, FieldValue>> + 'query>,
+    count: u64,
+    span: Option<S>,
+    query_context: Context,
+}
+
+impl<'query, S: Span> TracedResults<'query, S> {
+    fn finish(&mut self) {
+        if let Some(mut span) = self.span.take() {
+            span.set_attribute(KeyValue::new("trustfall.result_count", self.count as i64));
+            span.end();
+            self.query_context.span().end();
+        }
+    }
+}
+
+impl<'query, S: Span> Iterator for TracedResults<'query, S> {
+    type Item = BTreeMap<Arc<str>, FieldValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.results.next();
+        match result {
+            Some(_) => self.count += 1,
+            None => self.finish(),
+        }
+        result
+    }
+}
+
+impl<'query, S: Span> Drop for TracedResults<'query, S> {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Instruments {
+    calls: Counter<u64>,
+    duration: ValueRecorder<f64>,
+}
+
+/// An adapter that records metrics about the calls to the adapter it wraps.
+#[derive(Debug)]
+pub struct TelemetryAdapter<AdapterT> {
+    inner: Arc<AdapterT>,
+    instruments: Instruments,
+}
+
+impl<AdapterT> Clone for TelemetryAdapter<AdapterT> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            instruments: self.instruments.clone(),
+        }
+    }
+}
+
+impl<AdapterT> TelemetryAdapter<AdapterT> {
+    /// Record metrics with the global meter provider.
+    pub fn new(adapter: Arc<AdapterT>) -> Self {
+        Self::with_meter(adapter, &global::meter(INSTRUMENTATION_NAME))
+    }
+
+    pub fn with_meter(adapter: Arc<AdapterT>, meter: &Meter) -> Self {
+        let calls = meter
+            .u64_counter("trustfall.adapter.calls")
+            .with_description("The number of calls to the adapter's resolvers")
+            .init();
+        let duration = meter
+            .f64_value_recorder("trustfall.adapter.duration")
+            .with_description(
+                "The time each resolver call spends in the adapter, \
+                including while its outputs are iterated over",
+            )
+            .with_unit(Unit::new("s"))
+            .init();
+        Self {
+            inner: adapter,
+            instruments: Instruments { calls, duration },
+        }
+    }
+
+    pub fn inner(&self) -> &Arc<AdapterT> {
+        &self.inner
+    }
+
+    fn start_call(&self, attributes: Vec<KeyValue>) -> Rc<CallTimer> {
+        self.instruments.calls.add(1, &attributes);
+        Rc::new(CallTimer {
+            elapsed: Default::default(),
+            upstream: Default::default(),
+            duration: self.instruments.duration.clone(),
+            attributes,
+        })
+    }
+}
+
+/// Measures the time a resolver call spends in the adapter, recording it once the call's
+/// outputs are all dropped.
+///
+/// Iterating over the call's outputs may also iterate over its input contexts, which runs
+/// other parts of the query; the time spent doing so isn't counted.
+struct CallTimer {
+    elapsed: Cell<Duration>,
+    upstream: Cell<Duration>,
+    duration: ValueRecorder<f64>,
+    attributes: Vec<KeyValue>,
+}
+
+impl CallTimer {
+    fn time<T>(&self, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let value = f();
+        self.elapsed.set(self.elapsed.get() + start.elapsed());
+        value
+    }
+
+    /// Time the iterator's `next()` calls as time spent in the adapter, or outside of it.
+    fn wrap<'a, I: Iterator + 'a>(
+        self: &Rc<Self>,
+        iterator: I,
+        upstream: bool,
+    ) -> Box<dyn Iterator<Item = I::Item> + 'a> {
+        Box::new(TimedIterator {
+            inner: iterator,
+            timer: self.clone(),
+            upstream,
+        })
+    }
+}
+
+impl Drop for CallTimer {
+    fn drop(&mut self) {
+        let own_time = self.elapsed.get().saturating_sub(self.upstream.get());
+        self.duration
+            .record(own_time.as_secs_f64(), &self.attributes);
+    }
+}
+
+struct TimedIterator<I> {
+    inner: I,
+    timer: Rc<CallTimer>,
+    upstream: bool,
+}
+
+impl<I: Iterator> Iterator for TimedIterator<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = Instant::now();
+        let item = self.inner.next();
+        let cell = if self.upstream {
+            &self.timer.upstream
+        } else {
+            &self.timer.elapsed
+        };
+        cell.set(cell.get() + start.elapsed());
+        item
+    }
+}
+
+fn call_attributes(
+    resolver: &'static str,
+    type_name: &str,
+    field: (&'static str, &str),
+) -> Vec<KeyValue> {
+    vec![
+        KeyValue::new("trustfall.resolver", resolver),
+        KeyValue::new("trustfall.type_name", type_name.to_string()),
+        KeyValue::new(field.0, field.1.to_string()),
+    ]
+}
+
+impl<'vertex, AdapterT: Adapter<'vertex> + 'vertex> Adapter<'vertex>
+    for TelemetryAdapter<AdapterT>
+{
+    type Vertex = AdapterT::Vertex;
+
+    fn resolve_starting_vertices(
+        &self,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        resolve_info: &ResolveInfo,
+    ) -> VertexIterator<'vertex, Self::Vertex> {
+        let timer = self.start_call(vec![
+            KeyValue::new("trustfall.resolver", "resolve_starting_vertices"),
+            KeyValue::new("trustfall.edge_name", edge_name.to_string()),
+        ]);
+        let vertices = timer.time(|| {
+            self.inner
+                .resolve_starting_vertices(edge_name, parameters, resolve_info)
+        });
+        timer.wrap(vertices, false)
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        type_name: &Arc<str>,
+        property_name: &Arc<str>,
+        resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, FieldValue> {
+        let timer = self.start_call(call_attributes(
+            "resolve_property",
+            type_name,
+            ("trustfall.property_name", property_name),
+        ));
+        let contexts = timer.wrap(contexts, true);
+        let values = timer.time(|| {
+            self.inner
+                .resolve_property(contexts, type_name, property_name, resolve_info)
+        });
+        timer.wrap(values, false)
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        type_name: &Arc<str>,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        resolve_info: &ResolveEdgeInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, VertexIterator<'vertex, Self::Vertex>> {
+        let timer = self.start_call(call_attributes(
+            "resolve_neighbors",
+            type_name,
+            ("trustfall.edge_name", edge_name),
+        ));
+        let contexts = timer.wrap(contexts, true);
+        let neighbors = timer.time(|| {
+            self.inner
+                .resolve_neighbors(contexts, type_name, edge_name, parameters, resolve_info)
+        });
+
+        let neighbors_timer = timer.clone();
+        let neighbors = neighbors
+            .map(move |(context, neighbors)| (context, neighbors_timer.wrap(neighbors, false)));
+        timer.wrap(neighbors, false)
+    }
+
+    fn resolve_coercion(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        type_name: &Arc<str>,
+        coerce_to_type: &Arc<str>,
+        resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, bool> {
+        let timer = self.start_call(call_attributes(
+            "resolve_coercion",
+            type_name,
+            ("trustfall.coerce_to_type", coerce_to_type),
+        ));
+        let contexts = timer.wrap(contexts, true);
+        let coercions = timer.time(|| {
+            self.inner
+                .resolve_coercion(contexts, type_name, coerce_to_type, resolve_info)
+        });
+        timer.wrap(coercions, false)
+    }
+
+    fn vertex_identity(&self, vertex: &Self::Vertex) -> Option<u64> {
+        self.inner.vertex_identity(vertex)
+    }
+
+    fn skip_revisited_vertices_in_recursion(&self) -> bool {
+        self.inner.skip_revisited_vertices_in_recursion()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use opentelemetry::{
+        metrics::{MeterProvider, NumberKind},
+        sdk::{
+            export::{
+                metrics::{CheckpointSet, Count, ExportKindSelector, Sum},
+                trace::SpanData,
+            },
+            metrics::{
+                aggregators::{ArrayAggregator, SumAggregator},
+                controllers, selectors,
+            },
+            trace::{Span as SdkSpan, SpanProcessor, Tracer, TracerProvider},
+        },
+        trace::{StatusCode, TraceResult, TracerProvider as _},
+        Context, Key, Value,
+    };
+
+    use crate::{numbers_interpreter::NumbersAdapter, schema::Schema};
+
+    use super::{QueryTelemetry, TelemetryAdapter};
+
+    #[derive(Debug, Clone, Default)]
+    struct SpanRecorder(Arc<Mutex<Vec<SpanData>>>);
+
+    impl SpanProcessor for SpanRecorder {
+        fn on_start(&self, _span: &mut SdkSpan, _cx: &Context) {}
+
+        fn on_end(&self, span: SpanData) {
+            self.0.lock().unwrap().push(span);
+        }
+
+        fn force_flush(&self) -> TraceResult<()> {
+            Ok(())
+        }
+
+        fn shutdown(&mut self) -> TraceResult<()> {
+            Ok(())
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema::parse(include_str!("../../test_data/schemas/numbers.graphql")).unwrap()
+    }
+
+    /// The returned provider must outlive the telemetry for its spans to be recorded.
+    fn telemetry() -> (QueryTelemetry<Tracer>, SpanRecorder, TracerProvider) {
+        let recorder = SpanRecorder::default();
+        let provider = TracerProvider::builder()
+            .with_span_processor(recorder.clone())
+            .build();
+        let tracer = provider.tracer("trustfall");
+        (QueryTelemetry::with_tracer(tracer), recorder, provider)
+    }
+
+    fn attribute<'a>(span: &'a SpanData, key: &'static str) -> Option<&'a Value> {
+        span.attributes.get(&Key::new(key))
+    }
+
+    #[test]
+    fn records_spans_for_query_phases() {
+        let schema = schema();
+        let (telemetry, recorder, _provider) = telemetry();
+        let query = telemetry
+            .parse(&schema, "{ Number(max: 3) { value @output } }")
+            .unwrap();
+        let adapter = Arc::new(NumbersAdapter::new());
+        let results = telemetry
+            .execute(adapter, query, Arc::new(BTreeMap::new()))
+            .unwrap();
+        assert!(recorder
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|span| span.name != "trustfall.results"));
+        assert_eq!(4, results.count());
+
+        let spans = recorder.0.lock().unwrap();
+        let names: Vec<_> = spans.iter().map(|span| span.name.as_ref()).collect();
+        assert_eq!(
+            vec![
+                "trustfall.parse",
+                "trustfall.execute",
+                "trustfall.results",
+                "trustfall.query"
+            ],
+            names
+        );
+
+        let query_span = &spans[3];
+        assert_eq!(
+            Some(&Value::from("Number")),
+            attribute(query_span, "trustfall.root_edge")
+        );
+        for span in &spans[..3] {
+            assert_eq!(query_span.span_context.span_id(), span.parent_span_id);
+            assert_eq!(StatusCode::Unset, span.status_code);
+        }
+        assert_eq!(
+            Some(&Value::I64(4)),
+            attribute(&spans[2], "trustfall.result_count")
+        );
+    }
+
+    #[test]
+    fn records_spans_for_failed_queries() {
+        let schema = schema();
+        let (telemetry, recorder, _provider) = telemetry();
+        assert!(telemetry
+            .parse(&schema, "{ Number(max: 3) { nonexistent @output } }")
+            .is_err());
+
+        let spans = recorder.0.lock().unwrap();
+        let names: Vec<_> = spans.iter().map(|span| span.name.as_ref()).collect();
+        assert_eq!(vec!["trustfall.parse", "trustfall.query"], names);
+        assert!(spans
+            .iter()
+            .all(|span| span.status_code == StatusCode::Error));
+    }
+
+    #[test]
+    fn records_adapter_call_metrics() {
+        let schema = schema();
+        let mut controller = controllers::pull(
+            Box::new(selectors::simple::Selector::Exact),
+            Box::new(ExportKindSelector::Cumulative),
+        )
+        .with_memory(true)
+        .with_cache_period(Duration::ZERO)
+        .build();
+        let meter = controller.provider().meter("trustfall", None);
+        let adapter = Arc::new(TelemetryAdapter::with_meter(
+            Arc::new(NumbersAdapter::new()),
+            &meter,
+        ));
+
+        let query = r#"
+{
+    Number(max: 2) {
+        value @output
+        successor {
+            next: value @output
+        }
+    }
+}"#;
+        let (telemetry, _, _provider) = telemetry();
+        let query = telemetry.parse(&schema, query).unwrap();
+        let results = telemetry
+            .execute(adapter, query, Arc::new(BTreeMap::new()))
+            .unwrap();
+        assert_eq!(3, results.count());
+
+        controller.collect().unwrap();
+        let mut calls = BTreeMap::new();
+        let mut durations = BTreeMap::new();
+        controller
+            .try_for_each(&ExportKindSelector::Cumulative, &mut |record| {
+                let attributes: Vec<_> = record
+                    .attributes()
+                    .iter()
+                    .map(|(key, value)| format!("{}={}", key.as_str(), value.as_str()))
+                    .collect();
+                let aggregator = record.aggregator().unwrap().as_any();
+                match record.descriptor().name() {
+                    "trustfall.adapter.calls" => {
+                        let sum = aggregator.downcast_ref::<SumAggregator>().unwrap().sum()?;
+                        calls.insert(attributes, sum.to_u64(&NumberKind::U64));
+                    }
+                    "trustfall.adapter.duration" => {
+                        let recorded = aggregator.downcast_ref::<ArrayAggregator>().unwrap();
+                        durations.insert(attributes, recorded.count()?);
+                    }
+                    name => unreachable!("{name}"),
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        let key = |attributes: &[&str]| -> Vec<String> {
+            let mut attributes: Vec<_> = attributes.iter().map(|a| a.to_string()).collect();
+            attributes.sort();
+            attributes
+        };
+        let expected = BTreeMap::from([
+            (
+                key(&[
+                    "trustfall.edge_name=Number",
+                    "trustfall.resolver=resolve_starting_vertices",
+                ]),
+                1,
+            ),
+            (
+                key(&[
+                    "trustfall.property_name=value",
+                    "trustfall.resolver=resolve_property",
+                    "trustfall.type_name=Number",
+                ]),
+                2,
+            ),
+            (
+                key(&[
+                    "trustfall.edge_name=successor",
+                    "trustfall.resolver=resolve_neighbors",
+                    "trustfall.type_name=Number",
+                ]),
+                1,
+            ),
+        ]);
+        assert_eq!(expected, calls);
+        assert_eq!(expected, durations);
+    }
+}
@@ -500,4 +500,12 @@ where
             }),
         )
     }
+
+    fn vertex_identity(&self, vertex: &Self::Vertex) -> Option<u64> {
+        self.inner.vertex_identity(vertex)
+    }
+
+    fn skip_revisited_vertices_in_recursion(&self) -> bool {
+        self.inner.skip_revisited_vertices_in_recursion()
+    }
 }
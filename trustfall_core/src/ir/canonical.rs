@@ -0,0 +1,231 @@
+//! Canonical forms of compiled queries, and stable hashes identifying them.
+//!
+//! Compiling a query already discards most of the ways in which equivalent query text
+//! can differ: whitespace, the order of edge arguments and directives, edge aliases
+//! that aren't used as output names, the names of tags, and comments other than
+//! output descriptions. Canonicalization additionally drops output descriptions, and
+//! normalizes the parts of the [`IRQuery`] whose order or multiplicity doesn't matter,
+//! so that equivalent queries have equal canonical forms and equal [`QueryHash`] values.
+//!
+//! The names of outputs and variables are part of a query's interface,
+//! so queries that differ only in those names are considered different.
+//! Fields are also not reordered, since their order determines the vertices' [`Vid`]s
+//! and `@tag` directives must precede their uses.
+//!
+//! [`Vid`]: super::Vid
+use std::{fmt::Display, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+
+use super::{json::QUERY_JSON_VERSION, IRFold, IRQuery, IRQueryComponent, IndexedQuery};
+
+/// A stable hash identifying a query, computed from its canonical form.
+///
+/// Equivalent queries have the same hash, as described in the
+/// [module-level documentation](self). Hashes are stable across processes and platforms,
+/// so they may be persisted and used as keys by caches, persisted-query systems,
+/// and audit logs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct QueryHash(u64);
+
+impl QueryHash {
+    /// The hash with the given [`value`](Self::value), such as one that was persisted.
+    pub fn from_value(value: u64) -> Self {
+        Self(value)
+    }
+
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Display for QueryHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl IRQuery {
+    /// The canonical form of this query, which is equal for all equivalent queries.
+    ///
+    /// The canonical form sorts the filters of each vertex and fold and removes duplicates
+    /// among them, sorts the tags imported by each fold, and drops the query's
+    /// [`warnings`](Self::warnings) and [`output_descriptions`](Self::output_descriptions),
+    /// which are diagnostics and documentation rather than part of the query.
+    /// It produces the same results as the original query.
+    pub fn canonicalize(&self) -> IRQuery {
+        IRQuery {
+            root_component: Arc::new(canonicalize_component(&self.root_component)),
+            output_descriptions: Default::default(),
+            warnings: vec![],
+            ..self.clone()
+        }
+    }
+
+    /// Compute the [`QueryHash`] of this query.
+    pub fn content_hash(&self) -> QueryHash {
+        // Hash the canonical form's representation in the versioned format of `ir::json`
+        // with FNV-1a, like schema fingerprints. Changes to that representation come with
+        // a new format version, which is hashed along with the query.
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in self.hashed_representation().bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        QueryHash(hash)
+    }
+
+    fn hashed_representation(&self) -> String {
+        let canonical = VersionedQuery {
+            version: QUERY_JSON_VERSION,
+            query: &self.canonicalize(),
+        };
+        serde_json::to_string(&canonical).expect("failed to serialize query")
+    }
+}
+
+/// The parts of the [`QueryJson`](super::json::QueryJson) representation of a query
+/// that don't depend on the schema it's compiled against.
+#[derive(Serialize)]
+struct VersionedQuery<'a> {
+    version: u64,
+    query: &'a IRQuery,
+}
+
+impl IndexedQuery {
+    /// Compute the [`QueryHash`] of this query. See [`IRQuery::content_hash`].
+    pub fn content_hash(&self) -> QueryHash {
+        self.ir_query.content_hash()
+    }
+}
+
+fn canonicalize_component(component: &IRQueryComponent) -> IRQueryComponent {
+    let mut component = component.clone();
+    for vertex in component.vertices.values_mut() {
+        sort_and_dedup(&mut vertex.filters);
+    }
+    for fold in component.folds.values_mut() {
+        *fold = Arc::new(canonicalize_fold(fold));
+    }
+    component
+}
+
+fn canonicalize_fold(fold: &IRFold) -> IRFold {
+    let mut fold = fold.clone();
+    fold.component = Arc::new(canonicalize_component(&fold.component));
+    fold.imported_tags.sort();
+    fold.imported_tags.dedup();
+    sort_and_dedup(&mut fold.post_filters);
+    fold
+}
+
+/// Filters are combined with "and", so their order and repetition don't matter.
+/// They have no natural ordering, so sort them by their serialized representation.
+fn sort_and_dedup<T: Serialize + PartialEq>(filters: &mut SmallVec<[T; 1]>) {
+    filters.sort_by_cached_key(|filter| {
+        serde_json::to_string(filter).expect("failed to serialize filter")
+    });
+    filters.dedup();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::json;
+
+    use crate::{
+        frontend::parse,
+        ir::{json::QUERY_JSON_VERSION, IndexedQuery},
+        schema::Schema,
+    };
+
+    use super::QueryHash;
+
+    fn numbers_schema() -> Schema {
+        Schema::parse(fs::read_to_string("test_data/schemas/numbers.graphql").unwrap()).unwrap()
+    }
+
+    fn hash(schema: &Schema, query: &str) -> QueryHash {
+        parse(schema, query).unwrap().content_hash()
+    }
+
+    #[test]
+    fn equivalent_queries_have_equal_hashes() {
+        let schema = numbers_schema();
+        let query = r#"
+{
+    Number(min: 1, max: 10) {
+        value @output @tag(name: "value")
+            @filter(op: ">", value: ["$min"]) @filter(op: "<", value: ["$max"])
+        multiple(max: 3) @fold {
+            value @filter(op: ">", value: ["%value"]) @output(name: "multiples")
+        }
+    }
+}"#;
+        let equivalent = r#"
+# Reordered, repeated, renamed, and described, but otherwise identical.
+{
+    Number(max: 10, min: 1) {
+        # The number's value.
+        number: value @filter(op: "<", value: ["$max"]) @filter(op: ">", value: ["$min"])
+            @filter(op: "<", value: ["$max"]) @tag(name: "n") @output(name: "value")
+        m: multiple(max: 3) @fold {
+            value @output(name: "multiples") @filter(op: ">", value: ["%n"])
+        }
+    }
+}"#;
+        let expected = hash(&schema, query);
+        assert_eq!(expected, hash(&schema, equivalent));
+
+        let indexed_query = parse(&schema, query).unwrap();
+        let canonical = indexed_query.ir_query.canonicalize();
+        assert_eq!(canonical, canonical.canonicalize());
+        assert_eq!(expected, canonical.content_hash());
+
+        // The hash is stable across processes and platforms.
+        assert_eq!("2723252430716de6", expected.to_string());
+    }
+
+    /// Any change to the hashed representation of queries changes every persisted hash.
+    /// If this test fails, either keep the representation unchanged, or increment
+    /// the version of the `ir::json` format, whose stability its own tests also check.
+    #[test]
+    fn hashes_of_stored_queries_are_stable() {
+        let schema = numbers_schema();
+        let stored = fs::read_to_string("test_data/tests/query_json/v1.json").unwrap();
+        let indexed_query = IndexedQuery::from_json(&schema, &stored).unwrap();
+
+        let hashed: serde_json::Value =
+            serde_json::from_str(&indexed_query.ir_query.hashed_representation()).unwrap();
+        assert_eq!(json!(QUERY_JSON_VERSION), hashed["version"]);
+        assert_eq!(
+            json!(indexed_query.ir_query.canonicalize()),
+            hashed["query"]
+        );
+
+        assert_eq!("1f28fb1fe6d77c76", indexed_query.content_hash().to_string());
+    }
+
+    #[test]
+    fn interface_and_semantic_differences_change_hashes() {
+        let schema = numbers_schema();
+        let query = r#"
+{
+    Number(min: 1, max: 10) {
+        value @output @filter(op: ">", value: ["$min"])
+    }
+}"#;
+        let expected = hash(&schema, query);
+
+        for different in [
+            query.replace("$min", "$lower"),
+            query.replace("@output", "@output(name: \"number\")"),
+            query.replace("\">\"", "\">=\""),
+            query.replace("max: 10", "max: 11"),
+        ] {
+            assert_ne!(expected, hash(&schema, &different), "{different}");
+        }
+    }
+}
@@ -5,13 +5,13 @@ use std::{
     sync::Arc,
 };
 
-use async_graphql_parser::types::{BaseType, Type};
 use serde::{Deserialize, Serialize};
 
-use crate::util::BTreeMapTryInsertExt;
+use crate::{interpreter::planning::PlanCache, schema::CustomScalar, util::BTreeMapTryInsertExt};
 
 use super::{
-    types::is_scalar_only_subtype, Argument, Eid, IREdge, IRFold, IRQuery, IRQueryComponent, Vid,
+    Argument, Eid, FieldRef, FieldValue, IREdge, IRFold, IRQuery, IRQueryComponent, LocalField,
+    Operation, Type, Vid,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -23,22 +23,230 @@ pub struct IndexedQuery {
     pub eids: BTreeMap<Eid, EdgeKind>,
 
     pub outputs: BTreeMap<Arc<str>, Output>,
+
+    /// What executing the query does at each vertex.
+    pub vertices: BTreeMap<Vid, VertexMetadata>,
+
+    /// Hooks for the custom scalar types in the schema the query was compiled against.
+    ///
+    /// Hooks are code and cannot be serialized, so deserialized queries have none.
+    /// Use [`IndexedQuery::with_custom_scalars`] to attach them.
+    #[serde(skip)]
+    pub custom_scalars: BTreeMap<Arc<str>, CustomScalar>,
+
+    /// The steps that execute the query, planned when it's first executed.
+    #[serde(skip)]
+    pub(crate) plans: PlanCache,
+}
+
+impl IndexedQuery {
+    /// Attach the custom scalar hooks to be used when executing this query.
+    pub fn with_custom_scalars(mut self, custom_scalars: BTreeMap<Arc<str>, CustomScalar>) -> Self {
+        self.custom_scalars = custom_scalars;
+        self
+    }
+
+    /// The variables the query expects to receive when executed, sorted by name.
+    ///
+    /// Clients can use these to validate arguments before sending them,
+    /// or to generate input forms for the query.
+    pub fn expected_variables(&self) -> Vec<ExpectedVariable> {
+        self.ir_query
+            .variables
+            .iter()
+            .map(|(name, variable_type)| ExpectedVariable {
+                name: name.clone(),
+                variable_type: variable_type.clone(),
+                default_value: self.ir_query.variable_defaults.get(name).cloned(),
+                enum_values: self
+                    .ir_query
+                    .enum_values
+                    .get(variable_type.base_type())
+                    .cloned(),
+            })
+            .collect()
+    }
+
+    /// The slot of the variable among the query's variables, which are numbered
+    /// in order of name, or `None` if the query doesn't use the variable.
+    pub fn variable_slot(&self, name: &str) -> Option<usize> {
+        variable_slot(&self.ir_query.variables, name)
+    }
+}
+
+fn variable_slot(variables: &BTreeMap<Arc<str>, Type>, name: &str) -> Option<usize> {
+    variables
+        .keys()
+        .position(|variable| variable.as_ref() == name)
+}
+
+/// A variable that a query expects to receive when executed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedVariable {
+    pub name: Arc<str>,
+
+    /// The type of the values the variable accepts. Unless the query declares the variable's
+    /// type, it's the most general type that every use of the variable in the query accepts.
+    ///
+    /// A nullable type means that `null` is an acceptable value,
+    /// not that the variable may be omitted.
+    pub variable_type: Type,
+
+    /// The value the variable takes if it isn't provided, as declared by the query.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_value: Option<FieldValue>,
+
+    /// The values of the enum that the variable's type is, or is a list of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enum_values: Option<BTreeSet<Arc<str>>>,
+}
+
+impl ExpectedVariable {
+    /// Whether a value must be provided for the variable when executing the query.
+    pub fn is_required(&self) -> bool {
+        self.default_value.is_none()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Output {
     pub name: Arc<str>,
 
-    #[serde(serialize_with = "crate::ir::serialization::serde_type_serializer")]
-    #[serde(deserialize_with = "crate::ir::serialization::serde_type_deserializer")]
     pub value_type: Type,
 
     pub vid: Vid,
+
+    /// The description of the output given in the query, if any.
+    /// See [`IRQuery::output_descriptions`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<Arc<str>>,
+}
+
+/// What executing a query does at one of its vertices, in the order it does it.
+///
+/// This is derived from the vertex's [`IRQueryComponent`] once, when the query is indexed,
+/// so that executing the query doesn't have to find it there again every time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VertexMetadata {
+    pub type_name: Arc<str>,
+
+    /// The type the vertex is coerced from, if the query coerces it to [`Self::type_name`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coerced_from_type: Option<Arc<str>>,
+
+    /// Each of the vertex's properties that the query uses, listed once: those used by
+    /// its filters in the order they're applied, then those tagged, then those output.
+    pub properties: Vec<Arc<str>>,
+
+    /// The vertex's filters, in the order they're applied.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filters: Vec<VertexFilter>,
+
+    /// The vertex's properties whose values are tagged for use by filters, in order of name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<Arc<str>>,
+
+    /// The outputs that are properties of the vertex, in order of output name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<VertexOutput>,
+}
+
+/// A filter applied at a vertex.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VertexFilter {
+    pub operation: Operation<LocalField, Argument>,
+
+    /// The [slot](IndexedQuery::variable_slot) of the variable the filter
+    /// compares with, if its argument is a variable.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variable_slot: Option<usize>,
 }
 
+/// An output whose value is a property of a vertex.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VertexOutput {
+    pub name: Arc<str>,
+    pub field_name: Arc<str>,
+}
+
+/// Ways in which a query's IR can violate the invariants required for its execution.
+///
+/// The frontend only produces valid IR, so these errors are only possible for IR
+/// from other sources, such as IR that was deserialized or constructed by hand.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
 pub enum InvalidIRQueryError {
-    GetBetterVariant(i32),
+    #[error("The root vertex {0} of a query component is not one of the component's vertices.")]
+    MissingComponentRoot(Vid),
+
+    #[error("Vertex {0} is part of more than one query component.")]
+    DuplicatedVertex(Vid),
+
+    #[error(
+        "Vertex {0} is not the root of its component, \
+        and is not the destination of any edge in that component."
+    )]
+    UnreachableVertex(Vid),
+
+    #[error("Variable \"{0}\" is used in the query, but is missing from the query's variables.")]
+    UnrecordedVariable(String),
+
+    #[error(
+        "Variable \"{0}\" is recorded with type {1}, which is not valid \
+        for one of its uses which requires type {2}."
+    )]
+    VariableTypeMismatch(String, String, String),
+
+    #[error("Output \"{0}\" is produced by vertex {1}, which is not in the output's component.")]
+    OutputVertexOutsideComponent(String, Vid),
+
+    #[error("Multiple outputs are named \"{0}\".")]
+    DuplicatedOutputName(String),
+
+    #[error("The query has a description for output \"{0}\", but no such output.")]
+    DescribedOutputNotFound(String),
+
+    #[error("Edge {0} points to vertex {1} instead of the vertex whose Vid follows its Eid.")]
+    EdgeDestinationMismatch(Eid, Vid),
+
+    #[error("Edge {0} starts at vertex {1}, which is not before its destination vertex {2}.")]
+    EdgeAgainstVidOrder(Eid, Vid, Vid),
+
+    #[error("Edge {0} connects to vertex {1}, which is not in the edge's component.")]
+    EdgeEndpointOutsideComponent(Eid, Vid),
+
+    #[error("More than one edge has Eid {0}.")]
+    DuplicatedEdge(Eid),
+
+    #[error(
+        "The folded edge {0} points to vertex {1}, \
+        which is not the root vertex {2} of the fold's component."
+    )]
+    FoldDestinationNotComponentRoot(Eid, Vid, Vid),
+
+    #[error(
+        "Edge {1} is within the component rooted at vertex {0}, but the edges within \
+        a component and its folds must have consecutive Eids starting from that vertex's Vid."
+    )]
+    NonConsecutiveComponentEdge(Vid, Eid),
+
+    #[error("A filter on vertex {1} uses a tagged value from vertex {0}, which does not exist.")]
+    TagFromUnknownVertex(Vid, Vid),
+
+    #[error("A filter on vertex {1} uses a tagged value from the missing fold {0}.")]
+    TagFromUnknownFold(Eid, Vid),
+
+    #[error(
+        "A filter on vertex {1} uses a tagged value from vertex {0}, \
+        which is not expanded before the filter is applied."
+    )]
+    TagFromLaterVertex(Vid, Vid),
+
+    #[error(
+        "A filter on vertex {1} uses a tagged value from the fold {0}, \
+        which is not completed before the filter is applied."
+    )]
+    TagFromUnfinishedFold(Eid, Vid),
 }
 
 impl TryFrom<IRQuery> for IndexedQuery {
@@ -65,7 +273,6 @@ impl TryFrom<IRQuery> for IndexedQuery {
         // - vertices containing tagged values are always expanded into before the tag is used
         //   (i.e. the edge with the tagged value vertex as its "to" side has a lower Eid than
         //    the edge with the filtering vertex as its "to" side)
-        // TODO: most of the above
         let mut vids = Default::default();
         let mut eids = Default::default();
         let mut outputs = Default::default();
@@ -78,12 +285,26 @@ impl TryFrom<IRQuery> for IndexedQuery {
             &ir_query.root_component,
             &mut vec![],
         )?;
+        check_component_eids(&ir_query.root_component)?;
+        check_tag_uses(&ir_query.root_component, &vids, &eids)?;
+
+        for (name, description) in &ir_query.output_descriptions {
+            let output: &mut Output = outputs
+                .get_mut(name)
+                .ok_or_else(|| InvalidIRQueryError::DescribedOutputNotFound(name.to_string()))?;
+            output.description = Some(description.clone());
+        }
+
+        let vertices = get_vertex_metadata(&ir_query, &vids);
 
         Ok(Self {
             ir_query,
             vids,
             eids,
             outputs,
+            vertices,
+            custom_scalars: Default::default(),
+            plans: Default::default(),
         })
     }
 }
@@ -106,13 +327,10 @@ fn get_output_type(
 ) -> Type {
     let mut wrapped_output_type = field_type.clone();
     if component_optional_vertices.contains(&output_at) {
-        wrapped_output_type.nullable = true;
+        wrapped_output_type = wrapped_output_type.with_nullability(true);
     }
     for is_fold_optional in are_folds_optional.iter().rev() {
-        wrapped_output_type = Type {
-            base: BaseType::List(Box::new(wrapped_output_type)),
-            nullable: *is_fold_optional,
-        };
+        wrapped_output_type = Type::list_of(&wrapped_output_type, *is_fold_optional);
     }
     wrapped_output_type
 }
@@ -129,41 +347,27 @@ fn add_data_from_component(
 
     // the root vertex Vid must belong to an existing vertex in the component
     if component.vertices.get(&component.root).is_none() {
-        return Err(InvalidIRQueryError::GetBetterVariant(-1));
+        return Err(InvalidIRQueryError::MissingComponentRoot(component.root));
     }
 
     for (vid, vertex) in &component.vertices {
         let existing = vids.insert(*vid, component.clone());
         if existing.is_some() {
-            return Err(InvalidIRQueryError::GetBetterVariant(0));
+            return Err(InvalidIRQueryError::DuplicatedVertex(*vid));
+        }
+
+        // Every vertex other than the component's root must be reached by one of its edges.
+        if *vid != component.root && !component.edges.values().any(|edge| edge.to_vid == *vid) {
+            return Err(InvalidIRQueryError::UnreachableVertex(*vid));
         }
 
         for filter in &vertex.filters {
-            match filter.right() {
-                Some(Argument::Variable(vref)) => {
-                    match variables.get(&vref.variable_name) {
-                        Some(var_type) => {
-                            // The variable type at top level must be a subtype of (or same type as)
-                            // the type recorded at the point of use of the variable. It can be
-                            // a subtype if another point of use has narrowed the type:
-                            // for example, if the other point of use requires it to be non-null
-                            // but this point of use allows a nullable value.
-                            //
-                            // If the variable type at top level is not a subtype of the type here,
-                            // this query is not valid.
-                            if !is_scalar_only_subtype(&vref.variable_type, var_type) {
-                                return Err(InvalidIRQueryError::GetBetterVariant(-2));
-                            }
-                        }
-                        None => {
-                            // This variable is used in the query but never recorded at
-                            // the top level of the query. This query is invalid.
-                            return Err(InvalidIRQueryError::GetBetterVariant(-3));
-                        }
-                    }
-                }
-                Some(Argument::Tag(..)) | None => {}
-            }
+            check_variable_use(variables, filter.right())?;
+        }
+    }
+    for fold in component.folds.values() {
+        for filter in &fold.post_filters {
+            check_variable_use(variables, filter.right())?;
         }
     }
 
@@ -171,11 +375,12 @@ fn add_data_from_component(
         let output_vid = field.vertex_id;
 
         // the output must be from a vertex in this component
-        let output_component = vids
-            .get(&output_vid)
-            .ok_or(InvalidIRQueryError::GetBetterVariant(1))?;
-        if !ptr::eq(component.as_ref(), output_component.as_ref()) {
-            return Err(InvalidIRQueryError::GetBetterVariant(2));
+        let output_component = vids.get(&output_vid);
+        if !output_component.is_some_and(|c| ptr::eq(component.as_ref(), c.as_ref())) {
+            return Err(InvalidIRQueryError::OutputVertexOutsideComponent(
+                output_name.to_string(),
+                output_vid,
+            ));
         }
 
         let output_name = output_name.clone();
@@ -189,61 +394,88 @@ fn add_data_from_component(
             name: output_name.clone(),
             value_type: output_type,
             vid: output_vid,
+            description: None,
         };
-        let existing = outputs.insert(output_name, output);
-        if existing.is_some() {
-            return Err(InvalidIRQueryError::GetBetterVariant(3));
+        if let Some(existing) = outputs.insert(output_name, output) {
+            return Err(InvalidIRQueryError::DuplicatedOutputName(
+                existing.name.to_string(),
+            ));
         }
     }
 
     for (eid, edge) in component.edges.iter() {
         // the "to" vertex must have Vid equal to the edge's Eid + 1
         if usize::from(eid.0) + 1 != usize::from(edge.to_vid.0) {
-            return Err(InvalidIRQueryError::GetBetterVariant(4));
+            return Err(InvalidIRQueryError::EdgeDestinationMismatch(
+                *eid,
+                edge.to_vid,
+            ));
         }
 
-        // the edge's endpoints must be vertices from this component
-        let from_component = vids
-            .get(&edge.from_vid)
-            .ok_or(InvalidIRQueryError::GetBetterVariant(5))?;
-        if !ptr::eq(component.as_ref(), from_component.as_ref()) {
-            return Err(InvalidIRQueryError::GetBetterVariant(6));
+        // the edge must be expanded in the direction of increasing vids
+        if edge.from_vid >= edge.to_vid {
+            return Err(InvalidIRQueryError::EdgeAgainstVidOrder(
+                *eid,
+                edge.from_vid,
+                edge.to_vid,
+            ));
         }
-        let to_component = vids
-            .get(&edge.to_vid)
-            .ok_or(InvalidIRQueryError::GetBetterVariant(7))?;
-        if !ptr::eq(component.as_ref(), to_component.as_ref()) {
-            return Err(InvalidIRQueryError::GetBetterVariant(8));
+
+        // the edge's endpoints must be vertices from this component
+        for endpoint in [edge.from_vid, edge.to_vid] {
+            let endpoint_component = vids.get(&endpoint);
+            if !endpoint_component.is_some_and(|c| ptr::eq(component.as_ref(), c.as_ref())) {
+                return Err(InvalidIRQueryError::EdgeEndpointOutsideComponent(
+                    *eid, endpoint,
+                ));
+            }
         }
 
         let existing = eids.insert(*eid, EdgeKind::Regular(edge.clone()));
         if existing.is_some() {
-            return Err(InvalidIRQueryError::GetBetterVariant(9));
+            return Err(InvalidIRQueryError::DuplicatedEdge(*eid));
         }
     }
 
     for (eid, fold) in component.folds.iter() {
         // The "to" vertex must have Vid equal to the folded edge's Eid + 1.
         if usize::from(eid.0) + 1 != usize::from(fold.to_vid.0) {
-            return Err(InvalidIRQueryError::GetBetterVariant(10));
+            return Err(InvalidIRQueryError::EdgeDestinationMismatch(
+                *eid,
+                fold.to_vid,
+            ));
+        }
+
+        // The folded edge must be expanded in the direction of increasing vids.
+        if fold.from_vid >= fold.to_vid {
+            return Err(InvalidIRQueryError::EdgeAgainstVidOrder(
+                *eid,
+                fold.from_vid,
+                fold.to_vid,
+            ));
         }
 
         // The folded edge's "from" vertex must be from this component.
-        let from_component = vids
-            .get(&fold.from_vid)
-            .ok_or(InvalidIRQueryError::GetBetterVariant(11))?;
-        if !ptr::eq(component.as_ref(), from_component.as_ref()) {
-            return Err(InvalidIRQueryError::GetBetterVariant(12));
+        let from_component = vids.get(&fold.from_vid);
+        if !from_component.is_some_and(|c| ptr::eq(component.as_ref(), c.as_ref())) {
+            return Err(InvalidIRQueryError::EdgeEndpointOutsideComponent(
+                *eid,
+                fold.from_vid,
+            ));
         }
 
         // The folded edge's "to" vertex must be the root of the fold component.
         if fold.to_vid != fold.component.root {
-            return Err(InvalidIRQueryError::GetBetterVariant(13));
+            return Err(InvalidIRQueryError::FoldDestinationNotComponentRoot(
+                *eid,
+                fold.to_vid,
+                fold.component.root,
+            ));
         }
 
         let existing = eids.insert(*eid, EdgeKind::Fold(fold.clone()));
         if existing.is_some() {
-            return Err(InvalidIRQueryError::GetBetterVariant(14));
+            return Err(InvalidIRQueryError::DuplicatedEdge(*eid));
         }
 
         // Include fold-specific outputs in the list of outputs.
@@ -261,9 +493,12 @@ fn add_data_from_component(
                         name: name.clone(),
                         value_type: output_type,
                         vid: fold.to_vid,
+                        description: None,
                     },
                 )
-                .map_err(|_| InvalidIRQueryError::GetBetterVariant(15))?;
+                .map_err(|e| {
+                    InvalidIRQueryError::DuplicatedOutputName(e.entry.key().to_string())
+                })?;
         }
 
         are_folds_optional.push(component_optional_vertices.contains(&fold.from_vid));
@@ -283,6 +518,244 @@ fn add_data_from_component(
     Ok(())
 }
 
+fn check_variable_use(
+    variables: &BTreeMap<Arc<str>, Type>,
+    argument: Option<&Argument>,
+) -> Result<(), InvalidIRQueryError> {
+    if let Some(Argument::Variable(vref)) = argument {
+        match variables.get(&vref.variable_name) {
+            Some(var_type) => {
+                // The variable type at top level must be a subtype of (or same type as)
+                // the type recorded at the point of use of the variable. It can be
+                // a subtype if another point of use has narrowed the type:
+                // for example, if the other point of use requires it to be non-null
+                // but this point of use allows a nullable value.
+                //
+                // If the variable type at top level is not a subtype of the type here,
+                // this query is not valid.
+                if !var_type.is_scalar_only_subtype_of(&vref.variable_type) {
+                    return Err(InvalidIRQueryError::VariableTypeMismatch(
+                        vref.variable_name.to_string(),
+                        var_type.to_string(),
+                        vref.variable_type.to_string(),
+                    ));
+                }
+            }
+            None => {
+                // This variable is used in the query but never recorded at
+                // the top level of the query. This query is invalid.
+                return Err(InvalidIRQueryError::UnrecordedVariable(
+                    vref.variable_name.to_string(),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that the edges within the component and its (recursive) subcomponents have
+/// consecutive Eids, starting from the Eid of the edge that begins the component.
+///
+/// Since the edge with Eid i points to the vertex with Vid i+1, that edge's Eid is
+/// the same number as the component root's Vid minus one. Returns the largest Vid
+/// within the component and its subcomponents.
+fn check_component_eids(component: &IRQueryComponent) -> Result<Vid, InvalidIRQueryError> {
+    fn collect_eids(component: &IRQueryComponent, eids: &mut Vec<Eid>) {
+        eids.extend(component.edges.keys().copied());
+        for (eid, fold) in &component.folds {
+            eids.push(*eid);
+            collect_eids(&fold.component, eids);
+        }
+    }
+
+    let mut eids = vec![];
+    collect_eids(component, &mut eids);
+    eids.sort_unstable();
+
+    let first_eid = usize::from(component.root.0);
+    for (index, eid) in eids.iter().enumerate() {
+        if usize::from(eid.0) != first_eid + index {
+            return Err(InvalidIRQueryError::NonConsecutiveComponentEdge(
+                component.root,
+                *eid,
+            ));
+        }
+    }
+
+    for fold in component.folds.values() {
+        check_component_eids(&fold.component)?;
+    }
+
+    Ok(eids.last().map_or(component.root, |eid| {
+        Vid::new(
+            (usize::from(eid.0) + 1)
+                .try_into()
+                .expect("Vid is not zero"),
+        )
+    }))
+}
+
+/// Check that every tagged value is produced before the filters that use it are applied.
+///
+/// A vertex's filters may use the tagged values of that vertex and of the vertices expanded
+/// before it. The filters applied to the results of a fold may use the tagged values
+/// of the vertices expanded before the fold. In both cases, a fold's tagged values may be used
+/// once all vertices within the fold have been expanded.
+fn check_tag_uses(
+    component: &IRQueryComponent,
+    vids: &BTreeMap<Vid, Arc<IRQueryComponent>>,
+    eids: &BTreeMap<Eid, EdgeKind>,
+) -> Result<(), InvalidIRQueryError> {
+    let check_tag = |argument: Option<&Argument>, filtered_vid: Vid, inclusive: bool| {
+        // The latest Vid whose tagged values may be used at this point in the query.
+        let latest_usable_vid = if inclusive {
+            filtered_vid
+        } else {
+            Vid::new(
+                (usize::from(filtered_vid.0) - 1)
+                    .try_into()
+                    .expect("filtered Vid is the root of a fold, so it can't be the first Vid"),
+            )
+        };
+
+        match argument {
+            Some(Argument::Tag(FieldRef::ContextField(field))) => {
+                if !vids.contains_key(&field.vertex_id) {
+                    Err(InvalidIRQueryError::TagFromUnknownVertex(
+                        field.vertex_id,
+                        filtered_vid,
+                    ))
+                } else if field.vertex_id > latest_usable_vid {
+                    Err(InvalidIRQueryError::TagFromLaterVertex(
+                        field.vertex_id,
+                        filtered_vid,
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+            Some(Argument::Tag(FieldRef::FoldSpecificField(field))) => {
+                let Some(EdgeKind::Fold(fold)) = eids.get(&field.fold_eid) else {
+                    return Err(InvalidIRQueryError::TagFromUnknownFold(
+                        field.fold_eid,
+                        filtered_vid,
+                    ));
+                };
+                let last_fold_vid = check_component_eids(&fold.component)?;
+                let is_within_fold = (fold.to_vid..=last_fold_vid).contains(&filtered_vid);
+                if is_within_fold || last_fold_vid > latest_usable_vid {
+                    Err(InvalidIRQueryError::TagFromUnfinishedFold(
+                        field.fold_eid,
+                        filtered_vid,
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+            Some(Argument::Variable(..)) | None => Ok(()),
+        }
+    };
+
+    for (vid, vertex) in &component.vertices {
+        for filter in &vertex.filters {
+            check_tag(filter.right(), *vid, true)?;
+        }
+    }
+    for fold in component.folds.values() {
+        for filter in &fold.post_filters {
+            check_tag(filter.right(), fold.to_vid, false)?;
+        }
+        check_tag_uses(&fold.component, vids, eids)?;
+    }
+
+    Ok(())
+}
+
+fn get_vertex_metadata(
+    ir_query: &IRQuery,
+    vids: &BTreeMap<Vid, Arc<IRQueryComponent>>,
+) -> BTreeMap<Vid, VertexMetadata> {
+    let mut tags: BTreeMap<Vid, BTreeSet<Arc<str>>> = BTreeMap::new();
+    collect_tags(&ir_query.root_component, &mut tags);
+
+    vids.iter()
+        .map(|(vid, component)| {
+            let vertex = &component.vertices[vid];
+            let filters: Vec<_> = vertex
+                .filters
+                .iter()
+                .map(|filter| VertexFilter {
+                    operation: filter.clone(),
+                    variable_slot: match filter.right() {
+                        Some(Argument::Variable(variable)) => {
+                            variable_slot(&ir_query.variables, &variable.variable_name)
+                        }
+                        _ => None,
+                    },
+                })
+                .collect();
+            let tags: Vec<_> = tags.remove(vid).unwrap_or_default().into_iter().collect();
+            let outputs: Vec<_> = component
+                .outputs
+                .iter()
+                .filter(|(_, field)| field.vertex_id == *vid)
+                .map(|(name, field)| VertexOutput {
+                    name: name.clone(),
+                    field_name: field.field_name.clone(),
+                })
+                .collect();
+
+            let mut properties: Vec<Arc<str>> = vec![];
+            let used_properties = filters
+                .iter()
+                .map(|filter| &filter.operation.left().field_name)
+                .chain(&tags)
+                .chain(outputs.iter().map(|output| &output.field_name));
+            for property in used_properties {
+                if !properties.contains(property) {
+                    properties.push(property.clone());
+                }
+            }
+
+            let metadata = VertexMetadata {
+                type_name: vertex.type_name.clone(),
+                coerced_from_type: vertex.coerced_from_type.clone(),
+                properties,
+                filters,
+                tags,
+                outputs,
+            };
+            (*vid, metadata)
+        })
+        .collect()
+}
+
+/// Collect the properties that the filters within the component and its subcomponents
+/// use as tags, by the vertex whose properties they are.
+fn collect_tags(component: &IRQueryComponent, tags: &mut BTreeMap<Vid, BTreeSet<Arc<str>>>) {
+    let arguments = component
+        .vertices
+        .values()
+        .flat_map(|vertex| vertex.filters.iter().map(Operation::right))
+        .chain(
+            component
+                .folds
+                .values()
+                .flat_map(|fold| fold.post_filters.iter().map(Operation::right)),
+        );
+    for argument in arguments {
+        if let Some(Argument::Tag(FieldRef::ContextField(field))) = argument {
+            tags.entry(field.vertex_id)
+                .or_default()
+                .insert(field.field_name.clone());
+        }
+    }
+
+    for fold in component.folds.values() {
+        collect_tags(&fold.component, tags);
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EdgeKind {
     Regular(Arc<IREdge>),
@@ -300,3 +773,248 @@ impl From<Arc<IRFold>> for EdgeKind {
         Self::Fold(fold)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        frontend::{parse, parse_to_ir},
+        ir::{
+            Argument, Eid, FieldRef, FieldValue, IRQuery, IRQueryComponent, Operation, Type, Vid,
+        },
+        schema::Schema,
+    };
+
+    use super::{ExpectedVariable, IndexedQuery, InvalidIRQueryError, VertexFilter, VertexOutput};
+
+    fn vid(n: usize) -> Vid {
+        Vid::new(n.try_into().unwrap())
+    }
+
+    fn numbers_ir(query: &str) -> IRQuery {
+        let schema =
+            Schema::parse(include_str!("../../test_data/schemas/numbers.graphql")).unwrap();
+        parse_to_ir(&schema, query).unwrap()
+    }
+
+    fn root_component(ir_query: &mut IRQuery) -> &mut IRQueryComponent {
+        Arc::make_mut(&mut ir_query.root_component)
+    }
+
+    #[test]
+    fn edges_must_have_consecutive_eids() {
+        let mut ir_query = numbers_ir(
+            r#"
+{
+    Number(max: 10) {
+        value @output
+        successor {
+            value
+        }
+    }
+}"#,
+        );
+        assert!(IndexedQuery::try_from(ir_query.clone()).is_ok());
+
+        // Renumber the edge and the vertex it points to, leaving a gap in the Eids.
+        let component = root_component(&mut ir_query);
+        let mut edge = component
+            .edges
+            .remove(&Eid::new(1.try_into().unwrap()))
+            .unwrap();
+        let mut vertex = component.vertices.remove(&vid(2)).unwrap();
+        let edge_mut = Arc::make_mut(&mut edge);
+        edge_mut.eid = Eid::new(2.try_into().unwrap());
+        edge_mut.to_vid = vid(3);
+        vertex.vid = vid(3);
+        component.edges.insert(edge.eid, edge);
+        component.vertices.insert(vertex.vid, vertex);
+
+        assert_eq!(
+            Err(InvalidIRQueryError::NonConsecutiveComponentEdge(
+                vid(1),
+                Eid::new(2.try_into().unwrap())
+            )),
+            IndexedQuery::try_from(ir_query),
+        );
+    }
+
+    #[test]
+    fn vertices_must_be_reachable() {
+        let mut ir_query = numbers_ir(
+            r#"
+{
+    Number(max: 10) {
+        value @output
+    }
+}"#,
+        );
+        let component = root_component(&mut ir_query);
+        let mut vertex = component.vertices[&vid(1)].clone();
+        vertex.vid = vid(2);
+        component.vertices.insert(vertex.vid, vertex);
+
+        assert_eq!(
+            Err(InvalidIRQueryError::UnreachableVertex(vid(2))),
+            IndexedQuery::try_from(ir_query),
+        );
+    }
+
+    #[test]
+    fn tagged_values_must_be_produced_before_use() {
+        let mut ir_query = numbers_ir(
+            r#"
+{
+    Number(max: 10) {
+        value @tag(name: "value") @output
+        successor {
+            value @filter(op: ">", value: ["%value"])
+        }
+    }
+}"#,
+        );
+        assert!(IndexedQuery::try_from(ir_query.clone()).is_ok());
+
+        // Move the filter to the root vertex, with the tag now referring to the later vertex.
+        let component = root_component(&mut ir_query);
+        let mut filters = std::mem::take(&mut component.vertices.get_mut(&vid(2)).unwrap().filters);
+        let Operation::GreaterThan(_, Argument::Tag(FieldRef::ContextField(field))) =
+            &mut filters[0]
+        else {
+            unreachable!("{filters:?}")
+        };
+        field.vertex_id = vid(2);
+        component.vertices.get_mut(&vid(1)).unwrap().filters = filters;
+
+        assert_eq!(
+            Err(InvalidIRQueryError::TagFromLaterVertex(vid(2), vid(1))),
+            IndexedQuery::try_from(ir_query),
+        );
+    }
+
+    #[test]
+    fn vertex_metadata_lists_what_executes_at_each_vertex() {
+        let ir_query = numbers_ir(
+            r#"
+{
+    Number(max: 10) {
+        ... on Prime {
+            value @tag @output
+            name @filter(op: "=", value: ["$name"]) @filter(op: "is_not_null")
+
+            successor {
+                successor: value @filter(op: ">", value: ["%value"]) @output
+                name @filter(op: "has_prefix", value: ["$prefix"])
+            }
+        }
+    }
+}"#,
+        );
+        let indexed_query = IndexedQuery::try_from(ir_query).unwrap();
+        let names = |names: &[&str]| names.iter().copied().map(Arc::from).collect::<Vec<_>>();
+
+        let root = &indexed_query.vertices[&vid(1)];
+        assert_eq!("Prime", root.type_name.as_ref());
+        assert_eq!(Some("Number"), root.coerced_from_type.as_deref());
+        assert_eq!(names(&["name", "value"]), root.properties);
+        assert_eq!(names(&["value"]), root.tags);
+        assert_eq!(
+            vec![
+                (Some(indexed_query.variable_slot("name").unwrap()), "name"),
+                (None, "name"),
+            ],
+            root.filters
+                .iter()
+                .map(|filter| (
+                    filter.variable_slot,
+                    filter.operation.left().field_name.as_ref()
+                ))
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            vec![VertexOutput {
+                name: Arc::from("value"),
+                field_name: Arc::from("value"),
+            }],
+            root.outputs,
+        );
+
+        // Variables are numbered in order of name.
+        assert_eq!(Some(0), indexed_query.variable_slot("name"));
+        assert_eq!(Some(1), indexed_query.variable_slot("prefix"));
+        assert_eq!(None, indexed_query.variable_slot("missing"));
+
+        let successor = &indexed_query.vertices[&vid(2)];
+        assert_eq!(None, successor.coerced_from_type);
+        assert_eq!(names(&["value", "name"]), successor.properties);
+        assert!(successor.tags.is_empty());
+        assert!(
+            matches!(
+                &successor.filters[1],
+                VertexFilter {
+                    variable_slot: Some(1),
+                    operation: Operation::HasPrefix(..),
+                }
+            ),
+            "{:?}",
+            successor.filters,
+        );
+    }
+
+    #[test]
+    fn expected_variables_describe_accepted_values() {
+        let schema = Schema::parse(include_str!(
+            "../../test_data/tests/valid_schemas/enum_properties.graphql"
+        ))
+        .unwrap();
+        let query = r#"
+query($prefix: String! = "s") {
+    Shape {
+        name @output
+            @filter(op: "=", value: ["$name"])
+            @filter(op: "has_prefix", value: ["$prefix"])
+            @filter(op: "!=", value: ["$name"])
+        accents @filter(op: "contains", value: ["$accent"])
+    }
+}"#;
+        let indexed_query = parse(&schema, query).unwrap();
+        let expected_variables = indexed_query.expected_variables();
+
+        assert_eq!(
+            vec![
+                ExpectedVariable {
+                    name: Arc::from("accent"),
+                    variable_type: Type::parse("Color!").unwrap(),
+                    default_value: None,
+                    enum_values: Some(
+                        ["BLUE", "GREEN", "RED"]
+                            .into_iter()
+                            .map(Arc::from)
+                            .collect()
+                    ),
+                },
+                ExpectedVariable {
+                    name: Arc::from("name"),
+                    variable_type: Type::parse("String!").unwrap(),
+                    default_value: None,
+                    enum_values: None,
+                },
+                ExpectedVariable {
+                    name: Arc::from("prefix"),
+                    variable_type: Type::parse("String!").unwrap(),
+                    default_value: Some(FieldValue::String("s".to_string())),
+                    enum_values: None,
+                },
+            ],
+            expected_variables
+        );
+        assert_eq!(
+            vec![true, true, false],
+            expected_variables
+                .iter()
+                .map(ExpectedVariable::is_required)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! A versioned JSON representation of compiled queries, for storing queries
+//! and executing them later without parsing and compiling them again.
+//!
+//! A stored query is a JSON object with three fields:
+//! - `version`: the [`QUERY_JSON_VERSION`] of the format, as a number;
+//! - `schema_fingerprint`: the [`SchemaFingerprint`] of the schema the query was compiled
+//!   against, as a string of 16 lowercase hexadecimal digits;
+//! - `query`: the query's [`IRQuery`], in the same representation as its `serde` serialization.
+//!
+//! The format is stable: any change to it that older versions of trustfall would not be able
+//! to read comes with a new format version, and queries stored in the current format remain
+//! loadable for as long as that version is supported.
+//!
+//! Loading a stored query checks it against the current schema. If the schema's fingerprint
+//! has changed since the query was compiled, the query is only loaded if it is still valid
+//! according to [`Schema::check_query_compatibility`].
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::schema::{error::IncompatibleQueryError, Schema, SchemaFingerprint};
+
+use super::{IRQuery, IndexedQuery, InvalidIRQueryError};
+
+/// The version of the JSON query format produced by this version of trustfall.
+///
+/// It is incremented whenever the format changes in a way that older versions
+/// of trustfall would not be able to read.
+pub const QUERY_JSON_VERSION: u64 = 1;
+
+/// The JSON representation of a compiled query.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryJson {
+    pub version: u64,
+
+    #[serde(with = "fingerprint_hex")]
+    pub schema_fingerprint: SchemaFingerprint,
+
+    pub query: IRQuery,
+}
+
+/// Errors from loading a query stored with [`IndexedQuery::to_json`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, thiserror::Error)]
+pub enum QueryJsonError {
+    #[error("The stored query is not valid JSON in the query format: {0}")]
+    InvalidQueryJson(String),
+
+    #[error(
+        "The stored query uses version {0} of the query format, \
+        but only version {1} is supported."
+    )]
+    UnsupportedQueryJsonVersion(u64, u64),
+
+    #[error("The stored query is not valid against the current schema")]
+    IncompatibleSchema(#[from] IncompatibleQueryError),
+
+    #[error("The stored query is malformed")]
+    InvalidQuery(#[source] InvalidIRQueryError),
+}
+
+impl IndexedQuery {
+    /// Serialize the query as JSON, recording the schema it was compiled against.
+    /// See the [module-level documentation](self) for the format.
+    pub fn to_json(&self, schema: &Schema) -> String {
+        let query_json = QueryJson {
+            version: QUERY_JSON_VERSION,
+            schema_fingerprint: schema.fingerprint(),
+            query: self.ir_query.clone(),
+        };
+        serde_json::to_string(&query_json).expect("failed to serialize query")
+    }
+
+    /// Load a query from the JSON representation produced by [`IndexedQuery::to_json`],
+    /// checking that it is valid against the given schema.
+    pub fn from_json(schema: &Schema, input: &str) -> Result<Arc<Self>, QueryJsonError> {
+        let value: serde_json::Value = serde_json::from_str(input)
+            .map_err(|e| QueryJsonError::InvalidQueryJson(e.to_string()))?;
+
+        // Check the version first, so that newer formats produce a clear error
+        // instead of an obscure deserialization failure.
+        match value.get("version").and_then(|v| v.as_u64()) {
+            Some(QUERY_JSON_VERSION) => {}
+            Some(version) => {
+                return Err(QueryJsonError::UnsupportedQueryJsonVersion(
+                    version,
+                    QUERY_JSON_VERSION,
+                ))
+            }
+            None => {
+                return Err(QueryJsonError::InvalidQueryJson(
+                    "missing or invalid \"version\" field".to_string(),
+                ))
+            }
+        }
+
+        // Deserialize from the input itself rather than the parsed value,
+        // since the representation of types in queries borrows from the input.
+        let query_json: QueryJson = serde_json::from_str(input)
+            .map_err(|e| QueryJsonError::InvalidQueryJson(e.to_string()))?;
+        if query_json.schema_fingerprint != schema.fingerprint() {
+            schema.check_query_compatibility(&query_json.query)?;
+        }
+
+        let indexed_query =
+            IndexedQuery::try_from(query_json.query).map_err(QueryJsonError::InvalidQuery)?;
+        Ok(Arc::new(
+            indexed_query.with_custom_scalars(schema.custom_scalars.clone()),
+        ))
+    }
+}
+
+mod fingerprint_hex {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use crate::schema::SchemaFingerprint;
+
+    pub(super) fn serialize<S: Serializer>(
+        fingerprint: &SchemaFingerprint,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(fingerprint)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<SchemaFingerprint, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        if hex.len() != 16 {
+            return Err(D::Error::custom(format!(
+                "expected 16 hexadecimal digits, got: {hex:?}"
+            )));
+        }
+        u64::from_str_radix(&hex, 16)
+            .map(SchemaFingerprint::from_value)
+            .map_err(|e| D::Error::custom(format!("invalid schema fingerprint {hex:?}: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        frontend::parse,
+        ir::IndexedQuery,
+        schema::{error::IncompatibleQueryError, Schema},
+    };
+
+    use super::{QueryJsonError, QUERY_JSON_VERSION};
+
+    const QUERY: &str = r#"
+{
+    Number(min: 2, max: 10) {
+        value @tag(name: "start") @output
+        next: successor @optional {
+            name @output
+        }
+        multiple(max: 3) @fold @transform(op: "count") @filter(op: ">", value: ["$count"])
+                @output(name: "multiples") {
+            value @output(name: "multiple_values")
+        }
+        predecessor @recurse(depth: 2) {
+            ... on Prime {
+                value @filter(op: "<", value: ["%start"])
+            }
+        }
+        vowelsInName @filter(op: "contains", value: ["$vowel"])
+    }
+}"#;
+
+    fn numbers_schema() -> Schema {
+        Schema::parse(fs::read_to_string("test_data/schemas/numbers.graphql").unwrap()).unwrap()
+    }
+
+    /// Changes to the representation of queries must not silently change the stored format.
+    /// If this test fails, either keep the format unchanged, or increment
+    /// the format version and regenerate the stored query.
+    #[test]
+    fn format_is_stable() {
+        let schema = numbers_schema();
+        let indexed_query = parse(&schema, QUERY).unwrap();
+
+        let expected = fs::read_to_string("test_data/tests/query_json/v1.json").unwrap();
+        assert_eq!(1, QUERY_JSON_VERSION);
+        assert_eq!(expected.trim_end(), indexed_query.to_json(&schema));
+
+        let loaded = IndexedQuery::from_json(&schema, &expected).unwrap();
+        assert_eq!(indexed_query, loaded);
+    }
+
+    #[test]
+    fn queries_load_if_still_compatible_with_a_changed_schema() {
+        let schema = numbers_schema();
+        let stored = parse(&schema, QUERY).unwrap().to_json(&schema);
+
+        let extended_schema = Schema::parse(
+            fs::read_to_string("test_data/schemas/numbers.graphql")
+                .unwrap()
+                .replace(
+                    "type Prime implements Number & Named {",
+                    "type Prime implements Number & Named {\n    isMersenne: Boolean",
+                ),
+        )
+        .unwrap();
+        assert_ne!(schema.fingerprint(), extended_schema.fingerprint());
+        IndexedQuery::from_json(&extended_schema, &stored).unwrap();
+
+        let narrowed_schema = Schema::parse(
+            fs::read_to_string("test_data/schemas/numbers.graphql")
+                .unwrap()
+                .replace("    vowelsInName: [String]\n", ""),
+        )
+        .unwrap();
+        let error = IndexedQuery::from_json(&narrowed_schema, &stored).unwrap_err();
+        assert!(
+            matches!(
+                &error,
+                QueryJsonError::IncompatibleSchema(IncompatibleQueryError::NonExistentProperty(
+                    type_name,
+                    property
+                )) if type_name == "Number" && property == "vowelsInName"
+            ),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn errors_describe_their_causes_as_sources() {
+        let schema = numbers_schema();
+        let stored = parse(&schema, QUERY).unwrap().to_json(&schema);
+        let narrowed_schema = Schema::parse(
+            fs::read_to_string("test_data/schemas/numbers.graphql")
+                .unwrap()
+                .replace("    vowelsInName: [String]\n", ""),
+        )
+        .unwrap();
+        let error = IndexedQuery::from_json(&narrowed_schema, &stored).unwrap_err();
+
+        assert_eq!(
+            "The stored query is not valid against the current schema",
+            error.to_string()
+        );
+        let source = std::error::Error::source(&error).expect("error has no source");
+        assert_eq!(
+            "The query uses property \"vowelsInName\" on type \"Number\", \
+            which is not defined in the schema.",
+            source.to_string()
+        );
+        assert!(source.source().is_none());
+    }
+
+    #[test]
+    fn unsupported_versions_are_rejected() {
+        let schema = numbers_schema();
+        let stored = parse(&schema, QUERY).unwrap().to_json(&schema).replacen(
+            "\"version\":1",
+            "\"version\":2",
+            1,
+        );
+        assert_eq!(
+            QueryJsonError::UnsupportedQueryJsonVersion(2, 1),
+            IndexedQuery::from_json(&schema, &stored).unwrap_err()
+        );
+
+        assert!(matches!(
+            IndexedQuery::from_json(&schema, "{\"query\": {}}").unwrap_err(),
+            QueryJsonError::InvalidQueryJson(..)
+        ));
+    }
+}
@@ -0,0 +1,755 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Argument, Eid, FieldRef, FieldValue, IRFold, IRQuery, IRQueryComponent, LocalField, Operation,
+    Vid,
+};
+
+/// A likely mistake in a query that is nonetheless valid.
+///
+/// Lints are produced by [`IRQuery::lint`]. Each has a stable, machine-readable
+/// [`code`](Self::code) that tools can use to filter or suppress it.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, thiserror::Error)]
+pub enum QueryLint {
+    #[error("Variable \"{0}\" is declared by the query but never used.")]
+    UnusedVariable(String),
+
+    #[error(
+        "The @optional edge \"{0}\" and the vertices beyond it have no outputs, filters, \
+        or tagged values, so the edge does not affect the query's results."
+    )]
+    IneffectiveOptionalEdge(String, Eid),
+
+    #[error(
+        "The @fold over edge \"{0}\" has no outputs, no filters on its results, \
+        and no tagged values, so it does not affect the query's results."
+    )]
+    IneffectiveFold(String, Eid),
+
+    #[error(
+        "The filters {2} and {3} on property \"{0}\" can never both be satisfied, \
+        so vertex {1} never matches."
+    )]
+    UnsatisfiableFilters(String, Vid, String, String),
+
+    #[error(
+        "The filter {2} on property \"{0}\" of vertex {1} is redundant, \
+        since the filter {3} implies it."
+    )]
+    RedundantFilter(String, Vid, String, String),
+}
+
+impl QueryLint {
+    /// The machine-readable code identifying this kind of lint.
+    pub fn code(&self) -> &'static str {
+        match self {
+            QueryLint::UnusedVariable(..) => "unused_variable",
+            QueryLint::IneffectiveOptionalEdge(..) => "ineffective_optional_edge",
+            QueryLint::IneffectiveFold(..) => "ineffective_fold",
+            QueryLint::UnsatisfiableFilters(..) => "unsatisfiable_filters",
+            QueryLint::RedundantFilter(..) => "redundant_filter",
+        }
+    }
+}
+
+/// The query's uses of variables and tagged values, across all its components.
+#[derive(Debug, Default)]
+struct ArgumentUses<'a> {
+    variables: BTreeSet<&'a str>,
+    tagged_vertices: BTreeSet<Vid>,
+    tagged_folds: BTreeSet<Eid>,
+}
+
+impl<'a> ArgumentUses<'a> {
+    fn collect(&mut self, component: &'a IRQueryComponent) {
+        let vertex_filters = component.vertices.values().flat_map(|v| v.filters.iter());
+        let vertex_arguments = vertex_filters.filter_map(|filter| filter.right());
+        let post_filters = component.folds.values().flat_map(|f| f.post_filters.iter());
+        let post_arguments = post_filters.filter_map(|filter| filter.right());
+
+        for argument in vertex_arguments.chain(post_arguments) {
+            match argument {
+                Argument::Variable(variable) => {
+                    self.variables.insert(variable.variable_name.as_ref());
+                }
+                Argument::Tag(FieldRef::ContextField(field)) => {
+                    self.tagged_vertices.insert(field.vertex_id);
+                }
+                Argument::Tag(FieldRef::FoldSpecificField(field)) => {
+                    self.tagged_folds.insert(field.fold_eid);
+                }
+            }
+        }
+
+        for fold in component.folds.values() {
+            self.collect(&fold.component);
+        }
+    }
+}
+
+impl IRQuery {
+    /// Analyze the query for likely mistakes that do not make it invalid.
+    ///
+    /// Lints are reported in a deterministic order: unused variables sorted by name,
+    /// then the lints for the root component's edges and its folds, in order of [`Eid`].
+    /// The lints for each fold's own component immediately follow the lint for the fold.
+    /// Lints for the filters on each vertex's properties come last, in order of [`Vid`]
+    /// and then of property name.
+    ///
+    /// Filters are only reported as unsatisfiable or redundant if that is the case regardless
+    /// of the values of the query's variables. Use [`IRQuery::lint_with_arguments`] to also
+    /// check the filters against the values with which the query will be executed.
+    /// Filters on the results of folds are not analyzed.
+    ///
+    /// Unused `@tag` directives are not linted here, since the IR only records the uses
+    /// of tagged values and queries with unused tags fail to compile altogether.
+    pub fn lint(&self) -> Vec<QueryLint> {
+        let mut lints = vec![];
+
+        let mut uses = ArgumentUses::default();
+        uses.collect(&self.root_component);
+
+        for variable_name in self.variables.keys() {
+            if !uses.variables.contains(variable_name.as_ref()) {
+                lints.push(QueryLint::UnusedVariable(variable_name.to_string()));
+            }
+        }
+
+        lint_component(&self.root_component, &uses, &mut lints);
+        lint_filters(&self.root_component, None, &mut lints);
+
+        lints
+    }
+
+    /// Analyze the query for likely mistakes, like [`IRQuery::lint`], and additionally check
+    /// whether any filters are unsatisfiable or redundant given the values of its variables.
+    ///
+    /// Variables without a value in `arguments` take their declared default value, if any.
+    /// Filters whose variables have no value are only analyzed as in [`IRQuery::lint`].
+    /// This makes it possible to avoid executing queries that cannot produce any results.
+    pub fn lint_with_arguments(
+        &self,
+        arguments: &BTreeMap<Arc<str>, FieldValue>,
+    ) -> Vec<QueryLint> {
+        let mut lints = self.lint();
+
+        let mut values = self.variable_defaults.clone();
+        values.extend(arguments.iter().map(|(k, v)| (k.clone(), v.clone())));
+        lint_filters(&self.root_component, Some(&values), &mut lints);
+
+        lints
+    }
+}
+
+fn lint_component(component: &IRQueryComponent, uses: &ArgumentUses, lints: &mut Vec<QueryLint>) {
+    for edge in component.edges.values() {
+        if edge.optional && !subtree_has_effect(component, edge.to_vid, uses) {
+            lints.push(QueryLint::IneffectiveOptionalEdge(
+                edge.edge_name.to_string(),
+                edge.eid,
+            ));
+        }
+    }
+
+    for fold in component.folds.values() {
+        if !fold_has_effect(fold, uses) {
+            lints.push(QueryLint::IneffectiveFold(
+                fold.edge_name.to_string(),
+                fold.eid,
+            ));
+        }
+        lint_component(&fold.component, uses, lints);
+    }
+}
+
+/// Whether the vertices reachable from the given vertex within the component affect
+/// the query's results, either by producing outputs or by discarding results.
+fn subtree_has_effect(component: &IRQueryComponent, root: Vid, uses: &ArgumentUses) -> bool {
+    let mut subtree: BTreeSet<Vid> = Default::default();
+    let mut stack = vec![root];
+    while let Some(vid) = stack.pop() {
+        if subtree.insert(vid) {
+            stack.extend(
+                component
+                    .edges
+                    .values()
+                    .filter(|edge| edge.from_vid == vid)
+                    .map(|edge| edge.to_vid),
+            );
+        }
+    }
+
+    subtree.iter().any(|vid| {
+        !component.vertices[vid].filters.is_empty() || uses.tagged_vertices.contains(vid)
+    }) || component
+        .outputs
+        .values()
+        .any(|field| subtree.contains(&field.vertex_id))
+        || component
+            .folds
+            .values()
+            .any(|fold| subtree.contains(&fold.from_vid) && fold_has_effect(fold, uses))
+}
+
+/// Whether the fold affects the query's results. Filters within a fold only have an effect
+/// through the fold's outputs and the filters applied to its results.
+fn fold_has_effect(fold: &IRFold, uses: &ArgumentUses) -> bool {
+    fn has_outputs(component: &IRQueryComponent, uses: &ArgumentUses) -> bool {
+        !component.outputs.is_empty()
+            || component
+                .folds
+                .values()
+                .any(|fold| fold_has_effect(fold, uses))
+    }
+
+    !fold.fold_specific_outputs.is_empty()
+        || !fold.post_filters.is_empty()
+        || uses.tagged_folds.contains(&fold.eid)
+        || has_outputs(&fold.component, uses)
+}
+
+type PropertyFilter = Operation<LocalField, Argument>;
+
+/// Lint the filters of the component's vertices, grouped by the property they filter.
+///
+/// Without argument values, report the combinations of filters that are unsatisfiable or
+/// redundant for any values of the query's variables. With argument values, report only
+/// those that are unsatisfiable or redundant because of the values of different variables.
+fn lint_filters(
+    component: &IRQueryComponent,
+    arguments: Option<&BTreeMap<Arc<str>, FieldValue>>,
+    lints: &mut Vec<QueryLint>,
+) {
+    for vertex in component.vertices.values() {
+        let mut filters_by_property: BTreeMap<&str, Vec<&PropertyFilter>> = Default::default();
+        for filter in &vertex.filters {
+            filters_by_property
+                .entry(filter.left().field_name.as_ref())
+                .or_default()
+                .push(filter);
+        }
+
+        for (property, filters) in filters_by_property {
+            match arguments {
+                None => lint_property_filters(property, vertex.vid, &filters, lints),
+                Some(arguments) => {
+                    lint_property_filter_values(property, vertex.vid, &filters, arguments, lints)
+                }
+            }
+        }
+    }
+
+    for fold in component.folds.values() {
+        lint_filters(&fold.component, arguments, lints);
+    }
+}
+
+fn lint_property_filters(
+    property: &str,
+    vid: Vid,
+    filters: &[&PropertyFilter],
+    lints: &mut Vec<QueryLint>,
+) {
+    for (index, first) in filters.iter().enumerate() {
+        for second in &filters[index + 1..] {
+            if first == second {
+                lints.push(redundant_filter(property, vid, second, first));
+            } else if are_contradictory(first, second) {
+                lints.push(unsatisfiable_filters(property, vid, first, second));
+            }
+        }
+    }
+
+    for filter in filters {
+        if matches!(filter, Operation::IsNotNull(..)) {
+            if let Some(implying) = filters.iter().find(|other| rejects_null(other)) {
+                lints.push(redundant_filter(property, vid, filter, implying));
+            }
+        }
+    }
+}
+
+/// Whether the filter never matches null property values, whatever the value of its variable.
+///
+/// Filters whose argument is a tagged value are not considered, since they always match
+/// if the tagged value comes from an `@optional` vertex that doesn't exist.
+fn rejects_null(filter: &PropertyFilter) -> bool {
+    matches!(filter.right(), Some(Argument::Variable(..)))
+        && matches!(
+            filter,
+            Operation::LessThan(..)
+                | Operation::LessThanOrEqual(..)
+                | Operation::GreaterThan(..)
+                | Operation::GreaterThanOrEqual(..)
+                | Operation::Contains(..)
+                | Operation::HasPrefix(..)
+                | Operation::HasSuffix(..)
+                | Operation::HasSubstring(..)
+                | Operation::RegexMatches(..)
+        )
+}
+
+/// Whether no property value satisfies both filters, whatever the values of their variables.
+fn are_contradictory(first: &PropertyFilter, second: &PropertyFilter) -> bool {
+    match (first, second) {
+        (Operation::IsNull(..), Operation::IsNotNull(..))
+        | (Operation::IsNotNull(..), Operation::IsNull(..)) => true,
+        (Operation::IsNull(..), other) | (other, Operation::IsNull(..)) => rejects_null(other),
+        _ => {
+            let (Some(Argument::Variable(left)), Some(Argument::Variable(right))) =
+                (first.right(), second.right())
+            else {
+                return false;
+            };
+            if left.variable_name != right.variable_name {
+                return false;
+            }
+
+            let mut operations = [first.operation_name(), second.operation_name()];
+            operations.sort_unstable();
+            matches!(
+                operations,
+                ["!=", "="]
+                    | ["<", "="]
+                    | ["=", ">"]
+                    | ["<", ">"]
+                    | ["<", ">="]
+                    | ["<=", ">"]
+                    | ["contains", "not_contains"]
+                    | ["not_one_of", "one_of"]
+                    | ["has_prefix", "not_has_prefix"]
+                    | ["has_suffix", "not_has_suffix"]
+                    | ["has_substring", "not_has_substring"]
+                    | ["not_regex", "regex"]
+            )
+        }
+    }
+}
+
+fn lint_property_filter_values(
+    property: &str,
+    vid: Vid,
+    filters: &[&PropertyFilter],
+    arguments: &BTreeMap<Arc<str>, FieldValue>,
+    lints: &mut Vec<QueryLint>,
+) {
+    let valued_filters: Vec<_> = filters
+        .iter()
+        .filter_map(|filter| match filter.right() {
+            Some(Argument::Variable(variable)) => arguments
+                .get(&variable.variable_name)
+                .map(|value| (*filter, variable.variable_name.as_ref(), value)),
+            _ => None,
+        })
+        .collect();
+
+    for (index, &(first, first_variable, first_value)) in valued_filters.iter().enumerate() {
+        for &(second, second_variable, second_value) in &valued_filters[index + 1..] {
+            // Filters using the same variable were already analyzed without its value.
+            if first_variable == second_variable {
+                continue;
+            }
+
+            if contradicts(first, first_value, second, second_value)
+                || contradicts(second, second_value, first, first_value)
+            {
+                lints.push(unsatisfiable_filters(property, vid, first, second));
+            } else if implies(first, first_value, second, second_value) {
+                lints.push(redundant_filter(property, vid, second, first));
+            } else if implies(second, second_value, first, first_value) {
+                lints.push(redundant_filter(property, vid, first, second));
+            }
+        }
+    }
+}
+
+/// Compare two values the way the ordering filters do, if they are comparable.
+fn compare_values(left: &FieldValue, right: &FieldValue) -> Option<Ordering> {
+    match (left, right) {
+        (
+            FieldValue::Int64(..) | FieldValue::Uint64(..),
+            FieldValue::Int64(..) | FieldValue::Uint64(..),
+        ) => left.partial_cmp(right),
+        (FieldValue::Float64(l), FieldValue::Float64(r)) => l.partial_cmp(r),
+        (FieldValue::String(l), FieldValue::String(r)) => Some(l.cmp(r)),
+        (FieldValue::DateTimeUtc(l), FieldValue::DateTimeUtc(r)) => Some(l.cmp(r)),
+        _ => None,
+    }
+}
+
+/// Whether the ordering filter matches a property value that compares to the filter's
+/// argument value in the given way.
+fn ordering_matches(filter: &PropertyFilter, ordering: Ordering) -> bool {
+    match filter {
+        Operation::LessThan(..) => ordering.is_lt(),
+        Operation::LessThanOrEqual(..) => ordering.is_le(),
+        Operation::GreaterThan(..) => ordering.is_gt(),
+        Operation::GreaterThanOrEqual(..) => ordering.is_ge(),
+        _ => unreachable!("not an ordering filter: {filter:?}"),
+    }
+}
+
+fn is_lower_bound(filter: &PropertyFilter) -> bool {
+    matches!(
+        filter,
+        Operation::GreaterThan(..) | Operation::GreaterThanOrEqual(..)
+    )
+}
+
+fn is_upper_bound(filter: &PropertyFilter) -> bool {
+    matches!(
+        filter,
+        Operation::LessThan(..) | Operation::LessThanOrEqual(..)
+    )
+}
+
+fn is_strict_bound(filter: &PropertyFilter) -> bool {
+    matches!(filter, Operation::LessThan(..) | Operation::GreaterThan(..))
+}
+
+fn list_contains(list: &FieldValue, value: &FieldValue) -> Option<bool> {
+    match list {
+        FieldValue::List(values) => Some(values.iter().any(|element| element == value)),
+        _ => None,
+    }
+}
+
+/// Whether no property value satisfies the first filter with the first value
+/// as well as the second filter with the second value.
+fn contradicts(
+    first: &PropertyFilter,
+    first_value: &FieldValue,
+    second: &PropertyFilter,
+    second_value: &FieldValue,
+) -> bool {
+    match first {
+        Operation::Equals(..) => match second {
+            Operation::Equals(..) => first_value != second_value,
+            Operation::NotEquals(..) => first_value == second_value,
+            Operation::OneOf(..) => list_contains(second_value, first_value) == Some(false),
+            Operation::NotOneOf(..) => list_contains(second_value, first_value) == Some(true),
+            _ if is_upper_bound(second) || is_lower_bound(second) => {
+                // Ordering filters never match null values.
+                matches!(first_value, FieldValue::Null)
+                    || compare_values(first_value, second_value)
+                        .is_some_and(|ordering| !ordering_matches(second, ordering))
+            }
+            _ => false,
+        },
+        _ if is_lower_bound(first) && is_upper_bound(second) => {
+            match compare_values(first_value, second_value) {
+                Some(Ordering::Greater) => true,
+                Some(Ordering::Equal) => is_strict_bound(first) || is_strict_bound(second),
+                Some(Ordering::Less) | None => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Whether every property value that satisfies the first filter with the first value
+/// also satisfies the second filter with the second value.
+fn implies(
+    first: &PropertyFilter,
+    first_value: &FieldValue,
+    second: &PropertyFilter,
+    second_value: &FieldValue,
+) -> bool {
+    match first {
+        Operation::Equals(..) => match second {
+            Operation::NotEquals(..) => first_value != second_value,
+            Operation::OneOf(..) => list_contains(second_value, first_value) == Some(true),
+            Operation::NotOneOf(..) => list_contains(second_value, first_value) == Some(false),
+            _ if is_upper_bound(second) || is_lower_bound(second) => {
+                compare_values(first_value, second_value)
+                    .is_some_and(|ordering| ordering_matches(second, ordering))
+            }
+            _ => false,
+        },
+        _ if (is_lower_bound(first) && is_lower_bound(second))
+            || (is_upper_bound(first) && is_upper_bound(second)) =>
+        {
+            // A bound implies another in the same direction if it's at least as tight.
+            let tighter = if is_lower_bound(first) {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+            match compare_values(first_value, second_value) {
+                Some(ordering) if ordering == tighter => true,
+                Some(Ordering::Equal) => is_strict_bound(first) || !is_strict_bound(second),
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+fn describe_filter(filter: &PropertyFilter) -> String {
+    match filter.right() {
+        None => format!("@filter(op: \"{}\")", filter.operation_name()),
+        Some(argument) => {
+            let argument = match argument {
+                Argument::Variable(variable) => format!("${}", variable.variable_name),
+                Argument::Tag(FieldRef::ContextField(field)) => {
+                    format!("%{}", field.field_name)
+                }
+                Argument::Tag(FieldRef::FoldSpecificField(..)) => "%<fold count>".to_string(),
+            };
+            format!(
+                "@filter(op: \"{}\", value: [\"{argument}\"])",
+                filter.operation_name()
+            )
+        }
+    }
+}
+
+fn unsatisfiable_filters(
+    property: &str,
+    vid: Vid,
+    first: &PropertyFilter,
+    second: &PropertyFilter,
+) -> QueryLint {
+    QueryLint::UnsatisfiableFilters(
+        property.to_string(),
+        vid,
+        describe_filter(first),
+        describe_filter(second),
+    )
+}
+
+fn redundant_filter(
+    property: &str,
+    vid: Vid,
+    redundant: &PropertyFilter,
+    implying: &PropertyFilter,
+) -> QueryLint {
+    QueryLint::RedundantFilter(
+        property.to_string(),
+        vid,
+        describe_filter(redundant),
+        describe_filter(implying),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, fs, sync::Arc};
+
+    use crate::{
+        frontend::parse,
+        ir::{Eid, FieldValue, Vid},
+        schema::Schema,
+    };
+
+    use super::QueryLint;
+
+    fn numbers_schema() -> Schema {
+        Schema::parse(fs::read_to_string("test_data/schemas/numbers.graphql").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn effective_optional_edges_and_folds_are_lint_free() {
+        let query = r#"
+{
+    Number(max: 10) {
+        value @output
+        predecessor @optional {
+            value @output(name: "predecessor")
+        }
+        successor @optional {
+            value @filter(op: ">", value: ["$min"])
+        }
+        multiple(max: 3) @fold @transform(op: "count") @filter(op: ">", value: ["$count"]) {
+            value
+        }
+        vowels: predecessor @fold {
+            value @output(name: "factors")
+        }
+    }
+}"#;
+        let indexed_query = parse(&numbers_schema(), query).unwrap();
+        assert_eq!(Vec::<QueryLint>::new(), indexed_query.ir_query.lint());
+    }
+
+    #[test]
+    fn ineffective_optional_edges_and_folds() {
+        let query = r#"
+{
+    Number(max: 10) {
+        value @output
+        predecessor @optional {
+            value
+            successor {
+                name
+            }
+        }
+        multiple(max: 3) @fold {
+            predecessor @optional {
+                value @output(name: "predecessors")
+            }
+            successor @optional {
+                value
+            }
+        }
+        vowels: predecessor @fold {
+            value
+        }
+    }
+}"#;
+        let indexed_query = parse(&numbers_schema(), query).unwrap();
+        let lints = indexed_query.ir_query.lint();
+        assert_eq!(
+            vec![
+                QueryLint::IneffectiveOptionalEdge(
+                    "predecessor".to_string(),
+                    Eid::new(1.try_into().unwrap())
+                ),
+                QueryLint::IneffectiveOptionalEdge(
+                    "successor".to_string(),
+                    Eid::new(5.try_into().unwrap())
+                ),
+                QueryLint::IneffectiveFold(
+                    "predecessor".to_string(),
+                    Eid::new(6.try_into().unwrap())
+                ),
+            ],
+            lints
+        );
+        assert_eq!(
+            vec![
+                "ineffective_optional_edge",
+                "ineffective_optional_edge",
+                "ineffective_fold"
+            ],
+            lints.iter().map(|lint| lint.code()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn declared_variables_must_be_used() {
+        let query = r#"
+{
+    Number(max: 10) {
+        value @output @filter(op: ">", value: ["$min"])
+    }
+}"#;
+        let mut ir_query = parse(&numbers_schema(), query).unwrap().ir_query.clone();
+        let variable_type = ir_query.variables["min"].clone();
+        ir_query
+            .variables
+            .insert(Arc::from("unused"), variable_type);
+
+        assert_eq!(
+            vec![QueryLint::UnusedVariable("unused".to_string())],
+            ir_query.lint()
+        );
+    }
+
+    #[test]
+    fn contradictory_and_redundant_filters() {
+        let query = r#"
+{
+    Number(max: 10) {
+        value @output
+            @filter(op: "=", value: ["$value"])
+            @filter(op: "!=", value: ["$value"])
+            @filter(op: ">", value: ["$min"])
+            @filter(op: ">", value: ["$min"])
+        name @filter(op: "is_not_null") @filter(op: "has_prefix", value: ["$prefix"])
+        successor {
+            name @filter(op: "is_null") @filter(op: "regex", value: ["$pattern"])
+        }
+    }
+}"#;
+        let indexed_query = parse(&numbers_schema(), query).unwrap();
+        let vid = |n: usize| Vid::new(n.try_into().unwrap());
+        assert_eq!(
+            vec![
+                QueryLint::RedundantFilter(
+                    "name".to_string(),
+                    vid(1),
+                    "@filter(op: \"is_not_null\")".to_string(),
+                    "@filter(op: \"has_prefix\", value: [\"$prefix\"])".to_string(),
+                ),
+                QueryLint::UnsatisfiableFilters(
+                    "value".to_string(),
+                    vid(1),
+                    "@filter(op: \"=\", value: [\"$value\"])".to_string(),
+                    "@filter(op: \"!=\", value: [\"$value\"])".to_string(),
+                ),
+                QueryLint::RedundantFilter(
+                    "value".to_string(),
+                    vid(1),
+                    "@filter(op: \">\", value: [\"$min\"])".to_string(),
+                    "@filter(op: \">\", value: [\"$min\"])".to_string(),
+                ),
+                QueryLint::UnsatisfiableFilters(
+                    "name".to_string(),
+                    vid(2),
+                    "@filter(op: \"is_null\")".to_string(),
+                    "@filter(op: \"regex\", value: [\"$pattern\"])".to_string(),
+                ),
+            ],
+            indexed_query.ir_query.lint()
+        );
+    }
+
+    #[test]
+    fn filters_contradicting_given_arguments() {
+        let query = r#"
+query($other: String = "two") {
+    Number(max: 10) {
+        value @output
+            @filter(op: ">", value: ["$min"])
+            @filter(op: "<", value: ["$max"])
+            @filter(op: ">=", value: ["$lower"])
+        name @filter(op: "=", value: ["$name"]) @filter(op: "=", value: ["$other"])
+    }
+}"#;
+        let indexed_query = parse(&numbers_schema(), query).unwrap();
+        let arguments = |min: i64, max: i64| -> BTreeMap<Arc<str>, FieldValue> {
+            btreemap! {
+                Arc::from("min") => FieldValue::Int64(min),
+                Arc::from("max") => FieldValue::Int64(max),
+                Arc::from("lower") => FieldValue::Int64(2),
+                Arc::from("name") => FieldValue::String("two".to_string()),
+            }
+        };
+
+        let lints = indexed_query.ir_query.lint_with_arguments(&arguments(3, 5));
+        assert_eq!(
+            vec![QueryLint::RedundantFilter(
+                "value".to_string(),
+                Vid::new(1.try_into().unwrap()),
+                "@filter(op: \">=\", value: [\"$lower\"])".to_string(),
+                "@filter(op: \">\", value: [\"$min\"])".to_string(),
+            )],
+            lints
+        );
+
+        let mut arguments = arguments(5, 5);
+        arguments.insert(Arc::from("other"), FieldValue::String("three".to_string()));
+        let codes: Vec<_> = indexed_query
+            .ir_query
+            .lint_with_arguments(&arguments)
+            .iter()
+            .map(|lint| lint.code())
+            .collect();
+        assert_eq!(
+            vec![
+                "unsatisfiable_filters",
+                "unsatisfiable_filters",
+                "redundant_filter"
+            ],
+            codes
+        );
+    }
+}
@@ -1,32 +1,42 @@
 //! Trustfall intermediate representation (IR)
 #![allow(dead_code)]
 
+pub mod canonical;
 mod indexed;
-pub mod serialization;
+pub mod json;
+pub mod lint;
+mod ty;
 pub mod types;
 pub mod value;
 
 use std::{
-    cmp::Ordering, collections::BTreeMap, fmt::Debug, num::NonZeroUsize, ops::Index, sync::Arc,
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    fmt::{Debug, Display},
+    num::NonZeroUsize,
+    ops::Index,
+    sync::Arc,
 };
 
-use async_graphql_parser::types::{BaseType, Type};
 use async_graphql_value::Name;
 use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
 
-use crate::frontend::error::FilterTypeError;
+use crate::frontend::error::{FilterTypeError, FrontendWarning};
 
-pub use self::indexed::{EdgeKind, IndexedQuery, InvalidIRQueryError, Output};
-use self::types::{
-    are_base_types_equal_ignoring_nullability, is_base_type_orderable, NamedTypedValue,
+pub use self::indexed::{
+    EdgeKind, ExpectedVariable, IndexedQuery, InvalidIRQueryError, Output, VertexFilter,
+    VertexMetadata, VertexOutput,
 };
+pub use self::ty::{InvalidTypeError, Type};
+use self::types::NamedTypedValue;
 pub use self::value::{FieldValue, TransparentValue};
 
 pub(crate) const TYPENAME_META_FIELD: &str = "__typename";
 
 lazy_static! {
     pub(crate) static ref TYPENAME_META_FIELD_NAME: Name = Name::new(TYPENAME_META_FIELD);
-    pub(crate) static ref TYPENAME_META_FIELD_TYPE: Type = Type::new("String!").unwrap();
+    pub(crate) static ref TYPENAME_META_FIELD_TYPE: Type = Type::named("String", false);
     pub(crate) static ref TYPENAME_META_FIELD_ARC: Arc<str> = Arc::from(TYPENAME_META_FIELD);
 }
 
@@ -41,6 +51,12 @@ impl Vid {
     }
 }
 
+impl Display for Vid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 /// Unique edge ID identifying a specific edge in a Trustfall query
 #[doc(alias = "edge")]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
@@ -52,6 +68,12 @@ impl Eid {
     }
 }
 
+impl Display for Eid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 /// Parameter values for an edge expansion.
 ///
 /// Passed as an argument to the [`Adapter::resolve_starting_vertices`] and
@@ -132,13 +154,53 @@ pub struct IRQuery {
 
     pub root_component: Arc<IRQueryComponent>,
 
-    #[serde(
-        default,
-        skip_serializing_if = "BTreeMap::is_empty",
-        serialize_with = "crate::ir::serialization::serde_variables_serializer",
-        deserialize_with = "crate::ir::serialization::serde_variables_deserializer"
-    )]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub variables: BTreeMap<Arc<str>, Type>,
+
+    /// The default values of the variables whose declarations in the query specify one.
+    /// These are used when the query is executed without a value for the variable.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variable_defaults: BTreeMap<Arc<str>, FieldValue>,
+
+    /// The declared values of each enum type used by the query's variables and outputs.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub enum_values: BTreeMap<Arc<str>, BTreeSet<Arc<str>>>,
+
+    /// The descriptions of outputs, taken from the comments preceding
+    /// the fields that produce them in the query text.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub output_descriptions: BTreeMap<Arc<str>, Arc<str>>,
+
+    /// Non-fatal diagnostics about the query, such as uses of deprecated schema fields,
+    /// in the order in which the query mentions them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<FrontendWarning>,
+
+    /// The schema fields marked `@restricted` that the query uses, sorted by type and field name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub restricted_fields: Vec<RestrictedFieldUse>,
+}
+
+/// How a query uses a schema field marked `@restricted(scope: String)`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RestrictedFieldUse {
+    pub type_name: Arc<str>,
+    pub field_name: Arc<str>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<Arc<str>>,
+
+    /// Whether the field is an edge rather than a property.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub is_edge: bool,
+
+    /// The names of the outputs containing the property's values.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub outputs: BTreeSet<Arc<str>>,
+
+    /// Whether the property's values are used by filters, either directly or via tags.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub used_in_filters: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -159,6 +221,10 @@ pub struct IREdge {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub recursive: Option<Recursive>,
+
+    /// The cost of expanding this edge, if the schema annotates it with `@cost`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<FieldCost>,
 }
 
 fn default_optional() -> bool {
@@ -183,6 +249,51 @@ impl Recursive {
     }
 }
 
+/// The relative cost of resolving a property or expanding an edge,
+/// as annotated in the schema with `@cost(fanout: Int, latency: String)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldCost {
+    /// The expected number of neighboring vertices produced by each expansion of the edge.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fanout: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency: Option<Latency>,
+}
+
+impl FieldCost {
+    /// A single number summarizing the cost, for comparing fields against each other.
+    ///
+    /// Fields without a `@cost` annotation have an estimate of 1: a single low-latency result.
+    pub fn estimate(&self) -> u64 {
+        let latency_weight = match self.latency.unwrap_or(Latency::Low) {
+            Latency::Low => 1,
+            Latency::Medium => 10,
+            Latency::High => 100,
+        };
+        self.fanout.unwrap_or(1).saturating_mul(latency_weight)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Latency {
+    Low,
+    Medium,
+    High,
+}
+
+impl Latency {
+    /// Parse the latency names used in the schema: `"low"`, `"medium"`, and `"high"`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "low" => Some(Self::Low),
+            "medium" => Some(Self::Medium),
+            "high" => Some(Self::High),
+            _ => None,
+        }
+    }
+}
+
 /// Representation of a vertex (node) in the Trustfall intermediate
 /// representation (IR).
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -195,8 +306,8 @@ pub struct IRVertex {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub coerced_from_type: Option<Arc<str>>,
 
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub filters: Vec<Operation<LocalField, Argument>>,
+    #[serde(default, skip_serializing_if = "SmallVec::is_empty")]
+    pub filters: SmallVec<[Operation<LocalField, Argument>; 1]>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -213,14 +324,18 @@ pub struct IRFold {
 
     /// Tags from the directly-enclosing component whose values are needed
     /// inside this fold's component or one of its subcomponents.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub imported_tags: Vec<FieldRef>,
+    #[serde(default, skip_serializing_if = "SmallVec::is_empty")]
+    pub imported_tags: SmallVec<[FieldRef; 1]>,
 
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub fold_specific_outputs: BTreeMap<Arc<str>, FoldSpecificFieldKind>,
 
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub post_filters: Vec<Operation<FoldSpecificFieldKind, Argument>>,
+    #[serde(default, skip_serializing_if = "SmallVec::is_empty")]
+    pub post_filters: SmallVec<[Operation<FoldSpecificFieldKind, Argument>; 1]>,
+
+    /// The cost of expanding this fold's edge, if the schema annotates it with `@cost`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<FieldCost>,
 }
 
 #[non_exhaustive]
@@ -230,10 +345,7 @@ pub enum FoldSpecificFieldKind {
 }
 
 lazy_static! {
-    static ref NON_NULL_INT_TYPE: Type = Type {
-        base: BaseType::Named(Name::new("Int")),
-        nullable: false,
-    };
+    static ref NON_NULL_INT_TYPE: Type = Type::named("Int", false);
 }
 
 impl FoldSpecificFieldKind {
@@ -598,9 +710,14 @@ where
 }
 
 impl<LeftT: NamedTypedValue> Operation<LeftT, Argument> {
+    /// Check that the operand types are valid for this operation.
+    ///
+    /// Whether a type may be used with ordering operations like `<` is decided by
+    /// the `is_orderable` function, since schemas may define additional orderable types.
     pub(crate) fn operand_types_valid(
         &self,
         tag_name: Option<&str>,
+        is_orderable: impl Fn(&Type) -> bool,
     ) -> Result<(), Vec<FilterTypeError>> {
         let left = self.left();
         let right = self.right();
@@ -620,7 +737,7 @@ impl<LeftT: NamedTypedValue> Operation<LeftT, Argument> {
         match self {
             Operation::IsNull(_) | Operation::IsNotNull(_) => {
                 // Checking non-nullable types for null or non-null is pointless.
-                if left_type.nullable {
+                if left_type.is_nullable() {
                     Ok(())
                 } else {
                     Err(vec![
@@ -639,7 +756,7 @@ impl<LeftT: NamedTypedValue> Operation<LeftT, Argument> {
                 // For the operands relative to each other, nullability doesn't matter,
                 // but the rest of the type must be the same.
                 let right_type = right_type.unwrap();
-                if are_base_types_equal_ignoring_nullability(&left_type.base, &right_type.base) {
+                if left_type.equal_ignoring_nullability(right_type) {
                     Ok(())
                 } else {
                     // The right argument must be a tag at this point. If it is not a tag
@@ -666,7 +783,7 @@ impl<LeftT: NamedTypedValue> Operation<LeftT, Argument> {
                 let right_type = right_type.unwrap();
 
                 let mut errors = vec![];
-                if !is_base_type_orderable(&left_type.base) {
+                if !is_orderable(left_type) {
                     errors.push(FilterTypeError::OrderingFilterOperationOnNonOrderableField(
                         self.operation_name().to_string(),
                         left.named().to_string(),
@@ -674,12 +791,10 @@ impl<LeftT: NamedTypedValue> Operation<LeftT, Argument> {
                     ));
                 }
 
-                if !is_base_type_orderable(&right_type.base) {
-                    // The right argument must be a tag at this point. If it is not a tag
-                    // and the second .unwrap() below panics, then our type inference
-                    // has inferred an incorrect type for the variable in the argument.
-                    let tag = right.unwrap().as_tag().unwrap();
-
+                // Variables' types are inferred from the left operand's type, so
+                // a non-orderable variable type has already been reported above.
+                let right_tag = right.and_then(|x| x.as_tag());
+                if let Some(tag) = right_tag.filter(|_| !is_orderable(right_type)) {
                     errors.push(FilterTypeError::OrderingFilterOperationOnNonOrderableTag(
                         self.operation_name().to_string(),
                         tag_name.unwrap().to_string(),
@@ -690,7 +805,7 @@ impl<LeftT: NamedTypedValue> Operation<LeftT, Argument> {
 
                 // For the operands relative to each other, nullability doesn't matter,
                 // but the types must be equal to each other.
-                if !are_base_types_equal_ignoring_nullability(&left_type.base, &right_type.base) {
+                if !left_type.equal_ignoring_nullability(right_type) {
                     // The right argument must be a tag at this point. If it is not a tag
                     // and the second .unwrap() below panics, then our type inference
                     // has inferred an incorrect type for the variable in the argument.
@@ -715,22 +830,19 @@ impl<LeftT: NamedTypedValue> Operation<LeftT, Argument> {
             Operation::Contains(_, _) | Operation::NotContains(_, _) => {
                 // The left-hand operand needs to be a list, ignoring nullability.
                 // The right-hand operand may be anything, if considered individually.
-                let inner_type = match &left_type.base {
-                    BaseType::List(ty) => Ok(ty),
-                    BaseType::Named(_) => {
-                        Err(vec![FilterTypeError::ListFilterOperationOnNonListField(
-                            self.operation_name().to_string(),
-                            left.named().to_string(),
-                            left_type.to_string(),
-                        )])
-                    }
-                }?;
+                let inner_type = left_type.element_type().ok_or_else(|| {
+                    vec![FilterTypeError::ListFilterOperationOnNonListField(
+                        self.operation_name().to_string(),
+                        left.named().to_string(),
+                        left_type.to_string(),
+                    )]
+                })?;
 
                 let right_type = right_type.unwrap();
 
                 // However, the type inside the left-hand list must be equal,
                 // ignoring nullability, to the type of the right-hand operand.
-                if are_base_types_equal_ignoring_nullability(&inner_type.base, &right_type.base) {
+                if inner_type.equal_ignoring_nullability(right_type) {
                     Ok(())
                 } else {
                     // The right argument must be a tag at this point. If it is not a tag
@@ -752,26 +864,23 @@ impl<LeftT: NamedTypedValue> Operation<LeftT, Argument> {
                 // The right-hand operand needs to be a list, ignoring nullability.
                 // The left-hand operand may be anything, if considered individually.
                 let right_type = right_type.unwrap();
-                let inner_type = match &right_type.base {
-                    BaseType::List(ty) => Ok(ty),
-                    BaseType::Named(_) => {
-                        // The right argument must be a tag at this point. If it is not a tag
-                        // and the second .unwrap() below panics, then our type inference
-                        // has inferred an incorrect type for the variable in the argument.
-                        let tag = right.unwrap().as_tag().unwrap();
-
-                        Err(vec![FilterTypeError::ListFilterOperationOnNonListTag(
-                            self.operation_name().to_string(),
-                            tag_name.unwrap().to_string(),
-                            tag.field_name().to_string(),
-                            tag.field_type().to_string(),
-                        )])
-                    }
-                }?;
+                let inner_type = right_type.element_type().ok_or_else(|| {
+                    // The right argument must be a tag at this point. If it is not a tag
+                    // and the second .unwrap() below panics, then our type inference
+                    // has inferred an incorrect type for the variable in the argument.
+                    let tag = right.unwrap().as_tag().unwrap();
+
+                    vec![FilterTypeError::ListFilterOperationOnNonListTag(
+                        self.operation_name().to_string(),
+                        tag_name.unwrap().to_string(),
+                        tag.field_name().to_string(),
+                        tag.field_type().to_string(),
+                    )]
+                })?;
 
                 // However, the type inside the right-hand list must be equal,
                 // ignoring nullability, to the type of the left-hand operand.
-                if are_base_types_equal_ignoring_nullability(&left_type.base, &inner_type.base) {
+                if left_type.equal_ignoring_nullability(&inner_type) {
                     Ok(())
                 } else {
                     // The right argument must be a tag at this point. If it is not a tag
@@ -800,31 +909,26 @@ impl<LeftT: NamedTypedValue> Operation<LeftT, Argument> {
                 let mut errors = vec![];
 
                 // Both operands need to be strings, ignoring nullability.
-                match &left_type.base {
-                    BaseType::Named(ty) if ty == "String" => {}
-                    _ => {
-                        errors.push(FilterTypeError::StringFilterOperationOnNonStringField(
-                            self.operation_name().to_string(),
-                            left.named().to_string(),
-                            left_type.to_string(),
-                        ));
-                    }
-                };
-
-                match &right_type.unwrap().base {
-                    BaseType::Named(ty) if ty == "String" => {}
-                    _ => {
-                        // The right argument must be a tag at this point. If it is not a tag
-                        // and the second .unwrap() below panics, then our type inference
-                        // has inferred an incorrect type for the variable in the argument.
-                        let tag = right.unwrap().as_tag().unwrap();
-                        errors.push(FilterTypeError::StringFilterOperationOnNonStringTag(
-                            self.operation_name().to_string(),
-                            tag_name.unwrap().to_string(),
-                            tag.field_name().to_string(),
-                            tag.field_type().to_string(),
-                        ));
-                    }
+                let is_string = |ty: &Type| !ty.is_list() && ty.base_type() == "String";
+                if !is_string(left_type) {
+                    errors.push(FilterTypeError::StringFilterOperationOnNonStringField(
+                        self.operation_name().to_string(),
+                        left.named().to_string(),
+                        left_type.to_string(),
+                    ));
+                }
+
+                if !is_string(right_type.unwrap()) {
+                    // The right argument must be a tag at this point. If it is not a tag
+                    // and the second .unwrap() below panics, then our type inference
+                    // has inferred an incorrect type for the variable in the argument.
+                    let tag = right.unwrap().as_tag().unwrap();
+                    errors.push(FilterTypeError::StringFilterOperationOnNonStringTag(
+                        self.operation_name().to_string(),
+                        tag_name.unwrap().to_string(),
+                        tag.field_name().to_string(),
+                        tag.field_type().to_string(),
+                    ));
                 }
 
                 if errors.is_empty() {
@@ -843,8 +947,6 @@ pub struct ContextField {
 
     pub field_name: Arc<str>,
 
-    #[serde(serialize_with = "crate::ir::serialization::serde_type_serializer")]
-    #[serde(deserialize_with = "crate::ir::serialization::serde_type_deserializer")]
     pub field_type: Type,
 }
 
@@ -852,8 +954,6 @@ pub struct ContextField {
 pub struct LocalField {
     pub field_name: Arc<str>,
 
-    #[serde(serialize_with = "crate::ir::serialization::serde_type_serializer")]
-    #[serde(deserialize_with = "crate::ir::serialization::serde_type_deserializer")]
     pub field_type: Type,
 }
 
@@ -861,8 +961,6 @@ pub struct LocalField {
 pub struct VariableRef {
     pub variable_name: Arc<str>,
 
-    #[serde(serialize_with = "crate::ir::serialization::serde_type_serializer")]
-    #[serde(deserialize_with = "crate::ir::serialization::serde_type_deserializer")]
     pub variable_type: Type,
 }
 
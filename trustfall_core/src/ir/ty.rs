@@ -0,0 +1,289 @@
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use async_graphql_parser::types::{BaseType, Type as GraphQLType};
+use serde::{Deserialize, Serialize};
+
+/// The types of lists nested more deeply than this can't be represented.
+const MAX_LIST_DEPTH: u8 = 31;
+
+/// The type of a property, parameter, or variable in a query: a named type,
+/// possibly nested within lists, with the nullability of each layer.
+///
+/// Unlike the types of the GraphQL parser, this doesn't allocate a layer per list:
+/// the nullability of every layer fits in one bitmask, so types are cheap to clone,
+/// compare, and check for subtyping.
+///
+/// Types are (de)serialized in their GraphQL syntax, such as `[String!]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Type {
+    base: Arc<str>,
+
+    /// Bit `i` is set if the layer `i` lists deep is nullable: bit 0 is the type itself,
+    /// and bit `list_depth` is the named type within its lists.
+    nullable: u32,
+
+    list_depth: u8,
+}
+
+impl Type {
+    /// The named type, outside of any lists.
+    pub fn named(base: impl Into<Arc<str>>, nullable: bool) -> Self {
+        Self {
+            base: base.into(),
+            nullable: nullable as u32,
+            list_depth: 0,
+        }
+    }
+
+    /// The type of a list of the given element type.
+    ///
+    /// # Panics
+    ///
+    /// If the element type is already nested within the most lists that can be represented.
+    pub fn list_of(element: &Type, nullable: bool) -> Self {
+        assert!(
+            element.list_depth < MAX_LIST_DEPTH,
+            "{element} is nested within too many lists to be the elements of a list"
+        );
+        Self {
+            base: element.base.clone(),
+            nullable: (element.nullable << 1) | nullable as u32,
+            list_depth: element.list_depth + 1,
+        }
+    }
+
+    /// Parse a type from its GraphQL syntax, such as `[String!]`.
+    pub fn parse(ty: &str) -> Result<Self, InvalidTypeError> {
+        let invalid = || InvalidTypeError(ty.to_string());
+
+        let (rest, nullable) = match ty.trim().strip_suffix('!') {
+            Some(rest) => (rest.trim_end(), false),
+            None => (ty.trim(), true),
+        };
+        if let Some(element) = rest
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            let element = Self::parse(element).map_err(|_| invalid())?;
+            if element.list_depth >= MAX_LIST_DEPTH {
+                return Err(invalid());
+            }
+            Ok(Self::list_of(&element, nullable))
+        } else if is_name(rest) {
+            Ok(Self::named(rest, nullable))
+        } else {
+            Err(invalid())
+        }
+    }
+
+    /// The name of the type within all its lists, such as `String` for `[String!]`.
+    pub fn base_type(&self) -> &str {
+        &self.base
+    }
+
+    pub fn is_nullable(&self) -> bool {
+        self.nullable & 1 != 0
+    }
+
+    pub fn is_list(&self) -> bool {
+        self.list_depth > 0
+    }
+
+    /// The type of the elements of this list type, or `None` if this isn't a list type.
+    pub fn element_type(&self) -> Option<Type> {
+        self.is_list().then(|| Self {
+            base: self.base.clone(),
+            nullable: self.nullable >> 1,
+            list_depth: self.list_depth - 1,
+        })
+    }
+
+    /// This type, but with the given nullability. The nullability of any elements is unchanged.
+    pub fn with_nullability(&self, nullable: bool) -> Self {
+        Self {
+            base: self.base.clone(),
+            nullable: (self.nullable & !1) | nullable as u32,
+            list_depth: self.list_depth,
+        }
+    }
+
+    /// Whether the types are the same if the nullability of all their layers is ignored.
+    pub fn equal_ignoring_nullability(&self, other: &Type) -> bool {
+        self.list_depth == other.list_depth && self.base == other.base
+    }
+
+    /// Check for scalar-only subtyping: whether every value of this type is also
+    /// a value of the parent type.
+    ///
+    /// Scalars don't have an inheritance structure, so they are able to be compared without a schema.
+    /// Callers of this function must guarantee that the types are either scalars or
+    /// (potentially multiply-nested) lists of scalars.
+    ///
+    /// This function considers types of different names to always be non-equal and unrelated:
+    /// neither is a subtype of the other. So given `interface Base` and `type Derived implements Base`,
+    /// `Derived` is not considered a subtype of `Base`, since this function never sees
+    /// the definitions of `Base` and `Derived` as those are part of a schema which this function
+    /// never gets.
+    pub(crate) fn is_scalar_only_subtype_of(&self, parent_type: &Type) -> bool {
+        // Wherever the parent type is non-nullable, its subtypes must be non-nullable as well.
+        // Where it's nullable, its subtypes may be either nullable or non-nullable.
+        self.equal_ignoring_nullability(parent_type) && (self.nullable & !parent_type.nullable) == 0
+    }
+
+    /// The types of values that are both of this type and of the other one, if there are any.
+    pub(crate) fn intersect(&self, other: &Type) -> Option<Type> {
+        self.equal_ignoring_nullability(other).then(|| Self {
+            base: self.base.clone(),
+            nullable: self.nullable & other.nullable,
+            list_depth: self.list_depth,
+        })
+    }
+
+    fn is_layer_nullable(&self, layer: u8) -> bool {
+        self.nullable & (1 << layer) != 0
+    }
+}
+
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(first) if first == '_' || first.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for _ in 0..self.list_depth {
+            f.write_str("[")?;
+        }
+        f.write_str(&self.base)?;
+        for layer in (0..=self.list_depth).rev() {
+            if !self.is_layer_nullable(layer) {
+                f.write_str("!")?;
+            }
+            if layer > 0 {
+                f.write_str("]")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Type {
+    type Err = InvalidTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl From<Type> for String {
+    fn from(value: Type) -> Self {
+        value.to_string()
+    }
+}
+
+impl TryFrom<String> for Type {
+    type Error = InvalidTypeError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl From<&GraphQLType> for Type {
+    fn from(value: &GraphQLType) -> Self {
+        match &value.base {
+            BaseType::Named(name) => Self::named(name.as_str(), value.nullable),
+            BaseType::List(element) => Self::list_of(&Self::from(element.as_ref()), value.nullable),
+        }
+    }
+}
+
+/// The string is not the GraphQL syntax of a type that can be represented.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("not a valid GraphQL type: {0}")]
+pub struct InvalidTypeError(String);
+
+#[cfg(test)]
+mod tests {
+    use async_graphql_parser::types::Type as GraphQLType;
+
+    use super::Type;
+
+    #[test]
+    fn types_round_trip_through_their_syntax() {
+        for ty in [
+            "String",
+            "Int!",
+            "[String]",
+            "[String!]!",
+            "[[Int]!]",
+            "[[[ID!]]!]!",
+        ] {
+            let parsed = Type::parse(ty).unwrap();
+            assert_eq!(ty, parsed.to_string());
+
+            let graphql = GraphQLType::new(ty).unwrap();
+            assert_eq!(parsed, Type::from(&graphql));
+        }
+
+        assert_eq!(
+            Type::parse("[ String !] !").unwrap().to_string(),
+            "[String!]!"
+        );
+        for invalid in [
+            "",
+            "!",
+            "[String",
+            "String]",
+            "[]",
+            "[String!]!!",
+            "Str-ing",
+            "1D",
+        ] {
+            assert!(Type::parse(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn types_are_composed_of_layers() {
+        let ty = Type::parse("[[Int]!]").unwrap();
+        assert!(ty.is_nullable());
+        assert!(ty.is_list());
+        assert_eq!("Int", ty.base_type());
+
+        let element = ty.element_type().unwrap();
+        assert_eq!(Type::parse("[Int]!").unwrap(), element);
+        assert_eq!(ty, Type::list_of(&element, true));
+        assert_eq!(
+            Type::parse("[[Int]!]!").unwrap(),
+            ty.with_nullability(false)
+        );
+
+        let innermost = element.element_type().unwrap();
+        assert_eq!(Type::named("Int", true), innermost);
+        assert_eq!(None, innermost.element_type());
+    }
+
+    #[test]
+    fn types_are_compared_layer_by_layer() {
+        let parse = |ty| Type::parse(ty).unwrap();
+
+        assert!(parse("[Int]").equal_ignoring_nullability(&parse("[Int!]!")));
+        assert!(!parse("[Int]").equal_ignoring_nullability(&parse("[[Int]]")));
+        assert!(!parse("[Int]").equal_ignoring_nullability(&parse("[String]")));
+
+        assert!(parse("[Int!]!").is_scalar_only_subtype_of(&parse("[Int]")));
+        assert!(parse("[Int!]!").is_scalar_only_subtype_of(&parse("[Int]!")));
+        assert!(!parse("[Int]").is_scalar_only_subtype_of(&parse("[Int!]")));
+        assert!(!parse("[Int]").is_scalar_only_subtype_of(&parse("[Int]!")));
+        assert!(!parse("[Int]").is_scalar_only_subtype_of(&parse("[String]")));
+
+        assert_eq!(
+            Some(parse("[String!]!")),
+            parse("[String]!").intersect(&parse("[String!]")),
+        );
+        assert_eq!(None, parse("[String]").intersect(&parse("String")));
+    }
+}
@@ -1,10 +1,10 @@
 use std::fmt::Debug;
 
-use async_graphql_parser::types::{BaseType, Type};
+use async_graphql_parser::types::{BaseType, Type as GraphQLType};
 
 use super::{
     Argument, ContextField, FieldRef, FieldValue, FoldSpecificField, FoldSpecificFieldKind,
-    LocalField, VariableRef,
+    LocalField, Type, VariableRef,
 };
 
 pub trait NamedTypedValue: Debug + Clone + PartialEq + Eq {
@@ -95,108 +95,53 @@ impl NamedTypedValue for Argument {
     }
 }
 
-pub(crate) fn are_base_types_equal_ignoring_nullability(left: &BaseType, right: &BaseType) -> bool {
-    match (left, right) {
-        (BaseType::Named(l), BaseType::Named(r)) => l == r,
-        (BaseType::List(l), BaseType::List(r)) => {
-            are_base_types_equal_ignoring_nullability(&l.base, &r.base)
-        }
-        (BaseType::Named(_), BaseType::List(_)) | (BaseType::List(_), BaseType::Named(_)) => false,
-    }
+pub(crate) fn is_base_type_orderable(operand_type: &Type) -> bool {
+    matches!(
+        operand_type.base_type(),
+        "Int" | "Float" | "String" | "DateTime"
+    )
 }
 
-pub(crate) fn is_base_type_orderable(operand_type: &BaseType) -> bool {
-    match operand_type {
-        BaseType::Named(name) => {
-            name == "Int" || name == "Float" || name == "String" || name == "DateTime"
-        }
-        BaseType::List(l) => is_base_type_orderable(&l.base),
-    }
+pub(crate) fn is_builtin_value_type(name: &str) -> bool {
+    matches!(
+        name,
+        "Int" | "Float" | "String" | "Boolean" | "ID" | "DateTime"
+    )
 }
 
-pub(crate) fn get_base_named_type(ty: &Type) -> &str {
+/// The name of the type within all the lists of a type as it's defined in the schema.
+pub(crate) fn get_base_named_type(ty: &GraphQLType) -> &str {
     match &ty.base {
         BaseType::Named(n) => n.as_ref(),
         BaseType::List(l) => get_base_named_type(l.as_ref()),
     }
 }
 
-/// Check for scalar-only subtyping.
-///
-/// Scalars don't have an inheritance structure, so they are able to be compared without a schema.
-/// Callers of this function must guarantee that the passed types are either scalars or
-/// (potentially multiply-nested) lists of scalars.
-///
-/// This function considers types of different names to always be non-equal and unrelated:
-/// neither is a subtype of the other. So given `interface Base` and `type Derived implements Base`,
-/// that means `is_scalar_only_subtype(Base, Derived) == false`, since this function never sees
-/// the definitions of `Base` and `Derived` as those are part of a schema which this function
-/// never gets.
-pub(crate) fn is_scalar_only_subtype(parent_type: &Type, maybe_subtype: &Type) -> bool {
-    // If the parent type is non-nullable, all its subtypes must be non-nullable as well.
-    // If the parent type is nullable, it can have both nullable and non-nullable subtypes.
-    if !parent_type.nullable && maybe_subtype.nullable {
-        return false;
-    }
-
-    match (&parent_type.base, &maybe_subtype.base) {
-        (BaseType::Named(parent), BaseType::Named(subtype)) => parent == subtype,
-        (BaseType::List(parent_type), BaseType::List(maybe_subtype)) => {
-            is_scalar_only_subtype(parent_type, maybe_subtype)
-        }
-        (BaseType::Named(..), BaseType::List(..)) | (BaseType::List(..), BaseType::Named(..)) => {
-            false
-        }
-    }
-}
-
 /// For two types, return a type that is a subtype of both, or None if no such type exists.
 /// For example:
 /// ```rust
-/// use async_graphql_parser::types::Type;
-/// use trustfall_core::ir::types::intersect_types;
+/// use trustfall_core::ir::{types::intersect_types, Type};
 ///
-/// let left = Type::new("[String]!").unwrap();
-/// let right = Type::new("[String!]").unwrap();
+/// let left = Type::parse("[String]!").unwrap();
+/// let right = Type::parse("[String!]").unwrap();
 /// let result = intersect_types(&left, &right);
-/// assert_eq!(Some(Type::new("[String!]!").unwrap()), result);
+/// assert_eq!(Some(Type::parse("[String!]!").unwrap()), result);
 ///
-/// let incompatible = Type::new("[Int]").unwrap();
+/// let incompatible = Type::parse("[Int]").unwrap();
 /// let result = intersect_types(&left, &incompatible);
 /// assert_eq!(None, result);
 /// ```
 pub fn intersect_types(left: &Type, right: &Type) -> Option<Type> {
-    let nullable = left.nullable && right.nullable;
-
-    match (&left.base, &right.base) {
-        (BaseType::Named(l), BaseType::Named(r)) => {
-            if l == r {
-                Some(Type {
-                    base: left.base.clone(),
-                    nullable,
-                })
-            } else {
-                None
-            }
-        }
-        (BaseType::List(left), BaseType::List(right)) => {
-            intersect_types(left, right).map(|inner| Type {
-                base: BaseType::List(Box::new(inner)),
-                nullable,
-            })
-        }
-        (BaseType::Named(_), BaseType::List(_)) | (BaseType::List(_), BaseType::Named(_)) => None,
-    }
+    left.intersect(right)
 }
 
 /// Check if the given argument value is valid for the specified variable type.
 ///
 /// In particular, mixed integer types in a list are considered valid for types like `[Int]`.
 /// ```rust
-/// use async_graphql_parser::types::Type;
-/// use trustfall_core::ir::{FieldValue, types::is_argument_type_valid};
+/// use trustfall_core::ir::{FieldValue, Type, types::is_argument_type_valid};
 ///
-/// let variable_type = Type::new("[Int]").unwrap();
+/// let variable_type = Type::parse("[Int]").unwrap();
 /// let argument_value = FieldValue::List(vec![
 ///     FieldValue::Int64(-1),
 ///     FieldValue::Uint64(1),
@@ -205,67 +150,69 @@ pub fn intersect_types(left: &Type, right: &Type) -> Option<Type> {
 /// assert!(is_argument_type_valid(&variable_type, &argument_value));
 /// ```
 pub fn is_argument_type_valid(variable_type: &Type, argument_value: &FieldValue) -> bool {
+    // Values other than lists and nulls are valid only for a named type, ignoring nullability.
+    let is_named = |name: &str| !variable_type.is_list() && variable_type.base_type() == name;
     match argument_value {
         FieldValue::Null => {
             // This is a valid value only if this layer is nullable.
-            variable_type.nullable
+            variable_type.is_nullable()
         }
         FieldValue::Int64(_) | FieldValue::Uint64(_) => {
-            // This is a valid value only if the type is Int, ignoring nullability.
-            matches!(&variable_type.base, BaseType::Named(n) if n == "Int")
+            // This is a valid value only if the type is Int.
+            is_named("Int")
         }
         FieldValue::Float64(_) => {
-            // This is a valid value only if the type is Float, ignoring nullability.
-            matches!(&variable_type.base, BaseType::Named(n) if n == "Float")
+            // This is a valid value only if the type is Float.
+            is_named("Float")
         }
         FieldValue::String(_) => {
-            // This is a valid value only if the type is String, ignoring nullability.
-            matches!(&variable_type.base, BaseType::Named(n) if n == "String")
+            // This is a valid value only if the type is String or ID.
+            is_named("String") || is_named("ID")
         }
         FieldValue::Boolean(_) => {
-            // This is a valid value only if the type is Boolean, ignoring nullability.
-            matches!(&variable_type.base, BaseType::Named(n) if n == "Boolean")
+            // This is a valid value only if the type is Boolean.
+            is_named("Boolean")
         }
         FieldValue::DateTimeUtc(_) => {
-            // This is a valid value only if the type is DateTime, ignoring nullability.
-            matches!(&variable_type.base, BaseType::Named(n) if n == "DateTime")
+            // This is a valid value only if the type is DateTime.
+            is_named("DateTime")
         }
         FieldValue::List(nested_values) => {
             // This is a valid value only if the type is a list, and all the inner elements
             // are valid instances of the type inside the list.
-            match &variable_type.base {
-                BaseType::List(inner) => nested_values
+            match variable_type.element_type() {
+                Some(inner) => nested_values
                     .iter()
-                    .all(|value| is_argument_type_valid(inner.as_ref(), value)),
-                BaseType::Named(_) => false,
+                    .all(|value| is_argument_type_valid(&inner, value)),
+                None => false,
             }
         }
-        FieldValue::Enum(_) => todo!(),
+        FieldValue::Enum(_) => {
+            // Without a schema, we can't tell which types are enums or which values they allow.
+            // The best we can do is ensure the type isn't a list or a built-in scalar.
+            !variable_type.is_list() && !is_builtin_value_type(variable_type.base_type())
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use async_graphql_parser::types::Type;
     use itertools::Itertools;
 
-    use crate::ir::{types::is_argument_type_valid, FieldValue};
+    use crate::ir::{types::is_argument_type_valid, FieldValue, Type};
 
     #[test]
     fn null_values_are_only_valid_for_nullable_types() {
         let nullable_types = vec![
-            Type::new("Int").unwrap(),
-            Type::new("String").unwrap(),
-            Type::new("Boolean").unwrap(),
-            Type::new("[Int!]").unwrap(),
-            Type::new("[[Int!]!]").unwrap(),
+            Type::parse("Int").unwrap(),
+            Type::parse("String").unwrap(),
+            Type::parse("Boolean").unwrap(),
+            Type::parse("[Int!]").unwrap(),
+            Type::parse("[[Int!]!]").unwrap(),
         ];
         let non_nullable_types = nullable_types
             .iter()
-            .map(|t| Type {
-                base: t.base.clone(),
-                nullable: false,
-            })
+            .map(|t| t.with_nullability(false))
             .collect_vec();
 
         for nullable_type in &nullable_types {
@@ -286,12 +233,12 @@ mod tests {
 
     #[test]
     fn int_values_are_valid_only_for_int_type_regardless_of_nullability() {
-        let matching_types = vec![Type::new("Int").unwrap(), Type::new("Int!").unwrap()];
+        let matching_types = vec![Type::parse("Int").unwrap(), Type::parse("Int!").unwrap()];
         let non_matching_types = vec![
-            Type::new("String").unwrap(),
-            Type::new("[Int!]").unwrap(),
-            Type::new("[Int!]!").unwrap(),
-            Type::new("[[Int!]!]").unwrap(),
+            Type::parse("String").unwrap(),
+            Type::parse("[Int!]").unwrap(),
+            Type::parse("[Int!]!").unwrap(),
+            Type::parse("[[Int!]!]").unwrap(),
         ];
         let values = vec![
             FieldValue::Int64(-42),
@@ -317,13 +264,18 @@ mod tests {
     }
 
     #[test]
-    fn string_values_are_valid_only_for_string_type_regardless_of_nullability() {
-        let matching_types = vec![Type::new("String").unwrap(), Type::new("String!").unwrap()];
+    fn string_values_are_valid_only_for_string_and_id_types_regardless_of_nullability() {
+        let matching_types = vec![
+            Type::parse("String").unwrap(),
+            Type::parse("String!").unwrap(),
+            Type::parse("ID").unwrap(),
+            Type::parse("ID!").unwrap(),
+        ];
         let non_matching_types = vec![
-            Type::new("Int").unwrap(),
-            Type::new("[String!]").unwrap(),
-            Type::new("[String!]!").unwrap(),
-            Type::new("[[String!]!]").unwrap(),
+            Type::parse("Int").unwrap(),
+            Type::parse("[String!]").unwrap(),
+            Type::parse("[String!]!").unwrap(),
+            Type::parse("[[String!]!]").unwrap(),
         ];
         let values = vec![
             FieldValue::String("".to_string()), // empty string is not the same value as null
@@ -349,14 +301,14 @@ mod tests {
     #[test]
     fn boolean_values_are_valid_only_for_boolean_type_regardless_of_nullability() {
         let matching_types = vec![
-            Type::new("Boolean").unwrap(),
-            Type::new("Boolean!").unwrap(),
+            Type::parse("Boolean").unwrap(),
+            Type::parse("Boolean!").unwrap(),
         ];
         let non_matching_types = vec![
-            Type::new("Int").unwrap(),
-            Type::new("[Boolean!]").unwrap(),
-            Type::new("[Boolean!]!").unwrap(),
-            Type::new("[[Boolean!]!]").unwrap(),
+            Type::parse("Int").unwrap(),
+            Type::parse("[Boolean!]").unwrap(),
+            Type::parse("[Boolean!]!").unwrap(),
+            Type::parse("[[Boolean!]!]").unwrap(),
         ];
         let values = vec![FieldValue::Boolean(false), FieldValue::Boolean(true)];
 
@@ -378,16 +330,20 @@ mod tests {
 
     #[test]
     fn list_types_correctly_check_contents_of_list() {
-        let non_nullable_contents_matching_types =
-            vec![Type::new("[Int!]").unwrap(), Type::new("[Int!]!").unwrap()];
-        let nullable_contents_matching_types =
-            vec![Type::new("[Int]").unwrap(), Type::new("[Int]!").unwrap()];
+        let non_nullable_contents_matching_types = vec![
+            Type::parse("[Int!]").unwrap(),
+            Type::parse("[Int!]!").unwrap(),
+        ];
+        let nullable_contents_matching_types = vec![
+            Type::parse("[Int]").unwrap(),
+            Type::parse("[Int]!").unwrap(),
+        ];
         let non_matching_types = vec![
-            Type::new("Int").unwrap(),
-            Type::new("Int!").unwrap(),
-            Type::new("[String!]").unwrap(),
-            Type::new("[String!]!").unwrap(),
-            Type::new("[[String!]!]").unwrap(),
+            Type::parse("Int").unwrap(),
+            Type::parse("Int!").unwrap(),
+            Type::parse("[String!]").unwrap(),
+            Type::parse("[String!]!").unwrap(),
+            Type::parse("[[String!]!]").unwrap(),
         ];
         let non_nullable_values = vec![
             FieldValue::List((1..3).map(FieldValue::Int64).collect_vec()),
@@ -9,15 +9,17 @@ extern crate maplit;
 #[macro_use]
 extern crate lazy_static;
 
+pub mod binary;
 pub mod frontend;
 pub mod graphql_query;
 pub mod interpreter;
 pub mod ir;
+pub mod output;
 pub mod schema;
 mod serialization;
 mod util;
 
-pub use serialization::TryIntoStruct;
+pub use serialization::{check_result_struct, ResultStructError, TryIntoStruct};
 
 // Test-only uses. `#[doc(hidden)]` items are not part of public API
 // and are not subject to semantic versioning rules.
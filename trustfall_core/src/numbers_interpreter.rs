@@ -32,7 +32,7 @@ impl Typename for NumbersVertex {
     }
 }
 
-trait Number {
+pub(crate) trait Number {
     fn typename(&self) -> &'static str;
 
     fn value(&self) -> i64;
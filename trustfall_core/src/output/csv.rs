@@ -0,0 +1,217 @@
+use std::{
+    io::{self, Write},
+    sync::Arc,
+};
+
+use chrono::SecondsFormat;
+
+use crate::ir::FieldValue;
+
+use super::{json_lines::JsonValue, Row};
+
+/// Writes query results as CSV: a header row with the column names, then one row per result.
+///
+/// The columns are usually the names of the query's outputs, e.g. `query.outputs.keys()`
+/// for an [`IndexedQuery`](crate::ir::IndexedQuery). Null values and outputs a result
+/// doesn't have are written as empty cells, and lists such as the outputs of `@fold` blocks
+/// are written as JSON-encoded cells like `[1,2,3]`. Cells are quoted as in RFC 4180
+/// when they contain the delimiter, quotes, or line breaks.
+#[derive(Debug)]
+pub struct CsvWriter<W: Write> {
+    writer: W,
+    columns: Vec<Arc<str>>,
+    delimiter: u8,
+    write_header: bool,
+    header_written: bool,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(writer: W, columns: impl IntoIterator<Item = impl Into<Arc<str>>>) -> Self {
+        Self {
+            writer,
+            columns: columns.into_iter().map(Into::into).collect(),
+            delimiter: b',',
+            write_header: true,
+            header_written: false,
+        }
+    }
+
+    /// Separate cells with the given byte instead of a comma, e.g. `b'\t'` for TSV.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the delimiter is a quote or a line break, or isn't ASCII.
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        assert!(
+            delimiter.is_ascii() && !matches!(delimiter, b'"' | b'\n' | b'\r'),
+            "invalid CSV delimiter {:?}",
+            delimiter as char
+        );
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Whether to write a header row with the column names. Defaults to `true`.
+    pub fn with_header(mut self, write_header: bool) -> Self {
+        self.write_header = write_header;
+        self
+    }
+
+    fn ensure_header(&mut self) -> io::Result<()> {
+        if self.write_header && !self.header_written {
+            let header = self.columns.iter().map(AsRef::as_ref);
+            write_record(&mut self.writer, self.delimiter, header)?;
+        }
+        self.header_written = true;
+        Ok(())
+    }
+
+    /// Write one query result, preceded by the header row if it hasn't been written yet.
+    pub fn write_row(&mut self, row: &Row) -> io::Result<()> {
+        self.ensure_header()?;
+        let cells = self
+            .columns
+            .iter()
+            .map(|column| row.get(column).map(cell).transpose())
+            .collect::<Result<Vec<_>, _>>()?;
+        let cells = cells.iter().map(|cell| cell.as_deref().unwrap_or(""));
+        write_record(&mut self.writer, self.delimiter, cells)
+    }
+
+    /// Write each of the query results, then flush the underlying writer.
+    /// Returns the number of results written.
+    ///
+    /// The header row is written even if there are no results.
+    pub fn write_all(&mut self, results: impl IntoIterator<Item = Row>) -> io::Result<usize> {
+        self.ensure_header()?;
+        let mut count = 0;
+        for row in results {
+            self.write_row(&row)?;
+            count += 1;
+        }
+        self.flush()?;
+        Ok(count)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+fn write_record<'a>(
+    writer: &mut impl Write,
+    delimiter: u8,
+    cells: impl Iterator<Item = &'a str>,
+) -> io::Result<()> {
+    for (index, cell) in cells.enumerate() {
+        if index > 0 {
+            writer.write_all(&[delimiter])?;
+        }
+        let needs_quotes = cell
+            .bytes()
+            .any(|b| b == delimiter || matches!(b, b'"' | b'\n' | b'\r'));
+        if needs_quotes {
+            write!(writer, "\"{}\"", cell.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(cell.as_bytes())?;
+        }
+    }
+    writer.write_all(b"\n")
+}
+
+fn cell(value: &FieldValue) -> io::Result<String> {
+    Ok(match value {
+        FieldValue::Null => String::new(),
+        FieldValue::Int64(x) => x.to_string(),
+        FieldValue::Uint64(x) => x.to_string(),
+        FieldValue::Float64(x) => x.to_string(),
+        FieldValue::String(x) | FieldValue::Enum(x) => x.clone(),
+        FieldValue::Boolean(x) => x.to_string(),
+        FieldValue::DateTimeUtc(x) => x.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        FieldValue::List(_) => serde_json::to_string(&JsonValue(value))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use chrono::{TimeZone, Utc};
+
+    use crate::ir::FieldValue;
+
+    use super::CsvWriter;
+
+    fn write(writer: CsvWriter<Vec<u8>>, rows: Vec<BTreeMap<Arc<str>, FieldValue>>) -> String {
+        let mut writer = writer;
+        writer.write_all(rows).unwrap();
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn writes_cells() {
+        let row = BTreeMap::from([
+            (
+                Arc::from("text"),
+                FieldValue::String("a, \"quoted\"\nvalue".to_string()),
+            ),
+            (Arc::from("int"), FieldValue::Int64(-3)),
+            (Arc::from("float"), FieldValue::Float64(0.5)),
+            (Arc::from("flag"), FieldValue::Boolean(true)),
+            (Arc::from("null"), FieldValue::Null),
+            (
+                Arc::from("time"),
+                FieldValue::DateTimeUtc(Utc.with_ymd_and_hms(2023, 1, 2, 3, 4, 5).unwrap()),
+            ),
+            (
+                Arc::from("list"),
+                FieldValue::List(vec![FieldValue::String("x".to_string()), FieldValue::Null]),
+            ),
+        ]);
+        let columns = [
+            "text", "int", "float", "flag", "null", "time", "list", "missing",
+        ];
+        assert_eq!(
+            "\
+text,int,float,flag,null,time,list,missing
+\"a, \"\"quoted\"\"
+value\",-3,0.5,true,,2023-01-02T03:04:05Z,\"[\"\"x\"\",null]\",
+",
+            write(CsvWriter::new(vec![], columns), vec![row]),
+        );
+    }
+
+    #[test]
+    fn writes_header_without_rows() {
+        assert_eq!("a,b\n", write(CsvWriter::new(vec![], ["a", "b"]), vec![]));
+        assert_eq!(
+            "",
+            write(
+                CsvWriter::new(vec![], ["a", "b"]).with_header(false),
+                vec![]
+            )
+        );
+    }
+
+    #[test]
+    fn uses_delimiter() {
+        let row = BTreeMap::from([
+            (Arc::from("a"), FieldValue::String("one, two".to_string())),
+            (
+                Arc::from("b"),
+                FieldValue::String("tab\tseparated".to_string()),
+            ),
+        ]);
+        assert_eq!(
+            "a\tb\none, two\t\"tab\tseparated\"\n",
+            write(
+                CsvWriter::new(vec![], ["a", "b"]).with_delimiter(b'\t'),
+                vec![row]
+            ),
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use std::io::{self, Write};
+
+use chrono::{DateTime, Utc};
+use serde::{ser::SerializeMap, ser::SerializeSeq, Serialize, Serializer};
+
+use crate::ir::FieldValue;
+
+use super::Row;
+
+/// Writes query results as JSON Lines: one JSON object per result, each on its own line.
+///
+/// Each object's keys are the names of the result's outputs, in sorted order.
+#[derive(Debug)]
+pub struct JsonLinesWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Write one query result.
+    pub fn write_row(&mut self, row: &Row) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, &JsonRow(row))?;
+        self.writer.write_all(b"\n")
+    }
+
+    /// Write each of the query results, then flush the underlying writer.
+    /// Returns the number of results written.
+    pub fn write_all(&mut self, results: impl IntoIterator<Item = Row>) -> io::Result<usize> {
+        let mut count = 0;
+        for row in results {
+            self.write_row(&row)?;
+            count += 1;
+        }
+        self.flush()?;
+        Ok(count)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+struct JsonRow<'a>(&'a Row);
+
+impl Serialize for JsonRow<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (name, value) in self.0 {
+            map.serialize_entry(name.as_ref(), &JsonValue(value))?;
+        }
+        map.end()
+    }
+}
+
+/// Serializes a value the way [`TransparentValue`](crate::ir::TransparentValue) does,
+/// without having to convert it first.
+pub(super) struct JsonValue<'a>(pub(super) &'a FieldValue);
+
+impl Serialize for JsonValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            FieldValue::Null => serializer.serialize_unit(),
+            FieldValue::Int64(x) => serializer.serialize_i64(*x),
+            FieldValue::Uint64(x) => serializer.serialize_u64(*x),
+            FieldValue::Float64(x) => serializer.serialize_f64(*x),
+            FieldValue::String(x) | FieldValue::Enum(x) => serializer.serialize_str(x),
+            FieldValue::Boolean(x) => serializer.serialize_bool(*x),
+            FieldValue::DateTimeUtc(x) => <DateTime<Utc> as Serialize>::serialize(x, serializer),
+            FieldValue::List(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(&JsonValue(value))?;
+                }
+                seq.end()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use chrono::{TimeZone, Utc};
+
+    use crate::ir::{FieldValue, TransparentValue};
+
+    use super::{JsonLinesWriter, JsonValue};
+
+    #[test]
+    fn values_match_transparent_value() {
+        let values = [
+            FieldValue::Null,
+            FieldValue::Int64(-1),
+            FieldValue::Uint64(u64::MAX),
+            FieldValue::Float64(1.5),
+            FieldValue::String("quote \" and newline \n".to_string()),
+            FieldValue::Boolean(false),
+            FieldValue::DateTimeUtc(Utc.with_ymd_and_hms(2023, 1, 2, 3, 4, 5).unwrap()),
+            FieldValue::Enum("RED".to_string()),
+            FieldValue::List(vec![
+                FieldValue::List(vec![FieldValue::Int64(1)]),
+                FieldValue::Null,
+            ]),
+        ];
+        for value in values {
+            assert_eq!(
+                serde_json::to_string(&TransparentValue::from(value.clone())).unwrap(),
+                serde_json::to_string(&JsonValue(&value)).unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    fn writes_one_line_per_row() {
+        let rows = (1..=3).map(|i| {
+            BTreeMap::from([
+                (Arc::from("value"), FieldValue::Int64(i)),
+                (Arc::from("optional"), FieldValue::Null),
+            ])
+        });
+        let mut writer = JsonLinesWriter::new(vec![]);
+        assert_eq!(3, writer.write_all(rows).unwrap());
+        assert_eq!(
+            "\
+{\"optional\":null,\"value\":1}
+{\"optional\":null,\"value\":2}
+{\"optional\":null,\"value\":3}
+",
+            String::from_utf8(writer.into_inner()).unwrap(),
+        );
+    }
+}
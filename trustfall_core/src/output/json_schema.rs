@@ -0,0 +1,232 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+use serde_json::{json, Map, Value};
+
+use crate::ir::{IndexedQuery, Type};
+
+/// The [JSON Schema](https://json-schema.org/) dialect of the documents [`result_json_schema`] emits.
+pub const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// A JSON Schema document describing the query's result rows, as written by
+/// [`JsonLinesWriter`](super::JsonLinesWriter) and in the [`TransparentValue`]
+/// representation.
+///
+/// Each row is an object with exactly one property per output, all of which are required.
+/// Outputs that may be null, such as those inside `@optional` blocks, also accept `null`,
+/// and outputs inside `@fold` blocks are arrays, nested once per fold.
+/// Enum outputs only accept the values the enum declares. Values of custom scalar types
+/// may be of any JSON type, so their schema accepts all values.
+///
+/// [`TransparentValue`]: crate::ir::TransparentValue
+///
+/// ```rust
+/// # use std::fs;
+/// use serde_json::json;
+/// use trustfall_core::{frontend::parse, output::result_json_schema, schema::Schema};
+///
+/// # let schema = Schema::parse(
+/// #     fs::read_to_string("test_data/schemas/numbers.graphql").unwrap(),
+/// # ).unwrap();
+/// let query = parse(&schema, r#"
+/// {
+///     Number(max: 10) {
+///         name @output
+///         multiple(max: 3) @fold @transform(op: "count") @output(name: "multiples") {
+///             __typename
+///         }
+///     }
+/// }"#).unwrap();
+///
+/// let document = result_json_schema(&query);
+/// assert_eq!(json!({"type": ["string", "null"]}), document["properties"]["name"]);
+/// assert_eq!(json!({"type": "integer"}), document["properties"]["multiples"]);
+/// assert_eq!(json!(["multiples", "name"]), document["required"]);
+/// ```
+pub fn result_json_schema(query: &IndexedQuery) -> Value {
+    let enum_values = &query.ir_query.enum_values;
+
+    let mut properties = Map::new();
+    for (name, output) in &query.outputs {
+        let mut property = value_schema(&output.value_type, enum_values);
+        if let (Some(description), Value::Object(property)) = (&output.description, &mut property) {
+            property.insert("description".to_string(), json!(description.as_ref()));
+        }
+        properties.insert(name.to_string(), property);
+    }
+
+    json!({
+        "$schema": JSON_SCHEMA_DIALECT,
+        "type": "object",
+        "properties": properties,
+        "required": query.outputs.keys().map(|name| name.as_ref()).collect::<Vec<_>>(),
+        "additionalProperties": false,
+    })
+}
+
+fn value_schema(value_type: &Type, enum_values: &BTreeMap<Arc<str>, BTreeSet<Arc<str>>>) -> Value {
+    let schema = match value_type.element_type() {
+        Some(item_type) => json!({
+            "type": "array",
+            "items": value_schema(&item_type, enum_values),
+        }),
+        None => match value_type.base_type() {
+            "Int" => json!({"type": "integer"}),
+            "Float" => json!({"type": "number"}),
+            "String" | "ID" => json!({"type": "string"}),
+            "Boolean" => json!({"type": "boolean"}),
+            name => match enum_values.get(name) {
+                Some(values) => json!({"type": "string", "enum": values}),
+                None => return json!({}),
+            },
+        },
+    };
+
+    if !value_type.is_nullable() {
+        return schema;
+    }
+    match schema {
+        Value::Object(mut schema) => {
+            let value_type = schema.remove("type").expect("schema has no type");
+            schema.insert("type".to_string(), json!([value_type, "null"]));
+            if let Some(Value::Array(values)) = schema.get_mut("enum") {
+                values.push(Value::Null);
+            }
+            Value::Object(schema)
+        }
+        _ => unreachable!("schema is not an object: {schema}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::json;
+
+    use crate::{frontend::parse, schema::Schema};
+
+    use super::{result_json_schema, JSON_SCHEMA_DIALECT};
+
+    #[test]
+    fn describes_outputs() {
+        let schema =
+            Schema::parse(fs::read_to_string("test_data/schemas/numbers.graphql").unwrap())
+                .unwrap();
+        let query = parse(
+            &schema,
+            r#"
+{
+    Number(max: 10) {
+        # The number's value.
+        value @output
+        predecessor @optional {
+            previous: name @output
+        }
+        multiple(max: 3) @fold {
+            multiples: value @output
+            predecessor @fold {
+                names: name @output
+            }
+        }
+        multiple(max: 3) @fold @transform(op: "count") @output(name: "multiple_count") {
+            __typename
+        }
+    }
+}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            json!({
+                "$schema": JSON_SCHEMA_DIALECT,
+                "type": "object",
+                "properties": {
+                    "multiple_count": {"type": "integer"},
+                    "multiples": {"type": "array", "items": {"type": ["integer", "null"]}},
+                    "names": {
+                        "type": "array",
+                        "items": {
+                            "type": "array",
+                            "items": {"type": ["string", "null"]},
+                        },
+                    },
+                    "previous": {"type": ["string", "null"]},
+                    "value": {"type": ["integer", "null"], "description": "The number's value."},
+                },
+                "required": ["multiple_count", "multiples", "names", "previous", "value"],
+                "additionalProperties": false,
+            }),
+            result_json_schema(&query)
+        );
+    }
+
+    #[test]
+    fn enums_and_custom_scalars() {
+        let schema = Schema::parse(
+            r#"
+schema {
+    query: RootSchemaQuery
+}
+directive @filter(op: String!, value: [String!]) on FIELD | INLINE_FRAGMENT
+directive @tag(name: String) on FIELD
+directive @output(name: String) on FIELD
+directive @optional on FIELD
+directive @recurse(depth: Int!) on FIELD
+directive @fold on FIELD
+directive @transform(op: String!) on FIELD
+
+scalar DateTime
+enum Color {
+    RED
+    GREEN
+}
+
+type RootSchemaQuery {
+    Paint: [Paint!]!
+}
+
+type Paint {
+    color: Color!
+    accent: Color
+    mixedAt: DateTime!
+    available: Boolean
+    price: Float!
+}"#,
+        )
+        .unwrap();
+        let query = parse(
+            &schema,
+            r#"
+{
+    Paint {
+        color @output
+        accent @output
+        mixedAt @output
+        available @output
+        price @output
+    }
+}"#,
+        )
+        .unwrap();
+
+        let document = result_json_schema(&query);
+        let properties = &document["properties"];
+        assert_eq!(
+            json!({"type": "string", "enum": ["GREEN", "RED"]}),
+            properties["color"]
+        );
+        assert_eq!(
+            json!({"type": ["string", "null"], "enum": ["GREEN", "RED", null]}),
+            properties["accent"]
+        );
+        assert_eq!(json!({}), properties["mixedAt"]);
+        assert_eq!(
+            json!({"type": ["boolean", "null"]}),
+            properties["available"]
+        );
+        assert_eq!(json!({"type": "number"}), properties["price"]);
+    }
+}
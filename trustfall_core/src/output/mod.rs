@@ -0,0 +1,47 @@
+//! Writers that stream query results to any [`std::io::Write`] as JSON Lines or CSV,
+//! and [JSON Schema](result_json_schema) documents describing those results.
+//!
+//! Both writers consume rows as the result iterator produces them, so results are written
+//! without first being collected in memory:
+//! ```rust
+//! # use std::{collections::BTreeMap, sync::Arc};
+//! # use trustfall_core::ir::FieldValue;
+//! use trustfall_core::output::{CsvWriter, JsonLinesWriter};
+//!
+//! # let results = || {
+//! #     vec![BTreeMap::from([
+//! #         (Arc::from("name"), FieldValue::String("two".to_string())),
+//! #         (Arc::from("divisors"), FieldValue::List(vec![FieldValue::Int64(1), FieldValue::Int64(2)])),
+//! #     ])].into_iter()
+//! # };
+//! let mut json = JsonLinesWriter::new(vec![]);
+//! json.write_all(results()).unwrap();
+//! assert_eq!(
+//!     "{\"divisors\":[1,2],\"name\":\"two\"}\n",
+//!     String::from_utf8(json.into_inner()).unwrap(),
+//! );
+//!
+//! // Lists, such as the outputs of `@fold` blocks, are written as JSON-encoded cells.
+//! let mut csv = CsvWriter::new(vec![], ["name", "divisors"]);
+//! csv.write_all(results()).unwrap();
+//! assert_eq!(
+//!     "name,divisors\ntwo,\"[1,2]\"\n",
+//!     String::from_utf8(csv.into_inner()).unwrap(),
+//! );
+//! ```
+//!
+//! Values are written as in the untagged [`TransparentValue`](crate::ir::TransparentValue)
+//! representation: timestamps are written as RFC 3339 strings, and enums by name.
+use std::{collections::BTreeMap, sync::Arc};
+
+use crate::ir::FieldValue;
+
+mod csv;
+mod json_lines;
+mod json_schema;
+
+pub use self::csv::CsvWriter;
+pub use self::json_lines::JsonLinesWriter;
+pub use self::json_schema::{result_json_schema, JSON_SCHEMA_DIALECT};
+
+type Row = BTreeMap<Arc<str>, FieldValue>;
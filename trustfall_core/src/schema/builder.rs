@@ -0,0 +1,531 @@
+//! Constructing schemas programmatically, for adapters whose schema is only known at runtime.
+use std::collections::{BTreeMap, BTreeSet};
+
+use async_graphql_parser::types::{BaseType, Type};
+use async_graphql_value::{ConstValue, Name, Number};
+
+use crate::ir::FieldValue;
+
+use super::{
+    error::InvalidSchemaError,
+    json::{
+        DirectiveJson, EnumJson, FieldJson, ParameterJson, SchemaJson, VertexTypeJson,
+        VertexTypeKind, SCHEMA_JSON_VERSION,
+    },
+    Schema,
+};
+
+/// The name of the root query type of schemas made by [`SchemaBuilder`],
+/// unless a different name is chosen with [`SchemaBuilder::query_type_name`].
+pub const DEFAULT_QUERY_TYPE_NAME: &str = "RootSchemaQuery";
+
+/// The type of a property, edge, or edge parameter, for use with [`SchemaBuilder`].
+///
+/// Types are nullable unless made non-null with [`Ty::non_null`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ty(Type);
+
+impl Ty {
+    /// The scalar, enum, or vertex type with the given name.
+    pub fn named(name: &str) -> Self {
+        Self(Type {
+            base: BaseType::Named(Name::new(name)),
+            nullable: true,
+        })
+    }
+
+    pub fn string() -> Self {
+        Self::named("String")
+    }
+
+    pub fn int() -> Self {
+        Self::named("Int")
+    }
+
+    pub fn float() -> Self {
+        Self::named("Float")
+    }
+
+    pub fn boolean() -> Self {
+        Self::named("Boolean")
+    }
+
+    pub fn id() -> Self {
+        Self::named("ID")
+    }
+
+    /// A list whose elements are of this type.
+    pub fn list(self) -> Self {
+        Self(Type {
+            base: BaseType::List(Box::new(self.0)),
+            nullable: true,
+        })
+    }
+
+    /// This type, but not allowing `null` values.
+    pub fn non_null(mut self) -> Self {
+        self.0.nullable = false;
+        self
+    }
+
+    pub fn as_type(&self) -> &Type {
+        &self.0
+    }
+}
+
+impl From<Ty> for Type {
+    fn from(value: Ty) -> Self {
+        value.0
+    }
+}
+
+impl std::fmt::Display for Ty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Builds a [`Schema`] without writing its definition as GraphQL SDL text.
+///
+/// Methods like [`property`](Self::property) and [`edge`](Self::edge) add fields to the vertex
+/// type most recently started with [`vertex_type`](Self::vertex_type) or
+/// [`interface`](Self::interface), and [`parameter`](Self::parameter) adds parameters
+/// to the most recently added edge. Calling them out of order is a bug, and panics.
+/// The schema is only validated by [`build`](Self::build).
+///
+/// ```
+/// use trustfall_core::schema::builder::{SchemaBuilder, Ty};
+///
+/// let schema = SchemaBuilder::new()
+///     .root_edge("Crate", Ty::named("Crate").non_null().list())
+///     .parameter("name", Ty::string().non_null())
+///     .vertex_type("Crate")
+///     .property("name", Ty::string().non_null())
+///     .edge("dependencies", Ty::named("Crate").non_null().list())
+///     .build()
+///     .unwrap();
+/// assert!(schema.vertex_type("Crate").is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SchemaBuilder {
+    query_type: VertexTypeJson,
+    scalars: Vec<String>,
+    enums: Vec<EnumJson>,
+    types: Vec<VertexTypeJson>,
+    last_field: Option<FieldPosition>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FieldPosition {
+    Root(usize),
+    Type(usize, usize),
+}
+
+impl Default for SchemaBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SchemaBuilder {
+    pub fn new() -> Self {
+        Self {
+            query_type: VertexTypeJson {
+                name: DEFAULT_QUERY_TYPE_NAME.to_string(),
+                description: None,
+                kind: VertexTypeKind::Object,
+                implements: vec![],
+                directives: vec![],
+                fields: vec![],
+            },
+            scalars: vec![],
+            enums: vec![],
+            types: vec![],
+            last_field: None,
+        }
+    }
+
+    /// Use a different name for the root query type.
+    pub fn query_type_name(mut self, name: impl Into<String>) -> Self {
+        self.query_type.name = name.into();
+        self
+    }
+
+    /// Declare a custom scalar type.
+    pub fn scalar(mut self, name: impl Into<String>) -> Self {
+        self.scalars.push(name.into());
+        self
+    }
+
+    /// Declare an enum type with the given values.
+    pub fn enum_type(
+        mut self,
+        name: impl Into<String>,
+        values: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.enums.push(EnumJson {
+            name: name.into(),
+            description: None,
+            values: values.into_iter().map(Into::into).collect(),
+        });
+        self
+    }
+
+    /// Add an edge to the root query type, from which queries can start.
+    pub fn root_edge(mut self, name: impl Into<String>, edge_type: Ty) -> Self {
+        self.query_type
+            .fields
+            .push(make_field(name.into(), edge_type));
+        self.last_field = Some(FieldPosition::Root(self.query_type.fields.len() - 1));
+        self
+    }
+
+    /// Start defining an object vertex type, to which subsequent fields are added.
+    pub fn vertex_type(self, name: impl Into<String>) -> Self {
+        self.start_type(name.into(), VertexTypeKind::Object)
+    }
+
+    /// Start defining an interface vertex type, to which subsequent fields are added.
+    pub fn interface(self, name: impl Into<String>) -> Self {
+        self.start_type(name.into(), VertexTypeKind::Interface)
+    }
+
+    fn start_type(mut self, name: String, kind: VertexTypeKind) -> Self {
+        self.types.push(VertexTypeJson {
+            name,
+            description: None,
+            kind,
+            implements: vec![],
+            directives: vec![],
+            fields: vec![],
+        });
+        self.last_field = None;
+        self
+    }
+
+    /// Declare that the current vertex type implements the named interface.
+    ///
+    /// As in SDL schemas, the type must still define all the interface's fields itself.
+    pub fn implements(mut self, interface: impl Into<String>) -> Self {
+        self.current_type("implements")
+            .implements
+            .push(interface.into());
+        self
+    }
+
+    /// Add a property to the current vertex type.
+    pub fn property(self, name: impl Into<String>, property_type: Ty) -> Self {
+        self.add_field("property", name.into(), property_type)
+    }
+
+    /// Add an edge to the current vertex type.
+    pub fn edge(self, name: impl Into<String>, edge_type: Ty) -> Self {
+        self.add_field("edge", name.into(), edge_type)
+    }
+
+    fn add_field(mut self, method: &str, name: String, field_type: Ty) -> Self {
+        let current_type = self.current_type(method);
+        current_type.fields.push(make_field(name, field_type));
+        let field_index = current_type.fields.len() - 1;
+        self.last_field = Some(FieldPosition::Type(self.types.len() - 1, field_index));
+        self
+    }
+
+    /// Add a parameter to the most recently added edge.
+    pub fn parameter(mut self, name: impl Into<String>, parameter_type: Ty) -> Self {
+        self.last_field("parameter").parameters.push(ParameterJson {
+            name: name.into(),
+            description: None,
+            parameter_type: parameter_type.to_string(),
+            default: None,
+            directives: vec![],
+        });
+        self
+    }
+
+    /// Add a parameter with a default value to the most recently added edge.
+    pub fn parameter_with_default(
+        mut self,
+        name: impl Into<String>,
+        parameter_type: Ty,
+        default: impl Into<FieldValue>,
+    ) -> Self {
+        let default = field_value_to_const_value(default.into());
+        self.last_field("parameter_with_default")
+            .parameters
+            .push(ParameterJson {
+                name: name.into(),
+                description: None,
+                parameter_type: parameter_type.to_string(),
+                default: Some(default),
+                directives: vec![],
+            });
+        self
+    }
+
+    /// Set the description of the most recently added field, or of the current vertex type
+    /// if no fields have been added to it yet.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        let description = Some(description.into());
+        match self.last_field {
+            Some(_) => self.last_field("description").description = description,
+            None => self.current_type("description").description = description,
+        }
+        self
+    }
+
+    /// Mark the most recently added field as `@deprecated`, optionally with a reason.
+    pub fn deprecated(mut self, reason: Option<&str>) -> Self {
+        let arguments = reason
+            .map(|reason| {
+                BTreeMap::from([("reason".to_string(), ConstValue::String(reason.to_string()))])
+            })
+            .unwrap_or_default();
+        self.last_field("deprecated")
+            .directives
+            .push(DirectiveJson {
+                name: "deprecated".to_string(),
+                arguments,
+            });
+        self
+    }
+
+    /// Validate and construct the schema.
+    pub fn build(self) -> Result<Schema, InvalidSchemaError> {
+        let mut types = self.types;
+        let query_type = self.query_type.name.clone();
+        types.push(self.query_type);
+
+        Schema::try_from(SchemaJson {
+            version: SCHEMA_JSON_VERSION,
+            query_type,
+            directives: vec![],
+            scalars: self.scalars,
+            enums: self.enums,
+            types,
+        })
+    }
+
+    fn current_type(&mut self, method: &str) -> &mut VertexTypeJson {
+        self.types.last_mut().unwrap_or_else(|| {
+            panic!("called SchemaBuilder::{method}() before starting a vertex type")
+        })
+    }
+
+    fn last_field(&mut self, method: &str) -> &mut FieldJson {
+        match self.last_field {
+            Some(FieldPosition::Root(index)) => &mut self.query_type.fields[index],
+            Some(FieldPosition::Type(type_index, index)) => {
+                &mut self.types[type_index].fields[index]
+            }
+            None => panic!("called SchemaBuilder::{method}() before adding a field"),
+        }
+    }
+}
+
+/// A valid GraphQL name resembling the given text, such as a column name or a JSON key,
+/// for naming the types and fields of schemas derived from data.
+///
+/// Characters other than ASCII letters and digits are replaced with underscores,
+/// names starting with a digit are prefixed with one, and the `__` prefix
+/// reserved for introspection is removed.
+pub fn graphql_name(text: &str) -> String {
+    let mut name: String = text
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    while name.starts_with("__") {
+        name.remove(0);
+    }
+    name
+}
+
+/// The `PascalCase` form of a `snake_case` name, as is conventional for type names.
+pub fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Make the name unique among those already taken, then take it.
+///
+/// Names that are already taken get the lowest numeric suffix that makes them unique:
+/// the second `name` becomes `name_2`, the third `name_3`, and so on.
+pub fn unique_name(taken: &mut BTreeSet<String>, name: String) -> String {
+    let mut candidate = name.clone();
+    let mut suffix = 2;
+    while taken.contains(&candidate) {
+        candidate = format!("{name}_{suffix}");
+        suffix += 1;
+    }
+    taken.insert(candidate.clone());
+    candidate
+}
+
+fn make_field(name: String, field_type: Ty) -> FieldJson {
+    FieldJson {
+        name,
+        description: None,
+        field_type: field_type.to_string(),
+        parameters: vec![],
+        directives: vec![],
+    }
+}
+
+fn field_value_to_const_value(value: FieldValue) -> ConstValue {
+    match value {
+        FieldValue::Null => ConstValue::Null,
+        FieldValue::Int64(x) => ConstValue::Number(x.into()),
+        FieldValue::Uint64(x) => ConstValue::Number(x.into()),
+        FieldValue::Float64(x) => {
+            ConstValue::Number(Number::from_f64(x).expect("float value is not finite"))
+        }
+        FieldValue::String(x) => ConstValue::String(x),
+        FieldValue::Boolean(x) => ConstValue::Boolean(x),
+        FieldValue::DateTimeUtc(x) => ConstValue::String(x.to_rfc3339()),
+        // Enum default values are represented as strings, and resolved against the schema
+        // when the schema is built.
+        FieldValue::Enum(x) => ConstValue::String(x),
+        FieldValue::List(x) => {
+            ConstValue::List(x.into_iter().map(field_value_to_const_value).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        ir::FieldValue,
+        schema::{error::InvalidSchemaError, Schema},
+    };
+
+    use std::collections::BTreeSet;
+
+    use super::{graphql_name, pascal_case, unique_name, SchemaBuilder, Ty};
+
+    #[test]
+    fn builds_schema_equivalent_to_sdl() {
+        let expected = Schema::parse(
+            fs::read_to_string("test_data/tests/valid_schemas/schema_builder.graphql").unwrap(),
+        )
+        .unwrap();
+
+        let schema = SchemaBuilder::new()
+            .root_edge("Crate", Ty::named("Crate").non_null().list().non_null())
+            .parameter("name", Ty::string())
+            .parameter_with_default("limit", Ty::int().non_null(), 10)
+            .root_edge("User", Ty::named("User").non_null().list().non_null())
+            .enum_type("Visibility", ["PUBLIC", "PRIVATE"])
+            .scalar("Timestamp")
+            .interface("Named")
+            .description("Anything with a name.")
+            .property("name", Ty::string().non_null())
+            .vertex_type("Crate")
+            .implements("Named")
+            .property("name", Ty::string().non_null())
+            .description("The crate's name on crates.io.")
+            .property("published", Ty::named("Timestamp"))
+            .property("downloads", Ty::int())
+            .deprecated(Some("use `releases` instead"))
+            .edge("owners", Ty::named("User").non_null().list())
+            .edge("releases", Ty::named("Release").non_null().list())
+            .parameter_with_default(
+                "visibility",
+                Ty::named("Visibility"),
+                FieldValue::Enum("PUBLIC".to_string()),
+            )
+            .vertex_type("Release")
+            .property("version", Ty::string().non_null())
+            .property("yanked", Ty::boolean())
+            .vertex_type("User")
+            .implements("Named")
+            .property("name", Ty::string().non_null())
+            .edge("crates", Ty::named("Crate").non_null().list())
+            .build()
+            .unwrap();
+
+        assert_eq!(expected.fingerprint(), schema.fingerprint());
+        assert_eq!(
+            Some("The crate's name on crates.io."),
+            schema
+                .vertex_type("Crate")
+                .unwrap()
+                .property("name")
+                .unwrap()
+                .description()
+        );
+        assert_eq!(
+            Some("use `releases` instead"),
+            schema
+                .vertex_type("Crate")
+                .unwrap()
+                .property("downloads")
+                .unwrap()
+                .deprecation_reason()
+        );
+    }
+
+    #[test]
+    fn invalid_schemas_are_rejected() {
+        let missing_interface_field = SchemaBuilder::new()
+            .root_edge("Crate", Ty::named("Crate"))
+            .interface("Named")
+            .property("name", Ty::string())
+            .vertex_type("Crate")
+            .implements("Named")
+            .build();
+        assert_eq!(
+            Err(InvalidSchemaError::MissingRequiredField(
+                "Crate".to_string(),
+                "Named".to_string(),
+                "name".to_string(),
+                "String".to_string(),
+            )),
+            missing_interface_field.map(|_| ())
+        );
+
+        let undefined_type = SchemaBuilder::new()
+            .root_edge("Crate", Ty::named("Crate"))
+            .build();
+        assert_eq!(
+            Err(InvalidSchemaError::InvalidSchemaJson(
+                "field \"Crate\" on type \"RootSchemaQuery\" refers to undefined type \"Crate\""
+                    .to_string()
+            )),
+            undefined_type.map(|_| ())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "called SchemaBuilder::property() before starting a vertex type")]
+    fn fields_require_a_vertex_type() {
+        let _ = SchemaBuilder::new().property("name", Ty::string());
+    }
+
+    #[test]
+    fn names_derived_from_data_are_valid_and_unique() {
+        assert_eq!("first_released", graphql_name("first released"));
+        assert_eq!("_2fa", graphql_name("2fa"));
+        assert_eq!("_", graphql_name(""));
+        assert_eq!("_typename", graphql_name("__typename"));
+        assert_eq!("FirstReleased", pascal_case("first_released"));
+        assert_eq!("Typename", pascal_case("_typename"));
+
+        let mut taken = BTreeSet::new();
+        assert_eq!("name", unique_name(&mut taken, "name".to_string()));
+        assert_eq!("name_2", unique_name(&mut taken, "name".to_string()));
+        assert_eq!("name_3", unique_name(&mut taken, "name".to_string()));
+        assert_eq!("other", unique_name(&mut taken, "other".to_string()));
+    }
+}
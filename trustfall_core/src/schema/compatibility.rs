@@ -0,0 +1,485 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use async_graphql_parser::types::{TypeDefinition, TypeKind};
+use async_graphql_value::Name;
+use serde::{Deserialize, Serialize};
+
+use crate::ir::{
+    types::get_base_named_type, Argument, ContextField, EdgeParameters, FieldRef, IRQuery,
+    IRQueryComponent, IRVertex, Type, Vid, TYPENAME_META_FIELD,
+};
+
+use super::{
+    error::IncompatibleQueryError, get_vertex_type_fields, get_vertex_type_implements, Schema,
+};
+
+/// A fingerprint of the parts of a schema that determine which queries are valid against it.
+///
+/// Two schemas have the same fingerprint if they define the same types, fields, edge parameters,
+/// scalars, and enum values, regardless of the order of their definitions. Descriptions,
+/// comments, and formatting do not affect the fingerprint.
+///
+/// Fingerprints are stable across processes and platforms, so they may be persisted
+/// alongside compiled queries: if the fingerprint of the current schema matches the one
+/// the query was compiled against, the query is still valid. Otherwise, use
+/// [`Schema::check_query_compatibility`] to find out whether the query is still valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SchemaFingerprint(u64);
+
+impl SchemaFingerprint {
+    /// The fingerprint with the given [`value`](Self::value), such as one that was persisted.
+    pub fn from_value(value: u64) -> Self {
+        Self(value)
+    }
+
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Display for SchemaFingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl Schema {
+    /// Compute the [`SchemaFingerprint`] of this schema.
+    pub fn fingerprint(&self) -> SchemaFingerprint {
+        // Build a canonical textual description of the schema, then hash it with FNV-1a.
+        // Since its output is fully specified, it's stable across Rust versions and platforms,
+        // unlike the hashers in the standard library.
+        let mut lines: BTreeSet<String> = Default::default();
+        lines.insert(format!("schema query {}", self.query_type_name()));
+        lines.extend(self.scalars.keys().map(|name| format!("scalar {name}")));
+        for (name, defn) in &self.enums {
+            let TypeKind::Enum(enum_type) = &defn.kind else {
+                unreachable!()
+            };
+            let values: BTreeSet<&str> = enum_type
+                .values
+                .iter()
+                .map(|v| v.node.value.node.as_str())
+                .collect();
+            lines.insert(format!("enum {name} {values:?}"));
+        }
+        for (name, defn) in &self.vertex_types {
+            let kind = match &defn.kind {
+                TypeKind::Object(_) => "type",
+                TypeKind::Interface(_) => "interface",
+                _ => unreachable!(),
+            };
+            let implements: BTreeSet<&str> = get_vertex_type_implements(defn)
+                .iter()
+                .map(|x| x.node.as_str())
+                .collect();
+            lines.insert(format!("{kind} {name} implements {implements:?}"));
+
+            for field in get_vertex_type_fields(defn) {
+                let field = &field.node;
+                let parameters: BTreeSet<String> = field
+                    .arguments
+                    .iter()
+                    .map(|param| {
+                        let param = &param.node;
+                        match &param.default_value {
+                            Some(default) => {
+                                format!("{}: {} = {}", param.name.node, param.ty.node, default.node)
+                            }
+                            None => format!("{}: {}", param.name.node, param.ty.node),
+                        }
+                    })
+                    .collect();
+                lines.insert(format!(
+                    "field {name}.{} {parameters:?}: {}",
+                    field.name.node, field.ty.node
+                ));
+            }
+        }
+
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for line in &lines {
+            for byte in line.bytes().chain(std::iter::once(b'\n')) {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+        SchemaFingerprint(hash)
+    }
+
+    /// Check whether a previously-compiled query is still valid against this schema.
+    ///
+    /// The query may have been compiled against a different version of this schema,
+    /// for example if it was persisted and the schema has since changed. In that case,
+    /// this function reports every way in which the query no longer matches the schema:
+    /// types, edges, and properties that no longer exist or have changed type,
+    /// coercions that are no longer valid, and edge parameters that were added, removed,
+    /// or no longer accept the query's values.
+    pub fn check_query_compatibility(&self, query: &IRQuery) -> Result<(), IncompatibleQueryError> {
+        let mut vertices: BTreeMap<Vid, &IRVertex> = Default::default();
+        collect_vertices(&mut vertices, &query.root_component);
+
+        let mut checker = CompatibilityChecker {
+            schema: self,
+            vertices,
+            errors: vec![],
+        };
+        let root_vertex = &query.root_component.vertices[&query.root_component.root];
+        checker.check_edge(
+            self.query_type_name(),
+            &query.root_name,
+            &query.root_parameters,
+            root_vertex,
+        );
+        checker.check_component(&query.root_component);
+
+        if checker.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(checker.errors.into())
+        }
+    }
+}
+
+fn collect_vertices<'a>(result: &mut BTreeMap<Vid, &'a IRVertex>, component: &'a IRQueryComponent) {
+    result.extend(
+        component
+            .vertices
+            .iter()
+            .map(|(vid, vertex)| (*vid, vertex)),
+    );
+    for fold in component.folds.values() {
+        collect_vertices(result, &fold.component);
+    }
+}
+
+struct CompatibilityChecker<'a> {
+    schema: &'a Schema,
+    vertices: BTreeMap<Vid, &'a IRVertex>,
+    errors: Vec<IncompatibleQueryError>,
+}
+
+impl<'a> CompatibilityChecker<'a> {
+    fn report(&mut self, error: IncompatibleQueryError) {
+        // The same field may be used many times in a query, but only needs to be reported once.
+        if !self.errors.contains(&error) {
+            self.errors.push(error);
+        }
+    }
+
+    fn vertex_type(&self, type_name: &str) -> Option<&'a TypeDefinition> {
+        self.schema.vertex_types.get(type_name)
+    }
+
+    fn check_component(&mut self, component: &IRQueryComponent) {
+        for vertex in component.vertices.values() {
+            self.check_vertex(vertex);
+        }
+
+        for edge in component.edges.values() {
+            let from_type = self.vertices[&edge.from_vid].type_name.clone();
+            let to_vertex = self.vertices[&edge.to_vid];
+            self.check_edge(&from_type, &edge.edge_name, &edge.parameters, to_vertex);
+        }
+
+        for fold in component.folds.values() {
+            let from_type = self.vertices[&fold.from_vid].type_name.clone();
+            let to_vertex = self.vertices[&fold.to_vid];
+            self.check_edge(&from_type, &fold.edge_name, &fold.parameters, to_vertex);
+
+            for imported_tag in &fold.imported_tags {
+                if let FieldRef::ContextField(field) = imported_tag {
+                    self.check_context_field(field);
+                }
+            }
+            for filter in &fold.post_filters {
+                if let Some(Argument::Tag(FieldRef::ContextField(field))) = filter.right() {
+                    self.check_context_field(field);
+                }
+            }
+
+            self.check_component(&fold.component);
+        }
+
+        for output in component.outputs.values() {
+            self.check_context_field(output);
+        }
+    }
+
+    fn check_vertex(&mut self, vertex: &IRVertex) {
+        if self.vertex_type(&vertex.type_name).is_none() {
+            self.report(IncompatibleQueryError::NonExistentType(
+                vertex.type_name.to_string(),
+            ));
+            return;
+        }
+
+        if let Some(coerced_from) = &vertex.coerced_from_type {
+            match self.vertex_type(coerced_from).map(|defn| &defn.kind) {
+                None => {
+                    self.report(IncompatibleQueryError::NonExistentType(
+                        coerced_from.to_string(),
+                    ));
+                }
+                Some(kind) => {
+                    if !matches!(kind, TypeKind::Interface(_))
+                        || !self
+                            .schema
+                            .is_named_type_subtype(coerced_from, &vertex.type_name)
+                    {
+                        self.report(IncompatibleQueryError::InvalidCoercion(
+                            coerced_from.to_string(),
+                            vertex.type_name.to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        for filter in &vertex.filters {
+            let field = filter.left();
+            self.check_property(&vertex.type_name, &field.field_name, &field.field_type);
+
+            if let Some(Argument::Tag(FieldRef::ContextField(field))) = filter.right() {
+                self.check_context_field(field);
+            }
+        }
+    }
+
+    fn check_context_field(&mut self, field: &ContextField) {
+        let type_name = self.vertices[&field.vertex_id].type_name.clone();
+        self.check_property(&type_name, &field.field_name, &field.field_type);
+    }
+
+    fn check_property(&mut self, type_name: &str, property_name: &str, property_type: &Type) {
+        if property_name == TYPENAME_META_FIELD || self.vertex_type(type_name).is_none() {
+            // The __typename property always exists, and missing types are reported elsewhere.
+            return;
+        }
+
+        match self
+            .schema
+            .fields
+            .get(&(type_name.into(), property_name.into()))
+        {
+            None => self.report(IncompatibleQueryError::NonExistentProperty(
+                type_name.to_string(),
+                property_name.to_string(),
+            )),
+            Some(defn) => {
+                if &Type::from(&defn.ty.node) != property_type {
+                    self.report(IncompatibleQueryError::PropertyTypeChanged(
+                        type_name.to_string(),
+                        property_name.to_string(),
+                        property_type.to_string(),
+                        defn.ty.node.to_string(),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn check_edge(
+        &mut self,
+        from_type: &str,
+        edge_name: &str,
+        parameters: &EdgeParameters,
+        to_vertex: &IRVertex,
+    ) {
+        if self.vertex_type(from_type).is_none() {
+            // Already reported when checking the vertex itself.
+            return;
+        }
+
+        let Some(defn) = self
+            .schema
+            .fields
+            .get(&(from_type.into(), edge_name.into()))
+        else {
+            self.report(IncompatibleQueryError::NonExistentEdge(
+                from_type.to_string(),
+                edge_name.to_string(),
+            ));
+            return;
+        };
+
+        let target_type = get_base_named_type(&defn.ty.node);
+        let expected_type = to_vertex
+            .coerced_from_type
+            .as_ref()
+            .unwrap_or(&to_vertex.type_name);
+        if self.vertex_type(target_type).is_none() {
+            // The field exists, but is now a property rather than an edge.
+            self.report(IncompatibleQueryError::NonExistentEdge(
+                from_type.to_string(),
+                edge_name.to_string(),
+            ));
+            return;
+        } else if target_type != expected_type.as_ref() {
+            self.report(IncompatibleQueryError::EdgeTargetTypeChanged(
+                from_type.to_string(),
+                edge_name.to_string(),
+                expected_type.to_string(),
+                target_type.to_string(),
+            ));
+        }
+
+        for (param_name, value) in parameters.iter() {
+            let param_defn = defn
+                .arguments
+                .iter()
+                .find(|arg| arg.node.name.node.as_ref() == param_name.as_ref());
+            match param_defn {
+                None => self.report(IncompatibleQueryError::UnexpectedEdgeParameter(
+                    from_type.to_string(),
+                    edge_name.to_string(),
+                    param_name.to_string(),
+                )),
+                Some(param_defn) => {
+                    let param_type = &param_defn.node.ty.node;
+                    if !self.schema.is_parameter_value_valid(param_type, value) {
+                        self.report(IncompatibleQueryError::InvalidEdgeParameterValue(
+                            from_type.to_string(),
+                            edge_name.to_string(),
+                            param_name.to_string(),
+                            param_type.to_string(),
+                            value.clone(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        for param_defn in &defn.arguments {
+            let param_name: &Name = &param_defn.node.name.node;
+            if parameters.get(param_name.as_str()).is_none() {
+                self.report(IncompatibleQueryError::MissingEdgeParameter(
+                    from_type.to_string(),
+                    edge_name.to_string(),
+                    param_name.to_string(),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use trustfall_filetests_macros::parameterize;
+
+    use crate::{
+        frontend::parse_to_ir,
+        schema::{error::IncompatibleQueryError, Schema},
+        test_types::{TestIRQuery, TestIRQueryResult},
+        util::DisplayVec,
+    };
+
+    fn load_schema(name: &str) -> Schema {
+        Schema::parse(fs::read_to_string(format!("test_data/schemas/{name}.graphql")).unwrap())
+            .unwrap()
+    }
+
+    #[parameterize("trustfall_core/test_data/tests/valid_queries")]
+    fn compiled_queries_are_compatible_with_their_schema(base: &Path, stem: &str) {
+        let mut path = PathBuf::from(base);
+        path.push(format!("{stem}.ir.ron"));
+        let ir_data = fs::read_to_string(path).unwrap();
+        let test_query: TestIRQueryResult = ron::from_str(&ir_data).unwrap();
+        let TestIRQuery {
+            schema_name,
+            ir_query,
+            ..
+        } = test_query.unwrap();
+
+        let schema = load_schema(&schema_name);
+        assert_eq!(Ok(()), schema.check_query_compatibility(&ir_query));
+    }
+
+    #[test]
+    fn fingerprint_ignores_definition_order_and_descriptions() {
+        let schema_text = fs::read_to_string("test_data/schemas/numbers.graphql").unwrap();
+        let schema = Schema::parse(&schema_text).unwrap();
+        assert_eq!(
+            schema.fingerprint(),
+            Schema::parse(&schema_text).unwrap().fingerprint()
+        );
+
+        let described = schema_text.replace(
+            "type Prime",
+            "\"\"\"A number with exactly two divisors.\"\"\"\ntype Prime",
+        );
+        assert_ne!(schema_text, described);
+        assert_eq!(
+            schema.fingerprint(),
+            Schema::parse(described).unwrap().fingerprint()
+        );
+
+        let changed = schema_text.replace("max: Int!", "max: Int");
+        assert_ne!(schema_text, changed);
+        assert_ne!(
+            schema.fingerprint(),
+            Schema::parse(changed).unwrap().fingerprint()
+        );
+    }
+
+    #[test]
+    fn schema_changes_produce_precise_incompatibilities() {
+        let original = load_schema("numbers");
+        let query = r#"
+{
+    Number(max: 10) {
+        value @output
+        name @output
+
+        successor {
+            value @output(name: "next")
+        }
+    }
+}"#;
+        let ir_query = parse_to_ir(&original, query).unwrap();
+        assert_eq!(Ok(()), original.check_query_compatibility(&ir_query));
+
+        let schema_text = fs::read_to_string("test_data/schemas/numbers.graphql").unwrap();
+        let changed_text = schema_text
+            .replace("name: String\n", "label: String\n")
+            .replace(
+                "Number(min: Int! = 0, max: Int!)",
+                "Number(min: Int! = 0, limit: Int!)",
+            );
+        let changed = Schema::parse(changed_text).unwrap();
+        assert_ne!(original.fingerprint(), changed.fingerprint());
+
+        let Err(IncompatibleQueryError::MultipleErrors(DisplayVec(errors))) =
+            changed.check_query_compatibility(&ir_query)
+        else {
+            panic!("expected multiple incompatibilities");
+        };
+        assert_eq!(
+            vec![
+                IncompatibleQueryError::UnexpectedEdgeParameter(
+                    "RootSchemaQuery".to_string(),
+                    "Number".to_string(),
+                    "max".to_string(),
+                ),
+                IncompatibleQueryError::MissingEdgeParameter(
+                    "RootSchemaQuery".to_string(),
+                    "Number".to_string(),
+                    "limit".to_string(),
+                ),
+                IncompatibleQueryError::NonExistentProperty(
+                    "Number".to_string(),
+                    "name".to_string(),
+                ),
+            ],
+            errors,
+        );
+    }
+}
@@ -0,0 +1,404 @@
+//! Validation constraints on edge parameters, declared in the schema with `@constraint`.
+use std::{cmp::Ordering, collections::HashMap, sync::Arc};
+
+use async_graphql_parser::types::{
+    BaseType, FieldDefinition, InputValueDefinition, Type, TypeDefinition,
+};
+use async_graphql_value::ConstValue;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::ir::FieldValue;
+
+use super::{error::InvalidSchemaError, get_vertex_type_fields, is_parameter_value_valid};
+
+pub(super) const CONSTRAINT_DIRECTIVE: &str = "constraint";
+pub(super) const CONSTRAINT_MIN_ARGUMENT: &str = "min";
+pub(super) const CONSTRAINT_MAX_ARGUMENT: &str = "max";
+pub(super) const CONSTRAINT_NON_EMPTY_ARGUMENT: &str = "nonEmpty";
+pub(super) const CONSTRAINT_ONE_OF_ARGUMENT: &str = "oneOf";
+
+/// The constraints that values of an edge parameter must satisfy, declared in the schema
+/// with a `@constraint` directive on the parameter, for example:
+/// ```graphql
+/// type Package {
+///     versions(limit: Int @constraint(min: 1, max: 100)): [Release!]
+/// }
+/// ```
+///
+/// A `null` value of a nullable parameter satisfies all constraints.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParameterConstraints {
+    /// The smallest allowed value of an `Int` or `Float` parameter, inclusive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<FieldValue>,
+
+    /// The largest allowed value of an `Int` or `Float` parameter, inclusive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<FieldValue>,
+
+    /// Whether values of a `String`, `ID`, or list parameter must not be empty.
+    #[serde(default)]
+    pub non_empty: bool,
+
+    /// The only values the parameter is allowed to take, if restricted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub one_of: Option<Vec<FieldValue>>,
+}
+
+impl ParameterConstraints {
+    /// Check whether the value satisfies all the constraints.
+    pub fn check(&self, value: &FieldValue) -> Result<(), ConstraintViolation> {
+        if matches!(value, FieldValue::Null) {
+            return Ok(());
+        }
+
+        if let Some(min) = &self.min {
+            if compare_numbers(value, min).is_some_and(Ordering::is_lt) {
+                return Err(ConstraintViolation::BelowMinimum(min.clone()));
+            }
+        }
+        if let Some(max) = &self.max {
+            if compare_numbers(value, max).is_some_and(Ordering::is_gt) {
+                return Err(ConstraintViolation::AboveMaximum(max.clone()));
+            }
+        }
+        if self.non_empty {
+            let is_empty = match value {
+                FieldValue::String(s) => s.is_empty(),
+                FieldValue::List(values) => values.is_empty(),
+                _ => false,
+            };
+            if is_empty {
+                return Err(ConstraintViolation::Empty);
+            }
+        }
+        if let Some(allowed) = &self.one_of {
+            if !allowed.contains(value) {
+                return Err(ConstraintViolation::NotOneOf(allowed.clone()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The way in which a value fails to satisfy a parameter's [`ParameterConstraints`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, thiserror::Error)]
+pub enum ConstraintViolation {
+    #[error("the value must be at least {0:?}")]
+    BelowMinimum(FieldValue),
+
+    #[error("the value must be at most {0:?}")]
+    AboveMaximum(FieldValue),
+
+    #[error("the value must not be empty")]
+    Empty,
+
+    #[error("the value must be one of {0:?}")]
+    NotOneOf(Vec<FieldValue>),
+}
+
+/// Numeric values of different representations are compared by their numeric value.
+/// Returns `None` if either value is not a number.
+fn compare_numbers(left: &FieldValue, right: &FieldValue) -> Option<Ordering> {
+    match (left, right) {
+        (FieldValue::Float64(_), _) | (_, FieldValue::Float64(_)) => {
+            as_f64(left)?.partial_cmp(&as_f64(right)?)
+        }
+        (
+            FieldValue::Int64(_) | FieldValue::Uint64(_),
+            FieldValue::Int64(_) | FieldValue::Uint64(_),
+        ) => left.partial_cmp(right),
+        _ => None,
+    }
+}
+
+fn as_f64(value: &FieldValue) -> Option<f64> {
+    match value {
+        FieldValue::Int64(x) => Some(*x as f64),
+        FieldValue::Uint64(x) => Some(*x as f64),
+        FieldValue::Float64(x) => Some(*x),
+        _ => None,
+    }
+}
+
+/// If the parameter is annotated with `@constraint`, returns its constraints.
+pub(crate) fn get_parameter_constraints(
+    param_defn: &InputValueDefinition,
+) -> Option<ParameterConstraints> {
+    let directive = param_defn
+        .directives
+        .iter()
+        .find(|d| d.node.name.node.as_ref() == CONSTRAINT_DIRECTIVE)?;
+
+    let number_argument = |name: &str| {
+        directive
+            .node
+            .get_argument(name)
+            .map(|value| parse_number(&value.node).expect("invalid @constraint bound"))
+    };
+    let non_empty = match directive.node.get_argument(CONSTRAINT_NON_EMPTY_ARGUMENT) {
+        None => false,
+        Some(value) => match &value.node {
+            ConstValue::Boolean(b) => *b,
+            _ => panic!("invalid @constraint nonEmpty"),
+        },
+    };
+    let one_of = directive
+        .node
+        .get_argument(CONSTRAINT_ONE_OF_ARGUMENT)
+        .map(|value| parse_one_of(&value.node).expect("invalid @constraint oneOf"));
+
+    Some(ParameterConstraints {
+        min: number_argument(CONSTRAINT_MIN_ARGUMENT),
+        max: number_argument(CONSTRAINT_MAX_ARGUMENT),
+        non_empty,
+        one_of,
+    })
+}
+
+fn parse_number(value: &ConstValue) -> Option<FieldValue> {
+    match value {
+        ConstValue::Number(_) => value.clone().try_into().ok(),
+        _ => None,
+    }
+}
+
+fn parse_one_of(value: &ConstValue) -> Option<Vec<FieldValue>> {
+    match value {
+        ConstValue::List(values) => values
+            .iter()
+            .map(|value| value.clone().try_into().ok())
+            .collect(),
+        _ => None,
+    }
+}
+
+fn is_numeric_type(ty: &Type) -> bool {
+    matches!(&ty.base, BaseType::Named(name) if name == "Int" || name == "Float")
+}
+
+fn is_sized_type(ty: &Type) -> bool {
+    match &ty.base {
+        BaseType::Named(name) => name == "String" || name == "ID",
+        BaseType::List(_) => true,
+    }
+}
+
+pub(super) fn check_constraint_directives(
+    vertex_types: &HashMap<Arc<str>, TypeDefinition>,
+    scalars: &HashMap<Arc<str>, TypeDefinition>,
+    enums: &HashMap<Arc<str>, TypeDefinition>,
+) -> Result<(), Vec<InvalidSchemaError>> {
+    let mut errors: Vec<InvalidSchemaError> = vec![];
+
+    // Sorted, so that errors are reported in a deterministic order.
+    for (type_name, type_defn) in vertex_types.iter().sorted_by_key(|(name, _)| *name) {
+        for defn in get_vertex_type_fields(type_defn) {
+            let field_defn = &defn.node;
+            for param in &field_defn.arguments {
+                if let Err(e) =
+                    check_parameter_constraints(type_name, field_defn, &param.node, scalars, enums)
+                {
+                    errors.extend(e);
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn check_parameter_constraints(
+    type_name: &str,
+    field_defn: &FieldDefinition,
+    param_defn: &InputValueDefinition,
+    scalars: &HashMap<Arc<str>, TypeDefinition>,
+    enums: &HashMap<Arc<str>, TypeDefinition>,
+) -> Result<(), Vec<InvalidSchemaError>> {
+    let mut errors: Vec<InvalidSchemaError> = vec![];
+    let field_name = field_defn.name.node.as_str();
+    let param_name = param_defn.name.node.as_str();
+    let param_type = &param_defn.ty.node;
+
+    let constraints = param_defn
+        .directives
+        .iter()
+        .filter(|d| d.node.name.node.as_ref() == CONSTRAINT_DIRECTIVE)
+        .collect_vec();
+    if constraints.len() > 1 {
+        errors.push(InvalidSchemaError::DuplicatedConstraintDirective(
+            type_name.to_string(),
+            field_name.to_string(),
+            param_name.to_string(),
+        ));
+    }
+
+    for directive in constraints {
+        for (arg_name, arg_value) in &directive.node.arguments {
+            let (is_applicable, is_valid) = match arg_name.node.as_ref() {
+                CONSTRAINT_MIN_ARGUMENT | CONSTRAINT_MAX_ARGUMENT => {
+                    let is_valid = match parse_number(&arg_value.node) {
+                        Some(FieldValue::Float64(_)) => matches!(
+                            &param_type.base, BaseType::Named(name) if name == "Float"
+                        ),
+                        Some(_) => true,
+                        None => false,
+                    };
+                    (is_numeric_type(param_type), is_valid)
+                }
+                CONSTRAINT_NON_EMPTY_ARGUMENT => (
+                    is_sized_type(param_type),
+                    matches!(arg_value.node, ConstValue::Boolean(_)),
+                ),
+                CONSTRAINT_ONE_OF_ARGUMENT => {
+                    let is_valid = parse_one_of(&arg_value.node).is_some_and(|values| {
+                        values.iter().all(|value| {
+                            is_parameter_value_valid(scalars, enums, param_type, value)
+                        })
+                    });
+                    (matches!(param_type.base, BaseType::Named(_)), is_valid)
+                }
+                _ => {
+                    errors.push(InvalidSchemaError::UnexpectedConstraintDirectiveArgument(
+                        type_name.to_string(),
+                        field_name.to_string(),
+                        param_name.to_string(),
+                        arg_name.node.to_string(),
+                    ));
+                    continue;
+                }
+            };
+
+            if !is_applicable {
+                errors.push(InvalidSchemaError::InapplicableConstraintDirectiveArgument(
+                    type_name.to_string(),
+                    field_name.to_string(),
+                    param_name.to_string(),
+                    arg_name.node.to_string(),
+                    param_type.to_string(),
+                ));
+            } else if !is_valid {
+                errors.push(InvalidSchemaError::InvalidConstraintDirectiveArgument(
+                    type_name.to_string(),
+                    field_name.to_string(),
+                    param_name.to_string(),
+                    arg_name.node.to_string(),
+                    arg_value.node.to_string(),
+                ));
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    // The directive's arguments are valid, so the constraints can be checked for consistency.
+    if let Some(constraints) = get_parameter_constraints(param_defn) {
+        if let (Some(min), Some(max)) = (&constraints.min, &constraints.max) {
+            if compare_numbers(min, max).is_some_and(Ordering::is_gt) {
+                errors.push(InvalidSchemaError::EmptyConstraintRange(
+                    type_name.to_string(),
+                    field_name.to_string(),
+                    param_name.to_string(),
+                ));
+            }
+        }
+
+        let default_value = param_defn
+            .default_value
+            .as_ref()
+            .and_then(|value| FieldValue::try_from(value.node.clone()).ok());
+        if let Some(default_value) = default_value {
+            if let Err(violation) = constraints.check(&default_value) {
+                errors.push(InvalidSchemaError::DefaultValueViolatesConstraint(
+                    type_name.to_string(),
+                    field_name.to_string(),
+                    param_name.to_string(),
+                    violation.to_string(),
+                ));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ir::FieldValue;
+
+    use super::{ConstraintViolation, ParameterConstraints};
+
+    #[test]
+    fn null_satisfies_all_constraints() {
+        let constraints = ParameterConstraints {
+            min: Some(FieldValue::Int64(1)),
+            max: Some(FieldValue::Int64(2)),
+            non_empty: true,
+            one_of: Some(vec![FieldValue::Int64(1)]),
+        };
+        assert_eq!(Ok(()), constraints.check(&FieldValue::Null));
+    }
+
+    #[test]
+    fn numeric_bounds_compare_across_representations() {
+        let constraints = ParameterConstraints {
+            min: Some(FieldValue::Int64(-1)),
+            max: Some(FieldValue::Float64(2.5)),
+            ..Default::default()
+        };
+        assert_eq!(Ok(()), constraints.check(&FieldValue::Uint64(2)));
+        assert_eq!(Ok(()), constraints.check(&FieldValue::Float64(-0.5)));
+        assert_eq!(
+            Err(ConstraintViolation::BelowMinimum(FieldValue::Int64(-1))),
+            constraints.check(&FieldValue::Float64(-1.5)),
+        );
+        assert_eq!(
+            Err(ConstraintViolation::AboveMaximum(FieldValue::Float64(2.5))),
+            constraints.check(&FieldValue::Uint64(3)),
+        );
+    }
+
+    #[test]
+    fn empty_values_and_disallowed_values() {
+        let constraints = ParameterConstraints {
+            non_empty: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            Err(ConstraintViolation::Empty),
+            constraints.check(&FieldValue::String(String::new())),
+        );
+        assert_eq!(
+            Err(ConstraintViolation::Empty),
+            constraints.check(&FieldValue::List(vec![])),
+        );
+        assert_eq!(Ok(()), constraints.check(&"x".into()));
+
+        let allowed = vec![
+            FieldValue::Enum("ASC".into()),
+            FieldValue::Enum("DESC".into()),
+        ];
+        let constraints = ParameterConstraints {
+            one_of: Some(allowed.clone()),
+            ..Default::default()
+        };
+        assert_eq!(Ok(()), constraints.check(&FieldValue::Enum("ASC".into())));
+        assert_eq!(
+            Err(ConstraintViolation::NotOneOf(allowed)),
+            constraints.check(&FieldValue::Enum("UP".into())),
+        );
+    }
+}
@@ -0,0 +1,391 @@
+use std::{cmp::Ordering, fmt::Debug, sync::Arc};
+
+use crate::ir::FieldValue;
+
+type ScalarParser = dyn Fn(&FieldValue) -> Result<FieldValue, String> + Send + Sync;
+type ScalarValidator = dyn Fn(&FieldValue) -> Result<(), String> + Send + Sync;
+type ScalarComparator = dyn Fn(&FieldValue, &FieldValue) -> Option<Ordering> + Send + Sync;
+
+/// Hooks describing how to handle values of a custom scalar type,
+/// declared in the schema with `scalar <Name>`.
+///
+/// Register these with [`Schema::register_custom_scalar`](super::Schema::register_custom_scalar).
+/// All hooks are optional:
+/// - the parser converts query argument values into the representation the adapter uses,
+/// - the validator rejects query argument values that aren't valid for the scalar,
+/// - the comparator defines an ordering over the scalar's values; scalars without one
+///   cannot be used with ordering filters like `<` and `>=`.
+///
+/// Hooks are never called with `null` values, since nullability is checked separately.
+///
+/// ```rust
+/// use trustfall_core::{ir::FieldValue, schema::CustomScalar};
+///
+/// let version = CustomScalar::new()
+///     .with_parser(|value| match value {
+///         FieldValue::String(s) => Ok(FieldValue::String(s.trim_start_matches('v').to_string())),
+///         _ => Err("versions must be strings".to_string()),
+///     })
+///     .with_validator(|value| match value.as_str() {
+///         Some(s) if s.split('.').all(|part| part.parse::<u64>().is_ok()) => Ok(()),
+///         _ => Err("versions must be dot-separated numbers".to_string()),
+///     });
+///
+/// assert_eq!(
+///     Ok(FieldValue::String("1.2.3".to_string())),
+///     version.parse_and_validate(&FieldValue::String("v1.2.3".to_string())),
+/// );
+/// assert!(version.parse_and_validate(&FieldValue::String("one".to_string())).is_err());
+/// ```
+#[derive(Clone, Default)]
+pub struct CustomScalar {
+    parser: Option<Arc<ScalarParser>>,
+    validator: Option<Arc<ScalarValidator>>,
+    comparator: Option<Arc<ScalarComparator>>,
+}
+
+impl CustomScalar {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_parser(
+        mut self,
+        parser: impl Fn(&FieldValue) -> Result<FieldValue, String> + Send + Sync + 'static,
+    ) -> Self {
+        self.parser = Some(Arc::new(parser));
+        self
+    }
+
+    pub fn with_validator(
+        mut self,
+        validator: impl Fn(&FieldValue) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.validator = Some(Arc::new(validator));
+        self
+    }
+
+    pub fn with_comparator(
+        mut self,
+        comparator: impl Fn(&FieldValue, &FieldValue) -> Option<Ordering> + Send + Sync + 'static,
+    ) -> Self {
+        self.comparator = Some(Arc::new(comparator));
+        self
+    }
+
+    /// Whether values of this scalar may be used with ordering filters like `<` and `>=`.
+    pub fn is_orderable(&self) -> bool {
+        self.comparator.is_some()
+    }
+
+    /// Run the parser and then the validator (if present) on the given value.
+    ///
+    /// `null` values are returned as-is without calling either hook.
+    pub fn parse_and_validate(&self, value: &FieldValue) -> Result<FieldValue, String> {
+        if matches!(value, FieldValue::Null) {
+            return Ok(FieldValue::Null);
+        }
+
+        let parsed = match &self.parser {
+            Some(parser) => parser(value)?,
+            None => value.clone(),
+        };
+        if let Some(validator) = &self.validator {
+            validator(&parsed)?;
+        }
+        Ok(parsed)
+    }
+
+    /// Compare two values of this scalar, if a comparator was registered.
+    ///
+    /// Returns `None` if either value is `null`, if no comparator is registered,
+    /// or if the comparator considers the values to be unordered relative to each other.
+    pub fn compare(&self, left: &FieldValue, right: &FieldValue) -> Option<Ordering> {
+        match (left, right) {
+            (FieldValue::Null, _) | (_, FieldValue::Null) => None,
+            _ => self.comparator.as_ref().and_then(|cmp| cmp(left, right)),
+        }
+    }
+}
+
+impl Debug for CustomScalar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomScalar")
+            .field("parser", &self.parser.is_some())
+            .field("validator", &self.validator.is_some())
+            .field("comparator", &self.comparator.is_some())
+            .finish()
+    }
+}
+
+/// Hooks are compared by identity: two `CustomScalar` values are equal
+/// if they were cloned from the same registration.
+impl PartialEq for CustomScalar {
+    fn eq(&self, other: &Self) -> bool {
+        fn same<T: ?Sized>(l: &Option<Arc<T>>, r: &Option<Arc<T>>) -> bool {
+            match (l, r) {
+                (None, None) => true,
+                (Some(l), Some(r)) => Arc::ptr_eq(l, r),
+                _ => false,
+            }
+        }
+
+        same(&self.parser, &other.parser)
+            && same(&self.validator, &other.validator)
+            && same(&self.comparator, &other.comparator)
+    }
+}
+
+impl Eq for CustomScalar {}
+
+#[cfg(test)]
+mod tests {
+    use std::{cmp::Ordering, collections::BTreeMap, sync::Arc};
+
+    use crate::{
+        frontend::{self, error::FilterTypeError, error::FrontendError},
+        interpreter::{
+            basic_adapter::BasicAdapter, error::QueryArgumentsError, execution::interpret_ir,
+            helpers::resolve_property_with, ContextIterator, ContextOutcomeIterator, Typename,
+            VertexIterator,
+        },
+        ir::{EdgeParameters, FieldValue},
+        schema::Schema,
+    };
+
+    use super::CustomScalar;
+
+    const RELEASES_SCHEMA: &str = r#"
+schema {
+    query: RootSchemaQuery
+}
+directive @filter(op: String!, value: [String!]) on FIELD | INLINE_FRAGMENT
+directive @tag(name: String) on FIELD
+directive @output(name: String) on FIELD
+directive @optional on FIELD
+directive @recurse(depth: Int!) on FIELD
+directive @fold on FIELD
+directive @transform(op: String!) on FIELD
+
+scalar Version
+
+type RootSchemaQuery {
+    Release: [Release!]!
+}
+
+type Release {
+    version: Version!
+}
+"#;
+
+    const RELEASES_QUERY: &str = r#"
+{
+    Release {
+        version @output @filter(op: ">=", value: ["$min"])
+    }
+}"#;
+
+    #[derive(Debug, Clone)]
+    struct Release(&'static str);
+
+    impl Typename for Release {
+        fn typename(&self) -> &'static str {
+            "Release"
+        }
+    }
+
+    struct ReleasesAdapter;
+
+    impl<'a> BasicAdapter<'a> for ReleasesAdapter {
+        type Vertex = Release;
+
+        fn resolve_starting_vertices(
+            &self,
+            _edge_name: &str,
+            _parameters: &EdgeParameters,
+        ) -> VertexIterator<'a, Self::Vertex> {
+            Box::new(["1.2.0", "1.9.0", "1.10.0"].into_iter().map(Release))
+        }
+
+        fn resolve_property(
+            &self,
+            contexts: ContextIterator<'a, Self::Vertex>,
+            _type_name: &str,
+            _property_name: &str,
+        ) -> ContextOutcomeIterator<'a, Self::Vertex, FieldValue> {
+            resolve_property_with(contexts, |release| release.0.into())
+        }
+
+        fn resolve_neighbors(
+            &self,
+            _contexts: ContextIterator<'a, Self::Vertex>,
+            _type_name: &str,
+            _edge_name: &str,
+            _parameters: &EdgeParameters,
+        ) -> ContextOutcomeIterator<'a, Self::Vertex, VertexIterator<'a, Self::Vertex>> {
+            unreachable!()
+        }
+
+        fn resolve_coercion(
+            &self,
+            _contexts: ContextIterator<'a, Self::Vertex>,
+            _type_name: &str,
+            _coerce_to_type: &str,
+        ) -> ContextOutcomeIterator<'a, Self::Vertex, bool> {
+            unreachable!()
+        }
+    }
+
+    fn version_parts(value: &FieldValue) -> Option<Vec<u64>> {
+        value
+            .as_str()?
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect()
+    }
+
+    fn version_scalar() -> CustomScalar {
+        CustomScalar::new()
+            .with_parser(|value| match value {
+                FieldValue::String(s) => Ok(s.trim_start_matches('v').into()),
+                _ => Err("versions must be strings".to_string()),
+            })
+            .with_validator(|value| {
+                version_parts(value)
+                    .map(|_| ())
+                    .ok_or_else(|| "versions must be dot-separated numbers".to_string())
+            })
+            .with_comparator(|l, r| Some(version_parts(l)?.cmp(&version_parts(r)?)))
+    }
+
+    #[test]
+    fn hooks_are_not_called_on_null() {
+        let scalar = CustomScalar::new()
+            .with_parser(|_| Err("parser called".to_string()))
+            .with_validator(|_| Err("validator called".to_string()))
+            .with_comparator(|_, _| panic!("comparator called"));
+
+        assert_eq!(
+            Ok(FieldValue::Null),
+            scalar.parse_and_validate(&FieldValue::Null)
+        );
+        assert_eq!(
+            None,
+            scalar.compare(&FieldValue::Null, &FieldValue::Int64(1))
+        );
+    }
+
+    #[test]
+    fn validator_sees_parsed_value() {
+        let scalar = CustomScalar::new()
+            .with_parser(|value| match value {
+                FieldValue::String(s) => s
+                    .parse::<i64>()
+                    .map(FieldValue::Int64)
+                    .map_err(|e| e.to_string()),
+                _ => Err("not a string".to_string()),
+            })
+            .with_validator(|value| match value {
+                FieldValue::Int64(x) if *x >= 0 => Ok(()),
+                _ => Err("negative".to_string()),
+            });
+
+        assert_eq!(
+            Ok(FieldValue::Int64(42)),
+            scalar.parse_and_validate(&FieldValue::String("42".to_string()))
+        );
+        assert_eq!(
+            Err("negative".to_string()),
+            scalar.parse_and_validate(&FieldValue::String("-1".to_string()))
+        );
+        assert!(scalar.parse_and_validate(&FieldValue::Int64(1)).is_err());
+    }
+
+    #[test]
+    fn comparator_defines_orderability() {
+        let unordered = CustomScalar::new();
+        assert!(!unordered.is_orderable());
+        assert_eq!(
+            None,
+            unordered.compare(&FieldValue::Int64(1), &FieldValue::Int64(2))
+        );
+
+        let reversed = CustomScalar::new().with_comparator(|l, r| match (l, r) {
+            (FieldValue::Int64(l), FieldValue::Int64(r)) => Some(r.cmp(l)),
+            _ => None,
+        });
+        assert!(reversed.is_orderable());
+        assert_eq!(
+            Some(Ordering::Greater),
+            reversed.compare(&FieldValue::Int64(1), &FieldValue::Int64(2))
+        );
+    }
+
+    #[test]
+    fn registering_undeclared_scalar_is_an_error() {
+        let mut schema = Schema::parse(RELEASES_SCHEMA).unwrap();
+        assert!(schema
+            .register_custom_scalar("Nonexistent", CustomScalar::new())
+            .is_err());
+        assert!(schema
+            .register_custom_scalar("Version", CustomScalar::new())
+            .is_ok());
+    }
+
+    #[test]
+    fn ordering_filters_require_a_comparator() {
+        let mut schema = Schema::parse(RELEASES_SCHEMA).unwrap();
+        schema
+            .register_custom_scalar("Version", CustomScalar::new())
+            .unwrap();
+
+        assert_eq!(
+            Err(FrontendError::FilterTypeError(
+                FilterTypeError::OrderingFilterOperationOnNonOrderableField(
+                    ">=".to_string(),
+                    "version".to_string(),
+                    "Version!".to_string(),
+                )
+            )),
+            frontend::parse(&schema, RELEASES_QUERY).map(|_| ()),
+        );
+    }
+
+    #[test]
+    fn custom_scalar_hooks_apply_during_execution() {
+        let mut schema = Schema::parse(RELEASES_SCHEMA).unwrap();
+        schema
+            .register_custom_scalar("Version", version_scalar())
+            .unwrap();
+        let query = frontend::parse(&schema, RELEASES_QUERY).unwrap();
+
+        let arguments: BTreeMap<Arc<str>, FieldValue> = btreemap! {
+            Arc::from("min") => "v1.9".into(),
+        };
+        let results: Vec<_> = interpret_ir(
+            Arc::new(ReleasesAdapter),
+            query.clone(),
+            Arc::new(arguments),
+        )
+        .unwrap()
+        .map(|row| row["version"].clone())
+        .collect();
+        assert_eq!(
+            vec![FieldValue::from("1.9.0"), FieldValue::from("1.10.0")],
+            results
+        );
+
+        let arguments: BTreeMap<Arc<str>, FieldValue> = btreemap! {
+            Arc::from("min") => "banana".into(),
+        };
+        match interpret_ir(Arc::new(ReleasesAdapter), query, Arc::new(arguments)) {
+            Err(QueryArgumentsError::InvalidCustomScalarArgument(name, ty, value, _)) => {
+                assert_eq!("min", name);
+                assert_eq!("Version!", ty);
+                assert_eq!(FieldValue::from("banana"), value);
+            }
+            Ok(_) => panic!("expected an error, but got results"),
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    }
+}
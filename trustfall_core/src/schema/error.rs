@@ -1,6 +1,6 @@
 use serde::{ser::Error as SerError, Deserialize, Serialize, Serializer};
 
-use crate::util::DisplayVec;
+use crate::{ir::FieldValue, util::DisplayVec};
 
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, thiserror::Error)]
@@ -116,6 +116,160 @@ pub enum InvalidSchemaError {
         for internal use and cannot be used in schemas."
     )]
     ReservedTypeName(String),
+
+    #[error(
+        "Field \"{1}\" on type \"{0}\" is marked @deprecated, but the deprecation reason \
+        is not a string: {2}"
+    )]
+    InvalidDeprecationReason(String, String, String),
+
+    #[error(
+        "Field \"{1}\" on type \"{0}\" has a @deprecated directive with unexpected \
+        argument \"{2}\". The only supported argument is \"reason\"."
+    )]
+    UnexpectedDeprecatedDirectiveArgument(String, String, String),
+
+    #[error("Field \"{1}\" on type \"{0}\" has more than one @deprecated directive.")]
+    DuplicatedDeprecatedDirective(String, String),
+
+    #[error(
+        "Field \"{1}\" on type \"{0}\" has a @cost directive with invalid value {3} for \
+        argument \"{2}\". The \"fanout\" argument must be a non-negative integer, \
+        and the \"latency\" argument must be one of \"low\", \"medium\", or \"high\"."
+    )]
+    InvalidCostDirectiveArgument(String, String, String, String),
+
+    #[error(
+        "Field \"{1}\" on type \"{0}\" has a @cost directive with unexpected \
+        argument \"{2}\". The only supported arguments are \"fanout\" and \"latency\"."
+    )]
+    UnexpectedCostDirectiveArgument(String, String, String),
+
+    #[error("Field \"{1}\" on type \"{0}\" has more than one @cost directive.")]
+    DuplicatedCostDirective(String, String),
+
+    #[error(
+        "Field \"{1}\" on type \"{0}\" is marked @restricted, but the restriction scope \
+        is not a string: {2}"
+    )]
+    InvalidRestrictionScope(String, String, String),
+
+    #[error(
+        "Field \"{1}\" on type \"{0}\" has a @restricted directive with unexpected \
+        argument \"{2}\". The only supported argument is \"scope\"."
+    )]
+    UnexpectedRestrictedDirectiveArgument(String, String, String),
+
+    #[error("Field \"{1}\" on type \"{0}\" has more than one @restricted directive.")]
+    DuplicatedRestrictedDirective(String, String),
+
+    #[error(
+        "Field \"{1}\" on type \"{0}\" comes from the implementation of interface \"{2}\", \
+        which marks it @restricted. Implementations must mark the field @restricted \
+        with the same scope, so that the restriction cannot be bypassed via type coercion."
+    )]
+    RestrictionNotInherited(String, String, String),
+
+    #[error(
+        "Property \"{1}\" on type \"{0}\" is marked @inverse, but only edges can have inverses."
+    )]
+    InverseDirectiveOnProperty(String, String),
+
+    #[error(
+        "Edge \"{1}\" on type \"{0}\" has an @inverse directive without an \"of\" argument \
+        naming its inverse edge."
+    )]
+    MissingInverseDirectiveArgument(String, String),
+
+    #[error(
+        "Edge \"{1}\" on type \"{0}\" has an @inverse directive whose \"of\" argument \
+        is not a string: {2}"
+    )]
+    InvalidInverseDirectiveArgument(String, String, String),
+
+    #[error(
+        "Edge \"{1}\" on type \"{0}\" has an @inverse directive with unexpected \
+        argument \"{2}\". The only supported argument is \"of\"."
+    )]
+    UnexpectedInverseDirectiveArgument(String, String, String),
+
+    #[error("Edge \"{1}\" on type \"{0}\" has more than one @inverse directive.")]
+    DuplicatedInverseDirective(String, String),
+
+    #[error(
+        "Edge \"{1}\" on type \"{0}\" is declared to be the inverse of edge \"{3}\" \
+        on type \"{2}\", but type \"{2}\" has no such edge."
+    )]
+    InverseEdgeNotFound(String, String, String, String),
+
+    #[error(
+        "Edge \"{1}\" on type \"{0}\" is declared to be the inverse of edge \"{3}\" \
+        on type \"{2}\", but that edge points to type \"{4}\" which is not compatible \
+        with type \"{0}\"."
+    )]
+    IncompatibleInverseEdge(String, String, String, String, String),
+
+    #[error(
+        "Edge \"{1}\" on type \"{0}\" is declared to be the inverse of edge \"{3}\" \
+        on type \"{2}\", but that edge is declared to be the inverse of edge \"{4}\" instead."
+    )]
+    InconsistentInverseEdges(String, String, String, String, String),
+
+    #[error(
+        "Parameter \"{2}\" of field \"{1}\" on type \"{0}\" has a @constraint directive with \
+        invalid value for argument \"{3}\": {4}. The \"min\" and \"max\" arguments must be \
+        numbers of the parameter's type, \"nonEmpty\" must be a boolean, and \"oneOf\" must be \
+        a list of valid values for the parameter."
+    )]
+    InvalidConstraintDirectiveArgument(String, String, String, String, String),
+
+    #[error(
+        "Parameter \"{2}\" of field \"{1}\" on type \"{0}\" has a @constraint directive with \
+        unexpected argument \"{3}\". The only supported arguments are \"min\", \"max\", \
+        \"nonEmpty\", and \"oneOf\"."
+    )]
+    UnexpectedConstraintDirectiveArgument(String, String, String, String),
+
+    #[error(
+        "Parameter \"{2}\" of field \"{1}\" on type \"{0}\" has a @constraint directive with \
+        argument \"{3}\", which does not apply to parameters of type {4}. The \"min\" and \
+        \"max\" arguments apply to Int and Float parameters, \"nonEmpty\" applies to String, \
+        ID, and list parameters, and \"oneOf\" applies to all non-list parameters."
+    )]
+    InapplicableConstraintDirectiveArgument(String, String, String, String, String),
+
+    #[error("Parameter \"{2}\" of field \"{1}\" on type \"{0}\" has more than one @constraint directive.")]
+    DuplicatedConstraintDirective(String, String, String),
+
+    #[error(
+        "Parameter \"{2}\" of field \"{1}\" on type \"{0}\" has a @constraint directive \
+        whose \"min\" is greater than its \"max\", so no value can satisfy it."
+    )]
+    EmptyConstraintRange(String, String, String),
+
+    #[error(
+        "The default value of parameter \"{2}\" of field \"{1}\" on type \"{0}\" does not \
+        satisfy the parameter's @constraint directive: {3}"
+    )]
+    DefaultValueViolatesConstraint(String, String, String, String),
+
+    #[error(
+        "Attempted to register hooks for custom scalar \"{0}\", but the schema does not \
+        declare a scalar by that name. Custom scalars must be declared with `scalar {0}`."
+    )]
+    UndeclaredCustomScalar(String),
+
+    #[error("Enum \"{0}\" declares the value \"{1}\" more than once.")]
+    DuplicateEnumValue(String, String),
+
+    #[error(
+        "Schema JSON uses format version {0}, but only version {1} is supported by this version \
+        of trustfall."
+    )]
+    UnsupportedSchemaJsonVersion(u64, u64),
+
+    #[error("Invalid schema JSON: {0}")]
+    InvalidSchemaJson(String),
 }
 
 impl From<Vec<InvalidSchemaError>> for InvalidSchemaError {
@@ -129,6 +283,101 @@ impl From<Vec<InvalidSchemaError>> for InvalidSchemaError {
     }
 }
 
+/// Errors when executing a standard GraphQL introspection query against a schema.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, thiserror::Error)]
+pub enum IntrospectionQueryError {
+    #[error("Failed to parse the introspection query: {0}")]
+    ParseError(String),
+
+    #[error("The query contains multiple operations, but no operation name was specified.")]
+    AmbiguousOperation,
+
+    #[error("The query contains no operation named \"{0}\".")]
+    UnknownOperation(String),
+
+    #[error("Introspection is only supported in query operations, not in {0} operations.")]
+    UnsupportedOperationType(String),
+
+    #[error("Field \"{1}\" is not defined on introspection type \"{0}\".")]
+    UnknownField(String, String),
+
+    #[error("Fragment \"{0}\" is used but not defined.")]
+    UnknownFragment(String),
+
+    #[error("Variable \"{0}\" is used but was not provided, and has no default value.")]
+    MissingVariable(String),
+
+    #[error("Invalid value for argument \"{1}\" of field \"{0}\": {2}")]
+    InvalidArgument(String, String, String),
+}
+
+/// Ways in which a previously-compiled query is no longer valid against a schema.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, thiserror::Error)]
+pub enum IncompatibleQueryError {
+    #[error("Multiple incompatibilities: {0}")]
+    MultipleErrors(DisplayVec<IncompatibleQueryError>),
+
+    #[error("The query uses type \"{0}\", which is not defined in the schema.")]
+    NonExistentType(String),
+
+    #[error("The query uses edge \"{1}\" on type \"{0}\", which is not defined in the schema.")]
+    NonExistentEdge(String, String),
+
+    #[error(
+        "The query expects edge \"{1}\" on type \"{0}\" to point to type \"{2}\", \
+        but in the schema it points to type \"{3}\"."
+    )]
+    EdgeTargetTypeChanged(String, String, String, String),
+
+    #[error(
+        "The query uses property \"{1}\" on type \"{0}\", which is not defined in the schema."
+    )]
+    NonExistentProperty(String, String),
+
+    #[error(
+        "The query expects property \"{1}\" on type \"{0}\" to have type \"{2}\", \
+        but in the schema its type is \"{3}\"."
+    )]
+    PropertyTypeChanged(String, String, String, String),
+
+    #[error(
+        "The query coerces type \"{0}\" to type \"{1}\", which is no longer valid: \
+        \"{1}\" is not a subtype of interface \"{0}\" in the schema."
+    )]
+    InvalidCoercion(String, String),
+
+    #[error(
+        "The query supplies parameter \"{2}\" to edge \"{1}\" on type \"{0}\", \
+        but the schema does not define that parameter."
+    )]
+    UnexpectedEdgeParameter(String, String, String),
+
+    #[error(
+        "The schema defines parameter \"{2}\" on edge \"{1}\" of type \"{0}\", \
+        but the query does not supply a value for it."
+    )]
+    MissingEdgeParameter(String, String, String),
+
+    #[error(
+        "The query supplies value {4:?} for parameter \"{2}\" of edge \"{1}\" on type \"{0}\", \
+        which is not valid for the parameter's type \"{3}\" in the schema."
+    )]
+    InvalidEdgeParameterValue(String, String, String, String, FieldValue),
+}
+
+impl From<Vec<IncompatibleQueryError>> for IncompatibleQueryError {
+    fn from(v: Vec<IncompatibleQueryError>) -> Self {
+        assert!(!v.is_empty());
+        if v.len() == 1 {
+            v.into_iter().next().unwrap()
+        } else {
+            Self::MultipleErrors(DisplayVec(v))
+        }
+    }
+}
+
 fn fail_serialize_schema_parse_error<S: Serializer>(
     _: &async_graphql_parser::Error,
     _: S,
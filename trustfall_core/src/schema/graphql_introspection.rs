@@ -0,0 +1,980 @@
+//! The standard GraphQL introspection queries (`__schema`, `__type`, and `__typename`),
+//! so that off-the-shelf GraphQL tooling can discover the types and fields of a schema
+//! exposed by a trustfall-powered GraphQL endpoint.
+//!
+//! Vertex types are presented as GraphQL object and interface types, and edges and properties
+//! as their fields. Trustfall schemas have no mutations, subscriptions, or input object types.
+use std::collections::{BTreeMap, HashMap};
+
+use async_graphql_parser::{
+    parse_query,
+    types::{
+        BaseType, Directive, DirectiveDefinition, DocumentOperations, EnumValueDefinition,
+        ExecutableDocument, FieldDefinition, FragmentDefinition, InputValueDefinition,
+        OperationDefinition, OperationType, Selection, SelectionSet, Type, TypeDefinition,
+        TypeKind,
+    },
+    Positioned,
+};
+use async_graphql_value::{ConstValue, Name, Value};
+use itertools::Itertools;
+use serde_json::{Map, Value as JsonValue};
+
+use super::{
+    error::IntrospectionQueryError, get_deprecation_reason, get_vertex_type_fields,
+    get_vertex_type_implements, json::directive_location_name, Schema, BUILTIN_SCALARS,
+    DEFAULT_DEPRECATION_REASON, DEPRECATED_DIRECTIVE, DEPRECATED_REASON_ARGUMENT,
+};
+
+const TYPENAME_FIELD: &str = "__typename";
+
+impl Schema {
+    /// Execute a GraphQL introspection query, such as the one GraphiQL and code generators
+    /// send to discover a GraphQL API, and return its `data` as JSON.
+    ///
+    /// The query may only select the `__schema`, `__type`, and `__typename` root fields.
+    /// If the query contains multiple operations, `operation_name` selects which to execute.
+    pub fn execute_introspection_query(
+        &self,
+        query: &str,
+        operation_name: Option<&str>,
+        variables: &Map<String, JsonValue>,
+    ) -> Result<JsonValue, IntrospectionQueryError> {
+        let document =
+            parse_query(query).map_err(|e| IntrospectionQueryError::ParseError(e.to_string()))?;
+        let operation = select_operation(&document, operation_name)?;
+        if operation.ty != OperationType::Query {
+            return Err(IntrospectionQueryError::UnsupportedOperationType(
+                operation.ty.to_string(),
+            ));
+        }
+
+        let mut variable_values: BTreeMap<Name, ConstValue> = operation
+            .variable_definitions
+            .iter()
+            .filter_map(|defn| {
+                let default = defn.node.default_value.as_ref()?;
+                Some((defn.node.name.node.clone(), default.node.clone()))
+            })
+            .collect();
+        for (name, value) in variables {
+            let value = ConstValue::from_json(value.clone()).map_err(|e| {
+                IntrospectionQueryError::ParseError(format!("invalid variable \"{name}\": {e}"))
+            })?;
+            variable_values.insert(Name::new(name), value);
+        }
+
+        let executor = Executor {
+            schema: self,
+            fragments: &document.fragments,
+            variables: variable_values,
+        };
+        let mut data = Map::new();
+        executor.execute_selection_set(Node::Query, &operation.selection_set.node, &mut data)?;
+        Ok(JsonValue::Object(data))
+    }
+}
+
+/// Whether the query only selects introspection fields, and so can be answered by
+/// [`Schema::execute_introspection_query`] instead of by executing a trustfall query.
+///
+/// Returns `false` for queries that fail to parse.
+pub fn is_introspection_query(query: &str, operation_name: Option<&str>) -> bool {
+    let Ok(document) = parse_query(query) else {
+        return false;
+    };
+    let Ok(operation) = select_operation(&document, operation_name) else {
+        return false;
+    };
+
+    fn only_selects_introspection_fields(
+        selection_set: &SelectionSet,
+        fragments: &HashMap<Name, Positioned<FragmentDefinition>>,
+    ) -> bool {
+        selection_set
+            .items
+            .iter()
+            .all(|selection| match &selection.node {
+                Selection::Field(field) => field.node.name.node.starts_with("__"),
+                Selection::FragmentSpread(spread) => fragments
+                    .get(&spread.node.fragment_name.node)
+                    .is_some_and(|fragment| {
+                        only_selects_introspection_fields(
+                            &fragment.node.selection_set.node,
+                            fragments,
+                        )
+                    }),
+                Selection::InlineFragment(fragment) => {
+                    only_selects_introspection_fields(&fragment.node.selection_set.node, fragments)
+                }
+            })
+    }
+
+    operation.ty == OperationType::Query
+        && only_selects_introspection_fields(&operation.selection_set.node, &document.fragments)
+}
+
+fn select_operation<'a>(
+    document: &'a ExecutableDocument,
+    operation_name: Option<&str>,
+) -> Result<&'a OperationDefinition, IntrospectionQueryError> {
+    match (&document.operations, operation_name) {
+        (DocumentOperations::Single(operation), _) => Ok(&operation.node),
+        (DocumentOperations::Multiple(operations), Some(name)) => operations
+            .get(name)
+            .map(|operation| &operation.node)
+            .ok_or_else(|| IntrospectionQueryError::UnknownOperation(name.to_string())),
+        (DocumentOperations::Multiple(operations), None) => {
+            if operations.len() == 1 {
+                Ok(&operations.values().next().expect("no operation").node)
+            } else {
+                Err(IntrospectionQueryError::AmbiguousOperation)
+            }
+        }
+    }
+}
+
+/// An object in the introspection schema, such as a `__Type` or `__Field`.
+#[derive(Debug, Clone, Copy)]
+enum Node<'a> {
+    Query,
+    Schema,
+    Type(TypeNode<'a>),
+    Field(&'a FieldDefinition),
+    InputValue(&'a InputValueDefinition),
+    EnumValue(&'a EnumValueDefinition),
+    Directive(&'a DirectiveDefinition),
+}
+
+/// A `__Type`: either a named type, or a non-null or list wrapper around another type.
+#[derive(Debug, Clone, Copy)]
+enum TypeNode<'a> {
+    Named(&'a str),
+
+    /// The type is non-null, and its `ofType` is the same type but nullable.
+    NonNull(&'a Type),
+
+    /// The type is a nullable list, with elements of the given type.
+    List(&'a Type),
+}
+
+impl<'a> TypeNode<'a> {
+    fn of(ty: &'a Type) -> Self {
+        if ty.nullable {
+            Self::of_nullable(ty)
+        } else {
+            Self::NonNull(ty)
+        }
+    }
+
+    fn of_nullable(ty: &'a Type) -> Self {
+        match &ty.base {
+            BaseType::Named(name) => Self::Named(name.as_str()),
+            BaseType::List(inner) => Self::List(inner),
+        }
+    }
+}
+
+/// The value of a field of an introspection object, before its subfields are selected.
+enum Resolved<'a> {
+    Value(JsonValue),
+    Node(Option<Node<'a>>),
+    Nodes(Option<Vec<Node<'a>>>),
+}
+
+struct Executor<'a> {
+    schema: &'a Schema,
+    fragments: &'a HashMap<Name, Positioned<FragmentDefinition>>,
+    variables: BTreeMap<Name, ConstValue>,
+}
+
+impl<'a> Executor<'a> {
+    fn execute_selection_set(
+        &self,
+        node: Node<'a>,
+        selection_set: &SelectionSet,
+        output: &mut Map<String, JsonValue>,
+    ) -> Result<(), IntrospectionQueryError> {
+        let typename = self.typename(node);
+        for selection in &selection_set.items {
+            match &selection.node {
+                Selection::Field(field) => {
+                    let field = &field.node;
+                    if !self.is_included(&field.directives)? {
+                        continue;
+                    }
+
+                    let field_name = field.name.node.as_str();
+                    let value = if field_name == TYPENAME_FIELD {
+                        JsonValue::String(typename.to_string())
+                    } else {
+                        let arguments = self.evaluate_arguments(typename, field_name, field)?;
+                        let selection_set = &field.selection_set.node;
+                        match self.resolve(node, field_name, &arguments)? {
+                            Resolved::Value(value) => value,
+                            Resolved::Node(None) | Resolved::Nodes(None) => JsonValue::Null,
+                            Resolved::Node(Some(inner)) => self.complete(inner, selection_set)?,
+                            Resolved::Nodes(Some(inner)) => JsonValue::Array(
+                                inner
+                                    .into_iter()
+                                    .map(|inner| self.complete(inner, selection_set))
+                                    .try_collect()?,
+                            ),
+                        }
+                    };
+
+                    let key = field.alias.as_ref().unwrap_or(&field.name).node.to_string();
+                    merge_value(output, key, value);
+                }
+                Selection::FragmentSpread(spread) => {
+                    if !self.is_included(&spread.node.directives)? {
+                        continue;
+                    }
+                    let fragment_name = &spread.node.fragment_name.node;
+                    let fragment = self.fragments.get(fragment_name).ok_or_else(|| {
+                        IntrospectionQueryError::UnknownFragment(fragment_name.to_string())
+                    })?;
+                    if fragment.node.type_condition.node.on.node.as_str() == typename {
+                        self.execute_selection_set(
+                            node,
+                            &fragment.node.selection_set.node,
+                            output,
+                        )?;
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    if !self.is_included(&fragment.node.directives)? {
+                        continue;
+                    }
+                    let applies = fragment
+                        .node
+                        .type_condition
+                        .as_ref()
+                        .is_none_or(|condition| condition.node.on.node.as_str() == typename);
+                    if applies {
+                        self.execute_selection_set(
+                            node,
+                            &fragment.node.selection_set.node,
+                            output,
+                        )?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn complete(
+        &self,
+        node: Node<'a>,
+        selection_set: &SelectionSet,
+    ) -> Result<JsonValue, IntrospectionQueryError> {
+        let mut output = Map::new();
+        self.execute_selection_set(node, selection_set, &mut output)?;
+        Ok(JsonValue::Object(output))
+    }
+
+    fn typename(&self, node: Node<'a>) -> &'a str {
+        match node {
+            Node::Query => self.schema.query_type_name(),
+            Node::Schema => "__Schema",
+            Node::Type(_) => "__Type",
+            Node::Field(_) => "__Field",
+            Node::InputValue(_) => "__InputValue",
+            Node::EnumValue(_) => "__EnumValue",
+            Node::Directive(_) => "__Directive",
+        }
+    }
+
+    /// Evaluate the `@skip` and `@include` directives, if present.
+    fn is_included(
+        &self,
+        directives: &[Positioned<Directive>],
+    ) -> Result<bool, IntrospectionQueryError> {
+        for directive in directives {
+            let directive_name = directive.node.name.node.as_str();
+            let expected = match directive_name {
+                "skip" => false,
+                "include" => true,
+                _ => continue,
+            };
+            let condition = match directive.node.get_argument("if") {
+                Some(value) => self.evaluate(&value.node)?,
+                None => ConstValue::Null,
+            };
+            match condition {
+                ConstValue::Boolean(condition) if condition != expected => return Ok(false),
+                ConstValue::Boolean(_) => {}
+                other => {
+                    return Err(IntrospectionQueryError::InvalidArgument(
+                        format!("@{directive_name}"),
+                        "if".to_string(),
+                        format!("expected a boolean but got {other}"),
+                    ))
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    fn evaluate(&self, value: &Value) -> Result<ConstValue, IntrospectionQueryError> {
+        value.clone().into_const_with(|name| {
+            self.variables
+                .get(&name)
+                .cloned()
+                .ok_or_else(|| IntrospectionQueryError::MissingVariable(name.to_string()))
+        })
+    }
+
+    fn evaluate_arguments(
+        &self,
+        typename: &str,
+        field_name: &str,
+        field: &async_graphql_parser::types::Field,
+    ) -> Result<FieldArguments, IntrospectionQueryError> {
+        let mut values = BTreeMap::new();
+        for (name, value) in &field.arguments {
+            values.insert(name.node.to_string(), self.evaluate(&value.node)?);
+        }
+        Ok(FieldArguments {
+            field: format!("{typename}.{field_name}"),
+            values,
+        })
+    }
+
+    fn resolve(
+        &self,
+        node: Node<'a>,
+        field_name: &str,
+        arguments: &FieldArguments,
+    ) -> Result<Resolved<'a>, IntrospectionQueryError> {
+        let schema = self.schema;
+        let resolved = match (node, field_name) {
+            (Node::Query, "__schema") => Resolved::Node(Some(Node::Schema)),
+            (Node::Query, "__type") => {
+                let name = arguments.required_string("name")?;
+                Resolved::Node(
+                    self.named_type(&name)
+                        .map(|name| Node::Type(TypeNode::Named(name))),
+                )
+            }
+
+            (Node::Schema, "description") => Resolved::Value(JsonValue::Null),
+            (Node::Schema, "types") => Resolved::Nodes(Some(
+                self.named_types()
+                    .map(|name| Node::Type(TypeNode::Named(name)))
+                    .collect(),
+            )),
+            (Node::Schema, "queryType") => {
+                Resolved::Node(Some(Node::Type(TypeNode::Named(schema.query_type_name()))))
+            }
+            (Node::Schema, "mutationType" | "subscriptionType") => Resolved::Node(None),
+            (Node::Schema, "directives") => Resolved::Nodes(Some(
+                schema
+                    .directives
+                    .values()
+                    .sorted_by_key(|defn| defn.name.node.as_str())
+                    .map(Node::Directive)
+                    .collect(),
+            )),
+
+            (Node::Type(type_node), _) => {
+                self.resolve_type_field(type_node, field_name, arguments)?
+            }
+
+            (Node::Field(defn), "name") => Resolved::Value(defn.name.node.as_str().into()),
+            (Node::Field(defn), "description") => Resolved::Value(description(&defn.description)),
+            (Node::Field(defn), "args") => Resolved::Nodes(Some(
+                defn.arguments
+                    .iter()
+                    .map(|arg| Node::InputValue(&arg.node))
+                    .collect(),
+            )),
+            (Node::Field(defn), "type") => {
+                Resolved::Node(Some(Node::Type(TypeNode::of(&defn.ty.node))))
+            }
+            (Node::Field(defn), "isDeprecated") => {
+                Resolved::Value(get_deprecation_reason(defn).is_some().into())
+            }
+            (Node::Field(defn), "deprecationReason") => {
+                Resolved::Value(get_deprecation_reason(defn).into())
+            }
+
+            (Node::InputValue(defn), "name") => Resolved::Value(defn.name.node.as_str().into()),
+            (Node::InputValue(defn), "description") => {
+                Resolved::Value(description(&defn.description))
+            }
+            (Node::InputValue(defn), "type") => {
+                Resolved::Node(Some(Node::Type(TypeNode::of(&defn.ty.node))))
+            }
+            (Node::InputValue(defn), "defaultValue") => Resolved::Value(
+                defn.default_value
+                    .as_ref()
+                    .map(|value| value.node.to_string())
+                    .into(),
+            ),
+            (Node::InputValue(_), "isDeprecated") => Resolved::Value(false.into()),
+            (Node::InputValue(_), "deprecationReason") => Resolved::Value(JsonValue::Null),
+
+            (Node::EnumValue(defn), "name") => Resolved::Value(defn.value.node.as_str().into()),
+            (Node::EnumValue(defn), "description") => {
+                Resolved::Value(description(&defn.description))
+            }
+            (Node::EnumValue(defn), "isDeprecated") => {
+                Resolved::Value(enum_value_deprecation_reason(defn).is_some().into())
+            }
+            (Node::EnumValue(defn), "deprecationReason") => {
+                Resolved::Value(enum_value_deprecation_reason(defn).into())
+            }
+
+            (Node::Directive(defn), "name") => Resolved::Value(defn.name.node.as_str().into()),
+            (Node::Directive(defn), "description") => {
+                Resolved::Value(description(&defn.description))
+            }
+            (Node::Directive(defn), "locations") => Resolved::Value(
+                defn.locations
+                    .iter()
+                    .map(|location| directive_location_name(&location.node))
+                    .collect(),
+            ),
+            (Node::Directive(defn), "args") => Resolved::Nodes(Some(
+                defn.arguments
+                    .iter()
+                    .map(|arg| Node::InputValue(&arg.node))
+                    .collect(),
+            )),
+            (Node::Directive(_), "isRepeatable") => Resolved::Value(false.into()),
+
+            _ => {
+                return Err(IntrospectionQueryError::UnknownField(
+                    self.typename(node).to_string(),
+                    field_name.to_string(),
+                ))
+            }
+        };
+        Ok(resolved)
+    }
+
+    fn resolve_type_field(
+        &self,
+        type_node: TypeNode<'a>,
+        field_name: &str,
+        arguments: &FieldArguments,
+    ) -> Result<Resolved<'a>, IntrospectionQueryError> {
+        let schema = self.schema;
+        let named = match type_node {
+            TypeNode::Named(name) => Some((name, self.type_definition(name))),
+            TypeNode::NonNull(_) | TypeNode::List(_) => None,
+        };
+        let vertex_type = named.and_then(|(_, defn)| match defn.map(|d| &d.kind) {
+            Some(TypeKind::Object(_) | TypeKind::Interface(_)) => defn,
+            _ => None,
+        });
+
+        let resolved = match field_name {
+            "kind" => Resolved::Value(
+                match (type_node, named.and_then(|(_, defn)| defn).map(|d| &d.kind)) {
+                    (TypeNode::NonNull(_), _) => "NON_NULL",
+                    (TypeNode::List(_), _) => "LIST",
+                    (_, Some(TypeKind::Object(_))) => "OBJECT",
+                    (_, Some(TypeKind::Interface(_))) => "INTERFACE",
+                    (_, Some(TypeKind::Enum(_))) => "ENUM",
+                    _ => "SCALAR",
+                }
+                .into(),
+            ),
+            "name" => Resolved::Value(named.map(|(name, _)| name).into()),
+            "description" => Resolved::Value(
+                named
+                    .and_then(|(_, defn)| defn)
+                    .map_or(JsonValue::Null, |defn| description(&defn.description)),
+            ),
+            "fields" => {
+                let include_deprecated = arguments.include_deprecated()?;
+                Resolved::Nodes(vertex_type.map(|defn| {
+                    get_vertex_type_fields(defn)
+                        .iter()
+                        .filter(|field| {
+                            include_deprecated || get_deprecation_reason(&field.node).is_none()
+                        })
+                        .map(|field| Node::Field(&field.node))
+                        .collect()
+                }))
+            }
+            "interfaces" => Resolved::Nodes(vertex_type.map(|defn| {
+                get_vertex_type_implements(defn)
+                    .iter()
+                    .map(|name| Node::Type(TypeNode::Named(name.node.as_str())))
+                    .collect()
+            })),
+            "possibleTypes" => Resolved::Nodes(match vertex_type.map(|defn| &defn.kind) {
+                Some(TypeKind::Interface(_)) => {
+                    let interface_name = named.expect("interface has no name").0;
+                    Some(
+                        schema
+                            .vertex_types
+                            .iter()
+                            .filter(|(_, defn)| {
+                                matches!(defn.kind, TypeKind::Object(_))
+                                    && get_vertex_type_implements(defn)
+                                        .iter()
+                                        .any(|name| name.node.as_str() == interface_name)
+                            })
+                            .map(|(name, _)| Node::Type(TypeNode::Named(name.as_ref())))
+                            .sorted_by_key(|node| match node {
+                                Node::Type(TypeNode::Named(name)) => *name,
+                                _ => unreachable!(),
+                            })
+                            .collect(),
+                    )
+                }
+                _ => None,
+            }),
+            "enumValues" => {
+                let include_deprecated = arguments.include_deprecated()?;
+                Resolved::Nodes(match named.and_then(|(_, defn)| defn).map(|d| &d.kind) {
+                    Some(TypeKind::Enum(enum_type)) => Some(
+                        enum_type
+                            .values
+                            .iter()
+                            .filter(|value| {
+                                include_deprecated
+                                    || enum_value_deprecation_reason(&value.node).is_none()
+                            })
+                            .map(|value| Node::EnumValue(&value.node))
+                            .collect(),
+                    ),
+                    _ => None,
+                })
+            }
+            "inputFields" => Resolved::Nodes(None),
+            "ofType" => Resolved::Node(match type_node {
+                TypeNode::Named(_) => None,
+                TypeNode::NonNull(ty) => Some(Node::Type(TypeNode::of_nullable(ty))),
+                TypeNode::List(inner) => Some(Node::Type(TypeNode::of(inner))),
+            }),
+            "specifiedByURL" | "specifiedByUrl" | "isOneOf" => Resolved::Value(JsonValue::Null),
+            _ => {
+                return Err(IntrospectionQueryError::UnknownField(
+                    "__Type".to_string(),
+                    field_name.to_string(),
+                ))
+            }
+        };
+        Ok(resolved)
+    }
+
+    /// The definition of the named type, or `None` for built-in scalars.
+    fn type_definition(&self, name: &str) -> Option<&'a TypeDefinition> {
+        let schema = self.schema;
+        schema
+            .vertex_types
+            .get(name)
+            .or_else(|| schema.enums.get(name))
+            .or_else(|| schema.scalars.get(name))
+    }
+
+    /// The name of the type, if the schema defines it or it's a built-in scalar.
+    fn named_type(&self, name: &str) -> Option<&'a str> {
+        self.named_types().find(|type_name| *type_name == name)
+    }
+
+    /// The names of all the types in the schema, including built-in scalars, in sorted order.
+    fn named_types(&self) -> impl Iterator<Item = &'a str> {
+        let schema = self.schema;
+        schema
+            .vertex_types
+            .keys()
+            .chain(schema.enums.keys())
+            .chain(schema.scalars.keys())
+            .map(|name| name.as_ref())
+            .chain(BUILTIN_SCALARS.iter().copied())
+            .sorted_unstable()
+            .dedup()
+    }
+}
+
+/// The argument values of a field in an introspection query.
+struct FieldArguments {
+    field: String,
+    values: BTreeMap<String, ConstValue>,
+}
+
+impl FieldArguments {
+    fn invalid(&self, argument: &str, message: String) -> IntrospectionQueryError {
+        IntrospectionQueryError::InvalidArgument(self.field.clone(), argument.to_string(), message)
+    }
+
+    fn required_string(&self, argument: &str) -> Result<String, IntrospectionQueryError> {
+        match self.values.get(argument) {
+            Some(ConstValue::String(value)) => Ok(value.clone()),
+            Some(other) => {
+                Err(self.invalid(argument, format!("expected a string but got {other}")))
+            }
+            None => Err(self.invalid(argument, "this argument is required".to_string())),
+        }
+    }
+
+    fn include_deprecated(&self) -> Result<bool, IntrospectionQueryError> {
+        match self.values.get("includeDeprecated") {
+            None | Some(ConstValue::Null) => Ok(false),
+            Some(ConstValue::Boolean(value)) => Ok(*value),
+            Some(other) => Err(self.invalid(
+                "includeDeprecated",
+                format!("expected a boolean but got {other}"),
+            )),
+        }
+    }
+}
+
+fn description(description: &Option<Positioned<String>>) -> JsonValue {
+    description.as_ref().map(|d| d.node.as_str()).into()
+}
+
+fn enum_value_deprecation_reason(defn: &EnumValueDefinition) -> Option<&str> {
+    let directive = defn
+        .directives
+        .iter()
+        .find(|d| d.node.name.node.as_ref() == DEPRECATED_DIRECTIVE)?;
+    match directive
+        .node
+        .get_argument(DEPRECATED_REASON_ARGUMENT)
+        .map(|value| &value.node)
+    {
+        Some(ConstValue::String(reason)) => Some(reason.as_str()),
+        _ => Some(DEFAULT_DEPRECATION_REASON),
+    }
+}
+
+/// Add the value to the output object, merging it with any value already selected
+/// under the same key, as happens when fragments select overlapping fields.
+fn merge_value(output: &mut Map<String, JsonValue>, key: String, value: JsonValue) {
+    match (output.get_mut(&key), value) {
+        (Some(JsonValue::Object(existing)), JsonValue::Object(new)) => {
+            for (key, value) in new {
+                merge_value(existing, key, value);
+            }
+        }
+        (Some(JsonValue::Array(existing)), JsonValue::Array(new))
+            if existing.len() == new.len() =>
+        {
+            for (existing, new) in existing.iter_mut().zip(new) {
+                match (existing, new) {
+                    (JsonValue::Object(existing), JsonValue::Object(new)) => {
+                        for (key, value) in new {
+                            merge_value(existing, key, value);
+                        }
+                    }
+                    (existing, new) => *existing = new,
+                }
+            }
+        }
+        (_, value) => {
+            output.insert(key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::{json, Map, Value};
+
+    use crate::schema::{error::IntrospectionQueryError, Schema};
+
+    use super::is_introspection_query;
+
+    /// The introspection query sent by GraphiQL and by `graphql-js`'s `getIntrospectionQuery()`.
+    const STANDARD_INTROSPECTION_QUERY: &str = r#"
+query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types {
+      ...FullType
+    }
+    directives {
+      name
+      description
+      locations
+      args {
+        ...InputValue
+      }
+    }
+  }
+}
+
+fragment FullType on __Type {
+  kind
+  name
+  description
+  fields(includeDeprecated: true) {
+    name
+    description
+    args {
+      ...InputValue
+    }
+    type {
+      ...TypeRef
+    }
+    isDeprecated
+    deprecationReason
+  }
+  inputFields {
+    ...InputValue
+  }
+  interfaces {
+    ...TypeRef
+  }
+  enumValues(includeDeprecated: true) {
+    name
+    description
+    isDeprecated
+    deprecationReason
+  }
+  possibleTypes {
+    ...TypeRef
+  }
+}
+
+fragment InputValue on __InputValue {
+  name
+  description
+  type { ...TypeRef }
+  defaultValue
+}
+
+fragment TypeRef on __Type {
+  kind
+  name
+  ofType {
+    kind
+    name
+    ofType {
+      kind
+      name
+      ofType {
+        kind
+        name
+      }
+    }
+  }
+}
+"#;
+
+    fn numbers_schema() -> Schema {
+        Schema::parse(fs::read_to_string("test_data/schemas/numbers.graphql").unwrap()).unwrap()
+    }
+
+    fn find<'a>(values: &'a Value, name: &str) -> &'a Value {
+        values
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|value| value["name"] == name)
+            .unwrap_or_else(|| panic!("{name} not found"))
+    }
+
+    #[test]
+    fn standard_introspection_query() {
+        let schema = numbers_schema();
+        let result = schema
+            .execute_introspection_query(STANDARD_INTROSPECTION_QUERY, None, &Map::new())
+            .unwrap();
+        let introspected = &result["__schema"];
+
+        assert_eq!(
+            json!({"name": "RootSchemaQuery"}),
+            introspected["queryType"]
+        );
+        assert_eq!(Value::Null, introspected["mutationType"]);
+
+        let types = &introspected["types"];
+        for scalar in ["Boolean", "Float", "ID", "Int", "String"] {
+            assert_eq!("SCALAR", find(types, scalar)["kind"]);
+        }
+
+        let number = find(types, "Number");
+        assert_eq!("INTERFACE", number["kind"]);
+        assert_eq!(
+            json!([
+                {"kind": "OBJECT", "name": "Composite", "ofType": null},
+                {"kind": "OBJECT", "name": "Neither", "ofType": null},
+                {"kind": "OBJECT", "name": "Prime", "ofType": null},
+            ]),
+            number["possibleTypes"]
+        );
+
+        let prime = find(types, "Prime");
+        assert_eq!("OBJECT", prime["kind"]);
+        assert_eq!(Value::Null, prime["possibleTypes"]);
+        assert_eq!(Value::Null, prime["enumValues"]);
+        let successor = find(&prime["fields"], "successor");
+        assert_eq!(
+            json!({
+                "kind": "NON_NULL",
+                "name": null,
+                "ofType": {"kind": "INTERFACE", "name": "Number", "ofType": null},
+            }),
+            successor["type"]
+        );
+        assert_eq!(false, successor["isDeprecated"]);
+
+        let root_edge = find(&find(types, "RootSchemaQuery")["fields"], "Number");
+        let min = find(&root_edge["args"], "min");
+        assert_eq!("0", min["defaultValue"]);
+        assert_eq!(
+            json!({
+                "kind": "LIST",
+                "name": null,
+                "ofType": {
+                    "kind": "NON_NULL",
+                    "name": null,
+                    "ofType": {"kind": "INTERFACE", "name": "Number", "ofType": null},
+                },
+            }),
+            root_edge["type"]
+        );
+
+        let filter = find(&introspected["directives"], "filter");
+        assert_eq!(json!(["FIELD", "INLINE_FRAGMENT"]), filter["locations"]);
+    }
+
+    #[test]
+    fn type_lookup_with_variables_and_aliases() {
+        let schema = numbers_schema();
+        let query = r#"
+query ($name: String!) {
+    kind: __type(name: $name) {
+        __typename
+        name
+        kind
+        interfaces { name }
+    }
+    missing: __type(name: "NoSuchType") { name }
+    __typename
+}"#;
+        let variables = json!({"name": "Prime"}).as_object().unwrap().clone();
+        let result = schema
+            .execute_introspection_query(query, None, &variables)
+            .unwrap();
+        assert_eq!(
+            json!({
+                "kind": {
+                    "__typename": "__Type",
+                    "name": "Prime",
+                    "kind": "OBJECT",
+                    "interfaces": [{"name": "Number"}, {"name": "Named"}],
+                },
+                "missing": null,
+                "__typename": "RootSchemaQuery",
+            }),
+            result
+        );
+    }
+
+    #[test]
+    fn deprecated_fields_and_enum_values() {
+        let schema = Schema::parse(
+            fs::read_to_string("test_data/tests/valid_schemas/deprecated_fields.graphql").unwrap(),
+        )
+        .unwrap();
+        let query = r#"
+{
+    __schema {
+        types {
+            name
+            all: fields(includeDeprecated: true) { name deprecationReason }
+            current: fields { name }
+        }
+    }
+}"#;
+        let result = schema
+            .execute_introspection_query(query, None, &Map::new())
+            .unwrap();
+        let types = &result["__schema"]["types"];
+        for vertex_type in types.as_array().unwrap() {
+            let Some(all_fields) = vertex_type["all"].as_array() else {
+                continue;
+            };
+            let current_fields: Vec<_> = vertex_type["current"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|field| &field["name"])
+                .collect();
+            for field in all_fields {
+                assert_eq!(
+                    field["deprecationReason"].is_null(),
+                    current_fields.contains(&&field["name"]),
+                    "{field}"
+                );
+            }
+        }
+        assert!(types
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|t| t["all"].as_array().map(Vec::len) > t["current"].as_array().map(Vec::len)));
+    }
+
+    #[test]
+    fn invalid_introspection_queries() {
+        let schema = numbers_schema();
+        let execute = |query: &str| schema.execute_introspection_query(query, None, &Map::new());
+
+        assert_eq!(
+            Err(IntrospectionQueryError::UnknownField(
+                "RootSchemaQuery".to_string(),
+                "Number".to_string()
+            )),
+            execute("{ Number(max: 3) { value } }"),
+        );
+        assert_eq!(
+            Err(IntrospectionQueryError::UnknownField(
+                "__Type".to_string(),
+                "value".to_string()
+            )),
+            execute(r#"{ __type(name: "Prime") { value } }"#),
+        );
+        assert_eq!(
+            Err(IntrospectionQueryError::MissingVariable("name".to_string())),
+            execute(r#"{ __type(name: $name) { name } }"#),
+        );
+        assert_eq!(
+            Err(IntrospectionQueryError::UnknownFragment(
+                "Missing".to_string()
+            )),
+            execute(r#"{ __schema { ...Missing } }"#),
+        );
+        assert_eq!(
+            Err(IntrospectionQueryError::AmbiguousOperation),
+            execute(r#"query A { __typename } query B { __typename }"#),
+        );
+        assert!(matches!(
+            execute("{ __schema"),
+            Err(IntrospectionQueryError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn detects_introspection_queries() {
+        assert!(is_introspection_query(STANDARD_INTROSPECTION_QUERY, None));
+        assert!(is_introspection_query(
+            r#"{ __type(name: "Prime") { name } }"#,
+            None
+        ));
+        assert!(!is_introspection_query(
+            r#"{ Number(max: 3) { value @output } }"#,
+            None
+        ));
+        assert!(!is_introspection_query(
+            r#"{ __typename Number(max: 3) { value @output } }"#,
+            None
+        ));
+        assert!(!is_introspection_query("{ __schema", None));
+    }
+}
@@ -0,0 +1,165 @@
+use std::{collections::HashSet, sync::Arc};
+
+use async_graphql_parser::types::{
+    BaseType, FieldDefinition, Type as GraphQLType, TypeDefinition, TypeKind,
+};
+use async_graphql_value::Name;
+
+use crate::ir::Type;
+
+use super::BUILTIN_SCALARS;
+
+/// Shared allocations for the names and types in a schema.
+///
+/// The schema's type, property, and edge names are interned while it's built, as are
+/// the names within the types of its fields and parameters. Lowering a query then looks
+/// names up here instead of allocating them again, so the IR of every query against
+/// the schema shares the schema's allocations. Besides saving memory, this makes comparing
+/// the names cheap: `Arc<str>` equality checks whether the pointers are equal first.
+///
+/// The interner only grows while the schema is built: looking up a name the schema
+/// doesn't define, such as a misspelled one in a query, allocates it without interning it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Interner {
+    names: HashSet<Arc<str>>,
+    type_names: HashSet<Name>,
+}
+
+impl Interner {
+    pub(crate) fn new() -> Self {
+        let mut interner = Self::default();
+        for scalar in BUILTIN_SCALARS.iter() {
+            interner.intern_name(scalar);
+            interner.intern_type_name(scalar);
+        }
+        interner
+    }
+
+    pub(crate) fn intern_name(&mut self, name: &str) -> Arc<str> {
+        match self.names.get(name) {
+            Some(interned) => interned.clone(),
+            None => {
+                let interned: Arc<str> = Arc::from(name);
+                self.names.insert(interned.clone());
+                interned
+            }
+        }
+    }
+
+    fn intern_type_name(&mut self, name: &str) -> Name {
+        match self.type_names.get(name) {
+            Some(interned) => interned.clone(),
+            None => {
+                let interned = Name::new(name);
+                self.type_names.insert(interned.clone());
+                interned
+            }
+        }
+    }
+
+    fn intern_type(&mut self, ty: &mut GraphQLType) {
+        match &mut ty.base {
+            BaseType::Named(name) => {
+                self.intern_name(name);
+                *name = self.intern_type_name(name);
+            }
+            BaseType::List(inner) => self.intern_type(inner),
+        }
+    }
+
+    /// Intern the names of the type's fields' parameters, and the names within the types
+    /// of its fields and their parameters.
+    pub(crate) fn intern_definition(&mut self, definition: &mut TypeDefinition) {
+        let fields = match &mut definition.kind {
+            TypeKind::Object(object) => &mut object.fields,
+            TypeKind::Interface(interface) => &mut interface.fields,
+            _ => return,
+        };
+        for field in fields {
+            let FieldDefinition { ty, arguments, .. } = &mut field.node;
+            self.intern_type(&mut ty.node);
+            for argument in arguments {
+                self.intern_name(&argument.node.name.node);
+                self.intern_type(&mut argument.node.ty.node);
+            }
+        }
+    }
+
+    /// The interned name, or a new allocation of it if the schema doesn't define it.
+    pub(crate) fn name(&self, name: &str) -> Arc<str> {
+        self.names
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| Arc::from(name))
+    }
+
+    /// The non-null type of the named scalar, vertex type, or enum, with the interned name.
+    pub(crate) fn named_type(&self, name: &str) -> Type {
+        Type::named(self.name(name), false)
+    }
+
+    /// The type of a field or parameter in the schema, with the interned name.
+    pub(crate) fn ir_type(&self, ty: &GraphQLType) -> Type {
+        match &ty.base {
+            BaseType::Named(name) => Type::named(self.name(name), ty.nullable),
+            BaseType::List(element) => Type::list_of(&self.ir_type(element), ty.nullable),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, sync::Arc};
+
+    use crate::{frontend::parse_to_ir, schema::Schema};
+
+    #[test]
+    fn queries_share_the_schema_allocations() {
+        let schema =
+            Schema::parse(fs::read_to_string("test_data/schemas/numbers.graphql").unwrap())
+                .unwrap();
+        let query = r#"
+{
+    Number(max: 3) {
+        ... on Prime {
+            name @output @filter(op: "has_prefix", value: ["$prefix"])
+
+            successor {
+                successor_value: value @output
+            }
+        }
+    }
+}"#;
+        let first = parse_to_ir(&schema, query).unwrap();
+        let second = parse_to_ir(&schema, query).unwrap();
+
+        assert!(Arc::ptr_eq(&first.root_name, &second.root_name));
+        let (schema_type_name, _) = schema.vertex_types.get_key_value("Prime").unwrap();
+        for query in [&first, &second] {
+            let vertex = query.root_component.vertices.values().next().unwrap();
+            assert!(Arc::ptr_eq(schema_type_name, &vertex.type_name));
+            let coerced_from = vertex.coerced_from_type.as_ref().unwrap();
+            assert!(Arc::ptr_eq(&schema.interner.name("Number"), coerced_from));
+        }
+
+        // Fields of the same name on different types share an allocation, as do the names
+        // within their types and the types of variables inferred from them.
+        let name_output = &first.root_component.outputs["name"];
+        let value_output = &second.root_component.outputs["successor_value"];
+        for (type_name, output) in [("Prime", name_output), ("Number", value_output)] {
+            let key = (Arc::from(type_name), output.field_name.clone());
+            let ((_, field_name), _) = schema.fields.get_key_value(&key).unwrap();
+            assert!(Arc::ptr_eq(field_name, &output.field_name));
+        }
+        assert_eq!(
+            name_output.field_type.base_type().as_ptr(),
+            first.variables["prefix"].base_type().as_ptr(),
+        );
+
+        // Names the schema doesn't define aren't interned.
+        assert!(!Arc::ptr_eq(
+            &schema.interner.name("Missing"),
+            &schema.interner.name("Missing")
+        ));
+    }
+}
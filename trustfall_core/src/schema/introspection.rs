@@ -0,0 +1,587 @@
+use std::sync::Arc;
+
+use async_graphql_parser::types::{
+    BaseType, FieldDefinition, InputValueDefinition, Type, TypeDefinition, TypeKind,
+};
+
+use crate::ir::{types::get_base_named_type, FieldCost, FieldValue};
+
+use super::{
+    get_deprecation_reason, get_field_cost, get_field_restriction, get_inverse_edge_name,
+    get_parameter_constraints, get_vertex_type_fields, get_vertex_type_implements,
+    ParameterConstraints, Schema,
+};
+
+impl Schema {
+    /// The root query type, whose edges are the starting points of every query.
+    pub fn root_type(&self) -> VertexTypeInfo<'_> {
+        self.vertex_type(self.query_type_name())
+            .expect("root query type is not defined")
+    }
+
+    /// The edges at which queries may begin, in schema declaration order.
+    pub fn root_edges(&self) -> impl Iterator<Item = EdgeInfo<'_>> {
+        self.root_type().edges()
+    }
+
+    /// Look up a vertex type (object type or interface) by name.
+    pub fn vertex_type(&self, name: &str) -> Option<VertexTypeInfo<'_>> {
+        let (name, defn) = self.vertex_types.get_key_value(name)?;
+        Some(VertexTypeInfo {
+            schema: self,
+            name,
+            defn,
+        })
+    }
+
+    /// Iterate through all vertex types other than the root query type, sorted by name.
+    pub fn vertex_types(&self) -> impl Iterator<Item = VertexTypeInfo<'_>> {
+        let query_type_name = self.query_type_name();
+        let mut names: Vec<_> = self
+            .vertex_types
+            .keys()
+            .filter(|name| name.as_ref() != query_type_name)
+            .collect();
+        names.sort_unstable();
+        names
+            .into_iter()
+            .map(|name| self.vertex_type(name).expect("vertex type not found"))
+    }
+
+    /// Iterate through the names of the custom scalar types declared in the schema,
+    /// sorted by name. Built-in scalars like `Int` and `String` are not included.
+    pub fn scalar_types(&self) -> impl Iterator<Item = &str> {
+        let mut names: Vec<_> = self.scalars.keys().map(|name| name.as_ref()).collect();
+        names.sort_unstable();
+        names.into_iter()
+    }
+
+    /// Iterate through the names of the enum types declared in the schema, sorted by name.
+    ///
+    /// Use [`Schema::enum_values`] to get the values of each enum.
+    pub fn enum_types(&self) -> impl Iterator<Item = &str> {
+        let mut names: Vec<_> = self.enums.keys().map(|name| name.as_ref()).collect();
+        names.sort_unstable();
+        names.into_iter()
+    }
+
+    /// Whether `maybe_subtype` is the same type as `parent_type`, or one of its subtypes.
+    ///
+    /// Returns `false` if either type is not a vertex type defined in the schema.
+    pub fn is_vertex_subtype(&self, parent_type: &str, maybe_subtype: &str) -> bool {
+        self.vertex_types.contains_key(parent_type)
+            && self.vertex_types.contains_key(maybe_subtype)
+            && self.is_named_type_subtype(parent_type, maybe_subtype)
+    }
+}
+
+/// A vertex type defined in the schema: either an object type or an interface.
+#[derive(Debug, Clone, Copy)]
+pub struct VertexTypeInfo<'a> {
+    schema: &'a Schema,
+    name: &'a Arc<str>,
+    defn: &'a TypeDefinition,
+}
+
+impl<'a> VertexTypeInfo<'a> {
+    pub fn name(&self) -> &'a str {
+        self.name.as_ref()
+    }
+
+    /// The description of this type from the schema's `"""docstring"""`, if any.
+    pub fn description(&self) -> Option<&'a str> {
+        self.defn.description.as_ref().map(|d| d.node.as_str())
+    }
+
+    pub fn is_interface(&self) -> bool {
+        matches!(self.defn.kind, TypeKind::Interface(_))
+    }
+
+    /// The interfaces this type implements, in declaration order.
+    ///
+    /// Schemas must declare implemented interfaces transitively, so this includes
+    /// every supertype of this type.
+    pub fn implements(&self) -> impl Iterator<Item = &'a str> {
+        get_vertex_type_implements(self.defn)
+            .iter()
+            .map(|name| name.node.as_ref())
+    }
+
+    /// The types that implement this type, sorted by name. Only interfaces have implementers.
+    pub fn implementers(&self) -> impl Iterator<Item = VertexTypeInfo<'a>> {
+        let schema = self.schema;
+        let name = self.name();
+        let mut implementers: Vec<_> = schema
+            .subtypes(name)
+            .expect("vertex type not found")
+            .filter(|subtype| *subtype != name)
+            .collect();
+        implementers.sort_unstable();
+        implementers
+            .into_iter()
+            .map(move |subtype| schema.vertex_type(subtype).expect("vertex type not found"))
+    }
+
+    /// Whether this type is the same type as `other`, or one of its subtypes.
+    pub fn is_subtype_of(&self, other: &str) -> bool {
+        self.schema.is_vertex_subtype(other, self.name())
+    }
+
+    /// The properties of this type, in declaration order.
+    pub fn properties(&self) -> impl Iterator<Item = PropertyInfo<'a>> {
+        let schema = self.schema;
+        get_vertex_type_fields(self.defn)
+            .iter()
+            .filter(move |field| !is_edge(schema, &field.node))
+            .map(|field| PropertyInfo { defn: &field.node })
+    }
+
+    /// Look up a property of this type by name.
+    ///
+    /// Returns `None` if the type has no such field, or if the field is an edge.
+    pub fn property(&self, name: &str) -> Option<PropertyInfo<'a>> {
+        self.properties().find(|property| property.name() == name)
+    }
+
+    /// The edges of this type, in declaration order.
+    pub fn edges(&self) -> impl Iterator<Item = EdgeInfo<'a>> {
+        let schema = self.schema;
+        let source_type = self.name;
+        get_vertex_type_fields(self.defn)
+            .iter()
+            .filter(move |field| is_edge(schema, &field.node))
+            .map(move |field| EdgeInfo {
+                schema,
+                source_type,
+                defn: &field.node,
+            })
+    }
+
+    /// Look up an edge of this type by name.
+    ///
+    /// Returns `None` if the type has no such field, or if the field is a property.
+    pub fn edge(&self, name: &str) -> Option<EdgeInfo<'a>> {
+        self.edges().find(|edge| edge.name() == name)
+    }
+}
+
+fn is_edge(schema: &Schema, field: &FieldDefinition) -> bool {
+    schema
+        .vertex_types
+        .contains_key(get_base_named_type(&field.ty.node))
+}
+
+/// A property of a vertex type.
+#[derive(Debug, Clone, Copy)]
+pub struct PropertyInfo<'a> {
+    defn: &'a FieldDefinition,
+}
+
+impl<'a> PropertyInfo<'a> {
+    pub fn name(&self) -> &'a str {
+        self.defn.name.node.as_ref()
+    }
+
+    /// The description of this property from the schema's `"""docstring"""`, if any.
+    pub fn description(&self) -> Option<&'a str> {
+        self.defn.description.as_ref().map(|d| d.node.as_str())
+    }
+
+    /// The type of the property's values, such as `String` or `[Int!]!`.
+    pub fn property_type(&self) -> &'a Type {
+        &self.defn.ty.node
+    }
+
+    /// If the property is marked `@deprecated`, the reason for its deprecation.
+    pub fn deprecation_reason(&self) -> Option<&'a str> {
+        get_deprecation_reason(self.defn)
+    }
+
+    /// The cost of this property, if the schema annotates it with `@cost`.
+    pub fn cost(&self) -> Option<FieldCost> {
+        get_field_cost(self.defn)
+    }
+
+    /// Whether this property is marked `@restricted`, making access to it subject to
+    /// the access policy used when executing queries.
+    pub fn is_restricted(&self) -> bool {
+        get_field_restriction(self.defn).is_some()
+    }
+
+    /// The scope of this property's `@restricted` directive, if any.
+    pub fn restriction_scope(&self) -> Option<&'a str> {
+        get_field_restriction(self.defn).and_then(|r| r.scope)
+    }
+}
+
+/// An edge from one vertex type to another.
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeInfo<'a> {
+    schema: &'a Schema,
+    source_type: &'a str,
+    defn: &'a FieldDefinition,
+}
+
+impl<'a> EdgeInfo<'a> {
+    pub fn name(&self) -> &'a str {
+        self.defn.name.node.as_ref()
+    }
+
+    /// The description of this edge from the schema's `"""docstring"""`, if any.
+    pub fn description(&self) -> Option<&'a str> {
+        self.defn.description.as_ref().map(|d| d.node.as_str())
+    }
+
+    /// The full type of the edge, such as `[Number!]` or `Number!`.
+    pub fn edge_type(&self) -> &'a Type {
+        &self.defn.ty.node
+    }
+
+    /// The vertex type at the other end of the edge.
+    pub fn target_type(&self) -> VertexTypeInfo<'a> {
+        self.schema
+            .vertex_type(get_base_named_type(self.edge_type()))
+            .expect("edge target type not found")
+    }
+
+    /// Whether the edge may point to more than one vertex.
+    pub fn is_list(&self) -> bool {
+        matches!(self.edge_type().base, BaseType::List(_))
+    }
+
+    /// The parameters of this edge, in declaration order.
+    pub fn parameters(&self) -> impl Iterator<Item = EdgeParameterInfo<'a>> {
+        self.defn
+            .arguments
+            .iter()
+            .map(|param| EdgeParameterInfo { defn: &param.node })
+    }
+
+    /// Look up a parameter of this edge by name.
+    pub fn parameter(&self, name: &str) -> Option<EdgeParameterInfo<'a>> {
+        self.parameters().find(|param| param.name() == name)
+    }
+
+    /// If the edge is marked `@deprecated`, the reason for its deprecation.
+    pub fn deprecation_reason(&self) -> Option<&'a str> {
+        get_deprecation_reason(self.defn)
+    }
+
+    /// The cost of this edge, if the schema annotates it with `@cost`.
+    pub fn cost(&self) -> Option<FieldCost> {
+        get_field_cost(self.defn)
+    }
+
+    /// Whether this edge is marked `@restricted`, making access to it subject to
+    /// the access policy used when executing queries.
+    pub fn is_restricted(&self) -> bool {
+        get_field_restriction(self.defn).is_some()
+    }
+
+    /// The scope of this edge's `@restricted` directive, if any.
+    pub fn restriction_scope(&self) -> Option<&'a str> {
+        get_field_restriction(self.defn).and_then(|r| r.scope)
+    }
+
+    /// The edge on the target type that traverses this edge in the opposite direction,
+    /// if either edge declares the other as its inverse with `@inverse(of: String)`.
+    pub fn inverse(&self) -> Option<EdgeInfo<'a>> {
+        let target = self.target_type();
+        if let Some(inverse_name) = get_inverse_edge_name(self.defn) {
+            return Some(target.edge(inverse_name).expect("inverse edge not found"));
+        }
+
+        target.edges().find(|edge| {
+            let edge_target = edge.target_type().name();
+            get_inverse_edge_name(edge.defn) == Some(self.name())
+                && (self
+                    .schema
+                    .is_named_type_subtype(self.source_type, edge_target)
+                    || self
+                        .schema
+                        .is_named_type_subtype(edge_target, self.source_type))
+        })
+    }
+}
+
+/// A parameter of an edge.
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeParameterInfo<'a> {
+    defn: &'a InputValueDefinition,
+}
+
+impl<'a> EdgeParameterInfo<'a> {
+    pub fn name(&self) -> &'a str {
+        self.defn.name.node.as_ref()
+    }
+
+    /// The description of this parameter from the schema's `"""docstring"""`, if any.
+    pub fn description(&self) -> Option<&'a str> {
+        self.defn.description.as_ref().map(|d| d.node.as_str())
+    }
+
+    pub fn parameter_type(&self) -> &'a Type {
+        &self.defn.ty.node
+    }
+
+    /// The value the parameter takes when a query does not specify one, if the schema
+    /// defines an explicit default.
+    ///
+    /// Nullable parameters without an explicit default implicitly default to `null`.
+    pub fn default_value(&self) -> Option<FieldValue> {
+        self.defn.default_value.as_ref().map(|value| {
+            FieldValue::try_from(value.node.clone()).expect("invalid default value in schema")
+        })
+    }
+
+    /// Whether queries must specify a value for this parameter.
+    pub fn is_required(&self) -> bool {
+        !self.defn.ty.node.nullable && self.defn.default_value.is_none()
+    }
+
+    /// The constraints that values of this parameter must satisfy, if the schema
+    /// annotates it with `@constraint`.
+    pub fn constraints(&self) -> Option<ParameterConstraints> {
+        get_parameter_constraints(self.defn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        ir::FieldValue,
+        schema::{ParameterConstraints, Schema},
+    };
+
+    fn numbers_schema() -> Schema {
+        Schema::parse(fs::read_to_string("test_data/schemas/numbers.graphql").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn root_edges_and_parameters() {
+        let schema = numbers_schema();
+        assert_eq!("RootSchemaQuery", schema.root_type().name());
+
+        let root_edges: Vec<_> = schema.root_edges().map(|edge| edge.name()).collect();
+        assert_eq!(
+            vec![
+                "Number",
+                "NumberImplicitNullDefault",
+                "Zero",
+                "One",
+                "Two",
+                "Four"
+            ],
+            root_edges
+        );
+
+        let number = schema.root_type().edge("Number").unwrap();
+        assert!(number.is_list());
+        assert_eq!("Number", number.target_type().name());
+
+        let params: Vec<_> = number
+            .parameters()
+            .map(|param| {
+                (
+                    param.name(),
+                    param.parameter_type().to_string(),
+                    param.default_value(),
+                    param.is_required(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("min", "Int!".to_string(), Some(FieldValue::Int64(0)), false),
+                ("max", "Int!".to_string(), None, true),
+            ],
+            params
+        );
+
+        let two = schema.root_type().edge("Two").unwrap();
+        assert!(!two.is_list());
+        assert!(two.parameters().next().is_none());
+    }
+
+    #[test]
+    fn vertex_type_properties_edges_and_subtypes() {
+        let schema = numbers_schema();
+        let vertex_types: Vec<_> = schema.vertex_types().map(|ty| ty.name()).collect();
+        assert_eq!(
+            vec!["Composite", "Letter", "Named", "Neither", "Number", "Prime"],
+            vertex_types
+        );
+
+        let number = schema.vertex_type("Number").unwrap();
+        assert!(number.is_interface());
+        assert_eq!(vec!["Named"], number.implements().collect::<Vec<_>>());
+
+        let properties: Vec<_> = number
+            .properties()
+            .map(|property| (property.name(), property.property_type().to_string()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("name", "String".to_string()),
+                ("value", "Int".to_string()),
+                ("vowelsInName", "[String]".to_string()),
+            ],
+            properties
+        );
+        assert!(number.property("successor").is_none());
+
+        let edges: Vec<_> = number
+            .edges()
+            .map(|edge| (edge.name(), edge.edge_type().to_string()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("predecessor", "Number".to_string()),
+                ("successor", "Number!".to_string()),
+                ("multiple", "[Composite!]".to_string()),
+            ],
+            edges
+        );
+        assert!(number.edge("value").is_none());
+
+        let implementers: Vec<_> = number.implementers().map(|ty| ty.name()).collect();
+        assert_eq!(vec!["Composite", "Neither", "Prime"], implementers);
+
+        let prime = schema.vertex_type("Prime").unwrap();
+        assert!(!prime.is_interface());
+        assert!(prime.implementers().next().is_none());
+        assert!(prime.is_subtype_of("Number"));
+        assert!(prime.is_subtype_of("Named"));
+        assert!(prime.is_subtype_of("Prime"));
+        assert!(!prime.is_subtype_of("Composite"));
+        assert!(!schema.is_vertex_subtype("Prime", "Number"));
+        assert!(!schema.is_vertex_subtype("Number", "NoSuchType"));
+    }
+
+    #[test]
+    fn descriptions() {
+        let schema = Schema::parse(
+            fs::read_to_string("test_data/tests/valid_schemas/documented_schema.graphql").unwrap(),
+        )
+        .unwrap();
+
+        let root_edge = schema.root_type().edge("Package").unwrap();
+        assert_eq!(Some("Look up a package by name."), root_edge.description());
+        assert_eq!(
+            Some("The exact name of the package."),
+            root_edge.parameter("name").unwrap().description()
+        );
+
+        let package = root_edge.target_type();
+        assert_eq!(
+            Some("A published package in the registry.\n\nPackages have one or more releases."),
+            package.description()
+        );
+        assert_eq!(
+            Some("The package's unique name."),
+            package.property("name").unwrap().description()
+        );
+        assert_eq!(
+            Some("Releases of this package, newest first."),
+            package.edge("releases").unwrap().description()
+        );
+
+        let release = package.edge("releases").unwrap().target_type();
+        assert_eq!(None, release.description());
+        assert_eq!(None, release.property("version").unwrap().description());
+    }
+
+    #[test]
+    fn inverse_edges() {
+        let schema = Schema::parse(
+            fs::read_to_string("test_data/tests/valid_schemas/inverse_edges.graphql").unwrap(),
+        )
+        .unwrap();
+        let inverse_of = |type_name: &str, edge_name: &str| {
+            let edge = schema
+                .vertex_type(type_name)
+                .unwrap()
+                .edge(edge_name)
+                .unwrap();
+            let inverse = edge.inverse()?;
+            assert_eq!(type_name, inverse.target_type().name());
+            Some(inverse.name())
+        };
+
+        // Inverses declared on both edges.
+        assert_eq!(Some("dependents"), inverse_of("Crate", "dependencies"));
+        assert_eq!(Some("dependencies"), inverse_of("Crate", "dependents"));
+
+        // Inverses declared on only one of the edges are visible from both.
+        assert_eq!(Some("crates"), inverse_of("Crate", "owners"));
+        assert_eq!(Some("owners"), inverse_of("User", "crates"));
+        assert_eq!(Some("crates"), inverse_of("Crate", "repository"));
+        assert_eq!(Some("repository"), inverse_of("Repository", "crates"));
+
+        assert_eq!(None, inverse_of("Crate", "related"));
+        assert_eq!(
+            None,
+            schema
+                .root_type()
+                .edge("Crate")
+                .unwrap()
+                .inverse()
+                .map(|e| e.name())
+        );
+    }
+
+    #[test]
+    fn parameter_constraints() {
+        let schema = Schema::parse(
+            fs::read_to_string("test_data/tests/valid_schemas/parameter_constraints.graphql")
+                .unwrap(),
+        )
+        .unwrap();
+        let versions = schema
+            .vertex_type("Package")
+            .unwrap()
+            .edge("versions")
+            .unwrap();
+
+        assert_eq!(
+            Some(ParameterConstraints {
+                min: Some(FieldValue::Int64(1)),
+                max: Some(FieldValue::Int64(100)),
+                ..Default::default()
+            }),
+            versions.parameter("limit").unwrap().constraints(),
+        );
+        assert_eq!(
+            Some(ParameterConstraints {
+                one_of: Some(vec![
+                    FieldValue::Enum("ASC".to_string()),
+                    FieldValue::Enum("DESC".to_string()),
+                ]),
+                ..Default::default()
+            }),
+            versions.parameter("order").unwrap().constraints(),
+        );
+
+        let package = schema.root_type().edge("Package").unwrap();
+        assert!(
+            package
+                .parameter("name")
+                .unwrap()
+                .constraints()
+                .unwrap()
+                .non_empty
+        );
+        assert_eq!(
+            None,
+            numbers_schema()
+                .root_type()
+                .edge("Number")
+                .unwrap()
+                .parameter("max")
+                .unwrap()
+                .constraints()
+        );
+    }
+}
@@ -0,0 +1,786 @@
+//! A JSON representation of schemas, for tools that produce or consume schemas
+//! programmatically instead of as GraphQL SDL text.
+//!
+//! Converting a [`Schema`] to [`SchemaJson`] and back produces an equivalent schema,
+//! and converting that schema to [`SchemaJson`] again produces an identical value.
+//! Definitions are sorted by name, except for fields, parameters, and enum values
+//! which keep their declaration order.
+use std::collections::{BTreeMap, HashSet};
+
+use async_graphql_parser::{
+    types::{
+        BaseType, ConstDirective, DirectiveDefinition, DirectiveLocation, EnumType,
+        EnumValueDefinition, FieldDefinition, InputValueDefinition, InterfaceType, ObjectType,
+        SchemaDefinition, ServiceDocument, Type, TypeDefinition, TypeKind, TypeSystemDefinition,
+    },
+    Pos, Positioned,
+};
+use async_graphql_value::{ConstValue, Name};
+use serde::{Deserialize, Serialize};
+
+use crate::ir::types::get_base_named_type;
+
+use super::{
+    constraints::{CONSTRAINT_DIRECTIVE, CONSTRAINT_ONE_OF_ARGUMENT},
+    error::InvalidSchemaError,
+    get_vertex_type_fields, get_vertex_type_implements, Schema, BUILTIN_SCALARS,
+};
+
+/// The version of the JSON schema format produced by this version of trustfall.
+///
+/// It is incremented whenever the format changes in a way that older versions
+/// of trustfall would not be able to read.
+pub const SCHEMA_JSON_VERSION: u64 = 1;
+
+/// The JSON representation of a [`Schema`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaJson {
+    pub version: u64,
+    pub query_type: String,
+    #[serde(default)]
+    pub directives: Vec<DirectiveDefinitionJson>,
+    #[serde(default)]
+    pub scalars: Vec<String>,
+    #[serde(default)]
+    pub enums: Vec<EnumJson>,
+    pub types: Vec<VertexTypeJson>,
+}
+
+/// The definition of a directive, such as `@filter`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirectiveDefinitionJson {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<ParameterJson>,
+
+    /// Where the directive may be used, such as `FIELD` or `INLINE_FRAGMENT`.
+    pub locations: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnumJson {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VertexTypeKind {
+    Object,
+    Interface,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VertexTypeJson {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub kind: VertexTypeKind,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub implements: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub directives: Vec<DirectiveJson>,
+    pub fields: Vec<FieldJson>,
+}
+
+/// A property or edge of a vertex type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldJson {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// The field's type in GraphQL syntax, such as `String` or `[Number!]!`.
+    #[serde(rename = "type")]
+    pub field_type: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<ParameterJson>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub directives: Vec<DirectiveJson>,
+}
+
+/// A parameter of an edge or directive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParameterJson {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// The parameter's type in GraphQL syntax, such as `Int!`.
+    #[serde(rename = "type")]
+    pub parameter_type: String,
+
+    /// The parameter's default value. Values of enum type are represented as strings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<ConstValue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub directives: Vec<DirectiveJson>,
+}
+
+/// A use of a directive, such as `@deprecated(reason: "...")` on a field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirectiveJson {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub arguments: BTreeMap<String, ConstValue>,
+}
+
+impl Schema {
+    /// Serialize the schema as JSON. See [`SchemaJson`] for the format.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&SchemaJson::from(self)).expect("failed to serialize schema")
+    }
+
+    /// Load a schema from the JSON representation produced by [`Schema::to_json`].
+    pub fn from_json(input: &str) -> Result<Self, InvalidSchemaError> {
+        let value: serde_json::Value = serde_json::from_str(input)
+            .map_err(|e| InvalidSchemaError::InvalidSchemaJson(e.to_string()))?;
+
+        // Check the version first, so that newer formats produce a clear error
+        // instead of an obscure deserialization failure.
+        match value.get("version").and_then(|v| v.as_u64()) {
+            Some(SCHEMA_JSON_VERSION) => {}
+            Some(version) => {
+                return Err(InvalidSchemaError::UnsupportedSchemaJsonVersion(
+                    version,
+                    SCHEMA_JSON_VERSION,
+                ))
+            }
+            None => {
+                return Err(InvalidSchemaError::InvalidSchemaJson(
+                    "missing or invalid \"version\" field".to_string(),
+                ))
+            }
+        }
+
+        let schema_json: SchemaJson = serde_json::from_value(value)
+            .map_err(|e| InvalidSchemaError::InvalidSchemaJson(e.to_string()))?;
+        Self::try_from(schema_json)
+    }
+}
+
+impl From<&Schema> for SchemaJson {
+    fn from(schema: &Schema) -> Self {
+        let mut directives: Vec<_> = schema
+            .directives
+            .values()
+            .map(|defn| DirectiveDefinitionJson {
+                name: defn.name.node.to_string(),
+                description: description_to_json(&defn.description),
+                parameters: defn
+                    .arguments
+                    .iter()
+                    .map(|param| parameter_to_json(&param.node))
+                    .collect(),
+                locations: defn
+                    .locations
+                    .iter()
+                    .map(|location| directive_location_name(&location.node).to_string())
+                    .collect(),
+            })
+            .collect();
+        directives.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+        let mut scalars: Vec<_> = schema.scalars.keys().map(|name| name.to_string()).collect();
+        scalars.sort_unstable();
+
+        let mut enums: Vec<_> = schema
+            .enums
+            .iter()
+            .map(|(name, defn)| match &defn.kind {
+                TypeKind::Enum(enum_type) => EnumJson {
+                    name: name.to_string(),
+                    description: description_to_json(&defn.description),
+                    values: enum_type
+                        .values
+                        .iter()
+                        .map(|value| value.node.value.node.to_string())
+                        .collect(),
+                },
+                _ => unreachable!(),
+            })
+            .collect();
+        enums.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+        let mut types: Vec<_> = schema
+            .vertex_types
+            .iter()
+            .map(|(name, defn)| VertexTypeJson {
+                name: name.to_string(),
+                description: description_to_json(&defn.description),
+                kind: match &defn.kind {
+                    TypeKind::Object(_) => VertexTypeKind::Object,
+                    TypeKind::Interface(_) => VertexTypeKind::Interface,
+                    _ => unreachable!(),
+                },
+                implements: get_vertex_type_implements(defn)
+                    .iter()
+                    .map(|name| name.node.to_string())
+                    .collect(),
+                directives: directives_to_json(&defn.directives),
+                fields: get_vertex_type_fields(defn)
+                    .iter()
+                    .map(|field| FieldJson {
+                        name: field.node.name.node.to_string(),
+                        description: description_to_json(&field.node.description),
+                        field_type: field.node.ty.node.to_string(),
+                        parameters: field
+                            .node
+                            .arguments
+                            .iter()
+                            .map(|param| parameter_to_json(&param.node))
+                            .collect(),
+                        directives: directives_to_json(&field.node.directives),
+                    })
+                    .collect(),
+            })
+            .collect();
+        types.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            version: SCHEMA_JSON_VERSION,
+            query_type: schema.query_type_name().to_string(),
+            directives,
+            scalars,
+            enums,
+            types,
+        }
+    }
+}
+
+impl TryFrom<SchemaJson> for Schema {
+    type Error = InvalidSchemaError;
+
+    fn try_from(value: SchemaJson) -> Result<Self, Self::Error> {
+        if value.version != SCHEMA_JSON_VERSION {
+            return Err(InvalidSchemaError::UnsupportedSchemaJsonVersion(
+                value.version,
+                SCHEMA_JSON_VERSION,
+            ));
+        }
+
+        let mut converter = JsonConverter {
+            enums: value.enums.iter().map(|e| e.name.as_str()).collect(),
+            errors: vec![],
+        };
+        let doc = converter.make_document(&value);
+        if converter.errors.is_empty() {
+            converter.check_type_references(&value);
+        }
+        if converter.errors.is_empty() {
+            Schema::new(doc)
+        } else {
+            Err(converter.errors.into())
+        }
+    }
+}
+
+fn parameter_to_json(param: &InputValueDefinition) -> ParameterJson {
+    ParameterJson {
+        name: param.name.node.to_string(),
+        description: description_to_json(&param.description),
+        parameter_type: param.ty.node.to_string(),
+        default: param.default_value.as_ref().map(|value| value.node.clone()),
+        directives: directives_to_json(&param.directives),
+    }
+}
+
+fn description_to_json(description: &Option<Positioned<String>>) -> Option<String> {
+    description.as_ref().map(|d| d.node.clone())
+}
+
+fn directives_to_json(directives: &[Positioned<ConstDirective>]) -> Vec<DirectiveJson> {
+    directives
+        .iter()
+        .map(|directive| DirectiveJson {
+            name: directive.node.name.node.to_string(),
+            arguments: directive
+                .node
+                .arguments
+                .iter()
+                .map(|(name, value)| (name.node.to_string(), value.node.clone()))
+                .collect(),
+        })
+        .collect()
+}
+
+const DIRECTIVE_LOCATIONS: &[(DirectiveLocation, &str)] = &[
+    (DirectiveLocation::Query, "QUERY"),
+    (DirectiveLocation::Mutation, "MUTATION"),
+    (DirectiveLocation::Subscription, "SUBSCRIPTION"),
+    (DirectiveLocation::Field, "FIELD"),
+    (DirectiveLocation::FragmentDefinition, "FRAGMENT_DEFINITION"),
+    (DirectiveLocation::FragmentSpread, "FRAGMENT_SPREAD"),
+    (DirectiveLocation::InlineFragment, "INLINE_FRAGMENT"),
+    (DirectiveLocation::Schema, "SCHEMA"),
+    (DirectiveLocation::Scalar, "SCALAR"),
+    (DirectiveLocation::Object, "OBJECT"),
+    (DirectiveLocation::FieldDefinition, "FIELD_DEFINITION"),
+    (DirectiveLocation::ArgumentDefinition, "ARGUMENT_DEFINITION"),
+    (DirectiveLocation::Interface, "INTERFACE"),
+    (DirectiveLocation::Union, "UNION"),
+    (DirectiveLocation::Enum, "ENUM"),
+    (DirectiveLocation::EnumValue, "ENUM_VALUE"),
+    (DirectiveLocation::InputObject, "INPUT_OBJECT"),
+    (
+        DirectiveLocation::InputFieldDefinition,
+        "INPUT_FIELD_DEFINITION",
+    ),
+    (DirectiveLocation::VariableDefinition, "VARIABLE_DEFINITION"),
+];
+
+pub(super) fn directive_location_name(location: &DirectiveLocation) -> &'static str {
+    DIRECTIVE_LOCATIONS
+        .iter()
+        .find_map(|(loc, name)| (loc == location).then_some(*name))
+        .expect("unknown directive location")
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c == '_' || c.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
+fn make_description(description: &Option<String>) -> Option<Positioned<String>> {
+    description.clone().map(positioned)
+}
+
+fn positioned<T>(node: T) -> Positioned<T> {
+    Positioned::new(node, Pos::default())
+}
+
+/// Converts [`SchemaJson`] into the document that [`Schema::new`] validates,
+/// catching the problems that the GraphQL parser would have caught for SDL input.
+struct JsonConverter<'a> {
+    enums: HashSet<&'a str>,
+    errors: Vec<InvalidSchemaError>,
+}
+
+impl<'a> JsonConverter<'a> {
+    fn error(&mut self, message: String) {
+        self.errors
+            .push(InvalidSchemaError::InvalidSchemaJson(message));
+    }
+
+    fn make_document(&mut self, value: &SchemaJson) -> ServiceDocument {
+        let mut definitions = vec![TypeSystemDefinition::Schema(positioned(SchemaDefinition {
+            extend: false,
+            directives: vec![],
+            query: Some(positioned(self.make_name(&value.query_type))),
+            mutation: None,
+            subscription: None,
+        }))];
+
+        let mut seen_directives: HashSet<&str> = Default::default();
+        for directive in &value.directives {
+            if !seen_directives.insert(&directive.name) {
+                self.error(format!(
+                    "directive \"{}\" is defined more than once",
+                    directive.name
+                ));
+                continue;
+            }
+
+            let locations = directive
+                .locations
+                .iter()
+                .filter_map(|location| {
+                    let found = DIRECTIVE_LOCATIONS
+                        .iter()
+                        .find_map(|(loc, name)| (*name == location).then_some(*loc));
+                    if found.is_none() {
+                        self.error(format!(
+                            "directive \"{}\" has unknown location \"{location}\"",
+                            directive.name
+                        ));
+                    }
+                    found.map(positioned)
+                })
+                .collect();
+            definitions.push(TypeSystemDefinition::Directive(positioned(
+                DirectiveDefinition {
+                    description: make_description(&directive.description),
+                    name: positioned(self.make_name(&directive.name)),
+                    arguments: self.make_parameters(&directive.parameters),
+                    locations,
+                },
+            )));
+        }
+
+        let mut seen_types: HashSet<&str> = Default::default();
+        let type_names = value
+            .scalars
+            .iter()
+            .chain(value.enums.iter().map(|e| &e.name))
+            .chain(value.types.iter().map(|t| &t.name));
+        for name in type_names {
+            if BUILTIN_SCALARS.contains(name.as_str()) {
+                self.error(format!(
+                    "type \"{name}\" conflicts with a built-in scalar type"
+                ));
+            } else if !seen_types.insert(name) {
+                self.error(format!("type \"{name}\" is defined more than once"));
+            }
+        }
+        if !value.types.iter().any(|t| t.name == value.query_type) {
+            self.error(format!(
+                "query type \"{}\" is not defined",
+                value.query_type
+            ));
+        }
+        if !self.errors.is_empty() {
+            // Duplicate or missing definitions would make the remaining conversion meaningless.
+            return ServiceDocument { definitions };
+        }
+
+        for scalar in &value.scalars {
+            definitions.push(self.make_type(scalar, &None, vec![], TypeKind::Scalar));
+        }
+
+        for enum_json in &value.enums {
+            let values = enum_json
+                .values
+                .iter()
+                .map(|value| {
+                    positioned(EnumValueDefinition {
+                        description: None,
+                        value: positioned(self.make_name(value)),
+                        directives: vec![],
+                    })
+                })
+                .collect();
+            definitions.push(self.make_type(
+                &enum_json.name,
+                &enum_json.description,
+                vec![],
+                TypeKind::Enum(EnumType { values }),
+            ));
+        }
+
+        for type_json in &value.types {
+            let implements = type_json
+                .implements
+                .iter()
+                .map(|name| positioned(self.make_name(name)))
+                .collect();
+            let fields = type_json
+                .fields
+                .iter()
+                .map(|field| {
+                    positioned(FieldDefinition {
+                        description: make_description(&field.description),
+                        name: positioned(self.make_name(&field.name)),
+                        arguments: self.make_parameters(&field.parameters),
+                        ty: positioned(self.make_type_ref(&field.field_type)),
+                        directives: self.make_directives(&field.directives),
+                    })
+                })
+                .collect();
+            let kind = match type_json.kind {
+                VertexTypeKind::Object => TypeKind::Object(ObjectType { implements, fields }),
+                VertexTypeKind::Interface => {
+                    TypeKind::Interface(InterfaceType { implements, fields })
+                }
+            };
+            let directives = self.make_directives(&type_json.directives);
+            definitions.push(self.make_type(
+                &type_json.name,
+                &type_json.description,
+                directives,
+                kind,
+            ));
+        }
+
+        ServiceDocument { definitions }
+    }
+
+    /// SDL schemas that use undefined types fail to parse, so check for them here too.
+    fn check_type_references(&mut self, value: &SchemaJson) {
+        let defined: HashSet<&str> = value
+            .scalars
+            .iter()
+            .map(String::as_str)
+            .chain(value.enums.iter().map(|e| e.name.as_str()))
+            .chain(value.types.iter().map(|t| t.name.as_str()))
+            .chain(BUILTIN_SCALARS.iter().copied())
+            .collect();
+
+        for vertex_type in &value.types {
+            for field in &vertex_type.fields {
+                let type_strs = std::iter::once(&field.field_type)
+                    .chain(field.parameters.iter().map(|param| &param.parameter_type));
+                for type_str in type_strs {
+                    let type_ = Type::new(type_str).expect("type was already validated");
+                    let type_name = get_base_named_type(&type_);
+                    if !defined.contains(type_name) {
+                        self.error(format!(
+                            "field \"{}\" on type \"{}\" refers to undefined type \"{type_name}\"",
+                            field.name, vertex_type.name,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    fn make_name(&mut self, name: &str) -> Name {
+        if !is_valid_name(name) {
+            self.error(format!("\"{name}\" is not a valid GraphQL name"));
+        }
+        Name::new(name)
+    }
+
+    fn make_type_ref(&mut self, type_str: &str) -> Type {
+        Type::new(type_str).unwrap_or_else(|| {
+            self.error(format!("\"{type_str}\" is not a valid GraphQL type"));
+            Type::new("String").unwrap()
+        })
+    }
+
+    fn make_type(
+        &mut self,
+        name: &str,
+        description: &Option<String>,
+        directives: Vec<Positioned<ConstDirective>>,
+        kind: TypeKind,
+    ) -> TypeSystemDefinition {
+        TypeSystemDefinition::Type(positioned(TypeDefinition {
+            extend: false,
+            description: make_description(description),
+            name: positioned(self.make_name(name)),
+            directives,
+            kind,
+        }))
+    }
+
+    fn make_parameters(
+        &mut self,
+        parameters: &[ParameterJson],
+    ) -> Vec<Positioned<InputValueDefinition>> {
+        parameters
+            .iter()
+            .map(|param| {
+                let ty = self.make_type_ref(&param.parameter_type);
+                let default_value = param
+                    .default
+                    .as_ref()
+                    .map(|value| positioned(self.restore_enum_values(&ty.base, value.clone())));
+                let mut directives = self.make_directives(&param.directives);
+                for directive in &mut directives {
+                    if directive.node.name.node != CONSTRAINT_DIRECTIVE {
+                        continue;
+                    }
+                    // The allowed values of a parameter are values of the parameter's type.
+                    let allowed_values_type = BaseType::List(Box::new(ty.clone()));
+                    for (name, value) in &mut directive.node.arguments {
+                        if name.node == CONSTRAINT_ONE_OF_ARGUMENT {
+                            value.node =
+                                self.restore_enum_values(&allowed_values_type, value.node.clone());
+                        }
+                    }
+                }
+                positioned(InputValueDefinition {
+                    description: make_description(&param.description),
+                    name: positioned(self.make_name(&param.name)),
+                    ty: positioned(ty),
+                    default_value,
+                    directives,
+                })
+            })
+            .collect()
+    }
+
+    fn make_directives(&mut self, directives: &[DirectiveJson]) -> Vec<Positioned<ConstDirective>> {
+        directives
+            .iter()
+            .map(|directive| {
+                positioned(ConstDirective {
+                    name: positioned(self.make_name(&directive.name)),
+                    arguments: directive
+                        .arguments
+                        .iter()
+                        .map(|(name, value)| {
+                            (positioned(self.make_name(name)), positioned(value.clone()))
+                        })
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    /// JSON represents enum values as strings, so turn them back into enum values
+    /// wherever the parameter's type says an enum is expected.
+    fn restore_enum_values(&self, base: &BaseType, value: ConstValue) -> ConstValue {
+        match (base, value) {
+            (BaseType::Named(name), ConstValue::String(s))
+                if self.enums.contains(name.as_str()) =>
+            {
+                ConstValue::Enum(Name::new(s))
+            }
+            (BaseType::List(inner), ConstValue::List(values)) => ConstValue::List(
+                values
+                    .into_iter()
+                    .map(|v| self.restore_enum_values(&inner.base, v))
+                    .collect(),
+            ),
+            (_, value) => value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use trustfall_filetests_macros::parameterize;
+
+    use crate::{
+        schema::{error::InvalidSchemaError, Schema},
+        util::DisplayVec,
+    };
+
+    use super::{SchemaJson, SCHEMA_JSON_VERSION};
+
+    fn assert_round_trips(schema: &Schema) {
+        let json = schema.to_json();
+        let loaded = Schema::from_json(&json).unwrap();
+        assert_eq!(json, loaded.to_json());
+        assert_eq!(schema.fingerprint(), loaded.fingerprint());
+    }
+
+    #[test]
+    fn test_schemas_round_trip() {
+        for entry in fs::read_dir("test_data/schemas").unwrap() {
+            let path = entry.unwrap().path();
+            let schema = Schema::parse(fs::read_to_string(path).unwrap()).unwrap();
+            assert_round_trips(&schema);
+        }
+    }
+
+    #[parameterize("trustfall_core/test_data/tests/valid_schemas", "*.graphql")]
+    fn valid_schemas_round_trip(base: &Path, stem: &str) {
+        let mut path = base.to_path_buf();
+        path.push(format!("{stem}.graphql"));
+        let schema = Schema::parse(fs::read_to_string(path).unwrap()).unwrap();
+        assert_round_trips(&schema);
+    }
+
+    #[test]
+    fn enum_defaults_are_restored() {
+        let schema_text =
+            fs::read_to_string("test_data/tests/valid_schemas/edge_parameter_defaults.graphql")
+                .unwrap();
+        let schema = Schema::parse(schema_text).unwrap();
+
+        let schema_json = SchemaJson::from(&schema);
+        let loaded = Schema::try_from(schema_json).unwrap();
+        let order = loaded
+            .vertex_type("Package")
+            .unwrap()
+            .edge("versions")
+            .unwrap()
+            .parameter("order")
+            .unwrap();
+        assert_eq!(
+            Some(crate::ir::FieldValue::Enum("DESC".into())),
+            order.default_value()
+        );
+    }
+
+    #[test]
+    fn descriptions_are_preserved() {
+        let schema = Schema::parse(
+            fs::read_to_string("test_data/tests/valid_schemas/documented_schema.graphql").unwrap(),
+        )
+        .unwrap();
+        let loaded = Schema::from_json(&schema.to_json()).unwrap();
+
+        let package = loaded.root_type().edge("Package").unwrap();
+        assert_eq!(Some("Look up a package by name."), package.description());
+        assert_eq!(
+            Some("The exact name of the package."),
+            package.parameter("name").unwrap().description()
+        );
+        assert_eq!(
+            Some("The package's unique name."),
+            package
+                .target_type()
+                .property("name")
+                .unwrap()
+                .description()
+        );
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let schema =
+            Schema::parse(fs::read_to_string("test_data/schemas/numbers.graphql").unwrap())
+                .unwrap();
+        let mut value: serde_json::Value = serde_json::from_str(&schema.to_json()).unwrap();
+        value["version"] = serde_json::json!(SCHEMA_JSON_VERSION + 1);
+
+        assert_eq!(
+            Err(InvalidSchemaError::UnsupportedSchemaJsonVersion(
+                SCHEMA_JSON_VERSION + 1,
+                SCHEMA_JSON_VERSION
+            )),
+            Schema::from_json(&value.to_string()).map(|_| ()),
+        );
+    }
+
+    #[test]
+    fn invalid_names_and_types() {
+        let input = r#"{
+            "version": 1,
+            "query_type": "RootSchemaQuery",
+            "types": [
+                {
+                    "name": "RootSchemaQuery",
+                    "kind": "object",
+                    "fields": [
+                        { "name": "Foo", "type": "[Foo!" },
+                        { "name": "not a name", "type": "Foo" }
+                    ]
+                },
+                { "name": "Foo", "kind": "object", "fields": [{ "name": "x", "type": "Int" }] },
+                { "name": "Foo", "kind": "object", "fields": [] },
+                { "name": "String", "kind": "object", "fields": [] }
+            ]
+        }"#;
+        assert_eq!(
+            Err(InvalidSchemaError::MultipleErrors(DisplayVec(vec![
+                InvalidSchemaError::InvalidSchemaJson(
+                    "type \"Foo\" is defined more than once".to_string()
+                ),
+                InvalidSchemaError::InvalidSchemaJson(
+                    "type \"String\" conflicts with a built-in scalar type".to_string()
+                ),
+            ]))),
+            Schema::from_json(input).map(|_| ()),
+        );
+
+        let input = input.replace(
+            r#"{ "name": "Foo", "kind": "object", "fields": [] },
+                { "name": "String", "kind": "object", "fields": [] }"#,
+            r#"{ "name": "Bar", "kind": "object", "fields": [] }"#,
+        );
+        assert_eq!(
+            Err(InvalidSchemaError::MultipleErrors(DisplayVec(vec![
+                InvalidSchemaError::InvalidSchemaJson(
+                    "\"[Foo!\" is not a valid GraphQL type".to_string()
+                ),
+                InvalidSchemaError::InvalidSchemaJson(
+                    "\"not a name\" is not a valid GraphQL name".to_string()
+                ),
+            ]))),
+            Schema::from_json(&input).map(|_| ()),
+        );
+    }
+}
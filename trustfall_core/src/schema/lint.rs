@@ -0,0 +1,250 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use super::Schema;
+
+/// A likely mistake in a schema that is nonetheless valid.
+///
+/// Lints are produced by [`Schema::lint`]. Each has a stable, machine-readable [`code`](Self::code)
+/// that tools can use to filter or suppress it.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, thiserror::Error)]
+pub enum SchemaLint {
+    #[error(
+        "Type \"{0}\" cannot be reached from any edge of the root query type, \
+        so no query can ever use it."
+    )]
+    UnreachableType(String),
+
+    #[error(
+        "Edge \"{1}\" on type \"{0}\" points to type \"{2}\", which has no properties. \
+        Queries using this edge can only output data from further edges."
+    )]
+    EdgeTargetWithoutProperties(String, String, String),
+
+    #[error("Interface \"{0}\" is not implemented by any type.")]
+    InterfaceWithoutImplementations(String),
+
+    #[error("Type \"{0}\" does not use PascalCase, unlike the convention for type names.")]
+    TypeNameNotPascalCase(String),
+
+    #[error("Field \"{1}\" on type \"{0}\" uses {2}, but most fields in the schema use {3}.")]
+    InconsistentFieldNaming(String, String, NamingStyle, NamingStyle),
+}
+
+impl SchemaLint {
+    /// The machine-readable code identifying this kind of lint.
+    pub fn code(&self) -> &'static str {
+        match self {
+            SchemaLint::UnreachableType(..) => "unreachable_type",
+            SchemaLint::EdgeTargetWithoutProperties(..) => "edge_target_without_properties",
+            SchemaLint::InterfaceWithoutImplementations(..) => "interface_without_implementations",
+            SchemaLint::TypeNameNotPascalCase(..) => "type_name_not_pascal_case",
+            SchemaLint::InconsistentFieldNaming(..) => "inconsistent_field_naming",
+        }
+    }
+}
+
+/// The way multi-word names are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum NamingStyle {
+    CamelCase,
+    SnakeCase,
+}
+
+impl std::fmt::Display for NamingStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NamingStyle::CamelCase => write!(f, "camelCase"),
+            NamingStyle::SnakeCase => write!(f, "snake_case"),
+        }
+    }
+}
+
+impl NamingStyle {
+    /// The style of a field name, or `None` if the name is a single word
+    /// and so fits either style.
+    fn of(name: &str) -> Option<Self> {
+        if name.contains('_') {
+            Some(NamingStyle::SnakeCase)
+        } else if name.chars().skip(1).any(|c| c.is_ascii_uppercase()) {
+            Some(NamingStyle::CamelCase)
+        } else {
+            None
+        }
+    }
+}
+
+fn is_pascal_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase()) && !name.contains('_')
+}
+
+impl Schema {
+    /// Analyze the schema for likely mistakes that do not make it invalid.
+    ///
+    /// Lints are reported in a deterministic order: grouped by kind, then sorted by type name.
+    /// Fields that interfaces require but implementations lack are not linted here,
+    /// since such schemas fail validation altogether.
+    pub fn lint(&self) -> Vec<SchemaLint> {
+        let mut lints = vec![];
+
+        let reachable = self.reachable_vertex_types();
+        for vertex_type in self.vertex_types() {
+            if !reachable.contains(vertex_type.name()) {
+                lints.push(SchemaLint::UnreachableType(vertex_type.name().to_string()));
+            }
+        }
+
+        for vertex_type in std::iter::once(self.root_type()).chain(self.vertex_types()) {
+            for edge in vertex_type.edges() {
+                let target = edge.target_type();
+                if target.properties().next().is_none() {
+                    lints.push(SchemaLint::EdgeTargetWithoutProperties(
+                        vertex_type.name().to_string(),
+                        edge.name().to_string(),
+                        target.name().to_string(),
+                    ));
+                }
+            }
+        }
+
+        for vertex_type in self.vertex_types() {
+            if vertex_type.is_interface() && vertex_type.implementers().next().is_none() {
+                lints.push(SchemaLint::InterfaceWithoutImplementations(
+                    vertex_type.name().to_string(),
+                ));
+            }
+        }
+
+        for vertex_type in self.vertex_types() {
+            if !is_pascal_case(vertex_type.name()) {
+                lints.push(SchemaLint::TypeNameNotPascalCase(
+                    vertex_type.name().to_string(),
+                ));
+            }
+        }
+
+        // The root query type's edges conventionally use PascalCase, so they don't count.
+        let field_styles: Vec<_> = self
+            .vertex_types()
+            .flat_map(|vertex_type| {
+                let properties = vertex_type.properties().map(|p| p.name());
+                let edges = vertex_type.edges().map(|e| e.name());
+                properties.chain(edges).filter_map(move |name| {
+                    Some((vertex_type.name(), name, NamingStyle::of(name)?))
+                })
+            })
+            .collect();
+        let snake_case_count = field_styles
+            .iter()
+            .filter(|(_, _, style)| *style == NamingStyle::SnakeCase)
+            .count();
+        let camel_case_count = field_styles.len() - snake_case_count;
+        let expected_style = match snake_case_count.cmp(&camel_case_count) {
+            std::cmp::Ordering::Less => Some(NamingStyle::CamelCase),
+            std::cmp::Ordering::Greater => Some(NamingStyle::SnakeCase),
+            std::cmp::Ordering::Equal => None,
+        };
+        if let Some(expected_style) = expected_style {
+            for (type_name, field_name, style) in field_styles {
+                if style != expected_style {
+                    lints.push(SchemaLint::InconsistentFieldNaming(
+                        type_name.to_string(),
+                        field_name.to_string(),
+                        style,
+                        expected_style,
+                    ));
+                }
+            }
+        }
+
+        lints
+    }
+
+    /// The vertex types that queries can reach: through edges from the root query type,
+    /// through type coercions, and as interfaces implemented by reachable types.
+    fn reachable_vertex_types(&self) -> BTreeSet<&str> {
+        let mut reachable: BTreeSet<&str> = Default::default();
+        let mut queue: VecDeque<&str> = self
+            .root_edges()
+            .map(|edge| edge.target_type().name())
+            .collect();
+
+        while let Some(type_name) = queue.pop_front() {
+            if !reachable.insert(type_name) {
+                continue;
+            }
+
+            let vertex_type = self.vertex_type(type_name).expect("vertex type not found");
+            queue.extend(vertex_type.edges().map(|edge| edge.target_type().name()));
+            queue.extend(vertex_type.implementers().map(|subtype| subtype.name()));
+            queue.extend(vertex_type.implements());
+        }
+
+        reachable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::schema::Schema;
+
+    use super::{NamingStyle, SchemaLint};
+
+    #[test]
+    fn test_schemas_are_lint_free() {
+        for schema_name in ["filesystem", "numbers", "nullables", "recurses"] {
+            let schema = Schema::parse(
+                fs::read_to_string(format!("test_data/schemas/{schema_name}.graphql")).unwrap(),
+            )
+            .unwrap();
+            assert_eq!(Vec::<SchemaLint>::new(), schema.lint(), "{schema_name}");
+        }
+    }
+
+    #[test]
+    fn lints_in_schema_with_mistakes() {
+        let schema = Schema::parse(
+            fs::read_to_string("test_data/tests/valid_schemas/schema_with_lints.graphql").unwrap(),
+        )
+        .unwrap();
+
+        let lints = schema.lint();
+        assert_eq!(
+            vec![
+                SchemaLint::UnreachableType("Orphan".to_string()),
+                SchemaLint::UnreachableType("Unused".to_string()),
+                SchemaLint::UnreachableType("lowercase_type".to_string()),
+                SchemaLint::EdgeTargetWithoutProperties(
+                    "Author".to_string(),
+                    "connections".to_string(),
+                    "Connections".to_string(),
+                ),
+                SchemaLint::InterfaceWithoutImplementations("Unused".to_string()),
+                SchemaLint::TypeNameNotPascalCase("lowercase_type".to_string()),
+                SchemaLint::InconsistentFieldNaming(
+                    "Author".to_string(),
+                    "birth_year".to_string(),
+                    NamingStyle::SnakeCase,
+                    NamingStyle::CamelCase,
+                ),
+            ],
+            lints
+        );
+        assert_eq!(
+            vec![
+                "unreachable_type",
+                "unreachable_type",
+                "unreachable_type",
+                "edge_target_without_properties",
+                "interface_without_implementations",
+                "type_name_not_pascal_case",
+                "inconsistent_field_naming",
+            ],
+            lints.iter().map(|lint| lint.code()).collect::<Vec<_>>()
+        );
+    }
+}
@@ -13,18 +13,42 @@ use async_graphql_parser::{
     },
     Positioned,
 };
+use async_graphql_value::ConstValue;
 
 pub use ::async_graphql_parser::Error;
 use async_graphql_value::Name;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
-use crate::ir::types::{get_base_named_type, is_argument_type_valid, is_scalar_only_subtype};
+use crate::ir::{
+    self,
+    types::{get_base_named_type, is_argument_type_valid, is_base_type_orderable},
+    FieldCost, FieldValue, Latency,
+};
 use crate::util::{BTreeMapTryInsertExt, HashMapTryInsertExt};
 
 use self::error::InvalidSchemaError;
-
+use self::interner::Interner;
+
+pub use self::compatibility::SchemaFingerprint;
+pub(crate) use self::constraints::get_parameter_constraints;
+pub use self::constraints::{ConstraintViolation, ParameterConstraints};
+pub use self::custom_scalar::CustomScalar;
+pub use self::introspection::{EdgeInfo, EdgeParameterInfo, PropertyInfo, VertexTypeInfo};
+pub use self::lint::{NamingStyle, SchemaLint};
+pub use self::renaming::SchemaRenaming;
+
+pub mod builder;
+mod compatibility;
+mod constraints;
+mod custom_scalar;
 pub mod error;
+pub mod graphql_introspection;
+mod interner;
+mod introspection;
+pub mod json;
+mod lint;
+mod renaming;
 
 #[derive(Debug, Clone)]
 pub struct Schema {
@@ -32,9 +56,12 @@ pub struct Schema {
     pub(crate) query_type: ObjectType,
     pub(crate) directives: HashMap<Arc<str>, DirectiveDefinition>,
     pub(crate) scalars: HashMap<Arc<str>, TypeDefinition>,
+    pub(crate) enums: HashMap<Arc<str>, TypeDefinition>,
     pub(crate) vertex_types: HashMap<Arc<str>, TypeDefinition>,
     pub(crate) fields: HashMap<(Arc<str>, Arc<str>), FieldDefinition>,
     pub(crate) field_origins: BTreeMap<(Arc<str>, Arc<str>), FieldOrigin>,
+    pub(crate) custom_scalars: BTreeMap<Arc<str>, CustomScalar>,
+    pub(crate) interner: Interner,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -82,6 +109,23 @@ lazy_static! {
 
 const RESERVED_PREFIX: &str = "__";
 
+const DEPRECATED_DIRECTIVE: &str = "deprecated";
+const DEPRECATED_REASON_ARGUMENT: &str = "reason";
+
+/// The reason reported for `@deprecated` fields that do not specify one.
+/// Matches the default value of the `reason` argument in the GraphQL spec.
+pub(crate) const DEFAULT_DEPRECATION_REASON: &str = "No longer supported";
+
+const RESTRICTED_DIRECTIVE: &str = "restricted";
+const RESTRICTED_SCOPE_ARGUMENT: &str = "scope";
+
+const INVERSE_DIRECTIVE: &str = "inverse";
+const INVERSE_OF_ARGUMENT: &str = "of";
+
+const COST_DIRECTIVE: &str = "cost";
+const COST_FANOUT_ARGUMENT: &str = "fanout";
+const COST_LATENCY_ARGUMENT: &str = "latency";
+
 impl Schema {
     pub const ALL_DIRECTIVE_DEFINITIONS: &'static str = "
 directive @filter(op: String!, value: [String!]) on FIELD | INLINE_FRAGMENT
@@ -102,6 +146,8 @@ directive @transform(op: String!) on FIELD
         let mut schema: Option<SchemaDefinition> = None;
         let mut directives: HashMap<Arc<str>, DirectiveDefinition> = Default::default();
         let mut scalars: HashMap<Arc<str>, TypeDefinition> = Default::default();
+        let mut enums: HashMap<Arc<str>, TypeDefinition> = Default::default();
+        let mut interner = Interner::new();
 
         // The schema is mostly type definitions, except for one schema definition, and
         // perhaps a small number of other definitions like custom scalars or directives.
@@ -128,8 +174,9 @@ directive @transform(op: String!) on FIELD
                         .unwrap();
                 }
                 TypeSystemDefinition::Type(t) => {
-                    let node = t.node;
-                    let type_name: Arc<str> = Arc::from(node.name.node.to_string());
+                    let mut node = t.node;
+                    interner.intern_definition(&mut node);
+                    let type_name = interner.intern_name(&node.name.node);
                     assert!(!BUILTIN_SCALARS.contains(type_name.as_ref()));
 
                     if node.extend {
@@ -147,7 +194,11 @@ directive @transform(op: String!) on FIELD
                                 .insert_or_error(type_name.clone(), node.clone())
                                 .unwrap();
                         }
-                        TypeKind::Enum(_) => unimplemented!(),
+                        TypeKind::Enum(_) => {
+                            enums
+                                .insert_or_error(type_name.clone(), node.clone())
+                                .unwrap();
+                        }
                         TypeKind::Union(_) => unimplemented!(),
                         TypeKind::InputObject(_) => unimplemented!(),
                     }
@@ -161,7 +212,7 @@ directive @transform(op: String!) on FIELD
                     if let Some(field_defs) = field_defs {
                         for field in field_defs {
                             let field_node = field.node;
-                            let field_name = Arc::from(field_node.name.node.to_string());
+                            let field_name = interner.intern_name(&field_node.name.node);
 
                             fields
                                 .insert_or_error((type_name.clone(), field_name), field_node)
@@ -202,14 +253,39 @@ directive @transform(op: String!) on FIELD
         if let Err(e) = check_ambiguous_field_origins(&fields, &field_origins) {
             errors.extend(e.into_iter());
         }
-        if let Err(e) =
-            check_type_and_property_and_edge_invariants(query_type_definition, &vertex_types)
-        {
+        if let Err(e) = check_type_and_property_and_edge_invariants(
+            query_type_definition,
+            &vertex_types,
+            &scalars,
+            &enums,
+        ) {
             errors.extend(e.into_iter());
         }
-        if let Err(e) =
-            check_root_query_type_invariants(query_type_definition, &query_type, &vertex_types)
-        {
+        if let Err(e) = check_root_query_type_invariants(
+            query_type_definition,
+            &query_type,
+            &vertex_types,
+            &scalars,
+            &enums,
+        ) {
+            errors.extend(e.into_iter());
+        }
+        if let Err(e) = check_deprecated_directives(&vertex_types) {
+            errors.extend(e.into_iter());
+        }
+        if let Err(e) = check_cost_directives(&vertex_types) {
+            errors.extend(e.into_iter());
+        }
+        if let Err(e) = check_restricted_directives(&vertex_types, &fields) {
+            errors.extend(e.into_iter());
+        }
+        if let Err(e) = check_inverse_directives(&vertex_types, &fields) {
+            errors.extend(e.into_iter());
+        }
+        if let Err(e) = constraints::check_constraint_directives(&vertex_types, &scalars, &enums) {
+            errors.extend(e.into_iter());
+        }
+        if let Err(e) = check_enum_definitions(&enums) {
             errors.extend(e.into_iter());
         }
         if errors.is_empty() {
@@ -218,9 +294,12 @@ directive @transform(op: String!) on FIELD
                 query_type,
                 directives,
                 scalars,
+                enums,
                 vertex_types,
                 fields,
                 field_origins,
+                custom_scalars: Default::default(),
+                interner,
             })
         } else {
             Err(errors.into())
@@ -250,6 +329,66 @@ directive @transform(op: String!) on FIELD
         }))
     }
 
+    /// Register hooks for parsing, validating, and comparing values of a custom scalar type.
+    ///
+    /// The scalar must have been declared in the schema with `scalar <name>`.
+    /// Registering hooks for the same scalar again replaces the previously-registered hooks.
+    pub fn register_custom_scalar(
+        &mut self,
+        name: &str,
+        scalar: CustomScalar,
+    ) -> Result<(), InvalidSchemaError> {
+        match self.scalars.get_key_value(name) {
+            Some((name, _)) => {
+                self.custom_scalars.insert(name.clone(), scalar);
+                Ok(())
+            }
+            None => Err(InvalidSchemaError::UndeclaredCustomScalar(name.to_string())),
+        }
+    }
+
+    /// Get the hooks registered for the named custom scalar type, if any.
+    pub fn custom_scalar(&self, name: &str) -> Option<&CustomScalar> {
+        self.custom_scalars.get(name)
+    }
+
+    /// If the named type is a defined enum, iterate through its values in declaration order.
+    /// Otherwise, return None.
+    pub fn enum_values(&self, enum_name: &str) -> Option<impl Iterator<Item = &str>> {
+        match &self.enums.get(enum_name)?.kind {
+            TypeKind::Enum(enum_type) => Some(
+                enum_type
+                    .values
+                    .iter()
+                    .map(|value| value.node.value.node.as_ref()),
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Whether the value is valid for an edge parameter of the given type.
+    ///
+    /// Unlike [`is_argument_type_valid`], this is aware of the schema's custom scalars and enums:
+    /// enum-typed parameters only accept the enum's declared values, and custom scalar parameters
+    /// accept any value since their representation is defined by the adapter.
+    pub(crate) fn is_parameter_value_valid(
+        &self,
+        parameter_type: &Type,
+        value: &FieldValue,
+    ) -> bool {
+        is_parameter_value_valid(&self.scalars, &self.enums, parameter_type, value)
+    }
+
+    /// Whether values of this type may be used with ordering filters like `<` and `>=`.
+    pub(crate) fn is_base_type_orderable(&self, operand_type: &ir::Type) -> bool {
+        is_base_type_orderable(operand_type)
+            || self
+                .custom_scalars
+                .get(operand_type.base_type())
+                .map(|scalar| scalar.is_orderable())
+                .unwrap_or(false)
+    }
+
     pub(crate) fn query_type_name(&self) -> &str {
         self.schema.query.as_ref().unwrap().node.as_ref()
     }
@@ -267,17 +406,510 @@ directive @transform(op: String!) on FIELD
     }
 }
 
+/// If the field is marked `@deprecated`, returns the reason for its deprecation.
+///
+/// Fields deprecated without an explicit reason use the [`DEFAULT_DEPRECATION_REASON`].
+pub(crate) fn get_deprecation_reason(field_defn: &FieldDefinition) -> Option<&str> {
+    let directive = field_defn
+        .directives
+        .iter()
+        .find(|d| d.node.name.node.as_ref() == DEPRECATED_DIRECTIVE)?;
+
+    match directive.node.get_argument(DEPRECATED_REASON_ARGUMENT) {
+        None => Some(DEFAULT_DEPRECATION_REASON),
+        Some(value) => match &value.node {
+            ConstValue::String(reason) => Some(reason.as_str()),
+            _ => unreachable!(
+                "non-string @deprecated reason on field {}, should have been caught by schema \
+                validation",
+                field_defn.name.node.as_ref(),
+            ),
+        },
+    }
+}
+
+/// If the field is marked `@restricted`, returns its restriction.
+pub(crate) fn get_field_restriction(field_defn: &FieldDefinition) -> Option<FieldRestriction<'_>> {
+    let directive = field_defn
+        .directives
+        .iter()
+        .find(|d| d.node.name.node.as_ref() == RESTRICTED_DIRECTIVE)?;
+
+    let scope = directive
+        .node
+        .get_argument(RESTRICTED_SCOPE_ARGUMENT)
+        .map(|value| match &value.node {
+            ConstValue::String(scope) => scope.as_str(),
+            _ => unreachable!(
+                "non-string @restricted scope on field {}, should have been caught by schema \
+                validation",
+                field_defn.name.node.as_ref(),
+            ),
+        });
+    Some(FieldRestriction { scope })
+}
+
+/// The restriction on a field marked `@restricted(scope: String)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FieldRestriction<'a> {
+    pub(crate) scope: Option<&'a str>,
+}
+
+fn check_restricted_directives(
+    vertex_types: &HashMap<Arc<str>, TypeDefinition>,
+    fields: &HashMap<(Arc<str>, Arc<str>), FieldDefinition>,
+) -> Result<(), Vec<InvalidSchemaError>> {
+    let mut errors: Vec<InvalidSchemaError> = vec![];
+
+    // Sorted, so that errors are reported in a deterministic order.
+    for (type_name, type_defn) in vertex_types.iter().sorted_by_key(|(name, _)| *name) {
+        for defn in get_vertex_type_fields(type_defn) {
+            let field_defn = &defn.node;
+            let restrictions = field_defn
+                .directives
+                .iter()
+                .filter(|d| d.node.name.node.as_ref() == RESTRICTED_DIRECTIVE)
+                .collect_vec();
+
+            if restrictions.len() > 1 {
+                errors.push(InvalidSchemaError::DuplicatedRestrictedDirective(
+                    type_name.to_string(),
+                    field_defn.name.node.to_string(),
+                ));
+            }
+
+            let mut has_valid_arguments = true;
+            for directive in restrictions {
+                for (arg_name, arg_value) in &directive.node.arguments {
+                    if arg_name.node.as_ref() != RESTRICTED_SCOPE_ARGUMENT {
+                        has_valid_arguments = false;
+                        errors.push(InvalidSchemaError::UnexpectedRestrictedDirectiveArgument(
+                            type_name.to_string(),
+                            field_defn.name.node.to_string(),
+                            arg_name.node.to_string(),
+                        ));
+                    } else if !matches!(arg_value.node, ConstValue::String(_)) {
+                        has_valid_arguments = false;
+                        errors.push(InvalidSchemaError::InvalidRestrictionScope(
+                            type_name.to_string(),
+                            field_defn.name.node.to_string(),
+                            arg_value.node.to_string(),
+                        ));
+                    }
+                }
+            }
+            if !has_valid_arguments {
+                continue;
+            }
+
+            for interface in get_vertex_type_implements(type_defn) {
+                let interface_name = interface.node.as_ref();
+                let Some(interface_field) = fields.get(&(
+                    Arc::from(interface_name),
+                    Arc::from(field_defn.name.node.as_ref()),
+                )) else {
+                    continue;
+                };
+
+                // The interface's own arguments are validated when checking the interface.
+                let interface_restriction = interface_field
+                    .directives
+                    .iter()
+                    .find(|d| d.node.name.node.as_ref() == RESTRICTED_DIRECTIVE)
+                    .map(|d| {
+                        d.node
+                            .get_argument(RESTRICTED_SCOPE_ARGUMENT)
+                            .map(|v| &v.node)
+                    });
+                let Some(interface_scope) = interface_restriction else {
+                    continue;
+                };
+                let own_scope = field_defn
+                    .directives
+                    .iter()
+                    .find(|d| d.node.name.node.as_ref() == RESTRICTED_DIRECTIVE)
+                    .map(|d| {
+                        d.node
+                            .get_argument(RESTRICTED_SCOPE_ARGUMENT)
+                            .map(|v| &v.node)
+                    });
+                if own_scope != Some(interface_scope) {
+                    errors.push(InvalidSchemaError::RestrictionNotInherited(
+                        type_name.to_string(),
+                        field_defn.name.node.to_string(),
+                        interface_name.to_string(),
+                    ));
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// If the edge is marked `@inverse(of: String)`, returns the name of its inverse edge,
+/// which is defined on the edge's destination type.
+pub(crate) fn get_inverse_edge_name(field_defn: &FieldDefinition) -> Option<&str> {
+    let directive = field_defn
+        .directives
+        .iter()
+        .find(|d| d.node.name.node.as_ref() == INVERSE_DIRECTIVE)?;
+
+    match &directive.node.get_argument(INVERSE_OF_ARGUMENT)?.node {
+        ConstValue::String(name) => Some(name.as_str()),
+        _ => unreachable!(
+            "non-string @inverse edge name on field {}, should have been caught by schema \
+            validation",
+            field_defn.name.node.as_ref(),
+        ),
+    }
+}
+
+fn check_inverse_directives(
+    vertex_types: &HashMap<Arc<str>, TypeDefinition>,
+    fields: &HashMap<(Arc<str>, Arc<str>), FieldDefinition>,
+) -> Result<(), Vec<InvalidSchemaError>> {
+    let mut errors: Vec<InvalidSchemaError> = vec![];
+
+    // Sorted, so that errors are reported in a deterministic order.
+    for (type_name, type_defn) in vertex_types.iter().sorted_by_key(|(name, _)| *name) {
+        for defn in get_vertex_type_fields(type_defn) {
+            let field_defn = &defn.node;
+            let field_name = field_defn.name.node.as_ref();
+            let inverses = field_defn
+                .directives
+                .iter()
+                .filter(|d| d.node.name.node.as_ref() == INVERSE_DIRECTIVE)
+                .collect_vec();
+            if inverses.is_empty() {
+                continue;
+            }
+
+            let target_type = get_base_named_type(&field_defn.ty.node);
+            if !vertex_types.contains_key(target_type) {
+                errors.push(InvalidSchemaError::InverseDirectiveOnProperty(
+                    type_name.to_string(),
+                    field_name.to_string(),
+                ));
+                continue;
+            }
+            if inverses.len() > 1 {
+                errors.push(InvalidSchemaError::DuplicatedInverseDirective(
+                    type_name.to_string(),
+                    field_name.to_string(),
+                ));
+            }
+
+            let mut has_valid_arguments = true;
+            for directive in &inverses {
+                if directive.node.get_argument(INVERSE_OF_ARGUMENT).is_none() {
+                    has_valid_arguments = false;
+                    errors.push(InvalidSchemaError::MissingInverseDirectiveArgument(
+                        type_name.to_string(),
+                        field_name.to_string(),
+                    ));
+                }
+                for (arg_name, arg_value) in &directive.node.arguments {
+                    if arg_name.node.as_ref() != INVERSE_OF_ARGUMENT {
+                        has_valid_arguments = false;
+                        errors.push(InvalidSchemaError::UnexpectedInverseDirectiveArgument(
+                            type_name.to_string(),
+                            field_name.to_string(),
+                            arg_name.node.to_string(),
+                        ));
+                    } else if !matches!(arg_value.node, ConstValue::String(_)) {
+                        has_valid_arguments = false;
+                        errors.push(InvalidSchemaError::InvalidInverseDirectiveArgument(
+                            type_name.to_string(),
+                            field_name.to_string(),
+                            arg_value.node.to_string(),
+                        ));
+                    }
+                }
+            }
+            if !has_valid_arguments {
+                continue;
+            }
+
+            let inverse_name = get_inverse_edge_name(field_defn).expect("no inverse edge name");
+            let Some(inverse_defn) = fields.get(&(Arc::from(target_type), Arc::from(inverse_name)))
+            else {
+                errors.push(InvalidSchemaError::InverseEdgeNotFound(
+                    type_name.to_string(),
+                    field_name.to_string(),
+                    target_type.to_string(),
+                    inverse_name.to_string(),
+                ));
+                continue;
+            };
+
+            // The inverse edge's vertices must be able to be of this edge's type.
+            let inverse_target_type = get_base_named_type(&inverse_defn.ty.node);
+            if !vertex_types.contains_key(inverse_target_type)
+                || !(is_named_type_subtype(vertex_types, type_name, inverse_target_type)
+                    || is_named_type_subtype(vertex_types, inverse_target_type, type_name))
+            {
+                errors.push(InvalidSchemaError::IncompatibleInverseEdge(
+                    type_name.to_string(),
+                    field_name.to_string(),
+                    target_type.to_string(),
+                    inverse_name.to_string(),
+                    inverse_target_type.to_string(),
+                ));
+                continue;
+            }
+
+            // If the inverse edge also declares its inverse, it must be this edge.
+            // Its own arguments are validated when checking its type.
+            let inverse_of_inverse = inverse_defn
+                .directives
+                .iter()
+                .find(|d| d.node.name.node.as_ref() == INVERSE_DIRECTIVE)
+                .and_then(|d| d.node.get_argument(INVERSE_OF_ARGUMENT))
+                .map(|value| &value.node);
+            if let Some(ConstValue::String(inverse_of_inverse)) = inverse_of_inverse {
+                if inverse_of_inverse != field_name {
+                    errors.push(InvalidSchemaError::InconsistentInverseEdges(
+                        type_name.to_string(),
+                        field_name.to_string(),
+                        target_type.to_string(),
+                        inverse_name.to_string(),
+                        inverse_of_inverse.to_string(),
+                    ));
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// If the field is annotated with `@cost`, returns its cost.
+pub(crate) fn get_field_cost(field_defn: &FieldDefinition) -> Option<FieldCost> {
+    let directive = field_defn
+        .directives
+        .iter()
+        .find(|d| d.node.name.node.as_ref() == COST_DIRECTIVE)?;
+
+    let fanout = directive
+        .node
+        .get_argument(COST_FANOUT_ARGUMENT)
+        .map(|value| parse_cost_fanout(&value.node).expect("invalid @cost fanout"));
+    let latency = directive
+        .node
+        .get_argument(COST_LATENCY_ARGUMENT)
+        .map(|value| parse_cost_latency(&value.node).expect("invalid @cost latency"));
+    Some(FieldCost { fanout, latency })
+}
+
+fn parse_cost_fanout(value: &ConstValue) -> Option<u64> {
+    match value {
+        ConstValue::Number(n) => n.as_u64(),
+        _ => None,
+    }
+}
+
+fn parse_cost_latency(value: &ConstValue) -> Option<Latency> {
+    match value {
+        ConstValue::String(s) => Latency::from_name(s),
+        _ => None,
+    }
+}
+
+fn check_cost_directives(
+    vertex_types: &HashMap<Arc<str>, TypeDefinition>,
+) -> Result<(), Vec<InvalidSchemaError>> {
+    let mut errors: Vec<InvalidSchemaError> = vec![];
+
+    // Sorted, so that errors are reported in a deterministic order.
+    for (type_name, type_defn) in vertex_types.iter().sorted_by_key(|(name, _)| *name) {
+        for defn in get_vertex_type_fields(type_defn) {
+            let field_defn = &defn.node;
+            let costs = field_defn
+                .directives
+                .iter()
+                .filter(|d| d.node.name.node.as_ref() == COST_DIRECTIVE)
+                .collect_vec();
+
+            if costs.len() > 1 {
+                errors.push(InvalidSchemaError::DuplicatedCostDirective(
+                    type_name.to_string(),
+                    field_defn.name.node.to_string(),
+                ));
+            }
+
+            for directive in costs {
+                for (arg_name, arg_value) in &directive.node.arguments {
+                    let is_valid = match arg_name.node.as_ref() {
+                        COST_FANOUT_ARGUMENT => parse_cost_fanout(&arg_value.node).is_some(),
+                        COST_LATENCY_ARGUMENT => parse_cost_latency(&arg_value.node).is_some(),
+                        _ => {
+                            errors.push(InvalidSchemaError::UnexpectedCostDirectiveArgument(
+                                type_name.to_string(),
+                                field_defn.name.node.to_string(),
+                                arg_name.node.to_string(),
+                            ));
+                            continue;
+                        }
+                    };
+                    if !is_valid {
+                        errors.push(InvalidSchemaError::InvalidCostDirectiveArgument(
+                            type_name.to_string(),
+                            field_defn.name.node.to_string(),
+                            arg_name.node.to_string(),
+                            arg_value.node.to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn check_deprecated_directives(
+    vertex_types: &HashMap<Arc<str>, TypeDefinition>,
+) -> Result<(), Vec<InvalidSchemaError>> {
+    let mut errors: Vec<InvalidSchemaError> = vec![];
+
+    for (type_name, type_defn) in vertex_types.iter().sorted_by_key(|(name, _)| *name) {
+        for defn in get_vertex_type_fields(type_defn) {
+            let field_defn = &defn.node;
+            let deprecations = field_defn
+                .directives
+                .iter()
+                .filter(|d| d.node.name.node.as_ref() == DEPRECATED_DIRECTIVE)
+                .collect_vec();
+
+            if deprecations.len() > 1 {
+                errors.push(InvalidSchemaError::DuplicatedDeprecatedDirective(
+                    type_name.to_string(),
+                    field_defn.name.node.to_string(),
+                ));
+            }
+
+            for directive in deprecations {
+                for (arg_name, arg_value) in &directive.node.arguments {
+                    if arg_name.node.as_ref() != DEPRECATED_REASON_ARGUMENT {
+                        errors.push(InvalidSchemaError::UnexpectedDeprecatedDirectiveArgument(
+                            type_name.to_string(),
+                            field_defn.name.node.to_string(),
+                            arg_name.node.to_string(),
+                        ));
+                    } else if !matches!(arg_value.node, ConstValue::String(_)) {
+                        errors.push(InvalidSchemaError::InvalidDeprecationReason(
+                            type_name.to_string(),
+                            field_defn.name.node.to_string(),
+                            arg_value.node.to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn is_parameter_value_valid(
+    scalars: &HashMap<Arc<str>, TypeDefinition>,
+    enums: &HashMap<Arc<str>, TypeDefinition>,
+    parameter_type: &Type,
+    value: &FieldValue,
+) -> bool {
+    match (&parameter_type.base, value) {
+        (_, FieldValue::Null) => parameter_type.nullable,
+        (BaseType::List(inner), FieldValue::List(values)) => values
+            .iter()
+            .all(|value| is_parameter_value_valid(scalars, enums, inner, value)),
+        (BaseType::List(_), _) => false,
+        (BaseType::Named(name), value) => {
+            if let Some(enum_defn) = enums.get(name.as_str()) {
+                let TypeKind::Enum(enum_type) = &enum_defn.kind else {
+                    unreachable!()
+                };
+                match value {
+                    FieldValue::Enum(variant) => enum_type
+                        .values
+                        .iter()
+                        .any(|x| x.node.value.node.as_str() == variant),
+                    _ => false,
+                }
+            } else if scalars.contains_key(name.as_str()) {
+                !matches!(value, FieldValue::List(_))
+            } else {
+                is_argument_type_valid(&parameter_type.into(), value)
+            }
+        }
+    }
+}
+
+fn check_enum_definitions(
+    enums: &HashMap<Arc<str>, TypeDefinition>,
+) -> Result<(), Vec<InvalidSchemaError>> {
+    let mut errors: Vec<InvalidSchemaError> = vec![];
+
+    for (enum_name, enum_defn) in enums {
+        if enum_name.as_ref().starts_with(RESERVED_PREFIX) {
+            errors.push(InvalidSchemaError::ReservedTypeName(enum_name.to_string()));
+        }
+
+        let enum_type = match &enum_defn.kind {
+            TypeKind::Enum(e) => e,
+            _ => unreachable!(),
+        };
+        let mut seen_values: BTreeSet<&str> = Default::default();
+        for value in &enum_type.values {
+            let value_name = value.node.value.node.as_ref();
+            if !seen_values.insert(value_name) {
+                errors.push(InvalidSchemaError::DuplicateEnumValue(
+                    enum_name.to_string(),
+                    value_name.to_string(),
+                ));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
 fn check_root_query_type_invariants(
     query_type_definition: &TypeDefinition,
     query_type: &ObjectType,
     vertex_types: &HashMap<Arc<str>, TypeDefinition>,
+    scalars: &HashMap<Arc<str>, TypeDefinition>,
+    enums: &HashMap<Arc<str>, TypeDefinition>,
 ) -> Result<(), Vec<InvalidSchemaError>> {
     let mut errors: Vec<InvalidSchemaError> = vec![];
 
     for field_defn in &query_type.fields {
         let field_type = &field_defn.node.ty.node;
         let base_named_type = get_base_named_type(field_type);
-        if BUILTIN_SCALARS.contains(base_named_type) {
+        if BUILTIN_SCALARS.contains(base_named_type)
+            || scalars.contains_key(base_named_type)
+            || enums.contains_key(base_named_type)
+        {
             errors.push(InvalidSchemaError::PropertyFieldOnRootQueryType(
                 query_type_definition.name.node.to_string(),
                 field_defn.node.name.node.to_string(),
@@ -300,6 +932,8 @@ fn check_root_query_type_invariants(
 fn check_type_and_property_and_edge_invariants(
     query_type_definition: &TypeDefinition,
     vertex_types: &HashMap<Arc<str>, TypeDefinition>,
+    scalars: &HashMap<Arc<str>, TypeDefinition>,
+    enums: &HashMap<Arc<str>, TypeDefinition>,
 ) -> Result<(), Vec<InvalidSchemaError>> {
     let mut errors: Vec<InvalidSchemaError> = vec![];
 
@@ -322,7 +956,10 @@ fn check_type_and_property_and_edge_invariants(
             }
 
             let base_named_type = get_base_named_type(field_type);
-            if BUILTIN_SCALARS.contains(base_named_type) {
+            if BUILTIN_SCALARS.contains(base_named_type)
+                || scalars.contains_key(base_named_type)
+                || enums.contains_key(base_named_type)
+            {
                 // We're looking at a property field.
                 if !field_defn.arguments.is_empty() {
                     errors.push(InvalidSchemaError::PropertyFieldWithParameters(
@@ -352,7 +989,8 @@ fn check_type_and_property_and_edge_invariants(
                             let param_type = &param_defn.node.ty.node;
                             match value.node.clone().try_into() {
                                 Ok(value) => {
-                                    if !is_argument_type_valid(param_type, &value) {
+                                    if !is_parameter_value_valid(scalars, enums, param_type, &value)
+                                    {
                                         errors.push(InvalidSchemaError::InvalidDefaultValueForFieldParameter(
                                             type_name.to_string(),
                                             field_defn.name.node.to_string(),
@@ -659,7 +1297,8 @@ fn check_field_type_narrowing(
                         if let Some(&parent_field_type) =
                             parent_field_parameters.get(field_parameter)
                         {
-                            if !is_scalar_only_subtype(field_type, parent_field_type) {
+                            let parent_field_type = ir::Type::from(parent_field_type);
+                            if !parent_field_type.is_scalar_only_subtype_of(&field_type.into()) {
                                 errors.push(InvalidSchemaError::InvalidTypeNarrowingOfInheritedFieldParameter(
                                     field_name.to_owned(),
                                     type_name.to_string(),
@@ -852,6 +1491,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn schema_enum_values() {
+        let input_data =
+            include_str!("../../test_data/tests/valid_schemas/enum_properties.graphql");
+        let schema = Schema::parse(input_data).expect("valid schema");
+
+        assert!(schema.enum_values("Nonexistent").is_none());
+        assert!(schema.enum_values("Shape").is_none());
+        assert_eq!(
+            vec!["RED", "GREEN", "BLUE"],
+            schema.enum_values("Color").unwrap().collect_vec()
+        );
+    }
+
     #[test]
     fn schema_subtypes() {
         let input_data = include_str!("../../test_data/schemas/numbers.graphql");
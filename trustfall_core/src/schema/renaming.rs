@@ -0,0 +1,284 @@
+//! Systematically renaming a schema's types and root query edges, so that schemas whose
+//! names would otherwise overlap can coexist, for example when stitching them together.
+//!
+//! Queries are written against the renamed schema, and executed with
+//! a [`RenamingAdapter`](crate::interpreter::renaming::RenamingAdapter) that translates
+//! the renamed names back into the original ones before delegating to the original adapter.
+use std::{collections::BTreeMap, sync::Arc};
+
+use async_graphql_parser::types::{BaseType, Type};
+
+use super::{
+    error::InvalidSchemaError,
+    json::{ParameterJson, SchemaJson},
+    Schema,
+};
+
+/// A renaming of the types and root query edges of a schema.
+///
+/// Enums are types too, and may be renamed. Scalars are never renamed,
+/// and neither are edges or properties other than the root query type's edges.
+/// Names that the renaming does not mention keep their original names.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaRenaming {
+    renamed_types: BTreeMap<Arc<str>, Arc<str>>,
+    original_types: BTreeMap<Arc<str>, Arc<str>>,
+    renamed_root_edges: BTreeMap<Arc<str>, Arc<str>>,
+    original_root_edges: BTreeMap<Arc<str>, Arc<str>>,
+}
+
+impl SchemaRenaming {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// The renaming that adds the given prefix to the names of all the schema's vertex types,
+    /// including the root query type, as well as to the names of all its enums and root edges.
+    pub fn with_prefix(schema: &Schema, prefix: &str) -> Self {
+        let type_names = schema
+            .vertex_types
+            .keys()
+            .chain(schema.enums.keys())
+            .cloned()
+            .collect::<Vec<_>>();
+        let root_edges = schema
+            .root_edges()
+            .map(|edge| Arc::<str>::from(edge.name()))
+            .collect::<Vec<_>>();
+
+        let mut renaming = Self::new();
+        for name in type_names {
+            renaming = renaming.rename_type(name.clone(), format!("{prefix}{name}"));
+        }
+        for name in root_edges {
+            renaming = renaming.rename_root_edge(name.clone(), format!("{prefix}{name}"));
+        }
+        renaming
+    }
+
+    /// Rename the vertex type or enum with the `original` name to `renamed`.
+    pub fn rename_type(
+        mut self,
+        original: impl Into<Arc<str>>,
+        renamed: impl Into<Arc<str>>,
+    ) -> Self {
+        let original = original.into();
+        let renamed = renamed.into();
+        if let Some(previous) = self.renamed_types.insert(original.clone(), renamed.clone()) {
+            self.original_types.remove(&previous);
+        }
+        self.original_types.insert(renamed, original);
+        self
+    }
+
+    /// Rename the root query type's edge with the `original` name to `renamed`.
+    pub fn rename_root_edge(
+        mut self,
+        original: impl Into<Arc<str>>,
+        renamed: impl Into<Arc<str>>,
+    ) -> Self {
+        let original = original.into();
+        let renamed = renamed.into();
+        if let Some(previous) = self
+            .renamed_root_edges
+            .insert(original.clone(), renamed.clone())
+        {
+            self.original_root_edges.remove(&previous);
+        }
+        self.original_root_edges.insert(renamed, original);
+        self
+    }
+
+    /// The renamed name of the type with the given original name.
+    pub fn renamed_type<'a>(&'a self, original: &'a str) -> &'a str {
+        self.renamed_types
+            .get(original)
+            .map(|name| name.as_ref())
+            .unwrap_or(original)
+    }
+
+    /// The original name of the type with the given renamed name.
+    pub fn original_type<'a>(&'a self, renamed: &'a str) -> &'a str {
+        self.original_types
+            .get(renamed)
+            .map(|name| name.as_ref())
+            .unwrap_or(renamed)
+    }
+
+    /// The renamed name of the root edge with the given original name.
+    pub fn renamed_root_edge<'a>(&'a self, original: &'a str) -> &'a str {
+        self.renamed_root_edges
+            .get(original)
+            .map(|name| name.as_ref())
+            .unwrap_or(original)
+    }
+
+    /// The original name of the root edge with the given renamed name.
+    pub fn original_root_edge<'a>(&'a self, renamed: &'a str) -> &'a str {
+        self.original_root_edges
+            .get(renamed)
+            .map(|name| name.as_ref())
+            .unwrap_or(renamed)
+    }
+
+    fn rename_type_reference(&self, type_: &str) -> String {
+        let type_ = Type::new(type_).expect("invalid type in schema");
+        self.rename_base_type(type_).to_string()
+    }
+
+    fn rename_base_type(&self, type_: Type) -> Type {
+        let base = match type_.base {
+            BaseType::Named(name) => BaseType::Named(async_graphql_value::Name::new(
+                self.renamed_type(name.as_str()),
+            )),
+            BaseType::List(inner) => BaseType::List(Box::new(self.rename_base_type(*inner))),
+        };
+        Type {
+            base,
+            nullable: type_.nullable,
+        }
+    }
+
+    fn rename_parameter(&self, parameter: &mut ParameterJson) {
+        parameter.parameter_type = self.rename_type_reference(&parameter.parameter_type);
+    }
+}
+
+impl Schema {
+    /// Produce a copy of this schema with its types and root edges renamed.
+    ///
+    /// Fails if the renamed schema is invalid, for example because two types were
+    /// given the same name, or because a type was renamed to the name of a built-in scalar.
+    pub fn rename(&self, renaming: &SchemaRenaming) -> Result<Schema, InvalidSchemaError> {
+        let mut schema = SchemaJson::from(self);
+        let root_type = schema.query_type.clone();
+
+        schema.query_type = renaming.renamed_type(&root_type).to_string();
+        for directive in &mut schema.directives {
+            directive
+                .parameters
+                .iter_mut()
+                .for_each(|param| renaming.rename_parameter(param));
+        }
+        for enum_json in &mut schema.enums {
+            enum_json.name = renaming.renamed_type(&enum_json.name).to_string();
+        }
+        for vertex_type in &mut schema.types {
+            let is_root_type = vertex_type.name == root_type;
+            vertex_type.name = renaming.renamed_type(&vertex_type.name).to_string();
+            for interface in &mut vertex_type.implements {
+                *interface = renaming.renamed_type(interface).to_string();
+            }
+            for field in &mut vertex_type.fields {
+                if is_root_type {
+                    field.name = renaming.renamed_root_edge(&field.name).to_string();
+                }
+                field.field_type = renaming.rename_type_reference(&field.field_type);
+                field
+                    .parameters
+                    .iter_mut()
+                    .for_each(|param| renaming.rename_parameter(param));
+            }
+        }
+
+        Schema::try_from(schema)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::schema::{error::InvalidSchemaError, Schema};
+
+    use super::SchemaRenaming;
+
+    fn load_schema(name: &str) -> Schema {
+        Schema::parse(fs::read_to_string(format!("test_data/schemas/{name}.graphql")).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn prefixing_renames_types_and_root_edges() {
+        let schema = load_schema("numbers");
+        let renaming = SchemaRenaming::with_prefix(&schema, "Num_");
+        let renamed = schema.rename(&renaming).unwrap();
+
+        assert_eq!("Num_RootSchemaQuery", renamed.query_type_name());
+        assert_eq!(
+            schema
+                .root_edges()
+                .map(|edge| format!("Num_{}", edge.name()))
+                .collect::<Vec<_>>(),
+            renamed
+                .root_edges()
+                .map(|edge| edge.name().to_string())
+                .collect::<Vec<_>>(),
+        );
+
+        let prime = renamed.vertex_type("Num_Prime").unwrap();
+        assert_eq!(
+            vec!["Num_Number", "Num_Named"],
+            prime.implements().collect::<Vec<_>>()
+        );
+        let successor = prime.edge("successor").unwrap();
+        assert_eq!("Num_Number!", successor.edge_type().to_string());
+        assert!(prime.property("value").is_some());
+        assert!(renamed.vertex_type("Prime").is_none());
+
+        assert_eq!("Prime", renaming.original_type("Num_Prime"));
+        assert_eq!("Num_Prime", renaming.renamed_type("Prime"));
+        assert_eq!("Number", renaming.original_root_edge("Num_Number"));
+        assert_eq!("Int", renaming.renamed_type("Int"));
+    }
+
+    #[test]
+    fn renaming_back_restores_the_original_schema() {
+        let schema = load_schema("filesystem");
+        let renamed = schema
+            .rename(&SchemaRenaming::with_prefix(&schema, "Fs_"))
+            .unwrap();
+
+        let mut restoring = SchemaRenaming::new();
+        for vertex_type in std::iter::once(schema.root_type()).chain(schema.vertex_types()) {
+            restoring =
+                restoring.rename_type(format!("Fs_{}", vertex_type.name()), vertex_type.name());
+        }
+        for edge in schema.root_edges() {
+            restoring = restoring.rename_root_edge(format!("Fs_{}", edge.name()), edge.name());
+        }
+        let restored = renamed.rename(&restoring).unwrap();
+
+        assert_eq!(schema.fingerprint(), restored.fingerprint());
+        assert_ne!(schema.fingerprint(), renamed.fingerprint());
+    }
+
+    #[test]
+    fn renamed_enums_are_renamed_everywhere() {
+        let schema = Schema::parse(
+            fs::read_to_string("test_data/tests/valid_schemas/enum_properties.graphql").unwrap(),
+        )
+        .unwrap();
+        let renamed = schema
+            .rename(&SchemaRenaming::new().rename_type("Color", "Colour"))
+            .unwrap();
+
+        assert!(renamed.enum_values("Color").is_none());
+        assert_eq!(
+            vec!["RED", "GREEN", "BLUE"],
+            renamed.enum_values("Colour").unwrap().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn conflicting_renames_are_errors() {
+        let schema = load_schema("numbers");
+        let renaming = SchemaRenaming::new()
+            .rename_type("Prime", "Number")
+            .rename_type("Letter", "String");
+        assert!(matches!(
+            schema.rename(&renaming),
+            Err(InvalidSchemaError::InvalidSchemaJson(_) | InvalidSchemaError::MultipleErrors(_))
+        ));
+    }
+}
@@ -235,6 +235,24 @@ impl<'de> de::Deserializer<'de> for FieldValueDeserializer {
         }
     }
 
+    /// Unit variants of enums are deserialized from strings and enum values holding their name.
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            FieldValue::String(v) | FieldValue::Enum(v) => {
+                de::value::StringDeserializer::new(v).deserialize_enum(name, variants, visitor)
+            }
+            _ => self.deserialize_any(visitor), // we'll let `deserialize_any()` raise the error
+        }
+    }
+
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
@@ -253,8 +271,8 @@ impl<'de> de::Deserializer<'de> for FieldValueDeserializer {
             FieldValue::Float64(v) => visitor.visit_f64(v),
             FieldValue::String(v) => visitor.visit_string(v),
             FieldValue::Boolean(v) => visitor.visit_bool(v),
-            FieldValue::DateTimeUtc(_) => todo!(),
-            FieldValue::Enum(_) => todo!(),
+            FieldValue::DateTimeUtc(v) => visitor.visit_string(v.to_rfc3339()),
+            FieldValue::Enum(v) => visitor.visit_string(v),
             FieldValue::List(v) => visitor.visit_seq(v.into_deserializer()),
         }
     }
@@ -262,6 +280,6 @@ impl<'de> de::Deserializer<'de> for FieldValueDeserializer {
     serde::forward_to_deserialize_any! {
         bool i64 i128 u64 u128 f64 char str string seq
         bytes byte_buf unit unit_struct newtype_struct
-        tuple_struct map enum struct identifier
+        tuple_struct map struct identifier
     }
 }
@@ -5,6 +5,9 @@ use serde::de;
 use crate::ir::FieldValue;
 
 mod deserializers;
+mod shape;
+
+pub use shape::{check_result_struct, ResultStructError};
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,481 @@
+use std::{collections::BTreeMap, fmt::Display, sync::Arc};
+
+use serde::de::{self, IntoDeserializer};
+
+use crate::{
+    ir::{Output, Type},
+    util::DisplayVec,
+};
+
+/// Why a query's results can't be deserialized into a given struct.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ResultStructError {
+    #[error("{0}")]
+    MultipleErrors(DisplayVec<ResultStructError>),
+
+    #[error(
+        "The struct could not be checked against the query's outputs: {0}. Only structs with \
+        named fields and a derived or equivalent Deserialize implementation are supported."
+    )]
+    UnsupportedStruct(String),
+
+    #[error("The struct's field \"{0}\" is not one of the query's outputs.")]
+    MissingOutput(String),
+
+    #[error(
+        "The query's output \"{0}\" does not correspond to any of the struct's fields. \
+        Please add a field for it, or remove its @output directive."
+    )]
+    UnusedOutput(String),
+
+    #[error(
+        "The query's output \"{name}\" of type {output_type} cannot be deserialized into \
+        the struct's field, which expects {expected}."
+    )]
+    IncompatibleType {
+        name: String,
+        output_type: String,
+        expected: String,
+    },
+}
+
+/// Check that the results of a query with the given outputs can be deserialized into `S`,
+/// without executing the query.
+///
+/// Each output must correspond to a field of `S`, and each value the output may produce,
+/// according to its type, must be valid for that field. For example, the values
+/// of outputs inside `@optional` blocks may be null, so they need `Option` fields.
+/// Fields of `S` may be missing from the outputs only if `S` has a default for them.
+///
+/// Query results that pass this check can be converted into `S`
+/// using [`TryIntoStruct`](crate::TryIntoStruct).
+///
+/// `S` is inspected by running its `Deserialize` implementation on placeholder values,
+/// so this works for structs that derive `Deserialize` without any further annotations.
+/// Fields whose types deserialize from any value, such as
+/// [`TransparentValue`](crate::ir::TransparentValue), accept outputs of all types.
+pub fn check_result_struct<S>(outputs: &BTreeMap<Arc<str>, Output>) -> Result<(), ResultStructError>
+where
+    S: for<'de> de::Deserialize<'de>,
+{
+    let mut fields = None;
+    S::deserialize(StructProbe {
+        outputs,
+        fields: &mut fields,
+    })
+    .map_err(|e| match e {
+        ProbeError::MissingField(name) => ResultStructError::MissingOutput(name.to_string()),
+        e => ResultStructError::UnsupportedStruct(e.to_string()),
+    })?;
+    let fields = fields.expect("struct fields were not recorded");
+
+    let mut errors = vec![];
+    for (name, output) in outputs {
+        match fields.iter().find(|(field, _)| *field == name.as_ref()) {
+            None => errors.push(ResultStructError::UnusedOutput(name.to_string())),
+            Some((_, kind)) if !kind.accepts(&output.value_type) => {
+                errors.push(ResultStructError::IncompatibleType {
+                    name: name.to_string(),
+                    output_type: output.value_type.to_string(),
+                    expected: kind.to_string(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.pop().unwrap()),
+        _ => Err(ResultStructError::MultipleErrors(DisplayVec(errors))),
+    }
+}
+
+/// The kind of values a struct field requested while being deserialized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FieldKind {
+    Any,
+    Boolean,
+    Integer,
+    Float,
+    String,
+    Optional(Box<FieldKind>),
+    List(Box<FieldKind>),
+}
+
+impl FieldKind {
+    /// Whether all values of the given type can be deserialized into a field of this kind.
+    ///
+    /// The values of custom scalar types may be of any kind, so they are accepted
+    /// by all non-list fields.
+    fn accepts(&self, value_type: &Type) -> bool {
+        match self {
+            FieldKind::Any => true,
+            FieldKind::Optional(inner) => inner.accepts(&value_type.with_nullability(false)),
+            _ if value_type.is_nullable() => false,
+            FieldKind::List(inner) => match value_type.element_type() {
+                Some(item_type) => inner.accepts(&item_type),
+                None => false,
+            },
+            _ if value_type.is_list() => false,
+            kind => match (kind, value_type.base_type()) {
+                (FieldKind::Boolean, "Boolean") => true,
+                (FieldKind::Integer, "Int") => true,
+                (FieldKind::Float, "Int" | "Float") => true,
+                (FieldKind::String, "String" | "ID") => true,
+                (_, "Boolean" | "Int" | "Float" | "String" | "ID") => false,
+                _ => true,
+            },
+        }
+    }
+}
+
+impl Display for FieldKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldKind::Any => write!(f, "any value"),
+            FieldKind::Boolean => write!(f, "a boolean"),
+            FieldKind::Integer => write!(f, "an integer"),
+            FieldKind::Float => write!(f, "a number"),
+            FieldKind::String => write!(f, "a string"),
+            FieldKind::Optional(inner) => write!(f, "{inner} or null"),
+            FieldKind::List(inner) => write!(f, "a list of which each item is {inner}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum ProbeError {
+    #[error("missing field `{0}`")]
+    MissingField(&'static str),
+
+    #[error("{0}")]
+    Custom(String),
+}
+
+impl de::Error for ProbeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self::MissingField(field)
+    }
+}
+
+/// Records the fields a struct expects, by deserializing it from a map with a placeholder
+/// value for each of its fields that is also a query output.
+struct StructProbe<'a> {
+    outputs: &'a BTreeMap<Arc<str>, Output>,
+    fields: &'a mut Option<Vec<(&'static str, FieldKind)>>,
+}
+
+impl<'de, 'a> de::Deserializer<'de> for StructProbe<'a> {
+    type Error = ProbeError;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(de::Error::custom("it is not a struct with named fields"))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let recorded = self.fields.insert(vec![]);
+        visitor.visit_map(MapProbe {
+            keys: fields
+                .iter()
+                .copied()
+                .filter(|field| self.outputs.contains_key(*field))
+                .collect::<Vec<_>>()
+                .into_iter(),
+            fields: recorded,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+struct MapProbe<'a> {
+    keys: std::vec::IntoIter<&'static str>,
+    fields: &'a mut Vec<(&'static str, FieldKind)>,
+}
+
+impl<'de, 'a> de::MapAccess<'de> for MapProbe<'a> {
+    type Error = ProbeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        self.keys
+            .next()
+            .map(|key| {
+                self.fields.push((key, FieldKind::Any));
+                seed.deserialize(key.into_deserializer())
+            })
+            .transpose()
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let (_, kind) = self
+            .fields
+            .last_mut()
+            .expect("called next_value_seed out of order");
+        seed.deserialize(ValueProbe { kind })
+    }
+}
+
+/// Records the kind of value a field requests, and produces a placeholder value of that kind.
+struct ValueProbe<'a> {
+    kind: &'a mut FieldKind,
+}
+
+impl<'a> ValueProbe<'a> {
+    fn record(self, kind: FieldKind) {
+        *self.kind = kind;
+    }
+}
+
+/// The placeholder for string values, chosen so that types that deserialize from strings
+/// in a particular format, like `chrono::DateTime`, also accept it.
+const PLACEHOLDER_STRING: &str = "1970-01-01T00:00:00Z";
+
+macro_rules! probe_integers {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: de::Visitor<'de>,
+            {
+                self.record(FieldKind::Integer);
+                visitor.$visit(0)
+            }
+        )*
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for ValueProbe<'a> {
+    type Error = ProbeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.record(FieldKind::Any);
+        visitor.visit_unit()
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.record(FieldKind::Boolean);
+        visitor.visit_bool(false)
+    }
+
+    probe_integers! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.record(FieldKind::Float);
+        visitor.visit_f32(0.0)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.record(FieldKind::Float);
+        visitor.visit_f64(0.0)
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.record(FieldKind::String);
+        visitor.visit_char('0')
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.record(FieldKind::String);
+        visitor.visit_str(PLACEHOLDER_STRING)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    /// Enums with unit variants are deserialized from the names of their variants.
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.record(FieldKind::String);
+        let variant = variants.first().copied().unwrap_or_default();
+        visitor.visit_enum(variant.into_deserializer())
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let mut inner = FieldKind::Any;
+        let value = visitor.visit_some(ValueProbe { kind: &mut inner })?;
+        self.record(FieldKind::Optional(Box::new(inner)));
+        Ok(value)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let mut item = FieldKind::Any;
+        let value = visitor.visit_seq(SeqProbe {
+            remaining: 1,
+            item: &mut item,
+        })?;
+        self.record(FieldKind::List(Box::new(item)));
+        Ok(value)
+    }
+
+    /// Tuples are deserialized from lists of the same length. Their items may be
+    /// of different kinds, so they are recorded as lists of any kind of item.
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let value = visitor.visit_seq(SeqProbe {
+            remaining: len,
+            item: &mut FieldKind::Any,
+        })?;
+        self.record(FieldKind::List(Box::new(FieldKind::Any)));
+        Ok(value)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(de::Error::custom(
+            "query results cannot contain maps or structs, only lists and scalar values",
+        ))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bytes byte_buf unit unit_struct
+    }
+}
+
+struct SeqProbe<'a> {
+    remaining: usize,
+    item: &'a mut FieldKind,
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for SeqProbe<'a> {
+    type Error = ProbeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(ValueProbe {
+            kind: &mut *self.item,
+        })
+        .map(Some)
+    }
+}
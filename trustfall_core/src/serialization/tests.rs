@@ -2,8 +2,13 @@ use std::{collections::BTreeMap, sync::Arc};
 
 use serde::Deserialize;
 
-use super::TryIntoStruct;
-use crate::ir::FieldValue;
+use super::{check_result_struct, ResultStructError, TryIntoStruct};
+use crate::{
+    frontend::parse,
+    ir::{FieldValue, IndexedQuery, TransparentValue},
+    schema::Schema,
+    util::DisplayVec,
+};
 
 #[test]
 fn deserialize_simple() {
@@ -234,3 +239,163 @@ fn deserialize_adjust_numeric_type_signedness() {
         output_value
     );
 }
+
+#[test]
+fn deserialize_enum_and_datetime() {
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    enum Color {
+        Red,
+        Green,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Output {
+        color: Color,
+        named: Color,
+        time: chrono::DateTime<chrono::Utc>,
+    }
+
+    let time = chrono::DateTime::parse_from_rfc3339("2023-01-02T03:04:05Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+    let value: BTreeMap<Arc<str>, FieldValue> = btreemap! {
+        Arc::from("color") => FieldValue::Enum("Green".to_string()),
+        Arc::from("named") => FieldValue::String("Red".to_string()),
+        Arc::from("time") => FieldValue::DateTimeUtc(time),
+    };
+
+    let output_value = value
+        .try_into_struct::<Output>()
+        .expect("failed to create struct");
+    assert_eq!(
+        Output {
+            color: Color::Green,
+            named: Color::Red,
+            time,
+        },
+        output_value
+    );
+}
+
+fn numbers_query(query: &str) -> Arc<IndexedQuery> {
+    let schema = Schema::parse(include_str!("../../test_data/schemas/numbers.graphql")).unwrap();
+    parse(&schema, query).unwrap()
+}
+
+const NUMBERS_QUERY: &str = r#"
+{
+    Number(max: 4) {
+        value @output
+        name @output
+
+        predecessor @optional {
+            previous: value @output
+        }
+
+        multiple(max: 3) @fold {
+            multiples: value @output
+        }
+    }
+}"#;
+
+#[test]
+fn check_matching_struct() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Output {
+        value: Option<i32>,
+        name: Option<String>,
+        #[serde(rename = "previous")]
+        predecessor: Option<u64>,
+        multiples: Vec<Option<f64>>,
+    }
+
+    let query = numbers_query(NUMBERS_QUERY);
+    check_result_struct::<Output>(&query.outputs).expect("struct did not match");
+}
+
+#[test]
+fn check_struct_with_any_values() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Output {
+        value: TransparentValue,
+        name: TransparentValue,
+        previous: Option<TransparentValue>,
+        multiples: Vec<TransparentValue>,
+    }
+
+    let query = numbers_query(NUMBERS_QUERY);
+    check_result_struct::<Output>(&query.outputs).expect("struct did not match");
+}
+
+#[test]
+fn check_struct_with_missing_and_unused_fields() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Output {
+        value: Option<i64>,
+        name: Option<String>,
+        multiples: Vec<Option<i64>>,
+        extra: i64,
+    }
+
+    let query = numbers_query(NUMBERS_QUERY);
+    assert_eq!(
+        Err(ResultStructError::MissingOutput("extra".to_string())),
+        check_result_struct::<Output>(&query.outputs),
+    );
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct OutputWithDefault {
+        value: Option<i64>,
+        name: Option<String>,
+        multiples: Vec<Option<i64>>,
+        #[serde(default)]
+        extra: i64,
+    }
+
+    assert_eq!(
+        Err(ResultStructError::UnusedOutput("previous".to_string())),
+        check_result_struct::<OutputWithDefault>(&query.outputs),
+    );
+}
+
+#[test]
+fn check_struct_with_incompatible_types() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Output {
+        value: Option<String>,
+        name: Option<String>,
+        previous: i64,
+        multiples: Option<i64>,
+    }
+
+    let query = numbers_query(NUMBERS_QUERY);
+    let error =
+        |name: &str, output_type: &str, expected: &str| ResultStructError::IncompatibleType {
+            name: name.to_string(),
+            output_type: output_type.to_string(),
+            expected: expected.to_string(),
+        };
+    assert_eq!(
+        Err(ResultStructError::MultipleErrors(DisplayVec(vec![
+            error("multiples", "[Int]!", "an integer or null"),
+            error("previous", "Int", "an integer"),
+            error("value", "Int", "a string or null"),
+        ]))),
+        check_result_struct::<Output>(&query.outputs),
+    );
+}
+
+#[test]
+fn check_unsupported_struct() {
+    let query = numbers_query(NUMBERS_QUERY);
+    let result = check_result_struct::<BTreeMap<String, TransparentValue>>(&query.outputs);
+    assert!(
+        matches!(result, Err(ResultStructError::UnsupportedStruct(_))),
+        "{result:?}"
+    );
+}
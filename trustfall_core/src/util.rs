@@ -2,6 +2,7 @@ use std::collections::{btree_map, hash_map, BTreeMap, HashMap};
 use std::fmt::{Debug, Display};
 use std::hash::Hash;
 
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
@@ -142,3 +143,125 @@ impl<K: Ord, V> BTreeMapTryInsertExt<K, V> for BTreeMap<K, V> {
         }
     }
 }
+
+/// The most similar candidates to a name that was not found, closest first,
+/// for "did you mean" suggestions in error messages.
+///
+/// Candidates are compared case-insensitively by edit distance, and only reasonably close
+/// candidates are suggested: a third of the name's length, but at least one edit.
+pub(crate) fn similar_names<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
+    const MAX_SUGGESTIONS: usize = 3;
+
+    let name_lowercase = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    let mut similar: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let distance = edit_distance(&name_lowercase, &candidate.to_lowercase());
+            (distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect();
+    similar.sort_unstable();
+    similar.dedup();
+
+    similar
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// The edit distance between two strings, counted in characters: the number of insertions,
+/// deletions, substitutions, and transpositions of adjacent characters needed to turn one
+/// into the other, without editing any substring more than once.
+fn edit_distance(left: &str, right: &str) -> usize {
+    let left: Vec<char> = left.chars().collect();
+    let right: Vec<char> = right.chars().collect();
+
+    // `distances[i][j]` is the distance between the first `i` characters of `left`
+    // and the first `j` characters of `right`.
+    let mut distances = vec![vec![0; right.len() + 1]; left.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=left.len() {
+        for j in 1..=right.len() {
+            let cost = usize::from(left[i - 1] != right[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && left[i - 1] == right[j - 2] && left[i - 2] == right[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[left.len()][right.len()]
+}
+
+/// Formats suggestions from [`similar_names`] as a sentence to append to an error message.
+pub(crate) fn did_you_mean(suggestions: &[String]) -> String {
+    match suggestions {
+        [] => String::new(),
+        [suggestion] => format!(" Did you mean \"{suggestion}\"?"),
+        _ => format!(
+            " Did you mean one of: {}?",
+            suggestions.iter().map(|s| format!("\"{s}\"")).join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{did_you_mean, edit_distance, similar_names};
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(0, edit_distance("name", "name"));
+        assert_eq!(1, edit_distance("name", "nme"));
+        assert_eq!(1, edit_distance("name", "names"));
+        assert_eq!(1, edit_distance("name", "nmae"));
+        assert_eq!(2, edit_distance("name", "nmea"));
+        assert_eq!(3, edit_distance("", "abc"));
+        assert_eq!(1, edit_distance("café", "cafe"));
+    }
+
+    #[test]
+    fn suggests_close_names_closest_first() {
+        let candidates = [
+            "value",
+            "vowelsInName",
+            "name",
+            "Value",
+            "values",
+            "successor",
+        ];
+        assert_eq!(vec!["Value", "value"], similar_names("valeu", candidates));
+        assert_eq!(
+            vec!["values", "Value", "value"],
+            similar_names("VALUES", candidates)
+        );
+        assert_eq!(vec!["successor"], similar_names("sucessor", candidates));
+        assert!(similar_names("predecessor", candidates).is_empty());
+    }
+
+    #[test]
+    fn suggestion_messages() {
+        assert_eq!("", did_you_mean(&[]));
+        assert_eq!(
+            " Did you mean \"name\"?",
+            did_you_mean(&["name".to_string()])
+        );
+        assert_eq!(
+            " Did you mean one of: \"name\", \"names\"?",
+            did_you_mean(&["name".to_string(), "names".to_string()])
+        );
+    }
+}
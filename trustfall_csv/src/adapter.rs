@@ -0,0 +1,272 @@
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+};
+
+use csv::{Reader, ReaderBuilder, StringRecord};
+use trustfall_core::{
+    interpreter::{
+        basic_adapter::BasicAdapter, helpers::resolve_property_with, ContextIterator,
+        ContextOutcomeIterator, Typename, VertexIterator,
+    },
+    ir::{EdgeParameters, FieldValue},
+    schema::{error::InvalidSchemaError, Schema},
+};
+
+use crate::columns::{Columns, ROW_NUMBER_PROPERTY, ROW_TYPE};
+
+/// How many rows [`CsvOptions::default`] infers column types from.
+pub const DEFAULT_SAMPLE_ROWS: usize = 1000;
+
+/// Errors from opening a file for querying.
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum CsvError {
+    #[error("Failed to read the file")]
+    Read(#[from] csv::Error),
+
+    #[error("The schema is not valid")]
+    InvalidSchema(#[from] InvalidSchemaError),
+
+    #[error("The schema has no \"{ROW_TYPE}\" vertex type.")]
+    MissingRowType,
+
+    #[error("The file has no column for the property \"{0}\".")]
+    MissingColumn(String),
+
+    #[error(
+        "The property \"{0}\" has type {1}, but columns can only be \
+        Boolean, Int, Float, or String properties."
+    )]
+    UnsupportedPropertyType(String, String),
+}
+
+/// How to read a file's rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    delimiter: u8,
+    sample_rows: usize,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            sample_rows: DEFAULT_SAMPLE_ROWS,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// The default options for the file: tab-delimited if its extension is `.tsv`,
+    /// and comma-delimited otherwise.
+    pub fn for_path(path: impl AsRef<Path>) -> Self {
+        let is_tsv = path
+            .as_ref()
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("tsv"));
+        let options = Self::default();
+        if is_tsv {
+            options.with_delimiter(b'\t')
+        } else {
+            options
+        }
+    }
+
+    /// Separate values with the given byte instead of a comma.
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Infer column types from at most this many rows at the start of the file,
+    /// instead of [`DEFAULT_SAMPLE_ROWS`].
+    pub fn with_sample_rows(mut self, sample_rows: usize) -> Self {
+        self.sample_rows = sample_rows;
+        self
+    }
+
+    fn reader(&self, path: &Path) -> Result<Reader<std::fs::File>, csv::Error> {
+        ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            // Rows with missing values are common, and those values are queried as `null`.
+            .flexible(true)
+            .from_path(path)
+    }
+}
+
+/// A row of a file.
+#[derive(Debug, Clone)]
+pub struct Row {
+    number: u64,
+    record: Rc<StringRecord>,
+}
+
+impl Row {
+    /// The row's number, counting from 1 and not counting the header row.
+    pub fn number(&self) -> u64 {
+        self.number
+    }
+
+    /// The row's values, in the order of the file's columns.
+    pub fn record(&self) -> &StringRecord {
+        &self.record
+    }
+}
+
+impl Typename for Row {
+    fn typename(&self) -> &'static str {
+        ROW_TYPE
+    }
+}
+
+/// An adapter querying the rows of a CSV or TSV file, with a property for each column.
+///
+/// The file's first row is its header, naming its columns. The column types are either
+/// inferred from the first rows of the file or read from a schema. Rows are read
+/// from the file as the query needs them, so files of any size can be queried
+/// without loading the whole file.
+///
+/// Empty values are `null`, and so are values that aren't of their column's type,
+/// such as `abc` in a `Float` column. Queries panic if the file can't be read
+/// after the adapter was created, for example because it isn't valid UTF-8.
+#[derive(Debug, Clone)]
+pub struct CsvAdapter {
+    path: PathBuf,
+    options: CsvOptions,
+    columns: Arc<Columns>,
+}
+
+impl CsvAdapter {
+    /// Query the file with the default options for it, inferring its column types.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CsvError> {
+        let options = CsvOptions::for_path(&path);
+        Self::open_with(path, options)
+    }
+
+    /// Query the file with the given options, inferring its column types.
+    pub fn open_with(path: impl AsRef<Path>, options: CsvOptions) -> Result<Self, CsvError> {
+        let path = path.as_ref().to_path_buf();
+        let mut reader = options.reader(&path)?;
+        let headers = reader.headers()?.clone();
+        let sample = reader
+            .records()
+            .take(options.sample_rows)
+            .collect::<Result<Vec<_>, _>>()?;
+        let columns = Columns::infer(&headers, &sample)?;
+        Ok(Self {
+            path,
+            options,
+            columns: Arc::new(columns),
+        })
+    }
+
+    /// Query the file with the given options, using the given schema's column types.
+    ///
+    /// The schema's `Row` vertex type has a property for each queried column,
+    /// named either the same as the column's header or as the property
+    /// [`CsvAdapter::open`] would infer for the column. Not all columns need
+    /// properties. The schema's starting edges all list the file's rows.
+    pub fn with_schema(
+        path: impl AsRef<Path>,
+        options: CsvOptions,
+        schema: &str,
+    ) -> Result<Self, CsvError> {
+        let path = path.as_ref().to_path_buf();
+        let headers = options.reader(&path)?.headers()?.clone();
+        let columns = Columns::from_schema(&headers, schema)?;
+        Ok(Self {
+            path,
+            options,
+            columns: Arc::new(columns),
+        })
+    }
+
+    /// The schema for querying the file's rows.
+    pub fn schema(&self) -> &Schema {
+        self.columns.schema()
+    }
+
+    /// The file's columns that are queried as properties.
+    pub fn columns(&self) -> &Columns {
+        &self.columns
+    }
+
+    fn rows(&self) -> impl Iterator<Item = Row> {
+        let path = self.path.clone();
+        let mut reader = self
+            .options
+            .reader(&path)
+            .unwrap_or_else(|e| panic!("failed to open {}: {e}", path.display()));
+        let mut number = 0;
+        std::iter::from_fn(move || {
+            let mut record = StringRecord::new();
+            let has_row = reader
+                .read_record(&mut record)
+                .unwrap_or_else(|e| panic!("failed to read {}: {e}", path.display()));
+            has_row.then(|| {
+                number += 1;
+                Row {
+                    number,
+                    record: Rc::new(record),
+                }
+            })
+        })
+    }
+}
+
+impl<'a> BasicAdapter<'a> for CsvAdapter {
+    type Vertex = Row;
+
+    fn resolve_starting_vertices(
+        &self,
+        _edge_name: &str,
+        _parameters: &EdgeParameters,
+    ) -> VertexIterator<'a, Self::Vertex> {
+        // Every starting edge of a provided schema lists the rows.
+        Box::new(self.rows())
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'a, Self::Vertex>,
+        type_name: &str,
+        property_name: &str,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, FieldValue> {
+        if property_name == ROW_NUMBER_PROPERTY && self.columns.column(property_name).is_none() {
+            return resolve_property_with(contexts, |row| row.number.into());
+        }
+
+        let column = self
+            .columns
+            .column(property_name)
+            .unwrap_or_else(|| unreachable!("unexpected property {type_name}.{property_name}"));
+        let (index, column_type) = (column.index, column.column_type);
+        resolve_property_with(contexts, move |row| match row.record.get(index) {
+            Some(value) => column_type.convert(value),
+            None => FieldValue::Null,
+        })
+    }
+
+    fn resolve_neighbors(
+        &self,
+        _contexts: ContextIterator<'a, Self::Vertex>,
+        type_name: &str,
+        edge_name: &str,
+        _parameters: &EdgeParameters,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, VertexIterator<'a, Self::Vertex>> {
+        unreachable!("unexpected edge {type_name}.{edge_name}: rows have no edges")
+    }
+
+    fn resolve_coercion(
+        &self,
+        _contexts: ContextIterator<'a, Self::Vertex>,
+        type_name: &str,
+        coerce_to_type: &str,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, bool> {
+        unreachable!(
+            "unexpected coercion from {type_name} to {coerce_to_type}: rows have no subtypes"
+        )
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::BTreeSet;
+
+use csv::StringRecord;
+use trustfall_core::{
+    ir::FieldValue,
+    schema::{
+        builder::{graphql_name, unique_name, SchemaBuilder, Ty},
+        json::SchemaJson,
+        Schema,
+    },
+};
+
+use crate::CsvError;
+
+/// The name of the vertex type of rows, and of the starting edge producing them.
+pub const ROW_TYPE: &str = "Row";
+
+/// The property holding each row's number, counting from 1 and not counting the header row.
+pub const ROW_NUMBER_PROPERTY: &str = "rowNumber";
+
+/// The type of the values in a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Boolean,
+    Int,
+    Float,
+    String,
+}
+
+impl ColumnType {
+    /// The narrowest type the value is of, unless it's empty.
+    fn of(value: &str) -> Option<Self> {
+        if value.is_empty() {
+            None
+        } else if parse_bool(value).is_some() {
+            Some(Self::Boolean)
+        } else if value.parse::<i64>().is_ok() {
+            Some(Self::Int)
+        } else if value.parse::<f64>().is_ok_and(f64::is_finite) {
+            Some(Self::Float)
+        } else {
+            Some(Self::String)
+        }
+    }
+
+    /// The narrowest type both types' values are of.
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Self::Int, Self::Float) | (Self::Float, Self::Int) => Self::Float,
+            _ => Self::String,
+        }
+    }
+
+    fn from_type_name(name: &str) -> Option<Self> {
+        match name {
+            "Boolean" => Some(Self::Boolean),
+            "Int" => Some(Self::Int),
+            "Float" => Some(Self::Float),
+            "String" => Some(Self::String),
+            _ => None,
+        }
+    }
+
+    fn ty(self) -> Ty {
+        match self {
+            Self::Boolean => Ty::boolean(),
+            Self::Int => Ty::int(),
+            Self::Float => Ty::float(),
+            Self::String => Ty::string(),
+        }
+    }
+
+    /// Convert the value, which is null if it's empty or not of this type.
+    pub(crate) fn convert(self, value: &str) -> FieldValue {
+        if value.is_empty() {
+            return FieldValue::Null;
+        }
+        match self {
+            Self::Boolean => parse_bool(value).into(),
+            Self::Int => value.parse::<i64>().ok().into(),
+            Self::Float => match value.parse::<f64>() {
+                Ok(value) if value.is_finite() => FieldValue::Float64(value),
+                _ => FieldValue::Null,
+            },
+            Self::String => value.into(),
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    if value.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// A column of the file, queried as a property of rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    /// The column's position in each row, counting from 0.
+    pub index: usize,
+    /// The column's name in the header row.
+    pub header: String,
+    /// The name of the property holding the column's values.
+    pub property: String,
+    pub column_type: ColumnType,
+}
+
+/// The columns of a file, and the schema for querying its rows.
+#[derive(Debug, Clone)]
+pub struct Columns {
+    columns: Vec<Column>,
+    schema: Schema,
+}
+
+impl Columns {
+    /// Infer each column's type from its values in the given rows.
+    ///
+    /// Columns are `Boolean` if all their values are `true` or `false` ignoring case,
+    /// `Int` or `Float` if they are all numbers, and `String` otherwise. Their properties
+    /// are named after their headers, changed into valid GraphQL names if necessary:
+    /// for example, the property of the column `first name` is `first_name`.
+    pub(crate) fn infer<'r>(
+        headers: &StringRecord,
+        rows: impl IntoIterator<Item = &'r StringRecord>,
+    ) -> Result<Self, CsvError> {
+        let mut types: Vec<Option<ColumnType>> = vec![None; headers.len()];
+        for row in rows {
+            for (column_type, value) in types.iter_mut().zip(row.iter()) {
+                *column_type = match (*column_type, ColumnType::of(value)) {
+                    (Some(a), Some(b)) => Some(a.merge(b)),
+                    (a, b) => a.or(b),
+                };
+            }
+        }
+
+        let mut taken = BTreeSet::from([ROW_NUMBER_PROPERTY.to_string()]);
+        let columns = headers
+            .iter()
+            .zip(types)
+            .enumerate()
+            .map(|(index, (header, column_type))| Column {
+                index,
+                header: header.to_string(),
+                property: unique_name(&mut taken, graphql_name(header.trim())),
+                column_type: column_type.unwrap_or(ColumnType::String),
+            })
+            .collect();
+        Self::new(columns)
+    }
+
+    /// Match the properties of the schema's `Row` type to the columns with those headers.
+    ///
+    /// Properties may be `Boolean`, `Int`, `Float`, or `String`, and the `rowNumber` property
+    /// may be an `Int` holding row numbers instead of the values of a column.
+    pub(crate) fn from_schema(headers: &StringRecord, schema: &str) -> Result<Self, CsvError> {
+        let schema = Schema::parse(schema)?;
+        let schema_json = SchemaJson::from(&schema);
+        let row_type = schema_json
+            .types
+            .iter()
+            .find(|vertex_type| vertex_type.name == ROW_TYPE)
+            .ok_or(CsvError::MissingRowType)?;
+
+        let mut columns = vec![];
+        for field in &row_type.fields {
+            let type_name = field.field_type.trim_end_matches('!');
+            if field.name == ROW_NUMBER_PROPERTY && type_name == "Int" {
+                continue;
+            }
+            let column_type = ColumnType::from_type_name(type_name).ok_or_else(|| {
+                CsvError::UnsupportedPropertyType(field.name.clone(), field.field_type.clone())
+            })?;
+            let (index, header) = headers
+                .iter()
+                .enumerate()
+                .find(|(_, header)| {
+                    *header == field.name || graphql_name(header.trim()) == field.name
+                })
+                .ok_or_else(|| CsvError::MissingColumn(field.name.clone()))?;
+            columns.push(Column {
+                index,
+                header: header.to_string(),
+                property: field.name.clone(),
+                column_type,
+            });
+        }
+        Ok(Self { columns, schema })
+    }
+
+    fn new(columns: Vec<Column>) -> Result<Self, CsvError> {
+        let row_ty = Ty::named(ROW_TYPE).non_null().list().non_null();
+        let mut builder = SchemaBuilder::new()
+            .root_edge(ROW_TYPE, row_ty)
+            .description("The rows of the file, in order.")
+            .vertex_type(ROW_TYPE)
+            .property(ROW_NUMBER_PROPERTY, Ty::int().non_null())
+            .description("The row's number, counting from 1 and not counting the header row.");
+        for column in &columns {
+            builder = builder
+                .property(&column.property, column.column_type.ty())
+                .description(format!("The `{}` column.", column.header));
+        }
+        Ok(Self {
+            columns,
+            schema: builder.build()?,
+        })
+    }
+
+    /// The columns queried as properties, in the order they appear in the file.
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// The schema for querying the file's rows.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub(crate) fn column(&self, property: &str) -> Option<&Column> {
+        self.columns
+            .iter()
+            .find(|column| column.property == property)
+    }
+}
@@ -0,0 +1,37 @@
+//! Query CSV and TSV files with trustfall.
+//!
+//! [`CsvAdapter`] queries the rows of a delimited file, whose first row names its columns.
+//! Each row is a `Row` vertex with a property for each column, and a `rowNumber` property.
+//! The column types are inferred from the first rows of the file, or read from a schema
+//! provided with [`CsvAdapter::with_schema`].
+//!
+//! ```
+//! use std::{collections::BTreeMap, sync::Arc};
+//!
+//! use trustfall_core::{frontend::parse, interpreter::execution::interpret_ir, ir::FieldValue};
+//! use trustfall_csv::CsvAdapter;
+//!
+//! let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/crates.csv");
+//! let adapter = Arc::new(CsvAdapter::open(path).unwrap());
+//!
+//! // The column `first released` is queried as the property `first_released`.
+//! let query = parse(adapter.schema(), r#"
+//! {
+//!     Row {
+//!         name @output
+//!         first_released @output
+//!         downloads @filter(op: ">=", value: ["$min"])
+//!     }
+//! }"#).unwrap();
+//! let arguments = Arc::new(BTreeMap::from([("min".into(), 1_000_000i64.into())]));
+//! let rows: Vec<_> = interpret_ir(adapter, query, arguments).unwrap().collect();
+//!
+//! let names: Vec<_> = rows.iter().map(|row| row["name"].clone()).collect();
+//! assert_eq!(vec![FieldValue::from("serde"), "rand".into()], names);
+//! assert_eq!(FieldValue::from("2015-02-25"), rows[0]["first_released"]);
+//! ```
+mod adapter;
+mod columns;
+
+pub use adapter::{CsvAdapter, CsvError, CsvOptions, Row, DEFAULT_SAMPLE_ROWS};
+pub use columns::{Column, ColumnType, Columns, ROW_NUMBER_PROPERTY, ROW_TYPE};
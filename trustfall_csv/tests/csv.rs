@@ -0,0 +1,203 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use trustfall_core::ir::FieldValue;
+use trustfall_csv::{ColumnType, CsvAdapter, CsvError, CsvOptions};
+use trustfall_testing::{column, execute};
+
+const CRATES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/crates.csv");
+const LABELS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/labels.tsv");
+
+#[test]
+fn infers_column_types() {
+    let adapter = Arc::new(CsvAdapter::open(CRATES).unwrap());
+    let columns: Vec<_> = adapter
+        .columns()
+        .columns()
+        .iter()
+        .map(|column| (column.property.as_str(), column.column_type))
+        .collect();
+    assert_eq!(
+        vec![
+            ("name", ColumnType::String),
+            ("downloads", ColumnType::Int),
+            ("version", ColumnType::String),
+            ("rating", ColumnType::String),
+            ("yanked", ColumnType::Boolean),
+            ("first_released", ColumnType::String),
+        ],
+        columns,
+    );
+
+    let query = r#"
+{
+    Row {
+        rowNumber @output
+        name @output
+        downloads @output
+        yanked @output
+        first_released @output
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(
+        vec![1i64, 2, 3, 4]
+            .into_iter()
+            .map(FieldValue::from)
+            .collect::<Vec<_>>(),
+        column(&rows, "rowNumber"),
+    );
+    assert_eq!(
+        vec![300_000_000i64, 200_000_000, 5000, 12]
+            .into_iter()
+            .map(FieldValue::from)
+            .collect::<Vec<_>>(),
+        column(&rows, "downloads"),
+    );
+    assert_eq!(FieldValue::Boolean(true), rows[3]["yanked"]);
+    assert_eq!(FieldValue::Null, rows[3]["first_released"]);
+}
+
+#[test]
+fn values_not_of_the_sampled_type_are_null() {
+    let options = CsvOptions::default().with_sample_rows(3);
+    let adapter = Arc::new(CsvAdapter::open_with(CRATES, options).unwrap());
+    let query = r#"
+{
+    Row {
+        rating @output
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(
+        vec![
+            FieldValue::Float64(4.9),
+            FieldValue::Float64(4.7),
+            FieldValue::Null,
+            FieldValue::Null
+        ],
+        column(&rows, "rating"),
+    );
+}
+
+#[test]
+fn reads_tab_separated_files() {
+    let query = r#"
+{
+    Row {
+        id @output
+        label @output
+        weight @output
+    }
+}"#;
+    let adapter = Arc::new(CsvAdapter::open(LABELS).unwrap());
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(
+        vec![1i64, 2, 3]
+            .into_iter()
+            .map(FieldValue::from)
+            .collect::<Vec<_>>(),
+        column(&rows, "id"),
+    );
+    assert_eq!(
+        vec![FieldValue::from("first"), "second".into(), FieldValue::Null],
+        column(&rows, "label"),
+    );
+    assert_eq!(
+        vec![
+            FieldValue::Float64(0.5),
+            FieldValue::Float64(2.0),
+            FieldValue::Null
+        ],
+        column(&rows, "weight"),
+    );
+}
+
+#[test]
+fn uses_provided_schemas() {
+    let schema = r#"
+schema {
+    query: RootSchemaQuery
+}
+directive @filter(op: String!, value: [String!]) on FIELD | INLINE_FRAGMENT
+directive @output(name: String) on FIELD
+
+type RootSchemaQuery {
+    Crate: [Row!]!
+}
+
+type Row {
+    rowNumber: Int!
+    name: String!
+    version: String
+    first_released: String
+    rating: Float
+}"#;
+    let adapter = Arc::new(CsvAdapter::with_schema(CRATES, CsvOptions::default(), schema).unwrap());
+    let query = r#"
+{
+    Crate {
+        rowNumber @output
+        name @output
+        first_released @output
+        rating @output
+        version @filter(op: "has_prefix", value: ["$zero"])
+    }
+}"#;
+    let arguments = BTreeMap::from([("zero".into(), "0.".into())]);
+    let rows = execute(adapter.schema(), adapter.clone(), query, arguments);
+    assert_eq!(
+        vec![FieldValue::Int64(2), 3i64.into(), 4i64.into()],
+        column(&rows, "rowNumber")
+    );
+    assert_eq!(
+        vec![
+            FieldValue::from("rand"),
+            "trustfall".into(),
+            "ancient".into()
+        ],
+        column(&rows, "name")
+    );
+    assert_eq!(FieldValue::from("2022-10-03"), rows[1]["first_released"]);
+    assert_eq!(
+        vec![FieldValue::Float64(4.7), FieldValue::Null, FieldValue::Null],
+        column(&rows, "rating")
+    );
+}
+
+#[test]
+fn rejects_schemas_that_do_not_match_the_file() {
+    let schema = |fields: &str| {
+        format!(
+            "schema {{ query: RootSchemaQuery }}\n\
+            type RootSchemaQuery {{ Row: [Row!]! }}\n\
+            type Row {{ {fields} }}"
+        )
+    };
+    let open =
+        |fields: &str| CsvAdapter::with_schema(CRATES, CsvOptions::default(), &schema(fields));
+
+    assert!(open("name: String downloads: Int").is_ok());
+    assert!(matches!(
+        open("license: String"),
+        Err(CsvError::MissingColumn(property)) if property == "license"
+    ));
+    assert!(matches!(
+        open("name: [String]"),
+        Err(CsvError::UnsupportedPropertyType(property, _)) if property == "name"
+    ));
+    assert!(matches!(
+        CsvAdapter::with_schema(
+            CRATES,
+            CsvOptions::default(),
+            "schema { query: Q } type Q { Crate: [Crate!]! } type Crate { name: String }",
+        ),
+        Err(CsvError::MissingRowType)
+    ));
+    assert!(matches!(
+        CsvAdapter::open(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/missing.csv"
+        )),
+        Err(CsvError::Read(_))
+    ));
+}
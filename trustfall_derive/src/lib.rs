@@ -22,10 +22,12 @@
 //! ```
 
 use quote::quote;
-use syn::punctuated::Punctuated;
+use syn::{ext::IdentExt, punctuated::Punctuated};
 
 const TRUSTFALL_ATTRIBUTE: &str = "trustfall";
 const SKIP_CONVERSION_ATTRIBUTE: &str = "skip_conversion";
+const SKIP_PROPERTY_ATTRIBUTE: &str = "skip_property";
+const RENAME_ATTRIBUTE: &str = "rename";
 
 /// Adds the [`Typename`] trait and `as_<variant>()` methods on an enum used as a Trustfall vertex.
 ///
@@ -102,7 +104,8 @@ const SKIP_CONVERSION_ATTRIBUTE: &str = "skip_conversion";
 /// ```
 ///
 /// To add only the [`Typename`] implementation without the `as_<variant>()` conversions,
-/// use the [`Typename`](self::Typename) derive macro instead.
+/// use the [`Typename`](self::Typename) derive macro instead. To also resolve the properties
+/// of struct variants by name, use the [`TrustfallVertex`] derive macro.
 ///
 /// [`Typename`]: https://docs.rs/trustfall/0.2.0/trustfall/provider/trait.Typename.html
 #[proc_macro_derive(TrustfallEnumVertex, attributes(trustfall))]
@@ -114,6 +117,109 @@ pub fn trustfall_enum_vertex_derive(input: proc_macro::TokenStream) -> proc_macr
     .into()
 }
 
+/// Adds everything [`TrustfallEnumVertex`] does, plus a `property()` method resolving
+/// the properties of a vertex by name, on an enum used as a Trustfall vertex.
+///
+/// Each field of a struct variant is considered a property of the corresponding type,
+/// named the same as the field. Its value is the field's value converted into
+/// a `FieldValue`, so the field's type must implement `Clone` and `Into<FieldValue>`.
+/// The `__typename` property of every type is resolved as well.
+///
+/// For example:
+/// ```rust
+/// # use trustfall_derive::TrustfallVertex;
+/// #
+/// #[derive(Debug, Clone, TrustfallVertex)]
+/// enum Vertex {
+///     User(String),
+///     Message { author: String, content: String },
+/// }
+/// ```
+/// will get the implementations generated by [`TrustfallEnumVertex`], plus the following one:
+/// ```rust
+/// # use trustfall::{provider::Typename, FieldValue};
+/// #
+/// # #[derive(Debug, Clone, trustfall_derive::Typename)]
+/// # enum Vertex {
+/// #     User(String),
+/// #     Message { author: String, content: String },
+/// # }
+/// #
+/// impl Vertex {
+///     fn property(&self, property_name: &str) -> Option<FieldValue> {
+///         match (self, property_name) {
+///             (_, "__typename") => Some(self.typename().into()),
+///             (Self::Message { author, .. }, "author") => Some(author.clone().into()),
+///             (Self::Message { content, .. }, "content") => Some(content.clone().into()),
+///             _ => None,
+///         }
+///     }
+/// }
+/// ```
+///
+/// Adapters can then resolve the properties of struct variants in one place:
+/// ```rust
+/// # use std::sync::Arc;
+/// # use trustfall::{
+/// #     provider::{resolve_property_with, ContextIterator, ContextOutcomeIterator},
+/// #     FieldValue,
+/// # };
+/// # use trustfall_derive::TrustfallVertex;
+/// #
+/// # #[derive(Debug, Clone, TrustfallVertex)]
+/// # enum Vertex {
+/// #     User(String),
+/// #     Message { author: String, content: String },
+/// # }
+/// #
+/// fn resolve_property<'a>(
+///     contexts: ContextIterator<'a, Vertex>,
+///     type_name: &Arc<str>,
+///     property_name: &Arc<str>,
+/// ) -> ContextOutcomeIterator<'a, Vertex, FieldValue> {
+///     let (type_name, property_name) = (type_name.clone(), property_name.clone());
+///     resolve_property_with(contexts, move |vertex| {
+///         vertex
+///             .property(&property_name)
+///             .unwrap_or_else(|| unreachable!("unexpected property {type_name}.{property_name}"))
+///     })
+/// }
+/// ```
+///
+/// A field can be resolved as a property of a different name using
+/// the `#[trustfall(rename = "otherName")]` attribute, or opt out of being a property
+/// using the `#[trustfall(skip_property)]` attribute.
+///
+/// In this example, the `Message` type has the `authorName` property and no other properties:
+/// ```rust
+/// # use trustfall_derive::TrustfallVertex;
+/// #
+/// # #[derive(Debug, Clone)]
+/// # struct Attachment;
+/// #
+/// #[derive(Debug, Clone, TrustfallVertex)]
+/// enum Vertex {
+///     Message {
+///         #[trustfall(rename = "authorName")]
+///         author_name: String,
+///
+///         #[trustfall(skip_property)]
+///         attachment: Attachment,
+///     },
+/// }
+/// ```
+///
+/// The properties of other variants, such as `User(String)` above, need to be resolved
+/// by the adapter. Their `property()` method only resolves `__typename`.
+#[proc_macro_derive(TrustfallVertex, attributes(trustfall))]
+pub fn trustfall_vertex_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match syn::parse(input) {
+        Ok(ast) => impl_trustfall_vertex(&ast).unwrap_or_else(syn::Error::into_compile_error),
+        Err(e) => e.into_compile_error(),
+    }
+    .into()
+}
+
 /// Derives the [`Typename`] trait on the enum being used as a Trustfall vertex.
 ///
 /// Each variant is considered a Trustfall type with the corresponding name.
@@ -246,6 +352,49 @@ fn impl_trustfall_enum_vertex(ast: &syn::DeriveInput) -> syn::Result<proc_macro2
     Ok(gen)
 }
 
+fn impl_trustfall_vertex(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let variants = match &ast.data {
+        syn::Data::Enum(d) => &d.variants,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                ast,
+                "only enums can derive TrustfallVertex",
+            ))
+        }
+    };
+
+    let mut arms = proc_macro2::TokenStream::new();
+    for variant in variants {
+        arms.extend(generate_property_arms(variant)?);
+    }
+
+    let enum_vertex_impl = impl_trustfall_enum_vertex(ast)?;
+
+    let gen = quote! {
+        #enum_vertex_impl
+
+        #[automatically_derived]
+        impl #impl_generics #name #ty_generics #where_clause {
+            pub(crate) fn property(
+                &self,
+                property_name: &str,
+            ) -> ::std::option::Option<::trustfall::FieldValue> {
+                match (self, property_name) {
+                    (_, "__typename") => ::std::option::Option::Some(
+                        ::trustfall::provider::Typename::typename(self).into(),
+                    ),
+                    #arms
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+    };
+    Ok(gen)
+}
+
 fn generate_typename_arm(variant: &syn::Variant) -> proc_macro2::TokenStream {
     let variant_ident = &variant.ident;
     let variant_name = variant_ident.to_string();
@@ -407,6 +556,74 @@ fn generate_conversion_method(variant: &syn::Variant) -> syn::Result<proc_macro2
     }
 }
 
+/// The match arms resolving the properties of a struct variant, one per field.
+fn generate_property_arms(variant: &syn::Variant) -> syn::Result<proc_macro2::TokenStream> {
+    let variant_ident = &variant.ident;
+    let mut arms = proc_macro2::TokenStream::new();
+    let syn::Fields::Named(named_fields) = &variant.fields else {
+        return Ok(arms);
+    };
+
+    for field in named_fields.named.iter() {
+        let field_name = field
+            .ident
+            .as_ref()
+            .expect("struct variant field had no name");
+        let Some(property_name) = parse_property_name(field)? else {
+            continue;
+        };
+        let property_name = proc_macro2::Literal::string(&property_name);
+        arms.extend(quote! {
+            (Self::#variant_ident { #field_name, .. }, #property_name) => {
+                ::std::option::Option::Some(::std::clone::Clone::clone(#field_name).into())
+            }
+        });
+    }
+    Ok(arms)
+}
+
+/// The name of the property resolved from the field, or `None` if the field has
+/// a `#[trustfall(skip_property)]` attribute.
+fn parse_property_name(field: &syn::Field) -> syn::Result<Option<String>> {
+    const EXPECTED: &str = "unexpected arguments found, did you mean \
+        `#[trustfall(skip_property)]` or `#[trustfall(rename = \"propertyName\")]`?";
+
+    // Raw identifiers like `r#type` are named without their `r#` prefix.
+    let mut property_name = field
+        .ident
+        .as_ref()
+        .expect("struct variant field had no name")
+        .unraw()
+        .to_string();
+    for attr in &field.attrs {
+        if !attr.path.is_ident(TRUSTFALL_ATTRIBUTE) {
+            continue;
+        }
+
+        let syn::Meta::List(values) = attr.parse_meta()? else {
+            return Err(syn::Error::new_spanned(attr, EXPECTED));
+        };
+        for nested in values.nested.iter() {
+            match nested {
+                syn::NestedMeta::Meta(syn::Meta::Path(path))
+                    if path.is_ident(SKIP_PROPERTY_ATTRIBUTE) =>
+                {
+                    return Ok(None);
+                }
+                syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(name),
+                    ..
+                })) if path.is_ident(RENAME_ATTRIBUTE) => {
+                    property_name = name.value();
+                }
+                _ => return Err(syn::Error::new_spanned(nested, EXPECTED)),
+            }
+        }
+    }
+    Ok(Some(property_name))
+}
+
 /// Returns a tuple of references to all field types.
 /// The input must contain more than one field.
 fn tuple_of_field_types(
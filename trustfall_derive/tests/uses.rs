@@ -1,7 +1,7 @@
 use std::fmt::Debug;
 
-use trustfall::provider::Typename;
-use trustfall_derive::TrustfallEnumVertex;
+use trustfall::{provider::Typename, FieldValue};
+use trustfall_derive::{TrustfallEnumVertex, TrustfallVertex};
 
 #[test]
 fn empty_enum() {
@@ -251,6 +251,69 @@ fn typename() {
     assert_eq!("Second", second.typename());
 }
 
+#[test]
+fn vertex_properties() {
+    #[derive(Debug, Clone, TrustfallVertex)]
+    enum Vertex {
+        User(String),
+        Message {
+            #[trustfall(rename = "authorName")]
+            author_name: String,
+            content: Option<String>,
+            #[trustfall(skip_property)]
+            attachment: Vec<u8>,
+        },
+    }
+
+    let user = Vertex::User("user".into());
+    assert_eq!("User", user.typename());
+    assert_eq!(Some(&"user".to_string()), user.as_user());
+    assert_eq!(Some(FieldValue::from("User")), user.property("__typename"));
+    assert_eq!(None, user.property("authorName"));
+
+    let message = Vertex::Message {
+        author_name: "author".into(),
+        content: None,
+        attachment: vec![1, 2],
+    };
+    assert_eq!(
+        Some((&"author".to_string(), &None, &vec![1, 2])),
+        message.as_message()
+    );
+    assert_eq!(
+        Some(FieldValue::from("Message")),
+        message.property("__typename")
+    );
+    assert_eq!(
+        Some(FieldValue::from("author")),
+        message.property("authorName")
+    );
+    assert_eq!(Some(FieldValue::Null), message.property("content"));
+    assert_eq!(None, message.property("author_name"));
+    assert_eq!(None, message.property("attachment"));
+}
+
+#[test]
+fn raw_identifier_properties() {
+    #[derive(Debug, Clone, TrustfallVertex)]
+    enum Vertex {
+        Item {
+            r#type: String,
+            #[trustfall(rename = "isAsync")]
+            r#async: bool,
+        },
+    }
+
+    let item = Vertex::Item {
+        r#type: "book".into(),
+        r#async: false,
+    };
+    assert_eq!(Some(FieldValue::from("book")), item.property("type"));
+    assert_eq!(Some(FieldValue::from(false)), item.property("isAsync"));
+    assert_eq!(None, item.property("r#type"));
+    assert_eq!(None, item.property("async"));
+}
+
 #[test]
 fn ui() {
     let t = trybuild::TestCases::new();
@@ -0,0 +1,202 @@
+use std::{
+    io,
+    path::{Component, Path, PathBuf},
+    rc::Rc,
+};
+
+use trustfall_core::{
+    accessor_property,
+    interpreter::{
+        basic_adapter::BasicAdapter,
+        helpers::{resolve_coercion_with, resolve_neighbors_with, resolve_property_with},
+        ContextIterator, ContextOutcomeIterator, Typename, VertexIterator,
+    },
+    ir::{EdgeParameters, FieldValue},
+    schema::Schema,
+};
+
+use crate::{
+    vertex::{is_contained, Vertex},
+    SCHEMA,
+};
+
+/// An adapter querying the files and directories under a root directory.
+///
+/// Directories are read when the query reaches them, and file contents when the query
+/// first uses them. Entries that can't be read, such as broken symbolic links,
+/// are left out of the results.
+#[derive(Debug, Clone)]
+pub struct FilesystemAdapter {
+    root: PathBuf,
+    schema: Schema,
+}
+
+impl FilesystemAdapter {
+    /// Query the files and directories under the given directory.
+    ///
+    /// Fails if the directory doesn't exist or isn't a directory.
+    pub fn new(root: impl AsRef<Path>) -> io::Result<Self> {
+        let root = root.as_ref().canonicalize()?;
+        if !root.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a directory", root.display()),
+            ));
+        }
+        Ok(Self {
+            root,
+            schema: Schema::parse(SCHEMA).expect("schema is not valid"),
+        })
+    }
+
+    /// The schema of the files and directories this adapter queries.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// The absolute path of the directory this adapter queries.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl<'a> BasicAdapter<'a> for FilesystemAdapter {
+    type Vertex = Vertex;
+
+    fn resolve_starting_vertices(
+        &self,
+        edge_name: &str,
+        parameters: &EdgeParameters,
+    ) -> VertexIterator<'a, Self::Vertex> {
+        let root: Rc<Path> = Rc::from(self.root.as_path());
+        let vertex = match edge_name {
+            "Root" => Vertex::load(&root, PathBuf::new()),
+            "Path" => {
+                let path = Path::new(parameters["path"].as_str().expect("path is not a string"));
+                if is_contained(path) {
+                    // Normalize away `.` components, so paths are the same however they're reached.
+                    let path = path
+                        .components()
+                        .filter(|component| matches!(component, Component::Normal(_)))
+                        .collect();
+                    Vertex::load(&root, path)
+                } else {
+                    None
+                }
+            }
+            _ => unreachable!("unexpected starting edge: {edge_name}"),
+        };
+        Box::new(vertex.into_iter())
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'a, Self::Vertex>,
+        type_name: &str,
+        property_name: &str,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, FieldValue> {
+        match (type_name, property_name) {
+            // properties on Entry and its implementers
+            ("Entry" | "Directory" | "File", "name") => {
+                resolve_property_with(contexts, accessor_property!(as_entry, name))
+            }
+            ("Entry" | "Directory" | "File", "path") => {
+                resolve_property_with(contexts, accessor_property!(as_entry, path))
+            }
+            ("Entry" | "Directory" | "File", "size") => {
+                resolve_property_with(contexts, accessor_property!(as_entry, size))
+            }
+            ("Entry" | "Directory" | "File", "modified") => {
+                resolve_property_with(contexts, accessor_property!(as_entry, modified))
+            }
+            ("Entry" | "Directory" | "File", "hidden") => {
+                resolve_property_with(contexts, accessor_property!(as_entry, hidden))
+            }
+            ("Entry" | "Directory" | "File", "readonly") => {
+                resolve_property_with(contexts, accessor_property!(as_entry, readonly))
+            }
+            ("Entry" | "Directory" | "File", "symlink") => {
+                resolve_property_with(contexts, accessor_property!(as_entry, symlink))
+            }
+
+            // properties on File
+            ("File", "extension") => {
+                resolve_property_with(contexts, accessor_property!(as_file, extension))
+            }
+            ("File", "contents") => resolve_property_with(
+                contexts,
+                accessor_property!(as_file, contents, { contents.as_deref().into() }),
+            ),
+            ("File", "lineCount") => resolve_property_with(
+                contexts,
+                accessor_property!(as_file, contents, {
+                    contents
+                        .map(|contents| contents.lines().count() as u64)
+                        .into()
+                }),
+            ),
+
+            // properties on Line
+            ("Line", "number") => {
+                resolve_property_with(contexts, |vertex| vertex.as_line().unwrap().number.into())
+            }
+            ("Line", "contents") => resolve_property_with(contexts, |vertex| {
+                vertex.as_line().unwrap().contents.as_str().into()
+            }),
+            _ => unreachable!("unexpected property {type_name}.{property_name}"),
+        }
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: ContextIterator<'a, Self::Vertex>,
+        type_name: &str,
+        edge_name: &str,
+        _parameters: &EdgeParameters,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, VertexIterator<'a, Self::Vertex>> {
+        match (type_name, edge_name) {
+            ("Entry" | "Directory" | "File", "parent") => {
+                resolve_neighbors_with(contexts, |vertex| {
+                    let parent = vertex.as_entry().unwrap().parent();
+                    Box::new(parent.into_iter())
+                })
+            }
+            ("Directory", "entries") => resolve_neighbors_with(contexts, |vertex| {
+                let entries = vertex.as_directory().unwrap().entries();
+                Box::new(entries.into_iter())
+            }),
+            ("Directory", "subdirectories") => resolve_neighbors_with(contexts, |vertex| {
+                let entries = vertex.as_directory().unwrap().entries();
+                Box::new(
+                    entries
+                        .into_iter()
+                        .filter(|entry| matches!(entry, Vertex::Directory(_))),
+                )
+            }),
+            ("Directory", "files") => resolve_neighbors_with(contexts, |vertex| {
+                let entries = vertex.as_directory().unwrap().entries();
+                Box::new(
+                    entries
+                        .into_iter()
+                        .filter(|entry| matches!(entry, Vertex::File(_))),
+                )
+            }),
+            ("File", "lines") => resolve_neighbors_with(contexts, |vertex| {
+                let lines = vertex.as_file().unwrap().lines();
+                Box::new(lines.into_iter())
+            }),
+            _ => unreachable!("unexpected edge {type_name}.{edge_name}"),
+        }
+    }
+
+    fn resolve_coercion(
+        &self,
+        contexts: ContextIterator<'a, Self::Vertex>,
+        _type_name: &str,
+        coerce_to_type: &str,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, bool> {
+        // Only `Entry` has subtypes, and they have none of their own.
+        let coerce_to_type = coerce_to_type.to_string();
+        resolve_coercion_with(contexts, move |vertex| vertex.typename() == coerce_to_type)
+    }
+}
@@ -0,0 +1,50 @@
+//! Query files and directories with trustfall.
+//!
+//! [`FilesystemAdapter`] queries the files and directories under a root directory
+//! with the schema in [`SCHEMA`]: directories, files, and the lines of text files,
+//! along with their sizes, modification times, and other metadata.
+//!
+//! Directory trees are what `@recurse` is for, and questions about what's in them
+//! are what `@fold` is for. Since the entries of a directory, its subdirectories,
+//! and its parent are all edges, queries can walk down and up the tree:
+//!
+//! ```
+//! use std::{collections::BTreeMap, sync::Arc};
+//!
+//! use trustfall_core::{frontend::parse, interpreter::execution::interpret_ir, ir::FieldValue};
+//! use trustfall_filesystem::FilesystemAdapter;
+//!
+//! let root = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/project");
+//! let adapter = Arc::new(FilesystemAdapter::new(root).unwrap());
+//!
+//! // Every directory up to two levels deep, with how many Rust files it contains
+//! // and how many lines they have.
+//! let query = parse(adapter.schema(), r#"
+//! {
+//!     Root {
+//!         subdirectories @recurse(depth: 2) {
+//!             path @output
+//!
+//!             files @fold @transform(op: "count") @output(name: "rust_files") {
+//!                 extension @filter(op: "=", value: ["$rust"])
+//!                 lines: lineCount @output
+//!             }
+//!         }
+//!     }
+//! }"#).unwrap();
+//! let arguments = Arc::new(BTreeMap::from([("rust".into(), "rs".into())]));
+//! let rows: Vec<_> = interpret_ir(adapter, query, arguments).unwrap().collect();
+//!
+//! let paths: Vec<_> = rows.iter().map(|row| row["path"].clone()).collect();
+//! assert_eq!(vec![FieldValue::from("."), "src".into(), "src/util".into()], paths);
+//! let rust_files: Vec<_> = rows.iter().map(|row| row["rust_files"].clone()).collect();
+//! assert_eq!(vec![FieldValue::Uint64(0), 1u64.into(), 1u64.into()], rust_files);
+//! ```
+mod adapter;
+mod vertex;
+
+pub use adapter::FilesystemAdapter;
+pub use vertex::{Entry, Line, Vertex};
+
+/// The schema of the files and directories queried by [`FilesystemAdapter`].
+pub const SCHEMA: &str = include_str!("filesystem.graphql");
@@ -0,0 +1,211 @@
+use std::{
+    cell::OnceCell,
+    fs::{self, Metadata},
+    path::{Component, Path, PathBuf},
+    rc::Rc,
+    time::UNIX_EPOCH,
+};
+
+use trustfall_core::interpreter::Typename;
+
+/// A vertex of the filesystem schema.
+#[derive(Debug, Clone)]
+pub enum Vertex {
+    Directory(Rc<Entry>),
+    File(Rc<Entry>),
+    Line(Rc<Line>),
+}
+
+impl Vertex {
+    /// Load the entry at the given path relative to the root, if it exists and can be read.
+    pub(crate) fn load(root: &Rc<Path>, relative: PathBuf) -> Option<Self> {
+        let absolute = root.join(&relative);
+        let metadata = fs::metadata(&absolute).ok()?;
+        let symlink = fs::symlink_metadata(&absolute)
+            .map(|metadata| metadata.file_type().is_symlink())
+            .unwrap_or(false);
+        let entry = Rc::new(Entry {
+            root: root.clone(),
+            relative,
+            metadata,
+            symlink,
+            contents: OnceCell::new(),
+        });
+        if entry.metadata.is_dir() {
+            Some(Self::Directory(entry))
+        } else {
+            Some(Self::File(entry))
+        }
+    }
+
+    pub fn as_entry(&self) -> Option<&Entry> {
+        match self {
+            Self::Directory(entry) | Self::File(entry) => Some(entry),
+            Self::Line(_) => None,
+        }
+    }
+
+    pub fn as_directory(&self) -> Option<&Entry> {
+        match self {
+            Self::Directory(entry) => Some(entry),
+            _ => None,
+        }
+    }
+
+    pub fn as_file(&self) -> Option<&Entry> {
+        match self {
+            Self::File(entry) => Some(entry),
+            _ => None,
+        }
+    }
+
+    pub fn as_line(&self) -> Option<&Line> {
+        match self {
+            Self::Line(line) => Some(line),
+            _ => None,
+        }
+    }
+}
+
+impl Typename for Vertex {
+    fn typename(&self) -> &'static str {
+        match self {
+            Self::Directory(_) => "Directory",
+            Self::File(_) => "File",
+            Self::Line(_) => "Line",
+        }
+    }
+}
+
+/// A file or directory under the root directory.
+#[derive(Debug)]
+pub struct Entry {
+    root: Rc<Path>,
+    relative: PathBuf,
+    metadata: Metadata,
+    symlink: bool,
+
+    /// Read the first time the file's contents are needed.
+    contents: OnceCell<Option<Rc<str>>>,
+}
+
+impl Entry {
+    pub fn name(&self) -> String {
+        let absolute = self.absolute_path();
+        let name = match self.relative.file_name() {
+            Some(name) => Some(name),
+            None => absolute.file_name(),
+        };
+        name.map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    pub fn path(&self) -> String {
+        if self.relative.as_os_str().is_empty() {
+            ".".to_string()
+        } else {
+            self.relative.to_string_lossy().into_owned()
+        }
+    }
+
+    pub fn absolute_path(&self) -> PathBuf {
+        self.root.join(&self.relative)
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    pub fn size(&self) -> u64 {
+        self.metadata.len()
+    }
+
+    pub fn modified(&self) -> Option<u64> {
+        let modified = self.metadata.modified().ok()?;
+        modified
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|duration| duration.as_secs())
+    }
+
+    pub fn hidden(&self) -> bool {
+        self.relative
+            .file_name()
+            .map(|name| name.to_string_lossy().starts_with('.'))
+            .unwrap_or(false)
+    }
+
+    pub fn readonly(&self) -> bool {
+        self.metadata.permissions().readonly()
+    }
+
+    pub fn symlink(&self) -> bool {
+        self.symlink
+    }
+
+    pub fn extension(&self) -> Option<String> {
+        self.relative
+            .extension()
+            .map(|extension| extension.to_string_lossy().into_owned())
+    }
+
+    /// The file's contents, or `None` if it isn't valid UTF-8 or can't be read.
+    pub fn contents(&self) -> Option<Rc<str>> {
+        self.contents
+            .get_or_init(|| {
+                let bytes = fs::read(self.absolute_path()).ok()?;
+                String::from_utf8(bytes).ok().map(Rc::from)
+            })
+            .clone()
+    }
+
+    pub(crate) fn parent(&self) -> Option<Vertex> {
+        let parent = self.relative.parent()?;
+        Vertex::load(&self.root, parent.to_path_buf())
+    }
+
+    /// The directory's entries, in order of name.
+    pub(crate) fn entries(&self) -> Vec<Vertex> {
+        let Ok(entries) = fs::read_dir(self.absolute_path()) else {
+            return vec![];
+        };
+        let mut names: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.file_name()))
+            .collect();
+        names.sort();
+        names
+            .into_iter()
+            .filter_map(|name| Vertex::load(&self.root, self.relative.join(name)))
+            .collect()
+    }
+
+    pub(crate) fn lines(&self) -> Vec<Vertex> {
+        let Some(contents) = self.contents() else {
+            return vec![];
+        };
+        contents
+            .lines()
+            .enumerate()
+            .map(|(index, line)| {
+                Vertex::Line(Rc::new(Line {
+                    number: index as u64 + 1,
+                    contents: line.to_string(),
+                }))
+            })
+            .collect()
+    }
+}
+
+/// A line of a text file.
+#[derive(Debug)]
+pub struct Line {
+    pub number: u64,
+    pub contents: String,
+}
+
+/// Whether the path stays within the directory it's relative to,
+/// without being absolute or going up through `..`.
+pub(crate) fn is_contained(path: &Path) -> bool {
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
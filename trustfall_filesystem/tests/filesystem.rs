@@ -0,0 +1,201 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use trustfall_core::ir::FieldValue;
+use trustfall_filesystem::FilesystemAdapter;
+use trustfall_testing::{column, execute};
+
+const ROOT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/project");
+
+#[test]
+fn lists_entries_in_order_of_name() {
+    let adapter = Arc::new(FilesystemAdapter::new(ROOT).unwrap());
+    let query = r#"
+{
+    Root {
+        root: name @output
+        root_path: path @output
+
+        entries {
+            name @output
+            path @output
+            kind: __typename @output
+            hidden @output
+        }
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(vec![FieldValue::from("project"); 4], column(&rows, "root"));
+    assert_eq!(vec![FieldValue::from("."); 4], column(&rows, "root_path"));
+    assert_eq!(
+        vec![".hidden", "data.bin", "notes.txt", "src"]
+            .into_iter()
+            .map(FieldValue::from)
+            .collect::<Vec<_>>(),
+        column(&rows, "name"),
+    );
+    assert_eq!(column(&rows, "name"), column(&rows, "path"));
+    assert_eq!(
+        vec!["File", "File", "File", "Directory"]
+            .into_iter()
+            .map(FieldValue::from)
+            .collect::<Vec<_>>(),
+        column(&rows, "kind"),
+    );
+    assert_eq!(
+        vec![true, false, false, false]
+            .into_iter()
+            .map(FieldValue::from)
+            .collect::<Vec<_>>(),
+        column(&rows, "hidden"),
+    );
+}
+
+#[test]
+fn resolves_file_metadata_and_contents() {
+    let adapter = Arc::new(FilesystemAdapter::new(ROOT).unwrap());
+    let query = r#"
+{
+    Root {
+        files {
+            name @filter(op: "one_of", value: ["$names"]) @output
+            size @output
+            modified @output
+            symlink @output
+            extension @output
+            contents @output
+            lineCount @output
+        }
+    }
+}"#;
+    let arguments = BTreeMap::from([(
+        "names".into(),
+        FieldValue::List(vec!["data.bin".into(), "notes.txt".into()]),
+    )]);
+    let rows = execute(adapter.schema(), adapter.clone(), query, arguments);
+    assert_eq!(2, rows.len());
+
+    let (binary, text) = (&rows[0], &rows[1]);
+    assert_eq!(FieldValue::Uint64(9), binary["size"]);
+    assert_eq!(FieldValue::from("bin"), binary["extension"]);
+    assert_eq!(FieldValue::Null, binary["contents"]);
+    assert_eq!(FieldValue::Null, binary["lineCount"]);
+
+    let contents = "Project notes.\n\n  Keep the build green.  \n";
+    assert_eq!(FieldValue::Uint64(contents.len() as u64), text["size"]);
+    assert_eq!(FieldValue::from("txt"), text["extension"]);
+    assert_eq!(FieldValue::from(contents), text["contents"]);
+    assert_eq!(FieldValue::Uint64(3), text["lineCount"]);
+
+    for row in &rows {
+        assert_eq!(FieldValue::Boolean(false), row["symlink"]);
+        assert!(
+            matches!(row["modified"], FieldValue::Uint64(t) if t > 0),
+            "{row:?}"
+        );
+    }
+}
+
+#[test]
+fn resolves_lines_of_text_files() {
+    let adapter = Arc::new(FilesystemAdapter::new(ROOT).unwrap());
+    let query = r#"
+{
+    Path(path: "./notes.txt") {
+        ... on File {
+            path @output
+
+            lines {
+                number @output
+                contents @output
+            }
+        }
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(
+        vec![FieldValue::from("notes.txt"); 3],
+        column(&rows, "path")
+    );
+    assert_eq!(
+        vec![1u64, 2, 3]
+            .into_iter()
+            .map(FieldValue::from)
+            .collect::<Vec<_>>(),
+        column(&rows, "number"),
+    );
+    assert_eq!(
+        vec!["Project notes.", "", "  Keep the build green.  "]
+            .into_iter()
+            .map(FieldValue::from)
+            .collect::<Vec<_>>(),
+        column(&rows, "contents"),
+    );
+}
+
+#[test]
+fn recurses_down_and_up_the_tree() {
+    let adapter = Arc::new(FilesystemAdapter::new(ROOT).unwrap());
+    let query = r#"
+{
+    Root {
+        subdirectories @recurse(depth: 3) {
+            files @fold @transform(op: "count") @filter(op: ">", value: ["$zero"]) {
+                path @output
+            }
+        }
+    }
+}"#;
+    let arguments = BTreeMap::from([("zero".into(), FieldValue::Int64(0))]);
+    let rows = execute(adapter.schema(), adapter.clone(), query, arguments);
+    assert_eq!(
+        vec![
+            FieldValue::List(vec![
+                ".hidden".into(),
+                "data.bin".into(),
+                "notes.txt".into()
+            ]),
+            FieldValue::List(vec!["src/main.rs".into()]),
+            FieldValue::List(vec!["src/util/mod.rs".into()]),
+        ],
+        column(&rows, "path"),
+    );
+
+    let query = r#"
+{
+    Path(path: "src/util/mod.rs") {
+        parent {
+            parent @recurse(depth: 5) {
+                ancestor: path @output
+            }
+        }
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(
+        vec!["src/util", "src", "."]
+            .into_iter()
+            .map(FieldValue::from)
+            .collect::<Vec<_>>(),
+        column(&rows, "ancestor"),
+    );
+}
+
+#[test]
+fn paths_outside_the_root_match_nothing() {
+    let adapter = Arc::new(FilesystemAdapter::new(ROOT).unwrap());
+    for path in ["..", "src/../../project", "/etc", "missing.txt"] {
+        let query = format!(r#"{{ Path(path: "{path}") {{ name @output }} }}"#);
+        assert!(
+            execute(adapter.schema(), adapter.clone(), &query, BTreeMap::new()).is_empty(),
+            "{path}"
+        );
+    }
+}
+
+#[test]
+fn root_must_be_a_directory() {
+    let root = std::path::Path::new(ROOT);
+    assert!(FilesystemAdapter::new(root.join("missing")).is_err());
+    let error = FilesystemAdapter::new(root.join("notes.txt")).unwrap_err();
+    assert_eq!(std::io::ErrorKind::InvalidInput, error.kind());
+}
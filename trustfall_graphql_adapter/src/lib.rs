@@ -0,0 +1,306 @@
+//! Query existing GraphQL APIs with trustfall, adding trustfall's filtering, recursion,
+//! and folding on top of what the API itself supports.
+//!
+//! Given the SDL of an upstream GraphQL API, [`GraphQLAdapter`] derives a trustfall schema
+//! with a vertex type per object and interface type, and translates trustfall queries into
+//! queries against the upstream API. Each starting edge is fetched with a single upstream
+//! request, which also fetches every neighbor the query will traverse from its vertices,
+//! including those in `@optional`, `@fold`, and `@recurse` blocks and type coercions.
+//!
+//! ```no_run
+//! use std::{collections::BTreeMap, sync::Arc};
+//!
+//! use trustfall_core::{frontend::parse, interpreter::execution::interpret_ir};
+//! use trustfall_graphql_adapter::{GraphQLAdapter, HttpTransport};
+//!
+//! let sdl = r#"
+//! type Query {
+//!     countries: [Country!]!
+//! }
+//! type Country {
+//!     name: String!
+//!     languages: [Language!]!
+//! }
+//! type Language {
+//!     name: String
+//! }
+//! "#;
+//! let transport = HttpTransport::new("https://countries.trevorblades.com/graphql");
+//! let adapter = GraphQLAdapter::new(sdl, transport).unwrap();
+//!
+//! // Countries with more than two official languages.
+//! let query = parse(adapter.schema(), r#"
+//! {
+//!     countries {
+//!         name @output
+//!         languages @fold @transform(op: "count") @filter(op: ">", value: ["$min"]) {
+//!             language: name @output
+//!         }
+//!     }
+//! }"#).unwrap();
+//! let arguments = Arc::new(BTreeMap::from([("min".into(), 2.into())]));
+//! for row in interpret_ir(Arc::new(adapter), query, arguments).unwrap() {
+//!     println!("{row:?}");
+//! }
+//! ```
+//!
+//! Since the trustfall query's filters are applied after fetching, upstream queries fetch
+//! all of each vertex's properties, and all the vertices reachable through the query's edges.
+//! Use the upstream API's own arguments, exposed as edge parameters, to limit what's fetched.
+use std::{collections::BTreeSet, sync::Arc};
+
+use async_graphql_parser::types::{BaseType, Type};
+use serde_json::{Map, Value};
+use trustfall_core::{
+    interpreter::{
+        helpers::{resolve_coercion_with, resolve_neighbors_with, resolve_property_with},
+        Adapter, ContextIterator, ContextOutcomeIterator, ResolveEdgeInfo, ResolveInfo,
+        VertexIterator,
+    },
+    ir::{EdgeParameters, FieldValue},
+    schema::Schema,
+};
+
+mod query;
+mod schema;
+mod transport;
+
+use query::{EdgeAliases, QueryBuilder, ROOT_ALIAS};
+use schema::UpstreamSchema;
+
+pub use schema::UpstreamSchemaError;
+#[cfg(feature = "http")]
+pub use transport::HttpTransport;
+pub use transport::{GraphQLRequest, Transport, TransportError};
+
+/// A vertex fetched from the upstream API, along with its prefetched neighbors.
+#[derive(Debug, Clone)]
+pub struct Vertex(Arc<VertexData>);
+
+#[derive(Debug)]
+struct VertexData {
+    typename: String,
+    fields: Map<String, Value>,
+    aliases: Arc<EdgeAliases>,
+}
+
+impl Vertex {
+    /// The name of the vertex's type in the upstream schema.
+    pub fn typename(&self) -> &str {
+        &self.0.typename
+    }
+
+    /// The vertex's fields as returned by the upstream API.
+    pub fn fields(&self) -> &Map<String, Value> {
+        &self.0.fields
+    }
+}
+
+/// An adapter running trustfall queries against an upstream GraphQL API.
+///
+/// # Panics
+///
+/// Trustfall adapters can't return errors, so resolving starting vertices panics
+/// if the upstream request fails, if the upstream API returns errors, or if its response
+/// doesn't match its schema.
+#[derive(Debug)]
+pub struct GraphQLAdapter<T> {
+    upstream: Arc<UpstreamSchema>,
+    schema: Schema,
+    transport: T,
+}
+
+impl<T: Transport> GraphQLAdapter<T> {
+    /// Make an adapter for the upstream API with the given SDL, which sends requests
+    /// with the given transport.
+    ///
+    /// See [`GraphQLAdapter::schema`] for how the upstream schema maps to a trustfall schema.
+    pub fn new(upstream_sdl: &str, transport: T) -> Result<Self, UpstreamSchemaError> {
+        let (upstream, schema) = UpstreamSchema::parse(upstream_sdl)?;
+        Ok(Self {
+            upstream: Arc::new(upstream),
+            schema,
+            transport,
+        })
+    }
+
+    /// The trustfall schema derived from the upstream API's schema, to parse queries against.
+    ///
+    /// Object and interface types become vertex types, and fields of the query type that
+    /// return them become the starting edges. Fields returning scalars and enums become
+    /// properties, and fields returning object or interface types become edges, whose
+    /// scalar and enum arguments become edge parameters.
+    ///
+    /// Trustfall has no equivalent of some GraphQL features, so these are left out:
+    /// - union and input object types, and fields returning unions or nested lists of vertices;
+    /// - fields with required arguments of input object types, and optional ones of those
+    ///   arguments on other fields;
+    /// - arguments of property fields, which are always fetched without arguments,
+    ///   and property fields with required arguments;
+    /// - mutations, subscriptions, and type extensions.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<'vertex, T: Transport> Adapter<'vertex> for GraphQLAdapter<T> {
+    type Vertex = Vertex;
+
+    fn resolve_starting_vertices(
+        &self,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        resolve_info: &ResolveInfo,
+    ) -> VertexIterator<'vertex, Self::Vertex> {
+        let (request, aliases) =
+            QueryBuilder::new(&self.upstream).build(edge_name, parameters, resolve_info);
+        let mut data = self
+            .transport
+            .execute(&request)
+            .and_then(transport::response_data)
+            .unwrap_or_else(|e| panic!("{e}"));
+
+        let root = data.remove(ROOT_ALIAS).unwrap_or(Value::Null);
+        Box::new(to_vertices(root, &Arc::new(aliases)).into_iter())
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        type_name: &Arc<str>,
+        property_name: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, FieldValue> {
+        if property_name.as_ref() == "__typename" {
+            return resolve_property_with(contexts, |vertex| vertex.typename().into());
+        }
+
+        let upstream = self.upstream.clone();
+        let property_type = upstream
+            .property_type(type_name, property_name)
+            .expect("not a property of the upstream type")
+            .clone();
+        let property_name = property_name.clone();
+        resolve_property_with(contexts, move |vertex| {
+            match vertex.fields().get(property_name.as_ref()) {
+                Some(value) => to_field_value(value, &property_type, &upstream.enums),
+                None => FieldValue::Null,
+            }
+        })
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        _type_name: &Arc<str>,
+        _edge_name: &Arc<str>,
+        _parameters: &EdgeParameters,
+        resolve_info: &ResolveEdgeInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, VertexIterator<'vertex, Self::Vertex>> {
+        let eid = resolve_info.eid();
+        resolve_neighbors_with(contexts, move |vertex| {
+            let neighbors = vertex
+                .0
+                .aliases
+                .get(&eid)
+                .and_then(|alias| vertex.fields().get(alias))
+                .cloned()
+                .unwrap_or(Value::Null);
+            Box::new(to_vertices(neighbors, &vertex.0.aliases).into_iter())
+        })
+    }
+
+    fn resolve_coercion(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        _type_name: &Arc<str>,
+        coerce_to_type: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, bool> {
+        let subtypes: BTreeSet<String> = self
+            .schema
+            .subtypes(coerce_to_type)
+            .expect("not a type in the schema")
+            .map(str::to_string)
+            .collect();
+        resolve_coercion_with(contexts, move |vertex| subtypes.contains(vertex.typename()))
+    }
+}
+
+/// The vertices in an upstream field's value: none for `null`, one for an object,
+/// or the items of a list, skipping `null` items.
+fn to_vertices(value: Value, aliases: &Arc<EdgeAliases>) -> Vec<Vertex> {
+    let items = match value {
+        Value::Null => vec![],
+        Value::Array(items) => items,
+        value => vec![value],
+    };
+    items
+        .into_iter()
+        .filter_map(|item| match item {
+            Value::Null => None,
+            Value::Object(mut fields) => {
+                let typename = match fields.remove("__typename") {
+                    Some(Value::String(typename)) => typename,
+                    _ => panic!("upstream API returned a vertex without __typename: {fields:?}"),
+                };
+                Some(Vertex(Arc::new(VertexData {
+                    typename,
+                    fields,
+                    aliases: aliases.clone(),
+                })))
+            }
+            item => panic!("upstream API returned a non-object value for a vertex: {item}"),
+        })
+        .collect()
+}
+
+fn to_field_value(value: &Value, value_type: &Type, enums: &BTreeSet<String>) -> FieldValue {
+    match (&value_type.base, value) {
+        (_, Value::Null) => FieldValue::Null,
+        (BaseType::List(item_type), Value::Array(items)) => FieldValue::List(
+            items
+                .iter()
+                .map(|item| to_field_value(item, item_type, enums))
+                .collect(),
+        ),
+        (BaseType::Named(name), value) => match (name.as_str(), value) {
+            ("Float", Value::Number(number)) => {
+                FieldValue::Float64(number.as_f64().expect("number is not a valid float"))
+            }
+            ("ID", Value::Number(number)) => FieldValue::String(number.to_string()),
+            (name, Value::String(s)) if enums.contains(name) => FieldValue::Enum(s.clone()),
+            ("Int" | "Float" | "String" | "ID" | "Boolean", Value::Array(_) | Value::Object(_)) => {
+                panic!("upstream API returned {value} for a value of type {value_type}")
+            }
+            _ => json_to_field_value(value),
+        },
+        (BaseType::List(_), value) => {
+            panic!("upstream API returned {value} for a value of type {value_type}")
+        }
+    }
+}
+
+/// Values of custom scalar types may be any JSON value. Objects are kept as JSON text.
+fn json_to_field_value(value: &Value) -> FieldValue {
+    match value {
+        Value::Null => FieldValue::Null,
+        Value::Bool(b) => FieldValue::Boolean(*b),
+        Value::Number(number) => {
+            if let Some(n) = number.as_i64() {
+                FieldValue::Int64(n)
+            } else if let Some(n) = number.as_u64() {
+                FieldValue::Uint64(n)
+            } else {
+                FieldValue::Float64(number.as_f64().expect("number is not a valid float"))
+            }
+        }
+        Value::String(s) => FieldValue::String(s.clone()),
+        Value::Array(items) => FieldValue::List(items.iter().map(json_to_field_value).collect()),
+        Value::Object(_) => FieldValue::String(value.to_string()),
+    }
+}
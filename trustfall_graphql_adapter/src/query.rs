@@ -0,0 +1,186 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use async_graphql_parser::types::Type;
+use serde_json::{Map, Value};
+use trustfall_core::{
+    interpreter::VertexInfo,
+    ir::{EdgeParameters, Eid, FieldValue, TransparentValue},
+};
+
+use crate::{schema::UpstreamSchema, transport::GraphQLRequest};
+
+/// The response key of the starting edge's field in upstream queries.
+pub(crate) const ROOT_ALIAS: &str = "root";
+
+/// The response keys under which each edge's neighbors are found in the upstream response.
+///
+/// Every place where the query uses an edge gets its own alias, so that the same field
+/// can be fetched with different arguments. Its neighbors are under the same alias
+/// at every level of a `@recurse`.
+pub(crate) type EdgeAliases = BTreeMap<Eid, String>;
+
+/// Builds a single upstream query fetching everything the trustfall query will traverse
+/// from the vertices of a starting edge.
+pub(crate) struct QueryBuilder<'a> {
+    upstream: &'a UpstreamSchema,
+    variable_definitions: Vec<String>,
+    variables: Map<String, Value>,
+    aliases: EdgeAliases,
+}
+
+impl<'a> QueryBuilder<'a> {
+    pub(crate) fn new(upstream: &'a UpstreamSchema) -> Self {
+        Self {
+            upstream,
+            variable_definitions: vec![],
+            variables: Map::new(),
+            aliases: BTreeMap::new(),
+        }
+    }
+
+    /// The request for the starting edge's vertices, and the aliases of the edges
+    /// whose neighbors it prefetches.
+    pub(crate) fn build(
+        mut self,
+        edge_name: &str,
+        parameters: &EdgeParameters,
+        info: &impl VertexInfo,
+    ) -> (GraphQLRequest, EdgeAliases) {
+        let edge = self
+            .upstream
+            .edge(&self.upstream.query_type, edge_name)
+            .expect("not a starting edge of the upstream schema");
+        let arguments = self.arguments(&edge.arguments, parameters);
+        let selection = self.selection(&edge.target_type, info);
+
+        let mut query = "query".to_string();
+        if !self.variable_definitions.is_empty() {
+            write!(query, "({})", self.variable_definitions.join(", ")).expect("write failed");
+        }
+        write!(
+            query,
+            " {{ {ROOT_ALIAS}: {edge_name}{arguments} {selection} }}"
+        )
+        .expect("write failed");
+
+        let request = GraphQLRequest {
+            query,
+            variables: self.variables,
+        };
+        (request, self.aliases)
+    }
+
+    /// The selection set fetching a vertex of the given type, and all the neighbors
+    /// the query will reach from it.
+    ///
+    /// All properties are fetched, since a vertex's [`VertexInfo`] doesn't say which
+    /// properties the query will use.
+    fn selection(&mut self, type_name: &str, info: &impl VertexInfo) -> String {
+        let mut fields = vec!["__typename".to_string()];
+        self.fields(type_name, info, &mut fields);
+        format!("{{ {} }}", fields.join(" "))
+    }
+
+    fn fields(&mut self, type_name: &str, info: &impl VertexInfo, fields: &mut Vec<String>) {
+        let upstream = self.upstream;
+        fields.extend(upstream.types[type_name].properties.keys().cloned());
+
+        // Properties and edges of the type the vertex is coerced to have to be selected
+        // in a fragment on that type.
+        if let Some(coerced_to) = info.coerced_to_type() {
+            if coerced_to.as_ref() != type_name {
+                let mut coerced_fields = vec![];
+                self.fields(coerced_to, info, &mut coerced_fields);
+                fields.push(format!(
+                    "... on {coerced_to} {{ {} }}",
+                    coerced_fields.join(" ")
+                ));
+                return;
+            }
+        }
+
+        for (edge_name, edge) in &upstream.types[type_name].edges {
+            for edge_info in info.edges_with_name(edge_name) {
+                let alias = format!("e{}", self.aliases.len());
+                self.aliases.insert(edge_info.eid(), alias.clone());
+                let arguments = self.arguments(&edge.arguments, edge_info.parameters());
+                let field = format!("{alias}: {edge_name}{arguments}");
+
+                let destination = edge_info.destination();
+                match edge_info.recursive() {
+                    None => {
+                        let selection = self.selection(&edge.target_type, destination);
+                        fields.push(format!("{field} {selection}"));
+                    }
+                    Some(recursive) => {
+                        // Recursing to depth N starts with the vertex itself, so the fields
+                        // the query needs from the neighbors are also needed here.
+                        let mut neighbor_fields = vec!["__typename".to_string()];
+                        self.fields(&edge.target_type, destination, &mut neighbor_fields);
+                        if edge.target_type != type_name {
+                            fields.push(format!(
+                                "... on {} {{ {} }}",
+                                edge.target_type,
+                                neighbor_fields.join(" ")
+                            ));
+                        } else {
+                            for field in &neighbor_fields {
+                                if !fields.contains(field) {
+                                    fields.push(field.clone());
+                                }
+                            }
+                        }
+
+                        let neighbor_fields = neighbor_fields.join(" ");
+                        let mut selection = String::new();
+                        for _ in 0..recursive.depth.get() {
+                            let nested = if selection.is_empty() {
+                                String::new()
+                            } else {
+                                let nested = format!(" {field} {selection}");
+                                match &recursive.coerce_to {
+                                    Some(coerce_to) => format!(" ... on {coerce_to} {{{nested} }}"),
+                                    None => nested,
+                                }
+                            };
+                            selection = format!("{{ {neighbor_fields}{nested} }}");
+                        }
+                        fields.push(format!("{field} {selection}"));
+                    }
+                }
+            }
+        }
+    }
+
+    /// The arguments of an upstream field, passed as variables.
+    ///
+    /// Null parameter values are left out, so the upstream API applies its own defaults.
+    fn arguments(
+        &mut self,
+        arguments: &BTreeMap<String, Type>,
+        parameters: &EdgeParameters,
+    ) -> String {
+        let mut rendered = vec![];
+        for (name, argument_type) in arguments {
+            let value = match parameters.get(name) {
+                None | Some(FieldValue::Null) => continue,
+                Some(value) => value,
+            };
+            let variable = format!("v{}", self.variables.len());
+            self.variable_definitions
+                .push(format!("${variable}: {argument_type}"));
+            self.variables.insert(
+                variable.clone(),
+                serde_json::to_value(TransparentValue::from(value.clone()))
+                    .expect("failed to serialize parameter value"),
+            );
+            rendered.push(format!("{name}: ${variable}"));
+        }
+
+        if rendered.is_empty() {
+            String::new()
+        } else {
+            format!("({})", rendered.join(", "))
+        }
+    }
+}
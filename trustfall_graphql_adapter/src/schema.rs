@@ -0,0 +1,325 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use async_graphql_parser::{
+    parse_schema,
+    types::{
+        BaseType, ConstDirective, FieldDefinition, Type, TypeDefinition, TypeKind,
+        TypeSystemDefinition,
+    },
+};
+use trustfall_core::{
+    ir::FieldValue,
+    schema::{
+        builder::{SchemaBuilder, Ty},
+        error::InvalidSchemaError,
+        Schema,
+    },
+};
+
+const BUILTIN_SCALARS: [&str; 5] = ["Int", "Float", "String", "Boolean", "ID"];
+const RESERVED_PREFIX: &str = "__";
+
+/// Errors from making a trustfall schema out of an upstream GraphQL API's SDL.
+#[non_exhaustive]
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum UpstreamSchemaError {
+    #[error("The upstream schema is not valid GraphQL SDL: {0}")]
+    ParseError(String),
+
+    #[error("The upstream schema does not define its query type \"{0}\".")]
+    MissingQueryType(String),
+
+    #[error("The trustfall schema derived from the upstream schema is not valid")]
+    InvalidSchema(#[from] InvalidSchemaError),
+}
+
+/// What the adapter needs to know about the upstream API's types to query it.
+#[derive(Debug, Clone)]
+pub(crate) struct UpstreamSchema {
+    pub(crate) query_type: String,
+    pub(crate) types: BTreeMap<String, UpstreamType>,
+    pub(crate) enums: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct UpstreamType {
+    /// Property fields without required arguments, which are fetched for every vertex.
+    pub(crate) properties: BTreeMap<String, Type>,
+    pub(crate) edges: BTreeMap<String, UpstreamEdge>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct UpstreamEdge {
+    pub(crate) target_type: String,
+    /// The upstream types of the field's arguments that are also edge parameters.
+    pub(crate) arguments: BTreeMap<String, Type>,
+}
+
+enum FieldShape {
+    Property,
+    Edge(String),
+    Unsupported,
+}
+
+impl UpstreamSchema {
+    /// Derive a trustfall schema from the upstream API's SDL, along with the information
+    /// needed to translate traversals of its types into upstream queries.
+    ///
+    /// See [`GraphQLAdapter::schema`](crate::GraphQLAdapter::schema) for how upstream types
+    /// and fields become vertex types, properties, and edges.
+    pub(crate) fn parse(sdl: &str) -> Result<(Self, Schema), UpstreamSchemaError> {
+        let document =
+            parse_schema(sdl).map_err(|e| UpstreamSchemaError::ParseError(e.to_string()))?;
+
+        let mut query_type = "Query".to_string();
+        let mut definitions: BTreeMap<String, TypeDefinition> = BTreeMap::new();
+        for definition in document.definitions {
+            match definition {
+                TypeSystemDefinition::Schema(schema) if !schema.node.extend => {
+                    if let Some(query) = schema.node.query {
+                        query_type = query.node.to_string();
+                    }
+                }
+                TypeSystemDefinition::Type(defn) if !defn.node.extend => {
+                    let name = defn.node.name.node.to_string();
+                    if !name.starts_with(RESERVED_PREFIX) {
+                        definitions.insert(name, defn.node);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !matches!(
+            definitions.get(&query_type).map(|defn| &defn.kind),
+            Some(TypeKind::Object(_))
+        ) {
+            return Err(UpstreamSchemaError::MissingQueryType(query_type));
+        }
+
+        let mut upstream = UpstreamSchema {
+            query_type: query_type.clone(),
+            types: BTreeMap::new(),
+            enums: BTreeSet::new(),
+        };
+        let mut builder = SchemaBuilder::new().query_type_name(&query_type);
+
+        for (name, defn) in &definitions {
+            match &defn.kind {
+                TypeKind::Scalar if !BUILTIN_SCALARS.contains(&name.as_str()) => {
+                    builder = builder.scalar(name);
+                }
+                TypeKind::Enum(enum_type) => {
+                    upstream.enums.insert(name.clone());
+                    builder = builder.enum_type(
+                        name,
+                        enum_type
+                            .values
+                            .iter()
+                            .map(|value| value.node.value.node.to_string()),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        for (name, defn) in &definitions {
+            let (fields, implements) = match &defn.kind {
+                TypeKind::Object(object) => (&object.fields, &object.implements),
+                TypeKind::Interface(interface) => (&interface.fields, &interface.implements),
+                _ => continue,
+            };
+            let is_query_type = name == &query_type;
+            if !is_query_type {
+                builder = match defn.kind {
+                    TypeKind::Interface(_) => builder.interface(name),
+                    _ => builder.vertex_type(name),
+                };
+                if let Some(description) = &defn.description {
+                    builder = builder.description(&description.node);
+                }
+                for interface in implements {
+                    if matches!(
+                        definitions
+                            .get(interface.node.as_str())
+                            .map(|defn| &defn.kind),
+                        Some(TypeKind::Interface(_))
+                    ) {
+                        builder = builder.implements(interface.node.as_str());
+                    }
+                }
+            }
+
+            let mut upstream_type = UpstreamType::default();
+            for field in fields {
+                let field = &field.node;
+                let field_name = field.name.node.as_str();
+                if field_name.starts_with(RESERVED_PREFIX) {
+                    continue;
+                }
+                let field_type = &field.ty.node;
+
+                match field_shape(&definitions, field) {
+                    FieldShape::Property if !is_query_type => {
+                        builder = builder.property(field_name, to_ty(field_type));
+                        builder = describe_field(builder, field);
+                        upstream_type
+                            .properties
+                            .insert(field_name.to_string(), field_type.clone());
+                    }
+                    FieldShape::Edge(target_type) => {
+                        builder = if is_query_type {
+                            builder.root_edge(field_name, to_ty(field_type))
+                        } else {
+                            builder.edge(field_name, to_ty(field_type))
+                        };
+                        builder = describe_field(builder, field);
+
+                        let mut arguments = BTreeMap::new();
+                        for argument in &field.arguments {
+                            let argument = &argument.node;
+                            let argument_type = &argument.ty.node;
+                            if !is_scalar_or_enum(&definitions, argument_type) {
+                                continue;
+                            }
+                            let argument_name = argument.name.node.as_str();
+                            let default = argument
+                                .default_value
+                                .as_ref()
+                                .and_then(|value| FieldValue::try_from(value.node.clone()).ok())
+                                .filter(|value| !matches!(value, FieldValue::Null));
+                            builder = match default {
+                                Some(default) => builder.parameter_with_default(
+                                    argument_name,
+                                    to_ty(argument_type),
+                                    default,
+                                ),
+                                None => builder.parameter(argument_name, to_ty(argument_type)),
+                            };
+                            arguments.insert(argument_name.to_string(), argument_type.clone());
+                        }
+                        upstream_type.edges.insert(
+                            field_name.to_string(),
+                            UpstreamEdge {
+                                target_type,
+                                arguments,
+                            },
+                        );
+                    }
+                    FieldShape::Property | FieldShape::Unsupported => {}
+                }
+            }
+            upstream.types.insert(name.clone(), upstream_type);
+        }
+
+        let schema = builder.build()?;
+        Ok((upstream, schema))
+    }
+
+    pub(crate) fn edge(&self, type_name: &str, edge_name: &str) -> Option<&UpstreamEdge> {
+        self.types.get(type_name)?.edges.get(edge_name)
+    }
+
+    pub(crate) fn property_type(&self, type_name: &str, property_name: &str) -> Option<&Type> {
+        self.types.get(type_name)?.properties.get(property_name)
+    }
+}
+
+fn field_shape(
+    definitions: &BTreeMap<String, TypeDefinition>,
+    field: &FieldDefinition,
+) -> FieldShape {
+    let field_type = &field.ty.node;
+    let (base_name, list_depth) = base_type_name(field_type);
+
+    let kind = match BUILTIN_SCALARS.contains(&base_name) {
+        true => &TypeKind::Scalar,
+        false => match definitions.get(base_name) {
+            Some(defn) => &defn.kind,
+            None => return FieldShape::Unsupported,
+        },
+    };
+    match kind {
+        TypeKind::Scalar | TypeKind::Enum(_) => {
+            let has_required_argument = field.arguments.iter().any(|argument| {
+                !argument.node.ty.node.nullable && argument.node.default_value.is_none()
+            });
+            if has_required_argument {
+                FieldShape::Unsupported
+            } else {
+                FieldShape::Property
+            }
+        }
+        TypeKind::Object(_) | TypeKind::Interface(_) if list_depth <= 1 => {
+            let has_required_input_object = field.arguments.iter().any(|argument| {
+                let argument = &argument.node;
+                !argument.ty.node.nullable
+                    && argument.default_value.is_none()
+                    && !is_scalar_or_enum(definitions, &argument.ty.node)
+            });
+            if has_required_input_object {
+                FieldShape::Unsupported
+            } else {
+                FieldShape::Edge(base_name.to_string())
+            }
+        }
+        _ => FieldShape::Unsupported,
+    }
+}
+
+fn is_scalar_or_enum(definitions: &BTreeMap<String, TypeDefinition>, value_type: &Type) -> bool {
+    let (base_name, _) = base_type_name(value_type);
+    BUILTIN_SCALARS.contains(&base_name)
+        || matches!(
+            definitions.get(base_name).map(|defn| &defn.kind),
+            Some(TypeKind::Scalar | TypeKind::Enum(_))
+        )
+}
+
+/// The name of the type at the bottom of any lists, and how many lists it's nested in.
+pub(crate) fn base_type_name(value_type: &Type) -> (&str, usize) {
+    match &value_type.base {
+        BaseType::Named(name) => (name.as_str(), 0),
+        BaseType::List(inner) => {
+            let (name, depth) = base_type_name(inner);
+            (name, depth + 1)
+        }
+    }
+}
+
+fn to_ty(value_type: &Type) -> Ty {
+    let ty = match &value_type.base {
+        BaseType::Named(name) => Ty::named(name),
+        BaseType::List(inner) => to_ty(inner).list(),
+    };
+    if value_type.nullable {
+        ty
+    } else {
+        ty.non_null()
+    }
+}
+
+fn describe_field(mut builder: SchemaBuilder, field: &FieldDefinition) -> SchemaBuilder {
+    if let Some(description) = &field.description {
+        builder = builder.description(&description.node);
+    }
+    if let Some(deprecated) = find_directive(&field.directives, "deprecated") {
+        let reason = deprecated
+            .get_argument("reason")
+            .and_then(|reason| match &reason.node {
+                async_graphql_value::ConstValue::String(reason) => Some(reason.as_str()),
+                _ => None,
+            });
+        builder = builder.deprecated(reason);
+    }
+    builder
+}
+
+fn find_directive<'a>(
+    directives: &'a [async_graphql_parser::Positioned<ConstDirective>],
+    name: &str,
+) -> Option<&'a ConstDirective> {
+    directives
+        .iter()
+        .map(|directive| &directive.node)
+        .find(|directive| directive.name.node.as_str() == name)
+}
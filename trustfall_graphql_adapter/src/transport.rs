@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// A request to the upstream GraphQL API, in the shape of the standard POST body.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphQLRequest {
+    pub query: String,
+
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub variables: Map<String, Value>,
+}
+
+/// Errors from sending requests to the upstream GraphQL API.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TransportError {
+    #[error("The request to the upstream API failed: {0}")]
+    RequestFailed(String),
+
+    #[error("The upstream API's response is not a valid GraphQL response: {0}")]
+    InvalidResponse(String),
+
+    #[error("The upstream API returned errors: {}", .0.join("; "))]
+    GraphQLErrors(Vec<String>),
+}
+
+/// Sends requests to the upstream GraphQL API.
+///
+/// Implementations return the response body, with the query's results under `data`
+/// and any errors under `errors`, as in the GraphQL over HTTP spec.
+/// Closures taking a [`GraphQLRequest`] are also transports, which is convenient in tests.
+pub trait Transport {
+    fn execute(&self, request: &GraphQLRequest) -> Result<Value, TransportError>;
+}
+
+impl<F> Transport for F
+where
+    F: Fn(&GraphQLRequest) -> Result<Value, TransportError>,
+{
+    fn execute(&self, request: &GraphQLRequest) -> Result<Value, TransportError> {
+        self(request)
+    }
+}
+
+/// The `data` of a GraphQL response, or its errors if it has any.
+pub(crate) fn response_data(response: Value) -> Result<Map<String, Value>, TransportError> {
+    let Value::Object(mut response) = response else {
+        return Err(TransportError::InvalidResponse(
+            "the response is not a JSON object".to_string(),
+        ));
+    };
+
+    match response.remove("errors") {
+        None | Some(Value::Null) => {}
+        Some(Value::Array(errors)) if errors.is_empty() => {}
+        Some(Value::Array(errors)) => {
+            let messages = errors
+                .iter()
+                .map(|error| match error.get("message") {
+                    Some(Value::String(message)) => message.clone(),
+                    _ => error.to_string(),
+                })
+                .collect();
+            return Err(TransportError::GraphQLErrors(messages));
+        }
+        Some(_) => {
+            return Err(TransportError::InvalidResponse(
+                "the response's \"errors\" is not a list".to_string(),
+            ))
+        }
+    }
+
+    match response.remove("data") {
+        Some(Value::Object(data)) => Ok(data),
+        _ => Err(TransportError::InvalidResponse(
+            "the response has no \"data\" object".to_string(),
+        )),
+    }
+}
+
+/// Sends requests to a GraphQL endpoint as HTTP POST requests with JSON bodies.
+#[cfg(feature = "http")]
+#[derive(Debug, Clone)]
+pub struct HttpTransport {
+    client: reqwest::blocking::Client,
+    url: String,
+    headers: Vec<(String, String)>,
+}
+
+#[cfg(feature = "http")]
+impl HttpTransport {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::blocking::Client::new(),
+            url: url.into(),
+            headers: vec![],
+        }
+    }
+
+    /// Send the given header with every request, for example for authentication.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+#[cfg(feature = "http")]
+impl Transport for HttpTransport {
+    fn execute(&self, request: &GraphQLRequest) -> Result<Value, TransportError> {
+        let mut builder = self.client.post(&self.url).json(request);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        let response = builder
+            .send()
+            .map_err(|e| TransportError::RequestFailed(e.to_string()))?;
+
+        // GraphQL servers may report errors with non-success statuses,
+        // so the body is parsed regardless of the status.
+        let status = response.status();
+        response.json().map_err(|e| {
+            TransportError::InvalidResponse(format!("HTTP status {status}, with body error: {e}"))
+        })
+    }
+}
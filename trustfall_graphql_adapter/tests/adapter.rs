@@ -0,0 +1,308 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use serde_json::{json, Value};
+use trustfall_core::{frontend::parse, interpreter::execution::interpret_ir, ir::FieldValue};
+use trustfall_graphql_adapter::{
+    GraphQLAdapter, GraphQLRequest, Transport, TransportError, UpstreamSchemaError,
+};
+
+const UPSTREAM_SDL: &str = r#"
+schema {
+    query: Library
+}
+
+scalar Date
+
+enum Genre {
+    FICTION
+    HISTORY
+}
+
+input BookFilter {
+    genre: Genre
+}
+
+interface Named {
+    name: String!
+}
+
+union SearchResult = Book | Author
+
+type Library {
+    "All the books, newest first."
+    books(first: Int = 10, genre: Genre): [Book!]!
+    author(name: String!): Author
+    named: [Named!]!
+    search(filter: BookFilter!): [SearchResult!]!
+    filteredBooks(filter: BookFilter, first: Int): [Book!]!
+    bookCount: Int!
+}
+
+type Book {
+    title: String!
+    published: Date
+    genre: Genre!
+    pages(unit: String = "page"): Int
+    excerpt(length: Int!): String
+    author: Author!
+    shelves: [[Shelf!]!]!
+}
+
+type Shelf {
+    label: String!
+}
+
+type Author implements Named {
+    name: String!
+    books: [Book!]!
+    mentor: Author @deprecated(reason: "Use mentors instead.")
+}
+"#;
+
+/// A transport that records each request and answers with the given responses in order.
+#[derive(Debug, Default)]
+struct MockTransport {
+    requests: Mutex<Vec<GraphQLRequest>>,
+    responses: Mutex<Vec<Value>>,
+}
+
+impl MockTransport {
+    fn new(responses: impl IntoIterator<Item = Value>) -> Self {
+        let mut responses: Vec<_> = responses.into_iter().collect();
+        responses.reverse();
+        Self {
+            requests: Default::default(),
+            responses: Mutex::new(responses),
+        }
+    }
+}
+
+impl Transport for MockTransport {
+    fn execute(&self, request: &GraphQLRequest) -> Result<Value, TransportError> {
+        self.requests.lock().unwrap().push(request.clone());
+        Ok(self
+            .responses
+            .lock()
+            .unwrap()
+            .pop()
+            .expect("unexpected request"))
+    }
+}
+
+type Row = BTreeMap<Arc<str>, FieldValue>;
+
+fn run(
+    transport: MockTransport,
+    query: &str,
+    arguments: BTreeMap<Arc<str>, FieldValue>,
+) -> (Vec<Row>, Vec<GraphQLRequest>) {
+    let adapter = Arc::new(GraphQLAdapter::new(UPSTREAM_SDL, transport).unwrap());
+    let query = parse(adapter.schema(), query).unwrap();
+    let rows = interpret_ir(adapter.clone(), query, Arc::new(arguments))
+        .unwrap()
+        .collect();
+    let requests = adapter.transport().requests.lock().unwrap().clone();
+    (rows, requests)
+}
+
+#[test]
+fn derives_schema_from_upstream_sdl() {
+    let adapter = GraphQLAdapter::new(UPSTREAM_SDL, MockTransport::default()).unwrap();
+    let schema = adapter.schema();
+
+    let root_edges: Vec<_> = schema.root_edges().map(|edge| edge.name()).collect();
+    assert_eq!(
+        vec!["books", "author", "named", "filteredBooks"],
+        root_edges
+    );
+    let parameters: Vec<_> = schema
+        .root_type()
+        .edge("filteredBooks")
+        .unwrap()
+        .parameters()
+        .map(|parameter| parameter.name())
+        .collect();
+    assert_eq!(vec!["first"], parameters);
+
+    let book = schema.vertex_type("Book").unwrap();
+    let properties: Vec<_> = book.properties().map(|property| property.name()).collect();
+    assert_eq!(vec!["title", "published", "genre", "pages"], properties);
+    let edges: Vec<_> = book.edges().map(|edge| edge.name()).collect();
+    assert_eq!(vec!["author"], edges);
+
+    let author = schema.vertex_type("Author").unwrap();
+    assert_eq!(vec!["Named"], author.implements().collect::<Vec<_>>());
+    assert_eq!(
+        Some("Use mentors instead."),
+        author.edge("mentor").unwrap().deprecation_reason()
+    );
+
+    assert!(schema.vertex_type("Named").unwrap().is_interface());
+    assert!(schema.vertex_type("SearchResult").is_none());
+    assert!(schema.vertex_type("BookFilter").is_none());
+    assert_eq!(vec!["Date"], schema.scalar_types().collect::<Vec<_>>());
+}
+
+#[test]
+fn rejects_invalid_upstream_sdl() {
+    assert!(matches!(
+        GraphQLAdapter::new("type Query {", MockTransport::default()),
+        Err(UpstreamSchemaError::ParseError(_))
+    ));
+    assert!(matches!(
+        GraphQLAdapter::new("type Book { title: String }", MockTransport::default()),
+        Err(UpstreamSchemaError::MissingQueryType(name)) if name == "Query"
+    ));
+}
+
+#[test]
+fn fetches_traversal_in_one_request() {
+    let transport = MockTransport::new([json!({
+        "data": {
+            "root": [
+                {
+                    "__typename": "Book",
+                    "title": "Dune",
+                    "published": "1965-08-01",
+                    "genre": "FICTION",
+                    "pages": 412,
+                    "e0": {
+                        "__typename": "Author",
+                        "name": "Frank Herbert",
+                        "e1": [
+                            {"__typename": "Book", "title": "Dune", "published": null, "genre": "FICTION", "pages": 412},
+                            {"__typename": "Book", "title": "Dune Messiah", "published": null, "genre": "FICTION", "pages": 256},
+                        ],
+                    },
+                },
+                {
+                    "__typename": "Book",
+                    "title": "SPQR",
+                    "published": "2015-10-20",
+                    "genre": "HISTORY",
+                    "pages": 608,
+                    "e0": {"__typename": "Author", "name": "Mary Beard", "e1": []},
+                },
+            ],
+        },
+    })]);
+    let query = r#"
+{
+    books(first: 2) {
+        title @output
+        genre @output
+        pages @filter(op: ">", value: ["$min_pages"])
+
+        author {
+            author: name @output
+
+            books @fold {
+                other_titles: title @output
+            }
+        }
+    }
+}"#;
+    let arguments = BTreeMap::from([("min_pages".into(), FieldValue::Int64(500))]);
+    let (rows, requests) = run(transport, query, arguments);
+
+    let expected: Vec<Row> = vec![BTreeMap::from([
+        ("title".into(), "SPQR".into()),
+        ("genre".into(), FieldValue::Enum("HISTORY".to_string())),
+        ("author".into(), "Mary Beard".into()),
+        ("other_titles".into(), FieldValue::List(vec![])),
+    ])];
+    assert_eq!(expected, rows);
+
+    assert_eq!(1, requests.len());
+    assert_eq!(
+        "query($v0: Int) { root: books(first: $v0) { __typename genre pages published title \
+        e0: author { __typename name e1: books { __typename genre pages published title } } } }",
+        requests[0].query,
+    );
+    assert_eq!(
+        json!({"v0": 2}),
+        Value::Object(requests[0].variables.clone())
+    );
+}
+
+#[test]
+fn omits_null_arguments_and_converts_enums() {
+    let transport = MockTransport::new([json!({"data": {"root": []}})]);
+    let query = r#"
+{
+    books(genre: HISTORY) {
+        title @output
+    }
+}"#;
+    let (rows, requests) = run(transport, query, BTreeMap::new());
+    assert!(rows.is_empty());
+    assert_eq!(
+        "query($v0: Int, $v1: Genre) { root: books(first: $v0, genre: $v1) \
+        { __typename genre pages published title } }",
+        requests[0].query,
+    );
+    assert_eq!(
+        json!({"v0": 10, "v1": "HISTORY"}),
+        Value::Object(requests[0].variables.clone())
+    );
+}
+
+#[test]
+fn nests_recursion_and_coercions() {
+    let transport = MockTransport::new([json!({
+        "data": {
+            "root": [{
+                "__typename": "Author",
+                "name": "Ada",
+                "e0": {
+                    "__typename": "Author",
+                    "name": "Bea",
+                    "e0": {"__typename": "Author", "name": "Cy", "e0": null},
+                },
+            }],
+        },
+    })]);
+    let query = r#"
+{
+    named {
+        ... on Author {
+            mentor @recurse(depth: 2) {
+                name @output
+            }
+        }
+    }
+}"#;
+    let (rows, requests) = run(transport, query, BTreeMap::new());
+    let names: Vec<_> = rows.iter().map(|row| row["name"].clone()).collect();
+    assert_eq!(
+        vec!["Ada".into(), "Bea".into(), "Cy".into()] as Vec<FieldValue>,
+        names
+    );
+
+    assert_eq!(
+        "query { root: named { __typename name ... on Author { name __typename \
+        e0: mentor { __typename name e0: mentor { __typename name } } } } }",
+        requests[0].query,
+    );
+}
+
+#[test]
+fn reports_upstream_errors() {
+    let transport =
+        |_: &GraphQLRequest| Ok(json!({"errors": [{"message": "rate limited"}], "data": null}));
+    let adapter = Arc::new(GraphQLAdapter::new(UPSTREAM_SDL, transport).unwrap());
+    let query = parse(adapter.schema(), "{ named { name @output } }").unwrap();
+    let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        interpret_ir(adapter, query, Arc::new(BTreeMap::new()))
+            .unwrap()
+            .count()
+    }));
+    let message = outcome.unwrap_err();
+    let message = message
+        .downcast_ref::<String>()
+        .expect("panic message is not a string");
+    assert!(message.contains("rate limited"), "{message}");
+}
@@ -0,0 +1,32 @@
+//! Serve a trustfall schema and adapter as a GraphQL HTTP endpoint, so that existing
+//! GraphQL clients and tools can query trustfall-backed data sources directly.
+//!
+//! Endpoints accept GraphQL requests as GET query strings and as POST bodies,
+//! answer introspection queries from the schema, execute all other queries as trustfall
+//! queries, and respond with JSON. [`GraphQLService`] answers requests received
+//! by any HTTP library, and with the default `server` feature, [`serve`] runs
+//! a standalone server built on [hyper](https://hyper.rs).
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use trustfall_core::{numbers_interpreter::NumbersAdapter, schema::Schema};
+//! use trustfall_http::{serve, GraphQLService};
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let schema = Schema::parse(include_str!("../../trustfall_core/test_data/schemas/numbers.graphql")).unwrap();
+//! let service = GraphQLService::new(schema, Arc::new(NumbersAdapter::new()));
+//! serve(service, ([127, 0, 0, 1], 8080).into()).await.unwrap();
+//! # }
+//! ```
+mod request;
+mod response;
+#[cfg(feature = "server")]
+mod server;
+mod service;
+
+pub use request::{GraphQLRequest, RequestError};
+pub use response::{GraphQLError, GraphQLResponse};
+#[cfg(feature = "server")]
+pub use server::{handle_request, serve};
+pub use service::{GraphQLService, HttpRequest, HttpResponse};
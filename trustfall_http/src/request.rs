@@ -0,0 +1,82 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// A GraphQL request, as sent by GraphQL clients.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQLRequest {
+    pub query: String,
+
+    #[serde(default)]
+    pub operation_name: Option<String>,
+
+    #[serde(default, deserialize_with = "deserialize_variables")]
+    pub variables: Map<String, Value>,
+}
+
+/// Clients may send `"variables": null` when a query has no variables.
+fn deserialize_variables<'de, D>(deserializer: D) -> Result<Map<String, Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Option::<Map<String, Value>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RequestError {
+    #[error("Invalid request body: {0}")]
+    InvalidBody(String),
+
+    #[error("The request has no \"query\" parameter.")]
+    MissingQuery,
+
+    #[error("The \"variables\" parameter is not a JSON object: {0}")]
+    InvalidVariables(String),
+}
+
+impl GraphQLRequest {
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            operation_name: None,
+            variables: Map::new(),
+        }
+    }
+
+    pub fn with_variables(mut self, variables: Map<String, Value>) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    /// Parse the body of a POST request with the `application/json` content type.
+    pub fn from_json(body: &[u8]) -> Result<Self, RequestError> {
+        serde_json::from_slice(body).map_err(|e| RequestError::InvalidBody(e.to_string()))
+    }
+
+    /// Parse the query string of a GET request, whose `query`, `operationName`,
+    /// and JSON-encoded `variables` parameters make up the request.
+    pub fn from_query_string(query_string: &str) -> Result<Self, RequestError> {
+        let mut query = None;
+        let mut operation_name = None;
+        let mut variables = Map::new();
+        for (key, value) in form_urlencoded::parse(query_string.as_bytes()) {
+            match key.as_ref() {
+                "query" => query = Some(value.into_owned()),
+                "operationName" => operation_name = Some(value.into_owned()),
+                "variables" => {
+                    variables = serde_json::from_str::<Option<Map<String, Value>>>(&value)
+                        .map_err(|e| RequestError::InvalidVariables(e.to_string()))?
+                        .unwrap_or_default();
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            query: query.ok_or(RequestError::MissingQuery)?,
+            operation_name: operation_name.filter(|name| !name.is_empty()),
+            variables,
+        })
+    }
+}
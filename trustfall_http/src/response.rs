@@ -0,0 +1,40 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// The result of executing a GraphQL request, serialized as the JSON body of the response.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphQLResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<GraphQLError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphQLError {
+    pub message: String,
+}
+
+impl GraphQLResponse {
+    pub fn data(data: Value) -> Self {
+        Self {
+            data: Some(data),
+            errors: vec![],
+        }
+    }
+
+    /// A response for a request that failed before it could be executed.
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            data: None,
+            errors: vec![GraphQLError {
+                message: message.into(),
+            }],
+        }
+    }
+
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("failed to serialize response")
+    }
+}
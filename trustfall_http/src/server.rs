@@ -0,0 +1,83 @@
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+
+use hyper::{
+    body::to_bytes,
+    header::{HeaderValue, CONTENT_TYPE},
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+use trustfall_core::interpreter::Adapter;
+
+use crate::{GraphQLService, HttpRequest};
+
+/// Answer a request with the service, e.g. from a route of an existing hyper server.
+///
+/// Queries are executed on tokio's blocking thread pool, since adapters are synchronous.
+pub async fn handle_request<A>(
+    service: Arc<GraphQLService<A>>,
+    request: Request<Body>,
+) -> Response<Body>
+where
+    A: Adapter<'static> + Send + Sync + 'static,
+{
+    let (parts, body) = request.into_parts();
+    let body = match to_bytes(body).await {
+        Ok(body) => body,
+        Err(e) => {
+            return Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from(format!("failed to read request body: {e}")))
+                .expect("failed to build response");
+        }
+    };
+
+    let response = tokio::task::spawn_blocking(move || {
+        let content_type = parts
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok());
+        service.handle_http(HttpRequest {
+            method: parts.method.as_str(),
+            query_string: parts.uri.query(),
+            content_type,
+            body: &body,
+        })
+    })
+    .await;
+
+    match response {
+        Ok(response) => {
+            let mut builder = Response::builder().status(response.status);
+            for (name, value) in response.headers {
+                builder = builder.header(name, HeaderValue::from_static(value));
+            }
+            builder
+                .body(Body::from(response.body))
+                .expect("failed to build response")
+        }
+        Err(e) => Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(Body::from(format!("failed to handle request: {e}")))
+            .expect("failed to build response"),
+    }
+}
+
+/// Serve the GraphQL endpoint at every path of the given address, until the server fails.
+///
+/// Must be called from within a tokio runtime.
+pub async fn serve<A>(service: GraphQLService<A>, addr: SocketAddr) -> Result<(), hyper::Error>
+where
+    A: Adapter<'static> + Send + Sync + 'static,
+{
+    let service = Arc::new(service);
+    let make_service = make_service_fn(move |_connection| {
+        let service = service.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let service = service.clone();
+                async move { Ok::<_, Infallible>(handle_request(service, request).await) }
+            }))
+        }
+    });
+    Server::bind(&addr).serve(make_service).await
+}
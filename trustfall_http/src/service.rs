@@ -0,0 +1,211 @@
+use std::{
+    any::Any,
+    collections::BTreeMap,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::Arc,
+};
+
+use serde_json::{Map, Value};
+use trustfall_core::{
+    frontend::parse,
+    interpreter::{execution::interpret_ir, Adapter},
+    ir::{FieldValue, TransparentValue},
+    schema::{graphql_introspection::is_introspection_query, Schema},
+};
+
+use crate::{GraphQLRequest, GraphQLResponse};
+
+/// An HTTP request received by a GraphQL endpoint, as given by any HTTP library.
+#[derive(Debug, Clone, Copy)]
+pub struct HttpRequest<'a> {
+    pub method: &'a str,
+
+    /// The part of the URL after the `?`, if any.
+    pub query_string: Option<&'a str>,
+
+    /// The value of the `Content-Type` header, if any.
+    pub content_type: Option<&'a str>,
+
+    pub body: &'a [u8],
+}
+
+/// The HTTP response to send for a request to a GraphQL endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+
+    /// Headers to send in addition to the usual ones like `Content-Length`.
+    pub headers: Vec<(&'static str, &'static str)>,
+
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    fn json(status: u16, response: &GraphQLResponse) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type", "application/json")],
+            body: response.to_json(),
+        }
+    }
+}
+
+/// Executes GraphQL requests against a trustfall schema and adapter.
+///
+/// Introspection queries, which only select the `__schema`, `__type`, and `__typename`
+/// root fields, are answered from the schema. All other queries are executed as trustfall
+/// queries; since they produce a flat list of results rather than nested objects,
+/// the `data` of their responses is an object with a `results` list holding
+/// the outputs of each result.
+#[derive(Debug)]
+pub struct GraphQLService<A> {
+    schema: Arc<Schema>,
+    adapter: Arc<A>,
+}
+
+impl<A> Clone for GraphQLService<A> {
+    fn clone(&self) -> Self {
+        Self {
+            schema: self.schema.clone(),
+            adapter: self.adapter.clone(),
+        }
+    }
+}
+
+impl<A: Adapter<'static> + 'static> GraphQLService<A> {
+    pub fn new(schema: Schema, adapter: Arc<A>) -> Self {
+        Self {
+            schema: Arc::new(schema),
+            adapter,
+        }
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Execute the request, returning its data or the errors that prevented its execution.
+    ///
+    /// Trustfall queries must contain a single operation, so their operation names are unused.
+    /// The adapter panicking while the query executes is reported as an error too.
+    pub fn execute(&self, request: &GraphQLRequest) -> GraphQLResponse {
+        let operation_name = request.operation_name.as_deref();
+        if is_introspection_query(&request.query, operation_name) {
+            return match self.schema.execute_introspection_query(
+                &request.query,
+                operation_name,
+                &request.variables,
+            ) {
+                Ok(data) => GraphQLResponse::data(data),
+                Err(e) => GraphQLResponse::error(e.to_string()),
+            };
+        }
+
+        let query = match parse(&self.schema, &request.query) {
+            Ok(query) => query,
+            Err(e) => return GraphQLResponse::error(e.to_string()),
+        };
+        let variables = match convert_variables(&request.variables) {
+            Ok(variables) => variables,
+            Err(message) => return GraphQLResponse::error(message),
+        };
+
+        let adapter = self.adapter.clone();
+        let results = catch_unwind(AssertUnwindSafe(move || {
+            interpret_ir(adapter, query, Arc::new(variables)).map(|results| {
+                results
+                    .map(|row| {
+                        let row: Map<String, Value> = row
+                            .into_iter()
+                            .map(|(name, value)| (name.to_string(), to_json(value)))
+                            .collect();
+                        Value::Object(row)
+                    })
+                    .collect::<Vec<_>>()
+            })
+        }));
+        match results {
+            Ok(Ok(results)) => GraphQLResponse::data(Value::Object(Map::from_iter([(
+                "results".to_string(),
+                Value::Array(results),
+            )]))),
+            Ok(Err(e)) => GraphQLResponse::error(e.to_string()),
+            Err(panic) => GraphQLResponse::error(format!(
+                "The query failed to execute: {}",
+                panic_message(&*panic)
+            )),
+        }
+    }
+
+    /// Answer an HTTP request to the GraphQL endpoint, following the GraphQL over HTTP
+    /// conventions used by GraphQL clients.
+    ///
+    /// GET requests pass the request in the query string. POST requests pass it as
+    /// an `application/json` body, or pass just the query as an `application/graphql` body.
+    /// Requests that can't be parsed get a `400 Bad Request` response; requests that can be
+    /// parsed get a `200 OK` response, with any errors in its body.
+    pub fn handle_http(&self, request: HttpRequest<'_>) -> HttpResponse {
+        let parsed = match request.method {
+            "GET" => GraphQLRequest::from_query_string(request.query_string.unwrap_or_default()),
+            "POST" => {
+                let media_type = request
+                    .content_type
+                    .and_then(|content_type| content_type.split(';').next())
+                    .map(|media_type| media_type.trim().to_ascii_lowercase());
+                match media_type.as_deref() {
+                    Some("application/json") => GraphQLRequest::from_json(request.body),
+                    Some("application/graphql") => match std::str::from_utf8(request.body) {
+                        Ok(query) => Ok(GraphQLRequest::new(query)),
+                        Err(e) => {
+                            let message = format!("The request body is not valid UTF-8: {e}");
+                            return HttpResponse::json(400, &GraphQLResponse::error(message));
+                        }
+                    },
+                    _ => {
+                        let message = "POST requests must have the application/json \
+                            or application/graphql content type.";
+                        return HttpResponse::json(415, &GraphQLResponse::error(message));
+                    }
+                }
+            }
+            _ => {
+                let message = "Only GET and POST requests are supported.";
+                let mut response = HttpResponse::json(405, &GraphQLResponse::error(message));
+                response.headers.push(("Allow", "GET, POST"));
+                return response;
+            }
+        };
+
+        match parsed {
+            Ok(parsed) => HttpResponse::json(200, &self.execute(&parsed)),
+            Err(e) => HttpResponse::json(400, &GraphQLResponse::error(e.to_string())),
+        }
+    }
+}
+
+fn convert_variables(
+    variables: &Map<String, Value>,
+) -> Result<BTreeMap<Arc<str>, FieldValue>, String> {
+    variables
+        .iter()
+        .map(|(name, value)| {
+            let value: TransparentValue = serde_json::from_value(value.clone())
+                .map_err(|e| format!("Invalid value for variable \"{name}\": {e}"))?;
+            Ok((Arc::from(name.as_str()), value.into()))
+        })
+        .collect()
+}
+
+fn to_json(value: FieldValue) -> Value {
+    serde_json::to_value(TransparentValue::from(value)).expect("failed to serialize value")
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message
+    } else {
+        "the adapter panicked"
+    }
+}
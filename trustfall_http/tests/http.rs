@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use hyper::{body::to_bytes, Body, Request, StatusCode};
+use serde_json::{json, Value};
+use trustfall_core::{numbers_interpreter::NumbersAdapter, schema::Schema};
+use trustfall_http::{handle_request, GraphQLRequest, GraphQLService, HttpRequest, HttpResponse};
+
+const QUERY: &str = r#"
+query($min: Int!) {
+    Number(max: 3) {
+        value @output @filter(op: ">=", value: ["$min"])
+    }
+}"#;
+
+fn service() -> GraphQLService<NumbersAdapter> {
+    let schema = Schema::parse(include_str!(
+        "../../trustfall_core/test_data/schemas/numbers.graphql"
+    ))
+    .unwrap();
+    GraphQLService::new(schema, Arc::new(NumbersAdapter::new()))
+}
+
+fn post(content_type: &str, body: &[u8]) -> HttpResponse {
+    service().handle_http(HttpRequest {
+        method: "POST",
+        query_string: None,
+        content_type: Some(content_type),
+        body,
+    })
+}
+
+fn body(response: &HttpResponse) -> Value {
+    serde_json::from_slice(&response.body).unwrap()
+}
+
+fn expected_results() -> Value {
+    json!({"data": {"results": [{"value": 2}, {"value": 3}]}})
+}
+
+#[test]
+fn executes_json_requests() {
+    let request = json!({"query": QUERY, "variables": {"min": 2}, "operationName": null});
+    let response = post(
+        "application/json; charset=utf-8",
+        &serde_json::to_vec(&request).unwrap(),
+    );
+    assert_eq!(200, response.status);
+    assert!(response
+        .headers
+        .contains(&("Content-Type", "application/json")));
+    assert_eq!(expected_results(), body(&response));
+}
+
+#[test]
+fn executes_get_requests() {
+    let query_string = form_urlencoded::Serializer::new(String::new())
+        .append_pair("query", QUERY)
+        .append_pair("variables", r#"{"min": 2}"#)
+        .finish();
+    let response = service().handle_http(HttpRequest {
+        method: "GET",
+        query_string: Some(&query_string),
+        content_type: None,
+        body: &[],
+    });
+    assert_eq!(200, response.status);
+    assert_eq!(expected_results(), body(&response));
+}
+
+#[test]
+fn executes_graphql_bodies() {
+    let response = post("application/graphql", b"{ Two { name @output } }");
+    assert_eq!(200, response.status);
+    assert_eq!(
+        json!({"data": {"results": [{"name": "two"}]}}),
+        body(&response)
+    );
+}
+
+#[test]
+fn answers_introspection_queries() {
+    let request = GraphQLRequest::new("{ __schema { queryType { name } } }");
+    let response = service().execute(&request);
+    assert!(response.errors.is_empty(), "{response:?}");
+    assert_eq!(
+        Some(json!({"__schema": {"queryType": {"name": "RootSchemaQuery"}}})),
+        response.data
+    );
+}
+
+#[test]
+fn reports_query_errors() {
+    let request = GraphQLRequest::new("{ Number(max: 3) { nonexistent @output } }");
+    let response = service().execute(&request);
+    assert_eq!(None, response.data);
+    assert_eq!(1, response.errors.len());
+
+    let request = GraphQLRequest::new(QUERY);
+    let response = service().execute(&request);
+    assert_eq!(None, response.data);
+    assert!(
+        response.errors[0].message.contains("min"),
+        "{:?}",
+        response.errors
+    );
+}
+
+#[test]
+fn rejects_malformed_requests() {
+    let response = post("application/json", b"{\"query\": ");
+    assert_eq!(400, response.status);
+    assert!(body(&response)["errors"][0]["message"].is_string());
+
+    let response = post("application/json", b"{\"variables\": {}}");
+    assert_eq!(400, response.status);
+
+    let response = post("text/plain", b"{ Two { name @output } }");
+    assert_eq!(415, response.status);
+
+    let response = service().handle_http(HttpRequest {
+        method: "PUT",
+        query_string: None,
+        content_type: None,
+        body: &[],
+    });
+    assert_eq!(405, response.status);
+    assert!(response.headers.contains(&("Allow", "GET, POST")));
+}
+
+#[tokio::test]
+async fn handles_hyper_requests() {
+    let request = json!({"query": QUERY, "variables": {"min": 2}});
+    let request = Request::post("/graphql")
+        .header("Content-Type", "application/json")
+        .body(Body::from(serde_json::to_vec(&request).unwrap()))
+        .unwrap();
+    let response = handle_request(Arc::new(service()), request).await;
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!(
+        "application/json",
+        response.headers()["Content-Type"].to_str().unwrap()
+    );
+
+    let body = to_bytes(response.into_body()).await.unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(expected_results(), body);
+}
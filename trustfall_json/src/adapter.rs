@@ -0,0 +1,272 @@
+use std::sync::Arc;
+
+use serde_json::Value;
+use trustfall_core::{
+    interpreter::{
+        helpers::{resolve_coercion_with, resolve_neighbors_with, resolve_property_with},
+        Adapter, ContextIterator, ContextOutcomeIterator, ResolveEdgeInfo, ResolveInfo,
+        VertexIterator,
+    },
+    ir::{EdgeParameters, FieldValue},
+    schema::Schema,
+};
+
+use crate::{
+    infer::{InferenceError, InferredSchema, DOCUMENT_TYPE},
+    vertex::Vertex,
+    SCHEMA,
+};
+
+/// An adapter querying a JSON document.
+///
+/// By default, documents are queried with the generic schema in [`SCHEMA`], in which every
+/// value in the document is a vertex. Documents can also be queried with a schema inferred
+/// from a sample document, whose vertex types and fields are the sample's kinds of objects
+/// and their keys: see [`InferredSchema`].
+#[derive(Debug, Clone)]
+pub struct JsonAdapter {
+    document: Arc<Value>,
+    mode: Mode,
+}
+
+#[derive(Debug, Clone)]
+enum Mode {
+    Generic(Schema),
+    Inferred(Arc<InferredSchema>),
+}
+
+impl JsonAdapter {
+    /// Query the document with the generic schema in [`SCHEMA`].
+    pub fn new(document: Value) -> Self {
+        Self {
+            document: Arc::new(document),
+            mode: Mode::Generic(Schema::parse(SCHEMA).expect("schema is not valid")),
+        }
+    }
+
+    /// Query the document with a schema inferred from it.
+    ///
+    /// Fails if the document is not an object or an array of objects.
+    pub fn infer(document: Value) -> Result<Self, InferenceError> {
+        let schema = InferredSchema::infer(&document)?;
+        Ok(Self::with_schema(document, schema))
+    }
+
+    /// Query the document with a schema inferred from a sample document,
+    /// such as another response from the same API.
+    ///
+    /// Values that don't match the schema, for example a string where the sample
+    /// has a number, are queried as `null`.
+    pub fn with_schema(document: Value, schema: InferredSchema) -> Self {
+        Self {
+            document: Arc::new(document),
+            mode: Mode::Inferred(Arc::new(schema)),
+        }
+    }
+
+    /// The schema for querying the document.
+    pub fn schema(&self) -> &Schema {
+        match &self.mode {
+            Mode::Generic(schema) => schema,
+            Mode::Inferred(inferred) => inferred.schema(),
+        }
+    }
+
+    /// The document this adapter queries.
+    pub fn document(&self) -> &Value {
+        &self.document
+    }
+}
+
+/// The objects in the value: the value itself if it's an object, or the objects among its items
+/// if it's an array.
+fn objects(document: &Arc<Value>, pointer: String, typename: &str) -> Vec<Vertex> {
+    let Some(value) = document.pointer(&pointer) else {
+        return vec![];
+    };
+    match value {
+        Value::Object(_) => Vertex::new(document, pointer, typename)
+            .into_iter()
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.is_object())
+            .filter_map(|(index, _)| Vertex::new(document, format!("{pointer}/{index}"), typename))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+impl<'a> Adapter<'a> for JsonAdapter {
+    type Vertex = Vertex;
+
+    fn resolve_starting_vertices(
+        &self,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        _resolve_info: &ResolveInfo,
+    ) -> VertexIterator<'a, Self::Vertex> {
+        let vertices = match (&self.mode, edge_name.as_ref()) {
+            (Mode::Generic(_), "Root") => Vertex::generic(&self.document, String::new())
+                .into_iter()
+                .collect(),
+            (Mode::Generic(_), "Pointer") => {
+                let pointer = parameters["pointer"]
+                    .as_str()
+                    .expect("pointer is not a string");
+                Vertex::generic(&self.document, pointer.to_string())
+                    .into_iter()
+                    .collect()
+            }
+            (Mode::Inferred(_), DOCUMENT_TYPE) => {
+                objects(&self.document, String::new(), DOCUMENT_TYPE)
+            }
+            _ => unreachable!("unexpected starting edge: {edge_name}"),
+        };
+        Box::new(vertices.into_iter())
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'a, Self::Vertex>,
+        type_name: &Arc<str>,
+        property_name: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, FieldValue> {
+        if property_name.as_ref() == "__typename" {
+            return resolve_property_with(contexts, |vertex| vertex.typename().into());
+        }
+
+        let inferred = match &self.mode {
+            Mode::Generic(_) => return resolve_generic_property(contexts, property_name),
+            Mode::Inferred(inferred) => inferred.clone(),
+        };
+        let (key, kind) = inferred
+            .property(type_name, property_name)
+            .unwrap_or_else(|| unreachable!("unexpected property {type_name}.{property_name}"))
+            .clone();
+        resolve_property_with(contexts, move |vertex| match vertex.value().get(&key) {
+            Some(value) => kind.convert(value),
+            None => FieldValue::Null,
+        })
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: ContextIterator<'a, Self::Vertex>,
+        type_name: &Arc<str>,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        _resolve_info: &ResolveEdgeInfo,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, VertexIterator<'a, Self::Vertex>> {
+        let inferred = match &self.mode {
+            Mode::Generic(_) => return resolve_generic_neighbors(contexts, edge_name, parameters),
+            Mode::Inferred(inferred) => inferred,
+        };
+        let edge = inferred
+            .edge(type_name, edge_name)
+            .unwrap_or_else(|| unreachable!("unexpected edge {type_name}.{edge_name}"))
+            .clone();
+        resolve_neighbors_with(contexts, move |vertex| {
+            let pointer = vertex.child_pointer(&edge.key);
+            Box::new(objects(vertex.document(), pointer, &edge.target).into_iter())
+        })
+    }
+
+    fn resolve_coercion(
+        &self,
+        contexts: ContextIterator<'a, Self::Vertex>,
+        _type_name: &Arc<str>,
+        coerce_to_type: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, bool> {
+        // Only the generic schema's `Value` has subtypes, and they have none of their own.
+        let coerce_to_type = coerce_to_type.clone();
+        resolve_coercion_with(contexts, move |vertex| {
+            vertex.typename() == coerce_to_type.as_ref()
+        })
+    }
+}
+
+fn resolve_generic_property<'a>(
+    contexts: ContextIterator<'a, Vertex>,
+    property_name: &str,
+) -> ContextOutcomeIterator<'a, Vertex, FieldValue> {
+    match property_name {
+        "pointer" => resolve_property_with(contexts, |vertex| vertex.pointer().into()),
+        "key" => resolve_property_with(contexts, |vertex| vertex.key().into()),
+        "json" => resolve_property_with(contexts, |vertex| vertex.value().to_string().into()),
+        "value" => resolve_property_with(contexts, |vertex| match vertex.value() {
+            Value::Bool(b) => (*b).into(),
+            Value::Number(n) => n.as_f64().map(FieldValue::Float64).into(),
+            Value::String(s) => s.as_str().into(),
+            _ => unreachable!("vertex has no value property: {vertex:?}"),
+        }),
+        "integer" => resolve_property_with(contexts, |vertex| match vertex.value() {
+            Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+                (Some(n), _) => n.into(),
+                (None, Some(n)) => n.into(),
+                (None, None) => FieldValue::Null,
+            },
+            _ => unreachable!("vertex is not a number: {vertex:?}"),
+        }),
+        "length" => resolve_property_with(contexts, |vertex| match vertex.value() {
+            Value::Array(items) => (items.len() as u64).into(),
+            Value::Object(fields) => (fields.len() as u64).into(),
+            _ => unreachable!("vertex has no length property: {vertex:?}"),
+        }),
+        "keys" => resolve_property_with(contexts, |vertex| match vertex.value() {
+            Value::Object(fields) => fields.keys().map(String::as_str).collect(),
+            _ => unreachable!("vertex is not an object: {vertex:?}"),
+        }),
+        _ => unreachable!("unexpected property {property_name}"),
+    }
+}
+
+fn resolve_generic_neighbors<'a>(
+    contexts: ContextIterator<'a, Vertex>,
+    edge_name: &str,
+    parameters: &EdgeParameters,
+) -> ContextOutcomeIterator<'a, Vertex, VertexIterator<'a, Vertex>> {
+    match edge_name {
+        "parent" => resolve_neighbors_with(contexts, |vertex| {
+            let parent = vertex
+                .parent_pointer()
+                .and_then(|pointer| Vertex::generic(vertex.document(), pointer.to_string()));
+            Box::new(parent.into_iter())
+        }),
+        "children" => resolve_neighbors_with(contexts, |vertex| {
+            let keys: Vec<String> = match vertex.value() {
+                Value::Array(items) => (0..items.len()).map(|index| index.to_string()).collect(),
+                Value::Object(fields) => fields.keys().cloned().collect(),
+                _ => vec![],
+            };
+            let vertex = vertex.clone();
+            Box::new(keys.into_iter().filter_map(move |key| {
+                Vertex::generic(vertex.document(), vertex.child_pointer(&key))
+            }))
+        }),
+        "item" => {
+            let index = parameters["index"]
+                .as_i64()
+                .expect("index is not an integer");
+            resolve_neighbors_with(contexts, move |vertex| {
+                let pointer = vertex.child_pointer(&index.to_string());
+                let item = Vertex::generic(vertex.document(), pointer);
+                Box::new(item.into_iter())
+            })
+        }
+        "field" => {
+            let key = parameters["key"]
+                .as_str()
+                .expect("key is not a string")
+                .to_string();
+            resolve_neighbors_with(contexts, move |vertex| {
+                let field = Vertex::generic(vertex.document(), vertex.child_pointer(&key));
+                Box::new(field.into_iter())
+            })
+        }
+        _ => unreachable!("unexpected edge {edge_name}"),
+    }
+}
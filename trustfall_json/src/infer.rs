@@ -0,0 +1,282 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::Value;
+use trustfall_core::{
+    ir::FieldValue,
+    schema::{
+        builder::{graphql_name, pascal_case, unique_name, SchemaBuilder, Ty},
+        error::InvalidSchemaError,
+        Schema,
+    },
+};
+
+/// The name of the vertex type of the document's top-level objects, and of its starting edge.
+pub const DOCUMENT_TYPE: &str = "Document";
+
+/// Errors from inferring a schema from a sample document.
+#[non_exhaustive]
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum InferenceError {
+    #[error("The sample document is not an object or an array of objects.")]
+    NotObjects,
+
+    #[error("The inferred schema is not valid")]
+    InvalidSchema(#[from] InvalidSchemaError),
+}
+
+/// A schema with a vertex type for each kind of object in a sample document.
+///
+/// The document's top-level objects, which are either the document itself or the items
+/// of the array it consists of, are [`DOCUMENT_TYPE`] vertices. Each of their fields
+/// holding objects or arrays of objects becomes an edge to a vertex type named after
+/// the field, like `DocumentAuthor` for the field `author`, and so on for the fields of
+/// those objects. All other fields become properties:
+/// - booleans, numbers, and strings become `Boolean`, `Int` or `Float`, and `String` properties,
+///   and arrays of them become list properties;
+/// - fields whose values differ in type, or that hold nested arrays, become `String` properties
+///   holding the values as JSON text, as do fields that are always `null` or empty arrays.
+///
+/// Objects in arrays, and objects at the same position in the items of arrays, are merged
+/// into a single type with all their fields. All properties are nullable, since documents
+/// may leave out fields the sample has. Keys that aren't valid GraphQL names are changed
+/// into ones that are, for example `first-name` becomes `first_name`.
+#[derive(Debug, Clone)]
+pub struct InferredSchema {
+    schema: Schema,
+    types: BTreeMap<String, InferredType>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct InferredType {
+    /// The key holding each property's value, and how to convert it.
+    pub(crate) properties: BTreeMap<String, (String, Kind)>,
+    pub(crate) edges: BTreeMap<String, Edge>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Edge {
+    /// The key holding the edge's object, or array of objects.
+    pub(crate) key: String,
+    pub(crate) target: String,
+    pub(crate) is_list: bool,
+}
+
+/// How a property's values are converted from JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Boolean,
+    Int,
+    Float,
+    String,
+    Json,
+    List(Box<Kind>),
+}
+
+impl Kind {
+    fn ty(&self) -> Ty {
+        match self {
+            Self::Boolean => Ty::boolean(),
+            Self::Int => Ty::int(),
+            Self::Float => Ty::float(),
+            Self::String | Self::Json => Ty::string(),
+            Self::List(item) => item.ty().list(),
+        }
+    }
+
+    /// Convert the value, which is null if it doesn't match the property's type.
+    pub(crate) fn convert(&self, value: &Value) -> FieldValue {
+        match (self, value) {
+            (_, Value::Null) => FieldValue::Null,
+            (Self::Json, value) => value.to_string().into(),
+            (Self::Boolean, Value::Bool(b)) => (*b).into(),
+            (Self::Int, Value::Number(n)) => match (n.as_i64(), n.as_u64()) {
+                (Some(n), _) => n.into(),
+                (None, Some(n)) => n.into(),
+                (None, None) => FieldValue::Null,
+            },
+            (Self::Float, Value::Number(n)) => n.as_f64().map(FieldValue::Float64).into(),
+            (Self::String, Value::String(s)) => s.as_str().into(),
+            (Self::List(item), Value::Array(items)) => {
+                FieldValue::List(items.iter().map(|value| item.convert(value)).collect())
+            }
+            _ => FieldValue::Null,
+        }
+    }
+}
+
+/// The structure of the values at some position in the sample.
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    Null,
+    Boolean,
+    Int,
+    Float,
+    String,
+    Object(BTreeMap<String, Shape>),
+    Array(Box<Shape>),
+    Mixed,
+}
+
+impl Shape {
+    fn of(value: &Value) -> Self {
+        match value {
+            Value::Null => Self::Null,
+            Value::Bool(_) => Self::Boolean,
+            Value::Number(n) if n.is_f64() => Self::Float,
+            Value::Number(_) => Self::Int,
+            Value::String(_) => Self::String,
+            Value::Array(items) => Self::Array(Box::new(
+                items.iter().map(Self::of).fold(Self::Null, Self::merge),
+            )),
+            Value::Object(fields) => Self::Object(
+                fields
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Self::of(value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Null, shape) | (shape, Self::Null) => shape,
+            (Self::Int, Self::Float) | (Self::Float, Self::Int) => Self::Float,
+            (Self::Object(mut fields), Self::Object(other)) => {
+                for (key, shape) in other {
+                    let merged = match fields.remove(&key) {
+                        Some(existing) => existing.merge(shape),
+                        None => shape,
+                    };
+                    fields.insert(key, merged);
+                }
+                Self::Object(fields)
+            }
+            (Self::Array(item), Self::Array(other)) => Self::Array(Box::new(item.merge(*other))),
+            (a, b) if a == b => a,
+            _ => Self::Mixed,
+        }
+    }
+
+    /// The property kind for values of this shape, if they aren't objects.
+    fn kind(&self) -> Kind {
+        match self {
+            Self::Boolean => Kind::Boolean,
+            Self::Int => Kind::Int,
+            Self::Float => Kind::Float,
+            Self::String => Kind::String,
+            Self::Array(item) => match item.as_ref() {
+                Self::Boolean | Self::Int | Self::Float | Self::String => {
+                    Kind::List(Box::new(item.kind()))
+                }
+                Self::Null => Kind::List(Box::new(Kind::Json)),
+                _ => Kind::Json,
+            },
+            Self::Null | Self::Object(_) | Self::Mixed => Kind::Json,
+        }
+    }
+}
+
+/// The fields of the objects of this shape, unless it's not an object with fields,
+/// directly or in an array.
+fn object_fields(shape: &Shape) -> Option<(&BTreeMap<String, Shape>, bool)> {
+    match shape {
+        Shape::Object(fields) if !fields.is_empty() => Some((fields, false)),
+        Shape::Array(item) => match item.as_ref() {
+            Shape::Object(fields) if !fields.is_empty() => Some((fields, true)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl InferredSchema {
+    /// Infer a schema from a sample document, which must be an object
+    /// or an array of objects.
+    pub fn infer(sample: &Value) -> Result<Self, InferenceError> {
+        let shape = Shape::of(sample);
+        let (fields, root_is_array) = object_fields(&shape).ok_or(InferenceError::NotObjects)?;
+
+        let mut inference = Inference {
+            types: BTreeMap::new(),
+            type_names: BTreeSet::from([DOCUMENT_TYPE.to_string()]),
+        };
+        inference.add_type(DOCUMENT_TYPE.to_string(), fields);
+
+        let document_ty = Ty::named(DOCUMENT_TYPE).non_null();
+        let root_ty = if root_is_array {
+            document_ty.list().non_null()
+        } else {
+            document_ty
+        };
+        let mut builder = SchemaBuilder::new().root_edge(DOCUMENT_TYPE, root_ty);
+        for (type_name, inferred) in &inference.types {
+            builder = builder.vertex_type(type_name);
+            for (name, (_, kind)) in &inferred.properties {
+                builder = builder.property(name, kind.ty());
+            }
+            for (name, edge) in &inferred.edges {
+                let ty = Ty::named(&edge.target);
+                let ty = if edge.is_list {
+                    ty.non_null().list()
+                } else {
+                    ty
+                };
+                builder = builder.edge(name, ty);
+            }
+        }
+
+        Ok(Self {
+            schema: builder.build()?,
+            types: inference.types,
+        })
+    }
+
+    /// The inferred schema, to parse queries against.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub(crate) fn edge(&self, type_name: &str, edge_name: &str) -> Option<&Edge> {
+        self.types.get(type_name)?.edges.get(edge_name)
+    }
+
+    pub(crate) fn property(&self, type_name: &str, property_name: &str) -> Option<&(String, Kind)> {
+        self.types.get(type_name)?.properties.get(property_name)
+    }
+}
+
+struct Inference {
+    types: BTreeMap<String, InferredType>,
+    type_names: BTreeSet<String>,
+}
+
+impl Inference {
+    fn add_type(&mut self, type_name: String, fields: &BTreeMap<String, Shape>) {
+        let mut inferred = InferredType::default();
+        let mut field_names = BTreeSet::new();
+        for (key, shape) in fields {
+            let name = unique_name(&mut field_names, graphql_name(key));
+            match object_fields(shape) {
+                Some((object_fields, is_list)) => {
+                    let target = unique_name(
+                        &mut self.type_names,
+                        format!("{type_name}{}", pascal_case(&name)),
+                    );
+                    self.add_type(target.clone(), object_fields);
+                    let edge = Edge {
+                        key: key.clone(),
+                        target,
+                        is_list,
+                    };
+                    inferred.edges.insert(name, edge);
+                }
+                None => {
+                    inferred
+                        .properties
+                        .insert(name, (key.clone(), shape.kind()));
+                }
+            }
+        }
+        self.types.insert(type_name, inferred);
+    }
+}
@@ -0,0 +1,84 @@
+//! Query JSON documents with trustfall.
+//!
+//! [`JsonAdapter`] queries any [`serde_json::Value`], such as the contents of a JSON file
+//! or an API response, in one of two ways:
+//! - With the generic schema in [`SCHEMA`], every value in the document is a vertex:
+//!   objects and arrays have their fields and items as `children`, and scalars have
+//!   their `value`. This works for any document, and `@recurse` can search all of it.
+//! - With an [`InferredSchema`], the document's objects are vertices whose types,
+//!   properties, and edges are inferred from a sample document. This makes for shorter
+//!   queries over documents whose structure is known.
+//!
+//! ```
+//! use std::{collections::BTreeMap, sync::Arc};
+//!
+//! use serde_json::json;
+//! use trustfall_core::{frontend::parse, interpreter::execution::interpret_ir, ir::FieldValue};
+//! use trustfall_json::JsonAdapter;
+//!
+//! let document = json!({
+//!     "users": [
+//!         {"name": "Ada", "languages": ["en", "fr"], "manager": null},
+//!         {"name": "Grace", "languages": ["en"], "manager": {"name": "Ada"}},
+//!     ],
+//! });
+//!
+//! // Every string in the document that's within an array, and where it is.
+//! let adapter = Arc::new(JsonAdapter::new(document.clone()));
+//! let query = parse(adapter.schema(), r#"
+//! {
+//!     Root {
+//!         children @recurse(depth: 5) {
+//!             ... on StringValue {
+//!                 pointer @output
+//!                 value @output
+//!
+//!                 parent {
+//!                     __typename @filter(op: "=", value: ["$array"])
+//!                 }
+//!             }
+//!         }
+//!     }
+//! }"#).unwrap();
+//! let arguments = Arc::new(BTreeMap::from([("array".into(), "ArrayValue".into())]));
+//! let rows: Vec<_> = interpret_ir(adapter, query, arguments).unwrap().collect();
+//! let pointers: Vec<_> = rows.iter().map(|row| row["pointer"].clone()).collect();
+//! assert_eq!(
+//!     vec![
+//!         FieldValue::from("/users/0/languages/0"),
+//!         "/users/0/languages/1".into(),
+//!         "/users/1/languages/0".into(),
+//!     ],
+//!     pointers,
+//! );
+//!
+//! // The same document with an inferred schema, in which `users` is an edge
+//! // to `DocumentUsers` vertices, and `manager` one to `DocumentUsersManager` vertices.
+//! let adapter = Arc::new(JsonAdapter::infer(document).unwrap());
+//! let query = parse(adapter.schema(), r#"
+//! {
+//!     Document {
+//!         users {
+//!             name @output
+//!
+//!             manager {
+//!                 manager: name @output
+//!             }
+//!         }
+//!     }
+//! }"#).unwrap();
+//! let rows: Vec<_> = interpret_ir(adapter, query, Arc::new(BTreeMap::new())).unwrap().collect();
+//! assert_eq!(1, rows.len());
+//! assert_eq!(FieldValue::from("Grace"), rows[0]["name"]);
+//! assert_eq!(FieldValue::from("Ada"), rows[0]["manager"]);
+//! ```
+mod adapter;
+mod infer;
+mod vertex;
+
+pub use adapter::JsonAdapter;
+pub use infer::{InferenceError, InferredSchema, DOCUMENT_TYPE};
+pub use vertex::Vertex;
+
+/// The generic schema of values in JSON documents, queried by [`JsonAdapter::new`].
+pub const SCHEMA: &str = include_str!("json.graphql");
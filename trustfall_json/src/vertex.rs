@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use serde_json::Value;
+
+/// A value in a JSON document, identified by its JSON Pointer.
+#[derive(Debug, Clone)]
+pub struct Vertex {
+    document: Arc<Value>,
+    pointer: Arc<str>,
+    typename: Arc<str>,
+}
+
+impl Vertex {
+    /// The value at the pointer, of the given vertex type, unless there is no such value.
+    pub(crate) fn new(document: &Arc<Value>, pointer: String, typename: &str) -> Option<Self> {
+        document.pointer(&pointer)?;
+        Some(Self {
+            document: document.clone(),
+            pointer: pointer.into(),
+            typename: typename.into(),
+        })
+    }
+
+    /// The value at the pointer, as a vertex of the generic schema's type for its kind of value.
+    pub(crate) fn generic(document: &Arc<Value>, pointer: String) -> Option<Self> {
+        let typename = generic_typename(document.pointer(&pointer)?);
+        Self::new(document, pointer, typename)
+    }
+
+    /// The JSON Pointer to this value.
+    pub fn pointer(&self) -> &str {
+        &self.pointer
+    }
+
+    /// The JSON value this vertex represents.
+    pub fn value(&self) -> &Value {
+        self.document
+            .pointer(&self.pointer)
+            .expect("vertex pointer is not valid")
+    }
+
+    /// The name of this vertex's type in the schema being queried.
+    pub fn typename(&self) -> &str {
+        &self.typename
+    }
+
+    pub(crate) fn document(&self) -> &Arc<Value> {
+        &self.document
+    }
+
+    /// This value's key in the value containing it, unless this is the root value.
+    pub(crate) fn key(&self) -> Option<String> {
+        let (_, key) = self.pointer.rsplit_once('/')?;
+        Some(unescape(key))
+    }
+
+    /// The pointer to the value containing this one, unless this is the root value.
+    pub(crate) fn parent_pointer(&self) -> Option<&str> {
+        let (parent, _) = self.pointer.rsplit_once('/')?;
+        Some(parent)
+    }
+
+    /// The pointer to the given key or index within this value.
+    pub(crate) fn child_pointer(&self, key: &str) -> String {
+        format!("{}/{}", self.pointer, escape(key))
+    }
+}
+
+fn generic_typename(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "NullValue",
+        Value::Bool(_) => "BooleanValue",
+        Value::Number(_) => "NumberValue",
+        Value::String(_) => "StringValue",
+        Value::Array(_) => "ArrayValue",
+        Value::Object(_) => "ObjectValue",
+    }
+}
+
+/// Escape a key for use in a JSON Pointer, as specified by RFC 6901.
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape(key: &str) -> String {
+    key.replace("~1", "/").replace("~0", "~")
+}
@@ -0,0 +1,320 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use serde_json::{json, Value};
+use trustfall_core::ir::FieldValue;
+use trustfall_json::{InferenceError, InferredSchema, JsonAdapter};
+use trustfall_testing::{column, execute};
+
+fn document() -> Value {
+    json!({
+        "name": "trustfall",
+        "stars": 2000,
+        "score": 4.5,
+        "archived": false,
+        "license": null,
+        "tags/topics": ["query", "engine"],
+        "owner": {"login": "obi1kenobi", "id": 1},
+    })
+}
+
+#[test]
+fn resolves_values_in_the_generic_schema() {
+    let query = r#"
+{
+    Root {
+        children {
+            pointer @output
+            key @output
+            kind: __typename @output
+            json @output
+        }
+    }
+}"#;
+    let adapter = Arc::new(JsonAdapter::new(document()));
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(
+        vec![
+            "/archived",
+            "/license",
+            "/name",
+            "/owner",
+            "/score",
+            "/stars",
+            "/tags~1topics"
+        ]
+        .into_iter()
+        .map(FieldValue::from)
+        .collect::<Vec<_>>(),
+        column(&rows, "pointer"),
+    );
+    assert_eq!(FieldValue::from("tags/topics"), rows[6]["key"]);
+    assert_eq!(
+        vec![
+            "BooleanValue",
+            "NullValue",
+            "StringValue",
+            "ObjectValue",
+            "NumberValue",
+            "NumberValue",
+            "ArrayValue"
+        ]
+        .into_iter()
+        .map(FieldValue::from)
+        .collect::<Vec<_>>(),
+        column(&rows, "kind"),
+    );
+    assert_eq!(FieldValue::from(r#"["query","engine"]"#), rows[6]["json"]);
+
+    let query = r#"
+{
+    Root {
+        ... on ObjectValue {
+            length @output
+            keys @output
+            key @output
+        }
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(FieldValue::Uint64(7), rows[0]["length"]);
+    assert_eq!(FieldValue::Null, rows[0]["key"]);
+    assert_eq!(
+        FieldValue::List(
+            vec![
+                "archived",
+                "license",
+                "name",
+                "owner",
+                "score",
+                "stars",
+                "tags/topics"
+            ]
+            .into_iter()
+            .map(FieldValue::from)
+            .collect()
+        ),
+        rows[0]["keys"],
+    );
+}
+
+#[test]
+fn follows_pointers_fields_and_items() {
+    let query = r#"
+{
+    Pointer(pointer: "/score") {
+        ... on NumberValue {
+            value @output
+            integer @output
+        }
+    }
+}"#;
+    let adapter = Arc::new(JsonAdapter::new(document()));
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(FieldValue::Float64(4.5), rows[0]["value"]);
+    assert_eq!(FieldValue::Null, rows[0]["integer"]);
+
+    let query = r#"
+{
+    Root {
+        ... on ObjectValue {
+            field(key: "tags/topics") {
+                ... on ArrayValue {
+                    item(index: 1) {
+                        ... on StringValue {
+                            value @output
+
+                            parent {
+                                parent {
+                                    root: pointer @output
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(1, rows.len());
+    assert_eq!(FieldValue::from("engine"), rows[0]["value"]);
+    assert_eq!(FieldValue::from(""), rows[0]["root"]);
+
+    for pointer in ["/missing", "/tags~1topics/2", "/stars/0", "name"] {
+        let query = format!(r#"{{ Pointer(pointer: "{pointer}") {{ json @output }} }}"#);
+        let rows = execute(adapter.schema(), adapter.clone(), &query, BTreeMap::new());
+        assert!(rows.is_empty(), "{pointer}");
+    }
+}
+
+#[test]
+fn recurses_through_nested_values() {
+    let query = r#"
+{
+    Root {
+        children @recurse(depth: 3) {
+            ... on NumberValue {
+                pointer @output
+                integer @filter(op: ">", value: ["$min"]) @output
+            }
+        }
+    }
+}"#;
+    let arguments = BTreeMap::from([("min".into(), FieldValue::Int64(1))]);
+    let adapter = Arc::new(JsonAdapter::new(document()));
+    let rows = execute(adapter.schema(), adapter.clone(), query, arguments);
+    assert_eq!(vec![FieldValue::from("/stars")], column(&rows, "pointer"));
+    assert_eq!(vec![FieldValue::Uint64(2000)], column(&rows, "integer"));
+}
+
+#[test]
+fn infers_types_properties_and_edges() {
+    let sample = json!([
+        {"id": 1, "price": 10, "tags": ["a"], "seller": {"name": "x"}, "extra": null},
+        {"id": 2, "price": 2.5, "tags": [], "seller": {"name": "y", "rating": 5}},
+        {"id": 3, "mixed": 1, "matrix": [[1]], "reviews": [{"stars": 4}, {"stars": 5, "text": "ok"}]},
+        {"id": 4, "mixed": "one"},
+    ]);
+    let adapter = Arc::new(JsonAdapter::infer(sample).unwrap());
+    let query = r#"
+{
+    Document {
+        id @output
+        price @output
+        tags @output
+        mixed @output
+        matrix @output
+        extra @output
+
+        seller @optional {
+            seller: name @output
+            rating @output
+        }
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(
+        vec![1i64, 2, 3, 4]
+            .into_iter()
+            .map(FieldValue::from)
+            .collect::<Vec<_>>(),
+        column(&rows, "id")
+    );
+    assert_eq!(
+        vec![
+            FieldValue::Float64(10.0),
+            FieldValue::Float64(2.5),
+            FieldValue::Null,
+            FieldValue::Null
+        ],
+        column(&rows, "price")
+    );
+    assert_eq!(FieldValue::List(vec!["a".into()]), rows[0]["tags"]);
+    assert_eq!(FieldValue::List(vec![]), rows[1]["tags"]);
+    assert_eq!(FieldValue::from("1"), rows[2]["mixed"]);
+    assert_eq!(FieldValue::from(r#""one""#), rows[3]["mixed"]);
+    assert_eq!(FieldValue::from("[[1]]"), rows[2]["matrix"]);
+    assert_eq!(FieldValue::Null, rows[0]["extra"]);
+    assert_eq!(
+        vec![
+            FieldValue::from("x"),
+            "y".into(),
+            FieldValue::Null,
+            FieldValue::Null
+        ],
+        column(&rows, "seller")
+    );
+    assert_eq!(
+        vec![
+            FieldValue::Null,
+            5i64.into(),
+            FieldValue::Null,
+            FieldValue::Null
+        ],
+        column(&rows, "rating")
+    );
+
+    let query = r#"
+{
+    Document {
+        id @output
+
+        reviews {
+            stars @output
+            text @output
+        }
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(vec![FieldValue::Int64(3); 2], column(&rows, "id"));
+    assert_eq!(
+        vec![FieldValue::Int64(4), 5i64.into()],
+        column(&rows, "stars")
+    );
+    assert_eq!(vec![FieldValue::Null, "ok".into()], column(&rows, "text"));
+}
+
+#[test]
+fn renames_keys_that_are_not_graphql_names() {
+    let sample = json!({
+        "first-name": "Ada",
+        "first_name": "Lovelace",
+        "2fa": true,
+        "__proto__": "object",
+        "home address": {"zip code": "12345"},
+    });
+    let adapter = Arc::new(JsonAdapter::infer(sample).unwrap());
+    let query = r#"
+{
+    Document {
+        a: first_name @output
+        b: first_name_2 @output
+        _2fa @output
+        _proto__ @output
+
+        home_address {
+            kind: __typename @output
+            zip_code @output
+        }
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(1, rows.len());
+    let row = &rows[0];
+    assert_eq!(FieldValue::from("Ada"), row["a"]);
+    assert_eq!(FieldValue::from("Lovelace"), row["b"]);
+    assert_eq!(FieldValue::Boolean(true), row["_2fa"]);
+    assert_eq!(FieldValue::from("object"), row["_proto__"]);
+    assert_eq!(FieldValue::from("DocumentHomeAddress"), row["kind"]);
+    assert_eq!(FieldValue::from("12345"), row["zip_code"]);
+}
+
+#[test]
+fn queries_other_documents_with_an_inferred_schema() {
+    let schema = InferredSchema::infer(&json!({"count": 1, "items": [{"id": "a"}]})).unwrap();
+    let document = json!({"count": "many", "items": [{"id": "b"}, 3, {"id": "c"}]});
+    let adapter = Arc::new(JsonAdapter::with_schema(document, schema));
+    let query = r#"
+{
+    Document {
+        count @output
+
+        items {
+            id @output
+        }
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(vec![FieldValue::Null; 2], column(&rows, "count"));
+    assert_eq!(vec![FieldValue::from("b"), "c".into()], column(&rows, "id"));
+
+    for sample in [json!(1), json!([]), json!({}), json!([1, 2])] {
+        assert!(
+            matches!(
+                InferredSchema::infer(&sample),
+                Err(InferenceError::NotObjects)
+            ),
+            "{sample}"
+        );
+    }
+}
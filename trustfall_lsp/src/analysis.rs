@@ -0,0 +1,438 @@
+//! Where things are in a query: which vertex type each selection set is in,
+//! and what each field, directive, and type coercion refers to.
+//!
+//! The analysis works on tokens rather than on a parsed query, since queries being edited
+//! usually don't parse, and completions are needed most of all while they don't.
+use std::{ops::Range, sync::Arc};
+
+use trustfall_core::schema::{EdgeInfo, PropertyInfo, Schema, VertexTypeInfo};
+
+use crate::tokens::{tokenize, Token, TokenKind};
+
+pub(crate) const TYPENAME_META_FIELD: &str = "__typename";
+
+#[derive(Debug)]
+pub(crate) struct DirectiveInfo {
+    pub(crate) name: &'static str,
+    pub(crate) signature: &'static str,
+    pub(crate) description: &'static str,
+    pub(crate) on_properties: bool,
+    pub(crate) on_edges: bool,
+}
+
+pub(crate) const DIRECTIVES: &[DirectiveInfo] = &[
+    DirectiveInfo {
+        name: "filter",
+        signature: "@filter(op: String!, value: [String!])",
+        description:
+            "Keeps only the vertices whose property value satisfies the filter operation. \
+            Values are query variables like `$name`, or tagged values like `%name`.",
+        on_properties: true,
+        on_edges: false,
+    },
+    DirectiveInfo {
+        name: "output",
+        signature: "@output(name: String)",
+        description: "Includes the property's value in the query's results, \
+            named by its `name` argument, or else by the property's alias or name.",
+        on_properties: true,
+        on_edges: false,
+    },
+    DirectiveInfo {
+        name: "tag",
+        signature: "@tag(name: String)",
+        description: "Makes the property's value available to filters elsewhere in the query \
+            as `%name`, named by its `name` argument, or else by the property's alias or name.",
+        on_properties: true,
+        on_edges: false,
+    },
+    DirectiveInfo {
+        name: "optional",
+        signature: "@optional",
+        description: "Makes the edge optional: vertices without neighbors along it \
+            are still part of the results, with `null` outputs for everything within the edge.",
+        on_properties: false,
+        on_edges: true,
+    },
+    DirectiveInfo {
+        name: "recurse",
+        signature: "@recurse(depth: Int!)",
+        description: "Follows the edge repeatedly, up to `depth` times. \
+            The vertex the edge starts from is included, at depth 0.",
+        on_properties: false,
+        on_edges: true,
+    },
+    DirectiveInfo {
+        name: "fold",
+        signature: "@fold",
+        description: "Aggregates the edge's neighbors, making each output within the edge \
+            a list of values instead of producing a result for each neighbor.",
+        on_properties: false,
+        on_edges: true,
+    },
+    DirectiveInfo {
+        name: "transform",
+        signature: "@transform(op: String!)",
+        description: "Transforms the value the directive is applied to. \
+            The `count` operation counts the neighbors of a `@fold` edge.",
+        on_properties: false,
+        on_edges: true,
+    },
+];
+
+pub(crate) const FILTER_OPERATIONS: &[(&str, &str)] = &[
+    ("=", "Equal to the value."),
+    ("!=", "Not equal to the value."),
+    ("<", "Less than the value."),
+    ("<=", "Less than or equal to the value."),
+    (">", "Greater than the value."),
+    (">=", "Greater than or equal to the value."),
+    ("is_null", "Null. Takes no value."),
+    ("is_not_null", "Not null. Takes no value."),
+    ("contains", "A list containing the value."),
+    ("not_contains", "A list not containing the value."),
+    ("one_of", "One of the values in the list."),
+    ("not_one_of", "Not one of the values in the list."),
+    ("has_prefix", "A string starting with the value."),
+    ("not_has_prefix", "A string not starting with the value."),
+    ("has_suffix", "A string ending with the value."),
+    ("not_has_suffix", "A string not ending with the value."),
+    ("has_substring", "A string containing the value."),
+    ("not_has_substring", "A string not containing the value."),
+    ("regex", "A string matching the regular expression."),
+    ("not_regex", "A string not matching the regular expression."),
+];
+
+pub(crate) const TRANSFORM_OPERATIONS: &[(&str, &str)] =
+    &[("count", "The number of neighbors of the `@fold` edge.")];
+
+pub(crate) fn directive(name: &str) -> Option<&'static DirectiveInfo> {
+    DIRECTIVES.iter().find(|directive| directive.name == name)
+}
+
+/// What a name in the query refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum SymbolKind {
+    /// A property or edge of the vertex type of the selection set the name is in.
+    Field {
+        parent_type: Arc<str>,
+        name: String,
+    },
+    Directive(String),
+    TypeCoercion(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Symbol {
+    pub(crate) range: Range<usize>,
+    pub(crate) kind: SymbolKind,
+}
+
+/// A selection set: the text within a pair of braces, and the vertex type it selects from
+/// if that type is known.
+#[derive(Debug, Clone)]
+struct Scope {
+    range: Range<usize>,
+    type_name: Option<Arc<str>>,
+}
+
+/// What the text being typed at a position should complete to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum CompletionContext {
+    /// A property or edge of the given vertex type.
+    Field(Arc<str>),
+
+    /// A directive on a vertex's property or edge; `None` if it isn't clear which.
+    Directive { on_edge: Option<bool> },
+
+    /// A subtype of the given vertex type in a type coercion, or any vertex type
+    /// if the vertex type isn't known.
+    TypeCoercion(Option<Arc<str>>),
+
+    /// A parameter of the given edge.
+    EdgeParameter { parent_type: Arc<str>, edge: String },
+
+    /// The `op` argument of a `@filter` directive.
+    FilterOperation,
+
+    /// The `op` argument of a `@transform` directive.
+    TransformOperation,
+}
+
+#[derive(Debug)]
+pub(crate) struct Analysis<'a> {
+    schema: &'a Schema,
+    text: &'a str,
+    tokens: Vec<Token>,
+    symbols: Vec<Symbol>,
+    scopes: Vec<Scope>,
+}
+
+impl<'a> Analysis<'a> {
+    pub(crate) fn new(schema: &'a Schema, text: &'a str) -> Self {
+        let tokens = tokenize(text);
+        let mut symbols = vec![];
+        let mut scopes = vec![];
+
+        let root_type: Arc<str> = Arc::from(schema.root_type().name());
+        let mut open_scopes: Vec<(usize, Option<Arc<str>>)> = vec![];
+        let mut paren_depth = 0usize;
+
+        // The type that a selection set opened after the current token would select from.
+        let mut pending_type: Option<Arc<str>> = None;
+
+        for (index, token) in tokens.iter().enumerate() {
+            let previous = index.checked_sub(1).map(|i| &tokens[i]);
+            let before_previous = index.checked_sub(2).map(|i| &tokens[i]);
+            let next = tokens.get(index + 1);
+            let is = |token: Option<&Token>, kind, expected| {
+                token.is_some_and(|token| token.is(text, kind, expected))
+            };
+
+            match (token.kind, token.text(text)) {
+                (TokenKind::Punctuator, "(") => paren_depth += 1,
+                (TokenKind::Punctuator, ")") => paren_depth = paren_depth.saturating_sub(1),
+                _ if paren_depth > 0 => {}
+                (TokenKind::Punctuator, "{") => {
+                    let type_name = if open_scopes.is_empty() {
+                        Some(root_type.clone())
+                    } else {
+                        pending_type.take()
+                    };
+                    open_scopes.push((token.end, type_name));
+                }
+                (TokenKind::Punctuator, "}") => {
+                    if let Some((start, type_name)) = open_scopes.pop() {
+                        scopes.push(Scope {
+                            range: start..token.start,
+                            type_name,
+                        });
+                    }
+                    pending_type = None;
+                }
+                (TokenKind::Name, name) => {
+                    if is(previous, TokenKind::Punctuator, "@") {
+                        // Directives go between a field and its selection set,
+                        // so they leave the pending type as it is.
+                        symbols.push(Symbol {
+                            range: token.range(),
+                            kind: SymbolKind::Directive(name.to_string()),
+                        });
+                    } else if name == "on" && is(previous, TokenKind::Punctuator, "...") {
+                        // The keyword of a type coercion.
+                    } else if is(previous, TokenKind::Name, "on")
+                        && is(before_previous, TokenKind::Punctuator, "...")
+                    {
+                        symbols.push(Symbol {
+                            range: token.range(),
+                            kind: SymbolKind::TypeCoercion(name.to_string()),
+                        });
+                        pending_type = schema
+                            .vertex_type(name)
+                            .map(|vertex_type| Arc::from(vertex_type.name()));
+                    } else if open_scopes.is_empty() || is(next, TokenKind::Punctuator, ":") {
+                        // Operation types and names, and aliases.
+                    } else if let Some((_, Some(parent_type))) = open_scopes.last() {
+                        pending_type = schema
+                            .vertex_type(parent_type)
+                            .and_then(|vertex_type| vertex_type.edge(name))
+                            .map(|edge| Arc::from(edge.target_type().name()));
+                        symbols.push(Symbol {
+                            range: token.range(),
+                            kind: SymbolKind::Field {
+                                parent_type: parent_type.clone(),
+                                name: name.to_string(),
+                            },
+                        });
+                    } else {
+                        pending_type = None;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Selection sets that aren't closed yet extend to the end of the text.
+        for (start, type_name) in open_scopes {
+            scopes.push(Scope {
+                range: start..text.len(),
+                type_name,
+            });
+        }
+
+        Self {
+            schema,
+            text,
+            tokens,
+            symbols,
+            scopes,
+        }
+    }
+
+    pub(crate) fn schema(&self) -> &'a Schema {
+        self.schema
+    }
+
+    pub(crate) fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// The symbol at the offset, including at its end so that the offset of a cursor
+    /// just past a name refers to that name.
+    pub(crate) fn symbol_at(&self, offset: usize) -> Option<&Symbol> {
+        self.symbols
+            .iter()
+            .find(|symbol| symbol.range.start <= offset && offset <= symbol.range.end)
+    }
+
+    /// The vertex type of the innermost selection set containing the offset.
+    fn scope_type(&self, offset: usize) -> Option<Option<&Arc<str>>> {
+        self.scopes
+            .iter()
+            .filter(|scope| scope.range.start <= offset && offset <= scope.range.end)
+            .min_by_key(|scope| scope.range.len())
+            .map(|scope| scope.type_name.as_ref())
+    }
+
+    /// The range of the name being typed at the offset, and what it should complete to.
+    pub(crate) fn completion_context(
+        &self,
+        offset: usize,
+    ) -> Option<(Range<usize>, CompletionContext)> {
+        let text = self.text;
+
+        // Strings are completed as a whole, and names from their start up to the offset.
+        let current = self
+            .tokens
+            .iter()
+            .position(|token| token.start < offset && offset <= token.end)
+            .filter(|index| {
+                matches!(
+                    self.tokens[*index].kind,
+                    TokenKind::Name | TokenKind::String
+                )
+            });
+        if let Some(index) = current {
+            let token = &self.tokens[index];
+            if token.kind == TokenKind::String {
+                let context = self.operation_context(index)?;
+                return Some((token.range(), context));
+            }
+        }
+
+        let start = current.map_or(offset, |index| self.tokens[index].start);
+        let preceding: Vec<&Token> = self
+            .tokens
+            .iter()
+            .take_while(|token| token.end <= start)
+            .collect();
+        let previous = preceding.last().copied();
+        let range = start..offset;
+
+        // Within parentheses, only the names of edge parameters are completed.
+        let mut depth = 0usize;
+        for (index, token) in preceding.iter().enumerate().rev() {
+            match token.text(text) {
+                ")" => depth += 1,
+                "(" if depth > 0 => depth -= 1,
+                "(" => {
+                    let edge_token = preceding.get(index.checked_sub(1)?)?;
+                    let starts_parameter = !matches!(
+                        previous.map(|token| token.text(text)),
+                        Some(":" | "[" | "$")
+                    );
+                    return match self.symbol_at(edge_token.start) {
+                        Some(Symbol {
+                            kind: SymbolKind::Field { parent_type, name },
+                            ..
+                        }) if starts_parameter => {
+                            let context = CompletionContext::EdgeParameter {
+                                parent_type: parent_type.clone(),
+                                edge: name.clone(),
+                            };
+                            Some((range, context))
+                        }
+                        _ => None,
+                    };
+                }
+                _ => {}
+            }
+        }
+
+        match previous.map(|token| token.text(text)) {
+            Some("@") => {
+                let on_edge = self
+                    .symbols
+                    .iter()
+                    .rev()
+                    .find(|symbol| {
+                        symbol.range.end <= start && matches!(symbol.kind, SymbolKind::Field { .. })
+                    })
+                    .and_then(|symbol| match &symbol.kind {
+                        SymbolKind::Field { parent_type, name } => {
+                            self.field_is_edge(parent_type, name)
+                        }
+                        _ => None,
+                    });
+                Some((range, CompletionContext::Directive { on_edge }))
+            }
+            Some("on")
+                if preceding.len() >= 2 && preceding[preceding.len() - 2].text(text) == "..." =>
+            {
+                let scope_type = self.scope_type(start).flatten().cloned();
+                Some((range, CompletionContext::TypeCoercion(scope_type)))
+            }
+            _ => {
+                let scope_type = self.scope_type(start)??;
+                Some((range, CompletionContext::Field(scope_type.clone())))
+            }
+        }
+    }
+
+    /// The completion context of the string token at the index, if it's the `op` argument
+    /// of a `@filter` or `@transform` directive.
+    fn operation_context(&self, index: usize) -> Option<CompletionContext> {
+        let text = self.text;
+        let colon = &self.tokens[index.checked_sub(1)?];
+        let argument = &self.tokens[index.checked_sub(2)?];
+        if colon.text(text) != ":" || argument.text(text) != "op" {
+            return None;
+        }
+
+        let directive = self.symbols.iter().rev().find(|symbol| {
+            symbol.range.end <= argument.start && matches!(symbol.kind, SymbolKind::Directive(_))
+        })?;
+        match &directive.kind {
+            SymbolKind::Directive(name) if name == "filter" => {
+                Some(CompletionContext::FilterOperation)
+            }
+            SymbolKind::Directive(name) if name == "transform" => {
+                Some(CompletionContext::TransformOperation)
+            }
+            _ => None,
+        }
+    }
+
+    fn field_is_edge(&self, parent_type: &str, name: &str) -> Option<bool> {
+        let vertex_type = self.schema.vertex_type(parent_type)?;
+        if vertex_type.edge(name).is_some() {
+            Some(true)
+        } else if vertex_type.property(name).is_some() || name == TYPENAME_META_FIELD {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn vertex_type(&self, name: &str) -> Option<VertexTypeInfo<'a>> {
+        self.schema.vertex_type(name)
+    }
+
+    pub(crate) fn property(&self, parent_type: &str, name: &str) -> Option<PropertyInfo<'a>> {
+        self.schema.vertex_type(parent_type)?.property(name)
+    }
+
+    pub(crate) fn edge(&self, parent_type: &str, name: &str) -> Option<EdgeInfo<'a>> {
+        self.schema.vertex_type(parent_type)?.edge(name)
+    }
+}
@@ -0,0 +1,315 @@
+use trustfall_core::{
+    frontend::{self, error::FrontendWarning},
+    schema::{EdgeInfo, PropertyInfo, VertexTypeInfo},
+};
+
+use crate::{
+    analysis::{
+        directive, Analysis, CompletionContext, SymbolKind, DIRECTIVES, FILTER_OPERATIONS,
+        TRANSFORM_OPERATIONS, TYPENAME_META_FIELD,
+    },
+    protocol::{
+        CompletionItem, Diagnostic, Hover, Location, MarkupContent, TextEdit, COMPLETION_CLASS,
+        COMPLETION_FIELD, COMPLETION_FUNCTION, COMPLETION_INTERFACE, COMPLETION_OPERATOR,
+        COMPLETION_PROPERTY, COMPLETION_VARIABLE, SEVERITY_ERROR, SEVERITY_WARNING, TAG_DEPRECATED,
+    },
+    schema_index::SchemaIndex,
+    text::LineIndex,
+};
+
+fn item(
+    label: &str,
+    kind: u8,
+    detail: Option<String>,
+    documentation: Option<&str>,
+) -> CompletionItem {
+    CompletionItem {
+        label: label.to_string(),
+        kind,
+        detail,
+        documentation: documentation.map(|d| MarkupContent::markdown(d.to_string())),
+        deprecated: false,
+        filter_text: None,
+        text_edit: None,
+    }
+}
+
+fn property_item(property: PropertyInfo<'_>) -> CompletionItem {
+    CompletionItem {
+        deprecated: property.deprecation_reason().is_some(),
+        ..item(
+            property.name(),
+            COMPLETION_PROPERTY,
+            Some(property.property_type().to_string()),
+            property.description(),
+        )
+    }
+}
+
+fn edge_item(edge: EdgeInfo<'_>) -> CompletionItem {
+    CompletionItem {
+        deprecated: edge.deprecation_reason().is_some(),
+        ..item(
+            edge.name(),
+            COMPLETION_FIELD,
+            Some(edge_signature(edge)),
+            edge.description(),
+        )
+    }
+}
+
+fn vertex_type_item(vertex_type: VertexTypeInfo<'_>) -> CompletionItem {
+    let kind = if vertex_type.is_interface() {
+        COMPLETION_INTERFACE
+    } else {
+        COMPLETION_CLASS
+    };
+    item(vertex_type.name(), kind, None, vertex_type.description())
+}
+
+/// The edge's parameters and type, like `(max: Int!): [Number!]`.
+fn edge_signature(edge: EdgeInfo<'_>) -> String {
+    let parameters: Vec<_> = edge
+        .parameters()
+        .map(|parameter| format!("{}: {}", parameter.name(), parameter.parameter_type()))
+        .collect();
+    if parameters.is_empty() {
+        edge.edge_type().to_string()
+    } else {
+        format!("({}): {}", parameters.join(", "), edge.edge_type())
+    }
+}
+
+/// The completions of the text being typed at the offset, each of which replaces that text.
+pub(crate) fn completions(
+    analysis: &Analysis<'_>,
+    line_index: &LineIndex,
+    text: &str,
+    offset: usize,
+) -> Vec<CompletionItem> {
+    let Some((range, context)) = analysis.completion_context(offset) else {
+        return vec![];
+    };
+    let range = line_index.range(text, range);
+    let mut items = context_completions(analysis, context);
+    for item in &mut items {
+        let new_text = item
+            .filter_text
+            .clone()
+            .unwrap_or_else(|| item.label.clone());
+        item.text_edit = Some(TextEdit { range, new_text });
+    }
+    items
+}
+
+fn context_completions(analysis: &Analysis<'_>, context: CompletionContext) -> Vec<CompletionItem> {
+    match context {
+        CompletionContext::Field(type_name) => {
+            let Some(vertex_type) = analysis.vertex_type(&type_name) else {
+                return vec![];
+            };
+            let mut items: Vec<_> = vertex_type.properties().map(property_item).collect();
+
+            // The root type's fields are where queries start, rather than a vertex's fields.
+            if vertex_type.name() != analysis.schema().root_type().name() {
+                items.push(item(
+                    TYPENAME_META_FIELD,
+                    COMPLETION_PROPERTY,
+                    Some("String!".to_string()),
+                    Some("The name of the vertex's type."),
+                ));
+            }
+            items.extend(vertex_type.edges().map(edge_item));
+            items
+        }
+        CompletionContext::Directive { on_edge } => DIRECTIVES
+            .iter()
+            .filter(|directive| match on_edge {
+                Some(true) => directive.on_edges,
+                Some(false) => directive.on_properties,
+                None => true,
+            })
+            .map(|directive| {
+                item(
+                    directive.name,
+                    COMPLETION_FUNCTION,
+                    Some(directive.signature.to_string()),
+                    Some(directive.description),
+                )
+            })
+            .collect(),
+        CompletionContext::TypeCoercion(Some(type_name)) => analysis
+            .vertex_type(&type_name)
+            .into_iter()
+            .flat_map(|vertex_type| vertex_type.implementers())
+            .map(vertex_type_item)
+            .collect(),
+        CompletionContext::TypeCoercion(None) => analysis
+            .schema()
+            .vertex_types()
+            .map(vertex_type_item)
+            .collect(),
+        CompletionContext::EdgeParameter { parent_type, edge } => analysis
+            .edge(&parent_type, &edge)
+            .into_iter()
+            .flat_map(|edge| edge.parameters())
+            .map(|parameter| {
+                item(
+                    parameter.name(),
+                    COMPLETION_VARIABLE,
+                    Some(parameter.parameter_type().to_string()),
+                    parameter.description(),
+                )
+            })
+            .collect(),
+        CompletionContext::FilterOperation => operation_items(FILTER_OPERATIONS),
+        CompletionContext::TransformOperation => operation_items(TRANSFORM_OPERATIONS),
+    }
+}
+
+fn operation_items(operations: &[(&str, &str)]) -> Vec<CompletionItem> {
+    operations
+        .iter()
+        .map(|(name, description)| {
+            CompletionItem {
+                // Operations replace the whole string they're completed in, quotes included.
+                filter_text: Some(format!("\"{name}\"")),
+                ..item(name, COMPLETION_OPERATOR, None, Some(description))
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn hover(
+    analysis: &Analysis<'_>,
+    line_index: &LineIndex,
+    text: &str,
+    offset: usize,
+) -> Option<Hover> {
+    let symbol = analysis.symbol_at(offset)?;
+    let (signature, description, deprecation) = match &symbol.kind {
+        SymbolKind::Field { parent_type, name } => {
+            if let Some(property) = analysis.property(parent_type, name) {
+                (
+                    format!("{parent_type}.{name}: {}", property.property_type()),
+                    property.description(),
+                    property.deprecation_reason(),
+                )
+            } else if let Some(edge) = analysis.edge(parent_type, name) {
+                (
+                    format!("{parent_type}.{name}{}", edge_signature(edge)),
+                    edge.description(),
+                    edge.deprecation_reason(),
+                )
+            } else if name == TYPENAME_META_FIELD {
+                (
+                    format!("{parent_type}.{name}: String!"),
+                    Some("The name of the vertex's type."),
+                    None,
+                )
+            } else {
+                return None;
+            }
+        }
+        SymbolKind::Directive(name) => {
+            let directive = directive(name)?;
+            (
+                directive.signature.to_string(),
+                Some(directive.description),
+                None,
+            )
+        }
+        SymbolKind::TypeCoercion(name) => {
+            let vertex_type = analysis.vertex_type(name)?;
+            let kind = if vertex_type.is_interface() {
+                "interface"
+            } else {
+                "type"
+            };
+            (format!("{kind} {name}"), vertex_type.description(), None)
+        }
+    };
+
+    let mut contents = format!("```graphql\n{signature}\n```");
+    if let Some(reason) = deprecation {
+        contents.push_str(&format!("\n\n**Deprecated:** {reason}"));
+    }
+    if let Some(description) = description {
+        contents.push_str(&format!("\n\n{description}"));
+    }
+    Some(Hover {
+        contents: MarkupContent::markdown(contents),
+        range: line_index.range(text, symbol.range.clone()),
+    })
+}
+
+pub(crate) fn definition(
+    analysis: &Analysis<'_>,
+    schema: &SchemaIndex,
+    offset: usize,
+) -> Option<Location> {
+    match &analysis.symbol_at(offset)?.kind {
+        SymbolKind::Field { parent_type, name } => schema.field_location(parent_type, name),
+        SymbolKind::Directive(name) => schema.directive_location(name),
+        SymbolKind::TypeCoercion(name) => schema.type_location(name),
+    }
+}
+
+/// The errors that prevent the query from compiling, and warnings about its uses
+/// of deprecated properties and edges.
+pub(crate) fn diagnostics(
+    analysis: &Analysis<'_>,
+    line_index: &LineIndex,
+    text: &str,
+) -> Vec<Diagnostic> {
+    let query = match frontend::parse(analysis.schema(), text) {
+        Ok(query) => query,
+        Err(e) => {
+            return e
+                .errors()
+                .iter()
+                .map(|error| {
+                    let range = error
+                        .span()
+                        .and_then(|span| span.byte_range(text))
+                        .unwrap_or(0..0);
+                    Diagnostic {
+                        range: line_index.range(text, range),
+                        severity: SEVERITY_ERROR,
+                        source: "trustfall",
+                        message: error.to_string(),
+                        tags: vec![],
+                    }
+                })
+                .collect();
+        }
+    };
+
+    let mut diagnostics = vec![];
+    for warning in &query.ir_query.warnings {
+        let (type_name, field_name) = match warning {
+            FrontendWarning::DeprecatedPropertyUsed(type_name, field_name, _)
+            | FrontendWarning::DeprecatedEdgeUsed(type_name, field_name, _) => {
+                (type_name, field_name)
+            }
+            _ => continue,
+        };
+        let uses = analysis.symbols().iter().filter(|symbol| {
+            matches!(
+                &symbol.kind,
+                SymbolKind::Field { parent_type, name }
+                    if parent_type.as_ref() == type_name && name == field_name
+            )
+        });
+        for symbol in uses {
+            diagnostics.push(Diagnostic {
+                range: line_index.range(text, symbol.range.clone()),
+                severity: SEVERITY_WARNING,
+                source: "trustfall",
+                message: warning.to_string(),
+                tags: vec![TAG_DEPRECATED],
+            });
+        }
+    }
+    diagnostics
+}
@@ -0,0 +1,24 @@
+//! A language server for trustfall queries.
+//!
+//! Queries are checked against a configured schema as they're edited:
+//! - properties, edges, and their parameters are completed from the schema, as are
+//!   directives, the types of type coercions, and `@filter` and `@transform` operations;
+//! - hovering over a property, edge, directive, or type shows its type and description;
+//! - going to the definition of a property, edge, or type goes to where the schema defines it;
+//! - the errors that prevent a query from compiling, and uses of deprecated properties and
+//!   edges, are reported as diagnostics at the part of the query they refer to.
+//!
+//! The `trustfall-lsp` binary serves the protocol over stdin and stdout.
+#![forbid(unsafe_code)]
+
+mod analysis;
+mod features;
+mod protocol;
+mod schema_index;
+mod server;
+mod text;
+mod tokens;
+
+pub use protocol::{read_message, write_message, Position, Range};
+pub use schema_index::{SchemaIndex, SchemaLoadError};
+pub use server::Server;
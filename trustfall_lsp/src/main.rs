@@ -0,0 +1,35 @@
+#![forbid(unsafe_code)]
+
+use std::{env, io, process};
+
+use trustfall_lsp::{SchemaIndex, Server};
+
+const USAGE: &str = "Usage: trustfall-lsp [--schema <path>]
+
+Serves the Language Server Protocol over stdin and stdout. Without --schema,
+the schema is loaded from the `schema` initialization option sent by the client.";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let schema = match args.as_slice() {
+        [] => None,
+        [flag, path] if flag == "--schema" => match SchemaIndex::load(path) {
+            Ok(schema) => Some(schema),
+            Err(e) => {
+                eprintln!("{e}");
+                process::exit(2);
+            }
+        },
+        _ => {
+            eprintln!("{USAGE}");
+            process::exit(2);
+        }
+    };
+
+    let mut server = Server::new(schema);
+    if let Err(e) = server.run(io::stdin().lock(), io::stdout().lock()) {
+        eprintln!("trustfall-lsp: {e}");
+        process::exit(1);
+    }
+    process::exit(server.exit_code());
+}
@@ -0,0 +1,190 @@
+//! The subset of the Language Server Protocol that the server speaks,
+//! and the JSON-RPC framing its messages are sent with.
+use std::io::{self, BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Read the next message, or `None` if the input ended before it started.
+///
+/// Messages are JSON values preceded by headers, of which only `Content-Length` is used.
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            if content_length.is_none() {
+                return Ok(None);
+            }
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the input ended within the headers of a message",
+            ));
+        }
+
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                let length = value.trim().parse().map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid Content-Length header: {header}"),
+                    )
+                })?;
+                content_length = Some(length);
+            }
+        }
+    }
+
+    let content_length = content_length.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message is missing its Content-Length header",
+        )
+    })?;
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn write_message(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let content = serde_json::to_vec(message)?;
+    write!(writer, "Content-Length: {}\r\n\r\n", content.len())?;
+    writer.write_all(&content)?;
+    writer.flush()
+}
+
+/// A position in a document, as a 0-based line and 0-based UTF-16 code unit offset in that line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct Location {
+    pub uri: String,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct Diagnostic {
+    pub range: Range,
+    pub severity: u8,
+    pub source: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<u8>,
+}
+
+pub(crate) const SEVERITY_ERROR: u8 = 1;
+pub(crate) const SEVERITY_WARNING: u8 = 2;
+pub(crate) const TAG_DEPRECATED: u8 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CompletionItem {
+    pub label: String,
+    pub kind: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<MarkupContent>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_edit: Option<TextEdit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TextEdit {
+    pub range: Range,
+    pub new_text: String,
+}
+
+pub(crate) const COMPLETION_FUNCTION: u8 = 3;
+pub(crate) const COMPLETION_FIELD: u8 = 5;
+pub(crate) const COMPLETION_VARIABLE: u8 = 6;
+pub(crate) const COMPLETION_CLASS: u8 = 7;
+pub(crate) const COMPLETION_INTERFACE: u8 = 8;
+pub(crate) const COMPLETION_PROPERTY: u8 = 10;
+pub(crate) const COMPLETION_OPERATOR: u8 = 24;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct MarkupContent {
+    pub kind: &'static str,
+    pub value: String,
+}
+
+impl MarkupContent {
+    pub(crate) fn markdown(value: String) -> Self {
+        Self {
+            kind: "markdown",
+            value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct Hover {
+    pub contents: MarkupContent,
+    pub range: Range,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct TextDocumentIdentifier {
+    pub uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TextDocumentItem {
+    pub uri: String,
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DidOpenParams {
+    pub text_document: TextDocumentItem,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ContentChange {
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DidChangeParams {
+    pub text_document: TextDocumentIdentifier,
+    pub content_changes: Vec<ContentChange>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DidCloseParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TextDocumentPositionParams {
+    pub text_document: TextDocumentIdentifier,
+    pub position: Position,
+}
@@ -0,0 +1,130 @@
+use std::{collections::HashMap, fs, ops::Range, path::Path};
+
+use async_graphql_parser::{
+    parse_schema,
+    types::{TypeKind, TypeSystemDefinition},
+    Pos,
+};
+use trustfall_core::schema::{error::InvalidSchemaError, Schema};
+
+use crate::{protocol::Location, text::LineIndex};
+
+/// The schema queries are checked against, along with where each of its definitions is
+/// in its source text, for go-to-definition.
+#[derive(Debug, Clone)]
+pub struct SchemaIndex {
+    schema: Schema,
+    uri: Option<String>,
+    text: String,
+    line_index: LineIndex,
+    types: HashMap<String, Range<usize>>,
+    fields: HashMap<(String, String), Range<usize>>,
+    directives: HashMap<String, Range<usize>>,
+}
+
+/// An error loading the schema from a file.
+///
+/// Its message is shown to the user as-is, so it includes the message of its cause.
+#[derive(Debug, thiserror::Error)]
+pub enum SchemaLoadError {
+    #[error("Failed to read the schema file {0}: {1}")]
+    Io(String, std::io::Error),
+
+    #[error("The schema in {0} is invalid: {1}")]
+    InvalidSchema(String, InvalidSchemaError),
+}
+
+impl SchemaIndex {
+    /// Index the schema with the given text.
+    ///
+    /// Definitions can only be located when the URI of the schema's text is given.
+    pub fn new(text: impl Into<String>, uri: Option<String>) -> Result<Self, InvalidSchemaError> {
+        let text = text.into();
+        let schema = Schema::parse(&text)?;
+        let line_index = LineIndex::new(&text);
+        let mut index = Self {
+            schema,
+            uri,
+            text,
+            line_index,
+            types: Default::default(),
+            fields: Default::default(),
+            directives: Default::default(),
+        };
+
+        // The schema was just parsed successfully, so parsing it again can't fail.
+        let document = parse_schema(&index.text).expect("valid schema failed to parse");
+        for definition in &document.definitions {
+            match definition {
+                TypeSystemDefinition::Type(defn) => {
+                    let type_name = defn.node.name.node.to_string();
+                    let range = index.name_range(defn.node.name.pos, &type_name);
+                    index.types.insert(type_name.clone(), range);
+
+                    let fields = match &defn.node.kind {
+                        TypeKind::Object(object) => &object.fields,
+                        TypeKind::Interface(interface) => &interface.fields,
+                        _ => continue,
+                    };
+                    for field in fields {
+                        let field_name = field.node.name.node.to_string();
+                        let range = index.name_range(field.node.name.pos, &field_name);
+                        index.fields.insert((type_name.clone(), field_name), range);
+                    }
+                }
+                TypeSystemDefinition::Directive(defn) => {
+                    let name = defn.node.name.node.to_string();
+                    let range = index.name_range(defn.node.name.pos, &name);
+                    index.directives.insert(name, range);
+                }
+                TypeSystemDefinition::Schema(_) => {}
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// Load and index the schema in the given file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SchemaLoadError> {
+        let path = path.as_ref();
+        let display = path.display().to_string();
+        let text = fs::read_to_string(path).map_err(|e| SchemaLoadError::Io(display.clone(), e))?;
+        let uri = path
+            .canonicalize()
+            .ok()
+            .and_then(|path| url::Url::from_file_path(path).ok())
+            .map(String::from);
+        Self::new(text, uri).map_err(|e| SchemaLoadError::InvalidSchema(display, e))
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    fn name_range(&self, pos: Pos, name: &str) -> Range<usize> {
+        let start = self.line_index.parser_offset(&self.text, pos);
+        start..start + name.len()
+    }
+
+    fn location(&self, range: Option<&Range<usize>>) -> Option<Location> {
+        Some(Location {
+            uri: self.uri.clone()?,
+            range: self.line_index.range(&self.text, range?.clone()),
+        })
+    }
+
+    pub(crate) fn type_location(&self, type_name: &str) -> Option<Location> {
+        self.location(self.types.get(type_name))
+    }
+
+    pub(crate) fn field_location(&self, type_name: &str, field_name: &str) -> Option<Location> {
+        self.location(
+            self.fields
+                .get(&(type_name.to_string(), field_name.to_string())),
+        )
+    }
+
+    pub(crate) fn directive_location(&self, name: &str) -> Option<Location> {
+        self.location(self.directives.get(name))
+    }
+}
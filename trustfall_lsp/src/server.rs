@@ -0,0 +1,291 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::{
+    analysis::Analysis,
+    features,
+    protocol::{
+        read_message, write_message, DidChangeParams, DidCloseParams, DidOpenParams,
+        TextDocumentPositionParams,
+    },
+    schema_index::SchemaIndex,
+    text::LineIndex,
+};
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INVALID_REQUEST: i64 = -32600;
+
+const MESSAGE_ERROR: u8 = 1;
+const MESSAGE_WARNING: u8 = 2;
+
+#[derive(Debug)]
+struct Document {
+    text: String,
+    line_index: LineIndex,
+}
+
+impl Document {
+    fn new(text: String) -> Self {
+        let line_index = LineIndex::new(&text);
+        Self { text, line_index }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Running,
+    ShutDown,
+    Exited { after_shutdown: bool },
+}
+
+/// A language server for trustfall queries, checking them against a single schema.
+///
+/// The schema is either given when creating the server, or loaded from the path in
+/// the `schema` initialization option sent by the client, relative to the workspace root.
+/// Documents are synchronized in full on every change.
+#[derive(Debug)]
+pub struct Server {
+    schema: Option<SchemaIndex>,
+    documents: HashMap<String, Document>,
+    state: State,
+}
+
+type RequestResult = Result<Value, (i64, String)>;
+
+impl Server {
+    pub fn new(schema: Option<SchemaIndex>) -> Self {
+        Self {
+            schema,
+            documents: Default::default(),
+            state: State::Running,
+        }
+    }
+
+    /// Whether the client has told the server to exit.
+    pub fn has_exited(&self) -> bool {
+        matches!(self.state, State::Exited { .. })
+    }
+
+    /// The process exit code the protocol specifies: success only when
+    /// the client shut the server down before telling it to exit.
+    pub fn exit_code(&self) -> i32 {
+        match self.state {
+            State::Exited {
+                after_shutdown: true,
+            } => 0,
+            _ => 1,
+        }
+    }
+
+    /// Serve the messages read from the reader, until the client tells the server to exit
+    /// or the input ends.
+    pub fn run(&mut self, mut reader: impl BufRead, mut writer: impl Write) -> io::Result<()> {
+        while !self.has_exited() {
+            let Some(message) = read_message(&mut reader)? else {
+                break;
+            };
+            for outgoing in self.handle(message) {
+                write_message(&mut writer, &outgoing)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle a message from the client, returning the messages to send back to it.
+    pub fn handle(&mut self, message: Value) -> Vec<Value> {
+        let mut outgoing = vec![];
+        let method = message.get("method").and_then(Value::as_str);
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        match (method, message.get("id")) {
+            (Some(method), Some(id)) => {
+                let result = if self.state == State::Running {
+                    self.request(method, params, &mut outgoing)
+                } else {
+                    Err((INVALID_REQUEST, "The server is shutting down.".to_string()))
+                };
+                let response = match result {
+                    Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+                    Err((code, message)) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {"code": code, "message": message},
+                    }),
+                };
+                // The response comes before any notifications the request caused.
+                outgoing.insert(0, response);
+            }
+            (Some(method), None) => self.notification(method, params, &mut outgoing),
+            // The server doesn't send requests, so it doesn't expect any responses.
+            (None, _) => {}
+        }
+        outgoing
+    }
+
+    fn request(&mut self, method: &str, params: Value, outgoing: &mut Vec<Value>) -> RequestResult {
+        match method {
+            "initialize" => Ok(self.initialize(&params, outgoing)),
+            "shutdown" => {
+                self.state = State::ShutDown;
+                Ok(Value::Null)
+            }
+            "textDocument/completion" => {
+                let params: TextDocumentPositionParams = parse_params(params)?;
+                let items = self.with_document(&params, |analysis, document, offset| {
+                    features::completions(analysis, &document.line_index, &document.text, offset)
+                });
+                Ok(json!({"isIncomplete": false, "items": items.unwrap_or_default()}))
+            }
+            "textDocument/hover" => {
+                let params: TextDocumentPositionParams = parse_params(params)?;
+                let hover = self.with_document(&params, |analysis, document, offset| {
+                    features::hover(analysis, &document.line_index, &document.text, offset)
+                });
+                Ok(json!(hover.flatten()))
+            }
+            "textDocument/definition" => {
+                let params: TextDocumentPositionParams = parse_params(params)?;
+                let schema = self.schema.as_ref();
+                let location = self.with_document(&params, |analysis, _, offset| {
+                    features::definition(analysis, schema?, offset)
+                });
+                Ok(json!(location.flatten()))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("Unsupported method: {method}"))),
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: Value, outgoing: &mut Vec<Value>) {
+        match method {
+            "exit" => {
+                self.state = State::Exited {
+                    after_shutdown: self.state == State::ShutDown,
+                };
+            }
+            "textDocument/didOpen" => {
+                if let Ok(params) = parse_params::<DidOpenParams>(params) {
+                    let uri = params.text_document.uri;
+                    self.documents
+                        .insert(uri.clone(), Document::new(params.text_document.text));
+                    outgoing.extend(self.publish_diagnostics(&uri));
+                }
+            }
+            "textDocument/didChange" => {
+                if let Ok(params) = parse_params::<DidChangeParams>(params) {
+                    let uri = params.text_document.uri;
+                    if let Some(change) = params.content_changes.into_iter().last() {
+                        self.documents
+                            .insert(uri.clone(), Document::new(change.text));
+                        outgoing.extend(self.publish_diagnostics(&uri));
+                    }
+                }
+            }
+            "textDocument/didClose" => {
+                if let Ok(params) = parse_params::<DidCloseParams>(params) {
+                    let uri = params.text_document.uri;
+                    self.documents.remove(&uri);
+                    outgoing.push(diagnostics_notification(&uri, json!([])));
+                }
+            }
+            // Notifications the server doesn't support are ignored, as the protocol requires.
+            _ => {}
+        }
+    }
+
+    fn initialize(&mut self, params: &Value, outgoing: &mut Vec<Value>) -> Value {
+        if self.schema.is_none() {
+            let schema_path = params
+                .pointer("/initializationOptions/schema")
+                .and_then(Value::as_str);
+            match schema_path {
+                Some(path) => {
+                    let path = resolve_path(path, params.get("rootUri").and_then(Value::as_str));
+                    match SchemaIndex::load(path) {
+                        Ok(schema) => self.schema = Some(schema),
+                        Err(e) => outgoing.push(show_message(MESSAGE_ERROR, &e.to_string())),
+                    }
+                }
+                None => outgoing.push(show_message(
+                    MESSAGE_WARNING,
+                    "No trustfall schema is configured, so queries can't be checked. \
+                    Set the `schema` initialization option to the path of the schema file.",
+                )),
+            }
+        }
+
+        json!({
+            "capabilities": {
+                "textDocumentSync": 1,
+                "completionProvider": {"triggerCharacters": ["@", "\""]},
+                "hoverProvider": true,
+                "definitionProvider": true,
+            },
+            "serverInfo": {
+                "name": "trustfall-lsp",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        })
+    }
+
+    /// Analyze the document the parameters refer to, then call `f` with the analysis and
+    /// the byte offset of the position, or return `None` if the document or schema is missing.
+    fn with_document<T>(
+        &self,
+        params: &TextDocumentPositionParams,
+        f: impl FnOnce(&Analysis<'_>, &Document, usize) -> T,
+    ) -> Option<T> {
+        let schema = self.schema.as_ref()?;
+        let document = self.documents.get(&params.text_document.uri)?;
+        let analysis = Analysis::new(schema.schema(), &document.text);
+        let offset = document.line_index.offset(&document.text, params.position);
+        Some(f(&analysis, document, offset))
+    }
+
+    fn publish_diagnostics(&self, uri: &str) -> Option<Value> {
+        let schema = self.schema.as_ref()?;
+        let document = self.documents.get(uri)?;
+        let analysis = Analysis::new(schema.schema(), &document.text);
+        let diagnostics = features::diagnostics(&analysis, &document.line_index, &document.text);
+        Some(diagnostics_notification(uri, json!(diagnostics)))
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, (i64, String)> {
+    serde_json::from_value(params).map_err(|e| (INVALID_PARAMS, e.to_string()))
+}
+
+/// Resolve a relative path against the workspace root, if the root is a local directory.
+fn resolve_path(path: &str, root_uri: Option<&str>) -> PathBuf {
+    let path = PathBuf::from(path);
+    if path.is_relative() {
+        let root = root_uri
+            .and_then(|uri| url::Url::parse(uri).ok())
+            .and_then(|url| url.to_file_path().ok());
+        if let Some(root) = root {
+            return root.join(path);
+        }
+    }
+    path
+}
+
+fn show_message(kind: u8, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "window/showMessage",
+        "params": {"type": kind, "message": message},
+    })
+}
+
+fn diagnostics_notification(uri: &str, diagnostics: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": {"uri": uri, "diagnostics": diagnostics},
+    })
+}
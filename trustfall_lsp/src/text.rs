@@ -0,0 +1,64 @@
+use std::ops::Range as ByteRange;
+
+use async_graphql_parser::Pos;
+
+use crate::protocol::{Position, Range};
+
+/// Converts between byte offsets in a text and the positions used by the protocol.
+#[derive(Debug, Clone)]
+pub(crate) struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub(crate) fn new(text: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self { line_starts }
+    }
+
+    pub(crate) fn position(&self, text: &str, offset: usize) -> Position {
+        let offset = offset.min(text.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let line_start = self.line_starts[line];
+        let character = text[line_start..offset].encode_utf16().count();
+        Position {
+            line: line as u32,
+            character: character as u32,
+        }
+    }
+
+    pub(crate) fn range(&self, text: &str, range: ByteRange<usize>) -> Range {
+        Range {
+            start: self.position(text, range.start),
+            end: self.position(text, range.end),
+        }
+    }
+
+    /// The byte offset of the position, clamped to the end of its line and of the text.
+    pub(crate) fn offset(&self, text: &str, position: Position) -> usize {
+        let Some(line_start) = self.line_starts.get(position.line as usize) else {
+            return text.len();
+        };
+        let mut remaining = position.character as usize;
+        for (index, c) in text[*line_start..].char_indices() {
+            if remaining == 0 || c == '\n' {
+                return line_start + index;
+            }
+            remaining = remaining.saturating_sub(c.len_utf16());
+        }
+        text.len()
+    }
+
+    /// The byte offset of a position reported by the GraphQL parser,
+    /// whose lines and columns are 1-based and whose columns are counted in bytes.
+    pub(crate) fn parser_offset(&self, text: &str, pos: Pos) -> usize {
+        let line_start = self
+            .line_starts
+            .get(pos.line.saturating_sub(1))
+            .copied()
+            .unwrap_or(text.len());
+        (line_start + pos.column.saturating_sub(1)).min(text.len())
+    }
+}
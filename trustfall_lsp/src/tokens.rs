@@ -0,0 +1,111 @@
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    Name,
+    Punctuator,
+    String,
+    Number,
+}
+
+/// A token of GraphQL query text.
+///
+/// Queries being edited are frequently incomplete, so tokenizing never fails:
+/// unterminated strings end at the end of their line, and unexpected characters
+/// become punctuators of their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Token {
+    pub(crate) kind: TokenKind,
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+}
+
+impl Token {
+    pub(crate) fn text<'a>(&self, text: &'a str) -> &'a str {
+        &text[self.start..self.end]
+    }
+
+    pub(crate) fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    pub(crate) fn is(&self, text: &str, kind: TokenKind, expected: &str) -> bool {
+        self.kind == kind && self.text(text) == expected
+    }
+}
+
+pub(crate) fn tokenize(text: &str) -> Vec<Token> {
+    let bytes = text.as_bytes();
+    let mut tokens = vec![];
+    let mut index = 0;
+    while index < bytes.len() {
+        let start = index;
+        let kind = match bytes[index] {
+            // Commas are insignificant in GraphQL, just like whitespace.
+            b' ' | b'\t' | b'\r' | b'\n' | b',' => {
+                index += 1;
+                continue;
+            }
+            b'#' => {
+                index = find_line_end(bytes, index);
+                continue;
+            }
+            b'"' if bytes[index..].starts_with(b"\"\"\"") => {
+                index = match text[index + 3..].find("\"\"\"") {
+                    Some(offset) => index + 3 + offset + 3,
+                    None => bytes.len(),
+                };
+                TokenKind::String
+            }
+            b'"' => {
+                index += 1;
+                while index < bytes.len() && !matches!(bytes[index], b'"' | b'\n') {
+                    index += if bytes[index] == b'\\' { 2 } else { 1 };
+                }
+                if bytes.get(index) == Some(&b'"') {
+                    index += 1;
+                }
+                index = index.min(bytes.len());
+                TokenKind::String
+            }
+            b'.' if bytes[index..].starts_with(b"...") => {
+                index += 3;
+                TokenKind::Punctuator
+            }
+            b'_' | b'a'..=b'z' | b'A'..=b'Z' => {
+                while index < bytes.len()
+                    && matches!(bytes[index], b'_' | b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9')
+                {
+                    index += 1;
+                }
+                TokenKind::Name
+            }
+            b'-' | b'0'..=b'9' => {
+                index += 1;
+                while index < bytes.len()
+                    && matches!(bytes[index], b'0'..=b'9' | b'.' | b'e' | b'E' | b'+' | b'-')
+                {
+                    index += 1;
+                }
+                TokenKind::Number
+            }
+            _ => {
+                index += text[index..].chars().next().map_or(1, char::len_utf8);
+                TokenKind::Punctuator
+            }
+        };
+        tokens.push(Token {
+            kind,
+            start,
+            end: index,
+        });
+    }
+    tokens
+}
+
+fn find_line_end(bytes: &[u8], start: usize) -> usize {
+    bytes[start..]
+        .iter()
+        .position(|b| *b == b'\n')
+        .map_or(bytes.len(), |offset| start + offset)
+}
@@ -0,0 +1,404 @@
+use std::io::Cursor;
+
+use serde_json::{json, Value};
+use trustfall_lsp::{read_message, write_message, SchemaIndex, Server};
+
+const URI: &str = "file:///queries/query.graphql";
+const SCHEMA_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/schema.graphql");
+
+fn server() -> Server {
+    Server::new(Some(SchemaIndex::load(SCHEMA_PATH).unwrap()))
+}
+
+/// Split the cursor marked by `|` out of the text, returning its position too.
+fn with_cursor(text: &str) -> (String, Value) {
+    let offset = text.find('|').unwrap();
+    let before = &text[..offset];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    let character = before[line_start..].encode_utf16().count();
+    (
+        text.replacen('|', "", 1),
+        json!({"line": line, "character": character}),
+    )
+}
+
+fn open(server: &mut Server, text: &str) -> Vec<Value> {
+    server.handle(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": {"textDocument": {"uri": URI, "languageId": "graphql", "version": 1, "text": text}},
+    }))
+}
+
+fn request(server: &mut Server, method: &str, params: Value) -> Value {
+    let mut responses =
+        server.handle(json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params}));
+    assert_eq!(1, responses.len(), "{responses:?}");
+    let response = responses.pop().unwrap();
+    assert_eq!(json!(1), response["id"]);
+    response
+}
+
+fn at_cursor(method: &str, text: &str) -> Value {
+    let mut server = server();
+    let (text, position) = with_cursor(text);
+    open(&mut server, &text);
+    let response = request(
+        &mut server,
+        method,
+        json!({"textDocument": {"uri": URI}, "position": position}),
+    );
+    response["result"].clone()
+}
+
+fn completion_labels(text: &str) -> Vec<String> {
+    let result = at_cursor("textDocument/completion", text);
+    result["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["label"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn completes_properties_and_edges() {
+    assert_eq!(
+        vec![
+            "name",
+            "value",
+            "roman",
+            "__typename",
+            "successor",
+            "multiple"
+        ],
+        completion_labels("{ Number(max: 3) { | } }"),
+    );
+    assert_eq!(vec!["Number"], completion_labels("query {\n    |\n}"));
+
+    // Incomplete queries have completions too, within the type of the edge they're in.
+    let text = "{\n    Number(max: 3) {\n        n: name @output(name: \"ü\") successor { multiple(max: 4) { va|";
+    let result = at_cursor("textDocument/completion", text);
+    let items = result["items"].as_array().unwrap();
+    let labels: Vec<_> = items.iter().map(|item| item["label"].clone()).collect();
+    assert_eq!(
+        json!([
+            "name",
+            "value",
+            "roman",
+            "__typename",
+            "successor",
+            "multiple",
+            "divisor"
+        ]),
+        json!(labels),
+    );
+
+    let value = &items[1];
+    assert_eq!(json!("Int"), value["detail"]);
+    let line = text.lines().last().unwrap();
+    let start = line[..line.find("va").unwrap()].encode_utf16().count();
+    assert_eq!(
+        json!({
+            "range": {
+                "start": {"line": 2, "character": start},
+                "end": {"line": 2, "character": start + 2},
+            },
+            "newText": "value",
+        }),
+        value["textEdit"],
+    );
+
+    let roman = &items[2];
+    assert_eq!(json!(true), roman["deprecated"]);
+    assert_eq!(json!("(max: Int!): [Composite!]"), items[5]["detail"]);
+}
+
+#[test]
+fn completes_directives_and_their_operations() {
+    assert_eq!(
+        vec!["filter", "output", "tag"],
+        completion_labels("{ Number(max: 3) { value @| } }"),
+    );
+    assert_eq!(
+        vec!["optional", "recurse", "fold", "transform"],
+        completion_labels("{ Number(max: 3) { multiple(max: 4) @o| { value } } }"),
+    );
+    assert_eq!(
+        vec!["output"],
+        completion_labels(
+            "{ Number(max: 3) { value @filter(op: \"=\", value: [\"$x\"]) @output @| } }"
+        )
+        .into_iter()
+        .filter(|label| label == "output")
+        .collect::<Vec<_>>(),
+    );
+
+    let result = at_cursor(
+        "textDocument/completion",
+        "{ Number(max: 3) { value @filter(op: \"|\", value: [\"$x\"]) } }",
+    );
+    let items = result["items"].as_array().unwrap();
+    assert_eq!(20, items.len());
+    let one_of = items.iter().find(|item| item["label"] == "one_of").unwrap();
+    assert_eq!(
+        json!({
+            "range": {
+                "start": {"line": 0, "character": 37},
+                "end": {"line": 0, "character": 39},
+            },
+            "newText": "\"one_of\"",
+        }),
+        one_of["textEdit"],
+    );
+
+    assert_eq!(
+        vec!["count"],
+        completion_labels(
+            "{ Number(max: 3) { multiple(max: 4) @fold @transform(op: \"|\") { value } } }"
+        ),
+    );
+}
+
+#[test]
+fn completes_type_coercions_and_edge_parameters() {
+    assert_eq!(
+        vec!["Composite", "Prime"],
+        completion_labels("{ Number(max: 3) { ... on | } }"),
+    );
+    assert_eq!(
+        vec!["min", "max"],
+        completion_labels("{ Number(|) { value } }")
+    );
+    assert_eq!(
+        vec!["min", "max"],
+        completion_labels("{ Number(max: 3, m|) { value } }"),
+    );
+    assert!(completion_labels("{ Number(max: |) { value } }").is_empty());
+
+    // Within a type coercion, the fields are those of the type it coerces to.
+    assert!(
+        completion_labels("{ Number(max: 3) { ... on Composite { | } } }")
+            .contains(&"divisor".to_string())
+    );
+}
+
+#[test]
+fn describes_what_is_hovered() {
+    let hover = at_cursor(
+        "textDocument/hover",
+        "{ Num|ber(max: 3) { value @output } }",
+    );
+    assert_eq!(
+        json!({
+            "contents": {
+                "kind": "markdown",
+                "value": "```graphql\nRootSchemaQuery.Number(min: Int, max: Int!): [Number!]!\n```\n\nThe numbers between `min` and `max`.",
+            },
+            "range": {
+                "start": {"line": 0, "character": 2},
+                "end": {"line": 0, "character": 8},
+            },
+        }),
+        hover,
+    );
+
+    let hover = at_cursor(
+        "textDocument/hover",
+        "{ Number(max: 3) { roman| @output } }",
+    );
+    assert_eq!(
+        json!("```graphql\nNumber.roman: String\n```\n\n**Deprecated:** Use `name` instead."),
+        hover["contents"]["value"],
+    );
+
+    let hover = at_cursor(
+        "textDocument/hover",
+        "{ Number(max: 3) { successor @f|old { value } } }",
+    );
+    assert!(hover["contents"]["value"]
+        .as_str()
+        .unwrap()
+        .starts_with("```graphql\n@fold\n```"));
+
+    let hover = at_cursor(
+        "textDocument/hover",
+        "{ Number(max: 3) { ... on Com|posite { value } } }",
+    );
+    assert!(hover["contents"]["value"]
+        .as_str()
+        .unwrap()
+        .starts_with("```graphql\ntype Composite\n```"));
+
+    // Aliases aren't fields of the schema.
+    let hover = at_cursor(
+        "textDocument/hover",
+        "{ Number(max: 3) { al|ias: value @output } }",
+    );
+    assert_eq!(Value::Null, hover);
+}
+
+#[test]
+fn goes_to_definitions_in_the_schema() {
+    let schema = std::fs::read_to_string(SCHEMA_PATH).unwrap();
+    let uri = url::Url::from_file_path(std::fs::canonicalize(SCHEMA_PATH).unwrap())
+        .unwrap()
+        .to_string();
+    let location_of = |after: &str, name: &str| {
+        let start = schema.find(after).unwrap();
+        let offset = start + schema[start..].find(name).unwrap();
+        let line = schema[..offset].matches('\n').count();
+        let character = offset - schema[..offset].rfind('\n').unwrap() - 1;
+        json!({
+            "uri": uri,
+            "range": {
+                "start": {"line": line, "character": character},
+                "end": {"line": line, "character": character + name.len()},
+            },
+        })
+    };
+
+    let text = "{ Number(max: 3) { ... on Composite { div|isor { value } } } }";
+    assert_eq!(
+        location_of("type Composite", "divisor"),
+        at_cursor("textDocument/definition", text),
+    );
+    let text = "{ Number(max: 3) { ... on Pr|ime { value } } }";
+    assert_eq!(
+        location_of("type Prime", "Prime"),
+        at_cursor("textDocument/definition", text),
+    );
+    let text = "{ Number(max: 3) { value @out|put } }";
+    assert_eq!(
+        location_of("directive @output", "output"),
+        at_cursor("textDocument/definition", text),
+    );
+}
+
+#[test]
+fn reports_diagnostics() {
+    let mut server = server();
+    let diagnostics = |messages: Vec<Value>| {
+        assert_eq!(1, messages.len(), "{messages:?}");
+        assert_eq!(
+            json!("textDocument/publishDiagnostics"),
+            messages[0]["method"]
+        );
+        assert_eq!(json!(URI), messages[0]["params"]["uri"]);
+        messages[0]["params"]["diagnostics"].clone()
+    };
+
+    let result = diagnostics(open(&mut server, "{ Number(max: 3) { nope @output } }"));
+    assert_eq!(1, result.as_array().unwrap().len(), "{result}");
+    assert_eq!(json!(1), result[0]["severity"]);
+    assert!(
+        result[0]["message"].as_str().unwrap().contains("nope"),
+        "{result}"
+    );
+    assert_eq!(
+        json!({"start": {"line": 0, "character": 19}, "end": {"line": 0, "character": 23}}),
+        result[0]["range"],
+    );
+
+    let change = |text: &str| {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": {"uri": URI, "version": 2},
+                "contentChanges": [{"text": text}],
+            },
+        })
+    };
+    let result = diagnostics(server.handle(change("{ Number(max: 3) { roman @output } }")));
+    assert_eq!(
+        json!([{
+            "range": {"start": {"line": 0, "character": 19}, "end": {"line": 0, "character": 24}},
+            "severity": 2,
+            "source": "trustfall",
+            "message": "The query uses property \"roman\" on type \"Number\", which is deprecated: Use `name` instead.",
+            "tags": [2],
+        }]),
+        result,
+    );
+
+    let result = diagnostics(server.handle(change("{ Number(max: 3) { value @output } }")));
+    assert_eq!(json!([]), result);
+
+    let result = diagnostics(server.handle(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didClose",
+        "params": {"textDocument": {"uri": URI}},
+    })));
+    assert_eq!(json!([]), result);
+}
+
+#[test]
+fn follows_the_server_lifecycle() {
+    // Without a schema, the server warns that it can't check queries.
+    let mut server = Server::new(None);
+    let messages =
+        server.handle(json!({"jsonrpc": "2.0", "id": 0, "method": "initialize", "params": {}}));
+    assert_eq!(2, messages.len());
+    assert_eq!(
+        json!(true),
+        messages[0]["result"]["capabilities"]["hoverProvider"]
+    );
+    assert_eq!(json!("window/showMessage"), messages[1]["method"]);
+
+    // The schema is loaded from the initialization options, relative to the workspace root.
+    let mut server = Server::new(None);
+    let root = url::Url::from_directory_path(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let messages = server.handle(json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "initialize",
+        "params": {"rootUri": root.as_str(), "initializationOptions": {"schema": "tests/schema.graphql"}},
+    }));
+    assert_eq!(1, messages.len(), "{messages:?}");
+    assert_eq!(
+        1,
+        open(&mut server, "{ Number(max: 3) { value @output } }").len()
+    );
+
+    let response = request(&mut server, "textDocument/rename", json!({}));
+    assert_eq!(json!(-32601), response["error"]["code"]);
+
+    assert_eq!(
+        Value::Null,
+        request(&mut server, "shutdown", Value::Null)["result"]
+    );
+    let response = request(&mut server, "textDocument/hover", json!({}));
+    assert_eq!(json!(-32600), response["error"]["code"]);
+    assert!(server
+        .handle(json!({"jsonrpc": "2.0", "method": "exit"}))
+        .is_empty());
+    assert!(server.has_exited());
+    assert_eq!(0, server.exit_code());
+}
+
+#[test]
+fn serves_framed_messages() {
+    let mut input = vec![];
+    for message in [
+        json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}),
+        json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+        json!({"jsonrpc": "2.0", "method": "exit"}),
+        json!({"jsonrpc": "2.0", "id": 2, "method": "shutdown"}),
+    ] {
+        write_message(&mut input, &message).unwrap();
+    }
+
+    let mut output = vec![];
+    let mut server = server();
+    server.run(Cursor::new(input), &mut output).unwrap();
+    assert!(server.has_exited());
+    assert_eq!(1, server.exit_code());
+
+    // Messages after `exit` aren't read.
+    let mut output = Cursor::new(output);
+    let response = read_message(&mut output).unwrap().unwrap();
+    assert_eq!(json!(1), response["id"]);
+    assert_eq!(None, read_message(&mut output).unwrap());
+}
@@ -0,0 +1,129 @@
+export type JsFieldValue = string | boolean | number | null | JsFieldValue[];
+export type JsEdgeParameters = Record<string, JsFieldValue>;
+
+export interface JsContext<T> {
+    readonly localId: number;
+    readonly activeVertex: T | null;
+}
+
+export interface ContextAndValue {
+    localId: number;
+    value: JsFieldValue;
+}
+
+export interface ContextAndNeighbors<T> {
+    localId: number;
+    neighbors: Iterable<T>;
+}
+
+export interface ContextAndBool {
+    localId: number;
+    value: boolean;
+}
+
+/** The same interface as the `Adapter` of the WASM package. */
+export interface Adapter<T> {
+    resolveStartingVertices(
+        edge: string,
+        parameters: JsEdgeParameters,
+    ): Iterable<T>;
+
+    resolveProperty(
+        contexts: IterableIterator<JsContext<T>>,
+        type_name: string,
+        field_name: string
+    ): Iterable<ContextAndValue>;
+
+    resolveNeighbors(
+        contexts: IterableIterator<JsContext<T>>,
+        type_name: string,
+        edge_name: string,
+        parameters: JsEdgeParameters,
+    ): Iterable<ContextAndNeighbors<T>>;
+
+    resolveCoercion(
+        contexts: IterableIterator<JsContext<T>>,
+        type_name: string,
+        coerce_to_type: string
+    ): Iterable<ContextAndBool>;
+}
+
+/**
+ * Each value may be a sync or async iterable, or a `Promise` of either.
+ */
+export type MaybeAsyncIterable<T> = Iterable<T> | AsyncIterable<T> | Promise<Iterable<T> | AsyncIterable<T>>;
+
+export interface AsyncContextAndNeighbors<T> {
+    localId: number;
+    neighbors: MaybeAsyncIterable<T>;
+}
+
+/**
+ * The same interface as the `AsyncAdapter` of the WASM package.
+ *
+ * Unlike `Adapter`, its methods receive the contexts to resolve as an array.
+ * Each method is called at most once for each distinct call made by the engine,
+ * even though queries with asynchronous adapters may be executed more than once internally.
+ */
+export interface AsyncAdapter<T> {
+    resolveStartingVertices(
+        edge: string,
+        parameters: JsEdgeParameters,
+    ): MaybeAsyncIterable<T>;
+
+    resolveProperty(
+        contexts: JsContext<T>[],
+        type_name: string,
+        field_name: string
+    ): MaybeAsyncIterable<ContextAndValue>;
+
+    resolveNeighbors(
+        contexts: JsContext<T>[],
+        type_name: string,
+        edge_name: string,
+        parameters: JsEdgeParameters,
+    ): MaybeAsyncIterable<AsyncContextAndNeighbors<T>>;
+
+    resolveCoercion(
+        contexts: JsContext<T>[],
+        type_name: string,
+        coerce_to_type: string
+    ): MaybeAsyncIterable<ContextAndBool>;
+}
+
+export class Schema {
+    /**
+     * @throws if the schema is not valid.
+     */
+    static parse(input: string): Schema;
+
+    /**
+     * Returns the names of all subtypes (plus itself) of `type_name` in the schema.
+     *
+     * May be used in `Adapter.resolveCoercion` to determine if vertices can be coerced
+     * to the specified type.
+     * @throws if `type_name` is not an interface or object type in this schema.
+     */
+    subtypes(type_name: string): string[];
+}
+
+/**
+ * @throws if the query is not valid, or the arguments are not valid for the query.
+ */
+export function executeQuery<T>(
+    schema: Schema,
+    adapter: Adapter<T>,
+    query: string,
+    args: Record<string, JsFieldValue>,
+): IterableIterator<Record<string, JsFieldValue>>;
+
+/**
+ * Executes the query without blocking while waiting for the adapter.
+ * @throws if the query is not valid, or the arguments are not valid for the query.
+ */
+export function executeQueryAsync<T>(
+    schema: Schema,
+    adapter: AsyncAdapter<T>,
+    query: string,
+    args: Record<string, JsFieldValue>,
+): AsyncIterableIterator<Record<string, JsFieldValue>>;
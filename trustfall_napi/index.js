@@ -0,0 +1,56 @@
+"use strict";
+
+const native = require("./native.js");
+
+const { Schema } = native;
+
+/**
+ * Execute a query with an adapter whose resolver methods return iterables.
+ * Results are produced lazily, as the returned iterator is advanced.
+ */
+function* executeQuery(schema, adapter, query, args) {
+  const results = native.executeQuery(schema, adapter, query, args);
+  while (true) {
+    const result = results.advance();
+    if (result === undefined || result === null) {
+      return;
+    }
+    yield result;
+  }
+}
+
+/** Collect the values of a sync or async iterable, or of a Promise of one. */
+async function collect(iterable) {
+  const values = [];
+  for await (const value of await iterable) {
+    values.push(value);
+  }
+  return values;
+}
+
+/**
+ * Execute a query with an adapter whose resolver methods may also return Promises
+ * and async iterables, in addition to iterables. Results are produced lazily,
+ * as the returned async iterator is advanced.
+ */
+async function* executeQueryAsync(schema, adapter, query, args) {
+  const execution = native.startAsyncExecution(schema, adapter, query, args);
+  while (true) {
+    const step = execution.step();
+    if (step.pending !== undefined) {
+      const values = await collect(step.pending);
+      if (step.neighbors) {
+        for (const value of values) {
+          value.neighbors = await collect(value.neighbors);
+        }
+      }
+      execution.complete(values);
+    } else if (step.done) {
+      return;
+    } else {
+      yield step.value;
+    }
+  }
+}
+
+module.exports = { Schema, executeQuery, executeQueryAsync };
@@ -0,0 +1,516 @@
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc, sync::Arc};
+
+use napi::{Env, JsFunction, JsNumber, JsObject, JsUnknown, Result};
+use trustfall_core::{
+    interpreter::{
+        Adapter, ContextIterator, ContextOutcomeIterator, DataContext, ResolveEdgeInfo,
+        ResolveInfo, VertexIterator,
+    },
+    ir::{EdgeParameters, FieldValue},
+};
+
+use crate::{
+    js::{collect_array, is_async, JsIterator},
+    values::{field_value_from_js, parameters_to_js, JsVertex, Persistent, VertexStore},
+};
+
+type ContextRegistry = Rc<RefCell<BTreeMap<u32, DataContext<JsVertex>>>>;
+
+/// Panics with the JS error, since the engine's adapter interface has no way to report errors.
+/// The panic surfaces in JS as an exception thrown by the call that advanced the query.
+fn check<T>(result: Result<T>, description: &str) -> T {
+    result.unwrap_or_else(|e| panic!("{description}: {e}"))
+}
+
+/// The shape of the values returned by an adapter method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CallKind {
+    /// Vertices, or `{ localId, value }` objects.
+    Values,
+
+    /// `{ localId, neighbors }` objects, where `neighbors` is itself iterable.
+    Neighbors,
+}
+
+/// An asynchronous adapter call whose results must be awaited in JS before
+/// the query can continue. See [`crate::AsyncExecution`].
+pub(crate) struct PendingCall {
+    pub(crate) value: Persistent,
+    pub(crate) kind: CallKind,
+}
+
+#[derive(Default)]
+pub(crate) struct ReplayState {
+    /// The collected results of the adapter calls made so far, in the order they were made.
+    completed_calls: Vec<Persistent>,
+
+    /// The number of adapter calls made by the current attempt.
+    call_count: usize,
+
+    /// The asynchronous adapter call that abandoned the current attempt, if any.
+    pending: Option<PendingCall>,
+}
+
+/// The [`Adapter`] implementation wrapping a JS adapter object.
+///
+/// All of its methods must be called on the JS thread that created it, which is always the case
+/// since the engine only runs while JS is advancing a query's results.
+pub(crate) struct AdapterShim {
+    env: Env,
+    adapter: Persistent,
+    vertices: Rc<VertexStore>,
+
+    /// Only present for adapters that may be asynchronous.
+    replay: Option<Rc<RefCell<ReplayState>>>,
+}
+
+impl AdapterShim {
+    pub(crate) fn new(env: Env, adapter: JsObject) -> Result<Self> {
+        Ok(Self {
+            env,
+            adapter: Persistent::new(env, adapter)?,
+            vertices: VertexStore::new(env)?,
+            replay: None,
+        })
+    }
+
+    pub(crate) fn new_async(env: Env, adapter: JsObject) -> Result<Self> {
+        Ok(Self {
+            replay: Some(Default::default()),
+            ..Self::new(env, adapter)?
+        })
+    }
+
+    fn replay(&self) -> &Rc<RefCell<ReplayState>> {
+        self.replay.as_ref().expect("not an async adapter")
+    }
+
+    pub(crate) fn begin_attempt(&self) {
+        let mut state = self.replay().borrow_mut();
+        assert!(state.pending.is_none());
+        state.call_count = 0;
+    }
+
+    pub(crate) fn take_pending(&self) -> Option<PendingCall> {
+        self.replay().borrow_mut().pending.take()
+    }
+
+    pub(crate) fn complete_pending(&self, results: JsObject) -> Result<()> {
+        let results = Persistent::new(self.env, results)?;
+        self.replay().borrow_mut().completed_calls.push(results);
+        Ok(())
+    }
+
+    fn is_abandoned(&self) -> bool {
+        self.replay
+            .as_ref()
+            .map_or(false, |state| state.borrow().pending.is_some())
+    }
+
+    fn call_adapter(&self, method: &str, args: Vec<JsUnknown>) -> Result<JsUnknown> {
+        let adapter: JsObject = self.adapter.get()?;
+        let function: JsFunction = adapter.get_named_property(method)?;
+        function.call(Some(&adapter), &args)
+    }
+
+    /// Get the values returned by the next adapter call. Returns `None` if the current attempt
+    /// is being abandoned, which only happens for async adapters.
+    ///
+    /// Async adapters have each call's values collected into an array, and calls that were
+    /// already made by previous attempts are answered from those arrays.
+    fn resolve_call(
+        &self,
+        kind: CallKind,
+        description: &str,
+        call: impl FnOnce() -> Result<JsUnknown>,
+    ) -> Option<JsUnknown> {
+        let Some(replay) = &self.replay else {
+            return Some(check(call(), description));
+        };
+
+        let index = {
+            let mut state = replay.borrow_mut();
+            if state.pending.is_some() {
+                return None;
+            }
+            let index = state.call_count;
+            state.call_count += 1;
+            if let Some(results) = state.completed_calls.get(index) {
+                return Some(check(results.get(), description));
+            }
+            index
+        };
+
+        // Don't hold the state borrowed while calling into JS.
+        let returned = check(call(), description);
+        check(
+            self.collect_sync(kind, index, returned, description),
+            description,
+        )
+    }
+
+    /// Collect the adapter's returned values if they are available synchronously.
+    /// Otherwise, record the call as pending so JS can await it.
+    fn collect_sync(
+        &self,
+        kind: CallKind,
+        index: usize,
+        returned: JsUnknown,
+        description: &str,
+    ) -> Result<Option<JsUnknown>> {
+        let env = self.env;
+        let mut state = self.replay().borrow_mut();
+        assert_eq!(index, state.completed_calls.len());
+
+        if is_async(&env, &returned)? {
+            state.pending = Some(PendingCall {
+                value: Persistent::new(env, returned)?,
+                kind,
+            });
+            return Ok(None);
+        }
+
+        let mut results = collect_array(env, returned, description)?;
+        if kind == CallKind::Neighbors {
+            let length = results.get_array_length()?;
+            for i in 0..length {
+                let mut element: JsObject = results.get_element(i)?;
+                let neighbors: JsUnknown = element.get_named_property("neighbors")?;
+                if is_async(&env, &neighbors)? {
+                    // Sync iterables can only be iterated once, so JS continues
+                    // from the array collected so far.
+                    state.pending = Some(PendingCall {
+                        value: Persistent::new(env, results)?,
+                        kind,
+                    });
+                    return Ok(None);
+                }
+                let neighbors = collect_array(env, neighbors, description)?;
+                element.set_named_property("neighbors", neighbors)?;
+            }
+        }
+
+        let results = Persistent::new(env, results)?;
+        let value = results.get()?;
+        state.completed_calls.push(results);
+        Ok(Some(value))
+    }
+
+    /// Make the contexts available to the adapter: as a lazy iterator for sync adapters,
+    /// and as an array for async adapters, since their resolver methods may run after
+    /// the engine has moved on.
+    ///
+    /// Returns `None` if the current attempt was abandoned while collecting the contexts.
+    fn contexts_to_js(
+        &self,
+        contexts: ContextIterator<'static, JsVertex>,
+    ) -> Option<(JsUnknown, ContextRegistry)> {
+        let registry: ContextRegistry = Default::default();
+        if self.replay.is_none() {
+            let contexts = check(
+                make_context_iterator(self.env, contexts, registry.clone()),
+                "failed to create contexts iterator",
+            );
+            return Some((contexts.into_unknown(), registry));
+        }
+
+        let mut array = check(
+            self.env.create_array_with_length(0),
+            "failed to create array",
+        );
+        for (local_id, ctx) in (0u32..).zip(contexts) {
+            let js_context = check(make_context(self.env, local_id, &ctx), "invalid context");
+            check(
+                array.set_element(local_id, js_context),
+                "failed to set context",
+            );
+            registry.borrow_mut().insert(local_id, ctx);
+        }
+        if self.is_abandoned() {
+            None
+        } else {
+            Some((array.into_unknown(), registry))
+        }
+    }
+
+    fn outcomes<T: 'static>(
+        &self,
+        returned: JsUnknown,
+        registry: ContextRegistry,
+        description: &'static str,
+        extract: impl FnMut(Env, JsObject) -> Result<T> + 'static,
+    ) -> ContextOutcomeIterator<'static, JsVertex, T> {
+        let iterator = ContextOutcomes {
+            env: self.env,
+            source: check(
+                JsIterator::new(self.env, returned, description),
+                description,
+            ),
+            registry,
+            next_id: 0,
+            description,
+            extract,
+        };
+        self.until_abandoned(iterator)
+    }
+
+    fn until_abandoned<I: Iterator + 'static>(
+        &self,
+        inner: I,
+    ) -> Box<dyn Iterator<Item = I::Item>> {
+        match &self.replay {
+            None => Box::new(inner),
+            Some(replay) => Box::new(UntilAbandoned {
+                inner,
+                replay: replay.clone(),
+            }),
+        }
+    }
+}
+
+fn make_context(env: Env, local_id: u32, ctx: &DataContext<JsVertex>) -> Result<JsObject> {
+    let mut object = env.create_object()?;
+    object.set_named_property("localId", env.create_uint32(local_id)?)?;
+    match ctx.active_vertex() {
+        Some(vertex) => object.set_named_property("activeVertex", vertex.value()?)?,
+        None => object.set_named_property("activeVertex", env.get_null()?)?,
+    }
+    Ok(object)
+}
+
+/// A JS iterator over contexts, which pulls each context from the engine only when
+/// the adapter asks for it.
+fn make_context_iterator(
+    env: Env,
+    contexts: ContextIterator<'static, JsVertex>,
+    registry: ContextRegistry,
+) -> Result<JsObject> {
+    let contexts = RefCell::new((contexts, 0u32));
+    let next = env.create_function_from_closure("next", move |ctx| {
+        let mut result = ctx.env.create_object()?;
+        let (contexts, next_id) = &mut *contexts.borrow_mut();
+        match contexts.next() {
+            Some(context) => {
+                let local_id = *next_id;
+                *next_id += 1;
+                let value = make_context(*ctx.env, local_id, &context)?;
+                registry.borrow_mut().insert(local_id, context);
+                result.set_named_property("done", ctx.env.get_boolean(false)?)?;
+                result.set_named_property("value", value)?;
+            }
+            None => {
+                result.set_named_property("done", ctx.env.get_boolean(true)?)?;
+            }
+        }
+        Ok(result)
+    })?;
+    let iterator_method =
+        env.create_function_from_closure("[Symbol.iterator]", |ctx| ctx.this::<JsObject>())?;
+
+    let mut iterator = env.create_object()?;
+    iterator.set_named_property("next", next)?;
+    let symbol: JsUnknown = env
+        .get_global()?
+        .get_named_property::<JsObject>("Symbol")?
+        .get_named_property("iterator")?;
+    iterator.set_property(symbol, iterator_method)?;
+    Ok(iterator)
+}
+
+/// Pairs the `{ localId, ... }` objects produced by the adapter with their contexts.
+struct ContextOutcomes<F> {
+    env: Env,
+    source: JsIterator,
+    registry: ContextRegistry,
+    next_id: u32,
+    description: &'static str,
+    extract: F,
+}
+
+impl<T, F: FnMut(Env, JsObject) -> Result<T>> Iterator for ContextOutcomes<F> {
+    type Item = (DataContext<JsVertex>, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let description = self.description;
+        let Some(element) = check(self.source.advance(), description) else {
+            assert!(
+                self.registry.borrow().is_empty(),
+                "{description}: not every context was returned"
+            );
+            return None;
+        };
+        let element = check(element.coerce_to_object(), description);
+        let local_id = check(
+            element
+                .get_named_property::<JsNumber>("localId")
+                .and_then(|id| id.get_uint32()),
+            description,
+        );
+        assert_eq!(
+            local_id, self.next_id,
+            "{description}: contexts were returned out of order"
+        );
+        self.next_id += 1;
+
+        let ctx = self
+            .registry
+            .borrow_mut()
+            .remove(&local_id)
+            .expect("id not found");
+        let value = check((self.extract)(self.env, element), description);
+        Some((ctx, value))
+    }
+}
+
+struct JsVertexIterator {
+    source: JsIterator,
+    vertices: Rc<VertexStore>,
+    description: &'static str,
+}
+
+impl Iterator for JsVertexIterator {
+    type Item = JsVertex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = check(self.source.advance(), self.description)?;
+        Some(check(self.vertices.insert(value), self.description))
+    }
+}
+
+/// Stops producing values as soon as the current attempt is being abandoned,
+/// so the engine finishes the attempt without doing any further work.
+struct UntilAbandoned<I> {
+    inner: I,
+    replay: Rc<RefCell<ReplayState>>,
+}
+
+impl<I: Iterator> Iterator for UntilAbandoned<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.replay.borrow().pending.is_some() {
+            None
+        } else {
+            self.inner.next()
+        }
+    }
+}
+
+impl Adapter<'static> for AdapterShim {
+    type Vertex = JsVertex;
+
+    fn resolve_starting_vertices(
+        &self,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        _resolve_info: &ResolveInfo,
+    ) -> VertexIterator<'static, Self::Vertex> {
+        const DESCRIPTION: &str = "resolveStartingVertices()";
+        let Some(returned) = self.resolve_call(CallKind::Values, DESCRIPTION, || {
+            let edge = self.env.create_string(edge_name)?.into_unknown();
+            let parameters = parameters_to_js(&self.env, parameters)?;
+            self.call_adapter("resolveStartingVertices", vec![edge, parameters])
+        }) else {
+            return Box::new(std::iter::empty());
+        };
+
+        self.until_abandoned(JsVertexIterator {
+            source: check(
+                JsIterator::new(self.env, returned, DESCRIPTION),
+                DESCRIPTION,
+            ),
+            vertices: self.vertices.clone(),
+            description: DESCRIPTION,
+        })
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'static, Self::Vertex>,
+        type_name: &Arc<str>,
+        property_name: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'static, Self::Vertex, FieldValue> {
+        const DESCRIPTION: &str = "resolveProperty()";
+        let Some((contexts, registry)) = self.contexts_to_js(contexts) else {
+            return Box::new(std::iter::empty());
+        };
+        let Some(returned) = self.resolve_call(CallKind::Values, DESCRIPTION, || {
+            let type_name = self.env.create_string(type_name)?.into_unknown();
+            let property_name = self.env.create_string(property_name)?.into_unknown();
+            self.call_adapter("resolveProperty", vec![contexts, type_name, property_name])
+        }) else {
+            return Box::new(std::iter::empty());
+        };
+
+        self.outcomes(returned, registry, DESCRIPTION, |env, element| {
+            field_value_from_js(&env, element.get_named_property("value")?)
+        })
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: ContextIterator<'static, Self::Vertex>,
+        type_name: &Arc<str>,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        _resolve_info: &ResolveEdgeInfo,
+    ) -> ContextOutcomeIterator<'static, Self::Vertex, VertexIterator<'static, Self::Vertex>> {
+        const DESCRIPTION: &str = "resolveNeighbors()";
+        let Some((contexts, registry)) = self.contexts_to_js(contexts) else {
+            return Box::new(std::iter::empty());
+        };
+        let Some(returned) = self.resolve_call(CallKind::Neighbors, DESCRIPTION, || {
+            let type_name = self.env.create_string(type_name)?.into_unknown();
+            let edge_name = self.env.create_string(edge_name)?.into_unknown();
+            let parameters = parameters_to_js(&self.env, parameters)?;
+            self.call_adapter(
+                "resolveNeighbors",
+                vec![contexts, type_name, edge_name, parameters],
+            )
+        }) else {
+            return Box::new(std::iter::empty());
+        };
+
+        let vertices = self.vertices.clone();
+        self.outcomes(returned, registry, DESCRIPTION, move |env, element| {
+            let neighbors: VertexIterator<'static, JsVertex> = Box::new(JsVertexIterator {
+                source: JsIterator::new(
+                    env,
+                    element.get_named_property("neighbors")?,
+                    DESCRIPTION,
+                )?,
+                vertices: vertices.clone(),
+                description: DESCRIPTION,
+            });
+            Ok(neighbors)
+        })
+    }
+
+    fn resolve_coercion(
+        &self,
+        contexts: ContextIterator<'static, Self::Vertex>,
+        type_name: &Arc<str>,
+        coerce_to_type: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'static, Self::Vertex, bool> {
+        const DESCRIPTION: &str = "resolveCoercion()";
+        let Some((contexts, registry)) = self.contexts_to_js(contexts) else {
+            return Box::new(std::iter::empty());
+        };
+        let Some(returned) = self.resolve_call(CallKind::Values, DESCRIPTION, || {
+            let type_name = self.env.create_string(type_name)?.into_unknown();
+            let coerce_to_type = self.env.create_string(coerce_to_type)?.into_unknown();
+            self.call_adapter("resolveCoercion", vec![contexts, type_name, coerce_to_type])
+        }) else {
+            return Box::new(std::iter::empty());
+        };
+
+        self.outcomes(returned, registry, DESCRIPTION, |_, element| {
+            element
+                .get_named_property::<JsUnknown>("value")?
+                .coerce_to_bool()?
+                .get_value()
+        })
+    }
+}
@@ -0,0 +1,95 @@
+//! Helpers for the JS iteration protocols.
+use napi::{Env, JsFunction, JsObject, JsUnknown, Result, ValueType};
+
+use crate::values::Persistent;
+
+fn well_known_symbol(env: &Env, name: &str) -> Result<JsUnknown> {
+    env.get_global()?
+        .get_named_property::<JsObject>("Symbol")?
+        .get_named_property(name)
+}
+
+/// The value as an object, if it's an object or a function.
+pub(crate) fn as_object(value: &JsUnknown) -> Result<Option<JsObject>> {
+    match value.get_type()? {
+        // SAFETY: the value was just checked to be an object or a function.
+        ValueType::Object | ValueType::Function => Ok(Some(unsafe { value.cast() })),
+        _ => Ok(None),
+    }
+}
+
+fn has_method(env: &Env, object: &JsObject, symbol: &str) -> Result<bool> {
+    let method: JsUnknown = object.get_property(well_known_symbol(env, symbol)?)?;
+    Ok(method.get_type()? == ValueType::Function)
+}
+
+/// Whether the value is a `Promise` or other thenable, or an async iterable.
+pub(crate) fn is_async(env: &Env, value: &JsUnknown) -> Result<bool> {
+    let Some(object) = as_object(value)? else {
+        return Ok(false);
+    };
+    let then: JsUnknown = object.get_named_property("then")?;
+    Ok(then.get_type()? == ValueType::Function || has_method(env, &object, "asyncIterator")?)
+}
+
+/// A JS iterator, advanced from Rust.
+pub(crate) struct JsIterator {
+    iterator: Persistent,
+    next: Persistent,
+}
+
+impl JsIterator {
+    /// Start iterating over a JS iterable, such as an array or a generator.
+    pub(crate) fn new(env: Env, iterable: JsUnknown, description: &str) -> Result<Self> {
+        let object = match as_object(&iterable)? {
+            Some(object) if has_method(&env, &object, "iterator")? => object,
+            _ => {
+                let message = if is_async(&env, &iterable)? {
+                    format!("{description} returned a Promise or async iterable; use executeQueryAsync() for async adapters")
+                } else {
+                    format!("{description} did not return an iterable")
+                };
+                return Err(napi::Error::from_reason(message));
+            }
+        };
+
+        let method: JsFunction = object.get_property(well_known_symbol(&env, "iterator")?)?;
+        let iterator = method
+            .call_without_args(Some(&object))?
+            .coerce_to_object()?;
+        let next: JsFunction = iterator.get_named_property("next")?;
+        Ok(Self {
+            iterator: Persistent::new(env, iterator)?,
+            next: Persistent::new(env, next)?,
+        })
+    }
+
+    pub(crate) fn advance(&mut self) -> Result<Option<JsUnknown>> {
+        let iterator: JsObject = self.iterator.get()?;
+        let next: JsFunction = self.next.get()?;
+        let result = next
+            .call_without_args(Some(&iterator))?
+            .coerce_to_object()?;
+        let done = result
+            .get_named_property::<JsUnknown>("done")?
+            .coerce_to_bool()?
+            .get_value()?;
+        if done {
+            Ok(None)
+        } else {
+            result.get_named_property("value").map(Some)
+        }
+    }
+}
+
+/// Collect the values of a JS iterable into an array.
+pub(crate) fn collect_array(env: Env, iterable: JsUnknown, description: &str) -> Result<JsObject> {
+    let mut iterator = JsIterator::new(env, iterable, description)?;
+    let mut array = env.create_array_with_length(0)?;
+    let mut index = 0;
+    while let Some(value) = iterator.advance()? {
+        array.set_element(index, value)?;
+        index += 1;
+    }
+    Ok(array)
+}
@@ -0,0 +1,210 @@
+//! Native Node.js bindings for the trustfall query engine.
+//!
+//! JS adapters implement the same interface as for the WASM bindings in `trustfall_wasm`,
+//! but are called directly through Node-API, so vertices and values never cross
+//! a WASM memory boundary. The engine runs on the JS thread while JS is advancing
+//! a query's results, and results are produced lazily, one at a time.
+//!
+//! Adapters with asynchronous resolver methods are executed like in `trustfall_wasm`: since
+//! the engine is synchronous, an execution attempt that reaches an asynchronous adapter call
+//! is abandoned, the call's results are awaited in JS (see `index.js`), and the query is
+//! executed again, answering the calls made so far from their collected results and skipping
+//! the results that were already produced.
+use std::{collections::BTreeMap, sync::Arc};
+
+use napi::{Env, Error, JsObject, JsUnknown, Result};
+use napi_derive::napi;
+use trustfall_core::{
+    interpreter::execution::interpret_ir,
+    ir::{FieldValue, IndexedQuery},
+};
+
+use crate::{
+    adapter::{AdapterShim, CallKind},
+    values::{arguments_from_js, result_to_js},
+};
+
+mod adapter;
+mod js;
+mod values;
+
+type QueryResults = Box<dyn Iterator<Item = BTreeMap<Arc<str>, FieldValue>>>;
+
+#[napi]
+pub struct Schema {
+    inner: trustfall_core::schema::Schema,
+}
+
+#[napi]
+impl Schema {
+    #[napi(factory)]
+    pub fn parse(input: String) -> Result<Self> {
+        trustfall_core::schema::Schema::parse(input)
+            .map(|inner| Self { inner })
+            .map_err(|e| Error::from_reason(e.to_string()))
+    }
+
+    /// The names of all subtypes of the type, including itself.
+    #[napi]
+    pub fn subtypes(&self, type_name: String) -> Result<Vec<String>> {
+        let subtypes = self.inner.subtypes(&type_name).ok_or_else(|| {
+            Error::from_reason(format!("type {type_name} is not part of this schema"))
+        })?;
+        Ok(subtypes.map(str::to_string).collect())
+    }
+}
+
+fn parse_query(schema: &Schema, query: &str) -> Result<Arc<IndexedQuery>> {
+    trustfall_core::frontend::parse(&schema.inner, query)
+        .map_err(|e| Error::from_reason(e.to_string()))
+}
+
+/// The results of a query, produced lazily as JS advances the iterator.
+///
+/// `index.js` makes these iterable.
+#[napi]
+pub struct QueryResultIterator {
+    env: Env,
+    iter: QueryResults,
+}
+
+#[napi]
+impl QueryResultIterator {
+    /// Returns the next result, or `undefined` once there are no more results.
+    #[napi]
+    pub fn advance(&mut self) -> Result<Option<JsUnknown>> {
+        self.iter
+            .next()
+            .map(|result| result_to_js(&self.env, result))
+            .transpose()
+    }
+}
+
+#[napi(js_name = "executeQuery")]
+pub fn execute_query(
+    env: Env,
+    schema: &Schema,
+    adapter: JsObject,
+    query: String,
+    args: JsUnknown,
+) -> Result<QueryResultIterator> {
+    let args = arguments_from_js(&env, args)?;
+    let query = parse_query(schema, &query)?;
+    let adapter = Arc::new(AdapterShim::new(env, adapter)?);
+    let iter = interpret_ir(adapter, query, args).map_err(|e| Error::from_reason(e.to_string()))?;
+    Ok(QueryResultIterator { env, iter })
+}
+
+/// The state of a query executed with an adapter that may be asynchronous.
+///
+/// `index.js` drives it from an async generator: it calls [`AsyncExecution::step`]
+/// until the query produces a result or finishes, awaiting the values of each pending adapter
+/// call and passing them back with [`AsyncExecution::complete`].
+#[napi]
+pub struct AsyncExecution {
+    adapter: Arc<AdapterShim>,
+    query: Arc<IndexedQuery>,
+    arguments: Arc<BTreeMap<Arc<str>, FieldValue>>,
+    env: Env,
+
+    /// The results of the current attempt, if one is in progress.
+    attempt: Option<QueryResults>,
+
+    /// The number of results the current attempt must skip,
+    /// since previous attempts already produced them.
+    skip: usize,
+
+    /// The number of results produced so far.
+    produced: usize,
+}
+
+#[napi]
+impl AsyncExecution {
+    /// Advance the query. Returns an object with one of the following shapes:
+    /// - `{ done: false, value }` with the next result;
+    /// - `{ done: true }` if there are no more results;
+    /// - `{ pending, neighbors }` if the values of the adapter call `pending` must be awaited
+    ///   first. If `neighbors` is `true`, the `neighbors` of each of its values must be
+    ///   awaited as well. The awaited values must be passed to `complete()`
+    ///   before advancing the query again.
+    #[napi]
+    pub fn step(&mut self) -> Result<JsObject> {
+        let env = self.env;
+        let mut step = env.create_object()?;
+        loop {
+            if self.attempt.is_none() {
+                self.adapter.begin_attempt();
+                self.skip = self.produced;
+                let attempt = interpret_ir(
+                    self.adapter.clone(),
+                    self.query.clone(),
+                    self.arguments.clone(),
+                )
+                .expect("arguments were already validated");
+                self.attempt = Some(attempt);
+            }
+            let attempt = self.attempt.as_mut().expect("no attempt in progress");
+
+            let next = attempt.next();
+            if let Some(pending) = self.adapter.take_pending() {
+                // The attempt's remaining results, and the one it just produced,
+                // may be missing data from the pending call.
+                self.attempt = None;
+                step.set_named_property("pending", pending.value.get::<JsUnknown>()?)?;
+                step.set_named_property(
+                    "neighbors",
+                    env.get_boolean(pending.kind == CallKind::Neighbors)?,
+                )?;
+                return Ok(step);
+            }
+
+            match next {
+                None => {
+                    step.set_named_property("done", env.get_boolean(true)?)?;
+                    return Ok(step);
+                }
+                Some(_) if self.skip > 0 => self.skip -= 1,
+                Some(result) => {
+                    self.produced += 1;
+                    step.set_named_property("done", env.get_boolean(false)?)?;
+                    step.set_named_property("value", result_to_js(&env, result)?)?;
+                    return Ok(step);
+                }
+            }
+        }
+    }
+
+    /// Provide the awaited values of the pending adapter call.
+    #[napi]
+    pub fn complete(&mut self, values: JsObject) -> Result<()> {
+        if !values.is_array()? {
+            return Err(Error::from_reason("expected an array of values"));
+        }
+        self.adapter.complete_pending(values)
+    }
+}
+
+#[napi(js_name = "startAsyncExecution")]
+pub fn start_async_execution(
+    env: Env,
+    schema: &Schema,
+    adapter: JsObject,
+    query: String,
+    args: JsUnknown,
+) -> Result<AsyncExecution> {
+    let arguments = arguments_from_js(&env, args)?;
+    let query = parse_query(schema, &query)?;
+    let adapter = Arc::new(AdapterShim::new_async(env, adapter)?);
+    adapter.begin_attempt();
+    let attempt = interpret_ir(adapter.clone(), query.clone(), arguments.clone())
+        .map_err(|e| Error::from_reason(e.to_string()))?;
+    Ok(AsyncExecution {
+        adapter,
+        query,
+        arguments,
+        env,
+        attempt: Some(attempt),
+        skip: 0,
+        produced: 0,
+    })
+}
@@ -0,0 +1,188 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    fmt::Debug,
+    rc::Rc,
+    sync::Arc,
+};
+
+use napi::{Env, JsObject, JsUnknown, NapiRaw, NapiValue, Ref, Result};
+use serde::{Deserialize, Serialize};
+use trustfall_core::ir::{EdgeParameters, FieldValue};
+
+/// The representation of [`FieldValue`] in JS, matching the one in the WASM bindings.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum JsFieldValue {
+    Null,
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    List(Vec<JsFieldValue>),
+}
+
+impl From<JsFieldValue> for FieldValue {
+    fn from(v: JsFieldValue) -> Self {
+        match v {
+            JsFieldValue::Null => FieldValue::Null,
+            JsFieldValue::String(s) => FieldValue::String(s),
+            JsFieldValue::Integer(i) => FieldValue::Int64(i),
+            JsFieldValue::Float(n) => FieldValue::Float64(n),
+            JsFieldValue::Boolean(b) => FieldValue::Boolean(b),
+            JsFieldValue::List(v) => FieldValue::List(v.into_iter().map(|x| x.into()).collect()),
+        }
+    }
+}
+
+impl From<FieldValue> for JsFieldValue {
+    fn from(v: FieldValue) -> Self {
+        match v {
+            FieldValue::Null => JsFieldValue::Null,
+            FieldValue::String(s) => JsFieldValue::String(s),
+            FieldValue::Int64(i) => JsFieldValue::Integer(i),
+            FieldValue::Uint64(u) => match i64::try_from(u) {
+                Ok(i) => JsFieldValue::Integer(i),
+                Err(_) => JsFieldValue::Float(u as f64),
+            },
+            FieldValue::Float64(n) => JsFieldValue::Float(n),
+            FieldValue::Boolean(b) => JsFieldValue::Boolean(b),
+            FieldValue::List(v) => JsFieldValue::List(v.into_iter().map(|x| x.into()).collect()),
+            FieldValue::DateTimeUtc(d) => JsFieldValue::String(d.to_rfc3339()),
+            FieldValue::Enum(e) => JsFieldValue::String(e),
+        }
+    }
+}
+
+pub(crate) fn field_value_from_js(env: &Env, value: JsUnknown) -> Result<FieldValue> {
+    env.from_js_value::<JsFieldValue, _>(value).map(Into::into)
+}
+
+pub(crate) fn arguments_from_js(
+    env: &Env,
+    args: JsUnknown,
+) -> Result<Arc<BTreeMap<Arc<str>, FieldValue>>> {
+    let args = env
+        .from_js_value::<BTreeMap<String, JsFieldValue>, _>(args)?
+        .into_iter()
+        .map(|(k, v)| (Arc::from(k), v.into()))
+        .collect();
+    Ok(Arc::new(args))
+}
+
+pub(crate) fn parameters_to_js(env: &Env, parameters: &EdgeParameters) -> Result<JsUnknown> {
+    let parameters: BTreeMap<&str, JsFieldValue> = parameters
+        .iter()
+        .map(|(k, v)| (k.as_ref(), v.clone().into()))
+        .collect();
+    env.to_js_value(&parameters)
+}
+
+pub(crate) fn result_to_js(env: &Env, result: BTreeMap<Arc<str>, FieldValue>) -> Result<JsUnknown> {
+    let result: BTreeMap<Arc<str>, JsFieldValue> =
+        result.into_iter().map(|(k, v)| (k, v.into())).collect();
+    env.to_js_value(&result)
+}
+
+/// A JS object kept alive while Rust holds onto it.
+pub(crate) struct Persistent {
+    env: Env,
+    reference: Option<Ref<()>>,
+}
+
+impl Persistent {
+    pub(crate) fn new<T: NapiRaw>(env: Env, value: T) -> Result<Self> {
+        Ok(Self {
+            env,
+            reference: Some(env.create_reference(value)?),
+        })
+    }
+
+    pub(crate) fn get<T: NapiValue>(&self) -> Result<T> {
+        self.env
+            .get_reference_value(self.reference.as_ref().expect("reference was released"))
+    }
+}
+
+impl Drop for Persistent {
+    fn drop(&mut self) {
+        if let Some(reference) = self.reference.take() {
+            // Failing to release the reference only leaks the object.
+            let _ = reference.unref(self.env);
+        }
+    }
+}
+
+/// Keeps the vertices produced by the adapter alive while the engine holds them.
+///
+/// Vertices may be any JS value. Older Node versions can't create references to primitives,
+/// so vertices are stored in the slots of a JS array instead, and the slot of each vertex is
+/// cleared and reused once the engine no longer holds the vertex.
+pub(crate) struct VertexStore {
+    env: Env,
+    slots: Persistent,
+    free_slots: RefCell<Vec<u32>>,
+    next_slot: Cell<u32>,
+}
+
+impl VertexStore {
+    pub(crate) fn new(env: Env) -> Result<Rc<Self>> {
+        Ok(Rc::new(Self {
+            env,
+            slots: Persistent::new(env, env.create_array_with_length(0)?)?,
+            free_slots: Default::default(),
+            next_slot: Default::default(),
+        }))
+    }
+
+    pub(crate) fn insert(self: &Rc<Self>, value: JsUnknown) -> Result<JsVertex> {
+        let slot = self.free_slots.borrow_mut().pop().unwrap_or_else(|| {
+            let slot = self.next_slot.get();
+            self.next_slot.set(slot + 1);
+            slot
+        });
+        self.slots.get::<JsObject>()?.set_element(slot, value)?;
+        Ok(JsVertex(Rc::new(VertexSlot {
+            store: self.clone(),
+            slot,
+        })))
+    }
+}
+
+struct VertexSlot {
+    store: Rc<VertexStore>,
+    slot: u32,
+}
+
+impl Drop for VertexSlot {
+    fn drop(&mut self) {
+        let store = &self.store;
+        let cleared = store
+            .slots
+            .get::<JsObject>()
+            .and_then(|mut slots| slots.set_element(self.slot, store.env.get_undefined()?));
+        if cleared.is_ok() {
+            store.free_slots.borrow_mut().push(self.slot);
+        }
+    }
+}
+
+/// A vertex produced by the JS adapter.
+#[derive(Clone)]
+pub(crate) struct JsVertex(Rc<VertexSlot>);
+
+impl JsVertex {
+    pub(crate) fn value(&self) -> Result<JsUnknown> {
+        self.0
+            .store
+            .slots
+            .get::<JsObject>()?
+            .get_element(self.0.slot)
+    }
+}
+
+impl Debug for JsVertex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("JsVertex").field(&self.0.slot).finish()
+    }
+}
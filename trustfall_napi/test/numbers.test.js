@@ -0,0 +1,144 @@
+"use strict";
+
+const assert = require("node:assert");
+const { readFileSync } = require("node:fs");
+const path = require("node:path");
+const test = require("node:test");
+
+const { Schema, executeQuery, executeQueryAsync } = require("..");
+
+const schema = Schema.parse(
+  readFileSync(
+    path.join(__dirname, "../../trustfall_core/test_data/schemas/numbers.graphql"),
+    "utf-8",
+  ),
+);
+
+const PRIMES = new Set([2, 3, 5, 7, 11]);
+
+class NumbersAdapter {
+  *resolveStartingVertices(edge, parameters) {
+    assert.strictEqual(edge, "Number");
+    for (let i = 1; i <= parameters.max; i++) {
+      yield i;
+    }
+  }
+
+  *resolveProperty(contexts, typeName, fieldName) {
+    assert.strictEqual(fieldName, "value");
+    for (const ctx of contexts) {
+      yield { localId: ctx.localId, value: ctx.activeVertex };
+    }
+  }
+
+  *resolveNeighbors(contexts, typeName, edgeName) {
+    assert.strictEqual(edgeName, "successor");
+    for (const ctx of contexts) {
+      yield {
+        localId: ctx.localId,
+        neighbors: ctx.activeVertex === null ? [] : [ctx.activeVertex + 1],
+      };
+    }
+  }
+
+  *resolveCoercion(contexts, typeName, coerceToType) {
+    assert.strictEqual(coerceToType, "Prime");
+    for (const ctx of contexts) {
+      yield { localId: ctx.localId, value: PRIMES.has(ctx.activeVertex) };
+    }
+  }
+}
+
+// Resolves starting vertices and properties asynchronously, with a mix of Promises
+// and async generators, and neighbors synchronously but with Promises of neighbors.
+class AsyncNumbersAdapter {
+  constructor() {
+    this.calls = 0;
+  }
+
+  resolveStartingVertices(edge, parameters) {
+    this.calls++;
+    const vertices = [];
+    for (let i = 1; i <= parameters.max; i++) {
+      vertices.push(i);
+    }
+    return new Promise((resolve) => setTimeout(() => resolve(vertices), 0));
+  }
+
+  async *resolveProperty(contexts) {
+    this.calls++;
+    for (const ctx of contexts) {
+      await null;
+      yield { localId: ctx.localId, value: ctx.activeVertex };
+    }
+  }
+
+  resolveNeighbors(contexts) {
+    this.calls++;
+    return contexts.map((ctx) => ({
+      localId: ctx.localId,
+      neighbors: Promise.resolve([ctx.activeVertex + 1]),
+    }));
+  }
+
+  resolveCoercion(contexts) {
+    this.calls++;
+    return contexts.map((ctx) => ({
+      localId: ctx.localId,
+      value: PRIMES.has(ctx.activeVertex),
+    }));
+  }
+}
+
+const QUERY = `
+{
+    Number(max: 10) {
+        ... on Prime {
+            value @output
+
+            successor {
+                next: value @output
+            }
+        }
+    }
+}`;
+
+const EXPECTED = [
+  { value: 2, next: 3 },
+  { value: 3, next: 4 },
+  { value: 5, next: 6 },
+  { value: 7, next: 8 },
+];
+
+test("executes queries with sync adapters", () => {
+  const results = [...executeQuery(schema, new NumbersAdapter(), QUERY, {})];
+  assert.deepStrictEqual(results, EXPECTED);
+});
+
+test("provides results lazily", () => {
+  const results = executeQuery(schema, new NumbersAdapter(), QUERY, {});
+  assert.deepStrictEqual(results.next(), { done: false, value: EXPECTED[0] });
+});
+
+test("executes queries with async adapters", async () => {
+  const adapter = new AsyncNumbersAdapter();
+  const results = [];
+  for await (const result of executeQueryAsync(schema, adapter, QUERY, {})) {
+    results.push(result);
+  }
+  assert.deepStrictEqual(results, EXPECTED);
+
+  // Starting vertices, the coercion, "value", "successor", and "next":
+  // one call each, even though the query is executed again after each async call.
+  assert.strictEqual(adapter.calls, 5);
+});
+
+test("rejects async values from sync adapters", () => {
+  const adapter = new NumbersAdapter();
+  adapter.resolveStartingVertices = async () => [1, 2, 3];
+  assert.throws(() => [...executeQuery(schema, adapter, QUERY, {})], /executeQueryAsync/);
+});
+
+test("reports invalid queries", () => {
+  assert.throws(() => executeQuery(schema, new NumbersAdapter(), "{ Nonexistent { x @output } }", {}));
+});
@@ -0,0 +1,277 @@
+use std::path::{Path, PathBuf};
+
+use trustfall_core::{
+    accessor_property,
+    interpreter::{
+        basic_adapter::BasicAdapter,
+        helpers::{resolve_coercion_with, resolve_neighbors_with, resolve_property_with},
+        ContextIterator, ContextOutcomeIterator, Typename, VertexIterator,
+    },
+    ir::{EdgeParameters, FieldValue},
+    schema::Schema,
+};
+
+use crate::{
+    proc::Proc,
+    vertex::{Process, Vertex},
+    SCHEMA,
+};
+
+/// An adapter querying the processes, sockets, and mounted filesystems of the machine
+/// it runs on, from the Linux `/proc` filesystem.
+///
+/// Each query reads a new snapshot: processes are read when the query reaches them,
+/// and the tables of sockets and mounts the first time the query uses them.
+/// Processes that exit while the query runs are left out of the results.
+/// Where there's no `/proc` filesystem, as on operating systems other than Linux,
+/// there's nothing to query and every query has no results.
+#[derive(Debug, Clone)]
+pub struct OsAdapter {
+    proc_root: PathBuf,
+    schema: Schema,
+}
+
+impl OsAdapter {
+    /// Query the machine's `/proc` filesystem.
+    pub fn new() -> Self {
+        Self::with_proc_root("/proc")
+    }
+
+    /// Query a `/proc` filesystem mounted somewhere else, such as a container host's,
+    /// or a directory laid out like one.
+    pub fn with_proc_root(proc_root: impl Into<PathBuf>) -> Self {
+        Self {
+            proc_root: proc_root.into(),
+            schema: Schema::parse(SCHEMA).expect("schema is not valid"),
+        }
+    }
+
+    /// The schema of the processes, sockets, and mounts this adapter queries.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// The `/proc` directory this adapter queries.
+    pub fn proc_root(&self) -> &Path {
+        &self.proc_root
+    }
+}
+
+impl Default for OsAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> BasicAdapter<'a> for OsAdapter {
+    type Vertex = Vertex;
+
+    fn resolve_starting_vertices(
+        &self,
+        edge_name: &str,
+        parameters: &EdgeParameters,
+    ) -> VertexIterator<'a, Self::Vertex> {
+        let proc = Proc::new(self.proc_root.clone());
+        match edge_name {
+            "Processes" => {
+                let processes: Vec<_> = proc
+                    .pids()
+                    .into_iter()
+                    .filter_map(|pid| Process::load(&proc, pid))
+                    .map(Vertex::Process)
+                    .collect();
+                Box::new(processes.into_iter())
+            }
+            "Process" => {
+                let pid = parameters["pid"].as_u64();
+                let process = pid.and_then(|pid| Process::load(&proc, pid));
+                Box::new(process.map(Vertex::Process).into_iter())
+            }
+            "CurrentProcess" => {
+                let process = proc.current_pid().and_then(|pid| Process::load(&proc, pid));
+                Box::new(process.map(Vertex::Process).into_iter())
+            }
+            "Ports" => {
+                let ports: Vec<_> = proc
+                    .sockets()
+                    .iter()
+                    .map(|socket| Vertex::port(&proc, socket))
+                    .collect();
+                Box::new(ports.into_iter())
+            }
+            "Mounts" => {
+                let mounts: Vec<_> = proc.mounts().iter().cloned().map(Vertex::Mount).collect();
+                Box::new(mounts.into_iter())
+            }
+            _ => unreachable!("unexpected starting edge: {edge_name}"),
+        }
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'a, Self::Vertex>,
+        type_name: &str,
+        property_name: &str,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, FieldValue> {
+        match (type_name, property_name) {
+            // properties on Process
+            ("Process", "pid") => {
+                resolve_property_with(contexts, accessor_property!(as_process, pid))
+            }
+            ("Process", "name") => {
+                resolve_property_with(contexts, accessor_property!(as_process, name))
+            }
+            ("Process", "state") => {
+                resolve_property_with(contexts, accessor_property!(as_process, state))
+            }
+            ("Process", "uid") => {
+                resolve_property_with(contexts, accessor_property!(as_process, uid))
+            }
+            ("Process", "threads") => {
+                resolve_property_with(contexts, accessor_property!(as_process, threads))
+            }
+            ("Process", "memory") => {
+                resolve_property_with(contexts, accessor_property!(as_process, memory))
+            }
+            ("Process", "commandLine") => {
+                resolve_property_with(contexts, accessor_property!(as_process, command_line))
+            }
+            ("Process", "executable") => {
+                resolve_property_with(contexts, accessor_property!(as_process, executable))
+            }
+            ("Process", "workingDirectory") => {
+                resolve_property_with(contexts, accessor_property!(as_process, working_directory))
+            }
+
+            // properties on EnvironmentVariable
+            ("EnvironmentVariable", "name") => resolve_property_with(contexts, |vertex| {
+                vertex
+                    .as_environment_variable()
+                    .unwrap()
+                    .name
+                    .as_str()
+                    .into()
+            }),
+            ("EnvironmentVariable", "value") => resolve_property_with(contexts, |vertex| {
+                vertex
+                    .as_environment_variable()
+                    .unwrap()
+                    .value
+                    .as_str()
+                    .into()
+            }),
+
+            // properties on OpenFile
+            ("OpenFile", "fd") => {
+                resolve_property_with(contexts, |vertex| vertex.as_open_file().unwrap().fd.into())
+            }
+            ("OpenFile", "path") => resolve_property_with(contexts, |vertex| {
+                vertex.as_open_file().unwrap().path.as_str().into()
+            }),
+
+            // properties on Port
+            ("Port", "protocol") => {
+                resolve_property_with(contexts, accessor_property!(as_port, protocol))
+            }
+            ("Port", "localAddress") => {
+                resolve_property_with(contexts, accessor_property!(as_port, local_address))
+            }
+            ("Port", "localPort") => {
+                resolve_property_with(contexts, accessor_property!(as_port, local_port))
+            }
+            ("Port", "remoteAddress") => {
+                resolve_property_with(contexts, accessor_property!(as_port, remote_address))
+            }
+            ("Port", "remotePort") => {
+                resolve_property_with(contexts, accessor_property!(as_port, remote_port))
+            }
+            ("Port", "state") => {
+                resolve_property_with(contexts, accessor_property!(as_port, state))
+            }
+            ("Port", "inode") => {
+                resolve_property_with(contexts, accessor_property!(as_port, inode))
+            }
+
+            // properties on Mount
+            ("Mount", "device") => resolve_property_with(contexts, |vertex| {
+                vertex.as_mount().unwrap().device.as_str().into()
+            }),
+            ("Mount", "mountPoint") => resolve_property_with(contexts, |vertex| {
+                vertex.as_mount().unwrap().mount_point.as_str().into()
+            }),
+            ("Mount", "filesystemType") => resolve_property_with(contexts, |vertex| {
+                vertex.as_mount().unwrap().filesystem_type.as_str().into()
+            }),
+            ("Mount", "options") => resolve_property_with(contexts, |vertex| {
+                vertex.as_mount().unwrap().options.as_slice().into()
+            }),
+            ("Mount", "readonly") => resolve_property_with(contexts, |vertex| {
+                vertex.as_mount().unwrap().readonly.into()
+            }),
+            _ => unreachable!("unexpected property {type_name}.{property_name}"),
+        }
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: ContextIterator<'a, Self::Vertex>,
+        type_name: &str,
+        edge_name: &str,
+        _parameters: &EdgeParameters,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, VertexIterator<'a, Self::Vertex>> {
+        match (type_name, edge_name) {
+            ("Process", "parent") => resolve_neighbors_with(contexts, |vertex| {
+                let parent = vertex.as_process().unwrap().parent();
+                Box::new(parent.into_iter())
+            }),
+            ("Process", "children") => resolve_neighbors_with(contexts, |vertex| {
+                let children = vertex.as_process().unwrap().children();
+                Box::new(children.into_iter())
+            }),
+            ("Process", "environment") => resolve_neighbors_with(contexts, |vertex| {
+                let environment = vertex.as_process().unwrap().environment();
+                Box::new(environment.into_iter())
+            }),
+            ("Process", "openFiles") => resolve_neighbors_with(contexts, |vertex| {
+                let Vertex::Process(process) = vertex else {
+                    unreachable!("not a process: {vertex:?}")
+                };
+                Box::new(process.open_files().into_iter().map(Vertex::OpenFile))
+            }),
+            ("Process", "ports") => resolve_neighbors_with(contexts, |vertex| {
+                let Vertex::Process(process) = vertex else {
+                    unreachable!("not a process: {vertex:?}")
+                };
+                Box::new(process.ports().into_iter())
+            }),
+            ("OpenFile", "process") => resolve_neighbors_with(contexts, |vertex| {
+                let process = vertex.as_open_file().unwrap().process_vertex();
+                Box::new(std::iter::once(process))
+            }),
+            ("OpenFile", "mount") => resolve_neighbors_with(contexts, |vertex| {
+                let mount = vertex.as_open_file().unwrap().mount();
+                Box::new(mount.into_iter())
+            }),
+            ("OpenFile", "port") => resolve_neighbors_with(contexts, |vertex| {
+                let port = vertex.as_open_file().unwrap().port();
+                Box::new(port.into_iter())
+            }),
+            ("Port", "processes") => resolve_neighbors_with(contexts, |vertex| {
+                let processes = vertex.as_port().unwrap().processes();
+                Box::new(processes.into_iter())
+            }),
+            _ => unreachable!("unexpected edge {type_name}.{edge_name}"),
+        }
+    }
+
+    fn resolve_coercion(
+        &self,
+        contexts: ContextIterator<'a, Self::Vertex>,
+        _type_name: &str,
+        coerce_to_type: &str,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, bool> {
+        // None of the types have subtypes, so vertices only coerce to their own type.
+        let coerce_to_type = coerce_to_type.to_string();
+        resolve_coercion_with(contexts, move |vertex| vertex.typename() == coerce_to_type)
+    }
+}
@@ -0,0 +1,64 @@
+//! Query running processes, open sockets, and mounted filesystems with trustfall.
+//!
+//! [`OsAdapter`] queries the Linux `/proc` filesystem with the schema in [`SCHEMA`]:
+//! processes, along with their command lines, environment variables, and open files,
+//! the TCP and UDP sockets they listen and connect on, and the mounted filesystems.
+//!
+//! Since an open file is an edge both to the filesystem it's on and to the socket it is,
+//! if it's a socket, queries can join across all of them. For example, which processes
+//! are listening for connections, and which processes have files open on read-only
+//! filesystems:
+//!
+//! ```
+//! use std::{collections::BTreeMap, sync::Arc};
+//!
+//! use trustfall_core::{frontend::parse, interpreter::execution::interpret_ir, ir::FieldValue};
+//! use trustfall_os::OsAdapter;
+//!
+//! let proc_root = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/proc");
+//! let adapter = Arc::new(OsAdapter::with_proc_root(proc_root));
+//!
+//! let query = parse(adapter.schema(), r#"
+//! {
+//!     Ports {
+//!         state @filter(op: "=", value: ["$listen"])
+//!         localPort @output
+//!
+//!         processes {
+//!             name @output
+//!         }
+//!     }
+//! }"#).unwrap();
+//! let arguments = Arc::new(BTreeMap::from([("listen".into(), "LISTEN".into())]));
+//! let rows: Vec<_> = interpret_ir(adapter.clone(), query, arguments).unwrap().collect();
+//! let ports: Vec<_> = rows.iter().map(|row| row["localPort"].clone()).collect();
+//! assert_eq!(vec![FieldValue::Uint64(8080), 8443u64.into()], ports);
+//!
+//! let query = parse(adapter.schema(), r#"
+//! {
+//!     Processes {
+//!         name @output
+//!
+//!         openFiles {
+//!             path @output
+//!
+//!             mount {
+//!                 readonly @filter(op: "=", value: ["$readonly"])
+//!             }
+//!         }
+//!     }
+//! }"#).unwrap();
+//! let arguments = Arc::new(BTreeMap::from([("readonly".into(), true.into())]));
+//! let rows: Vec<_> = interpret_ir(adapter, query, arguments).unwrap().collect();
+//! let names: Vec<_> = rows.iter().map(|row| row["name"].clone()).collect();
+//! assert_eq!(vec![FieldValue::from("server"), "client".into()], names);
+//! ```
+mod adapter;
+mod proc;
+mod vertex;
+
+pub use adapter::OsAdapter;
+pub use vertex::{EnvironmentVariable, Mount, OpenFile, Port, Process, Vertex};
+
+/// The schema of the processes, sockets, and mounts queried by [`OsAdapter`].
+pub const SCHEMA: &str = include_str!("os.graphql");
@@ -0,0 +1,238 @@
+use std::{
+    cell::OnceCell,
+    fs,
+    net::{Ipv4Addr, Ipv6Addr},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::vertex::Mount;
+
+/// The `/proc` directory a query reads, with the tables it reads at most once.
+#[derive(Debug)]
+pub(crate) struct Proc {
+    root: PathBuf,
+    sockets: OnceCell<Vec<Rc<Socket>>>,
+    mounts: OnceCell<Vec<Rc<Mount>>>,
+}
+
+impl Proc {
+    pub(crate) fn new(root: PathBuf) -> Rc<Self> {
+        Rc::new(Self {
+            root,
+            sockets: OnceCell::new(),
+            mounts: OnceCell::new(),
+        })
+    }
+
+    pub(crate) fn path(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.root.join(relative)
+    }
+
+    /// The IDs of the running processes, in increasing order.
+    pub(crate) fn pids(&self) -> Vec<u64> {
+        let Ok(entries) = fs::read_dir(&self.root) else {
+            return vec![];
+        };
+        let mut pids: Vec<u64> = entries
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .collect();
+        pids.sort_unstable();
+        pids
+    }
+
+    /// The ID of the process running the query, which `/proc/self` links to.
+    pub(crate) fn current_pid(&self) -> Option<u64> {
+        fs::read_link(self.path("self"))
+            .ok()?
+            .to_str()?
+            .parse()
+            .ok()
+    }
+
+    pub(crate) fn sockets(&self) -> &[Rc<Socket>] {
+        self.sockets.get_or_init(|| {
+            ["tcp", "tcp6", "udp", "udp6"]
+                .into_iter()
+                .flat_map(|protocol| {
+                    let table = fs::read_to_string(self.path("net").join(protocol));
+                    parse_sockets(protocol, &table.unwrap_or_default())
+                })
+                .map(Rc::new)
+                .collect()
+        })
+    }
+
+    pub(crate) fn mounts(&self) -> &[Rc<Mount>] {
+        self.mounts.get_or_init(|| {
+            let table = fs::read_to_string(self.path("mounts")).unwrap_or_default();
+            parse_mounts(&table).into_iter().map(Rc::new).collect()
+        })
+    }
+}
+
+/// The fields of a `/proc/<pid>/status` file that processes have as properties.
+#[derive(Debug, Default)]
+pub(crate) struct Status {
+    pub(crate) name: String,
+    pub(crate) state: Option<String>,
+    pub(crate) parent_pid: Option<u64>,
+    pub(crate) uid: Option<u64>,
+    pub(crate) threads: Option<u64>,
+    /// In bytes, although the file gives it in kilobytes.
+    pub(crate) memory: Option<u64>,
+}
+
+pub(crate) fn parse_status(status: &str) -> Status {
+    let mut parsed = Status::default();
+    for line in status.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        let first_number = || value.split_whitespace().next()?.parse().ok();
+        match key {
+            "Name" => parsed.name = value.to_string(),
+            "State" => parsed.state = value.split_whitespace().next().map(str::to_string),
+            "PPid" => parsed.parent_pid = first_number(),
+            "Uid" => parsed.uid = first_number(),
+            "Threads" => parsed.threads = first_number(),
+            "VmRSS" => parsed.memory = first_number().map(|kilobytes: u64| kilobytes * 1024),
+            _ => {}
+        }
+    }
+    parsed
+}
+
+/// The strings in a file of NUL-terminated strings, like `/proc/<pid>/cmdline`.
+pub(crate) fn split_nul(contents: &[u8]) -> Vec<String> {
+    contents
+        .split(|byte| *byte == 0)
+        .filter(|item| !item.is_empty())
+        .map(|item| String::from_utf8_lossy(item).into_owned())
+        .collect()
+}
+
+/// The inode of a socket, from the target of the file descriptor's link, like `socket:[12345]`.
+pub(crate) fn socket_inode(path: &str) -> Option<u64> {
+    path.strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+/// A line of a table of sockets, like `/proc/net/tcp`.
+#[derive(Debug)]
+pub(crate) struct Socket {
+    pub(crate) protocol: &'static str,
+    pub(crate) local_address: String,
+    pub(crate) local_port: u64,
+    pub(crate) remote_address: String,
+    pub(crate) remote_port: u64,
+    pub(crate) state: &'static str,
+    pub(crate) inode: u64,
+}
+
+/// The sockets in a table like `/proc/net/tcp`, skipping any lines that can't be parsed.
+fn parse_sockets(protocol: &'static str, table: &str) -> Vec<Socket> {
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<_> = line.split_whitespace().collect();
+            let (local_address, local_port) = parse_socket_address(fields.get(1)?)?;
+            let (remote_address, remote_port) = parse_socket_address(fields.get(2)?)?;
+            let state = u8::from_str_radix(fields.get(3)?, 16).ok()?;
+            Some(Socket {
+                protocol,
+                local_address,
+                local_port,
+                remote_address,
+                remote_port,
+                state: socket_state(state),
+                inode: fields.get(9)?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// An address like `0100007F:1F90`, as hexadecimal words in the host's byte order and a port.
+fn parse_socket_address(address: &str) -> Option<(String, u64)> {
+    let (host, port) = address.split_once(':')?;
+    let port = u64::from_str_radix(port, 16).ok()?;
+    let mut bytes = vec![];
+    for start in (0..host.len()).step_by(8) {
+        let word = u32::from_str_radix(host.get(start..start + 8)?, 16).ok()?;
+        bytes.extend(word.to_ne_bytes());
+    }
+    let host = match <[u8; 4]>::try_from(bytes.as_slice()) {
+        Ok(bytes) => Ipv4Addr::from(bytes).to_string(),
+        Err(_) => Ipv6Addr::from(<[u8; 16]>::try_from(bytes.as_slice()).ok()?).to_string(),
+    };
+    Some((host, port))
+}
+
+/// The name of a TCP state, which the kernel uses for UDP sockets too.
+fn socket_state(state: u8) -> &'static str {
+    match state {
+        0x01 => "ESTABLISHED",
+        0x02 => "SYN_SENT",
+        0x03 => "SYN_RECV",
+        0x04 => "FIN_WAIT1",
+        0x05 => "FIN_WAIT2",
+        0x06 => "TIME_WAIT",
+        0x07 => "CLOSE",
+        0x08 => "CLOSE_WAIT",
+        0x09 => "LAST_ACK",
+        0x0A => "LISTEN",
+        0x0B => "CLOSING",
+        0x0C => "NEW_SYN_RECV",
+        _ => "UNKNOWN",
+    }
+}
+
+/// The mounts in a table like `/proc/mounts`, skipping any lines that can't be parsed.
+fn parse_mounts(table: &str) -> Vec<Mount> {
+    table
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace().map(unescape);
+            let device = fields.next()?;
+            let mount_point = fields.next()?;
+            let filesystem_type = fields.next()?;
+            let options: Vec<_> = fields.next()?.split(',').map(str::to_string).collect();
+            Some(Mount {
+                readonly: options.iter().any(|option| option == "ro"),
+                device,
+                mount_point,
+                filesystem_type,
+                options,
+            })
+        })
+        .collect()
+}
+
+/// Replace the octal escapes of whitespace and backslashes in a field of `/proc/mounts`,
+/// like `\040` for a space.
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = bytes.get(i + 1..i + 4).filter(|_| bytes[i] == b'\\');
+        let escaped = escape
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+        match escaped {
+            Some(byte) => {
+                unescaped.push(byte);
+                i += 4;
+            }
+            None => {
+                unescaped.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
@@ -0,0 +1,329 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use trustfall_core::interpreter::Typename;
+
+use crate::proc::{parse_status, socket_inode, split_nul, Proc, Socket};
+
+/// A vertex of the operating system schema.
+#[derive(Debug, Clone)]
+pub enum Vertex {
+    Process(Rc<Process>),
+    EnvironmentVariable(Rc<EnvironmentVariable>),
+    OpenFile(Rc<OpenFile>),
+    Port(Rc<Port>),
+    Mount(Rc<Mount>),
+}
+
+impl Vertex {
+    pub(crate) fn port(proc: &Rc<Proc>, socket: &Rc<Socket>) -> Self {
+        Self::Port(Rc::new(Port {
+            proc: proc.clone(),
+            socket: socket.clone(),
+        }))
+    }
+
+    pub fn as_process(&self) -> Option<&Process> {
+        match self {
+            Self::Process(process) => Some(process),
+            _ => None,
+        }
+    }
+
+    pub fn as_environment_variable(&self) -> Option<&EnvironmentVariable> {
+        match self {
+            Self::EnvironmentVariable(variable) => Some(variable),
+            _ => None,
+        }
+    }
+
+    pub fn as_open_file(&self) -> Option<&OpenFile> {
+        match self {
+            Self::OpenFile(file) => Some(file),
+            _ => None,
+        }
+    }
+
+    pub fn as_port(&self) -> Option<&Port> {
+        match self {
+            Self::Port(port) => Some(port),
+            _ => None,
+        }
+    }
+
+    pub fn as_mount(&self) -> Option<&Mount> {
+        match self {
+            Self::Mount(mount) => Some(mount),
+            _ => None,
+        }
+    }
+}
+
+impl Typename for Vertex {
+    fn typename(&self) -> &'static str {
+        match self {
+            Self::Process(_) => "Process",
+            Self::EnvironmentVariable(_) => "EnvironmentVariable",
+            Self::OpenFile(_) => "OpenFile",
+            Self::Port(_) => "Port",
+            Self::Mount(_) => "Mount",
+        }
+    }
+}
+
+/// A running process.
+///
+/// Its status is read when it's loaded, and its other files when the query uses them.
+#[derive(Debug)]
+pub struct Process {
+    proc: Rc<Proc>,
+    pid: u64,
+    name: String,
+    state: Option<String>,
+    parent_pid: Option<u64>,
+    uid: Option<u64>,
+    threads: Option<u64>,
+    memory: Option<u64>,
+}
+
+impl Process {
+    /// Load the process with the given ID, if it's running.
+    pub(crate) fn load(proc: &Rc<Proc>, pid: u64) -> Option<Rc<Self>> {
+        let status = fs::read_to_string(proc.path(pid.to_string()).join("status")).ok()?;
+        let status = parse_status(&status);
+        Some(Rc::new(Self {
+            proc: proc.clone(),
+            pid,
+            name: status.name,
+            state: status.state,
+            parent_pid: status.parent_pid,
+            uid: status.uid,
+            threads: status.threads,
+            memory: status.memory,
+        }))
+    }
+
+    pub fn pid(&self) -> u64 {
+        self.pid
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn state(&self) -> Option<&str> {
+        self.state.as_deref()
+    }
+
+    pub fn uid(&self) -> Option<u64> {
+        self.uid
+    }
+
+    pub fn threads(&self) -> Option<u64> {
+        self.threads
+    }
+
+    /// The memory the process has in RAM, in bytes.
+    pub fn memory(&self) -> Option<u64> {
+        self.memory
+    }
+
+    pub fn command_line(&self) -> Option<Vec<String>> {
+        fs::read(self.file("cmdline"))
+            .ok()
+            .map(|bytes| split_nul(&bytes))
+    }
+
+    pub fn executable(&self) -> Option<String> {
+        self.link("exe")
+    }
+
+    pub fn working_directory(&self) -> Option<String> {
+        self.link("cwd")
+    }
+
+    fn file(&self, name: &str) -> PathBuf {
+        self.proc.path(self.pid.to_string()).join(name)
+    }
+
+    fn link(&self, name: &str) -> Option<String> {
+        let target = fs::read_link(self.file(name)).ok()?;
+        Some(target.to_string_lossy().into_owned())
+    }
+
+    pub(crate) fn parent(&self) -> Option<Vertex> {
+        let parent_pid = self.parent_pid.filter(|pid| *pid != 0)?;
+        Process::load(&self.proc, parent_pid).map(Vertex::Process)
+    }
+
+    pub(crate) fn children(&self) -> Vec<Vertex> {
+        self.proc
+            .pids()
+            .into_iter()
+            .filter_map(|pid| Process::load(&self.proc, pid))
+            .filter(|process| process.parent_pid == Some(self.pid))
+            .map(Vertex::Process)
+            .collect()
+    }
+
+    pub(crate) fn environment(&self) -> Vec<Vertex> {
+        let Ok(environ) = fs::read(self.file("environ")) else {
+            return vec![];
+        };
+        split_nul(&environ)
+            .into_iter()
+            .filter_map(|variable| {
+                let (name, value) = variable.split_once('=')?;
+                Some(Vertex::EnvironmentVariable(Rc::new(EnvironmentVariable {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                })))
+            })
+            .collect()
+    }
+
+    /// The process's open files, in order of file descriptor.
+    pub(crate) fn open_files(self: &Rc<Self>) -> Vec<Rc<OpenFile>> {
+        let Ok(entries) = fs::read_dir(self.file("fd")) else {
+            return vec![];
+        };
+        let mut files: Vec<_> = entries
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let fd = entry.file_name().to_str()?.parse().ok()?;
+                let path = fs::read_link(entry.path()).ok()?;
+                Some(Rc::new(OpenFile {
+                    fd,
+                    path: path.to_string_lossy().into_owned(),
+                    process: self.clone(),
+                }))
+            })
+            .collect();
+        files.sort_unstable_by_key(|file| file.fd);
+        files
+    }
+
+    pub(crate) fn ports(self: &Rc<Self>) -> Vec<Vertex> {
+        self.open_files()
+            .iter()
+            .filter_map(|file| file.port())
+            .collect()
+    }
+}
+
+/// An environment variable of a process.
+#[derive(Debug)]
+pub struct EnvironmentVariable {
+    pub name: String,
+    pub value: String,
+}
+
+/// A file descriptor of a process.
+#[derive(Debug)]
+pub struct OpenFile {
+    pub fd: u64,
+    /// The target of the file descriptor's link in `/proc/<pid>/fd`.
+    pub path: String,
+    process: Rc<Process>,
+}
+
+impl OpenFile {
+    pub fn process(&self) -> &Process {
+        &self.process
+    }
+
+    pub(crate) fn process_vertex(&self) -> Vertex {
+        Vertex::Process(self.process.clone())
+    }
+
+    /// The mount with the longest mount point containing the file. Of mounts on the same
+    /// mount point, the last one hides the others, so it's the one containing the file.
+    pub(crate) fn mount(&self) -> Option<Vertex> {
+        let path = Path::new(&self.path);
+        if !path.is_absolute() {
+            return None;
+        }
+        self.process
+            .proc
+            .mounts()
+            .iter()
+            .filter(|mount| path.starts_with(&mount.mount_point))
+            .max_by_key(|mount| Path::new(&mount.mount_point).components().count())
+            .map(|mount| Vertex::Mount(mount.clone()))
+    }
+
+    pub(crate) fn port(&self) -> Option<Vertex> {
+        let inode = socket_inode(&self.path)?;
+        let proc = &self.process.proc;
+        proc.sockets()
+            .iter()
+            .find(|socket| socket.inode == inode)
+            .map(|socket| Vertex::port(proc, socket))
+    }
+}
+
+/// A TCP or UDP socket.
+#[derive(Debug)]
+pub struct Port {
+    proc: Rc<Proc>,
+    socket: Rc<Socket>,
+}
+
+impl Port {
+    pub fn protocol(&self) -> &'static str {
+        self.socket.protocol
+    }
+
+    pub fn local_address(&self) -> &str {
+        &self.socket.local_address
+    }
+
+    pub fn local_port(&self) -> u64 {
+        self.socket.local_port
+    }
+
+    pub fn remote_address(&self) -> &str {
+        &self.socket.remote_address
+    }
+
+    pub fn remote_port(&self) -> u64 {
+        self.socket.remote_port
+    }
+
+    pub fn state(&self) -> &'static str {
+        self.socket.state
+    }
+
+    pub fn inode(&self) -> u64 {
+        self.socket.inode
+    }
+
+    /// The processes with an open file descriptor for the socket.
+    pub(crate) fn processes(&self) -> Vec<Vertex> {
+        self.proc
+            .pids()
+            .into_iter()
+            .filter_map(|pid| Process::load(&self.proc, pid))
+            .filter(|process| {
+                process
+                    .open_files()
+                    .iter()
+                    .any(|file| socket_inode(&file.path) == Some(self.socket.inode))
+            })
+            .map(Vertex::Process)
+            .collect()
+    }
+}
+
+/// A mounted filesystem.
+#[derive(Debug)]
+pub struct Mount {
+    pub device: String,
+    pub mount_point: String,
+    pub filesystem_type: String,
+    pub options: Vec<String>,
+    pub readonly: bool,
+}
@@ -0,0 +1,326 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use trustfall_core::ir::FieldValue;
+use trustfall_os::OsAdapter;
+use trustfall_testing::{column, execute, strings};
+
+const PROC_ROOT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/proc");
+
+#[test]
+fn reads_processes_in_order_of_pid() {
+    let query = r#"
+{
+    Processes {
+        pid @output
+        name @output
+        state @output
+        uid @output
+        threads @output
+        memory @output
+        commandLine @output
+        executable @output
+        workingDirectory @output
+    }
+}"#;
+    let adapter = Arc::new(OsAdapter::with_proc_root(PROC_ROOT));
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(
+        vec![
+            FieldValue::Uint64(1),
+            100u64.into(),
+            200u64.into(),
+            300u64.into()
+        ],
+        column(&rows, "pid"),
+    );
+    assert_eq!(
+        strings(&["init", "server", "client", "kworker/0:1"]),
+        column(&rows, "name"),
+    );
+    assert_eq!(strings(&["S", "S", "R", "I"]), column(&rows, "state"));
+    assert_eq!(
+        vec![
+            FieldValue::Uint64(1024 * 1024),
+            (20480u64 * 1024).into(),
+            (2048u64 * 1024).into(),
+            FieldValue::Null,
+        ],
+        column(&rows, "memory"),
+    );
+    assert_eq!(
+        vec![
+            FieldValue::List(strings(&["/sbin/init", "splash"])),
+            FieldValue::List(strings(&["server", "--port", "8080"])),
+            FieldValue::List(strings(&["client", "http://localhost:8080"])),
+            FieldValue::List(vec![]),
+        ],
+        column(&rows, "commandLine"),
+    );
+
+    // Only the status of the kernel thread can be read.
+    let kernel_thread = &rows[3];
+    assert_eq!(FieldValue::Uint64(0), kernel_thread["uid"]);
+    assert_eq!(FieldValue::Uint64(1), kernel_thread["threads"]);
+    assert_eq!(FieldValue::Null, kernel_thread["executable"]);
+    assert_eq!(FieldValue::Null, kernel_thread["workingDirectory"]);
+    assert_eq!(
+        FieldValue::from("/opt/server/bin/server"),
+        rows[1]["executable"]
+    );
+    assert_eq!(FieldValue::from("/srv/data"), rows[1]["workingDirectory"]);
+}
+
+#[test]
+fn follows_parents_children_and_environment() {
+    let query = r#"
+{
+    CurrentProcess {
+        pid @output
+
+        parent {
+            parent: name @output
+
+            children @fold {
+                sibling: name @output
+            }
+        }
+        environment @fold {
+            variable: name @output
+            value @output
+        }
+    }
+}"#;
+    let adapter = Arc::new(OsAdapter::with_proc_root(PROC_ROOT));
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(1, rows.len(), "{rows:?}");
+    let row = &rows[0];
+    assert_eq!(FieldValue::Uint64(100), row["pid"]);
+    assert_eq!(FieldValue::from("init"), row["parent"]);
+    assert_eq!(
+        FieldValue::List(strings(&["server", "client"])),
+        row["sibling"]
+    );
+    assert_eq!(
+        FieldValue::List(strings(&["PORT", "LANG", "EMPTY"])),
+        row["variable"]
+    );
+    assert_eq!(
+        FieldValue::List(strings(&["8080", "C.UTF-8", ""])),
+        row["value"]
+    );
+
+    // The kernel thread's parent isn't in the fixture, and it has no readable environment.
+    let query = r#"
+{
+    Process(pid: 300) {
+        name @output
+
+        parent @optional {
+            parent: name @output
+        }
+        environment @fold @transform(op: "count") @output(name: "variables")
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(1, rows.len(), "{rows:?}");
+    assert_eq!(FieldValue::Null, rows[0]["parent"]);
+    assert_eq!(FieldValue::Uint64(0), rows[0]["variables"]);
+}
+
+#[test]
+fn joins_open_files_to_mounts_and_ports() {
+    let query = r#"
+{
+    Process(pid: 100) {
+        openFiles {
+            fd @output
+            path @output
+
+            mount @optional {
+                device @output
+                mountPoint @output
+                readonly @output
+            }
+            port @optional {
+                protocol @output
+                localAddress @output
+                localPort @output
+            }
+        }
+    }
+}"#;
+    let adapter = Arc::new(OsAdapter::with_proc_root(PROC_ROOT));
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(
+        vec![
+            FieldValue::Uint64(0),
+            3u64.into(),
+            4u64.into(),
+            5u64.into(),
+            10u64.into()
+        ],
+        column(&rows, "fd"),
+    );
+    assert_eq!(
+        strings(&[
+            "/dev/null",
+            "socket:[5001]",
+            "/srv/data/log.txt",
+            "pipe:[6001]",
+            "socket:[5002]"
+        ]),
+        column(&rows, "path"),
+    );
+    // The last of the mounts on the same mount point is the one files are on.
+    assert_eq!(
+        vec![
+            FieldValue::from("/dev/sda1"),
+            FieldValue::Null,
+            "/dev/sdc1".into(),
+            FieldValue::Null,
+            FieldValue::Null,
+        ],
+        column(&rows, "device"),
+    );
+    assert_eq!(
+        vec![
+            FieldValue::Boolean(false),
+            FieldValue::Null,
+            true.into(),
+            FieldValue::Null,
+            FieldValue::Null,
+        ],
+        column(&rows, "readonly"),
+    );
+    assert_eq!(
+        vec![
+            FieldValue::Null,
+            "0.0.0.0".into(),
+            FieldValue::Null,
+            FieldValue::Null,
+            "::1".into(),
+        ],
+        column(&rows, "localAddress"),
+    );
+
+    let query = r#"
+{
+    Ports {
+        protocol @output
+        localAddress @output
+        localPort @output
+        remoteAddress @output
+        remotePort @output
+        state @output
+        inode @output
+
+        processes @fold {
+            name @output
+        }
+    }
+}"#;
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(
+        strings(&["tcp", "tcp", "tcp6", "udp"]),
+        column(&rows, "protocol")
+    );
+    assert_eq!(
+        strings(&["0.0.0.0", "127.0.0.1", "::1", "0.0.0.0"]),
+        column(&rows, "localAddress"),
+    );
+    assert_eq!(
+        vec![
+            FieldValue::Uint64(8080),
+            45678u64.into(),
+            8443u64.into(),
+            53u64.into()
+        ],
+        column(&rows, "localPort"),
+    );
+    assert_eq!(
+        strings(&["0.0.0.0", "127.0.0.1", "::", "0.0.0.0"]),
+        column(&rows, "remoteAddress"),
+    );
+    assert_eq!(
+        strings(&["LISTEN", "ESTABLISHED", "LISTEN", "CLOSE"]),
+        column(&rows, "state"),
+    );
+    assert_eq!(
+        vec![
+            FieldValue::List(strings(&["server"])),
+            FieldValue::List(strings(&["client"])),
+            FieldValue::List(strings(&["server"])),
+            FieldValue::List(vec![]),
+        ],
+        column(&rows, "name"),
+    );
+}
+
+#[test]
+fn reads_mounts_in_order() {
+    let query = r#"
+{
+    Mounts {
+        mountPoint @output
+        filesystemType @output
+        options @output
+    }
+}"#;
+    let adapter = Arc::new(OsAdapter::with_proc_root(PROC_ROOT));
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(
+        strings(&["/", "/proc", "/srv/data", "/srv/data", "/mnt/scratch space"]),
+        column(&rows, "mountPoint"),
+    );
+    assert_eq!(
+        strings(&["ext4", "proc", "xfs", "xfs", "tmpfs"]),
+        column(&rows, "filesystemType"),
+    );
+    assert_eq!(
+        FieldValue::List(strings(&["rw", "size=1024k"])),
+        rows[4]["options"]
+    );
+}
+
+#[test]
+fn reads_nothing_without_a_proc_filesystem() {
+    let adapter = Arc::new(OsAdapter::with_proc_root(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/missing"
+    )));
+    let query = r#"
+{
+    Processes {
+        pid @output
+    }
+}"#;
+    assert!(execute(adapter.schema(), adapter.clone(), query, BTreeMap::new()).is_empty());
+    let query = r#"
+{
+    Ports {
+        inode @output
+    }
+}"#;
+    assert!(execute(adapter.schema(), adapter.clone(), query, BTreeMap::new()).is_empty());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn finds_the_current_process() {
+    let query = r#"
+{
+    CurrentProcess {
+        pid @output
+
+        openFiles @fold @transform(op: "count") @output(name: "files")
+    }
+}"#;
+    let adapter = Arc::new(OsAdapter::new());
+    let rows = execute(adapter.schema(), adapter.clone(), query, BTreeMap::new());
+    assert_eq!(1, rows.len(), "{rows:?}");
+    assert_eq!(
+        FieldValue::Uint64(std::process::id().into()),
+        rows[0]["pid"]
+    );
+    assert!(rows[0]["files"].as_u64().unwrap() > 0, "{rows:?}");
+}
@@ -0,0 +1,313 @@
+use std::sync::Arc;
+
+use petgraph::{
+    graph::{DefaultIx, EdgeIndex, Graph, IndexType, NodeIndex},
+    visit::EdgeRef,
+    Directed, Direction, EdgeType,
+};
+use serde::Serialize;
+use trustfall_core::{
+    interpreter::{
+        helpers::{resolve_coercion_with, resolve_neighbors_with, resolve_property_with},
+        Adapter, ContextIterator, ContextOutcomeIterator, ResolveEdgeInfo, ResolveInfo,
+        VertexIterator,
+    },
+    ir::{EdgeParameters, FieldValue},
+    schema::{
+        builder::{SchemaBuilder, Ty},
+        error::InvalidSchemaError,
+        Schema,
+    },
+};
+
+use crate::properties::Properties;
+
+pub const NODE_TYPE: &str = "Node";
+pub const EDGE_TYPE: &str = "Edge";
+
+const NODE_FIELDS: [&str; 5] = [
+    "index",
+    "outgoing",
+    "incoming",
+    "successors",
+    "predecessors",
+];
+const EDGE_FIELDS: [&str; 3] = ["index", "source", "target"];
+
+/// Errors from making an adapter for a graph.
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum PetgraphError {
+    #[error("Failed to serialize the graph's weights")]
+    Serialize(#[from] serde_json::Error),
+
+    #[error("The schema derived from the graph is not valid")]
+    InvalidSchema(#[from] InvalidSchemaError),
+}
+
+/// A node or an edge of the graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Vertex<Ix = DefaultIx> {
+    Node(NodeIndex<Ix>),
+    Edge(EdgeIndex<Ix>),
+}
+
+/// An adapter querying a [`Graph`], with the fields of its node and edge weights
+/// as properties.
+///
+/// The adapter serializes every weight when it's made, to find the properties of the schema,
+/// so its vertices are the graph's nodes and edges as of then. The graph can't be changed
+/// while the adapter borrows it.
+#[derive(Debug)]
+pub struct PetgraphAdapter<'a, N, E, Ty: EdgeType = Directed, Ix: IndexType = DefaultIx> {
+    graph: &'a Graph<N, E, Ty, Ix>,
+    schema: Schema,
+    nodes: Arc<Properties>,
+    edges: Arc<Properties>,
+}
+
+impl<'a, N, E, Ty, Ix> PetgraphAdapter<'a, N, E, Ty, Ix>
+where
+    N: Serialize,
+    E: Serialize,
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    /// Make an adapter for the graph, whose schema has a property for each field
+    /// of the serialized node and edge weights.
+    pub fn new(graph: &'a Graph<N, E, Ty, Ix>) -> Result<Self, PetgraphError> {
+        let nodes = Properties::infer(graph.node_weights(), &NODE_FIELDS)?;
+        let edges = Properties::infer(graph.edge_weights(), &EDGE_FIELDS)?;
+        let schema = derive_schema(&nodes, &edges)?;
+        Ok(Self {
+            graph,
+            schema,
+            nodes: Arc::new(nodes),
+            edges: Arc::new(edges),
+        })
+    }
+
+    /// The schema of the graph's nodes and edges, to parse queries against.
+    ///
+    /// ```graphql
+    /// type RootSchemaQuery {
+    ///     Nodes: [Node!]!
+    ///     Node(index: Int!): Node
+    ///     Edges: [Edge!]!
+    ///     Edge(index: Int!): Edge
+    /// }
+    ///
+    /// type Node {
+    ///     index: Int!
+    ///     # ... a property for each field of the node weights
+    ///
+    ///     outgoing: [Edge!]
+    ///     incoming: [Edge!]
+    ///     successors: [Node!]
+    ///     predecessors: [Node!]
+    /// }
+    ///
+    /// type Edge {
+    ///     index: Int!
+    ///     # ... a property for each field of the edge weights
+    ///
+    ///     source: Node!
+    ///     target: Node!
+    /// }
+    /// ```
+    ///
+    /// Weights serializing to JSON objects have a property for each of their fields,
+    /// and other weights, like numbers or strings, are the value of the `weight` property.
+    /// Properties are named after their fields, renamed if needed to be valid GraphQL names
+    /// that don't conflict with other fields. They're always nullable, since not every weight
+    /// may have every field. Their types are inferred from the weights' values: fields holding
+    /// objects, or values of different types, are strings of JSON text.
+    ///
+    /// In undirected graphs, `outgoing` and `incoming` are both every edge of the node,
+    /// and `successors` and `predecessors` are both every neighbor. As in petgraph,
+    /// a node's edges and neighbors are produced in the reverse of the order they were added.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub fn graph(&self) -> &'a Graph<N, E, Ty, Ix> {
+        self.graph
+    }
+}
+
+fn derive_schema(nodes: &Properties, edges: &Properties) -> Result<Schema, InvalidSchemaError> {
+    let node = || Ty::named(NODE_TYPE);
+    let edge = || Ty::named(EDGE_TYPE);
+
+    let mut builder = SchemaBuilder::new()
+        .root_edge("Nodes", node().non_null().list().non_null())
+        .description("All the graph's nodes, in order of their indexes.")
+        .root_edge("Node", node())
+        .description("The node with the given index, if any.")
+        .parameter("index", Ty::int().non_null())
+        .root_edge("Edges", edge().non_null().list().non_null())
+        .description("All the graph's edges, in order of their indexes.")
+        .root_edge("Edge", edge())
+        .description("The edge with the given index, if any.")
+        .parameter("index", Ty::int().non_null());
+
+    builder = builder
+        .vertex_type(NODE_TYPE)
+        .property("index", Ty::int().non_null());
+    for (name, (_, kind)) in &nodes.kinds {
+        builder = builder.property(name, kind.ty());
+    }
+    builder = builder
+        .edge("outgoing", edge().non_null().list())
+        .description("The edges starting from the node.")
+        .edge("incoming", edge().non_null().list())
+        .description("The edges ending at the node.")
+        .edge("successors", node().non_null().list())
+        .description("The nodes at the end of the node's outgoing edges.")
+        .edge("predecessors", node().non_null().list())
+        .description("The nodes at the start of the node's incoming edges.");
+
+    builder = builder
+        .vertex_type(EDGE_TYPE)
+        .property("index", Ty::int().non_null());
+    for (name, (_, kind)) in &edges.kinds {
+        builder = builder.property(name, kind.ty());
+    }
+    builder = builder
+        .edge("source", node().non_null())
+        .edge("target", node().non_null());
+
+    builder.build()
+}
+
+impl<'a, N, E, Ty, Ix> Adapter<'a> for PetgraphAdapter<'a, N, E, Ty, Ix>
+where
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    type Vertex = Vertex<Ix>;
+
+    fn resolve_starting_vertices(
+        &self,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        _resolve_info: &ResolveInfo,
+    ) -> VertexIterator<'a, Self::Vertex> {
+        let index = || match parameters.get("index") {
+            Some(FieldValue::Int64(index)) => usize::try_from(*index).ok(),
+            Some(FieldValue::Uint64(index)) => usize::try_from(*index).ok(),
+            _ => unreachable!("index parameter is not an integer: {parameters:?}"),
+        };
+        match edge_name.as_ref() {
+            "Nodes" => Box::new(self.graph.node_indices().map(Vertex::Node)),
+            "Edges" => Box::new(self.graph.edge_indices().map(Vertex::Edge)),
+            "Node" => {
+                let node = index()
+                    .filter(|index| *index < self.graph.node_count())
+                    .map(|index| Vertex::Node(NodeIndex::new(index)));
+                Box::new(node.into_iter())
+            }
+            "Edge" => {
+                let edge = index()
+                    .filter(|index| *index < self.graph.edge_count())
+                    .map(|index| Vertex::Edge(EdgeIndex::new(index)));
+                Box::new(edge.into_iter())
+            }
+            _ => unreachable!("unexpected starting edge {edge_name}"),
+        }
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'a, Self::Vertex>,
+        type_name: &Arc<str>,
+        property_name: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, FieldValue> {
+        let properties = match type_name.as_ref() {
+            NODE_TYPE => self.nodes.clone(),
+            EDGE_TYPE => self.edges.clone(),
+            _ => unreachable!("unexpected type {type_name}"),
+        };
+        let property_name = property_name.clone();
+        resolve_property_with(contexts, move |vertex| {
+            let index = match vertex {
+                Vertex::Node(node) => node.index(),
+                Vertex::Edge(edge) => edge.index(),
+            };
+            match property_name.as_ref() {
+                "__typename" => match vertex {
+                    Vertex::Node(_) => NODE_TYPE.into(),
+                    Vertex::Edge(_) => EDGE_TYPE.into(),
+                },
+                "index" => (index as i64).into(),
+                property => properties.get(index, property),
+            }
+        })
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: ContextIterator<'a, Self::Vertex>,
+        _type_name: &Arc<str>,
+        edge_name: &Arc<str>,
+        _parameters: &EdgeParameters,
+        _resolve_info: &ResolveEdgeInfo,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, VertexIterator<'a, Self::Vertex>> {
+        let graph = self.graph;
+        let edge_name = edge_name.clone();
+        resolve_neighbors_with(
+            contexts,
+            move |vertex| -> VertexIterator<'a, Self::Vertex> {
+                match (vertex, edge_name.as_ref()) {
+                    (Vertex::Node(node), "outgoing") => Box::new(
+                        graph
+                            .edges_directed(*node, Direction::Outgoing)
+                            .map(|edge| Vertex::Edge(edge.id())),
+                    ),
+                    (Vertex::Node(node), "incoming") => Box::new(
+                        graph
+                            .edges_directed(*node, Direction::Incoming)
+                            .map(|edge| Vertex::Edge(edge.id())),
+                    ),
+                    (Vertex::Node(node), "successors") => Box::new(
+                        graph
+                            .neighbors_directed(*node, Direction::Outgoing)
+                            .map(Vertex::Node),
+                    ),
+                    (Vertex::Node(node), "predecessors") => Box::new(
+                        graph
+                            .neighbors_directed(*node, Direction::Incoming)
+                            .map(Vertex::Node),
+                    ),
+                    (Vertex::Edge(edge), "source" | "target") => {
+                        let (source, target) = graph
+                            .edge_endpoints(*edge)
+                            .expect("edge is not in the graph");
+                        let node = if edge_name.as_ref() == "source" {
+                            source
+                        } else {
+                            target
+                        };
+                        Box::new(std::iter::once(Vertex::Node(node)))
+                    }
+                    _ => unreachable!("unexpected edge {edge_name} of {vertex:?}"),
+                }
+            },
+        )
+    }
+
+    fn resolve_coercion(
+        &self,
+        contexts: ContextIterator<'a, Self::Vertex>,
+        _type_name: &Arc<str>,
+        coerce_to_type: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'a, Self::Vertex, bool> {
+        let coerce_to_type = coerce_to_type.clone();
+        resolve_coercion_with(contexts, move |vertex| match vertex {
+            Vertex::Node(_) => coerce_to_type.as_ref() == NODE_TYPE,
+            Vertex::Edge(_) => coerce_to_type.as_ref() == EDGE_TYPE,
+        })
+    }
+}
@@ -0,0 +1,61 @@
+//! Query [petgraph](https://docs.rs/petgraph) graphs with trustfall.
+//!
+//! [`PetgraphAdapter`] needs no schema or adapter code: it serializes the graph's node
+//! and edge weights with serde, and exposes their fields as properties of the `Node`
+//! and `Edge` vertex types, with edges to traverse the graph in either direction.
+//! See [`PetgraphAdapter::schema`] for the schema's types.
+//!
+//! ```
+//! use std::{collections::BTreeMap, sync::Arc};
+//!
+//! use petgraph::Graph;
+//! use serde::Serialize;
+//! use trustfall_core::{frontend::parse, interpreter::execution::interpret_ir, ir::FieldValue};
+//! use trustfall_petgraph::PetgraphAdapter;
+//!
+//! #[derive(Serialize)]
+//! struct Station {
+//!     name: &'static str,
+//! }
+//!
+//! #[derive(Serialize)]
+//! struct Track {
+//!     minutes: u32,
+//! }
+//!
+//! let mut graph = Graph::new();
+//! let north = graph.add_node(Station { name: "North" });
+//! let center = graph.add_node(Station { name: "Center" });
+//! let south = graph.add_node(Station { name: "South" });
+//! graph.add_edge(north, center, Track { minutes: 4 });
+//! graph.add_edge(center, south, Track { minutes: 11 });
+//!
+//! let adapter = Arc::new(PetgraphAdapter::new(&graph).unwrap());
+//!
+//! // Stations more than ten minutes from their next station.
+//! let query = parse(adapter.schema(), r#"
+//! {
+//!     Nodes {
+//!         name @output
+//!
+//!         outgoing {
+//!             minutes @filter(op: ">", value: ["$min"])
+//!
+//!             target {
+//!                 next: name @output
+//!             }
+//!         }
+//!     }
+//! }"#).unwrap();
+//! let arguments = Arc::new(BTreeMap::from([("min".into(), 10.into())]));
+//! let rows: Vec<_> = interpret_ir(adapter, query, arguments).unwrap().collect();
+//!
+//! assert_eq!(1, rows.len());
+//! assert_eq!(FieldValue::from("Center"), rows[0]["name"]);
+//! assert_eq!(FieldValue::from("South"), rows[0]["next"]);
+//! ```
+mod adapter;
+mod properties;
+
+pub use adapter::{PetgraphAdapter, PetgraphError, Vertex, EDGE_TYPE, NODE_TYPE};
+pub use properties::WEIGHT_PROPERTY;
@@ -0,0 +1,135 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use trustfall_core::{
+    ir::FieldValue,
+    schema::builder::{graphql_name, unique_name, Ty},
+};
+
+/// The property holding weights that don't serialize to JSON objects, like numbers or strings.
+pub const WEIGHT_PROPERTY: &str = "weight";
+
+/// The type of a property, inferred from the values of all the weights.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Kind {
+    /// Only `null` values, so far.
+    Unknown,
+    Boolean,
+    Int,
+    Float,
+    String,
+    /// Objects, or values of different types, as JSON text.
+    Json,
+    List(Box<Kind>),
+}
+
+impl Kind {
+    fn of(value: &Value) -> Self {
+        match value {
+            Value::Null => Self::Unknown,
+            Value::Bool(_) => Self::Boolean,
+            Value::Number(n) if n.is_f64() => Self::Float,
+            Value::Number(_) => Self::Int,
+            Value::String(_) => Self::String,
+            Value::Array(items) => Self::List(Box::new(
+                items.iter().map(Self::of).fold(Self::Unknown, Self::merge),
+            )),
+            Value::Object(_) => Self::Json,
+        }
+    }
+
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Unknown, kind) | (kind, Self::Unknown) => kind,
+            (Self::Int, Self::Float) | (Self::Float, Self::Int) => Self::Float,
+            (Self::List(a), Self::List(b)) => Self::List(Box::new(a.merge(*b))),
+            (a, b) if a == b => a,
+            _ => Self::Json,
+        }
+    }
+
+    pub(crate) fn ty(&self) -> Ty {
+        match self {
+            Self::Boolean => Ty::boolean(),
+            Self::Int => Ty::int(),
+            Self::Float => Ty::float(),
+            Self::Unknown | Self::String | Self::Json => Ty::string(),
+            Self::List(item) => item.ty().list(),
+        }
+    }
+
+    fn convert(&self, value: &Value) -> FieldValue {
+        match (self, value) {
+            (_, Value::Null) => FieldValue::Null,
+            (Self::Json, value) => value.to_string().into(),
+            (Self::Boolean, Value::Bool(b)) => (*b).into(),
+            (Self::Int, Value::Number(n)) => match (n.as_i64(), n.as_u64()) {
+                (Some(n), _) => n.into(),
+                (None, Some(n)) => n.into(),
+                (None, None) => FieldValue::Null,
+            },
+            (Self::Float, Value::Number(n)) => n.as_f64().map(FieldValue::Float64).into(),
+            (Self::String, Value::String(s)) => s.as_str().into(),
+            (Self::List(item), Value::Array(items)) => {
+                FieldValue::List(items.iter().map(|value| item.convert(value)).collect())
+            }
+            _ => FieldValue::Null,
+        }
+    }
+}
+
+/// The properties of the nodes or edges of a graph, from their serialized weights.
+#[derive(Debug, Clone)]
+pub(crate) struct Properties {
+    /// Each property's key in the serialized weights, and its type.
+    pub(crate) kinds: BTreeMap<String, (String, Kind)>,
+    /// The serialized weights, by node or edge index.
+    values: Vec<Map<String, Value>>,
+}
+
+impl Properties {
+    /// Serialize the weights, and infer a property for each of their fields.
+    /// `reserved` are the names of the type's other fields, which properties can't use.
+    pub(crate) fn infer<'w, W: Serialize + 'w>(
+        weights: impl Iterator<Item = &'w W>,
+        reserved: &[&str],
+    ) -> Result<Self, serde_json::Error> {
+        let values = weights
+            .map(|weight| {
+                Ok(match serde_json::to_value(weight)? {
+                    Value::Object(fields) => fields,
+                    Value::Null => Map::new(),
+                    value => Map::from_iter([(WEIGHT_PROPERTY.to_string(), value)]),
+                })
+            })
+            .collect::<Result<Vec<_>, serde_json::Error>>()?;
+
+        let mut key_kinds: BTreeMap<&str, Kind> = BTreeMap::new();
+        for (key, value) in values.iter().flatten() {
+            let kind = key_kinds.remove(key.as_str()).unwrap_or(Kind::Unknown);
+            key_kinds.insert(key, kind.merge(Kind::of(value)));
+        }
+
+        let mut taken: BTreeSet<String> = reserved.iter().map(|name| name.to_string()).collect();
+        let kinds = key_kinds
+            .into_iter()
+            .map(|(key, kind)| {
+                let name = unique_name(&mut taken, graphql_name(key));
+                (name, (key.to_string(), kind))
+            })
+            .collect();
+        Ok(Self { kinds, values })
+    }
+
+    /// The value of the property of the node or edge with the given index.
+    pub(crate) fn get(&self, index: usize, property: &str) -> FieldValue {
+        let Some((key, kind)) = self.kinds.get(property) else {
+            unreachable!("not a property of the graph: {property}")
+        };
+        match self.values[index].get(key) {
+            Some(value) => kind.convert(value),
+            None => FieldValue::Null,
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use petgraph::{Graph, Undirected};
+use serde::Serialize;
+use trustfall_core::{
+    frontend::parse, interpreter::execution::interpret_ir, ir::FieldValue, schema::Schema,
+};
+use trustfall_petgraph::PetgraphAdapter;
+
+#[derive(Serialize)]
+struct Crate {
+    name: &'static str,
+    version: &'static str,
+    downloads: Option<u64>,
+    // Conflicts with the `index` field of nodes.
+    index: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Dependency {
+    Normal {
+        optional: bool,
+    },
+    Dev {
+        kind: &'static str,
+        features: Vec<&'static str>,
+    },
+}
+
+fn crates() -> Graph<Crate, Dependency> {
+    let mut graph = Graph::new();
+    let trustfall = graph.add_node(Crate {
+        name: "trustfall",
+        version: "0.5.0",
+        downloads: Some(12000),
+        index: "crates.io",
+    });
+    let serde = graph.add_node(Crate {
+        name: "serde",
+        version: "1.0.163",
+        downloads: None,
+        index: "crates.io",
+    });
+    let serde_json = graph.add_node(Crate {
+        name: "serde_json",
+        version: "1.0.96",
+        downloads: Some(250000000),
+        index: "crates.io",
+    });
+    graph.add_edge(trustfall, serde, Dependency::Normal { optional: false });
+    graph.add_edge(trustfall, serde_json, Dependency::Normal { optional: true });
+    graph.add_edge(serde_json, serde, Dependency::Normal { optional: false });
+    graph.add_edge(
+        trustfall,
+        serde_json,
+        Dependency::Dev {
+            kind: "dev",
+            features: vec!["std"],
+        },
+    );
+    graph
+}
+
+type Row = BTreeMap<Arc<str>, FieldValue>;
+
+fn run(
+    adapter: PetgraphAdapter<'_, Crate, Dependency>,
+    query: &str,
+    arguments: BTreeMap<Arc<str>, FieldValue>,
+) -> Vec<Row> {
+    let adapter = Arc::new(adapter);
+    let query = parse(adapter.schema(), query).unwrap();
+    interpret_ir(adapter, query, Arc::new(arguments))
+        .unwrap()
+        .collect()
+}
+
+fn column(rows: &[Row], name: &str) -> Vec<FieldValue> {
+    rows.iter().map(|row| row[name].clone()).collect()
+}
+
+fn field_types(schema: &Schema, type_name: &str) -> Vec<(String, String)> {
+    let vertex_type = schema.vertex_type(type_name).unwrap();
+    vertex_type
+        .properties()
+        .map(|property| {
+            (
+                property.name().to_string(),
+                property.property_type().to_string(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn infers_properties_from_weights() {
+    let graph = crates();
+    let adapter = PetgraphAdapter::new(&graph).unwrap();
+    let pairs = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, ty)| (name.to_string(), ty.to_string()))
+            .collect()
+    };
+    assert_eq!(
+        pairs(&[
+            ("index", "Int!"),
+            ("downloads", "Int"),
+            ("index_2", "String"),
+            ("name", "String"),
+            ("version", "String"),
+        ]),
+        field_types(adapter.schema(), "Node"),
+    );
+    assert_eq!(
+        pairs(&[
+            ("index", "Int!"),
+            ("features", "[String]"),
+            ("kind", "String"),
+            ("optional", "Boolean"),
+        ]),
+        field_types(adapter.schema(), "Edge"),
+    );
+
+    let rows = run(
+        adapter,
+        r#"
+{
+    Nodes {
+        index @output
+        name @output
+        downloads @output
+        registry: index_2 @output
+    }
+}"#,
+        BTreeMap::new(),
+    );
+    assert_eq!(
+        vec![
+            FieldValue::Int64(0),
+            FieldValue::Int64(1),
+            FieldValue::Int64(2)
+        ],
+        column(&rows, "index")
+    );
+    assert_eq!(
+        vec![
+            FieldValue::Int64(12000),
+            FieldValue::Null,
+            FieldValue::Int64(250000000)
+        ],
+        column(&rows, "downloads")
+    );
+    assert_eq!(
+        vec![FieldValue::from("crates.io"); 3],
+        column(&rows, "registry")
+    );
+}
+
+#[test]
+fn traverses_edges_in_both_directions() {
+    let graph = crates();
+    let rows = run(
+        PetgraphAdapter::new(&graph).unwrap(),
+        r#"
+{
+    Node(index: 1) {
+        name @output
+
+        incoming @fold {
+            optional @output
+
+            source {
+                dependent: name @output
+            }
+        }
+        predecessors @fold {
+            predecessor: name @output
+        }
+    }
+}"#,
+        BTreeMap::new(),
+    );
+    assert_eq!(vec![FieldValue::from("serde")], column(&rows, "name"));
+    // Edges are produced in the reverse of the order they were added.
+    assert_eq!(
+        vec![FieldValue::List(vec![
+            "serde_json".into(),
+            "trustfall".into()
+        ])],
+        column(&rows, "dependent")
+    );
+    assert_eq!(
+        vec![FieldValue::List(vec![false.into(), false.into()])],
+        column(&rows, "optional")
+    );
+    assert_eq!(column(&rows, "dependent"), column(&rows, "predecessor"));
+
+    let rows = run(
+        PetgraphAdapter::new(&graph).unwrap(),
+        r#"
+{
+    Edges {
+        kind @filter(op: "=", value: ["$kind"]) @output
+        features @output
+
+        source {
+            from: name @output
+        }
+        target {
+            to: name @output
+        }
+    }
+}"#,
+        BTreeMap::from([("kind".into(), "dev".into())]),
+    );
+    assert_eq!(1, rows.len(), "{rows:?}");
+    assert_eq!(FieldValue::from("trustfall"), rows[0]["from"]);
+    assert_eq!(FieldValue::from("serde_json"), rows[0]["to"]);
+    assert_eq!(FieldValue::List(vec!["std".into()]), rows[0]["features"]);
+}
+
+#[test]
+fn exposes_scalar_weights_and_undirected_neighbors() {
+    let mut graph: Graph<&str, f64, Undirected> = Graph::new_undirected();
+    let a = graph.add_node("a");
+    let b = graph.add_node("b");
+    let c = graph.add_node("c");
+    graph.add_edge(a, b, 1.5);
+    graph.add_edge(c, a, 2.0);
+
+    let adapter = Arc::new(PetgraphAdapter::new(&graph).unwrap());
+    let query = parse(
+        adapter.schema(),
+        r#"
+{
+    Node(index: 0) {
+        weight @output
+
+        outgoing @fold {
+            distance: weight @output
+        }
+        successors @fold {
+            neighbor: weight @output
+        }
+    }
+}"#,
+    )
+    .unwrap();
+    let rows: Vec<_> = interpret_ir(adapter, query, Arc::new(BTreeMap::new()))
+        .unwrap()
+        .collect();
+    assert_eq!(vec![FieldValue::from("a")], column(&rows, "weight"));
+    assert_eq!(
+        vec![FieldValue::List(vec![
+            FieldValue::Float64(2.0),
+            FieldValue::Float64(1.5)
+        ])],
+        column(&rows, "distance")
+    );
+    assert_eq!(
+        vec![FieldValue::List(vec!["c".into(), "b".into()])],
+        column(&rows, "neighbor")
+    );
+}
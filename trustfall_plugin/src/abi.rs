@@ -0,0 +1,133 @@
+//! The binary interface between plugin hosts and the adapters they load.
+//!
+//! A plugin is a WebAssembly module exporting these functions, where pointers and lengths
+//! are offsets and sizes in bytes within the module's exported `memory`:
+//!
+//! | Export                        | Signature                 |
+//! |-------------------------------|---------------------------|
+//! | [`ABI_VERSION_EXPORT`]        | `() -> u32`               |
+//! | [`ALLOC_EXPORT`]              | `(len: u32) -> u32`       |
+//! | [`DEALLOC_EXPORT`]            | `(ptr: u32, len: u32)`    |
+//! | [`CALL_EXPORT`]               | `(ptr: u32, len: u32) -> u64` |
+//!
+//! To make a call, the host checks that the plugin's ABI version is [`ABI_VERSION`],
+//! allocates a buffer in the plugin's memory, writes a JSON-encoded [`RequestMessage`] into it,
+//! and passes it to the call export. The plugin takes ownership of the request buffer,
+//! and returns the location of a JSON-encoded [`Response`] as `(ptr << 32) | len`.
+//! The host reads the response, then frees it with the dealloc export.
+//!
+//! The plugin keeps the vertices it produces, and the host refers to them by [`Handle`].
+//! Each handle is valid until the host releases it in a later request.
+//! Values are encoded as [`FieldValue`]s, so they keep the same types on both sides.
+//! Plugins don't import anything, so they can't reach anything outside their sandbox
+//! besides what the host's runtime chooses to provide.
+use serde::{Deserialize, Serialize};
+use trustfall_core::ir::{EdgeParameters, FieldValue};
+
+/// The version of the ABI described in this module.
+///
+/// It's incremented whenever a change would break existing plugins or hosts.
+pub const ABI_VERSION: u32 = 1;
+
+pub const ABI_VERSION_EXPORT: &str = "trustfall_abi_version";
+pub const ALLOC_EXPORT: &str = "trustfall_alloc";
+pub const DEALLOC_EXPORT: &str = "trustfall_dealloc";
+pub const CALL_EXPORT: &str = "trustfall_call";
+
+/// A plugin's name for one of its vertices, or for one of its starting vertex iterators.
+pub type Handle = u64;
+
+/// A request, together with the handles the host no longer uses.
+///
+/// Releasing handles along with the next request saves a call into the plugin
+/// for every vertex the host drops.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestMessage {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub release_vertices: Vec<Handle>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub release_iterators: Vec<Handle>,
+
+    #[serde(flatten)]
+    pub request: Request,
+}
+
+/// The operations a plugin performs for its host. Each has a single kind of [`Response`].
+///
+/// Batches of vertices are answered with one result per vertex, in the same order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Request {
+    /// The schema of the plugin's adapter. Answered with [`Response::Schema`].
+    Schema,
+
+    /// Start iterating over the vertices of a starting edge.
+    /// Answered with [`Response::Iterator`].
+    ResolveStartingVertices {
+        edge_name: String,
+        parameters: EdgeParameters,
+    },
+
+    /// Take up to `max` of a starting edge's vertices. Answered with [`Response::Vertices`].
+    NextVertices { iterator: Handle, max: usize },
+
+    /// Answered with [`Response::Values`]. Resolves `__typename` too.
+    ResolveProperty {
+        type_name: String,
+        property_name: String,
+        vertices: Vec<Handle>,
+    },
+
+    /// Answered with [`Response::Neighbors`].
+    ResolveNeighbors {
+        type_name: String,
+        edge_name: String,
+        parameters: EdgeParameters,
+        vertices: Vec<Handle>,
+    },
+
+    /// Answered with [`Response::Coercions`].
+    ResolveCoercion {
+        type_name: String,
+        coerce_to_type: String,
+        vertices: Vec<Handle>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Response {
+    Schema {
+        schema: String,
+    },
+
+    Iterator {
+        iterator: Handle,
+    },
+
+    /// The next vertices of a starting edge. Once `done`, the iterator's handle
+    /// is released by the plugin.
+    Vertices {
+        vertices: Vec<Handle>,
+        done: bool,
+    },
+
+    Values {
+        values: Vec<FieldValue>,
+    },
+
+    /// All the neighbors of each vertex.
+    Neighbors {
+        neighbors: Vec<Vec<Handle>>,
+    },
+
+    Coercions {
+        coercions: Vec<bool>,
+    },
+
+    /// The request could not be answered, for example because it used a released handle.
+    Error {
+        message: String,
+    },
+}
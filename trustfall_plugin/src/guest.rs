@@ -0,0 +1,301 @@
+//! Compiling an adapter into a plugin.
+//!
+//! Wrap the adapter in a [`PluginGuest`], and export it from the plugin's crate
+//! with [`export_adapter!`](crate::export_adapter), compiled to `wasm32-unknown-unknown`
+//! as a `cdylib`.
+use std::collections::HashMap;
+
+use trustfall_core::{
+    interpreter::{basic_adapter::BasicAdapter, DataContext, VertexIterator},
+    ir::{EdgeParameters, FieldValue},
+};
+
+use crate::abi::{Handle, Request, RequestMessage, Response};
+
+/// Answers a host's encoded requests. Implemented by [`PluginGuest`].
+pub trait Dispatch {
+    /// Answer a JSON-encoded [`RequestMessage`] with a JSON-encoded [`Response`].
+    fn dispatch(&mut self, request: &[u8]) -> Vec<u8>;
+}
+
+/// The plugin side of the ABI, answering requests with an adapter.
+///
+/// Panics in the adapter are not caught: in a plugin they abort the call into it,
+/// which the host reports as a failed call.
+pub struct PluginGuest<A: BasicAdapter<'static>> {
+    schema: String,
+    adapter: A,
+    vertices: HashMap<Handle, A::Vertex>,
+    iterators: HashMap<Handle, VertexIterator<'static, A::Vertex>>,
+    next_handle: Handle,
+}
+
+impl<A: BasicAdapter<'static>> std::fmt::Debug for PluginGuest<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PluginGuest")
+            .field("vertices", &self.vertices)
+            .field("iterators", &self.iterators.len())
+            .field("next_handle", &self.next_handle)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<A: BasicAdapter<'static>> PluginGuest<A> {
+    /// Serve the given adapter, whose schema is the given schema text.
+    pub fn new(schema: impl Into<String>, adapter: A) -> Self {
+        Self {
+            schema: schema.into(),
+            adapter,
+            vertices: HashMap::new(),
+            iterators: HashMap::new(),
+            next_handle: 0,
+        }
+    }
+
+    /// How many vertices the host holds handles to.
+    pub fn live_vertices(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Answer a request, after releasing the handles the host no longer uses.
+    pub fn handle(&mut self, message: RequestMessage) -> Response {
+        for handle in &message.release_vertices {
+            self.vertices.remove(handle);
+        }
+        for handle in &message.release_iterators {
+            self.iterators.remove(handle);
+        }
+
+        match self.answer(message.request) {
+            Ok(response) => response,
+            Err(message) => Response::Error { message },
+        }
+    }
+
+    fn answer(&mut self, request: Request) -> Result<Response, String> {
+        match request {
+            Request::Schema => Ok(Response::Schema {
+                schema: self.schema.clone(),
+            }),
+            Request::ResolveStartingVertices {
+                edge_name,
+                parameters,
+            } => {
+                let vertices = self
+                    .adapter
+                    .resolve_starting_vertices(&edge_name, &parameters);
+                let iterator = self.new_handle();
+                self.iterators.insert(iterator, vertices);
+                Ok(Response::Iterator { iterator })
+            }
+            Request::NextVertices { iterator, max } => {
+                let vertices = self
+                    .iterators
+                    .get_mut(&iterator)
+                    .ok_or_else(|| format!("unknown iterator handle {iterator}"))?;
+                let batch: Vec<_> = vertices.take(max).collect();
+                let done = batch.len() < max;
+                if done {
+                    self.iterators.remove(&iterator);
+                }
+                let vertices = batch.into_iter().map(|v| self.insert(v)).collect();
+                Ok(Response::Vertices { vertices, done })
+            }
+            Request::ResolveProperty {
+                type_name,
+                property_name,
+                vertices,
+            } => {
+                let contexts = self.contexts(&vertices)?;
+                let outcomes = if property_name == "__typename" {
+                    self.adapter.resolve_typename(contexts, &type_name)
+                } else {
+                    self.adapter
+                        .resolve_property(contexts, &type_name, &property_name)
+                };
+                let values: Vec<FieldValue> = outcomes.map(|(_, value)| value).collect();
+                expect_len(vertices.len(), values.len())?;
+                Ok(Response::Values { values })
+            }
+            Request::ResolveNeighbors {
+                type_name,
+                edge_name,
+                parameters,
+                vertices,
+            } => {
+                let contexts = self.contexts(&vertices)?;
+                let outcomes =
+                    self.resolve_neighbors(contexts, &type_name, &edge_name, &parameters);
+                let neighbors: Vec<Vec<Handle>> = outcomes
+                    .into_iter()
+                    .map(|neighbors| neighbors.into_iter().map(|v| self.insert(v)).collect())
+                    .collect();
+                expect_len(vertices.len(), neighbors.len())?;
+                Ok(Response::Neighbors { neighbors })
+            }
+            Request::ResolveCoercion {
+                type_name,
+                coerce_to_type,
+                vertices,
+            } => {
+                let contexts = self.contexts(&vertices)?;
+                let coercions: Vec<bool> = self
+                    .adapter
+                    .resolve_coercion(contexts, &type_name, &coerce_to_type)
+                    .map(|(_, can_coerce)| can_coerce)
+                    .collect();
+                expect_len(vertices.len(), coercions.len())?;
+                Ok(Response::Coercions { coercions })
+            }
+        }
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: VertexIterator<'static, DataContext<A::Vertex>>,
+        type_name: &str,
+        edge_name: &str,
+        parameters: &EdgeParameters,
+    ) -> Vec<Vec<A::Vertex>> {
+        self.adapter
+            .resolve_neighbors(contexts, type_name, edge_name, parameters)
+            .map(|(_, neighbors)| neighbors.collect())
+            .collect()
+    }
+
+    fn contexts(
+        &self,
+        handles: &[Handle],
+    ) -> Result<VertexIterator<'static, DataContext<A::Vertex>>, String> {
+        let vertices = handles
+            .iter()
+            .map(|handle| {
+                self.vertices
+                    .get(handle)
+                    .cloned()
+                    .map(|vertex| DataContext::new(Some(vertex)))
+                    .ok_or_else(|| format!("unknown vertex handle {handle}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Box::new(vertices.into_iter()))
+    }
+
+    fn insert(&mut self, vertex: A::Vertex) -> Handle {
+        let handle = self.new_handle();
+        self.vertices.insert(handle, vertex);
+        handle
+    }
+
+    fn new_handle(&mut self) -> Handle {
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        handle
+    }
+}
+
+impl<A: BasicAdapter<'static>> Dispatch for PluginGuest<A> {
+    fn dispatch(&mut self, request: &[u8]) -> Vec<u8> {
+        let response = match serde_json::from_slice(request) {
+            Ok(message) => self.handle(message),
+            Err(e) => Response::Error {
+                message: format!("invalid request: {e}"),
+            },
+        };
+        serde_json::to_vec(&response).expect("failed to serialize response")
+    }
+}
+
+fn expect_len(expected: usize, actual: usize) -> Result<(), String> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(format!(
+            "the adapter produced {actual} results for {expected} vertices"
+        ))
+    }
+}
+
+/// Allocate a zeroed buffer of `len` bytes and leak it, for the host to write a request into.
+pub fn alloc(len: usize) -> *mut u8 {
+    Box::into_raw(vec![0u8; len].into_boxed_slice()) as *mut u8
+}
+
+/// Free a buffer returned by [`alloc`] or [`call`].
+///
+/// # Safety
+///
+/// `ptr` and `len` must be those of a buffer returned by [`alloc`] or [`call`],
+/// which must not be used afterward.
+pub unsafe fn dealloc(ptr: *mut u8, len: usize) {
+    drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)));
+}
+
+/// Answer the request in the buffer at `ptr`, taking ownership of the buffer.
+/// Returns the location of the response, which must be freed with [`dealloc`].
+///
+/// # Safety
+///
+/// `ptr` and `len` must be those of a buffer returned by [`alloc`],
+/// which must not be used afterward.
+pub unsafe fn call(guest: &mut dyn Dispatch, ptr: *mut u8, len: usize) -> (*mut u8, usize) {
+    let request = Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len));
+    let response = guest.dispatch(&request).into_boxed_slice();
+    let len = response.len();
+    (Box::into_raw(response) as *mut u8, len)
+}
+
+/// Export the plugin ABI's functions, answering calls with the [`PluginGuest`]
+/// returned by the given function in the same module, which is called on the first call into the plugin.
+///
+/// The functions are only exported when compiling to `wasm32`, whose
+/// 32-bit pointers are what the ABI passes around.
+///
+/// ```ignore
+/// fn guest() -> PluginGuest<MyAdapter> {
+///     PluginGuest::new(include_str!("schema.graphql"), MyAdapter::new())
+/// }
+///
+/// trustfall_plugin::export_adapter!(guest);
+/// ```
+#[macro_export]
+macro_rules! export_adapter {
+    ($guest:ident) => {
+        #[cfg(target_arch = "wasm32")]
+        #[doc(hidden)]
+        pub mod __trustfall_plugin_exports {
+            use ::std::cell::RefCell;
+
+            use $crate::guest::Dispatch;
+
+            ::std::thread_local! {
+                static GUEST: RefCell<Option<Box<dyn Dispatch>>> = RefCell::new(None);
+            }
+
+            #[no_mangle]
+            pub extern "C" fn trustfall_abi_version() -> u32 {
+                $crate::abi::ABI_VERSION
+            }
+
+            #[no_mangle]
+            pub extern "C" fn trustfall_alloc(len: u32) -> u32 {
+                $crate::guest::alloc(len as usize) as u32
+            }
+
+            #[no_mangle]
+            pub unsafe extern "C" fn trustfall_dealloc(ptr: u32, len: u32) {
+                $crate::guest::dealloc(ptr as *mut u8, len as usize)
+            }
+
+            #[no_mangle]
+            pub unsafe extern "C" fn trustfall_call(ptr: u32, len: u32) -> u64 {
+                GUEST.with(|guest| {
+                    let mut guest = guest.borrow_mut();
+                    let guest = guest.get_or_insert_with(|| Box::new(super::$guest()));
+                    let (ptr, len) =
+                        $crate::guest::call(guest.as_mut(), ptr as *mut u8, len as usize);
+                    ((ptr as u64) << 32) | len as u64
+                })
+            }
+        }
+    };
+}
@@ -0,0 +1,449 @@
+//! Running queries against adapters loaded as plugins.
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use trustfall_core::{
+    interpreter::{
+        basic_adapter::BasicAdapter, Adapter, ContextIterator, ContextOutcomeIterator, DataContext,
+        ResolveEdgeInfo, ResolveInfo, VertexIterator,
+    },
+    ir::{EdgeParameters, FieldValue},
+    schema::{error::InvalidSchemaError, Schema},
+};
+
+use crate::{
+    abi::{Handle, Request, RequestMessage, Response, ABI_VERSION},
+    guest::{Dispatch, PluginGuest},
+};
+
+/// How many vertices are sent to the plugin in each call, unless configured otherwise.
+pub const DEFAULT_BATCH_SIZE: usize = 256;
+
+/// Errors from loading or calling into a plugin.
+#[non_exhaustive]
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum PluginError {
+    #[error("The plugin implements ABI version {found}, but version {expected} is required.")]
+    UnsupportedAbiVersion { found: u32, expected: u32 },
+
+    #[error("The plugin could not be loaded: {0}")]
+    LoadFailed(String),
+
+    #[error("The call into the plugin failed: {0}")]
+    CallFailed(String),
+
+    #[error("The plugin's response is not valid: {0}")]
+    InvalidResponse(String),
+
+    #[error("The plugin could not answer the request: {0}")]
+    PluginError(String),
+
+    #[error("The plugin's schema is not valid")]
+    InvalidSchema(#[from] InvalidSchemaError),
+}
+
+/// A loaded plugin, in whichever WebAssembly runtime the host uses.
+///
+/// Implementations call the plugin's exports as described in the [`abi`](crate::abi) module.
+/// The `trustfall_plugin_wasmtime` crate implements this for plugins run by wasmtime.
+pub trait PluginInstance {
+    /// The result of the plugin's [`ABI_VERSION_EXPORT`](crate::abi::ABI_VERSION_EXPORT).
+    fn abi_version(&mut self) -> Result<u32, PluginError>;
+
+    /// Pass the encoded request to the plugin's [`CALL_EXPORT`](crate::abi::CALL_EXPORT),
+    /// and return its encoded response.
+    fn call(&mut self, request: &[u8]) -> Result<Vec<u8>, PluginError>;
+}
+
+/// An adapter running in the host's own process, answering requests as a plugin would.
+///
+/// Useful for testing plugins without compiling them to WebAssembly,
+/// and for serving trusted adapters alongside plugins through the same [`PluginAdapter`].
+#[derive(Debug)]
+pub struct LocalPlugin<A: BasicAdapter<'static>> {
+    guest: PluginGuest<A>,
+}
+
+impl<A: BasicAdapter<'static>> LocalPlugin<A> {
+    pub fn new(guest: PluginGuest<A>) -> Self {
+        Self { guest }
+    }
+
+    pub fn guest(&self) -> &PluginGuest<A> {
+        &self.guest
+    }
+}
+
+impl<A: BasicAdapter<'static>> PluginInstance for LocalPlugin<A> {
+    fn abi_version(&mut self) -> Result<u32, PluginError> {
+        Ok(ABI_VERSION)
+    }
+
+    fn call(&mut self, request: &[u8]) -> Result<Vec<u8>, PluginError> {
+        Ok(self.guest.dispatch(request))
+    }
+}
+
+/// A vertex held by the plugin. Once the host drops every clone of it,
+/// the plugin is told to drop it with the next call.
+#[derive(Debug, Clone)]
+pub struct PluginVertex(Rc<VertexHandle>);
+
+impl PluginVertex {
+    /// The plugin's handle for this vertex.
+    pub fn handle(&self) -> Handle {
+        self.0.handle
+    }
+}
+
+#[derive(Debug)]
+struct VertexHandle {
+    handle: Handle,
+    released: Rc<Released>,
+}
+
+impl Drop for VertexHandle {
+    fn drop(&mut self) {
+        self.released.vertices.borrow_mut().push(self.handle);
+    }
+}
+
+/// Handles dropped by the host since the last call, to release with the next one.
+///
+/// Kept apart from the instance, since vertices are dropped while it may be in use.
+#[derive(Debug, Default)]
+struct Released {
+    vertices: RefCell<Vec<Handle>>,
+    iterators: RefCell<Vec<Handle>>,
+}
+
+#[derive(Debug)]
+struct Connection<I> {
+    instance: RefCell<I>,
+    released: Rc<Released>,
+}
+
+impl<I: PluginInstance> Connection<I> {
+    fn send(&self, request: Request) -> Result<Response, PluginError> {
+        let message = RequestMessage {
+            release_vertices: self.released.vertices.take(),
+            release_iterators: self.released.iterators.take(),
+            request,
+        };
+        let request = serde_json::to_vec(&message).expect("failed to serialize request");
+        let response = self.instance.borrow_mut().call(&request)?;
+        match serde_json::from_slice(&response) {
+            Ok(Response::Error { message }) => Err(PluginError::PluginError(message)),
+            Ok(response) => Ok(response),
+            Err(e) => Err(PluginError::InvalidResponse(e.to_string())),
+        }
+    }
+
+    /// Send the request, panicking if the plugin doesn't answer it, since adapters
+    /// have no way to return errors.
+    fn request(&self, request: Request) -> Response {
+        self.send(request).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn vertices(self: &Rc<Self>, handles: Vec<Handle>) -> Vec<PluginVertex> {
+        handles
+            .into_iter()
+            .map(|handle| {
+                PluginVertex(Rc::new(VertexHandle {
+                    handle,
+                    released: self.released.clone(),
+                }))
+            })
+            .collect()
+    }
+}
+
+fn unexpected(response: Response) -> ! {
+    panic!(
+        "{}",
+        PluginError::InvalidResponse(format!("unexpected response {response:?}"))
+    )
+}
+
+fn expect_len<T>(results: Vec<T>, expected: usize) -> Vec<T> {
+    if results.len() != expected {
+        panic!(
+            "{}",
+            PluginError::InvalidResponse(format!(
+                "{} results for {expected} vertices",
+                results.len()
+            ))
+        );
+    }
+    results
+}
+
+/// An adapter whose vertices are resolved by a plugin.
+///
+/// Vertices are sent to the plugin in batches, so resolving a property, neighbors,
+/// or coercion of many vertices takes one call per batch instead of one per vertex.
+/// Each starting edge's vertices are fetched a batch at a time as the query needs them,
+/// while each vertex's neighbors are fetched all at once.
+///
+/// # Panics
+///
+/// Trustfall adapters can't return errors, so resolving vertices panics
+/// if a call into the plugin fails or the plugin can't answer it.
+#[derive(Debug)]
+pub struct PluginAdapter<I> {
+    connection: Rc<Connection<I>>,
+    schema: Schema,
+    batch_size: usize,
+}
+
+impl<I: PluginInstance> PluginAdapter<I> {
+    /// Load the plugin's schema, after checking that it implements this crate's ABI version.
+    pub fn new(mut instance: I) -> Result<Self, PluginError> {
+        let found = instance.abi_version()?;
+        if found != ABI_VERSION {
+            return Err(PluginError::UnsupportedAbiVersion {
+                found,
+                expected: ABI_VERSION,
+            });
+        }
+
+        let connection = Rc::new(Connection {
+            instance: RefCell::new(instance),
+            released: Default::default(),
+        });
+        let schema = match connection.send(Request::Schema)? {
+            Response::Schema { schema } => Schema::parse(schema)?,
+            response => {
+                return Err(PluginError::InvalidResponse(format!(
+                    "unexpected response {response:?}"
+                )))
+            }
+        };
+        Ok(Self {
+            connection,
+            schema,
+            batch_size: DEFAULT_BATCH_SIZE,
+        })
+    }
+
+    /// Send up to this many vertices to the plugin in each call.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "batch size must be positive");
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// The schema of the plugin's adapter, to parse queries against.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Run a closure with the plugin instance, for example to inspect the runtime's state.
+    pub fn with_instance<R>(&self, f: impl FnOnce(&mut I) -> R) -> R {
+        f(&mut self.connection.instance.borrow_mut())
+    }
+
+    fn resolve_batched<'vertex, T: 'vertex>(
+        &self,
+        contexts: ContextIterator<'vertex, PluginVertex>,
+        missing: impl Fn() -> T + 'vertex,
+        mut resolve: impl FnMut(&Rc<Connection<I>>, Vec<Handle>) -> Vec<T> + 'vertex,
+    ) -> ContextOutcomeIterator<'vertex, PluginVertex, T>
+    where
+        I: 'vertex,
+    {
+        let connection = self.connection.clone();
+        let batch_size = self.batch_size;
+        let mut contexts = contexts;
+        let batches = std::iter::from_fn(move || {
+            let batch: Vec<DataContext<PluginVertex>> =
+                contexts.by_ref().take(batch_size).collect();
+            if batch.is_empty() {
+                return None;
+            }
+
+            // Contexts without an active vertex aren't sent to the plugin.
+            let handles: Vec<Handle> = batch
+                .iter()
+                .filter_map(|ctx| ctx.active_vertex().map(PluginVertex::handle))
+                .collect();
+            let mut results = if handles.is_empty() {
+                vec![]
+            } else {
+                let expected = handles.len();
+                expect_len(resolve(&connection, handles), expected)
+            }
+            .into_iter();
+
+            let outcomes: Vec<_> = batch
+                .into_iter()
+                .map(|ctx| {
+                    let result = match ctx.active_vertex() {
+                        Some(_) => results.next().expect("result for vertex"),
+                        None => missing(),
+                    };
+                    (ctx, result)
+                })
+                .collect();
+            Some(outcomes)
+        });
+        Box::new(batches.flatten())
+    }
+}
+
+/// The vertices of a starting edge, fetched from the plugin a batch at a time.
+struct StartingVertices<I> {
+    connection: Rc<Connection<I>>,
+    iterator: Option<Handle>,
+    batch_size: usize,
+    buffer: std::vec::IntoIter<PluginVertex>,
+}
+
+impl<I: PluginInstance> Iterator for StartingVertices<I> {
+    type Item = PluginVertex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(vertex) = self.buffer.next() {
+                return Some(vertex);
+            }
+            let iterator = self.iterator?;
+            let request = Request::NextVertices {
+                iterator,
+                max: self.batch_size,
+            };
+            match self.connection.request(request) {
+                Response::Vertices { vertices, done } => {
+                    if done {
+                        self.iterator = None;
+                    }
+                    self.buffer = self.connection.vertices(vertices).into_iter();
+                }
+                response => unexpected(response),
+            }
+        }
+    }
+}
+
+impl<I> Drop for StartingVertices<I> {
+    fn drop(&mut self) {
+        if let Some(iterator) = self.iterator {
+            self.connection
+                .released
+                .iterators
+                .borrow_mut()
+                .push(iterator);
+        }
+    }
+}
+
+impl<'vertex, I: PluginInstance + 'vertex> Adapter<'vertex> for PluginAdapter<I> {
+    type Vertex = PluginVertex;
+
+    fn resolve_starting_vertices(
+        &self,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        _resolve_info: &ResolveInfo,
+    ) -> VertexIterator<'vertex, Self::Vertex> {
+        let request = Request::ResolveStartingVertices {
+            edge_name: edge_name.to_string(),
+            parameters: parameters.clone(),
+        };
+        let iterator = match self.connection.request(request) {
+            Response::Iterator { iterator } => iterator,
+            response => unexpected(response),
+        };
+        Box::new(StartingVertices {
+            connection: self.connection.clone(),
+            iterator: Some(iterator),
+            batch_size: self.batch_size,
+            buffer: vec![].into_iter(),
+        })
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        type_name: &Arc<str>,
+        property_name: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, FieldValue> {
+        let type_name = type_name.to_string();
+        let property_name = property_name.to_string();
+        self.resolve_batched(
+            contexts,
+            || FieldValue::Null,
+            move |connection, vertices| {
+                let request = Request::ResolveProperty {
+                    type_name: type_name.clone(),
+                    property_name: property_name.clone(),
+                    vertices,
+                };
+                match connection.request(request) {
+                    Response::Values { values } => values,
+                    response => unexpected(response),
+                }
+            },
+        )
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        type_name: &Arc<str>,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        _resolve_info: &ResolveEdgeInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, VertexIterator<'vertex, Self::Vertex>> {
+        let type_name = type_name.to_string();
+        let edge_name = edge_name.to_string();
+        let parameters = parameters.clone();
+        self.resolve_batched(
+            contexts,
+            || -> VertexIterator<'vertex, PluginVertex> { Box::new(std::iter::empty()) },
+            move |connection, vertices| {
+                let request = Request::ResolveNeighbors {
+                    type_name: type_name.clone(),
+                    edge_name: edge_name.clone(),
+                    parameters: parameters.clone(),
+                    vertices,
+                };
+                match connection.request(request) {
+                    Response::Neighbors { neighbors } => neighbors
+                        .into_iter()
+                        .map(|handles| -> VertexIterator<'vertex, PluginVertex> {
+                            Box::new(connection.vertices(handles).into_iter())
+                        })
+                        .collect(),
+                    response => unexpected(response),
+                }
+            },
+        )
+    }
+
+    fn resolve_coercion(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        type_name: &Arc<str>,
+        coerce_to_type: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, bool> {
+        let type_name = type_name.to_string();
+        let coerce_to_type = coerce_to_type.to_string();
+        self.resolve_batched(
+            contexts,
+            || false,
+            move |connection, vertices| {
+                let request = Request::ResolveCoercion {
+                    type_name: type_name.clone(),
+                    coerce_to_type: coerce_to_type.clone(),
+                    vertices,
+                };
+                match connection.request(request) {
+                    Response::Coercions { coercions } => coercions,
+                    response => unexpected(response),
+                }
+            },
+        )
+    }
+}
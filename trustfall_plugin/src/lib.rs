@@ -0,0 +1,95 @@
+//! Load trustfall adapters as WebAssembly plugins, and write adapters that can be loaded so.
+//!
+//! Plugins run in the host's WebAssembly runtime of choice, sandboxed from the host
+//! and from each other, so hosts can run adapters they don't trust and add adapters
+//! without being recompiled. The [`abi`] module describes the interface between them,
+//! which is versioned so that hosts can reject plugins built for a different version.
+//!
+//! In the plugin, wrap a [`BasicAdapter`](trustfall_core::interpreter::basic_adapter::BasicAdapter)
+//! in a [`PluginGuest`] and export it with [`export_adapter!`]. In the host, implement
+//! [`PluginInstance`] for the runtime's instances of a plugin, or use the implementation for
+//! wasmtime in the `trustfall_plugin_wasmtime` crate, and query them through
+//! a [`PluginAdapter`] like any other adapter:
+//!
+//! ```
+//! # use std::{collections::BTreeMap, sync::Arc};
+//! # use trustfall_core::{
+//! #     frontend::parse,
+//! #     interpreter::{
+//! #         basic_adapter::BasicAdapter, execution::interpret_ir, helpers::resolve_property_with,
+//! #         ContextIterator, ContextOutcomeIterator, Typename, VertexIterator,
+//! #     },
+//! #     ir::{EdgeParameters, FieldValue},
+//! # };
+//! use trustfall_plugin::{LocalPlugin, PluginAdapter, PluginGuest};
+//!
+//! # #[derive(Debug, Clone)]
+//! # struct Number(i64);
+//! # impl Typename for Number {
+//! #     fn typename(&self) -> &'static str { "Number" }
+//! # }
+//! # struct NumbersAdapter;
+//! # impl BasicAdapter<'static> for NumbersAdapter {
+//! #     type Vertex = Number;
+//! #     fn resolve_starting_vertices(
+//! #         &self, _: &str, parameters: &EdgeParameters,
+//! #     ) -> VertexIterator<'static, Number> {
+//! #         Box::new((0..parameters["max"].as_i64().unwrap()).map(Number))
+//! #     }
+//! #     fn resolve_property(
+//! #         &self, contexts: ContextIterator<'static, Number>, _: &str, _: &str,
+//! #     ) -> ContextOutcomeIterator<'static, Number, FieldValue> {
+//! #         resolve_property_with(contexts, |n| n.0.into())
+//! #     }
+//! #     fn resolve_neighbors(
+//! #         &self, _: ContextIterator<'static, Number>, _: &str, _: &str, _: &EdgeParameters,
+//! #     ) -> ContextOutcomeIterator<'static, Number, VertexIterator<'static, Number>> {
+//! #         unreachable!()
+//! #     }
+//! #     fn resolve_coercion(
+//! #         &self, _: ContextIterator<'static, Number>, _: &str, _: &str,
+//! #     ) -> ContextOutcomeIterator<'static, Number, bool> {
+//! #         unreachable!()
+//! #     }
+//! # }
+//! let schema = r#"
+//! schema {
+//!     query: RootSchemaQuery
+//! }
+//! directive @filter(op: String!, value: [String!]) on FIELD | INLINE_FRAGMENT
+//! directive @tag(name: String) on FIELD
+//! directive @output(name: String) on FIELD
+//! directive @optional on FIELD
+//! directive @recurse(depth: Int!) on FIELD
+//! directive @fold on FIELD
+//! directive @transform(op: String!) on FIELD
+//!
+//! type RootSchemaQuery {
+//!     Number(max: Int!): [Number!]!
+//! }
+//! type Number {
+//!     value: Int!
+//! }
+//! "#;
+//!
+//! // A WebAssembly runtime's instance of a plugin would take the place of `LocalPlugin`.
+//! let plugin = LocalPlugin::new(PluginGuest::new(schema, NumbersAdapter));
+//! let adapter = Arc::new(PluginAdapter::new(plugin).unwrap());
+//!
+//! let query = parse(adapter.schema(), "{ Number(max: 3) { value @output } }").unwrap();
+//! let values: Vec<_> = interpret_ir(adapter, query, Arc::new(BTreeMap::new()))
+//!     .unwrap()
+//!     .map(|row| row["value"].clone())
+//!     .collect();
+//! assert_eq!(vec![FieldValue::Int64(0), 1.into(), 2.into()], values);
+//! ```
+#![allow(clippy::result_large_err)]
+
+pub mod abi;
+pub mod guest;
+mod host;
+
+pub use guest::PluginGuest;
+pub use host::{
+    LocalPlugin, PluginAdapter, PluginError, PluginInstance, PluginVertex, DEFAULT_BATCH_SIZE,
+};
@@ -0,0 +1,311 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use trustfall_core::{
+    frontend::parse,
+    interpreter::{
+        basic_adapter::BasicAdapter,
+        execution::interpret_ir,
+        helpers::{resolve_coercion_with, resolve_neighbors_with, resolve_property_with},
+        ContextIterator, ContextOutcomeIterator, Typename, VertexIterator,
+    },
+    ir::{EdgeParameters, FieldValue},
+};
+use trustfall_plugin::{
+    abi::{Request, RequestMessage, Response, ABI_VERSION},
+    guest::{self, Dispatch},
+    LocalPlugin, PluginAdapter, PluginError, PluginGuest, PluginInstance,
+};
+
+const SCHEMA: &str = r#"
+schema {
+    query: RootSchemaQuery
+}
+directive @filter(op: String!, value: [String!]) on FIELD | INLINE_FRAGMENT
+directive @tag(name: String) on FIELD
+directive @output(name: String) on FIELD
+directive @optional on FIELD
+directive @recurse(depth: Int!) on FIELD
+directive @fold on FIELD
+directive @transform(op: String!) on FIELD
+
+type RootSchemaQuery {
+    Number(max: Int!): [Number!]!
+}
+
+interface Number {
+    value: Int!
+    successor: Number!
+}
+
+type Prime implements Number {
+    value: Int!
+    successor: Number!
+}
+
+type Composite implements Number {
+    value: Int!
+    successor: Number!
+    divisors: [Number!]!
+}
+"#;
+
+#[derive(Debug, Clone)]
+struct Number(i64);
+
+impl Number {
+    fn is_prime(&self) -> bool {
+        self.0 >= 2 && (2..self.0).all(|d| self.0 % d != 0)
+    }
+}
+
+impl Typename for Number {
+    fn typename(&self) -> &'static str {
+        if self.is_prime() {
+            "Prime"
+        } else {
+            "Composite"
+        }
+    }
+}
+
+struct NumbersAdapter;
+
+impl BasicAdapter<'static> for NumbersAdapter {
+    type Vertex = Number;
+
+    fn resolve_starting_vertices(
+        &self,
+        _edge_name: &str,
+        parameters: &EdgeParameters,
+    ) -> VertexIterator<'static, Self::Vertex> {
+        let max = parameters["max"].as_i64().unwrap();
+        Box::new((0..max).map(Number))
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'static, Self::Vertex>,
+        _type_name: &str,
+        property_name: &str,
+    ) -> ContextOutcomeIterator<'static, Self::Vertex, FieldValue> {
+        match property_name {
+            "value" => resolve_property_with(contexts, |number| number.0.into()),
+            _ => unreachable!("{property_name}"),
+        }
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: ContextIterator<'static, Self::Vertex>,
+        _type_name: &str,
+        edge_name: &str,
+        _parameters: &EdgeParameters,
+    ) -> ContextOutcomeIterator<'static, Self::Vertex, VertexIterator<'static, Self::Vertex>> {
+        match edge_name {
+            "successor" => resolve_neighbors_with(contexts, |number| {
+                Box::new(std::iter::once(Number(number.0 + 1)))
+            }),
+            "divisors" => resolve_neighbors_with(contexts, |number| {
+                let n = number.0;
+                Box::new((2..n).filter(move |d| n % d == 0).map(Number))
+            }),
+            _ => unreachable!("{edge_name}"),
+        }
+    }
+
+    fn resolve_coercion(
+        &self,
+        contexts: ContextIterator<'static, Self::Vertex>,
+        _type_name: &str,
+        coerce_to_type: &str,
+    ) -> ContextOutcomeIterator<'static, Self::Vertex, bool> {
+        let coerce_to_type = coerce_to_type.to_string();
+        resolve_coercion_with(contexts, move |number| number.typename() == coerce_to_type)
+    }
+}
+
+/// Counts the calls into the plugin it wraps.
+struct Counting<I> {
+    inner: I,
+    calls: usize,
+}
+
+impl<I: PluginInstance> PluginInstance for Counting<I> {
+    fn abi_version(&mut self) -> Result<u32, PluginError> {
+        self.inner.abi_version()
+    }
+
+    fn call(&mut self, request: &[u8]) -> Result<Vec<u8>, PluginError> {
+        self.calls += 1;
+        self.inner.call(request)
+    }
+}
+
+type Row = BTreeMap<Arc<str>, FieldValue>;
+
+const QUERY: &str = r#"
+{
+    Number(max: 20) {
+        value @output
+        __typename @output
+
+        successor {
+            ... on Composite {
+                next: value @output
+
+                divisors @fold {
+                    divisors: value @output
+                }
+            }
+        }
+    }
+}"#;
+
+fn local_plugin() -> Counting<LocalPlugin<NumbersAdapter>> {
+    Counting {
+        inner: LocalPlugin::new(PluginGuest::new(SCHEMA, NumbersAdapter)),
+        calls: 0,
+    }
+}
+
+fn run_in_process(query: &str) -> Vec<Row> {
+    let schema = trustfall_core::schema::Schema::parse(SCHEMA).unwrap();
+    let query = parse(&schema, query).unwrap();
+    interpret_ir(Arc::new(NumbersAdapter), query, Arc::new(BTreeMap::new()))
+        .unwrap()
+        .collect()
+}
+
+#[test]
+fn plugin_results_match_in_process_adapter() {
+    let expected = run_in_process(QUERY);
+    assert_eq!(12, expected.len());
+
+    for batch_size in [1, 3, 1000] {
+        let adapter = Arc::new(
+            PluginAdapter::new(local_plugin())
+                .unwrap()
+                .with_batch_size(batch_size),
+        );
+        let query = parse(adapter.schema(), QUERY).unwrap();
+        let rows: Vec<Row> = interpret_ir(adapter, query, Arc::new(BTreeMap::new()))
+            .unwrap()
+            .collect();
+        assert_eq!(expected, rows, "batch size {batch_size}");
+    }
+}
+
+#[test]
+fn vertices_are_resolved_in_batches() {
+    let adapter = Arc::new(PluginAdapter::new(local_plugin()).unwrap());
+    let query = r#"
+{
+    Number(max: 100) {
+        value @output
+
+        successor {
+            next: value @output
+        }
+    }
+}"#;
+    let query = parse(adapter.schema(), query).unwrap();
+    let rows = interpret_ir(adapter.clone(), query, Arc::new(BTreeMap::new()))
+        .unwrap()
+        .count();
+    assert_eq!(100, rows);
+
+    // Loading the schema, starting the iterator and taking its vertices,
+    // then one call each for `value`, `successor`, and the successor's `value`.
+    let calls = adapter.with_instance(|instance| instance.calls);
+    assert_eq!(6, calls);
+}
+
+#[test]
+fn dropped_vertices_are_released() {
+    let adapter = Arc::new(PluginAdapter::new(local_plugin()).unwrap());
+    let query = parse(adapter.schema(), "{ Number(max: 5) { value @output } }").unwrap();
+    let rows = interpret_ir(adapter.clone(), query, Arc::new(BTreeMap::new()))
+        .unwrap()
+        .count();
+    assert_eq!(5, rows);
+
+    // Released handles are sent along with the next call.
+    let query = parse(adapter.schema(), "{ Number(max: 1) { value @output } }").unwrap();
+    let mut results = interpret_ir(adapter.clone(), query, Arc::new(BTreeMap::new())).unwrap();
+    results.next().unwrap();
+    let live = adapter.with_instance(|instance| instance.inner.guest().live_vertices());
+    assert_eq!(1, live);
+}
+
+#[test]
+fn rejects_other_abi_versions() {
+    struct FuturePlugin;
+
+    impl PluginInstance for FuturePlugin {
+        fn abi_version(&mut self) -> Result<u32, PluginError> {
+            Ok(ABI_VERSION + 1)
+        }
+
+        fn call(&mut self, _request: &[u8]) -> Result<Vec<u8>, PluginError> {
+            unreachable!("plugins with other ABI versions aren't called")
+        }
+    }
+
+    assert!(matches!(
+        PluginAdapter::new(FuturePlugin),
+        Err(PluginError::UnsupportedAbiVersion { found, expected })
+            if found == ABI_VERSION + 1 && expected == ABI_VERSION
+    ));
+}
+
+#[test]
+fn guest_reports_invalid_requests() {
+    let mut guest = PluginGuest::new(SCHEMA, NumbersAdapter);
+
+    let response = guest.handle(RequestMessage {
+        release_vertices: vec![],
+        release_iterators: vec![],
+        request: Request::ResolveProperty {
+            type_name: "Number".to_string(),
+            property_name: "value".to_string(),
+            vertices: vec![42],
+        },
+    });
+    assert_eq!(
+        Response::Error {
+            message: "unknown vertex handle 42".to_string()
+        },
+        response
+    );
+
+    let response: Response = serde_json::from_slice(&guest.dispatch(b"{}")).unwrap();
+    assert!(
+        matches!(&response, Response::Error { message } if message.starts_with("invalid request")),
+        "{response:?}"
+    );
+}
+
+#[test]
+fn exported_functions_pass_messages_through_guest_memory() {
+    let mut guest = PluginGuest::new(SCHEMA, NumbersAdapter);
+    let request = br#"{"kind": "schema"}"#;
+
+    let ptr = guest::alloc(request.len());
+    // SAFETY: `ptr` is a fresh allocation of `request.len()` bytes, which `call` takes over,
+    // and the response is freed exactly once after being read.
+    let response = unsafe {
+        std::ptr::copy_nonoverlapping(request.as_ptr(), ptr, request.len());
+        let (response_ptr, response_len) = guest::call(&mut guest, ptr, request.len());
+        let response = std::slice::from_raw_parts(response_ptr, response_len).to_vec();
+        guest::dealloc(response_ptr, response_len);
+        response
+    };
+
+    let response: Response = serde_json::from_slice(&response).unwrap();
+    assert_eq!(
+        Response::Schema {
+            schema: SCHEMA.to_string()
+        },
+        response
+    );
+}
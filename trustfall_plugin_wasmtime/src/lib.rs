@@ -0,0 +1,138 @@
+//! Load trustfall adapter plugins with the [wasmtime](https://wasmtime.dev) runtime.
+//!
+//! A [`WasmtimePlugin`] is an instance of a plugin's module, which calls the module's exports
+//! as described in [`trustfall_plugin::abi`]. Query it through a
+//! [`PluginAdapter`](trustfall_plugin::PluginAdapter) like any other adapter:
+//!
+//! ```no_run
+//! # use std::{collections::BTreeMap, sync::Arc};
+//! # use trustfall_core::{frontend::parse, interpreter::execution::interpret_ir};
+//! use trustfall_plugin::PluginAdapter;
+//! use trustfall_plugin_wasmtime::WasmtimePlugin;
+//!
+//! let plugin = WasmtimePlugin::from_file("numbers_plugin.wasm").unwrap();
+//! let adapter = Arc::new(PluginAdapter::new(plugin).unwrap());
+//!
+//! let query = parse(adapter.schema(), "{ Number(max: 3) { value @output } }").unwrap();
+//! for row in interpret_ir(adapter, query, Arc::new(BTreeMap::new())).unwrap() {
+//!     println!("{row:?}");
+//! }
+//! ```
+use std::path::Path;
+
+use trustfall_plugin::{
+    abi::{ABI_VERSION_EXPORT, ALLOC_EXPORT, CALL_EXPORT, DEALLOC_EXPORT},
+    PluginError, PluginInstance,
+};
+use wasmtime::{Engine, Instance, Memory, Module, Store, TypedFunc};
+
+/// The name of the memory that plugins export, which the ABI's pointers point into.
+const MEMORY_EXPORT: &str = "memory";
+
+/// An instance of a plugin's module, sandboxed by wasmtime.
+///
+/// Plugins don't import anything, so the instance isn't given any imports:
+/// a module that imports something fails to load.
+pub struct WasmtimePlugin {
+    store: Store<()>,
+    memory: Memory,
+    abi_version: TypedFunc<(), u32>,
+    alloc: TypedFunc<u32, u32>,
+    dealloc: TypedFunc<(u32, u32), ()>,
+    call: TypedFunc<(u32, u32), u64>,
+}
+
+impl std::fmt::Debug for WasmtimePlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WasmtimePlugin")
+            .field("memory_size", &self.memory.data_size(&self.store))
+            .finish_non_exhaustive()
+    }
+}
+
+fn load_failed(error: impl std::fmt::Display) -> PluginError {
+    PluginError::LoadFailed(format!("{error:#}"))
+}
+
+fn call_failed(error: impl std::fmt::Display) -> PluginError {
+    PluginError::CallFailed(format!("{error:#}"))
+}
+
+impl WasmtimePlugin {
+    /// Compile the module and instantiate it, with wasmtime's default configuration.
+    pub fn new(module: &[u8]) -> Result<Self, PluginError> {
+        let engine = Engine::default();
+        let module = Module::new(&engine, module).map_err(load_failed)?;
+        Self::instantiate(&engine, &module)
+    }
+
+    /// Read the module from the file, then compile and instantiate it like [`WasmtimePlugin::new`].
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, PluginError> {
+        let module = std::fs::read(path.as_ref()).map_err(|e| {
+            PluginError::LoadFailed(format!("failed to read {}: {e}", path.as_ref().display()))
+        })?;
+        Self::new(&module)
+    }
+
+    /// Instantiate a module that's already compiled, such as to run several instances of it.
+    pub fn instantiate(engine: &Engine, module: &Module) -> Result<Self, PluginError> {
+        let mut store = Store::new(engine, ());
+        let instance = Instance::new(&mut store, module, &[]).map_err(load_failed)?;
+        let memory = instance
+            .get_memory(&mut store, MEMORY_EXPORT)
+            .ok_or_else(|| load_failed(format!("the module doesn't export `{MEMORY_EXPORT}`")))?;
+        Ok(Self {
+            abi_version: instance
+                .get_typed_func(&mut store, ABI_VERSION_EXPORT)
+                .map_err(load_failed)?,
+            alloc: instance
+                .get_typed_func(&mut store, ALLOC_EXPORT)
+                .map_err(load_failed)?,
+            dealloc: instance
+                .get_typed_func(&mut store, DEALLOC_EXPORT)
+                .map_err(load_failed)?,
+            call: instance
+                .get_typed_func(&mut store, CALL_EXPORT)
+                .map_err(load_failed)?,
+            memory,
+            store,
+        })
+    }
+
+    /// The size of the plugin's memory, in bytes.
+    pub fn memory_size(&self) -> usize {
+        self.memory.data_size(&self.store)
+    }
+}
+
+impl PluginInstance for WasmtimePlugin {
+    fn abi_version(&mut self) -> Result<u32, PluginError> {
+        self.abi_version
+            .call(&mut self.store, ())
+            .map_err(call_failed)
+    }
+
+    fn call(&mut self, request: &[u8]) -> Result<Vec<u8>, PluginError> {
+        let len = u32::try_from(request.len())
+            .map_err(|_| call_failed("the request doesn't fit in the plugin's memory"))?;
+        let ptr = self.alloc.call(&mut self.store, len).map_err(call_failed)?;
+        self.memory
+            .write(&mut self.store, ptr as usize, request)
+            .map_err(call_failed)?;
+
+        // The plugin takes ownership of the request, and returns a buffer for the host to free.
+        let packed = self
+            .call
+            .call(&mut self.store, (ptr, len))
+            .map_err(call_failed)?;
+        let (ptr, len) = ((packed >> 32) as u32, packed as u32);
+        let mut response = vec![0; len as usize];
+        self.memory
+            .read(&self.store, ptr as usize, &mut response)
+            .map_err(call_failed)?;
+        self.dealloc
+            .call(&mut self.store, (ptr, len))
+            .map_err(call_failed)?;
+        Ok(response)
+    }
+}
@@ -0,0 +1,94 @@
+//! A plugin for the tests to load: the numbers adapter from `trustfall_plugin`'s tests,
+//! built for `wasm32-unknown-unknown`.
+use trustfall_core::{
+    interpreter::{
+        basic_adapter::BasicAdapter,
+        helpers::{resolve_coercion_with, resolve_neighbors_with, resolve_property_with},
+        ContextIterator, ContextOutcomeIterator, Typename, VertexIterator,
+    },
+    ir::{EdgeParameters, FieldValue},
+};
+use trustfall_plugin::PluginGuest;
+
+#[derive(Debug, Clone)]
+pub struct Number(i64);
+
+impl Number {
+    fn is_prime(&self) -> bool {
+        self.0 >= 2 && (2..self.0).all(|d| self.0 % d != 0)
+    }
+}
+
+impl Typename for Number {
+    fn typename(&self) -> &'static str {
+        if self.is_prime() {
+            "Prime"
+        } else {
+            "Composite"
+        }
+    }
+}
+
+pub struct NumbersAdapter;
+
+impl BasicAdapter<'static> for NumbersAdapter {
+    type Vertex = Number;
+
+    fn resolve_starting_vertices(
+        &self,
+        _edge_name: &str,
+        parameters: &EdgeParameters,
+    ) -> VertexIterator<'static, Self::Vertex> {
+        let max = parameters["max"].as_i64().unwrap();
+        Box::new((0..max).map(Number))
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'static, Self::Vertex>,
+        _type_name: &str,
+        property_name: &str,
+    ) -> ContextOutcomeIterator<'static, Self::Vertex, FieldValue> {
+        match property_name {
+            "value" => resolve_property_with(contexts, |number| number.0.into()),
+            _ => unreachable!("{property_name}"),
+        }
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: ContextIterator<'static, Self::Vertex>,
+        _type_name: &str,
+        edge_name: &str,
+        _parameters: &EdgeParameters,
+    ) -> ContextOutcomeIterator<'static, Self::Vertex, VertexIterator<'static, Self::Vertex>> {
+        match edge_name {
+            "successor" => resolve_neighbors_with(contexts, |number| {
+                Box::new(std::iter::once(Number(number.0 + 1)))
+            }),
+            "divisors" => resolve_neighbors_with(contexts, |number| {
+                let n = number.0;
+                Box::new((2..n).filter(move |d| n % d == 0).map(Number))
+            }),
+            _ => unreachable!("{edge_name}"),
+        }
+    }
+
+    fn resolve_coercion(
+        &self,
+        contexts: ContextIterator<'static, Self::Vertex>,
+        _type_name: &str,
+        coerce_to_type: &str,
+    ) -> ContextOutcomeIterator<'static, Self::Vertex, bool> {
+        let coerce_to_type = coerce_to_type.to_string();
+        resolve_coercion_with(contexts, move |number| number.typename() == coerce_to_type)
+    }
+}
+
+// Only called by the exports, which are only compiled for `wasm32`.
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+fn guest() -> PluginGuest<NumbersAdapter> {
+    PluginGuest::new(include_str!("../schema.graphql"), NumbersAdapter)
+}
+
+trustfall_plugin::export_adapter!(guest);
@@ -0,0 +1,123 @@
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    process::Command,
+    sync::{Arc, OnceLock},
+};
+
+use trustfall_core::{frontend::parse, interpreter::execution::interpret_ir, ir::FieldValue};
+use trustfall_plugin::{
+    abi::{Request, RequestMessage},
+    PluginAdapter, PluginError, PluginInstance,
+};
+use trustfall_plugin_wasmtime::WasmtimePlugin;
+
+type Row = BTreeMap<Arc<str>, FieldValue>;
+
+/// Build the plugin in `tests/fixtures/numbers_plugin` once, and return its module.
+fn plugin_module() -> &'static [u8] {
+    static MODULE: OnceLock<Vec<u8>> = OnceLock::new();
+    MODULE.get_or_init(|| {
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let target_dir = manifest_dir.join("target").join("fixtures");
+        let status = Command::new(env!("CARGO"))
+            .args(["build", "--release", "--target", "wasm32-unknown-unknown"])
+            .arg("--manifest-path")
+            .arg(manifest_dir.join("tests/fixtures/numbers_plugin/Cargo.toml"))
+            .arg("--target-dir")
+            .arg(&target_dir)
+            .status()
+            .unwrap();
+        assert!(
+            status.success(),
+            "failed to build the plugin; is the target installed? \
+             `rustup target add wasm32-unknown-unknown`"
+        );
+
+        let module = target_dir.join("wasm32-unknown-unknown/release/numbers_plugin.wasm");
+        std::fs::read(module).unwrap()
+    })
+}
+
+fn row(value: i64, typename: &str, next: i64, divisors: &[i64]) -> Row {
+    BTreeMap::from([
+        ("value".into(), value.into()),
+        ("__typename".into(), typename.into()),
+        ("next".into(), next.into()),
+        (
+            "divisors".into(),
+            FieldValue::List(divisors.iter().map(|&d| d.into()).collect()),
+        ),
+    ])
+}
+
+#[test]
+fn queries_are_answered_by_the_loaded_plugin() {
+    let query = r#"
+{
+    Number(max: 10) {
+        value @output
+        __typename @output
+
+        successor {
+            ... on Composite {
+                next: value @output
+
+                divisors @fold {
+                    divisors: value @output
+                }
+            }
+        }
+    }
+}"#;
+    let expected = vec![
+        row(0, "Composite", 1, &[]),
+        row(3, "Prime", 4, &[2]),
+        row(5, "Prime", 6, &[2, 3]),
+        row(7, "Prime", 8, &[2, 4]),
+        row(8, "Composite", 9, &[3]),
+        row(9, "Composite", 10, &[2, 5]),
+    ];
+
+    for batch_size in [1, 1000] {
+        let plugin = WasmtimePlugin::new(plugin_module()).unwrap();
+        let adapter = Arc::new(
+            PluginAdapter::new(plugin)
+                .unwrap()
+                .with_batch_size(batch_size),
+        );
+        let query = parse(adapter.schema(), query).unwrap();
+        let rows: Vec<Row> = interpret_ir(adapter, query, Arc::new(BTreeMap::new()))
+            .unwrap()
+            .collect();
+        assert_eq!(expected, rows, "batch size {batch_size}");
+    }
+}
+
+#[test]
+fn invalid_modules_fail_to_load() {
+    assert!(matches!(
+        WasmtimePlugin::new(b"not a module"),
+        Err(PluginError::LoadFailed(_))
+    ));
+}
+
+#[test]
+fn panics_in_the_plugin_fail_the_call() {
+    let mut plugin = WasmtimePlugin::new(plugin_module()).unwrap();
+
+    // The adapter panics without its `max` parameter, which traps in `wasm32`.
+    let request = RequestMessage {
+        release_vertices: vec![],
+        release_iterators: vec![],
+        request: Request::ResolveStartingVertices {
+            edge_name: "Number".to_string(),
+            parameters: Default::default(),
+        },
+    };
+    let response = plugin.call(&serde_json::to_vec(&request).unwrap());
+    assert!(
+        matches!(&response, Err(PluginError::CallFailed(_))),
+        "{response:?}"
+    );
+}
@@ -0,0 +1,216 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use polars::prelude::{
+    DataFrame, DataType, NamedFrom, PolarsError, PolarsResult, Series, TimeUnit,
+};
+use trustfall_core::ir::{FieldValue, Type};
+
+use crate::schema::{Column, OutputSchema};
+
+type Row = BTreeMap<Arc<str>, FieldValue>;
+
+/// Collects query results into a [`DataFrame`] with a given schema,
+/// usually derived with [`PolarsOptions::schema`](crate::PolarsOptions::schema).
+#[derive(Debug)]
+pub struct DataFrameBuilder {
+    schema: OutputSchema,
+    columns: Vec<Vec<FieldValue>>,
+    rows: usize,
+}
+
+impl DataFrameBuilder {
+    pub fn new(schema: OutputSchema) -> Self {
+        let columns = schema.columns.iter().map(|_| vec![]).collect();
+        Self {
+            schema,
+            columns,
+            rows: 0,
+        }
+    }
+
+    pub fn schema(&self) -> &OutputSchema {
+        &self.schema
+    }
+
+    /// The number of rows pushed since the builder was created or last finished.
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// Add a query result as the next row.
+    ///
+    /// Outputs the schema has no column for are ignored, and columns the result has
+    /// no output for are null.
+    pub fn push(&mut self, mut row: Row) {
+        for (column, values) in self.schema.columns.iter().zip(self.columns.iter_mut()) {
+            values.push(row.remove(column.name.as_str()).unwrap_or_default());
+        }
+        self.rows += 1;
+    }
+
+    /// Build a data frame from the rows pushed so far, and reset the builder.
+    ///
+    /// Fails if a value can't be represented in its column's dtype,
+    /// for example if an output whose type isn't nullable has a null value.
+    pub fn finish(&mut self) -> PolarsResult<DataFrame> {
+        self.rows = 0;
+        let series = self
+            .schema
+            .columns
+            .iter()
+            .zip(self.columns.iter_mut())
+            .map(|(column, values)| {
+                let values = std::mem::take(values);
+                build_column(column, &values).map_err(|e| match e {
+                    PolarsError::ComputeError(message) => PolarsError::ComputeError(
+                        format!("invalid value for output {}: {message}", column.name).into(),
+                    ),
+                    e => e,
+                })
+            })
+            .collect::<PolarsResult<Vec<_>>>()?;
+        DataFrame::new(series)
+    }
+}
+
+fn build_column(column: &Column, values: &[FieldValue]) -> PolarsResult<Series> {
+    build_series(&column.name, &column.value_type, &column.dtype, values)
+}
+
+fn unexpected(value: &FieldValue, dtype: &DataType) -> PolarsError {
+    PolarsError::ComputeError(format!("value {value:?} can't be represented as {dtype}").into())
+}
+
+/// Convert the values of one trustfall type into a series of the given dtype.
+///
+/// Integers are converted to floats, and between signed and unsigned types if they fit.
+/// Enums and timestamps are converted to strings; timestamps are converted to RFC 3339.
+/// Nulls are only allowed if the trustfall type is nullable, since Polars doesn't
+/// track nullability itself.
+fn build_series(
+    name: &str,
+    value_type: &Type,
+    dtype: &DataType,
+    values: &[FieldValue],
+) -> PolarsResult<Series> {
+    if !value_type.is_nullable() && values.iter().any(|value| matches!(value, FieldValue::Null)) {
+        return Err(PolarsError::ComputeError(
+            format!("null value for non-nullable type {value_type}").into(),
+        ));
+    }
+
+    let series = match dtype {
+        DataType::Int64 => Series::new(
+            name,
+            values
+                .iter()
+                .map(|value| match value {
+                    FieldValue::Null => Ok(None),
+                    FieldValue::Int64(v) => Ok(Some(*v)),
+                    FieldValue::Uint64(v) => i64::try_from(*v)
+                        .map(Some)
+                        .map_err(|_| unexpected(value, dtype)),
+                    _ => Err(unexpected(value, dtype)),
+                })
+                .collect::<PolarsResult<Vec<_>>>()?,
+        ),
+        DataType::UInt64 => Series::new(
+            name,
+            values
+                .iter()
+                .map(|value| match value {
+                    FieldValue::Null => Ok(None),
+                    FieldValue::Uint64(v) => Ok(Some(*v)),
+                    FieldValue::Int64(v) => u64::try_from(*v)
+                        .map(Some)
+                        .map_err(|_| unexpected(value, dtype)),
+                    _ => Err(unexpected(value, dtype)),
+                })
+                .collect::<PolarsResult<Vec<_>>>()?,
+        ),
+        DataType::Float64 => Series::new(
+            name,
+            values
+                .iter()
+                .map(|value| match value {
+                    FieldValue::Null => Ok(None),
+                    FieldValue::Float64(v) => Ok(Some(*v)),
+                    FieldValue::Int64(v) => Ok(Some(*v as f64)),
+                    FieldValue::Uint64(v) => Ok(Some(*v as f64)),
+                    _ => Err(unexpected(value, dtype)),
+                })
+                .collect::<PolarsResult<Vec<_>>>()?,
+        ),
+        DataType::Boolean => Series::new(
+            name,
+            values
+                .iter()
+                .map(|value| match value {
+                    FieldValue::Null => Ok(None),
+                    FieldValue::Boolean(v) => Ok(Some(*v)),
+                    _ => Err(unexpected(value, dtype)),
+                })
+                .collect::<PolarsResult<Vec<_>>>()?,
+        ),
+        DataType::Utf8 => Series::new(
+            name,
+            values
+                .iter()
+                .map(|value| match value {
+                    FieldValue::Null => Ok(None),
+                    FieldValue::String(v) | FieldValue::Enum(v) => Ok(Some(v.clone())),
+                    FieldValue::DateTimeUtc(v) => Ok(Some(v.to_rfc3339())),
+                    _ => Err(unexpected(value, dtype)),
+                })
+                .collect::<PolarsResult<Vec<_>>>()?,
+        ),
+        DataType::Datetime(unit, _) => Series::new(
+            name,
+            values
+                .iter()
+                .map(|value| match value {
+                    FieldValue::Null => Ok(None),
+                    FieldValue::DateTimeUtc(v) => match unit {
+                        TimeUnit::Milliseconds => Ok(Some(v.timestamp_millis())),
+                        TimeUnit::Microseconds => Ok(Some(v.timestamp_micros())),
+                        TimeUnit::Nanoseconds => v
+                            .timestamp_nanos_opt()
+                            .map(Some)
+                            .ok_or_else(|| unexpected(value, dtype)),
+                    },
+                    _ => Err(unexpected(value, dtype)),
+                })
+                .collect::<PolarsResult<Vec<Option<i64>>>>()?,
+        )
+        .cast(dtype)?,
+        DataType::List(item_dtype) => {
+            let Some(item_type) = value_type.element_type() else {
+                return Err(PolarsError::InvalidOperation(
+                    format!("type {value_type} is not a list, but its dtype is {dtype}").into(),
+                ));
+            };
+            let lists = values
+                .iter()
+                .map(|value| match value {
+                    FieldValue::Null => Ok(None),
+                    FieldValue::List(items) => {
+                        build_series("", &item_type, item_dtype, items).map(Some)
+                    }
+                    _ => Err(unexpected(value, dtype)),
+                })
+                .collect::<PolarsResult<Vec<_>>>()?;
+            // Without any non-empty lists, Polars can't infer the item dtype.
+            Series::new(name, lists).cast(dtype)?
+        }
+        _ => {
+            return Err(PolarsError::InvalidOperation(
+                format!("converting values to {dtype} is not supported").into(),
+            ))
+        }
+    };
+    Ok(series)
+}
@@ -0,0 +1,65 @@
+//! Collects trustfall query results into Polars [`DataFrame`]s with dtypes derived
+//! from the types of the query's outputs.
+//!
+//! Each output becomes a column of the same name, with outputs in `@fold` blocks
+//! becoming list columns and outputs in `@optional` blocks allowing nulls.
+//!
+//! ```rust
+//! # use std::{collections::BTreeMap, sync::Arc};
+//! # use trustfall_core::{
+//! #     frontend::parse, interpreter::execution::interpret_ir,
+//! #     numbers_interpreter::NumbersAdapter, schema::Schema,
+//! # };
+//! use polars::prelude::DataType;
+//! use trustfall_polars::{to_data_frame, PolarsOptions};
+//!
+//! # let schema = Schema::parse(include_str!("../../trustfall_core/test_data/schemas/numbers.graphql")).unwrap();
+//! let query = parse(&schema, r#"
+//! {
+//!     Number(max: 6) {
+//!         value @output
+//!
+//!         multiple(max: 3) @fold {
+//!             multiples: value @output
+//!         }
+//!     }
+//! }"#).unwrap();
+//! # let adapter = Arc::new(NumbersAdapter::new());
+//! let results = interpret_ir(adapter, query.clone(), Arc::new(BTreeMap::new())).unwrap();
+//!
+//! let df = to_data_frame(&query.outputs, results, &PolarsOptions::new()).unwrap();
+//! assert_eq!(
+//!     &DataType::List(Box::new(DataType::Int64)),
+//!     df.column("multiples").unwrap().dtype(),
+//! );
+//! ```
+use std::{collections::BTreeMap, sync::Arc};
+
+use polars::prelude::{DataFrame, PolarsResult};
+use trustfall_core::ir::{FieldValue, Output};
+
+mod frame;
+mod schema;
+
+pub use frame::DataFrameBuilder;
+pub use schema::{OutputSchema, PolarsOptions};
+
+/// Collect all of a query's results into a data frame, with one column per output
+/// in order of output name.
+///
+/// Fails if a value can't be represented in its column's dtype,
+/// for example if an output whose type isn't nullable has a null value.
+pub fn to_data_frame<I>(
+    outputs: &BTreeMap<Arc<str>, Output>,
+    results: I,
+    options: &PolarsOptions,
+) -> PolarsResult<DataFrame>
+where
+    I: IntoIterator<Item = BTreeMap<Arc<str>, FieldValue>>,
+{
+    let mut builder = DataFrameBuilder::new(options.schema(outputs)?);
+    for row in results {
+        builder.push(row);
+    }
+    builder.finish()
+}
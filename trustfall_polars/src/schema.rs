@@ -0,0 +1,112 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use polars::prelude::{DataType, Field, PolarsError, PolarsResult, Schema};
+use trustfall_core::ir::{Output, Type};
+
+/// Options for collecting query results into data frames.
+#[derive(Debug, Clone, Default)]
+pub struct PolarsOptions {
+    pub(crate) named_types: BTreeMap<String, DataType>,
+}
+
+impl PolarsOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Use the given dtype for values of the named schema type.
+    ///
+    /// This is needed for custom scalars whose values aren't strings, like timestamps
+    /// stored as `DateTimeUtc` values, which can be mapped to
+    /// `DataType::Datetime(TimeUnit::Microseconds, Some("UTC".into()))`.
+    /// It also overrides the dtypes of the built-in scalars.
+    pub fn with_named_type(mut self, type_name: impl Into<String>, dtype: DataType) -> Self {
+        self.named_types.insert(type_name.into(), dtype);
+        self
+    }
+
+    fn named_dtype(&self, name: &str) -> DataType {
+        if let Some(dtype) = self.named_types.get(name) {
+            return dtype.clone();
+        }
+        match name {
+            "Int" => DataType::Int64,
+            "Float" => DataType::Float64,
+            "Boolean" => DataType::Boolean,
+            // Strings and IDs, and also enums and custom scalars by default.
+            _ => DataType::Utf8,
+        }
+    }
+
+    /// The dtype of values of the given trustfall type.
+    ///
+    /// Polars columns and list items are always nullable, so the type's nullability
+    /// is checked when values are collected instead of being part of the dtype.
+    pub fn dtype(&self, value_type: &Type) -> DataType {
+        match value_type.element_type() {
+            Some(element) => DataType::List(Box::new(self.dtype(&element))),
+            None => self.named_dtype(value_type.base_type()),
+        }
+    }
+
+    /// The schema of the data frames holding a query's results:
+    /// one column per output, in order of output name.
+    ///
+    /// Fails if an output's dtype is one that values can't be converted to.
+    pub fn schema(&self, outputs: &BTreeMap<Arc<str>, Output>) -> PolarsResult<OutputSchema> {
+        let columns = outputs
+            .values()
+            .map(|output| {
+                let dtype = self.dtype(&output.value_type);
+                if !is_supported(&dtype) {
+                    return Err(PolarsError::InvalidOperation(
+                        format!("output {} can't be converted to {dtype}", output.name).into(),
+                    ));
+                }
+                Ok(Column {
+                    name: output.name.to_string(),
+                    value_type: output.value_type.clone(),
+                    dtype,
+                })
+            })
+            .collect::<PolarsResult<_>>()?;
+        Ok(OutputSchema { columns })
+    }
+}
+
+fn is_supported(dtype: &DataType) -> bool {
+    match dtype {
+        DataType::Int64
+        | DataType::UInt64
+        | DataType::Float64
+        | DataType::Boolean
+        | DataType::Utf8
+        | DataType::Datetime(_, _) => true,
+        DataType::List(inner) => is_supported(inner),
+        _ => false,
+    }
+}
+
+/// The columns of the data frames holding a query's results,
+/// with the trustfall types their values are checked against.
+#[derive(Debug, Clone)]
+pub struct OutputSchema {
+    pub(crate) columns: Vec<Column>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Column {
+    pub(crate) name: String,
+    pub(crate) value_type: Type,
+    pub(crate) dtype: DataType,
+}
+
+impl OutputSchema {
+    /// The Polars schema of the data frames.
+    pub fn polars_schema(&self) -> Schema {
+        self.columns
+            .iter()
+            .map(|column| Field::new(&column.name, column.dtype.clone()))
+            .collect()
+    }
+}
@@ -0,0 +1,157 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use polars::prelude::{DataType, Field, Schema, TimeUnit};
+use trustfall_core::{
+    frontend::parse,
+    interpreter::execution::interpret_ir,
+    ir::{FieldValue, IndexedQuery},
+    numbers_interpreter::NumbersAdapter,
+    schema::Schema as TrustfallSchema,
+};
+use trustfall_polars::{to_data_frame, DataFrameBuilder, PolarsOptions};
+
+type Row = BTreeMap<Arc<str>, FieldValue>;
+
+fn execute(query: &str) -> (Arc<IndexedQuery>, Vec<Row>) {
+    let schema = TrustfallSchema::parse(include_str!(
+        "../../trustfall_core/test_data/schemas/numbers.graphql"
+    ))
+    .unwrap();
+    let query = parse(&schema, query).unwrap();
+    let adapter = Arc::new(NumbersAdapter::new());
+    let results = interpret_ir(adapter, query.clone(), Arc::new(BTreeMap::new()))
+        .unwrap()
+        .collect();
+    (query, results)
+}
+
+const QUERY: &str = r#"
+{
+    Number(min: 1, max: 4) {
+        value @output
+        name @output
+
+        predecessor @optional {
+            previous: value @output
+        }
+
+        multiple(max: 3) @fold {
+            multiples: value @output
+        }
+    }
+}"#;
+
+#[test]
+fn derives_schema_from_outputs() {
+    let (query, _) = execute(QUERY);
+    let schema = PolarsOptions::new().schema(&query.outputs).unwrap();
+    let expected = Schema::from_iter([
+        Field::new("multiples", DataType::List(Box::new(DataType::Int64))),
+        Field::new("name", DataType::Utf8),
+        Field::new("previous", DataType::Int64),
+        Field::new("value", DataType::Int64),
+    ]);
+    assert_eq!(expected, schema.polars_schema());
+}
+
+#[test]
+fn converts_results() {
+    let (query, results) = execute(QUERY);
+    let df = to_data_frame(&query.outputs, results, &PolarsOptions::new()).unwrap();
+    assert_eq!((4, 4), df.shape());
+
+    let values = df.column("value").unwrap().i64().unwrap();
+    assert_eq!(
+        vec![Some(1), Some(2), Some(3), Some(4)],
+        values.into_iter().collect::<Vec<_>>()
+    );
+
+    let names = df.column("name").unwrap().utf8().unwrap();
+    assert_eq!(Some("one"), names.get(0));
+
+    let previous = df.column("previous").unwrap().i64().unwrap();
+    assert_eq!(
+        vec![Some(0), Some(1), Some(2), Some(3)],
+        previous.into_iter().collect::<Vec<_>>()
+    );
+
+    let multiples = df.column("multiples").unwrap().list().unwrap();
+    let multiples_of_two = multiples.get(1).unwrap();
+    assert_eq!(
+        vec![Some(4), Some(6)],
+        multiples_of_two
+            .i64()
+            .unwrap()
+            .into_iter()
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn keeps_list_dtype_without_list_items() {
+    let (query, _) = execute(QUERY);
+    let mut row = Row::new();
+    row.insert("value".into(), FieldValue::Int64(1));
+    row.insert("multiples".into(), FieldValue::List(vec![]));
+    let df = to_data_frame(&query.outputs, [row], &PolarsOptions::new()).unwrap();
+    assert_eq!(
+        &DataType::List(Box::new(DataType::Int64)),
+        df.column("multiples").unwrap().dtype()
+    );
+}
+
+#[test]
+fn builder_resets_after_finishing() {
+    let (query, results) = execute(QUERY);
+    let schema = PolarsOptions::new().schema(&query.outputs).unwrap();
+    let mut builder = DataFrameBuilder::new(schema);
+    for row in results.into_iter().take(3) {
+        builder.push(row);
+    }
+    assert_eq!(3, builder.len());
+    assert_eq!(3, builder.finish().unwrap().height());
+    assert!(builder.is_empty());
+    assert_eq!(0, builder.finish().unwrap().height());
+}
+
+#[test]
+fn rejects_values_of_other_types() {
+    let (query, _) = execute(QUERY);
+    let mut row = Row::new();
+    row.insert("value".into(), FieldValue::String("four".to_string()));
+    row.insert("multiples".into(), FieldValue::List(vec![]));
+    let error = to_data_frame(&query.outputs, [row], &PolarsOptions::new()).unwrap_err();
+    assert!(
+        error.to_string().contains("invalid value for output value"),
+        "{error}"
+    );
+}
+
+#[test]
+fn rejects_nulls_in_non_nullable_columns() {
+    let (query, _) = execute(QUERY);
+    let row = Row::new();
+    let error = to_data_frame(&query.outputs, [row], &PolarsOptions::new()).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("invalid value for output multiples"),
+        "{error}"
+    );
+}
+
+#[test]
+fn uses_named_type_overrides() {
+    let (query, results) = execute(QUERY);
+    let options = PolarsOptions::new().with_named_type("Int", DataType::UInt64);
+    let df = to_data_frame(&query.outputs, results, &options).unwrap();
+    assert_eq!(&DataType::UInt64, df.column("value").unwrap().dtype());
+    assert_eq!(
+        &DataType::List(Box::new(DataType::UInt64)),
+        df.column("multiples").unwrap().dtype()
+    );
+
+    let options =
+        PolarsOptions::new().with_named_type("Int", DataType::Duration(TimeUnit::Milliseconds));
+    assert!(options.schema(&query.outputs).is_err());
+}
@@ -0,0 +1,317 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+use trustfall_core::ir::{EdgeParameters, FieldValue};
+use url::Url;
+
+use crate::{
+    mapping::{Api, EndpointPlan, Pagination, Segment},
+    to_field_value,
+    transport::{HttpResponse, Transport, TransportError},
+    Vertex,
+};
+
+/// Sends the adapter's requests, keeping to the mapping's rate limit.
+#[derive(Debug)]
+pub(crate) struct Client<T> {
+    pub(crate) api: Api,
+    pub(crate) transport: T,
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl<T: Transport> Client<T> {
+    pub(crate) fn new(api: Api, transport: T) -> Self {
+        Self {
+            api,
+            transport,
+            last_request: Mutex::new(None),
+        }
+    }
+
+    /// Get the URL, waiting as long as the rate limit requires, and retrying if the API
+    /// rejects the request as rate-limited. Returns `None` if the API responds `404 Not Found`.
+    fn get(&self, url: &Url) -> Result<Option<HttpResponse>, TransportError> {
+        let mut retries = 0;
+        let response = loop {
+            self.wait_for_rate_limit();
+            let response = self.transport.get(url.as_str())?;
+            let is_rate_limited = matches!(response.status, 429 | 503);
+            if !is_rate_limited || retries >= self.api.rate_limit.max_retries {
+                break response;
+            }
+
+            let delay = response
+                .header("Retry-After")
+                .and_then(|seconds| seconds.trim().parse().ok())
+                .unwrap_or(1u64 << retries.min(10));
+            thread::sleep(Duration::from_secs(delay));
+            retries += 1;
+        };
+
+        match response.status {
+            200..=299 => Ok(Some(response)),
+            404 => Ok(None),
+            status => Err(TransportError::UnexpectedStatus {
+                url: url.to_string(),
+                status,
+            }),
+        }
+    }
+
+    fn wait_for_rate_limit(&self) {
+        let Some(rate) = self.api.rate_limit.requests_per_second else {
+            return;
+        };
+        let interval = Duration::from_secs_f64(1.0 / rate);
+
+        let mut last_request = self.last_request.lock().unwrap();
+        if let Some(elapsed) = last_request.map(|instant| instant.elapsed()) {
+            if elapsed < interval {
+                thread::sleep(interval - elapsed);
+            }
+        }
+        *last_request = Some(Instant::now());
+    }
+
+    /// The endpoint's URL with the given parameters, and for edges, the properties of the vertex
+    /// the edge starts from. `None` if the value of any of the path's placeholders is `null`.
+    pub(crate) fn url(
+        &self,
+        endpoint: &EndpointPlan,
+        parameters: &EdgeParameters,
+        vertex: Option<&Vertex>,
+    ) -> Option<Url> {
+        let mut path = String::new();
+        for segment in &endpoint.path {
+            let value = match segment {
+                Segment::Literal(literal) => {
+                    path.push_str(literal);
+                    continue;
+                }
+                Segment::Parameter(name) => parameters.get(name.as_str()).cloned(),
+                Segment::Property(name) => {
+                    let vertex = vertex.expect("placeholder of a property on a starting edge");
+                    let (property_type, pointer) = self.api.property(vertex.typename(), name);
+                    vertex
+                        .value()
+                        .pointer(pointer)
+                        .map(|value| to_field_value(value, property_type))
+                }
+            };
+            encode_path_segment(&mut path, &to_url_value(&value?)?);
+        }
+
+        let base = self.api.base_url.as_str().trim_end_matches('/');
+        let mut url = if path.is_empty() || path.starts_with('/') {
+            Url::parse(&format!("{base}{path}"))
+        } else {
+            Url::parse(&format!("{base}/{path}"))
+        }
+        .unwrap_or_else(|e| panic!("invalid URL for path {path}: {e}"));
+
+        let query: Vec<_> = endpoint
+            .query_parameters
+            .iter()
+            .filter_map(|name| {
+                let value = to_url_value(parameters.get(name.as_str())?)?;
+                Some((name, value))
+            })
+            .collect();
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        Some(url)
+    }
+}
+
+/// The vertices of an endpoint, fetching pages as they're needed.
+pub(crate) struct Pages<T> {
+    client: Arc<Client<T>>,
+    endpoint: Arc<EndpointPlan>,
+    /// The endpoint's URL, without the parameters of pagination.
+    url: Url,
+    next_page: Option<NextPage>,
+    items: std::vec::IntoIter<Value>,
+}
+
+enum NextPage {
+    Number(u64),
+    Offset(u64),
+    Cursor(Option<String>),
+    Url(Url),
+}
+
+impl<T: Transport> Pages<T> {
+    pub(crate) fn new(client: Arc<Client<T>>, endpoint: Arc<EndpointPlan>, url: Url) -> Self {
+        let next_page = match &endpoint.pagination {
+            Pagination::None | Pagination::LinkHeader => NextPage::Url(url.clone()),
+            Pagination::Page { first, .. } => NextPage::Number(*first),
+            Pagination::Offset { .. } => NextPage::Offset(0),
+            Pagination::Cursor { .. } => NextPage::Cursor(None),
+        };
+        Self {
+            client,
+            endpoint,
+            url,
+            next_page: Some(next_page),
+            items: vec![].into_iter(),
+        }
+    }
+
+    fn page_url(&self, page: &NextPage) -> Url {
+        let mut url = self.url.clone();
+        let mut append = |name: &str, value: String| {
+            url.query_pairs_mut().append_pair(name, &value);
+        };
+        match (&self.endpoint.pagination, page) {
+            (_, NextPage::Url(page_url)) => return page_url.clone(),
+            (
+                Pagination::Page {
+                    parameter,
+                    size_parameter,
+                    size,
+                    ..
+                }
+                | Pagination::Offset {
+                    parameter,
+                    size_parameter,
+                    size,
+                },
+                NextPage::Number(position) | NextPage::Offset(position),
+            ) => {
+                append(parameter, position.to_string());
+                if let (Some(size_parameter), Some(size)) = (size_parameter, size) {
+                    append(size_parameter, size.to_string());
+                }
+            }
+            (Pagination::Cursor { parameter, .. }, NextPage::Cursor(cursor)) => {
+                if let Some(cursor) = cursor {
+                    append(parameter, cursor.clone());
+                }
+            }
+            _ => unreachable!("page does not match the endpoint's pagination"),
+        }
+        url
+    }
+
+    /// Fetch the page, returning its vertices' values and the next page, if any.
+    fn fetch(&self, page: NextPage) -> (Vec<Value>, Option<NextPage>) {
+        let url = self.page_url(&page);
+        let Some(response) = self.client.get(&url).unwrap_or_else(|e| panic!("{e}")) else {
+            return (vec![], None);
+        };
+
+        let items = match &self.endpoint.items {
+            Some(pointer) => response.body.pointer(pointer).cloned().unwrap_or_default(),
+            None => response.body.clone(),
+        };
+        let items = match items {
+            Value::Null => vec![],
+            Value::Array(items) if self.endpoint.list => items,
+            Value::Object(_) if !self.endpoint.list => vec![items],
+            items => panic!(
+                "the API responded to {url} with {items}, which is not {}",
+                if self.endpoint.list {
+                    "a list"
+                } else {
+                    "an object"
+                }
+            ),
+        };
+
+        let is_full_page = |size: &Option<u64>| {
+            !items.is_empty() && size.is_none_or(|size| items.len() as u64 >= size)
+        };
+        let next_page = match (&self.endpoint.pagination, page) {
+            (Pagination::None, _) => None,
+            (Pagination::Page { size, .. }, NextPage::Number(number)) => {
+                is_full_page(size).then_some(NextPage::Number(number + 1))
+            }
+            (Pagination::Offset { size, .. }, NextPage::Offset(offset)) => {
+                is_full_page(size).then_some(NextPage::Offset(offset + items.len() as u64))
+            }
+            (Pagination::Cursor { next, .. }, _) => match response.body.pointer(next) {
+                Some(Value::String(cursor)) if !cursor.is_empty() => {
+                    Some(NextPage::Cursor(Some(cursor.clone())))
+                }
+                Some(Value::Number(cursor)) => Some(NextPage::Cursor(Some(cursor.to_string()))),
+                _ => None,
+            },
+            (Pagination::LinkHeader, _) => response
+                .header("Link")
+                .and_then(next_link)
+                .map(|next| {
+                    url.join(next)
+                        .unwrap_or_else(|e| panic!("invalid next page URL {next}: {e}"))
+                })
+                .map(NextPage::Url),
+            _ => unreachable!("page does not match the endpoint's pagination"),
+        };
+        (items, next_page)
+    }
+}
+
+impl<T: Transport> Iterator for Pages<T> {
+    type Item = Vertex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.items.next() {
+                Some(Value::Null) => continue,
+                Some(value @ Value::Object(_)) => {
+                    return Some(Vertex::new(self.endpoint.vertex_type.clone(), value))
+                }
+                Some(value) => {
+                    panic!("the API returned {value} as a vertex, which is not an object")
+                }
+                None => {
+                    let page = self.next_page.take()?;
+                    let (items, next_page) = self.fetch(page);
+                    self.items = items.into_iter();
+                    self.next_page = next_page;
+                }
+            }
+        }
+    }
+}
+
+/// The URL with `rel="next"` in the value of a `Link` header, as in RFC 8288.
+fn next_link(header: &str) -> Option<&str> {
+    header.split(',').find_map(|link| {
+        let (target, parameters) = link.trim().strip_prefix('<')?.split_once('>')?;
+        let is_next = parameters.split(';').any(|parameter| {
+            let parameter = parameter.trim().replace(' ', "");
+            parameter == "rel=\"next\"" || parameter == "rel=next"
+        });
+        is_next.then_some(target)
+    })
+}
+
+/// The text of a value in the endpoint's URL, or `None` if it's `null`.
+fn to_url_value(value: &FieldValue) -> Option<String> {
+    match value {
+        FieldValue::Null => None,
+        FieldValue::Int64(n) => Some(n.to_string()),
+        FieldValue::Uint64(n) => Some(n.to_string()),
+        FieldValue::Float64(n) => Some(n.to_string()),
+        FieldValue::String(s) | FieldValue::Enum(s) => Some(s.clone()),
+        FieldValue::Boolean(b) => Some(b.to_string()),
+        FieldValue::DateTimeUtc(d) => Some(d.to_rfc3339()),
+        FieldValue::List(_) => unreachable!("lists are not URL values: {value:?}"),
+    }
+}
+
+/// Percent-encode everything except the unreserved characters of RFC 3986.
+fn encode_path_segment(path: &mut String, value: &str) {
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            path.push(byte as char);
+        } else {
+            path.push_str(&format!("%{byte:02X}"));
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! Query REST APIs with trustfall, without writing an adapter for each API.
+//!
+//! An [`ApiMapping`], usually read from a JSON file, says which of an API's endpoints
+//! produce the vertices of each starting edge and edge, and which fields of the returned
+//! JSON objects are properties. [`RestAdapter`] derives a trustfall schema from the mapping,
+//! and fetches from the endpoints as queries traverse their edges: paginated endpoints
+//! are fetched one page at a time, as the query needs more vertices, and requests are
+//! sent no faster than the mapping's rate limit allows.
+//!
+//! ```
+//! use std::{collections::BTreeMap, sync::Arc};
+//!
+//! use serde_json::json;
+//! use trustfall_core::{frontend::parse, interpreter::execution::interpret_ir, ir::FieldValue};
+//! use trustfall_rest_adapter::{ApiMapping, HttpResponse, RestAdapter, TransportError};
+//!
+//! let mapping = ApiMapping::from_json(r#"
+//! {
+//!     "base_url": "https://api.example.com",
+//!     "starting_edges": {
+//!         "Users": { "type": "User", "path": "/users", "list": true }
+//!     },
+//!     "types": {
+//!         "User": {
+//!             "properties": { "id": "Int!", "name": "String!" },
+//!             "edges": {
+//!                 "posts": { "type": "Post", "path": "/users/{id}/posts", "list": true }
+//!             }
+//!         },
+//!         "Post": {
+//!             "properties": { "title": "String!", "likes": "Int" }
+//!         }
+//!     }
+//! }"#).unwrap();
+//!
+//! // A transport answering requests with canned responses, in place of `HttpTransport`.
+//! let transport = |url: &str| -> Result<HttpResponse, TransportError> {
+//!     Ok(HttpResponse::ok(match url {
+//!         "https://api.example.com/users" => json!([{ "id": 1, "name": "Ada" }]),
+//!         "https://api.example.com/users/1/posts" => json!([
+//!             { "title": "Notes", "likes": 12 },
+//!             { "title": "Engines", "likes": 3 },
+//!         ]),
+//!         _ => unreachable!("unexpected request to {url}"),
+//!     }))
+//! };
+//! let adapter = RestAdapter::new(&mapping, transport).unwrap();
+//!
+//! let query = parse(adapter.schema(), r#"
+//! {
+//!     Users {
+//!         name @output
+//!
+//!         posts {
+//!             title @output
+//!             likes @filter(op: ">=", value: ["$min"])
+//!         }
+//!     }
+//! }"#).unwrap();
+//! let arguments = Arc::new(BTreeMap::from([("min".into(), 10.into())]));
+//! let rows: Vec<_> = interpret_ir(Arc::new(adapter), query, arguments).unwrap().collect();
+//!
+//! assert_eq!(1, rows.len());
+//! assert_eq!(FieldValue::from("Notes"), rows[0]["title"]);
+//! ```
+//!
+//! Filters are applied after fetching, so use the API's own query parameters, exposed as
+//! edge parameters, to limit what's fetched.
+use std::sync::Arc;
+
+use async_graphql_parser::types::{BaseType, Type};
+use serde_json::Value;
+use trustfall_core::{
+    interpreter::{
+        helpers::{resolve_coercion_with, resolve_neighbors_with, resolve_property_with},
+        Adapter, ContextIterator, ContextOutcomeIterator, ResolveEdgeInfo, ResolveInfo,
+        VertexIterator,
+    },
+    ir::{EdgeParameters, FieldValue},
+    schema::Schema,
+};
+
+mod fetch;
+mod mapping;
+mod transport;
+
+use fetch::{Client, Pages};
+use mapping::Api;
+
+pub use mapping::{
+    ApiMapping, Endpoint, MappingError, Pagination, Property, RateLimit, ResourceType,
+};
+#[cfg(feature = "http")]
+pub use transport::HttpTransport;
+pub use transport::{HttpResponse, Transport, TransportError};
+
+/// A vertex returned by the API.
+#[derive(Debug, Clone)]
+pub struct Vertex(Arc<VertexData>);
+
+#[derive(Debug)]
+struct VertexData {
+    typename: Arc<str>,
+    value: Value,
+}
+
+impl Vertex {
+    fn new(typename: Arc<str>, value: Value) -> Self {
+        Self(Arc::new(VertexData { typename, value }))
+    }
+
+    pub fn typename(&self) -> &str {
+        &self.0.typename
+    }
+
+    /// The vertex's JSON object, as returned by the API.
+    pub fn value(&self) -> &Value {
+        &self.0.value
+    }
+}
+
+/// An adapter running trustfall queries against a REST API.
+///
+/// # Panics
+///
+/// Trustfall adapters can't return errors, so resolving vertices panics if a request fails,
+/// if the API responds with an unexpected status, or if its response doesn't match
+/// the mapping. Endpoints responding `404 Not Found` have no vertices.
+#[derive(Debug)]
+pub struct RestAdapter<T> {
+    client: Arc<Client<T>>,
+    schema: Schema,
+}
+
+impl<T: Transport> RestAdapter<T> {
+    /// Make an adapter for the API described by the mapping, which sends requests
+    /// with the given transport.
+    ///
+    /// The adapter's schema has a vertex type for each of the mapping's types, with their
+    /// properties and edges, and a starting edge for each of the mapping's starting edges.
+    /// Edges of list endpoints have list types.
+    pub fn new(mapping: &ApiMapping, transport: T) -> Result<Self, MappingError> {
+        let (api, schema) = Api::new(mapping)?;
+        Ok(Self {
+            client: Arc::new(Client::new(api, transport)),
+            schema,
+        })
+    }
+
+    /// The trustfall schema derived from the mapping, to parse queries against.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.client.transport
+    }
+}
+
+impl<'vertex, T: Transport + 'vertex> Adapter<'vertex> for RestAdapter<T> {
+    type Vertex = Vertex;
+
+    fn resolve_starting_vertices(
+        &self,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        _resolve_info: &ResolveInfo,
+    ) -> VertexIterator<'vertex, Self::Vertex> {
+        let endpoint = &self.client.api.starting_edges[edge_name.as_ref()];
+        match self.client.url(endpoint, parameters, None) {
+            Some(url) => Box::new(Pages::new(self.client.clone(), endpoint.clone(), url)),
+            None => Box::new(std::iter::empty()),
+        }
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        type_name: &Arc<str>,
+        property_name: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, FieldValue> {
+        if property_name.as_ref() == "__typename" {
+            return resolve_property_with(contexts, |vertex| vertex.typename().into());
+        }
+
+        let (property_type, pointer) = self.client.api.property(type_name, property_name).clone();
+        resolve_property_with(contexts, move |vertex| {
+            match vertex.value().pointer(&pointer) {
+                Some(value) => to_field_value(value, &property_type),
+                None => FieldValue::Null,
+            }
+        })
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        type_name: &Arc<str>,
+        edge_name: &Arc<str>,
+        parameters: &EdgeParameters,
+        _resolve_info: &ResolveEdgeInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, VertexIterator<'vertex, Self::Vertex>> {
+        let client = self.client.clone();
+        let endpoint = client.api.edge(type_name, edge_name).clone();
+        let parameters = parameters.clone();
+        resolve_neighbors_with(contexts, move |vertex| {
+            match client.url(&endpoint, &parameters, Some(vertex)) {
+                Some(url) => Box::new(Pages::new(client.clone(), endpoint.clone(), url)),
+                None => Box::new(std::iter::empty()),
+            }
+        })
+    }
+
+    fn resolve_coercion(
+        &self,
+        contexts: ContextIterator<'vertex, Self::Vertex>,
+        _type_name: &Arc<str>,
+        coerce_to_type: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'vertex, Self::Vertex, bool> {
+        // Mappings have no interfaces, so vertices are only ever of their own type.
+        let coerce_to_type = coerce_to_type.clone();
+        resolve_coercion_with(contexts, move |vertex| {
+            vertex.typename() == coerce_to_type.as_ref()
+        })
+    }
+}
+
+fn to_field_value(value: &Value, value_type: &Type) -> FieldValue {
+    let mismatch = || -> ! { panic!("the API returned {value} for a value of type {value_type}") };
+    match (&value_type.base, value) {
+        (_, Value::Null) => FieldValue::Null,
+        (BaseType::List(item_type), Value::Array(items)) => FieldValue::List(
+            items
+                .iter()
+                .map(|item| to_field_value(item, item_type))
+                .collect(),
+        ),
+        (BaseType::List(_), _) => mismatch(),
+        (BaseType::Named(name), value) => match (name.as_str(), value) {
+            ("Int", Value::Number(number)) => {
+                if let Some(n) = number.as_i64() {
+                    FieldValue::Int64(n)
+                } else if let Some(n) = number.as_u64() {
+                    FieldValue::Uint64(n)
+                } else {
+                    mismatch()
+                }
+            }
+            ("Float", Value::Number(number)) => {
+                FieldValue::Float64(number.as_f64().expect("number is not a valid float"))
+            }
+            ("String" | "ID", Value::String(s)) => FieldValue::String(s.clone()),
+            ("ID", Value::Number(number)) => FieldValue::String(number.to_string()),
+            ("Boolean", Value::Bool(b)) => FieldValue::Boolean(*b),
+            _ => mismatch(),
+        },
+    }
+}
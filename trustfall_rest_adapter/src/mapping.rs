@@ -0,0 +1,514 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use async_graphql_parser::types::{BaseType, Type};
+use serde::Deserialize;
+use trustfall_core::schema::{
+    builder::{SchemaBuilder, Ty},
+    error::InvalidSchemaError,
+    Schema,
+};
+use url::Url;
+
+const SCALARS: [&str; 5] = ["Int", "Float", "String", "Boolean", "ID"];
+
+/// Errors from making an adapter out of an [`ApiMapping`].
+#[non_exhaustive]
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum MappingError {
+    #[error("The mapping is not valid: {0}")]
+    ParseError(String),
+
+    #[error("The base URL \"{0}\" is not a valid URL: {1}")]
+    InvalidBaseUrl(String, String),
+
+    #[error("The rate limit of {0} requests per second is not a positive number.")]
+    InvalidRateLimit(f64),
+
+    #[error("{0} has type \"{1}\", which is not a GraphQL type of scalars.")]
+    InvalidType(String, String),
+
+    #[error("{0} returns vertices of type \"{1}\", which is not in the mapping.")]
+    UnknownType(String, String),
+
+    #[error(
+        "{0} has the placeholder \"{{{1}}}\" in its path, which is neither one of its \
+        parameters nor a property of the vertex type it starts from."
+    )]
+    UnknownPlaceholder(String, String),
+
+    #[error("{0} has the placeholder \"{{{1}}}\" in its path, whose type \"{2}\" is a list type.")]
+    ListPlaceholder(String, String, String),
+
+    #[error("The trustfall schema derived from the mapping is not valid")]
+    InvalidSchema(#[from] InvalidSchemaError),
+}
+
+/// How a REST API's endpoints map to vertex types and edges.
+///
+/// Mappings are usually read from JSON files with [`ApiMapping::from_json`]:
+///
+/// ```json
+/// {
+///     "base_url": "https://api.github.com",
+///     "pagination": { "style": "link_header" },
+///     "rate_limit": { "requests_per_second": 1 },
+///     "starting_edges": {
+///         "User": { "type": "User", "path": "/users/{login}", "parameters": { "login": "String!" } }
+///     },
+///     "types": {
+///         "User": {
+///             "properties": { "login": "String!", "name": "String", "followers": "Int" },
+///             "edges": {
+///                 "repos": { "type": "Repo", "path": "/users/{login}/repos", "list": true }
+///             }
+///         },
+///         "Repo": {
+///             "properties": {
+///                 "name": "String!",
+///                 "stars": { "type": "Int", "pointer": "/stargazers_count" }
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ApiMapping {
+    /// The URL that endpoint paths are relative to, like `https://api.example.com/v1`.
+    pub base_url: String,
+
+    /// The endpoints producing the starting vertices of queries, by starting edge name.
+    pub starting_edges: BTreeMap<String, Endpoint>,
+
+    /// The vertex types, by name.
+    pub types: BTreeMap<String, ResourceType>,
+
+    /// How list endpoints are paginated, unless they specify their own pagination.
+    /// By default, list endpoints aren't paginated.
+    #[serde(default)]
+    pub pagination: Option<Pagination>,
+
+    #[serde(default)]
+    pub rate_limit: RateLimit,
+}
+
+impl ApiMapping {
+    /// Read a mapping from its JSON representation.
+    pub fn from_json(json: &str) -> Result<Self, MappingError> {
+        serde_json::from_str(json).map_err(|e| MappingError::ParseError(e.to_string()))
+    }
+}
+
+/// A vertex type, whose vertices are the JSON objects returned by endpoints.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ResourceType {
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// The properties of the type's vertices, by name.
+    pub properties: BTreeMap<String, Property>,
+
+    /// The endpoints producing the neighbors of the type's vertices, by edge name.
+    #[serde(default)]
+    pub edges: BTreeMap<String, Endpoint>,
+}
+
+/// A property of a vertex type. In JSON, a property is either its GraphQL type,
+/// like `"String!"`, or an object with its `type`, `pointer`, and `description`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(from = "PropertyDefinition")]
+pub struct Property {
+    /// The property's GraphQL type, which must be a scalar or a list of scalars.
+    pub property_type: String,
+
+    /// The JSON pointer to the property's value in each vertex's object.
+    /// By default, the value is the object's field named after the property.
+    pub pointer: Option<String>,
+
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PropertyDefinition {
+    Type(String),
+    Full {
+        #[serde(rename = "type")]
+        property_type: String,
+        #[serde(default)]
+        pointer: Option<String>,
+        #[serde(default)]
+        description: Option<String>,
+    },
+}
+
+impl From<PropertyDefinition> for Property {
+    fn from(definition: PropertyDefinition) -> Self {
+        match definition {
+            PropertyDefinition::Type(property_type) => Self {
+                property_type,
+                pointer: None,
+                description: None,
+            },
+            PropertyDefinition::Full {
+                property_type,
+                pointer,
+                description,
+            } => Self {
+                property_type,
+                pointer,
+                description,
+            },
+        }
+    }
+}
+
+/// An endpoint producing the vertices of a starting edge, or the neighbors of a vertex.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Endpoint {
+    /// The type of the vertices the endpoint returns.
+    #[serde(rename = "type")]
+    pub vertex_type: String,
+
+    /// The endpoint's path relative to the base URL, like `/users/{login}/repos`.
+    ///
+    /// Placeholders in braces are replaced by the value of the edge parameter of the same name,
+    /// or for edges of vertex types, by the value of the vertex's property of the same name.
+    /// Edges whose placeholders' values are `null` have no neighbors.
+    pub path: String,
+
+    /// Whether the endpoint returns a list of vertices, rather than a single vertex.
+    #[serde(default)]
+    pub list: bool,
+
+    /// The JSON pointer to the vertices in the response, like `/items`.
+    /// By default, the vertices are the whole response.
+    #[serde(default)]
+    pub items: Option<String>,
+
+    /// The edge's parameters, by name, with their GraphQL types. Parameters without
+    /// placeholders in the path are sent as query parameters, unless their value is `null`.
+    #[serde(default)]
+    pub parameters: BTreeMap<String, String>,
+
+    /// How the endpoint is paginated, instead of the mapping's default pagination.
+    /// Only list endpoints are paginated.
+    #[serde(default)]
+    pub pagination: Option<Pagination>,
+
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// How a list endpoint splits its vertices into pages.
+///
+/// Pages are fetched as the query needs more vertices, so queries only fetch
+/// as many pages as they use.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "style", rename_all = "snake_case")]
+pub enum Pagination {
+    /// All the vertices are in a single response.
+    None,
+
+    /// Pages are numbered in the `parameter` query parameter, starting from `first`.
+    /// The last page is the first one that is empty, or has fewer than `size` vertices.
+    Page {
+        parameter: String,
+        #[serde(default = "first_page")]
+        first: u64,
+        /// The query parameter setting the number of vertices per page.
+        #[serde(default)]
+        size_parameter: Option<String>,
+        #[serde(default)]
+        size: Option<u64>,
+    },
+
+    /// The `parameter` query parameter is the number of vertices before the page.
+    /// The last page is the first one that is empty, or has fewer than `size` vertices.
+    Offset {
+        parameter: String,
+        #[serde(default)]
+        size_parameter: Option<String>,
+        #[serde(default)]
+        size: Option<u64>,
+    },
+
+    /// Each page's response has the cursor of the next page at the JSON pointer `next`,
+    /// which is sent in the `parameter` query parameter. The last page is the first one
+    /// without a next cursor.
+    Cursor { parameter: String, next: String },
+
+    /// Each page's `Link` header has the URL of the next page, with `rel="next"`.
+    LinkHeader,
+}
+
+fn first_page() -> u64 {
+    1
+}
+
+/// How fast requests are sent to the API.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RateLimit {
+    /// Send at most this many requests per second. By default, requests aren't delayed.
+    #[serde(default)]
+    pub requests_per_second: Option<f64>,
+
+    /// How many times to retry requests that the API rejects with the status 429 or 503.
+    /// Retries wait as long as the response's `Retry-After` header asks, or if it has none,
+    /// one second and then twice as long as the previous retry.
+    #[serde(default = "default_retries")]
+    pub max_retries: u32,
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            requests_per_second: None,
+            max_retries: default_retries(),
+        }
+    }
+}
+
+/// A mapping checked against itself, in the form the adapter uses to send requests.
+#[derive(Debug, Clone)]
+pub(crate) struct Api {
+    pub(crate) base_url: Url,
+    pub(crate) starting_edges: BTreeMap<String, Arc<EndpointPlan>>,
+    pub(crate) types: BTreeMap<String, TypePlan>,
+    pub(crate) rate_limit: RateLimit,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TypePlan {
+    /// The type and JSON pointer of each property.
+    pub(crate) properties: BTreeMap<String, (Type, String)>,
+    pub(crate) edges: BTreeMap<String, Arc<EndpointPlan>>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct EndpointPlan {
+    pub(crate) vertex_type: Arc<str>,
+    pub(crate) path: Vec<Segment>,
+    pub(crate) list: bool,
+    pub(crate) items: Option<String>,
+    /// The parameters that aren't placeholders, sent as query parameters.
+    pub(crate) query_parameters: Vec<String>,
+    pub(crate) pagination: Pagination,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Segment {
+    Literal(String),
+    Parameter(String),
+    Property(String),
+}
+
+impl Api {
+    /// Check the mapping, and derive the trustfall schema of its types and edges.
+    pub(crate) fn new(mapping: &ApiMapping) -> Result<(Self, Schema), MappingError> {
+        let base_url = Url::parse(&mapping.base_url)
+            .map_err(|e| MappingError::InvalidBaseUrl(mapping.base_url.clone(), e.to_string()))?;
+        if let Some(rate) = mapping.rate_limit.requests_per_second {
+            if !(rate > 0.0 && rate.is_finite()) {
+                return Err(MappingError::InvalidRateLimit(rate));
+            }
+        }
+
+        let mut builder = SchemaBuilder::new();
+
+        let mut starting_edges = BTreeMap::new();
+        for (name, endpoint) in &mapping.starting_edges {
+            let context = format!("Starting edge \"{name}\"");
+            let plan = EndpointPlan::new(&context, endpoint, mapping, None)?;
+            let edge_type = if endpoint.list {
+                Ty::named(&endpoint.vertex_type)
+                    .non_null()
+                    .list()
+                    .non_null()
+            } else {
+                Ty::named(&endpoint.vertex_type)
+            };
+            builder = builder.root_edge(name, edge_type);
+            builder = add_edge_details(builder, &format!("starting edge \"{name}\""), endpoint)?;
+            starting_edges.insert(name.clone(), Arc::new(plan));
+        }
+
+        let mut types = BTreeMap::new();
+        for (type_name, resource_type) in &mapping.types {
+            builder = builder.vertex_type(type_name);
+            if let Some(description) = &resource_type.description {
+                builder = builder.description(description);
+            }
+
+            let mut properties = BTreeMap::new();
+            for (name, property) in &resource_type.properties {
+                let context = format!("Property \"{type_name}.{name}\"");
+                let property_type = parse_type(&context, &property.property_type, true)?;
+                builder = builder.property(name, to_ty(&property_type));
+                if let Some(description) = &property.description {
+                    builder = builder.description(description);
+                }
+                let pointer = property
+                    .pointer
+                    .clone()
+                    .unwrap_or_else(|| format!("/{name}"));
+                properties.insert(name.clone(), (property_type, pointer));
+            }
+
+            let mut edges = BTreeMap::new();
+            for (name, endpoint) in &resource_type.edges {
+                let context = format!("Edge \"{type_name}.{name}\"");
+                let plan = EndpointPlan::new(&context, endpoint, mapping, Some(resource_type))?;
+                let edge_type = if endpoint.list {
+                    Ty::named(&endpoint.vertex_type).non_null().list()
+                } else {
+                    Ty::named(&endpoint.vertex_type)
+                };
+                builder = builder.edge(name, edge_type);
+                builder =
+                    add_edge_details(builder, &format!("edge \"{type_name}.{name}\""), endpoint)?;
+                edges.insert(name.clone(), Arc::new(plan));
+            }
+
+            types.insert(type_name.clone(), TypePlan { properties, edges });
+        }
+
+        let api = Self {
+            base_url,
+            starting_edges,
+            types,
+            rate_limit: mapping.rate_limit.clone(),
+        };
+        Ok((api, builder.build()?))
+    }
+
+    pub(crate) fn property(&self, type_name: &str, property_name: &str) -> &(Type, String) {
+        &self.types[type_name].properties[property_name]
+    }
+
+    pub(crate) fn edge(&self, type_name: &str, edge_name: &str) -> &Arc<EndpointPlan> {
+        &self.types[type_name].edges[edge_name]
+    }
+}
+
+/// Add the description and parameters of the endpoint to the edge just added to the schema.
+fn add_edge_details(
+    mut builder: SchemaBuilder,
+    edge: &str,
+    endpoint: &Endpoint,
+) -> Result<SchemaBuilder, MappingError> {
+    if let Some(description) = &endpoint.description {
+        builder = builder.description(description);
+    }
+    for (name, parameter_type) in &endpoint.parameters {
+        let context = format!("Parameter \"{name}\" of {edge}");
+        let parameter_type = parse_type(&context, parameter_type, false)?;
+        builder = builder.parameter(name, to_ty(&parameter_type));
+    }
+    Ok(builder)
+}
+
+impl EndpointPlan {
+    fn new(
+        context: &str,
+        endpoint: &Endpoint,
+        mapping: &ApiMapping,
+        source_type: Option<&ResourceType>,
+    ) -> Result<Self, MappingError> {
+        if !mapping.types.contains_key(&endpoint.vertex_type) {
+            return Err(MappingError::UnknownType(
+                context.to_string(),
+                endpoint.vertex_type.clone(),
+            ));
+        }
+
+        let mut path = vec![];
+        let mut rest = endpoint.path.as_str();
+        while let Some((literal, after)) = rest.split_once('{') {
+            let (placeholder, after) = after.split_once('}').unwrap_or((after, ""));
+            path.push(Segment::Literal(literal.to_string()));
+
+            let property = source_type.and_then(|source| source.properties.get(placeholder));
+            if endpoint.parameters.contains_key(placeholder) {
+                path.push(Segment::Parameter(placeholder.to_string()));
+            } else if let Some(property) = property {
+                let is_list = Type::new(&property.property_type)
+                    .is_some_and(|property_type| matches!(property_type.base, BaseType::List(_)));
+                if is_list {
+                    return Err(MappingError::ListPlaceholder(
+                        context.to_string(),
+                        placeholder.to_string(),
+                        property.property_type.clone(),
+                    ));
+                }
+                path.push(Segment::Property(placeholder.to_string()));
+            } else {
+                return Err(MappingError::UnknownPlaceholder(
+                    context.to_string(),
+                    placeholder.to_string(),
+                ));
+            }
+            rest = after;
+        }
+        path.push(Segment::Literal(rest.to_string()));
+
+        let query_parameters = endpoint
+            .parameters
+            .keys()
+            .filter(|name| !path.contains(&Segment::Parameter(name.to_string())))
+            .cloned()
+            .collect();
+        let pagination = if endpoint.list {
+            endpoint
+                .pagination
+                .as_ref()
+                .or(mapping.pagination.as_ref())
+                .cloned()
+                .unwrap_or(Pagination::None)
+        } else {
+            Pagination::None
+        };
+
+        Ok(Self {
+            vertex_type: endpoint.vertex_type.as_str().into(),
+            path,
+            list: endpoint.list,
+            items: endpoint.items.clone(),
+            query_parameters,
+            pagination,
+        })
+    }
+}
+
+/// Parse a GraphQL type of scalars, or only of a single scalar if lists aren't allowed.
+fn parse_type(context: &str, text: &str, allow_lists: bool) -> Result<Type, MappingError> {
+    let invalid = || MappingError::InvalidType(context.to_string(), text.to_string());
+    let parsed = Type::new(text).ok_or_else(invalid)?;
+
+    let mut base = &parsed.base;
+    while let BaseType::List(item) = base {
+        if !allow_lists {
+            return Err(invalid());
+        }
+        base = &item.base;
+    }
+    match base {
+        BaseType::Named(name) if SCALARS.contains(&name.as_str()) => Ok(parsed),
+        _ => Err(invalid()),
+    }
+}
+
+fn to_ty(value_type: &Type) -> Ty {
+    let ty = match &value_type.base {
+        BaseType::Named(name) => Ty::named(name),
+        BaseType::List(item) => to_ty(item).list(),
+    };
+    if value_type.nullable {
+        ty
+    } else {
+        ty.non_null()
+    }
+}
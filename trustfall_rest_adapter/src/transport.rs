@@ -0,0 +1,133 @@
+use serde_json::Value;
+
+/// A response from the API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+
+    /// The response's JSON body, or `null` if it has none.
+    pub body: Value,
+}
+
+impl HttpResponse {
+    /// A `200 OK` response with the given body and no headers.
+    pub fn ok(body: Value) -> Self {
+        Self {
+            status: 200,
+            headers: vec![],
+            body,
+        }
+    }
+
+    /// A response with the given status, no headers, and no body.
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            body: Value::Null,
+        }
+    }
+
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// The value of the first header with the given name, which is case-insensitive.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Errors from sending requests to the API.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TransportError {
+    #[error("The request to the API failed: {0}")]
+    RequestFailed(String),
+
+    #[error("The API's response is not valid JSON: {0}")]
+    InvalidResponse(String),
+
+    #[error("The API responded to {url} with HTTP status {status}.")]
+    UnexpectedStatus { url: String, status: u16 },
+}
+
+/// Sends `GET` requests to the API.
+///
+/// Closures taking a URL are also transports, which is convenient in tests.
+pub trait Transport {
+    fn get(&self, url: &str) -> Result<HttpResponse, TransportError>;
+}
+
+impl<F> Transport for F
+where
+    F: Fn(&str) -> Result<HttpResponse, TransportError>,
+{
+    fn get(&self, url: &str) -> Result<HttpResponse, TransportError> {
+        self(url)
+    }
+}
+
+/// Sends requests over HTTP, accepting JSON responses.
+#[cfg(feature = "http")]
+#[derive(Debug, Clone, Default)]
+pub struct HttpTransport {
+    client: reqwest::blocking::Client,
+    headers: Vec<(String, String)>,
+}
+
+#[cfg(feature = "http")]
+impl HttpTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send the given header with every request, for example for authentication.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+#[cfg(feature = "http")]
+impl Transport for HttpTransport {
+    fn get(&self, url: &str) -> Result<HttpResponse, TransportError> {
+        let mut builder = self
+            .client
+            .get(url)
+            .header(reqwest::header::ACCEPT, "application/json");
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        let response = builder
+            .send()
+            .map_err(|e| TransportError::RequestFailed(e.to_string()))?;
+
+        let status = response.status();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let text = response
+            .text()
+            .map_err(|e| TransportError::RequestFailed(e.to_string()))?;
+
+        // Error responses often aren't JSON, and only their status matters.
+        let body = match serde_json::from_str(&text) {
+            Ok(body) => body,
+            Err(_) if text.trim().is_empty() || !status.is_success() => Value::Null,
+            Err(e) => return Err(TransportError::InvalidResponse(e.to_string())),
+        };
+        Ok(HttpResponse {
+            status: status.as_u16(),
+            headers,
+            body,
+        })
+    }
+}
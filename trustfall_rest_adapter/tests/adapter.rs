@@ -0,0 +1,407 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use serde_json::{json, Value};
+use trustfall_core::{frontend::parse, interpreter::execution::interpret_ir, ir::FieldValue};
+use trustfall_rest_adapter::{
+    ApiMapping, HttpResponse, MappingError, RestAdapter, Transport, TransportError,
+};
+
+const MAPPING: &str = r#"
+{
+    "base_url": "https://api.example.com/v1/",
+    "pagination": { "style": "page", "parameter": "page", "size_parameter": "per_page", "size": 2 },
+    "starting_edges": {
+        "Users": {
+            "type": "User",
+            "path": "/users",
+            "list": true,
+            "parameters": { "role": "String" },
+            "description": "All users, oldest first."
+        },
+        "User": { "type": "User", "path": "/users/{login}", "parameters": { "login": "String!" } },
+        "SearchRepos": {
+            "type": "Repo",
+            "path": "/search/repos",
+            "list": true,
+            "items": "/items",
+            "parameters": { "q": "String!" },
+            "pagination": { "style": "cursor", "parameter": "after", "next": "/next" }
+        }
+    },
+    "types": {
+        "User": {
+            "description": "A user account.",
+            "properties": {
+                "login": "String!",
+                "name": "String",
+                "email": { "type": "String", "pointer": "/contact/email", "description": "Public email." }
+            },
+            "edges": {
+                "repos": {
+                    "type": "Repo",
+                    "path": "/users/{login}/repos",
+                    "list": true,
+                    "parameters": { "sort": "String" },
+                    "pagination": { "style": "link_header" }
+                },
+                "manager": { "type": "User", "path": "/users/{login}/manager" }
+            }
+        },
+        "Repo": {
+            "properties": { "name": "String!", "stars": "Int", "topics": "[String!]" }
+        }
+    }
+}
+"#;
+
+/// A transport answering each URL with its responses in order, and recording the requests.
+#[derive(Debug, Default)]
+struct MockTransport {
+    requests: Mutex<Vec<String>>,
+    responses: Mutex<BTreeMap<String, VecDeque<HttpResponse>>>,
+}
+
+impl MockTransport {
+    fn new(responses: impl IntoIterator<Item = (&'static str, HttpResponse)>) -> Self {
+        let mut by_url: BTreeMap<String, VecDeque<HttpResponse>> = BTreeMap::new();
+        for (url, response) in responses {
+            by_url
+                .entry(url.to_string())
+                .or_default()
+                .push_back(response);
+        }
+        Self {
+            requests: Default::default(),
+            responses: Mutex::new(by_url),
+        }
+    }
+}
+
+impl Transport for MockTransport {
+    fn get(&self, url: &str) -> Result<HttpResponse, TransportError> {
+        self.requests.lock().unwrap().push(url.to_string());
+        Ok(self
+            .responses
+            .lock()
+            .unwrap()
+            .get_mut(url)
+            .and_then(VecDeque::pop_front)
+            .unwrap_or_else(|| panic!("unexpected request to {url}")))
+    }
+}
+
+type Row = BTreeMap<Arc<str>, FieldValue>;
+
+fn adapter(transport: MockTransport) -> Arc<RestAdapter<MockTransport>> {
+    let mapping = ApiMapping::from_json(MAPPING).unwrap();
+    Arc::new(RestAdapter::new(&mapping, transport).unwrap())
+}
+
+fn run(
+    transport: MockTransport,
+    query: &str,
+    arguments: BTreeMap<Arc<str>, FieldValue>,
+    max_rows: usize,
+) -> (Vec<Row>, Vec<String>) {
+    let adapter = adapter(transport);
+    let query = parse(adapter.schema(), query).unwrap();
+    let rows = interpret_ir(adapter.clone(), query, Arc::new(arguments))
+        .unwrap()
+        .take(max_rows)
+        .collect();
+    let requests = adapter.transport().requests.lock().unwrap().clone();
+    (rows, requests)
+}
+
+fn column(rows: &[Row], name: &str) -> Vec<FieldValue> {
+    rows.iter().map(|row| row[name].clone()).collect()
+}
+
+fn users(logins: &[&str]) -> HttpResponse {
+    let users: Vec<Value> = logins
+        .iter()
+        .map(|login| json!({ "login": login }))
+        .collect();
+    HttpResponse::ok(Value::Array(users))
+}
+
+#[test]
+fn derives_schema_from_mapping() {
+    let adapter = adapter(MockTransport::default());
+    let schema = adapter.schema();
+
+    let root_edges: Vec<_> = schema
+        .root_edges()
+        .map(|edge| (edge.name(), edge.edge_type().to_string()))
+        .collect();
+    assert_eq!(
+        vec![
+            ("SearchRepos", "[Repo!]!".to_string()),
+            ("User", "User".to_string()),
+            ("Users", "[User!]!".to_string()),
+        ],
+        root_edges
+    );
+    let users = schema.root_type().edge("Users").unwrap();
+    assert_eq!(Some("All users, oldest first."), users.description());
+    let parameters: Vec<_> = users.parameters().map(|p| p.name()).collect();
+    assert_eq!(vec!["role"], parameters);
+
+    let user = schema.vertex_type("User").unwrap();
+    assert_eq!(Some("A user account."), user.description());
+    let properties: Vec<_> = user
+        .properties()
+        .map(|property| (property.name(), property.property_type().to_string()))
+        .collect();
+    assert_eq!(
+        vec![
+            ("email", "String".to_string()),
+            ("login", "String!".to_string()),
+            ("name", "String".to_string()),
+        ],
+        properties
+    );
+    let edges: Vec<_> = user
+        .edges()
+        .map(|edge| (edge.name(), edge.edge_type().to_string()))
+        .collect();
+    assert_eq!(
+        vec![
+            ("manager", "User".to_string()),
+            ("repos", "[Repo!]".to_string())
+        ],
+        edges
+    );
+    let topics = schema
+        .vertex_type("Repo")
+        .unwrap()
+        .property("topics")
+        .unwrap();
+    assert_eq!("[String!]", topics.property_type().to_string());
+}
+
+#[test]
+fn fetches_pages_only_as_needed() {
+    let transport = MockTransport::new([
+        (
+            "https://api.example.com/v1/users?role=admin&page=1&per_page=2",
+            users(&["ada", "grace"]),
+        ),
+        (
+            "https://api.example.com/v1/users?role=admin&page=2&per_page=2",
+            users(&["edsger", "barbara"]),
+        ),
+        (
+            "https://api.example.com/v1/users?role=admin&page=3&per_page=2",
+            users(&["donald"]),
+        ),
+    ]);
+    let query = r#"
+{
+    Users(role: "admin") {
+        login @output
+    }
+}"#;
+    let arguments = BTreeMap::new();
+
+    let (rows, requests) = run(transport, query, arguments.clone(), 3);
+    assert_eq!(
+        vec![FieldValue::from("ada"), "grace".into(), "edsger".into()],
+        column(&rows, "login")
+    );
+    assert_eq!(2, requests.len(), "{requests:?}");
+
+    let transport = MockTransport::new([
+        (
+            "https://api.example.com/v1/users?role=admin&page=1&per_page=2",
+            users(&["ada", "grace"]),
+        ),
+        (
+            "https://api.example.com/v1/users?role=admin&page=2&per_page=2",
+            users(&["donald"]),
+        ),
+    ]);
+    // The second page isn't full, so it's the last one.
+    let (rows, requests) = run(transport, query, arguments, usize::MAX);
+    assert_eq!(3, rows.len());
+    assert_eq!(2, requests.len(), "{requests:?}");
+}
+
+#[test]
+fn fills_paths_with_parameters_and_properties() {
+    let transport = MockTransport::new([
+        (
+            "https://api.example.com/v1/users/ada%20l",
+            HttpResponse::ok(json!({
+                "login": "ada l",
+                "name": "Ada",
+                "contact": { "email": "ada@example.com" },
+            })),
+        ),
+        (
+            "https://api.example.com/v1/users/ada%20l/repos?sort=stars",
+            HttpResponse::ok(json!([{ "name": "engine", "stars": 12, "topics": ["math"] }]))
+                .with_header(
+                    "link",
+                    r#"<https://api.example.com/v1/users/ada%20l/repos?sort=stars&page=2>; rel="next", <https://api.example.com/v1/users/ada%20l/repos?sort=stars&page=2>; rel="last""#,
+                ),
+        ),
+        (
+            "https://api.example.com/v1/users/ada%20l/repos?sort=stars&page=2",
+            HttpResponse::ok(json!([{ "name": "notes", "stars": null }])),
+        ),
+        (
+            "https://api.example.com/v1/users/ada%20l/manager",
+            HttpResponse::status(404),
+        ),
+    ]);
+    let query = r#"
+{
+    User(login: "ada l") {
+        name @output
+        email @output
+
+        manager @optional {
+            manager: login @output
+        }
+
+        repos(sort: "stars") {
+            repo: name @output
+            stars @output
+            topics @output
+        }
+    }
+}"#;
+    let (rows, requests) = run(transport, query, BTreeMap::new(), usize::MAX);
+
+    assert_eq!(
+        vec![FieldValue::from("engine"), "notes".into()],
+        column(&rows, "repo")
+    );
+    assert_eq!(
+        vec![FieldValue::Int64(12), FieldValue::Null],
+        column(&rows, "stars")
+    );
+    assert_eq!(
+        vec![FieldValue::List(vec!["math".into()]), FieldValue::Null],
+        column(&rows, "topics")
+    );
+    assert_eq!(
+        vec![FieldValue::from("ada@example.com"); 2],
+        column(&rows, "email")
+    );
+    assert_eq!(vec![FieldValue::Null; 2], column(&rows, "manager"));
+    assert_eq!(4, requests.len(), "{requests:?}");
+}
+
+#[test]
+fn follows_cursors_to_the_next_page() {
+    let transport = MockTransport::new([
+        (
+            "https://api.example.com/v1/search/repos?q=lang%3Arust",
+            HttpResponse::ok(json!({ "items": [{ "name": "trustfall" }], "next": "abc" })),
+        ),
+        (
+            "https://api.example.com/v1/search/repos?q=lang%3Arust&after=abc",
+            HttpResponse::ok(json!({ "items": [{ "name": "serde" }], "next": null })),
+        ),
+    ]);
+    let query = r#"
+{
+    SearchRepos(q: "lang:rust") {
+        name @output
+    }
+}"#;
+    let (rows, requests) = run(transport, query, BTreeMap::new(), usize::MAX);
+    assert_eq!(
+        vec![FieldValue::from("trustfall"), "serde".into()],
+        column(&rows, "name")
+    );
+    assert_eq!(2, requests.len());
+}
+
+#[test]
+fn retries_rate_limited_requests() {
+    let url = "https://api.example.com/v1/users/ada";
+    let transport = MockTransport::new([
+        (
+            url,
+            HttpResponse::status(429).with_header("Retry-After", "0"),
+        ),
+        (
+            url,
+            HttpResponse::status(503).with_header("Retry-After", "0"),
+        ),
+        (url, HttpResponse::ok(json!({ "login": "ada" }))),
+    ]);
+    let query = r#"
+{
+    User(login: "ada") {
+        login @output
+    }
+}"#;
+    let (rows, requests) = run(transport, query, BTreeMap::new(), usize::MAX);
+    assert_eq!(vec![FieldValue::from("ada")], column(&rows, "login"));
+    assert_eq!(vec![url; 3], requests);
+}
+
+#[test]
+#[should_panic(expected = "HTTP status 500")]
+fn panics_on_unexpected_statuses() {
+    let transport = MockTransport::new([(
+        "https://api.example.com/v1/users/ada",
+        HttpResponse::status(500),
+    )]);
+    let query = r#"
+{
+    User(login: "ada") {
+        login @output
+    }
+}"#;
+    run(transport, query, BTreeMap::new(), usize::MAX);
+}
+
+#[test]
+fn rejects_invalid_mappings() {
+    let transport = |_: &str| -> Result<HttpResponse, TransportError> { unreachable!() };
+    let mut mapping = ApiMapping::from_json(MAPPING).unwrap();
+    mapping
+        .types
+        .get_mut("User")
+        .unwrap()
+        .edges
+        .get_mut("manager")
+        .unwrap()
+        .path = "/users/{id}/manager".to_string();
+    match RestAdapter::new(&mapping, transport).err() {
+        Some(MappingError::UnknownPlaceholder(edge, placeholder)) => {
+            assert_eq!("Edge \"User.manager\"", edge);
+            assert_eq!("id", placeholder);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    let mut mapping = ApiMapping::from_json(MAPPING).unwrap();
+    mapping.starting_edges.get_mut("Users").unwrap().vertex_type = "Account".to_string();
+    assert!(matches!(
+        RestAdapter::new(&mapping, transport).err(),
+        Some(MappingError::UnknownType(_, vertex_type)) if vertex_type == "Account",
+    ));
+
+    let mut mapping = ApiMapping::from_json(MAPPING).unwrap();
+    mapping
+        .types
+        .get_mut("Repo")
+        .unwrap()
+        .properties
+        .get_mut("stars")
+        .unwrap()
+        .property_type = "Repo".to_string();
+    assert!(matches!(
+        RestAdapter::new(&mapping, transport).err(),
+        Some(MappingError::InvalidType(property, _)) if property == "Property \"Repo.stars\"",
+    ));
+}
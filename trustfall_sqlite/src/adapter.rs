@@ -0,0 +1,181 @@
+use std::{collections::HashMap, path::Path};
+
+use rusqlite::{
+    types::{Value, ValueRef},
+    Connection,
+};
+use trustfall_core::{
+    interpreter::sql::{
+        ParameterStyle, RelationalAdapter, RelationalMapping, Select, SqlAdapter, SqlVertex,
+    },
+    ir::{EdgeParameters, FieldValue},
+    schema::{error::InvalidSchemaError, Schema},
+};
+
+use crate::{
+    introspect::{read_tables, TableInfo},
+    schema::{derive_schema, PropertyType},
+};
+
+/// Errors from opening a database for querying.
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum SqliteError {
+    #[error("Failed to read the database")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error("The schema derived from the database is not valid")]
+    InvalidSchema(#[from] InvalidSchemaError),
+}
+
+/// A SQLite database, with a schema derived from its tables.
+///
+/// Wrap it in a [`SqlAdapter`] with [`SqliteDatabase::into_adapter`] to query it.
+/// The adapter translates the query's filters on each table's rows to `WHERE` conditions,
+/// the `limit` parameters of starting edges to `LIMIT` clauses, and edges across foreign keys
+/// to one statement per batch of rows, so that the database only returns the rows
+/// the query may need.
+#[derive(Debug)]
+pub struct SqliteDatabase {
+    connection: Connection,
+    tables: Vec<TableInfo>,
+    schema: Schema,
+    mapping: RelationalMapping,
+    column_types: HashMap<(String, String), PropertyType>,
+}
+
+impl SqliteDatabase {
+    /// Open the database file.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SqliteError> {
+        Self::new(Connection::open(path)?)
+    }
+
+    /// Query the database of the connection. Its schema is derived from the tables
+    /// it has now, so tables created later aren't part of it.
+    pub fn new(connection: Connection) -> Result<Self, SqliteError> {
+        let tables = read_tables(&connection)?;
+        let derived = derive_schema(&tables)?;
+        Ok(Self {
+            connection,
+            tables,
+            schema: derived.schema,
+            mapping: derived.mapping,
+            column_types: derived.column_types,
+        })
+    }
+
+    /// The schema derived from the database's tables.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// The metadata of the tables the schema was derived from.
+    pub fn tables(&self) -> &[TableInfo] {
+        &self.tables
+    }
+
+    /// The connection to the database.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// An adapter querying the database with its derived schema.
+    pub fn into_adapter(self) -> SqlAdapter<Self> {
+        SqlAdapter::new(self)
+    }
+
+    fn to_field_value(&self, table: &str, column: &str, value: ValueRef<'_>) -> FieldValue {
+        let property_type = self
+            .column_types
+            .get(&(table.to_string(), column.to_string()));
+        match (property_type, value) {
+            (_, ValueRef::Null) => FieldValue::Null,
+            (Some(PropertyType::Boolean), ValueRef::Integer(value)) => (value != 0).into(),
+            (Some(PropertyType::Float), ValueRef::Integer(value)) => {
+                FieldValue::Float64(value as f64)
+            }
+            (_, ValueRef::Integer(value)) => value.into(),
+            (_, ValueRef::Real(value)) => FieldValue::Float64(value),
+            (_, ValueRef::Text(text)) => String::from_utf8_lossy(text).into_owned().into(),
+            // Blob columns aren't properties, and are only selected as the columns of joins.
+            (_, ValueRef::Blob(_)) => FieldValue::Null,
+        }
+    }
+}
+
+/// The SQLite value of a parameter of a statement.
+fn to_sql_value(value: &FieldValue) -> Value {
+    match value {
+        FieldValue::Null => Value::Null,
+        FieldValue::Int64(value) => Value::Integer(*value),
+        FieldValue::Uint64(value) => match i64::try_from(*value) {
+            Ok(value) => Value::Integer(value),
+            Err(_) => Value::Real(*value as f64),
+        },
+        FieldValue::Float64(value) => Value::Real(*value),
+        FieldValue::String(value) | FieldValue::Enum(value) => Value::Text(value.clone()),
+        FieldValue::Boolean(value) => Value::Integer(i64::from(*value)),
+        FieldValue::DateTimeUtc(value) => Value::Text(value.to_rfc3339()),
+        FieldValue::List(_) => unreachable!("lists are not compared in SQL: {value:?}"),
+    }
+}
+
+impl RelationalAdapter for SqliteDatabase {
+    type Error = rusqlite::Error;
+
+    fn mapping(&self) -> &RelationalMapping {
+        &self.mapping
+    }
+
+    fn execute(&self, select: &Select) -> Result<Vec<Vec<FieldValue>>, Self::Error> {
+        let (sql, parameters) = select.to_sql(ParameterStyle::QuestionMark);
+        let mut statement = self.connection.prepare_cached(&sql)?;
+        let mut rows = statement.query(rusqlite::params_from_iter(
+            parameters.iter().map(to_sql_value),
+        ))?;
+
+        let mut results = vec![];
+        while let Some(row) = rows.next()? {
+            let values = select
+                .columns
+                .iter()
+                .enumerate()
+                .map(|(index, column)| {
+                    Ok(self.to_field_value(&select.table, column, row.get_ref(index)?))
+                })
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            results.push(values);
+        }
+        Ok(results)
+    }
+
+    // Every field of the schema derived from the tables is in the mapping derived with it,
+    // so queries against that schema never reach these.
+
+    fn resolve_unmapped_starting_vertices(
+        &self,
+        edge_name: &str,
+        _parameters: &EdgeParameters,
+    ) -> Vec<SqlVertex> {
+        unreachable!("starting edge {edge_name} is not in the schema derived from the database")
+    }
+
+    fn resolve_unmapped_property(&self, vertex: &SqlVertex, property_name: &str) -> FieldValue {
+        unreachable!(
+            "property {property_name} of {} is not in the schema derived from the database",
+            vertex.type_name()
+        )
+    }
+
+    fn resolve_unmapped_neighbors(
+        &self,
+        vertex: &SqlVertex,
+        edge_name: &str,
+        _parameters: &EdgeParameters,
+    ) -> Vec<SqlVertex> {
+        unreachable!(
+            "edge {edge_name} of {} is not in the schema derived from the database",
+            vertex.type_name()
+        )
+    }
+}
@@ -0,0 +1,130 @@
+use rusqlite::Connection;
+
+/// A table of the database, as described by its metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableInfo {
+    pub name: String,
+    pub columns: Vec<ColumnInfo>,
+    pub foreign_keys: Vec<ForeignKey>,
+}
+
+/// A column of a table, as described by `PRAGMA table_info`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnInfo {
+    pub name: String,
+    /// The column's declared type, such as `INTEGER` or `VARCHAR(255)`. Empty if it has none.
+    pub declared_type: String,
+    pub not_null: bool,
+    pub primary_key: bool,
+}
+
+/// A single-column foreign key, as described by `PRAGMA foreign_key_list`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignKey {
+    pub column: String,
+    pub to_table: String,
+    pub to_column: String,
+}
+
+/// Read the metadata of the database's tables, in order of their names.
+///
+/// SQLite's internal tables are left out, and so are foreign keys spanning several columns.
+pub fn read_tables(connection: &Connection) -> rusqlite::Result<Vec<TableInfo>> {
+    let mut statement = connection.prepare(
+        "SELECT name FROM sqlite_master \
+        WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+    )?;
+    let names = statement
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut tables = names
+        .into_iter()
+        .map(|name| {
+            let columns = read_columns(connection, &name)?;
+            Ok(TableInfo {
+                name,
+                columns,
+                foreign_keys: vec![],
+            })
+        })
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    // Foreign keys that don't name the columns they refer to refer to the primary key,
+    // so they are read once the primary keys of all tables are known.
+    let foreign_keys = tables
+        .iter()
+        .map(|table| read_foreign_keys(connection, &table.name, &tables))
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (table, foreign_keys) in tables.iter_mut().zip(foreign_keys) {
+        table.foreign_keys = foreign_keys;
+    }
+    Ok(tables)
+}
+
+fn read_columns(connection: &Connection, table: &str) -> rusqlite::Result<Vec<ColumnInfo>> {
+    let mut statement = connection
+        .prepare("SELECT name, type, \"notnull\", pk FROM pragma_table_info(?1) ORDER BY cid")?;
+    let columns = statement
+        .query_map([table], |row| {
+            Ok(ColumnInfo {
+                name: row.get(0)?,
+                declared_type: row.get(1)?,
+                not_null: row.get(2)?,
+                primary_key: row.get::<_, i64>(3)? > 0,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(columns)
+}
+
+fn read_foreign_keys(
+    connection: &Connection,
+    table: &str,
+    tables: &[TableInfo],
+) -> rusqlite::Result<Vec<ForeignKey>> {
+    let mut statement = connection.prepare(
+        "SELECT id, \"table\", \"from\", \"to\" FROM pragma_foreign_key_list(?1) \
+        ORDER BY id, seq",
+    )?;
+    let rows = statement
+        .query_map([table], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut foreign_keys = vec![];
+    for (id, to_table, column, to_column) in &rows {
+        let is_composite = rows.iter().filter(|row| row.0 == *id).count() > 1;
+        if is_composite {
+            continue;
+        }
+        let to_column = match to_column {
+            Some(to_column) => Some(to_column.clone()),
+            None => primary_key(tables, to_table),
+        };
+        if let Some(to_column) = to_column {
+            foreign_keys.push(ForeignKey {
+                column: column.clone(),
+                to_table: to_table.clone(),
+                to_column,
+            });
+        }
+    }
+    Ok(foreign_keys)
+}
+
+/// The table's primary key column, unless it has none or several.
+fn primary_key(tables: &[TableInfo], table: &str) -> Option<String> {
+    let table = tables.iter().find(|info| info.name == table)?;
+    let mut primary_key = table.columns.iter().filter(|column| column.primary_key);
+    match (primary_key.next(), primary_key.next()) {
+        (Some(column), None) => Some(column.name.clone()),
+        _ => None,
+    }
+}
@@ -0,0 +1,57 @@
+//! Query SQLite databases with trustfall.
+//!
+//! [`SqliteDatabase`] derives a schema from the database's tables, with a vertex type
+//! for each table, a property for each column, and edges in both directions across
+//! each foreign key. Its adapter is a [`SqlAdapter`](trustfall_core::interpreter::sql::SqlAdapter),
+//! which translates the query's filters on rows, the `limit` parameters of starting
+//! edges, and edges across foreign keys to SQL, so the database returns only the rows
+//! the query may need instead of whole tables.
+//!
+//! ```
+//! use std::{collections::BTreeMap, sync::Arc};
+//!
+//! use rusqlite::Connection;
+//! use trustfall_core::{frontend::parse, interpreter::execution::interpret_ir, ir::FieldValue};
+//! use trustfall_sqlite::SqliteDatabase;
+//!
+//! let connection = Connection::open_in_memory().unwrap();
+//! connection
+//!     .execute_batch(
+//!         "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+//!         CREATE TABLE posts (
+//!             id INTEGER PRIMARY KEY,
+//!             title TEXT NOT NULL,
+//!             likes INTEGER,
+//!             author_id INTEGER REFERENCES users (id)
+//!         );
+//!         INSERT INTO users VALUES (1, 'Ada'), (2, 'Grace');
+//!         INSERT INTO posts VALUES (1, 'Notes', 12, 1), (2, 'Compilers', 40, 2), (3, 'Engines', 3, 1);",
+//!     )
+//!     .unwrap();
+//! let adapter = Arc::new(SqliteDatabase::new(connection).unwrap().into_adapter());
+//!
+//! // The filter on `likes` is part of the statement selecting each batch of users' posts.
+//! let query = parse(adapter.inner().schema(), r#"
+//! {
+//!     Users {
+//!         name @output
+//!
+//!         posts {
+//!             title @output
+//!             likes @filter(op: ">=", value: ["$min"])
+//!         }
+//!     }
+//! }"#).unwrap();
+//! let arguments = Arc::new(BTreeMap::from([("min".into(), 10i64.into())]));
+//! let rows: Vec<_> = interpret_ir(adapter, query, arguments).unwrap().collect();
+//!
+//! let titles: Vec<_> = rows.iter().map(|row| row["title"].clone()).collect();
+//! assert_eq!(vec![FieldValue::from("Notes"), "Compilers".into()], titles);
+//! ```
+mod adapter;
+mod introspect;
+mod schema;
+
+pub use adapter::{SqliteDatabase, SqliteError};
+pub use introspect::{read_tables, ColumnInfo, ForeignKey, TableInfo};
+pub use schema::{PropertyType, LIMIT_PARAMETER};
@@ -0,0 +1,255 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use trustfall_core::{
+    interpreter::sql::{Join, RelationalMapping, StartingEdge, Table},
+    schema::{
+        builder::{graphql_name, pascal_case, unique_name, SchemaBuilder, Ty},
+        error::InvalidSchemaError,
+        Schema,
+    },
+};
+
+use crate::introspect::{ColumnInfo, TableInfo};
+
+/// The parameter of every starting edge that limits how many rows it produces.
+pub const LIMIT_PARAMETER: &str = "limit";
+
+/// The type of a column's property, following SQLite's rules for the affinity
+/// of declared column types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyType {
+    Int,
+    Float,
+    String,
+    Boolean,
+}
+
+impl PropertyType {
+    /// The property type of columns with the declared type, or `None` for columns
+    /// holding blobs or without a declared type, which aren't queried as properties.
+    pub fn of(declared_type: &str) -> Option<Self> {
+        let declared_type = declared_type.to_ascii_uppercase();
+        let contains = |pattern: &str| declared_type.contains(pattern);
+        if contains("BOOL") {
+            // Not an SQLite affinity, but a common way to declare columns holding 0 or 1.
+            Some(Self::Boolean)
+        } else if contains("INT") {
+            Some(Self::Int)
+        } else if contains("CHAR") || contains("CLOB") || contains("TEXT") {
+            Some(Self::String)
+        } else if declared_type.is_empty() || contains("BLOB") {
+            None
+        } else {
+            // Both REAL and NUMERIC affinity columns may hold fractional numbers.
+            Some(Self::Float)
+        }
+    }
+
+    fn ty(self, not_null: bool) -> Ty {
+        let ty = match self {
+            Self::Int => Ty::int(),
+            Self::Float => Ty::float(),
+            Self::String => Ty::string(),
+            Self::Boolean => Ty::boolean(),
+        };
+        if not_null {
+            ty.non_null()
+        } else {
+            ty
+        }
+    }
+}
+
+/// The schema and relational mapping derived from a database's tables.
+#[derive(Debug, Clone)]
+pub(crate) struct DerivedSchema {
+    pub(crate) schema: Schema,
+    pub(crate) mapping: RelationalMapping,
+    /// The property type of each table's columns, by table and column name.
+    pub(crate) column_types: HashMap<(String, String), PropertyType>,
+}
+
+/// A table's vertex type, and how its fields are stored in the table.
+struct VertexType {
+    type_name: String,
+    field_names: BTreeSet<String>,
+    fields: Vec<Field>,
+    table: Table,
+}
+
+struct Field {
+    name: String,
+    ty: Ty,
+    is_edge: bool,
+    description: String,
+}
+
+/// Derive a schema with a vertex type for each table, named after the table in PascalCase.
+///
+/// - Each column is a property of the same name, unless it holds blobs or has
+///   no declared type. Columns declared `NOT NULL` are non-null properties.
+/// - Each single-column foreign key is an edge from the row to the row it refers to,
+///   named after the column without its `_id` suffix, like `author` for `author_id`.
+/// - The other direction of each such foreign key is an edge from the row to the rows
+///   referring to it, named after the referring table, like `posts`; or if the table has
+///   several foreign keys to the same table, named after the column too, like
+///   `posts_by_author_id`.
+/// - Each table has a starting edge named after its vertex type, producing all its rows
+///   or at most `limit` of them.
+pub(crate) fn derive_schema(tables: &[TableInfo]) -> Result<DerivedSchema, InvalidSchemaError> {
+    let mut type_names = BTreeSet::new();
+    let mut column_types = HashMap::new();
+    let mut vertex_types: BTreeMap<&str, VertexType> = BTreeMap::new();
+    for table in tables {
+        let type_name = unique_name(&mut type_names, pascal_case(&graphql_name(&table.name)));
+        let mut vertex_type = VertexType {
+            type_name,
+            field_names: BTreeSet::new(),
+            fields: vec![],
+            table: Table::new(&table.name),
+        };
+        for ColumnInfo {
+            name,
+            declared_type,
+            not_null,
+            ..
+        } in &table.columns
+        {
+            let Some(property_type) = PropertyType::of(declared_type) else {
+                continue;
+            };
+            let property = unique_name(&mut vertex_type.field_names, graphql_name(name));
+            vertex_type.table = vertex_type
+                .table
+                .with_column(property.as_str(), name.as_str());
+            vertex_type.fields.push(Field {
+                name: property,
+                ty: property_type.ty(*not_null),
+                is_edge: false,
+                description: format!("The `{name}` column."),
+            });
+            column_types.insert((table.name.clone(), name.clone()), property_type);
+        }
+        vertex_types.insert(&table.name, vertex_type);
+    }
+
+    // Add the edges after all properties, so that edges are the ones renamed
+    // when a property and an edge would have the same name.
+    for table in tables {
+        for foreign_key in &table.foreign_keys {
+            let Some(to_type) = vertex_types.get(foreign_key.to_table.as_str()) else {
+                continue;
+            };
+            let to_type = to_type.type_name.clone();
+            let from = vertex_types
+                .get_mut(table.name.as_str())
+                .expect("table has no vertex type");
+            let from_type = from.type_name.clone();
+
+            let edge = unique_name(
+                &mut from.field_names,
+                forward_edge_name(&foreign_key.column, &foreign_key.to_table),
+            );
+            from.table = from.table.clone().with_join(
+                edge.as_str(),
+                Join::new(
+                    to_type.as_str(),
+                    &foreign_key.column,
+                    &foreign_key.to_column,
+                ),
+            );
+            from.fields.push(Field {
+                name: edge,
+                ty: Ty::named(&to_type),
+                is_edge: true,
+                description: format!(
+                    "The `{}` row whose `{}` is this row's `{}`.",
+                    foreign_key.to_table, foreign_key.to_column, foreign_key.column,
+                ),
+            });
+
+            let is_only_reference = table
+                .foreign_keys
+                .iter()
+                .filter(|other| other.to_table == foreign_key.to_table)
+                .count()
+                == 1;
+            let reverse = if is_only_reference {
+                graphql_name(&table.name)
+            } else {
+                graphql_name(&format!("{}_by_{}", table.name, foreign_key.column))
+            };
+            let to = vertex_types
+                .get_mut(foreign_key.to_table.as_str())
+                .expect("table has no vertex type");
+            let reverse = unique_name(&mut to.field_names, reverse);
+            to.table = to.table.clone().with_join(
+                reverse.as_str(),
+                Join::new(
+                    from_type.as_str(),
+                    &foreign_key.to_column,
+                    &foreign_key.column,
+                ),
+            );
+            to.fields.push(Field {
+                name: reverse,
+                ty: Ty::named(&from_type).non_null().list(),
+                is_edge: true,
+                description: format!(
+                    "The `{}` rows whose `{}` is this row's `{}`.",
+                    table.name, foreign_key.column, foreign_key.to_column,
+                ),
+            });
+        }
+    }
+
+    let mut builder = SchemaBuilder::new();
+    let mut mapping = RelationalMapping::new();
+    for table in tables {
+        let type_name = vertex_types[table.name.as_str()].type_name.as_str();
+        builder = builder
+            .root_edge(type_name, Ty::named(type_name).non_null().list().non_null())
+            .description(format!("The rows of the `{}` table.", table.name))
+            .parameter(LIMIT_PARAMETER, Ty::int());
+        mapping = mapping.with_starting_edge(
+            type_name,
+            StartingEdge::new(type_name).with_limit_parameter(LIMIT_PARAMETER),
+        );
+    }
+    for table in tables {
+        let vertex_type = vertex_types
+            .remove(table.name.as_str())
+            .expect("table has no vertex type");
+        builder = builder
+            .vertex_type(&vertex_type.type_name)
+            .description(format!("A row of the `{}` table.", table.name));
+        for field in vertex_type.fields {
+            builder = if field.is_edge {
+                builder.edge(field.name, field.ty)
+            } else {
+                builder.property(field.name, field.ty)
+            };
+            builder = builder.description(field.description);
+        }
+        mapping = mapping.with_table(vertex_type.type_name, vertex_type.table);
+    }
+
+    Ok(DerivedSchema {
+        schema: builder.build()?,
+        mapping,
+        column_types,
+    })
+}
+
+/// The name of the edge across a foreign key: the column's name without its `_id` suffix,
+/// or if it has none, the column's name followed by the name of the table it refers to.
+fn forward_edge_name(column: &str, to_table: &str) -> String {
+    let stripped = column
+        .strip_suffix("_id")
+        .or_else(|| column.strip_suffix("Id"))
+        .filter(|stripped| !stripped.is_empty());
+    match stripped {
+        Some(stripped) => graphql_name(stripped),
+        None => graphql_name(&format!("{column}_{to_table}")),
+    }
+}
@@ -0,0 +1,285 @@
+use std::{cell::RefCell, collections::BTreeMap, sync::Arc};
+
+use rusqlite::Connection;
+use trustfall_core::{
+    frontend::parse,
+    interpreter::{
+        execution::interpret_ir,
+        sql::{
+            ParameterStyle, RelationalAdapter, RelationalMapping, Select, SqlAdapter, SqlVertex,
+        },
+    },
+    ir::{EdgeParameters, FieldValue},
+    schema::json::SchemaJson,
+};
+use trustfall_sqlite::{ForeignKey, SqliteDatabase};
+
+const TABLES: &str = "
+CREATE TABLE users (
+    id INTEGER PRIMARY KEY,
+    name VARCHAR(64) NOT NULL,
+    active BOOLEAN,
+    avatar BLOB
+);
+CREATE TABLE posts (
+    id INTEGER PRIMARY KEY,
+    title TEXT NOT NULL,
+    score NUMERIC,
+    author_id INTEGER REFERENCES users,
+    editor_id INTEGER REFERENCES users (id)
+);
+CREATE TABLE tags (
+    post INTEGER NOT NULL REFERENCES posts (id),
+    label TEXT NOT NULL
+);
+
+INSERT INTO users VALUES (1, 'Ada', 1, x'00'), (2, 'Grace', 0, NULL), (3, 'Edsger', NULL, NULL);
+INSERT INTO posts VALUES
+    (1, 'Notes on engines', 12, 1, 2),
+    (2, 'Compilers', 40.5, 2, NULL),
+    (3, 'Structured programming', 7, 3, 1),
+    (4, 'Engines, again', 25, 1, 1);
+INSERT INTO tags VALUES (1, 'history'), (1, 'math'), (2, 'compilers'), (4, 'math');
+";
+
+fn database() -> SqliteDatabase {
+    let connection = Connection::open_in_memory().unwrap();
+    connection.execute_batch(TABLES).unwrap();
+    SqliteDatabase::new(connection).unwrap()
+}
+
+/// Records the SQL of the statements the database executes.
+struct Recording {
+    database: SqliteDatabase,
+    statements: RefCell<Vec<String>>,
+}
+
+impl RelationalAdapter for Recording {
+    type Error = rusqlite::Error;
+
+    fn mapping(&self) -> &RelationalMapping {
+        self.database.mapping()
+    }
+
+    fn execute(&self, select: &Select) -> Result<Vec<Vec<FieldValue>>, Self::Error> {
+        let (sql, _) = select.to_sql(ParameterStyle::QuestionMark);
+        self.statements.borrow_mut().push(sql);
+        self.database.execute(select)
+    }
+
+    fn resolve_unmapped_starting_vertices(
+        &self,
+        edge_name: &str,
+        parameters: &EdgeParameters,
+    ) -> Vec<SqlVertex> {
+        self.database
+            .resolve_unmapped_starting_vertices(edge_name, parameters)
+    }
+
+    fn resolve_unmapped_property(&self, vertex: &SqlVertex, property_name: &str) -> FieldValue {
+        self.database
+            .resolve_unmapped_property(vertex, property_name)
+    }
+
+    fn resolve_unmapped_neighbors(
+        &self,
+        vertex: &SqlVertex,
+        edge_name: &str,
+        parameters: &EdgeParameters,
+    ) -> Vec<SqlVertex> {
+        self.database
+            .resolve_unmapped_neighbors(vertex, edge_name, parameters)
+    }
+}
+
+type Row = BTreeMap<Arc<str>, FieldValue>;
+
+fn run(query: &str, arguments: BTreeMap<Arc<str>, FieldValue>) -> (Vec<Row>, Vec<String>) {
+    let database = database();
+    let query = parse(database.schema(), query).unwrap();
+    let adapter = Arc::new(SqlAdapter::new(Recording {
+        database,
+        statements: RefCell::new(vec![]),
+    }));
+    let rows = interpret_ir(adapter.clone(), query, Arc::new(arguments))
+        .unwrap()
+        .collect();
+    let statements = adapter.inner().statements.take();
+    (rows, statements)
+}
+
+fn column(rows: &[Row], name: &str) -> Vec<FieldValue> {
+    rows.iter().map(|row| row[name].clone()).collect()
+}
+
+#[test]
+fn derives_schema_from_tables_and_foreign_keys() {
+    let database = database();
+    let posts = database
+        .tables()
+        .iter()
+        .find(|table| table.name == "posts")
+        .unwrap();
+    assert_eq!(
+        vec![
+            ForeignKey {
+                column: "author_id".into(),
+                to_table: "users".into(),
+                to_column: "id".into(),
+            },
+            ForeignKey {
+                column: "editor_id".into(),
+                to_table: "users".into(),
+                to_column: "id".into(),
+            },
+        ],
+        posts.foreign_keys,
+    );
+
+    let schema = SchemaJson::from(database.schema());
+    let fields = |type_name: &str| -> Vec<(String, String)> {
+        let vertex_type = schema
+            .types
+            .iter()
+            .find(|vertex_type| vertex_type.name == type_name)
+            .unwrap();
+        vertex_type
+            .fields
+            .iter()
+            .map(|field| (field.name.clone(), field.field_type.clone()))
+            .collect()
+    };
+    let pairs = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, ty)| (name.to_string(), ty.to_string()))
+            .collect()
+    };
+    assert_eq!(
+        pairs(&[
+            ("id", "Int"),
+            ("name", "String!"),
+            ("active", "Boolean"),
+            ("posts_by_author_id", "[Posts!]"),
+            ("posts_by_editor_id", "[Posts!]"),
+        ]),
+        fields("Users"),
+    );
+    assert_eq!(
+        pairs(&[
+            ("id", "Int"),
+            ("title", "String!"),
+            ("score", "Float"),
+            ("author_id", "Int"),
+            ("editor_id", "Int"),
+            ("author", "Users"),
+            ("editor", "Users"),
+            ("tags", "[Tags!]"),
+        ]),
+        fields("Posts"),
+    );
+    assert_eq!(
+        pairs(&[
+            ("post", "Int!"),
+            ("label", "String!"),
+            ("post_posts", "Posts"),
+        ]),
+        fields("Tags"),
+    );
+}
+
+#[test]
+fn pushes_filters_down_into_sql() {
+    let query = r#"
+{
+    Posts {
+        title @output
+        score @filter(op: ">=", value: ["$min"]) @output
+
+        author {
+            name @output @filter(op: "one_of", value: ["$names"])
+        }
+    }
+}"#;
+    let arguments = BTreeMap::from([
+        ("min".into(), FieldValue::Float64(10.0)),
+        (
+            "names".into(),
+            FieldValue::List(vec!["Ada".into(), "Edsger".into()]),
+        ),
+    ]);
+    let (rows, statements) = run(query, arguments);
+    assert_eq!(
+        vec![
+            FieldValue::from("Notes on engines"),
+            "Engines, again".into()
+        ],
+        column(&rows, "title"),
+    );
+    assert_eq!(
+        vec![FieldValue::Float64(12.0), FieldValue::Float64(25.0)],
+        column(&rows, "score"),
+    );
+    assert_eq!(vec![FieldValue::from("Ada"); 2], column(&rows, "name"));
+    assert_eq!(
+        vec![
+            r#"SELECT "author_id", "editor_id", "id", "score", "title" FROM "posts" WHERE "score" >= ? OR "score" IS NULL"#
+                .to_string(),
+            r#"SELECT "active", "id", "name" FROM "users" WHERE "name" IN (?, ?) AND "id" IN (?, ?)"#
+                .to_string(),
+        ],
+        statements,
+    );
+}
+
+#[test]
+fn limits_starting_edges_and_follows_reverse_edges() {
+    let query = r#"
+{
+    Users(limit: 2) {
+        name @output
+        active @output
+
+        posts_by_author_id @fold @transform(op: "count") @output(name: "posts")
+
+        posts_by_editor_id @fold {
+            edited: title @output
+
+            tags @fold {
+                labels: label @output
+            }
+        }
+    }
+}"#;
+    let (rows, statements) = run(query, BTreeMap::new());
+    assert_eq!(
+        vec![FieldValue::from("Ada"), "Grace".into()],
+        column(&rows, "name")
+    );
+    assert_eq!(
+        vec![FieldValue::Boolean(true), FieldValue::Boolean(false)],
+        column(&rows, "active")
+    );
+    assert_eq!(
+        vec![FieldValue::Uint64(2), FieldValue::Uint64(1)],
+        column(&rows, "posts")
+    );
+    assert_eq!(
+        vec![
+            FieldValue::List(vec![
+                "Structured programming".into(),
+                "Engines, again".into()
+            ]),
+            FieldValue::List(vec!["Notes on engines".into()]),
+        ],
+        column(&rows, "edited"),
+    );
+    assert_eq!(
+        FieldValue::List(vec![
+            FieldValue::List(vec![]),
+            FieldValue::List(vec!["math".into()]),
+        ]),
+        rows[0]["labels"],
+    );
+    assert!(statements[0].ends_with(" LIMIT 2"), "{statements:?}");
+}
@@ -14,14 +14,13 @@ use std::{
     sync::Arc,
 };
 
-use async_graphql_parser::{parse_query, parse_schema};
+use async_graphql_parser::parse_schema;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use trustfall_core::{
     filesystem_interpreter::{FilesystemInterpreter, FilesystemVertex},
-    graphql_query::error::ParseError,
-    graphql_query::parse_document,
+    graphql_query::parse_query_text,
     interpreter::error::QueryArgumentsError,
     interpreter::{
         execution,
@@ -63,10 +62,8 @@ fn parse(path: &str) {
     let test_query: TestGraphQLQuery = ron::from_str(&input_data).unwrap();
 
     let arguments = test_query.arguments;
-    let result: TestParsedGraphQLQueryResult = parse_query(test_query.query)
-        .map_err(ParseError::from)
-        .and_then(|doc| parse_document(&doc))
-        .map(move |query| TestParsedGraphQLQuery {
+    let result: TestParsedGraphQLQueryResult = parse_query_text(&test_query.query).map(
+        move |query| TestParsedGraphQLQuery {
             schema_name: test_query.schema_name,
             query,
             arguments,
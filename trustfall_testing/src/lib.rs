@@ -0,0 +1,42 @@
+//! Helpers shared by the tests of the adapters in this repository.
+//!
+//! Adapters are tested by running queries against fixtures and comparing the outputs
+//! of the results, so these helpers execute a query to completion and pick out
+//! a column of its results.
+use std::{collections::BTreeMap, sync::Arc};
+
+use trustfall_core::{
+    frontend::parse,
+    interpreter::{execution::interpret_ir_rows, Adapter},
+    ir::FieldValue,
+    schema::Schema,
+};
+
+pub use trustfall_core::interpreter::Row;
+
+/// Execute the query against the adapter, and collect all its results.
+///
+/// # Panics
+///
+/// If the query isn't valid for the schema, or the arguments aren't valid for the query.
+pub fn execute<'query, AdapterT: Adapter<'query> + 'query>(
+    schema: &Schema,
+    adapter: Arc<AdapterT>,
+    query: &str,
+    arguments: BTreeMap<Arc<str>, FieldValue>,
+) -> Vec<Row> {
+    let query = parse(schema, query).unwrap();
+    interpret_ir_rows(adapter, query, Arc::new(arguments))
+        .unwrap()
+        .collect()
+}
+
+/// The values of the named output, in order of the results.
+pub fn column(rows: &[Row], name: &str) -> Vec<FieldValue> {
+    rows.iter().map(|row| row[name].clone()).collect()
+}
+
+/// The string values, for comparing with a column of strings.
+pub fn strings(values: &[&str]) -> Vec<FieldValue> {
+    values.iter().copied().map(FieldValue::from).collect()
+}
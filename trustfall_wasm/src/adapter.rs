@@ -53,12 +53,12 @@ extern "C" {
     ) -> js_sys::Iterator;
 }
 
-struct JsVertexIterator {
+pub(crate) struct JsVertexIterator {
     inner: js_sys::IntoIter,
 }
 
 impl JsVertexIterator {
-    fn new(inner: js_sys::IntoIter) -> Self {
+    pub(crate) fn new(inner: js_sys::IntoIter) -> Self {
         Self { inner }
     }
 }
@@ -76,14 +76,14 @@ impl Iterator for JsVertexIterator {
     }
 }
 
-struct ContextAndValueIterator {
+pub(crate) struct ContextAndValueIterator {
     inner: js_sys::Iterator,
     registry: Rc<RefCell<BTreeMap<u32, DataContext<JsValue>>>>,
     next_item: u32,
 }
 
 impl ContextAndValueIterator {
-    fn new(
+    pub(crate) fn new(
         inner: js_sys::Iterator,
         registry: Rc<RefCell<BTreeMap<u32, DataContext<JsValue>>>>,
     ) -> Self {
@@ -128,7 +128,7 @@ impl Iterator for ContextAndValueIterator {
     }
 }
 
-struct ContextAndNeighborsIterator {
+pub(crate) struct ContextAndNeighborsIterator {
     inner: js_sys::Iterator,
     registry: Rc<RefCell<BTreeMap<u32, DataContext<JsValue>>>>,
     next_item: u32,
@@ -136,7 +136,7 @@ struct ContextAndNeighborsIterator {
 }
 
 impl ContextAndNeighborsIterator {
-    fn new(
+    pub(crate) fn new(
         inner: js_sys::Iterator,
         registry: Rc<RefCell<BTreeMap<u32, DataContext<JsValue>>>>,
         constants: Rc<JsStringConstants>,
@@ -189,14 +189,14 @@ impl Iterator for ContextAndNeighborsIterator {
     }
 }
 
-struct ContextAndBoolIterator {
+pub(crate) struct ContextAndBoolIterator {
     inner: js_sys::Iterator,
     registry: Rc<RefCell<BTreeMap<u32, DataContext<JsValue>>>>,
     next_item: u32,
 }
 
 impl ContextAndBoolIterator {
-    fn new(
+    pub(crate) fn new(
         inner: js_sys::Iterator,
         registry: Rc<RefCell<BTreeMap<u32, DataContext<JsValue>>>>,
     ) -> Self {
@@ -0,0 +1,616 @@
+//! Support for JS adapters whose resolver methods are asynchronous.
+//!
+//! The engine pulls values from adapters synchronously, so it cannot wait for a `Promise`
+//! in the middle of executing a query. Instead, queries with asynchronous adapters are
+//! executed by replaying them:
+//! - Each adapter call's results are fully collected before the engine uses them.
+//!   If the adapter returns a `Promise`, an async iterable, or neighbors in an async iterable,
+//!   the execution attempt is abandoned and the results are awaited without blocking.
+//! - The engine's calls to the adapter are deterministic, so the next attempt makes the same
+//!   calls in the same order. Calls whose results were already collected are answered from
+//!   those results, without calling the adapter again.
+//! - Results are produced in the same order by every attempt, so each attempt skips
+//!   the results that were already returned by previous attempts.
+//!
+//! Adapters that return synchronous iterables never cause an attempt to be abandoned,
+//! so queries using them execute in a single attempt. Each asynchronous adapter call causes
+//! one more attempt, in which the work done before that call is repeated without
+//! calling into the adapter.
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    sync::Arc,
+};
+
+use js_sys::{try_iter, Array, AsyncIterator, Function, IteratorNext, Promise, Reflect};
+use trustfall_core::{
+    interpreter::{
+        error::QueryArgumentsError, execution::interpret_ir, Adapter, ContextIterator,
+        ContextOutcomeIterator, DataContext, ResolveEdgeInfo, ResolveInfo, VertexIterator,
+    },
+    ir::{EdgeParameters as CoreEdgeParameters, FieldValue, IndexedQuery},
+};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+
+use crate::{
+    adapter::{
+        ContextAndBoolIterator, ContextAndNeighborsIterator, ContextAndValueIterator,
+        JsVertexIterator,
+    },
+    shim::{JsContextIterator, JsEdgeParameters, JsStringConstants, QueryResultItem},
+};
+
+#[wasm_bindgen]
+extern "C" {
+    /// A JS adapter whose methods may return `Promise`s and async iterables,
+    /// in addition to the iterables returned by the methods of
+    /// [`JsAdapter`](crate::adapter::JsAdapter).
+    ///
+    /// Unlike `JsAdapter`, its methods receive the contexts to resolve as an array.
+    pub type JsAsyncAdapter;
+
+    #[wasm_bindgen(structural, method, js_name = "resolveStartingVertices")]
+    pub fn resolve_starting_vertices(
+        this: &JsAsyncAdapter,
+        edge: &str,
+        parameters: JsValue,
+    ) -> JsValue;
+
+    #[wasm_bindgen(structural, method, js_name = "resolveProperty")]
+    pub fn resolve_property(
+        this: &JsAsyncAdapter,
+        contexts: Array,
+        type_name: &str,
+        field_name: &str,
+    ) -> JsValue;
+
+    #[wasm_bindgen(structural, method, js_name = "resolveNeighbors")]
+    pub fn resolve_neighbors(
+        this: &JsAsyncAdapter,
+        contexts: Array,
+        type_name: &str,
+        edge_name: &str,
+        parameters: JsValue,
+    ) -> JsValue;
+
+    #[wasm_bindgen(structural, method, js_name = "resolveCoercion")]
+    pub fn resolve_coercion(
+        this: &JsAsyncAdapter,
+        contexts: Array,
+        type_name: &str,
+        coerce_to_type: &str,
+    ) -> JsValue;
+}
+
+type PendingCall = Pin<Box<dyn Future<Output = Result<Array, JsValue>>>>;
+
+/// The shape of the values returned by an adapter method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CallKind {
+    /// Vertices, or `{ localId, value }` objects.
+    Flat,
+
+    /// `{ localId, neighbors }` objects, where `neighbors` is itself iterable.
+    Neighbors,
+}
+
+#[derive(Default)]
+struct ReplayState {
+    /// The collected results of the adapter calls made so far, in the order they were made.
+    completed_calls: Vec<Array>,
+
+    /// The number of adapter calls made by the current attempt.
+    call_count: usize,
+
+    /// The asynchronous adapter call that abandoned the current attempt, if any.
+    pending: Option<PendingCall>,
+}
+
+pub struct AsyncAdapterShim {
+    inner: JsAsyncAdapter,
+    constants: Rc<JsStringConstants>,
+    state: RefCell<ReplayState>,
+}
+
+impl AsyncAdapterShim {
+    pub fn new(inner: JsAsyncAdapter) -> Self {
+        Self {
+            inner,
+            constants: Rc::new(JsStringConstants::new()),
+            state: Default::default(),
+        }
+    }
+
+    fn begin_attempt(&self) {
+        let mut state = self.state.borrow_mut();
+        assert!(state.pending.is_none());
+        state.call_count = 0;
+    }
+
+    fn take_pending(&self) -> Option<PendingCall> {
+        self.state.borrow_mut().pending.take()
+    }
+
+    fn complete_pending(&self, results: Array) {
+        self.state.borrow_mut().completed_calls.push(results);
+    }
+
+    fn is_abandoned(&self) -> bool {
+        self.state.borrow().pending.is_some()
+    }
+
+    /// Get the results of the next adapter call, either from a previous attempt or by
+    /// making the call. Returns `None` if the current attempt is being abandoned.
+    fn resolve_call(&self, kind: CallKind, call: impl FnOnce() -> JsValue) -> Option<Array> {
+        let index = {
+            let mut state = self.state.borrow_mut();
+            if state.pending.is_some() {
+                return None;
+            }
+            let index = state.call_count;
+            state.call_count += 1;
+            if let Some(results) = state.completed_calls.get(index) {
+                return Some(results.clone());
+            }
+            index
+        };
+
+        // Every call before this one was completed, so this is the earliest incomplete call.
+        // Don't hold the state borrowed while calling into JS.
+        let returned = call();
+        let mut state = self.state.borrow_mut();
+        assert_eq!(index, state.completed_calls.len());
+        match collect_sync(kind, returned, &self.constants) {
+            Ok(results) => {
+                state.completed_calls.push(results.clone());
+                Some(results)
+            }
+            Err(remaining) => {
+                let constants = self.constants.clone();
+                state.pending = Some(Box::pin(collect_async(kind, remaining, constants)));
+                None
+            }
+        }
+    }
+
+    /// Collect the contexts into an array to pass to the adapter.
+    /// Returns `None` if the current attempt was abandoned while collecting them.
+    fn collect_contexts(
+        &self,
+        contexts: ContextIterator<'static, JsValue>,
+    ) -> Option<(Array, Rc<RefCell<BTreeMap<u32, DataContext<JsValue>>>>)> {
+        let mut ctx_iter = JsContextIterator::new(contexts);
+        let registry = ctx_iter.registry.clone();
+
+        let array = Array::new();
+        loop {
+            let item = ctx_iter.advance();
+            match item.value() {
+                Some(ctx) => {
+                    array.push(&ctx.into());
+                }
+                None => break,
+            }
+        }
+
+        if self.is_abandoned() {
+            None
+        } else {
+            Some((array, registry))
+        }
+    }
+}
+
+/// Collect the adapter's returned values if they are available synchronously.
+/// Otherwise, return what still needs to be collected asynchronously.
+fn collect_sync(
+    kind: CallKind,
+    returned: JsValue,
+    constants: &JsStringConstants,
+) -> Result<Array, JsValue> {
+    if is_async(&returned) {
+        return Err(returned);
+    }
+
+    let results: Array = try_iter(&returned)
+        .expect("attempting to look up Symbol.iterator threw an exception")
+        .expect("adapter method did not return an iterable")
+        .map(|value| value.expect("unexpected value returned from JS iterator next()"))
+        .collect();
+
+    if kind == CallKind::Neighbors {
+        let mut has_async_neighbors = false;
+        for element in results.iter() {
+            let neighbors = Reflect::get(&element, &constants.neighbors)
+                .expect("could not retrieve target.neighbors value");
+            if is_async(&neighbors) {
+                has_async_neighbors = true;
+                break;
+            }
+            let neighbors: Array = try_iter(&neighbors)
+                .expect("attempting to look up Symbol.iterator threw an exception")
+                .expect("element neighbors value was not an iterator")
+                .map(|value| value.expect("unexpected value returned from JS iterator next()"))
+                .collect();
+            Reflect::set(&element, &constants.neighbors, &neighbors)
+                .expect("could not set target.neighbors value");
+        }
+        if has_async_neighbors {
+            // Sync iterables can only be iterated once, so continue from the collected array.
+            return Err(results.into());
+        }
+    }
+
+    Ok(results)
+}
+
+async fn collect_async(
+    kind: CallKind,
+    returned: JsValue,
+    constants: Rc<JsStringConstants>,
+) -> Result<Array, JsValue> {
+    let results = collect_iterable(returned).await?;
+    if kind == CallKind::Neighbors {
+        for element in results.iter() {
+            let neighbors = Reflect::get(&element, &constants.neighbors)?;
+            let neighbors = collect_iterable(neighbors).await?;
+            Reflect::set(&element, &constants.neighbors, &neighbors)?;
+        }
+    }
+    Ok(results)
+}
+
+/// Collect the values of a sync or async iterable, or of a `Promise` of one.
+async fn collect_iterable(value: JsValue) -> Result<Array, JsValue> {
+    let value = if is_thenable(&value) {
+        JsFuture::from(Promise::resolve(&value)).await?
+    } else {
+        value
+    };
+
+    let results = Array::new();
+    if let Some(iterator) = get_async_iterator(&value)? {
+        loop {
+            let next: IteratorNext = JsFuture::from(iterator.next()?).await?.unchecked_into();
+            if next.done() {
+                break;
+            }
+            results.push(&next.value());
+        }
+    } else {
+        let iterator = try_iter(&value)?
+            .ok_or_else(|| JsValue::from_str("adapter method did not return an iterable"))?;
+        for value in iterator {
+            results.push(&value?);
+        }
+    }
+    Ok(results)
+}
+
+fn is_thenable(value: &JsValue) -> bool {
+    value.is_object()
+        && Reflect::get(value, &JsValue::from_str("then")).map_or(false, |then| then.is_function())
+}
+
+fn is_async(value: &JsValue) -> bool {
+    is_thenable(value) || matches!(get_async_iterator(value), Ok(Some(_)))
+}
+
+fn get_async_iterator(value: &JsValue) -> Result<Option<AsyncIterator>, JsValue> {
+    if !value.is_object() {
+        return Ok(None);
+    }
+    let method = Reflect::get(value, &js_sys::Symbol::async_iterator())?;
+    if !method.is_function() {
+        return Ok(None);
+    }
+    let iterator = method.unchecked_into::<Function>().call0(value)?;
+    Ok(Some(iterator.unchecked_into()))
+}
+
+/// Stops producing values as soon as the current attempt is being abandoned,
+/// so the engine finishes the attempt without doing any further work.
+struct UntilAbandoned<I> {
+    inner: I,
+    adapter: Rc<AsyncAdapterShim>,
+}
+
+impl<I: Iterator> Iterator for UntilAbandoned<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.adapter.is_abandoned() {
+            None
+        } else {
+            self.inner.next()
+        }
+    }
+}
+
+/// The [`Adapter`] implementation used when executing queries with a [`JsAsyncAdapter`].
+///
+/// Wraps the shim in an `Rc` so the iterators it returns can check whether
+/// the current attempt is being abandoned.
+#[derive(Clone)]
+struct AsyncAdapter(Rc<AsyncAdapterShim>);
+
+impl AsyncAdapter {
+    fn until_abandoned<I: Iterator + 'static>(
+        &self,
+        inner: I,
+    ) -> Box<dyn Iterator<Item = I::Item>> {
+        Box::new(UntilAbandoned {
+            inner,
+            adapter: self.0.clone(),
+        })
+    }
+}
+
+impl Adapter<'static> for AsyncAdapter {
+    type Vertex = JsValue;
+
+    fn resolve_starting_vertices(
+        &self,
+        edge_name: &Arc<str>,
+        parameters: &CoreEdgeParameters,
+        _resolve_info: &ResolveInfo,
+    ) -> VertexIterator<'static, Self::Vertex> {
+        let parameters: JsEdgeParameters = parameters.clone().into();
+        let Some(vertices) = self.0.resolve_call(CallKind::Flat, || {
+            self.0
+                .inner
+                .resolve_starting_vertices(edge_name.as_ref(), parameters.into_js_dict())
+        }) else {
+            return Box::new(std::iter::empty());
+        };
+        self.until_abandoned(JsVertexIterator::new(vertices.values().into_iter()))
+    }
+
+    fn resolve_property(
+        &self,
+        contexts: ContextIterator<'static, Self::Vertex>,
+        type_name: &Arc<str>,
+        property_name: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'static, Self::Vertex, FieldValue> {
+        let Some((contexts, registry)) = self.0.collect_contexts(contexts) else {
+            return Box::new(std::iter::empty());
+        };
+        let Some(values) = self.0.resolve_call(CallKind::Flat, || {
+            self.0
+                .inner
+                .resolve_property(contexts, type_name.as_ref(), property_name.as_ref())
+        }) else {
+            return Box::new(std::iter::empty());
+        };
+        self.until_abandoned(ContextAndValueIterator::new(values.values(), registry))
+    }
+
+    fn resolve_neighbors(
+        &self,
+        contexts: ContextIterator<'static, Self::Vertex>,
+        type_name: &Arc<str>,
+        edge_name: &Arc<str>,
+        parameters: &CoreEdgeParameters,
+        _resolve_info: &ResolveEdgeInfo,
+    ) -> ContextOutcomeIterator<'static, Self::Vertex, VertexIterator<'static, Self::Vertex>> {
+        let Some((contexts, registry)) = self.0.collect_contexts(contexts) else {
+            return Box::new(std::iter::empty());
+        };
+        let parameters: JsEdgeParameters = parameters.clone().into();
+        let Some(neighbors) = self.0.resolve_call(CallKind::Neighbors, || {
+            self.0.inner.resolve_neighbors(
+                contexts,
+                type_name.as_ref(),
+                edge_name.as_ref(),
+                parameters.into_js_dict(),
+            )
+        }) else {
+            return Box::new(std::iter::empty());
+        };
+        self.until_abandoned(ContextAndNeighborsIterator::new(
+            neighbors.values(),
+            registry,
+            self.0.constants.clone(),
+        ))
+    }
+
+    fn resolve_coercion(
+        &self,
+        contexts: ContextIterator<'static, Self::Vertex>,
+        type_name: &Arc<str>,
+        coerce_to_type: &Arc<str>,
+        _resolve_info: &ResolveInfo,
+    ) -> ContextOutcomeIterator<'static, Self::Vertex, bool> {
+        let Some((contexts, registry)) = self.0.collect_contexts(contexts) else {
+            return Box::new(std::iter::empty());
+        };
+        let Some(values) = self.0.resolve_call(CallKind::Flat, || {
+            self.0
+                .inner
+                .resolve_coercion(contexts, type_name.as_ref(), coerce_to_type.as_ref())
+        }) else {
+            return Box::new(std::iter::empty());
+        };
+        self.until_abandoned(ContextAndBoolIterator::new(values.values(), registry))
+    }
+}
+
+type QueryResults = Box<dyn Iterator<Item = BTreeMap<Arc<str>, FieldValue>>>;
+
+enum Step {
+    Result(BTreeMap<Arc<str>, FieldValue>),
+    Pending(PendingCall),
+    Done,
+}
+
+struct AsyncExecution {
+    adapter: AsyncAdapter,
+    query: Arc<IndexedQuery>,
+    arguments: Arc<BTreeMap<Arc<str>, FieldValue>>,
+
+    /// The results of the current attempt, if one is in progress.
+    attempt: Option<QueryResults>,
+
+    /// The number of results the current attempt must skip,
+    /// since previous attempts already returned them.
+    skip: usize,
+
+    /// The number of results returned so far.
+    returned: usize,
+}
+
+impl AsyncExecution {
+    fn start(
+        adapter: JsAsyncAdapter,
+        query: Arc<IndexedQuery>,
+        arguments: Arc<BTreeMap<Arc<str>, FieldValue>>,
+    ) -> Result<Self, QueryArgumentsError> {
+        let adapter = AsyncAdapter(Rc::new(AsyncAdapterShim::new(adapter)));
+        adapter.0.begin_attempt();
+        let attempt = interpret_ir(Arc::new(adapter.clone()), query.clone(), arguments.clone())?;
+        Ok(Self {
+            adapter,
+            query,
+            arguments,
+            attempt: Some(attempt),
+            skip: 0,
+            returned: 0,
+        })
+    }
+
+    /// Advance the execution until it produces its next result, finishes,
+    /// or has to wait for an asynchronous adapter call.
+    fn advance(&mut self) -> Step {
+        loop {
+            let attempt = self.attempt.get_or_insert_with(|| {
+                self.adapter.0.begin_attempt();
+                self.skip = self.returned;
+                interpret_ir(
+                    Arc::new(self.adapter.clone()),
+                    self.query.clone(),
+                    self.arguments.clone(),
+                )
+                .expect("arguments were already validated")
+            });
+
+            let next = attempt.next();
+            if let Some(pending) = self.adapter.0.take_pending() {
+                // The attempt's remaining results, and the one it just produced,
+                // may be missing data from the pending call.
+                self.attempt = None;
+                return Step::Pending(pending);
+            }
+
+            match next {
+                None => return Step::Done,
+                Some(_) if self.skip > 0 => self.skip -= 1,
+                Some(result) => {
+                    self.returned += 1;
+                    return Step::Result(result);
+                }
+            }
+        }
+    }
+}
+
+/// The results of a query executed with [`execute_query_async`](crate::execute_query_async),
+/// as a JS async iterator.
+#[wasm_bindgen]
+pub struct AsyncQueryResultIterator {
+    execution: Rc<RefCell<Option<AsyncExecution>>>,
+    in_progress: Rc<Cell<bool>>,
+}
+
+impl AsyncQueryResultIterator {
+    pub fn start(
+        adapter: JsAsyncAdapter,
+        query: Arc<IndexedQuery>,
+        arguments: Arc<BTreeMap<Arc<str>, FieldValue>>,
+    ) -> Result<Self, QueryArgumentsError> {
+        let execution = AsyncExecution::start(adapter, query, arguments)?;
+        Ok(Self {
+            execution: Rc::new(RefCell::new(Some(execution))),
+            in_progress: Default::default(),
+        })
+    }
+
+    /// An iterator without any results.
+    pub(crate) fn finished() -> Self {
+        Self {
+            execution: Default::default(),
+            in_progress: Default::default(),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl AsyncQueryResultIterator {
+    /// Returns a `Promise` of the next result.
+    ///
+    /// Each call must wait for the `Promise` returned by the previous call to settle.
+    /// If the adapter throws or rejects, the returned `Promise` rejects with its error,
+    /// and the iterator produces no further results.
+    #[wasm_bindgen(js_name = "next")]
+    pub fn advance(&self) -> Promise {
+        if self.in_progress.replace(true) {
+            return Promise::reject(&JsValue::from_str(
+                "next() was called before the Promise returned by the previous call settled",
+            ));
+        }
+
+        let execution = self.execution.clone();
+        let in_progress = self.in_progress.clone();
+        future_to_promise(async move {
+            let outcome = next_result(&execution).await;
+            in_progress.set(false);
+            outcome.map(|result| {
+                match result {
+                    Some(result) => QueryResultItem::new_item(
+                        result.into_iter().map(|(k, v)| (k, v.into())).collect(),
+                    ),
+                    None => QueryResultItem::new_done(),
+                }
+                .into()
+            })
+        })
+    }
+}
+
+async fn next_result(
+    execution: &RefCell<Option<AsyncExecution>>,
+) -> Result<Option<BTreeMap<Arc<str>, FieldValue>>, JsValue> {
+    loop {
+        // Don't hold the execution borrowed while waiting for the adapter.
+        let pending = {
+            let mut guard = execution.borrow_mut();
+            let Some(current) = guard.as_mut() else {
+                return Ok(None);
+            };
+            match current.advance() {
+                Step::Result(result) => return Ok(Some(result)),
+                Step::Done => {
+                    *guard = None;
+                    return Ok(None);
+                }
+                Step::Pending(pending) => pending,
+            }
+        };
+
+        match pending.await {
+            Ok(results) => {
+                if let Some(current) = execution.borrow().as_ref() {
+                    current.adapter.0.complete_pending(results);
+                }
+            }
+            Err(e) => {
+                *execution.borrow_mut() = None;
+                return Err(e);
+            }
+        }
+    }
+}
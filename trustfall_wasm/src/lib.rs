@@ -7,13 +7,16 @@ use wasm_bindgen::prelude::*;
 
 use crate::{
     adapter::{AdapterShim, JsAdapter},
+    async_adapter::{AsyncQueryResultIterator, JsAsyncAdapter},
     shim::{JsFieldValue, QueryResultIterator},
 };
 
 #[macro_use]
 pub mod util;
 pub mod adapter;
+pub mod async_adapter;
 pub mod shim;
+pub mod typegen;
 
 // Schema
 make_wasm_bindgen_struct_with_debug_clone!(Schema, trustfall_core::schema::Schema);
@@ -100,3 +103,41 @@ pub fn execute_query(
 
     Ok(QueryResultIterator::new(results_iter))
 }
+
+/// Execute a query with an adapter whose methods may return `Promise`s and async iterables.
+///
+/// Returns an async iterator of the query's results, without blocking while waiting
+/// for the adapter. See the [`async_adapter`] module for how such queries are executed.
+#[wasm_bindgen(js_name = "executeQueryAsync")]
+pub fn execute_query_async(
+    schema: &Schema,
+    adapter: JsAsyncAdapter,
+    query: &str,
+    args: JsValue,
+) -> Result<AsyncQueryResultIterator, String> {
+    // TODO: add a proper error type
+    let args = from_js_args(args)?;
+
+    let query = trustfall_core::frontend::parse(schema, query).map_err(|e| format!("{e}"))?;
+
+    AsyncQueryResultIterator::start(adapter, query, args).map_err(|e| format!("{e}"))
+}
+
+/// Generate a TypeScript interface named `interface_name` for the result rows of the query,
+/// for use as the result type of `executeQuery()` and `executeQueryAsync()`.
+///
+/// See [`typegen::result_row_interface`] for how outputs are typed.
+#[wasm_bindgen(js_name = "queryResultType")]
+pub fn query_result_type(
+    schema: &Schema,
+    query: &str,
+    interface_name: &str,
+) -> Result<String, String> {
+    // TODO: add a proper error type
+    let query = trustfall_core::frontend::parse(schema, query).map_err(|e| format!("{e}"))?;
+
+    Ok(typegen::result_row_interface(
+        interface_name,
+        &query.outputs,
+    ))
+}
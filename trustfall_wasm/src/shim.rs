@@ -225,11 +225,11 @@ pub struct QueryResultItem {
 
 #[wasm_bindgen]
 impl QueryResultItem {
-    fn new_item(value: BTreeMap<Arc<str>, JsFieldValue>) -> Self {
+    pub(crate) fn new_item(value: BTreeMap<Arc<str>, JsFieldValue>) -> Self {
         Self { item: Some(value) }
     }
 
-    fn new_done() -> Self {
+    pub(crate) fn new_done() -> Self {
         Self { item: None }
     }
 
@@ -49,6 +49,49 @@ export interface Adapter<T> {
     ): IterableIterator<ContextAndBool>;
 }
 
+/**
+ * Each value may be a sync or async iterable, or a `Promise` of either.
+ */
+export type MaybeAsyncIterable<T> = Iterable<T> | AsyncIterable<T> | Promise<Iterable<T> | AsyncIterable<T>>;
+
+export interface AsyncContextAndNeighbors<T> {
+    localId: number;
+    neighbors: MaybeAsyncIterable<T>;
+}
+
+/**
+ * An adapter whose methods may return `Promise`s and async iterables.
+ *
+ * Unlike `Adapter`, its methods receive the contexts to resolve as an array.
+ * Each method is called at most once for each distinct call made by the engine,
+ * even though queries with asynchronous adapters may be executed more than once internally.
+ */
+export interface AsyncAdapter<T> {
+    resolveStartingVertices(
+        edge: string,
+        parameters: JsEdgeParameters,
+    ): MaybeAsyncIterable<T>;
+
+    resolveProperty(
+        contexts: JsContext<T>[],
+        type_name: string,
+        field_name: string
+    ): MaybeAsyncIterable<ContextAndValue>;
+
+    resolveNeighbors(
+        contexts: JsContext<T>[],
+        type_name: string,
+        edge_name: string,
+        parameters: JsEdgeParameters,
+    ): MaybeAsyncIterable<AsyncContextAndNeighbors<T>>;
+
+    resolveCoercion(
+        contexts: JsContext<T>[],
+        type_name: string,
+        coerce_to_type: string
+    ): MaybeAsyncIterable<ContextAndBool>;
+}
+
 export class Schema {
     free(): void;
 
@@ -75,13 +118,47 @@ export class Schema {
 * @param {Adapter<T>} adapter
 * @param {string} query
 * @param {Record<string, JsFieldValue>} args
-* @returns {IterableIterator<Record<string, JsFieldValue>>}
+* @returns {IterableIterator<R>}
 */
-export function executeQuery<T>(
+export function executeQuery<T, R = Record<string, JsFieldValue>>(
     schema: Schema,
     adapter: Adapter<T>,
     query: string,
     args: Record<string, JsFieldValue>,
-): IterableIterator<Record<string, JsFieldValue>>;
+): IterableIterator<R>;
+
+/**
+* Executes the query without blocking while waiting for the adapter.
+* Each call to `next()` on the returned iterator must wait for the previous call's `Promise`.
+* @param {Schema} schema
+* @param {AsyncAdapter<T>} adapter
+* @param {string} query
+* @param {Record<string, JsFieldValue>} args
+* @returns {AsyncIterableIterator<R>}
+*/
+export function executeQueryAsync<T, R = Record<string, JsFieldValue>>(
+    schema: Schema,
+    adapter: AsyncAdapter<T>,
+    query: string,
+    args: Record<string, JsFieldValue>,
+): AsyncIterableIterator<R>;
+
+/**
+* Returns the source of a TypeScript interface named `interfaceName` describing
+* the query's result rows, to be used as the `R` type parameter of `executeQuery()`
+* and `executeQueryAsync()`.
+*
+* Outputs that may be null have `| null` types, and outputs within `@fold` blocks are arrays.
+* @param {Schema} schema
+* @param {string} query
+* @param {string} interfaceName
+* @returns {string}
+* @throws if the query is not valid against the schema.
+*/
+export function queryResultType(
+    schema: Schema,
+    query: string,
+    interfaceName: string,
+): string;
 
 export function initialize(): void;
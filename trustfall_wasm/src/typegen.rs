@@ -0,0 +1,49 @@
+//! TypeScript definitions of the rows a query produces, so that code consuming
+//! the results of `executeQuery()` and `executeQueryAsync()` can be type-checked.
+use std::{collections::BTreeMap, sync::Arc};
+
+use trustfall_core::ir::{Output, Type};
+
+/// The TypeScript interface named `interface_name` describing the result rows
+/// of a query with the given outputs, one property per output.
+///
+/// Outputs that may be null, such as those within `@optional` blocks, have
+/// `| null` types, and outputs within `@fold` blocks are arrays with one
+/// level of nesting per fold. Values of types other than the built-in scalars
+/// are typed as `JsFieldValue`.
+pub fn result_row_interface(interface_name: &str, outputs: &BTreeMap<Arc<str>, Output>) -> String {
+    let mut definition = format!("export interface {interface_name} {{\n");
+    for (name, output) in outputs {
+        definition.push_str(&format!(
+            "    {name}: {};\n",
+            typescript_type(&output.value_type)
+        ));
+    }
+    definition.push_str("}\n");
+    definition
+}
+
+fn typescript_type(value_type: &Type) -> String {
+    let base = match value_type.element_type() {
+        None => match value_type.base_type() {
+            "String" | "ID" => "string",
+            "Int" | "Float" => "number",
+            "Boolean" => "boolean",
+            _ => "JsFieldValue",
+        }
+        .to_string(),
+        Some(item_type) => {
+            let item = typescript_type(&item_type);
+            if item_type.is_nullable() {
+                format!("({item})[]")
+            } else {
+                format!("{item}[]")
+            }
+        }
+    };
+    if value_type.is_nullable() {
+        format!("{base} | null")
+    } else {
+        base
+    }
+}
@@ -1,7 +1,10 @@
 use js_sys::Object;
 use wasm_bindgen::prelude::*;
 
-use crate::shim::{JsContextIterator, QueryResultIterator};
+use crate::{
+    async_adapter::AsyncQueryResultIterator,
+    shim::{JsContextIterator, QueryResultIterator},
+};
 
 pub fn set_panic_hook() {
     // When the `console_error_panic_hook` feature is enabled, we can call the
@@ -51,16 +54,26 @@ macro_rules! log {
             return this;
         };
     }
+
+    export function asyncIterify(obj) {
+        obj[Symbol.asyncIterator] = function () {
+            return this;
+        };
+    }
 ")]
 extern "C" {
     pub fn iterify(obj: &Object);
+
+    #[wasm_bindgen(js_name = "asyncIterify")]
+    pub fn async_iterify(obj: &Object);
 }
 
 #[wasm_bindgen]
 pub fn initialize() -> Result<(), JsValue> {
     set_panic_hook();
 
-    // Update the ContextIterator and QueryResultIterator prototypes to make them be iterators.
+    // Update the ContextIterator and QueryResultIterator prototypes to make them be iterators,
+    // and the AsyncQueryResultIterator prototype to make it be an async iterator.
     // This uses the workaround suggested in https://github.com/rustwasm/wasm-bindgen/issues/1478
     //
     // One day, it might not be required to instantiate an object and patch its prototype
@@ -71,5 +84,8 @@ pub fn initialize() -> Result<(), JsValue> {
     let x: QueryResultIterator = QueryResultIterator::new(Box::new(std::iter::empty()));
     iterify(&Object::get_prototype_of(&x.into()));
 
+    let x = AsyncQueryResultIterator::finished();
+    async_iterify(&Object::get_prototype_of(&x.into()));
+
     Ok(())
 }
@@ -1,11 +1,15 @@
 use std::{collections::BTreeMap, sync::Arc};
 
+use gloo_utils::format::JsValueSerdeExt;
+
 use trustfall_wasm::{
     adapter::{AdapterShim, JsAdapter},
+    async_adapter::{AsyncQueryResultIterator, JsAsyncAdapter},
     shim::JsFieldValue,
     Schema,
 };
 use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
 
 #[wasm_bindgen(inline_js = r#"
     class JsNumbersAdapter {
@@ -175,6 +179,119 @@ pub fn run_numbers_query(
     Ok(results)
 }
 
+#[wasm_bindgen(inline_js = r#"
+    // Resolves starting vertices and properties asynchronously, with a mix of
+    // Promises and async generators, and neighbors synchronously. Counts calls
+    // to check that each call made by the engine reaches the adapter only once.
+    class JsAsyncNumbersAdapter {
+        constructor() {
+            this.calls = 0;
+        }
+
+        resolveStartingVertices(edge, parameters) {
+            this.calls++;
+            if (edge !== "Number") {
+                throw `unreachable edge name: ${edge}`;
+            }
+            const vertices = [];
+            for (var i = 1; i <= parameters["max"]; i++) {
+                vertices.push(i);
+            }
+            return new Promise((resolve) => setTimeout(() => resolve(vertices), 0));
+        }
+
+        async *resolveProperty(contexts, type_name, field_name) {
+            this.calls++;
+            if (field_name !== "value") {
+                throw `unreachable field name: ${type_name} ${field_name}`;
+            }
+            for (const ctx of contexts) {
+                await null;
+                yield {
+                    localId: ctx.localId,
+                    value: ctx.activeVertex,
+                };
+            }
+        }
+
+        resolveNeighbors(contexts, type_name, edge_name, parameters) {
+            this.calls++;
+            if (edge_name !== "successor") {
+                throw `unreachable neighbor name: ${type_name} ${edge_name}`;
+            }
+            return contexts.map((ctx) => {
+                return {
+                    localId: ctx.localId,
+                    neighbors: Promise.resolve([ctx.activeVertex + 1]),
+                };
+            });
+        }
+
+        resolveCoercion(contexts, type_name, coerce_to_type) {
+            this.calls++;
+            throw `unreachable coercion: ${type_name} ${coerce_to_type}`;
+        }
+    }
+
+    export function makeAsyncAdapter() {
+        return new JsAsyncNumbersAdapter();
+    }
+
+    export function adapterCalls(adapter) {
+        return adapter.calls;
+    }
+"#)]
+extern "C" {
+    #[wasm_bindgen(js_name = "makeAsyncAdapter")]
+    pub fn make_async_adapter() -> JsAsyncAdapter;
+
+    #[wasm_bindgen(js_name = "adapterCalls")]
+    pub fn adapter_calls(adapter: &JsAsyncAdapter) -> u32;
+}
+
+/// Runs the query with the async numbers adapter, also returning the number of adapter calls.
+pub async fn run_async_numbers_query(
+    query: &str,
+    args: BTreeMap<String, JsFieldValue>,
+) -> Result<(Vec<BTreeMap<String, JsFieldValue>>, u32), String> {
+    trustfall_wasm::util::initialize().expect("init failed");
+
+    let schema = trustfall_core::schema::Schema::parse(include_str!(
+        "../../trustfall_core/test_data/schemas/numbers.graphql"
+    ))
+    .unwrap();
+    let adapter = make_async_adapter();
+    let counter = adapter.clone();
+
+    let query = trustfall_core::frontend::parse(&schema, query).map_err(|e| e.to_string())?;
+    let iter = AsyncQueryResultIterator::start(
+        adapter,
+        query,
+        Arc::new(
+            args.into_iter()
+                .map(|(k, v)| (Arc::from(k), v.into()))
+                .collect(),
+        ),
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut results = vec![];
+    loop {
+        let next = JsFuture::from(iter.advance())
+            .await
+            .map_err(|e| format!("{e:?}"))?;
+        let next: js_sys::IteratorNext = next.unchecked_into();
+        if next.done() {
+            break;
+        }
+        let result: BTreeMap<String, JsFieldValue> =
+            next.value().into_serde().map_err(|e| e.to_string())?;
+        results.push(result);
+    }
+
+    Ok((results, adapter_calls(&counter)))
+}
+
 pub fn make_test_schema() -> Schema {
     let schema_text = "\
 schema {
@@ -1,4 +1,4 @@
-use common::{make_test_schema, run_numbers_query};
+use common::{make_test_schema, run_async_numbers_query, run_numbers_query};
 use trustfall_core::ir::FieldValue;
 use trustfall_wasm::shim::JsFieldValue;
 use wasm_bindgen_test::wasm_bindgen_test;
@@ -61,3 +61,36 @@ pub fn test_execute_query_with_traversal_and_coercion() {
 
     assert_eq!(expected_results, actual_results);
 }
+
+#[wasm_bindgen_test]
+pub async fn test_execute_query_with_async_adapter() {
+    let query = r#"
+{
+    Number(max: 4) {
+        value @output
+
+        successor {
+            next: value @output
+        }
+    }
+}"#;
+    let args = Default::default();
+
+    let (actual_results, calls) = run_async_numbers_query(query, args)
+        .await
+        .expect("query and args were not valid");
+
+    let expected_results: Vec<_> = (1..=4)
+        .map(|value| {
+            btreemap! {
+                String::from("value") => JsFieldValue::Integer(value),
+                String::from("next") => JsFieldValue::Integer(value + 1),
+            }
+        })
+        .collect();
+    assert_eq!(expected_results, actual_results);
+
+    // The query is executed again after each asynchronous call, but each of its four
+    // adapter calls (starting vertices, "value", "successor", and "next") reaches the adapter once.
+    assert_eq!(4, calls);
+}
@@ -260,6 +260,41 @@ extern "C" {
     pub fn js_test_query();
 }
 
+#[wasm_bindgen_test]
+pub fn test_query_result_type() {
+    let query = r#"
+{
+    Number(max: 10) {
+        value @output
+        name @output
+
+        predecessor @optional {
+            predecessor: value @output
+        }
+        multiple(max: 3) @fold {
+            multiples: value @output
+            successor @fold {
+                successors: name @output
+            }
+        }
+    }
+}"#;
+
+    let definition = trustfall_wasm::query_result_type(&make_test_schema(), query, "NumberRow")
+        .expect("query was not valid");
+
+    let expected = "\
+export interface NumberRow {
+    multiples: number[];
+    name: string | null;
+    predecessor: number | null;
+    successors: (string | null)[][];
+    value: number;
+}
+";
+    assert_eq!(expected, definition);
+}
+
 #[wasm_bindgen_test]
 pub fn test_query() {
     js_test_query();